@@ -1,8 +1,10 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use interstellar_triangulum::renderer::RenderEngine;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use interstellar_triangulum::renderer::{
+    Compositor, FrameBuffer, FrameSink, ProgressEvent, RenderEngine,
+};
 use interstellar_triangulum::script::{Layer, Metadata, Resolution, Scene, VideoScript};
 use interstellar_triangulum::AssetLoader;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn create_test_script() -> VideoScript {
     VideoScript {
@@ -13,6 +15,7 @@ fn create_test_script() -> VideoScript {
             duration: 1.0,
             description: None,
             citations: vec![],
+            watermark: None,
         },
         scenes: vec![Scene {
             id: "bench".into(),
@@ -32,6 +35,8 @@ fn create_test_script() -> VideoScript {
                 effects: vec![],
             }],
             transition: None,
+            narration: None,
+            title: None,
         }],
         audio: None,
     }
@@ -49,5 +54,129 @@ fn bench_render_frame(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_render_frame);
+/// The per-pixel `fill_rect` that `Compositor::fill_rect` replaced
+fn fill_rect_per_pixel(
+    buffer: &mut FrameBuffer,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    color: [u8; 4],
+) {
+    let (buf_width, buf_height) = buffer.dimensions();
+    for dy in 0..height {
+        for dx in 0..width {
+            let px = x + dx as i32;
+            let py = y + dy as i32;
+            if px >= 0 && py >= 0 && (px as u32) < buf_width && (py as u32) < buf_height {
+                buffer.set_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+}
+
+/// Filling a 1080p frame with a background color, per pixel versus row by row
+fn bench_fill_rect(c: &mut Criterion) {
+    let mut buffer = FrameBuffer::new(1920, 1080);
+    let color = [20, 40, 80, 255];
+
+    let mut group = c.benchmark_group("fill_rect_1080p");
+    group.bench_function("per_pixel", |b| {
+        b.iter(|| fill_rect_per_pixel(&mut buffer, -1, -1, 1922, 1082, black_box(color)))
+    });
+    group.bench_function("row_wise", |b| {
+        b.iter(|| Compositor::fill_rect(&mut buffer, -1, -1, 1922, 1082, black_box(color)))
+    });
+    group.bench_function("clear", |b| b.iter(|| buffer.clear(black_box(color))));
+    group.finish();
+}
+
+/// Three 1-second 720p scenes, each showing a different full-frame image
+fn create_multi_scene_script(dir: &Path) -> VideoScript {
+    let scenes = (0..3u8)
+        .map(|i| {
+            let name = format!("scene{}.png", i);
+            image::RgbaImage::from_pixel(1280, 720, image::Rgba([i * 80, 100, 200, 255]))
+                .save(dir.join(&name))
+                .unwrap();
+            Scene {
+                id: format!("scene{}", i),
+                duration: 1.0,
+                scene_type: Default::default(),
+                layers: vec![Layer::Image {
+                    source: PathBuf::from(name),
+                    effects: vec![],
+                    transform: Default::default(),
+                    crop: None,
+                }],
+                transition: None,
+                narration: None,
+                title: None,
+            }
+        })
+        .collect();
+
+    VideoScript {
+        metadata: Metadata {
+            title: "Readback Benchmark".into(),
+            resolution: Resolution::Named("1280x720".into()),
+            fps: 30,
+            duration: 3.0,
+            description: None,
+            citations: vec![],
+            watermark: None,
+        },
+        scenes,
+        audio: None,
+    }
+}
+
+struct NullSink;
+
+impl FrameSink for NullSink {
+    fn write_frame(&mut self, frame: &FrameBuffer) -> interstellar_triangulum::error::Result<()> {
+        black_box(frame.as_bytes());
+        Ok(())
+    }
+}
+
+/// Frames/sec with each GPU readback awaited before the next frame is drawn,
+/// versus `render`, which reads a frame back while drawing the next one
+fn bench_gpu_readback(c: &mut Criterion) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut engine = RenderEngine::new(create_multi_scene_script(dir.path()), true);
+    if !engine.uses_gpu() {
+        println!("No GPU adapter, skipping GPU readback benchmark");
+        return;
+    }
+    let mut loader = AssetLoader::new(dir.path());
+    let total = engine.timeline().total_frames();
+
+    let mut group = c.benchmark_group("gpu_readback");
+    group.throughput(Throughput::Elements(total as u64));
+    group.sample_size(10);
+    group.bench_function("blocking", |b| {
+        b.iter(|| {
+            for frame in 0..total {
+                engine.render_frame(frame, &mut loader).unwrap();
+                NullSink.write_frame(engine.frame_buffer()).unwrap();
+            }
+        })
+    });
+    group.bench_function("pipelined", |b| {
+        b.iter(|| {
+            engine
+                .render(&mut NullSink, &mut loader, &mut |_: ProgressEvent| {})
+                .unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_render_frame,
+    bench_fill_rect,
+    bench_gpu_readback
+);
 criterion_main!(benches);
@@ -0,0 +1,379 @@
+#!/usr/bin/env python3
+"""Build tests/fonts/TriangulumTestPixel.ttf, the font the text tests draw with.
+
+A 5x7 pixel font covering printable ASCII, with a few kerning pairs so
+kerning can be tested. Glyphs are drawn on a grid of 100-unit pixels, so at
+font sizes that are multiples of 10 every pixel edge lands on a whole pixel.
+Released under the SIL Open Font License 1.1 (tests/fonts/OFL.txt).
+
+Usage: python3 scripts/make_test_font.py
+"""
+
+import struct
+from pathlib import Path
+
+OUT = Path(__file__).resolve().parent.parent / "tests" / "fonts" / "TriangulumTestPixel.ttf"
+
+FAMILY = "Triangulum Test Pixel"
+POSTSCRIPT = "TriangulumTestPixel-Regular"
+UNITS_PER_EM = 1000
+PIXEL = 100
+ADVANCE = 600
+LEFT = 50
+ASCENT = 800
+DESCENT = -200
+
+# Seven rows from the cap height down to the baseline, then up to two rows
+# below it for descenders
+GLYPHS = {
+    " ": [],
+    "!": ["..#..", "..#..", "..#..", "..#..", "..#..", ".....", "..#.."],
+    '"': [".#.#.", ".#.#."],
+    "#": [".#.#.", ".#.#.", "#####", ".#.#.", "#####", ".#.#.", ".#.#."],
+    "$": ["..#..", ".####", "#.#..", ".###.", "..#.#", "####.", "..#.."],
+    "%": ["##...", "##..#", "...#.", "..#..", ".#...", "#..##", "...##"],
+    "&": [".##..", "#..#.", "#.#..", ".#...", "#.#.#", "#..#.", ".##.#"],
+    "'": ["..#..", "..#..", ".#..."],
+    "(": ["...#.", "..#..", ".#...", ".#...", ".#...", "..#..", "...#."],
+    ")": [".#...", "..#..", "...#.", "...#.", "...#.", "..#..", ".#..."],
+    "*": [".....", "..#..", "#.#.#", ".###.", "#.#.#", "..#..", "....."],
+    "+": [".....", "..#..", "..#..", "#####", "..#..", "..#..", "....."],
+    ",": [".....", ".....", ".....", ".....", ".....", "..##.", "..##.", "..#.."],
+    "-": [".....", ".....", ".....", "#####", ".....", ".....", "....."],
+    ".": [".....", ".....", ".....", ".....", ".....", ".##..", ".##.."],
+    "/": [".....", "....#", "...#.", "..#..", ".#...", "#....", "....."],
+    "0": [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."],
+    "1": ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    "2": [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+    "3": ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+    "4": ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+    "5": ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+    "6": ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+    "7": ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+    "8": [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+    "9": [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+    ":": [".....", ".##..", ".##..", ".....", ".##..", ".##..", "....."],
+    ";": [".....", ".##..", ".##..", ".....", ".##..", ".##..", "..#..", ".#..."],
+    "<": ["...#.", "..#..", ".#...", "#....", ".#...", "..#..", "...#."],
+    "=": [".....", ".....", "#####", ".....", "#####", ".....", "....."],
+    ">": [".#...", "..#..", "...#.", "....#", "...#.", "..#..", ".#..."],
+    "?": [".###.", "#...#", "....#", "...#.", "..#..", ".....", "..#.."],
+    "@": [".###.", "#...#", "....#", ".##.#", "#.#.#", "#.#.#", ".###."],
+    "A": [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    "B": ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."],
+    "C": [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."],
+    "D": ["###..", "#..#.", "#...#", "#...#", "#...#", "#..#.", "###.."],
+    "E": ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+    "F": ["#####", "#....", "#....", "####.", "#....", "#....", "#...."],
+    "G": [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"],
+    "H": ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    "I": [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    "J": ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+    "K": ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"],
+    "L": ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+    "M": ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"],
+    "N": ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"],
+    "O": [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+    "P": ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."],
+    "Q": [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"],
+    "R": ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"],
+    "S": [".####", "#....", "#....", ".###.", "....#", "....#", "####."],
+    "T": ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+    "U": ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+    "V": ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+    "W": ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."],
+    "X": ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"],
+    "Y": ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."],
+    "Z": ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"],
+    "[": [".###.", ".#...", ".#...", ".#...", ".#...", ".#...", ".###."],
+    "\\": [".....", "#....", ".#...", "..#..", "...#.", "....#", "....."],
+    "]": [".###.", "...#.", "...#.", "...#.", "...#.", "...#.", ".###."],
+    "^": ["..#..", ".#.#.", "#...#"],
+    "_": [".....", ".....", ".....", ".....", ".....", ".....", "#####"],
+    "`": [".#...", "..#.."],
+    "a": [".....", ".....", ".###.", "....#", ".####", "#...#", ".####"],
+    "b": ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "####."],
+    "c": [".....", ".....", ".###.", "#....", "#....", "#...#", ".###."],
+    "d": ["....#", "....#", ".##.#", "#..##", "#...#", "#...#", ".####"],
+    "e": [".....", ".....", ".###.", "#...#", "#####", "#....", ".###."],
+    "f": ["..##.", ".#..#", ".#...", "###..", ".#...", ".#...", ".#..."],
+    "g": [".....", ".....", ".####", "#...#", "#...#", "#...#", ".####", "....#", ".###."],
+    "h": ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "#...#"],
+    "i": ["..#..", ".....", ".##..", "..#..", "..#..", "..#..", ".###."],
+    "j": ["...#.", ".....", "..##.", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+    "k": ["#....", "#....", "#..#.", "#.#..", "##...", "#.#..", "#..#."],
+    "l": [".##..", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    "m": [".....", ".....", "##.#.", "#.#.#", "#.#.#", "#...#", "#...#"],
+    "n": [".....", ".....", "#.##.", "##..#", "#...#", "#...#", "#...#"],
+    "o": [".....", ".....", ".###.", "#...#", "#...#", "#...#", ".###."],
+    "p": [".....", ".....", "####.", "#...#", "#...#", "#...#", "####.", "#....", "#...."],
+    "q": [".....", ".....", ".####", "#...#", "#...#", "#...#", ".####", "....#", "....#"],
+    "r": [".....", ".....", "#.##.", "##..#", "#....", "#....", "#...."],
+    "s": [".....", ".....", ".####", "#....", ".###.", "....#", "####."],
+    "t": [".#...", ".#...", "###..", ".#...", ".#...", ".#..#", "..##."],
+    "u": [".....", ".....", "#...#", "#...#", "#...#", "#..##", ".##.#"],
+    "v": [".....", ".....", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+    "w": [".....", ".....", "#...#", "#...#", "#.#.#", "#.#.#", ".#.#."],
+    "x": [".....", ".....", "#...#", ".#.#.", "..#..", ".#.#.", "#...#"],
+    "y": [".....", ".....", "#...#", "#...#", "#...#", "#...#", ".####", "....#", ".###."],
+    "z": [".....", ".....", "#####", "...#.", "..#..", ".#...", "#####"],
+    "{": ["...#.", "..#..", "..#..", ".#...", "..#..", "..#..", "...#."],
+    "|": ["..#..", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+    "}": [".#...", "..#..", "..#..", "...#.", "..#..", "..#..", ".#..."],
+    "~": [".....", ".....", ".#...", "#.#.#", "...#.", ".....", "....."],
+}
+
+# .notdef: a hollow box
+NOTDEF = ["#####", "#...#", "#...#", "#...#", "#...#", "#...#", "#####"]
+
+KERNING = {
+    ("A", "V"): -100,
+    ("A", "W"): -50,
+    ("L", "T"): -100,
+    ("T", "o"): -100,
+    ("V", "A"): -100,
+    ("V", "o"): -100,
+    ("W", "o"): -50,
+}
+
+FIRST_CHAR, LAST_CHAR = 32, 126
+
+
+def glyph_rects(rows):
+    """Rectangles (x0, y0, x1, y1) in font units, one per horizontal run of ink"""
+    rects = []
+    for r, row in enumerate(rows):
+        top = (7 - r) * PIXEL
+        x = 0
+        while x < len(row):
+            if row[x] != "#":
+                x += 1
+                continue
+            start = x
+            while x < len(row) and row[x] == "#":
+                x += 1
+            rects.append((LEFT + start * PIXEL, top - PIXEL, LEFT + x * PIXEL, top))
+    return rects
+
+
+def glyf_entry(rects):
+    """A simple glyph of one clockwise contour per rectangle"""
+    if not rects:
+        return b"", (0, 0, 0, 0)
+    points = []
+    for x0, y0, x1, y1 in rects:
+        points += [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+    bounds = (
+        min(x for x, _ in points),
+        min(y for _, y in points),
+        max(x for x, _ in points),
+        max(y for _, y in points),
+    )
+    data = struct.pack(">hhhhh", len(rects), *bounds)
+    data += b"".join(struct.pack(">H", 4 * i + 3) for i in range(len(rects)))
+    data += struct.pack(">H", 0)  # no instructions
+    data += bytes([0x01] * len(points))  # on-curve, 16-bit deltas
+    previous = (0, 0)
+    xs, ys = b"", b""
+    for x, y in points:
+        xs += struct.pack(">h", x - previous[0])
+        ys += struct.pack(">h", y - previous[1])
+        previous = (x, y)
+    data += xs + ys
+    data += b"\0" * (-len(data) % 4)
+    return data, bounds
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def name_table():
+    records = {
+        0: "Copyright 2026 The interstellar-triangulum authors",
+        1: FAMILY,
+        2: "Regular",
+        3: "1.000;" + POSTSCRIPT,
+        4: FAMILY + " Regular",
+        5: "Version 1.000",
+        6: POSTSCRIPT,
+        13: "This Font Software is licensed under the SIL Open Font License, Version 1.1.",
+        14: "https://openfontlicense.org",
+    }
+    strings = b""
+    entries = b""
+    for name_id, text in records.items():
+        encoded = text.encode("utf-16-be")
+        entries += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    header = struct.pack(">HHH", 0, len(records), 6 + 12 * len(records))
+    return header + entries + strings
+
+
+def cmap_table():
+    # Format 4: one segment for printable ASCII, and the closing 0xFFFF one
+    seg_count = 2
+    subtable = struct.pack(
+        ">HHHHHHH", 4, 16 + 8 * seg_count, 0, 2 * seg_count, 4, 1, 0
+    )
+    subtable += struct.pack(">HH", LAST_CHAR, 0xFFFF)  # end codes
+    subtable += struct.pack(">H", 0)  # reserved pad
+    subtable += struct.pack(">HH", FIRST_CHAR, 0xFFFF)  # start codes
+    subtable += struct.pack(">hh", 1 - FIRST_CHAR, 1)  # id deltas
+    subtable += struct.pack(">HH", 0, 0)  # id range offsets
+    header = struct.pack(">HH", 0, 2)
+    header += struct.pack(">HHI", 0, 3, 4 + 8 * 2)
+    header += struct.pack(">HHI", 3, 1, 4 + 8 * 2)
+    return header + subtable
+
+
+def kern_table(glyph_ids):
+    pairs = sorted(
+        (glyph_ids[left], glyph_ids[right], value) for (left, right), value in KERNING.items()
+    )
+    n = len(pairs)
+    power = 1
+    while power * 2 <= n:
+        power *= 2
+    search_range = power * 6
+    entry_selector = power.bit_length() - 1
+    body = struct.pack(">HHHH", n, search_range, entry_selector, n * 6 - search_range)
+    body += b"".join(struct.pack(">HHh", *pair) for pair in pairs)
+    subtable = struct.pack(">HHH", 0, 6 + len(body), 0x0001) + body
+    return struct.pack(">HH", 0, 1) + subtable
+
+
+def build():
+    chars = [chr(c) for c in range(FIRST_CHAR, LAST_CHAR + 1)]
+    glyph_ids = {c: i + 1 for i, c in enumerate(chars)}
+    outlines = [glyf_entry(glyph_rects(NOTDEF))] + [glyf_entry(glyph_rects(GLYPHS[c])) for c in chars]
+    num_glyphs = len(outlines)
+
+    glyf = b""
+    loca = []
+    for data, _ in outlines:
+        loca.append(len(glyf))
+        glyf += data
+    loca.append(len(glyf))
+
+    all_bounds = [b for data, b in outlines if data]
+    x_min = min(b[0] for b in all_bounds)
+    y_min = min(b[1] for b in all_bounds)
+    x_max = max(b[2] for b in all_bounds)
+    y_max = max(b[3] for b in all_bounds)
+    max_points = max(len(data) and struct.unpack(">h", data[:2])[0] * 4 for data, _ in outlines)
+    max_contours = max(len(data) and struct.unpack(">h", data[:2])[0] for data, _ in outlines)
+
+    tables = {}
+    tables["head"] = struct.pack(
+        ">IIIIHHqqhhhhHHhhh",
+        0x00010000,  # version
+        0x00010000,  # font revision 1.0
+        0,  # checksum adjustment, filled in below
+        0x5F0F3CF5,
+        0x000B,  # baseline at y=0, integer scaling
+        UNITS_PER_EM,
+        0,
+        0,
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+        0,  # regular
+        8,  # smallest readable size
+        2,
+        1,  # long loca offsets
+        0,
+    )
+    tables["hhea"] = struct.pack(
+        ">IhhhHhhhhhhhhhhhH",
+        0x00010000,
+        ASCENT,
+        DESCENT,
+        0,
+        ADVANCE,
+        min(b[0] for b in all_bounds),
+        ADVANCE - max(b[2] for b in all_bounds),
+        x_max,
+        1,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        num_glyphs,
+    )
+    tables["maxp"] = struct.pack(
+        ">IHHHHHHHHHHHHHH", 0x00010000, num_glyphs, max_points, max_contours, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0
+    )
+    tables["OS/2"] = struct.pack(
+        ">HhHHHhhhhhhhhhhh10sIIII4sHHHhhhHHIIhhHHH",
+        4,
+        ADVANCE,
+        400,
+        5,
+        0,  # installable
+        650, 700, 0, 140,  # subscript
+        650, 700, 0, 480,  # superscript
+        50, 350,  # strikeout
+        0,
+        bytes(10),
+        1, 0, 0, 0,  # Basic Latin
+        b"ITRI",
+        0x40,  # regular
+        FIRST_CHAR,
+        LAST_CHAR,
+        ASCENT,
+        DESCENT,
+        0,
+        ASCENT,
+        -DESCENT,
+        1, 0,  # Latin 1 code page
+        500,
+        700,
+        0,
+        32,
+        2,
+    )
+    tables["hmtx"] = b"".join(
+        struct.pack(">Hh", ADVANCE, bounds[0] if data else 0) for data, bounds in outlines
+    )
+    tables["cmap"] = cmap_table()
+    tables["loca"] = b"".join(struct.pack(">I", offset) for offset in loca)
+    tables["glyf"] = glyf
+    tables["kern"] = kern_table(glyph_ids)
+    tables["name"] = name_table()
+    tables["post"] = struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 1, 0, 0, 0, 0)
+
+    tags = sorted(tables)
+    count = len(tags)
+    power = 1
+    while power * 2 <= count:
+        power *= 2
+    header = struct.pack(
+        ">IHHHH", 0x00010000, count, power * 16, power.bit_length() - 1, count * 16 - power * 16
+    )
+    offset = len(header) + 16 * count
+    directory = b""
+    body = b""
+    for tag in tags:
+        data = tables[tag]
+        directory += struct.pack(">4sIII", tag.encode(), checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font = bytearray(header + directory + body)
+
+    # head.checkSumAdjustment makes the whole file sum to 0xB1B0AFBA
+    head_offset = struct.unpack(">I", directory[16 * tags.index("head") + 8 : 16 * tags.index("head") + 12])[0]
+    adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+    font[head_offset + 8 : head_offset + 12] = struct.pack(">I", adjustment)
+
+    OUT.parent.mkdir(parents=True, exist_ok=True)
+    OUT.write_bytes(bytes(font))
+    print("Wrote %s (%d bytes, %d glyphs)" % (OUT, len(font), num_glyphs))
+
+
+if __name__ == "__main__":
+    build()
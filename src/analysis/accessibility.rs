@@ -0,0 +1,511 @@
+//! Whether on-screen text can be read: WCAG contrast against what is drawn behind
+//! it, font size for the output resolution, and distance from the frame edges.
+
+use crate::assets::AssetLoader;
+use crate::renderer::color::srgb_to_linear;
+use crate::renderer::Compositor;
+use crate::script::{Color, Crop, Layer, Position, Scene, VideoScript};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Sample points across each side of a text box when estimating its background
+const SAMPLES_PER_SIDE: u32 = 16;
+
+/// Thresholds of the accessibility checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    /// Lowest WCAG contrast ratio between text and its background
+    #[serde(default = "default_min_contrast")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub min_contrast: f32,
+    /// Smallest font size (px) at 1080p, scaled with the output height
+    #[serde(default = "default_min_font_size")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub min_font_size: f32,
+    /// Fraction of the width and height text must keep from each edge
+    #[serde(default = "default_safe_margin")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub safe_margin: f32,
+}
+
+fn default_min_contrast() -> f32 {
+    4.5
+}
+
+fn default_min_font_size() -> f32 {
+    24.0
+}
+
+fn default_safe_margin() -> f32 {
+    0.05
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            min_contrast: default_min_contrast(),
+            min_font_size: default_min_font_size(),
+            safe_margin: default_safe_margin(),
+        }
+    }
+}
+
+/// What an accessibility warning is about
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum AccessibilityCheck {
+    LowContrast,
+    SmallText,
+    OutsideSafeArea,
+}
+
+impl AccessibilityCheck {
+    /// Check part of the issue's rule id
+    pub fn rule(self) -> &'static str {
+        match self {
+            AccessibilityCheck::LowContrast => "low-contrast",
+            AccessibilityCheck::SmallText => "small-text",
+            AccessibilityCheck::OutsideSafeArea => "outside-safe-area",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextAccessibility {
+    pub scene_index: usize,
+    pub layer_index: usize,
+    /// `None` when a video or unreadable image is behind the text
+    pub contrast_ratio: Option<f32>,
+    pub font_size: f32,
+    pub inside_safe_area: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessibilityWarning {
+    pub scene_index: usize,
+    pub layer_index: usize,
+    pub check: AccessibilityCheck,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AccessibilityReport {
+    /// One entry per text layer
+    pub texts: Vec<TextAccessibility>,
+    pub warnings: Vec<AccessibilityWarning>,
+}
+
+/// Where a text layer is drawn, in frame pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TextBox {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+pub struct AccessibilityAnalyzer;
+
+impl AccessibilityAnalyzer {
+    /// Check every text layer; fonts and images are read through `assets`
+    pub fn analyze(
+        script: &VideoScript,
+        assets: &mut AssetLoader,
+        settings: &AccessibilitySettings,
+    ) -> AccessibilityReport {
+        let (frame_width, frame_height) = script.metadata.resolution.dimensions();
+        let (frame_width, frame_height) = (frame_width as f32, frame_height as f32);
+        let min_font_size = settings.min_font_size * frame_height / 1080.0;
+        let margin_x = settings.safe_margin * frame_width;
+        let margin_y = settings.safe_margin * frame_height;
+        let mut report = AccessibilityReport::default();
+
+        for (scene_index, scene) in script.scenes.iter().enumerate() {
+            for (layer_index, layer) in scene.layers.iter().enumerate() {
+                let Layer::Text {
+                    content,
+                    font,
+                    font_size,
+                    color,
+                    position,
+                    ..
+                } = layer
+                else {
+                    continue;
+                };
+                let text_box = Self::text_box(assets, font, content, *font_size, position);
+                let contrast_ratio = Self::background(
+                    scene,
+                    layer_index,
+                    text_box,
+                    frame_width,
+                    frame_height,
+                    assets,
+                )
+                .map(|background| contrast_ratio(color, background));
+                let inside_safe_area = text_box.x >= margin_x
+                    && text_box.y >= margin_y
+                    && text_box.x + text_box.width <= frame_width - margin_x
+                    && text_box.y + text_box.height <= frame_height - margin_y;
+
+                let mut warn = |check, message| {
+                    report.warnings.push(AccessibilityWarning {
+                        scene_index,
+                        layer_index,
+                        check,
+                        message,
+                    })
+                };
+                let text = format!("Scene {} text \"{}\"", scene_index + 1, preview(content));
+                if let Some(ratio) = contrast_ratio.filter(|&ratio| ratio < settings.min_contrast) {
+                    warn(
+                        AccessibilityCheck::LowContrast,
+                        format!(
+                            "{} has a contrast ratio of {:.1}:1 against its background (minimum {:.1}:1)",
+                            text, ratio, settings.min_contrast
+                        ),
+                    );
+                }
+                if *font_size < min_font_size {
+                    warn(
+                        AccessibilityCheck::SmallText,
+                        format!(
+                            "{} is {:.0}px, below the {:.0}px minimum at {}p",
+                            text, font_size, min_font_size, frame_height
+                        ),
+                    );
+                }
+                if !inside_safe_area {
+                    warn(
+                        AccessibilityCheck::OutsideSafeArea,
+                        format!(
+                            "{} reaches into the {:.0}% margin at the frame's edges",
+                            text,
+                            settings.safe_margin * 100.0
+                        ),
+                    );
+                }
+
+                report.texts.push(TextAccessibility {
+                    scene_index,
+                    layer_index,
+                    contrast_ratio,
+                    font_size: *font_size,
+                    inside_safe_area,
+                });
+            }
+        }
+
+        report
+    }
+
+    /// The text's extent as the compositor draws it (`position` is the top left),
+    /// estimated from the font size when the font can't be read
+    fn text_box(
+        assets: &mut AssetLoader,
+        font: &Path,
+        content: &str,
+        font_size: f32,
+        position: &Position,
+    ) -> TextBox {
+        let lines = content.lines().count().max(1) as f32;
+        let data = assets.load_font(font).ok().map(|asset| asset.data.clone());
+        let (width, height) = match data
+            .as_deref()
+            .and_then(|d| FontRef::try_from_slice(d).ok())
+        {
+            Some(font) => {
+                let scaled = font.as_scaled(PxScale::from(font_size));
+                let line_height = scaled.height() + scaled.line_gap();
+                (
+                    Compositor::text_width(&font, content, font_size),
+                    scaled.height() + (lines - 1.0) * line_height,
+                )
+            }
+            None => {
+                let longest = content.lines().map(|line| line.chars().count()).max();
+                (
+                    longest.unwrap_or(0) as f32 * 0.6 * font_size,
+                    lines * 1.2 * font_size,
+                )
+            }
+        };
+        TextBox {
+            x: position.x as f32,
+            y: position.y as f32,
+            width,
+            height,
+        }
+    }
+
+    /// Mean linear color of the frame behind the text box: the layers under the
+    /// text composited over the frame's black background. `None` when a video
+    /// or an image that can't be decoded is under it, or the box is off screen.
+    fn background(
+        scene: &Scene,
+        layer_index: usize,
+        text_box: TextBox,
+        frame_width: f32,
+        frame_height: f32,
+        assets: &mut AssetLoader,
+    ) -> Option<[f32; 3]> {
+        let left = text_box.x.max(0.0);
+        let top = text_box.y.max(0.0);
+        let right = (text_box.x + text_box.width).min(frame_width);
+        let bottom = (text_box.y + text_box.height).min(frame_height);
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        let mut samples = Vec::new();
+        for row in 0..SAMPLES_PER_SIDE {
+            for column in 0..SAMPLES_PER_SIDE {
+                let x = left + (column as f32 + 0.5) / SAMPLES_PER_SIDE as f32 * (right - left);
+                let y = top + (row as f32 + 0.5) / SAMPLES_PER_SIDE as f32 * (bottom - top);
+                samples.push((x, y, [0.0f32; 3]));
+            }
+        }
+
+        for layer in &scene.layers[..layer_index] {
+            match layer {
+                Layer::Image {
+                    source,
+                    transform,
+                    crop,
+                    ..
+                } => {
+                    let pixels = assets.load_image(source).ok()?.pixels.clone()?;
+                    let full = Crop {
+                        x: 0,
+                        y: 0,
+                        width: pixels.width(),
+                        height: pixels.height(),
+                    };
+                    let Some(region) = crop.unwrap_or(full).clamp_to(full.width, full.height)
+                    else {
+                        continue;
+                    };
+                    let scale = transform.scale;
+                    if scale <= 0.0 {
+                        continue;
+                    }
+                    let (sin, cos) = transform.rotation.to_radians().sin_cos();
+                    let (origin_x, origin_y) =
+                        (transform.position.x as f32, transform.position.y as f32);
+
+                    for (x, y, background) in &mut samples {
+                        // Back into the image, undoing the clockwise rotation about its position
+                        let (dx, dy) = (*x - origin_x, *y - origin_y);
+                        let u = (cos * dx + sin * dy) / scale;
+                        let v = (-sin * dx + cos * dy) / scale;
+                        if u < 0.0
+                            || v < 0.0
+                            || u >= region.width as f32
+                            || v >= region.height as f32
+                        {
+                            continue;
+                        }
+                        let pixel = pixels.get_pixel(region.x + u as u32, region.y + v as u32);
+                        let alpha = pixel[3] as f32 / 255.0 * transform.opacity.clamp(0.0, 1.0);
+                        for (channel, value) in background.iter_mut().enumerate() {
+                            *value =
+                                *value * (1.0 - alpha) + srgb_to_linear(pixel[channel]) * alpha;
+                        }
+                    }
+                }
+                // What a plugin draws is as unknown as a video frame
+                Layer::Video { .. } | Layer::Custom { .. } => return None,
+                Layer::Text { .. } | Layer::Waveform { .. } => {}
+            }
+        }
+
+        let mut mean = [0.0; 3];
+        for (_, _, background) in &samples {
+            for channel in 0..3 {
+                mean[channel] += background[channel] / samples.len() as f32;
+            }
+        }
+        Some(mean)
+    }
+}
+
+/// WCAG relative luminance of a linear color
+fn luminance(color: [f32; 3]) -> f32 {
+    0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2]
+}
+
+/// WCAG contrast ratio (1-21) of text in `color`, blended by its alpha, over
+/// a linear `background`
+fn contrast_ratio(color: &Color, background: [f32; 3]) -> f32 {
+    let alpha = color.a as f32 / 255.0;
+    let text = [color.r, color.g, color.b].map(srgb_to_linear);
+    let blended = [0, 1, 2].map(|i| text[i] * alpha + background[i] * (1.0 - alpha));
+    let (a, b) = (luminance(blended) + 0.05, luminance(background) + 0.05);
+    a.max(b) / a.min(b)
+}
+
+/// The start of `content` for messages
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default();
+    if line.chars().count() > 30 || content.lines().count() > 1 {
+        format!("{}...", line.chars().take(30).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Metadata, Resolution, SceneType, Transform};
+    use std::path::PathBuf;
+
+    fn text_layer(content: &str, font_size: f32, x: i32, y: i32, rgb: u8) -> Layer {
+        Layer::Text {
+            content: content.into(),
+            font: "missing.ttf".into(),
+            font_size,
+            color: Color {
+                r: rgb,
+                g: rgb,
+                b: rgb,
+                a: 255,
+            },
+            position: Position { x, y },
+            effects: vec![],
+        }
+    }
+
+    fn create_script(layers: Vec<Layer>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("640x360".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "intro".into(),
+                scene_type: SceneType::Hook,
+                duration: 3.0,
+                layers,
+                transition: None,
+                narration: None,
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    fn analyze(script: &VideoScript, dir: &Path) -> AccessibilityReport {
+        let mut assets = AssetLoader::new(dir);
+        AccessibilityAnalyzer::analyze(script, &mut assets, &AccessibilitySettings::default())
+    }
+
+    #[test]
+    fn test_white_on_white_is_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        image::RgbaImage::from_pixel(640, 360, image::Rgba([250, 250, 250, 255]))
+            .save(dir.path().join("white.png"))
+            .unwrap();
+        let script = create_script(vec![
+            Layer::Image {
+                source: PathBuf::from("white.png"),
+                effects: vec![],
+                transform: Transform::default(),
+                crop: None,
+            },
+            text_layer("Read me", 48.0, 100, 100, 255),
+        ]);
+        let report = analyze(&script, dir.path());
+
+        let text = &report.texts[0];
+        assert_eq!(text.layer_index, 1);
+        assert!(text.contrast_ratio.unwrap() < 1.1);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].check, AccessibilityCheck::LowContrast);
+        assert!(report.warnings[0]
+            .message
+            .starts_with("Scene 1 text \"Read me\" has a contrast ratio of 1.0:1"));
+
+        // A half-transparent image lets the black frame through
+        let mut faded = script.clone();
+        if let Layer::Image { transform, .. } = &mut faded.scenes[0].layers[0] {
+            transform.opacity = 0.1;
+        }
+        assert!(analyze(&faded, dir.path()).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_white_on_black_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = create_script(vec![text_layer("Read me", 48.0, 100, 100, 255)]);
+        let report = analyze(&script, dir.path());
+
+        assert!((report.texts[0].contrast_ratio.unwrap() - 21.0).abs() < 0.01);
+        assert!(report.texts[0].inside_safe_area);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_small_text_scales_with_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        // 24px at 1080p is 8px at 360p
+        let script = create_script(vec![
+            text_layer("Fine print", 7.0, 100, 100, 255),
+            text_layer("Fine print", 8.0, 100, 200, 255),
+        ]);
+        let report = analyze(&script, dir.path());
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].check, AccessibilityCheck::SmallText);
+        assert_eq!(report.warnings[0].layer_index, 0);
+        assert!(report.warnings[0]
+            .message
+            .contains("7px, below the 8px minimum at 360p"));
+    }
+
+    #[test]
+    fn test_safe_margin_edge() {
+        let dir = tempfile::tempdir().unwrap();
+        // 5% of 640x360 is 32px by 18px; "Hi" at 50px is estimated 60px wide, 60px high
+        let check = |x: i32, y: i32| {
+            let script = create_script(vec![text_layer("Hi", 50.0, x, y, 255)]);
+            analyze(&script, dir.path()).texts[0].inside_safe_area
+        };
+        assert!(check(32, 18));
+        assert!(!check(31, 18));
+        assert!(!check(32, 17));
+        // Right and bottom edges: 640 - 32 - 60 and 360 - 18 - 60
+        assert!(check(548, 282));
+        assert!(!check(549, 282));
+        assert!(!check(548, 283));
+
+        let script = create_script(vec![text_layer("Hi", 50.0, 0, 0, 255)]);
+        let report = analyze(&script, dir.path());
+        assert_eq!(
+            report.warnings[0].check,
+            AccessibilityCheck::OutsideSafeArea
+        );
+        assert!(report.warnings[0].message.contains("5% margin"));
+    }
+
+    #[test]
+    fn test_video_background_is_unknown() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = create_script(vec![
+            Layer::Video {
+                source: PathBuf::from("clip.mp4"),
+                effects: vec![],
+                transform: Transform::default(),
+            },
+            text_layer("Read me", 48.0, 100, 100, 255),
+        ]);
+        let report = analyze(&script, dir.path());
+        assert_eq!(report.texts[0].contrast_ratio, None);
+        assert!(report.warnings.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Analyzers plugged into the combined report, for checks that don't belong
+//! in the built-in ones, like a team's house rules. The report runs them after
+//! its own analyses; their findings become issues, and their scores count
+//! towards the overall score.
+//!
+//! ```
+//! use interstellar_triangulum::analysis::analyzer::{
+//!     AnalysisConfig, Analyzer, AnalyzerFindings, ExtraAnalyzers, Finding,
+//! };
+//! use interstellar_triangulum::analysis::narrative::Severity;
+//! use interstellar_triangulum::analysis::report::AnalysisReport;
+//! use interstellar_triangulum::script::VideoScript;
+//!
+//! /// Marketing's rule: no scene promises a "guaranteed" result
+//! struct BannedPhrases;
+//!
+//! impl Analyzer for BannedPhrases {
+//!     fn name(&self) -> &str {
+//!         "house-rules"
+//!     }
+//!
+//!     fn analyze(&self, script: &VideoScript, _config: &AnalysisConfig) -> AnalyzerFindings {
+//!         let findings = script
+//!             .scenes
+//!             .iter()
+//!             .enumerate()
+//!             .filter(|(_, scene)| scene.narration.as_deref().unwrap_or("").contains("guaranteed"))
+//!             .map(|(i, _)| {
+//!                 Finding::new(Severity::Error, "banned-phrase", "Says \"guaranteed\"").in_scene(i)
+//!             })
+//!             .collect();
+//!         AnalyzerFindings { findings, score: None }
+//!     }
+//! }
+//!
+//! # let script: VideoScript = serde_json::from_str(
+//! #     &std::fs::read_to_string("examples/simple.json").unwrap()).unwrap();
+//! let mut analyzers = ExtraAnalyzers::new();
+//! analyzers.push(BannedPhrases);
+//! let report = AnalysisReport::analyze(&script, &AnalysisConfig::default(), &analyzers);
+//! assert!(report.analyzers.iter().any(|name| name == "house-rules"));
+//! ```
+
+use crate::analysis::accessibility::AccessibilitySettings;
+use crate::analysis::credibility::CredibilityDictionaries;
+use crate::analysis::narrative::{NarrativeSettings, Severity};
+use crate::script::VideoScript;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Settings of every analysis, and where the script's files are
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    /// Directory relative asset and audio paths are resolved against
+    pub base_path: PathBuf,
+    pub dictionaries: CredibilityDictionaries,
+    pub narrative: NarrativeSettings,
+    pub accessibility: AccessibilitySettings,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            base_path: PathBuf::from("."),
+            dictionaries: CredibilityDictionaries::default(),
+            narrative: NarrativeSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+        }
+    }
+}
+
+/// One problem an analyzer found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Id of the check within its analyzer, e.g. `pacing-too-fast`; the
+    /// report's rule id is `<analyzer>/<rule>`
+    pub rule: String,
+    /// Position of the scene it's in, when it's in one
+    pub scene_index: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(severity: Severity, rule: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            rule: rule.into(),
+            scene_index: None,
+            message: message.into(),
+        }
+    }
+
+    /// The finding, in the scene at `index`
+    pub fn in_scene(mut self, index: usize) -> Self {
+        self.scene_index = Some(index);
+        self
+    }
+}
+
+/// An analyzer's part of the overall score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScoreContribution {
+    /// 0-100
+    pub score: f32,
+    /// Share of the overall score, relative to the other analyzers' weights
+    pub weight: f32,
+}
+
+/// What one analyzer found in a script
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AnalyzerFindings {
+    pub findings: Vec<Finding>,
+    /// `None` for analyzers that only report findings
+    pub score: Option<ScoreContribution>,
+}
+
+/// An analysis of a script. The narrative, credibility and retention
+/// analyzers implement it too, so they can be run on their own like any other.
+pub trait Analyzer: Send + Sync {
+    /// Name the analyzer's issues and score are reported under
+    fn name(&self) -> &str;
+
+    fn analyze(&self, script: &VideoScript, config: &AnalysisConfig) -> AnalyzerFindings;
+}
+
+/// Analyzers the combined report runs after its built-in ones, in the order
+/// they were pushed. It holds only those extras: the report always runs the
+/// built-in analyses itself, because it keeps their full reports and not just
+/// their findings, so none of them need to be pushed here.
+#[derive(Default)]
+pub struct ExtraAnalyzers {
+    analyzers: Vec<Box<dyn Analyzer>>,
+}
+
+impl ExtraAnalyzers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, analyzer: impl Analyzer + 'static) {
+        self.analyzers.push(Box::new(analyzer));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Analyzer> {
+        self.analyzers.iter().map(|analyzer| analyzer.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.analyzers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::credibility::CredibilityAnalyzer;
+    use crate::analysis::narrative::NarrativeAnalyzer;
+    use crate::analysis::report::AnalysisReport;
+    use crate::analysis::retention::RetentionAnalyzer;
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+
+    #[test]
+    fn test_built_in_analyzers_find_what_the_report_does() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(90.0));
+        script.scenes.pop();
+        let config = AnalysisConfig::default();
+        let report = AnalysisReport::analyze(&script, &config, &ExtraAnalyzers::new());
+
+        let built_in: [&dyn Analyzer; 3] =
+            [&NarrativeAnalyzer, &CredibilityAnalyzer, &RetentionAnalyzer];
+        for analyzer in built_in {
+            let findings = analyzer.analyze(&script, &config);
+            let issues: Vec<_> = report
+                .issues
+                .iter()
+                .filter(|issue| issue.source == analyzer.name())
+                .collect();
+            assert_eq!(findings.findings.len(), issues.len(), "{}", analyzer.name());
+            for finding in &findings.findings {
+                let rule = format!("{}/{}", analyzer.name(), finding.rule);
+                assert!(
+                    issues
+                        .iter()
+                        .any(|issue| issue.rule == rule && issue.message == finding.message),
+                    "{} missing from the report",
+                    rule
+                );
+            }
+            assert!(findings.score.is_some());
+        }
+
+        let narrative = NarrativeAnalyzer.analyze(&script, &config);
+        assert_eq!(
+            narrative.score.map(|part| part.score),
+            Some(report.narrative.score as f32)
+        );
+    }
+}
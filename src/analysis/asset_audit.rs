@@ -0,0 +1,418 @@
+//! Cruft in a script's assets: files next to the ones it uses that nothing
+//! references, and byte-identical copies of a file referenced under different paths.
+
+use crate::assets::AssetLoader;
+use crate::script::{Layer, VideoScript};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AssetKind {
+    Image,
+    Video,
+    Font,
+    Audio,
+}
+
+impl AssetKind {
+    /// How messages name it
+    pub fn label(self) -> &'static str {
+        match self {
+            AssetKind::Image => "image",
+            AssetKind::Video => "video",
+            AssetKind::Font => "font",
+            AssetKind::Audio => "audio",
+        }
+    }
+
+    /// The kind of file an extension names, `None` for files that aren't assets
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tga" | "svg" => {
+                Some(AssetKind::Image)
+            }
+            "mp4" | "mov" | "webm" | "mkv" | "avi" | "m4v" => Some(AssetKind::Video),
+            "ttf" | "otf" | "ttc" | "woff" | "woff2" => Some(AssetKind::Font),
+            "wav" | "mp3" | "flac" | "ogg" | "m4a" | "aac" | "opus" => Some(AssetKind::Audio),
+            _ => None,
+        }
+    }
+}
+
+/// A `source`, `font` or `audio_source` path in the script
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetReference {
+    /// As written in the script
+    pub path: PathBuf,
+    /// Where the loader looks for it
+    pub resolved: PathBuf,
+    pub kind: AssetKind,
+    /// `None` for audio tracks
+    pub scene_index: Option<usize>,
+    /// File size, `None` when the file doesn't exist
+    pub bytes: Option<u64>,
+}
+
+/// A file in an asset directory that the script doesn't reference
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedAsset {
+    pub path: PathBuf,
+    pub kind: AssetKind,
+    pub bytes: u64,
+}
+
+/// Referenced files with the same contents
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateAssets {
+    /// SHA-256 of the contents
+    pub hash: String,
+    /// Size of each copy
+    pub bytes: u64,
+    /// The copy referenced first
+    pub kept: PathBuf,
+    /// The other copies, whose references could point at `kept`
+    pub copies: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssetAuditReport {
+    pub references: Vec<AssetReference>,
+    /// Asset files in the directories of referenced ones that nothing references
+    pub unused: Vec<UnusedAsset>,
+    pub duplicates: Vec<DuplicateAssets>,
+    /// Bytes taken by the unused files and the duplicate copies
+    pub wasted_bytes: u64,
+    /// The unused files and duplicate copies
+    pub removable: Vec<PathBuf>,
+}
+
+pub struct AssetAuditAnalyzer;
+
+impl AssetAuditAnalyzer {
+    /// Audit the files the script references, resolving paths like the loader does.
+    /// Paths in the report are relative to the loader's base path where possible.
+    pub fn analyze(script: &VideoScript, assets: &AssetLoader) -> AssetAuditReport {
+        let base = fs::canonicalize(assets.base_path()).unwrap_or_default();
+        let display = |path: &Path| path.strip_prefix(&base).unwrap_or(path).to_path_buf();
+
+        // Every referenced file once, in the order first referenced
+        let mut references = Vec::new();
+        let mut files: Vec<(PathBuf, u64)> = Vec::new();
+        for (path, kind, scene_index) in Self::paths(script) {
+            let resolved = assets.resolve_path(&path);
+            let file = fs::canonicalize(&resolved).ok();
+            let bytes = file
+                .as_ref()
+                .and_then(|file| fs::metadata(file).ok())
+                .map(|metadata| metadata.len());
+            if let (Some(file), Some(bytes)) = (file, bytes) {
+                if !files.iter().any(|(known, _)| *known == file) {
+                    files.push((file, bytes));
+                }
+            }
+            references.push(AssetReference {
+                path,
+                resolved,
+                kind,
+                scene_index,
+                bytes,
+            });
+        }
+
+        let mut unused = Vec::new();
+        let directories: BTreeSet<&Path> =
+            files.iter().filter_map(|(file, _)| file.parent()).collect();
+        for directory in directories {
+            let Ok(entries) = fs::read_dir(directory) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                let Some(kind) = AssetKind::from_path(&path) else {
+                    continue;
+                };
+                let Ok(file) = fs::canonicalize(&path) else {
+                    continue;
+                };
+                let Ok(metadata) = fs::metadata(&file) else {
+                    continue;
+                };
+                if metadata.is_file() && !files.iter().any(|(known, _)| *known == file) {
+                    unused.push(UnusedAsset {
+                        path: display(&file),
+                        kind,
+                        bytes: metadata.len(),
+                    });
+                }
+            }
+        }
+        unused.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Same-sized files are hashed and grouped by contents. Empty placeholders
+        // waste nothing, so they're never duplicates.
+        let mut by_hash: Vec<(String, u64, Vec<PathBuf>)> = Vec::new();
+        let mut sizes: HashMap<u64, usize> = HashMap::new();
+        for (_, bytes) in &files {
+            *sizes.entry(*bytes).or_default() += 1;
+        }
+        for (file, bytes) in &files {
+            if *bytes == 0 || sizes[bytes] < 2 {
+                continue;
+            }
+            let Some(hash) = hash_file(file) else {
+                continue;
+            };
+            match by_hash.iter_mut().find(|(known, _, _)| *known == hash) {
+                Some((_, _, paths)) => paths.push(display(file)),
+                None => by_hash.push((hash, *bytes, vec![display(file)])),
+            }
+        }
+        let duplicates: Vec<DuplicateAssets> = by_hash
+            .into_iter()
+            .filter(|(_, _, paths)| paths.len() > 1)
+            .map(|(hash, bytes, mut paths)| DuplicateAssets {
+                hash,
+                bytes,
+                kept: paths.remove(0),
+                copies: paths,
+            })
+            .collect();
+
+        let wasted_bytes = unused.iter().map(|asset| asset.bytes).sum::<u64>()
+            + duplicates
+                .iter()
+                .map(|duplicate| duplicate.bytes * duplicate.copies.len() as u64)
+                .sum::<u64>();
+        let removable = unused
+            .iter()
+            .map(|asset| asset.path.clone())
+            .chain(
+                duplicates
+                    .iter()
+                    .flat_map(|duplicate| duplicate.copies.clone()),
+            )
+            .collect();
+
+        AssetAuditReport {
+            references,
+            unused,
+            duplicates,
+            wasted_bytes,
+            removable,
+        }
+    }
+
+    /// Every asset path in the script, with its kind and scene
+    pub fn paths(script: &VideoScript) -> Vec<(PathBuf, AssetKind, Option<usize>)> {
+        let mut paths = Vec::new();
+        for (i, scene) in script.scenes.iter().enumerate() {
+            for layer in &scene.layers {
+                let (path, kind) = match layer {
+                    Layer::Image { source, .. } => (source, AssetKind::Image),
+                    Layer::Video { source, .. } => (source, AssetKind::Video),
+                    Layer::Text { font, .. } => (font, AssetKind::Font),
+                    Layer::Waveform { audio_source, .. } => (audio_source, AssetKind::Audio),
+                    Layer::Custom { .. } => continue,
+                };
+                paths.push((path.clone(), kind, Some(i)));
+            }
+        }
+        if let Some(audio) = &script.audio {
+            for track in &audio.tracks {
+                paths.push((track.source.clone(), AssetKind::Audio, None));
+            }
+        }
+        paths
+    }
+
+    /// The same paths as [`Self::paths`], in the same order, to rewrite them
+    pub fn paths_mut(script: &mut VideoScript) -> Vec<&mut PathBuf> {
+        let mut paths = Vec::new();
+        for scene in &mut script.scenes {
+            for layer in &mut scene.layers {
+                paths.push(match layer {
+                    Layer::Image { source, .. } => source,
+                    Layer::Video { source, .. } => source,
+                    Layer::Text { font, .. } => font,
+                    Layer::Waveform { audio_source, .. } => audio_source,
+                    Layer::Custom { .. } => continue,
+                });
+            }
+        }
+        if let Some(audio) = &mut script.audio {
+            paths.extend(audio.tracks.iter_mut().map(|track| &mut track.source));
+        }
+        paths
+    }
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&data)))
+}
+
+/// `bytes` in B, KiB or MiB, for messages
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{AudioConfig, Color, Metadata, Position, Resolution, Scene, SceneType};
+    use tempfile::TempDir;
+
+    fn image(source: &str) -> Layer {
+        Layer::Image {
+            source: source.into(),
+            effects: vec![],
+            transform: Default::default(),
+            crop: None,
+        }
+    }
+
+    fn text(font: &str) -> Layer {
+        Layer::Text {
+            content: "Hello".into(),
+            font: font.into(),
+            font_size: 48.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+        }
+    }
+
+    fn create_script(layers: Vec<Layer>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "intro".into(),
+                scene_type: SceneType::Hook,
+                duration: 3.0,
+                layers,
+                transition: None,
+                narration: None,
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_image_and_unreferenced_font() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("brand")).unwrap();
+        fs::write(dir.path().join("logo.png"), [7u8; 300]).unwrap();
+        fs::write(dir.path().join("brand/logo-copy.png"), [7u8; 300]).unwrap();
+        // Same size, different contents
+        fs::write(dir.path().join("other.png"), [8u8; 300]).unwrap();
+        fs::write(dir.path().join("used.ttf"), [1u8; 50]).unwrap();
+        fs::write(dir.path().join("unused.otf"), [2u8; 40]).unwrap();
+        fs::write(dir.path().join("notes.txt"), "not an asset").unwrap();
+
+        let script = create_script(vec![
+            image("logo.png"),
+            image("./brand/logo-copy.png"),
+            image("other.png"),
+            text("used.ttf"),
+            // The same file again is neither a duplicate nor wasted
+            text("brand/../used.ttf"),
+        ]);
+        let report = AssetAuditAnalyzer::analyze(&script, &AssetLoader::new(dir.path()));
+
+        assert_eq!(report.references.len(), 5);
+        assert!(report.references.iter().all(|r| r.bytes.is_some()));
+        // Empty files aren't copies of each other
+        fs::write(dir.path().join("empty.png"), []).unwrap();
+        fs::write(dir.path().join("empty.ttf"), []).unwrap();
+        let mut with_empty = script.clone();
+        with_empty.scenes[0]
+            .layers
+            .extend([image("empty.png"), text("empty.ttf")]);
+        let with_empty = AssetAuditAnalyzer::analyze(&with_empty, &AssetLoader::new(dir.path()));
+        assert_eq!(with_empty.duplicates.len(), 1);
+
+        assert_eq!(report.duplicates.len(), 1);
+        let duplicate = &report.duplicates[0];
+        assert_eq!(duplicate.kept, PathBuf::from("logo.png"));
+        assert_eq!(duplicate.copies, vec![PathBuf::from("brand/logo-copy.png")]);
+        assert_eq!(duplicate.bytes, 300);
+
+        assert_eq!(report.unused.len(), 1);
+        assert_eq!(report.unused[0].path, PathBuf::from("unused.otf"));
+        assert_eq!(report.unused[0].kind, AssetKind::Font);
+
+        assert_eq!(report.wasted_bytes, 340);
+        assert_eq!(
+            report.removable,
+            vec![
+                PathBuf::from("unused.otf"),
+                PathBuf::from("brand/logo-copy.png")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths_resolve_like_the_asset_loader() {
+        let dir = TempDir::new().unwrap();
+        let script_dir = dir.path().join("scripts");
+        fs::create_dir(&script_dir).unwrap();
+        fs::write(dir.path().join("music.mp3"), [3u8; 10]).unwrap();
+        let absolute = dir.path().join("font.ttf");
+        fs::write(&absolute, [4u8; 10]).unwrap();
+
+        let mut script = create_script(vec![text(absolute.to_str().unwrap())]);
+        script.audio = Some(
+            serde_json::from_value::<AudioConfig>(
+                serde_json::json!({ "tracks": [{ "source": "../music.mp3" }] }),
+            )
+            .unwrap(),
+        );
+        let report = AssetAuditAnalyzer::analyze(&script, &AssetLoader::new(&script_dir));
+
+        // Relative to the script, absolute as is; nothing else in the directory
+        assert!(report.references.iter().all(|r| r.bytes == Some(10)));
+        assert_eq!(report.references[0].resolved, absolute);
+        assert_eq!(
+            report.references[1].resolved,
+            script_dir.join("../music.mp3")
+        );
+        assert_eq!(report.references[1].scene_index, None);
+        assert!(report.unused.is_empty());
+        assert!(report.duplicates.is_empty(), "{:?}", report.duplicates);
+        assert_eq!(report.wasted_bytes, 0);
+
+        // Missing files are listed without a size and not audited
+        let missing = create_script(vec![image("missing.png")]);
+        let report = AssetAuditAnalyzer::analyze(&missing, &AssetLoader::new(&script_dir));
+        assert_eq!(report.references[0].bytes, None);
+        assert!(report.unused.is_empty());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1_048_576), "5.0 MiB");
+    }
+}
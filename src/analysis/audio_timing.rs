@@ -0,0 +1,215 @@
+#[cfg(feature = "render")]
+use crate::audio::AudioDecoder;
+use crate::error::Result;
+use crate::script::VideoScript;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackTiming {
+    pub source: String,
+    pub start_time: f32,
+    /// Played length after trimming; `None` if the file could not be probed
+    pub duration: Option<f32>,
+    pub looping: bool,
+}
+
+impl TrackTiming {
+    /// Time at which the track stops playing, if known
+    pub fn end_time(&self) -> Option<f32> {
+        self.duration.map(|d| self.start_time + d)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioTimingReport {
+    pub tracks: Vec<TrackTiming>,
+    pub warnings: Vec<String>,
+}
+
+/// Compares audio track lengths against the video timeline
+pub struct AudioTimingAnalyzer;
+
+impl AudioTimingAnalyzer {
+    /// Probe every audio track (relative to `base_path`) and warn about tracks that
+    /// overhang the video or start after it ends
+    pub fn analyze(script: &VideoScript, base_path: &Path) -> AudioTimingReport {
+        let video_duration = script.metadata.duration;
+        // Allow one video frame of rounding
+        let tolerance = 1.0 / script.metadata.fps.max(1) as f32;
+
+        let mut tracks = Vec::new();
+        let mut warnings = Vec::new();
+
+        let Some(audio) = &script.audio else {
+            return AudioTimingReport { tracks, warnings };
+        };
+
+        for track in &audio.tracks {
+            let source = track.source.display().to_string();
+            let path = if track.source.is_absolute() {
+                track.source.clone()
+            } else {
+                base_path.join(&track.source)
+            };
+
+            let duration = match file_duration(&path) {
+                Ok(file_duration) => {
+                    let end = track.trim_end.unwrap_or(file_duration).min(file_duration);
+                    Some((end - track.trim_start).max(0.0))
+                }
+                Err(e) => {
+                    warnings.push(format!(
+                        "Audio track '{}' could not be probed: {}",
+                        source, e
+                    ));
+                    None
+                }
+            };
+
+            if track.start_time >= video_duration {
+                warnings.push(format!(
+                    "Audio track '{}' starts at {:.2}s, after the video ends ({:.2}s)",
+                    source, track.start_time, video_duration
+                ));
+            } else if let Some(duration) = duration.filter(|_| !track.looping) {
+                // Looping tracks always stop with the video
+                let end = track.start_time + duration;
+                if end > video_duration + tolerance {
+                    warnings.push(format!(
+                        "Audio track '{}' ends at {:.2}s but the video is {:.2}s; the last {:.2}s will be cut",
+                        source,
+                        end,
+                        video_duration,
+                        end - video_duration
+                    ));
+                }
+            }
+
+            tracks.push(TrackTiming {
+                source,
+                start_time: track.start_time,
+                duration,
+                looping: track.looping,
+            });
+        }
+
+        AudioTimingReport { tracks, warnings }
+    }
+}
+
+/// Length of the audio file at `path` in seconds
+#[cfg(feature = "render")]
+fn file_duration(path: &Path) -> Result<f32> {
+    AudioDecoder::duration(path)
+}
+
+#[cfg(not(feature = "render"))]
+fn file_duration(_path: &Path) -> Result<f32> {
+    Err(crate::VideoEngineError::invalid(
+        "audio files can't be read without the `render` feature",
+    ))
+}
+
+#[cfg(all(test, feature = "render"))]
+mod tests {
+    use super::*;
+    use crate::script::{AudioConfig, AudioTrack, AudioTrackType};
+    use tempfile::TempDir;
+
+    fn write_wav(path: &Path, seconds: f32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for _ in 0..(seconds * 8000.0) as usize {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn track(source: &str, start_time: f32) -> AudioTrack {
+        serde_json::from_value(serde_json::json!({
+            "source": source,
+            "start_time": start_time
+        }))
+        .unwrap()
+    }
+
+    fn script(duration: f32, tracks: Vec<AudioTrack>) -> VideoScript {
+        let mut script: VideoScript = serde_json::from_value(serde_json::json!({
+            "metadata": {"title": "Test", "resolution": "1280x720", "fps": 30, "duration": duration},
+            "scenes": [{"id": "s1", "duration": duration, "layers": [{"type": "image", "source": "t.png"}]}]
+        }))
+        .unwrap();
+        script.audio = Some(AudioConfig {
+            tracks,
+            normalization: None,
+            ducking: None,
+        });
+        script
+    }
+
+    #[test]
+    fn test_track_within_video_has_no_warnings() {
+        let dir = TempDir::new().unwrap();
+        write_wav(&dir.path().join("voice.wav"), 2.0);
+
+        let report =
+            AudioTimingAnalyzer::analyze(&script(3.0, vec![track("voice.wav", 1.0)]), dir.path());
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert_eq!(report.tracks[0].end_time(), Some(3.0));
+    }
+
+    #[test]
+    fn test_overhang_warning() {
+        let dir = TempDir::new().unwrap();
+        write_wav(&dir.path().join("voice.wav"), 3.0);
+
+        let report =
+            AudioTimingAnalyzer::analyze(&script(2.0, vec![track("voice.wav", 0.5)]), dir.path());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("ends at 3.50s"));
+        assert!(report.warnings[0].contains("last 1.50s will be cut"));
+    }
+
+    #[test]
+    fn test_trimmed_and_looping_tracks_do_not_overhang() {
+        let dir = TempDir::new().unwrap();
+        write_wav(&dir.path().join("music.wav"), 5.0);
+
+        let mut trimmed = track("music.wav", 0.0);
+        trimmed.trim_start = 1.0;
+        trimmed.trim_end = Some(2.5);
+        let mut looping = track("music.wav", 0.0);
+        looping.looping = true;
+
+        let report = AudioTimingAnalyzer::analyze(&script(2.0, vec![trimmed, looping]), dir.path());
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert_eq!(report.tracks[0].duration, Some(1.5));
+    }
+
+    #[test]
+    fn test_track_starting_after_video_end() {
+        let dir = TempDir::new().unwrap();
+        write_wav(&dir.path().join("sfx.wav"), 0.5);
+
+        let mut sfx = track("sfx.wav", 4.0);
+        sfx.track_type = AudioTrackType::SoundEffect;
+        let report = AudioTimingAnalyzer::analyze(&script(3.0, vec![sfx]), dir.path());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("after the video ends"));
+    }
+
+    #[test]
+    fn test_missing_file_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let report =
+            AudioTimingAnalyzer::analyze(&script(3.0, vec![track("missing.wav", 0.0)]), dir.path());
+        assert_eq!(report.tracks[0].duration, None);
+        assert!(report.warnings[0].contains("could not be probed"));
+    }
+}
@@ -0,0 +1,443 @@
+//! Words that get videos flagged in monetization and brand-safety reviews:
+//! profanity, violence and medical claims, plus words the user denies.
+
+use crate::script::{Layer, VideoScript};
+use serde::{Deserialize, Serialize};
+
+const PROFANITY: &[&str] = &[
+    "fuck", "shit", "bitch", "bastard", "asshole", "crap", "dick", "piss", "damn",
+];
+
+const VIOLENCE: &[&str] = &[
+    "kill",
+    "murder",
+    "massacre",
+    "slaughter",
+    "shoot",
+    "stab",
+    "bomb",
+    "behead",
+    "bloodbath",
+];
+
+const MEDICAL_CLAIMS: &[&str] = &[
+    "cure",
+    "miracle cure",
+    "clinically proven",
+    "treats cancer",
+    "detox",
+    "anti-aging",
+    "fda approved",
+    "lose weight fast",
+];
+
+/// Endings a listed word may take: `kill` also flags `kills`, `killed`, `killer`
+const SUFFIXES: &[&str] = &["", "s", "es", "ed", "ing", "er", "ers"];
+
+/// Why a word is flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BrandSafetyCategory {
+    Profanity,
+    Violence,
+    MedicalClaim,
+    /// From `credibility.brand_safety.deny`
+    Denylist,
+}
+
+impl BrandSafetyCategory {
+    /// How messages name it
+    pub fn label(self) -> &'static str {
+        match self {
+            BrandSafetyCategory::Profanity => "Profanity",
+            BrandSafetyCategory::Violence => "Violence",
+            BrandSafetyCategory::MedicalClaim => "Medical claim",
+            BrandSafetyCategory::Denylist => "Denied word",
+        }
+    }
+}
+
+/// User additions to the built-in lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrandSafetySettings {
+    /// More words and phrases to flag
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Words and phrases never flagged, e.g. "shoot" in a photography video
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Also flag words spelled with leetspeak or repeated letters (`sh1t`, `shiiit`)
+    #[serde(default = "default_match_obfuscations")]
+    pub match_obfuscations: bool,
+}
+
+fn default_match_obfuscations() -> bool {
+    true
+}
+
+impl Default for BrandSafetySettings {
+    fn default() -> Self {
+        Self {
+            deny: Vec::new(),
+            allow: Vec::new(),
+            match_obfuscations: default_match_obfuscations(),
+        }
+    }
+}
+
+/// A flagged word in the script
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrandSafetyMatch {
+    /// `None` in the title and description
+    pub scene_index: Option<usize>,
+    /// Where it was found: `title`, `description`, `text` or `narration`
+    pub field: &'static str,
+    pub category: BrandSafetyCategory,
+    /// The listed word or phrase
+    pub word: String,
+    /// As written in the script
+    pub text: String,
+    /// Only matched after undoing leetspeak or repeated letters
+    pub obfuscated: bool,
+}
+
+impl BrandSafetyMatch {
+    /// Where the match is, for messages: `scene 2 narration` or `the title`
+    pub fn location(&self) -> String {
+        match self.scene_index {
+            Some(index) => format!("scene {} {}", index + 1, self.field),
+            None => format!("the {}", self.field),
+        }
+    }
+}
+
+/// A listed word or phrase, split into lowercase words
+struct Entry {
+    category: BrandSafetyCategory,
+    word: String,
+    tokens: Vec<String>,
+}
+
+pub struct BrandSafetyAnalyzer;
+
+impl BrandSafetyAnalyzer {
+    /// Every flagged word in the title, description, text layers and narration
+    pub fn analyze(script: &VideoScript, settings: &BrandSafetySettings) -> Vec<BrandSafetyMatch> {
+        let builtin = [
+            (BrandSafetyCategory::Profanity, PROFANITY),
+            (BrandSafetyCategory::Violence, VIOLENCE),
+            (BrandSafetyCategory::MedicalClaim, MEDICAL_CLAIMS),
+        ];
+        let mut entries: Vec<Entry> = builtin
+            .iter()
+            .flat_map(|(category, words)| words.iter().map(move |word| (*category, *word)))
+            .chain(
+                settings
+                    .deny
+                    .iter()
+                    .map(|word| (BrandSafetyCategory::Denylist, word.as_str())),
+            )
+            .map(|(category, word)| Entry {
+                category,
+                word: word.to_string(),
+                tokens: tokens(word).into_iter().map(|(_, token)| token).collect(),
+            })
+            .filter(|entry| !entry.tokens.is_empty())
+            .collect();
+        // Longest first, so `miracle cure` is found rather than `cure`
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.tokens.len()));
+        let allowed: Vec<String> = settings.allow.iter().map(|word| phrase(word)).collect();
+
+        let mut texts: Vec<(Option<usize>, &'static str, &str)> =
+            vec![(None, "title", script.metadata.title.as_str())];
+        texts.extend(
+            script
+                .metadata
+                .description
+                .as_deref()
+                .map(|description| (None, "description", description)),
+        );
+        for (i, scene) in script.scenes.iter().enumerate() {
+            for layer in &scene.layers {
+                if let Layer::Text { content, .. } = layer {
+                    texts.push((Some(i), "text", content.as_str()));
+                }
+            }
+            texts.extend(
+                scene
+                    .narration
+                    .as_deref()
+                    .map(|narration| (Some(i), "narration", narration)),
+            );
+        }
+
+        let mut matches = Vec::new();
+        for (scene_index, field, text) in texts {
+            let words = tokens(text);
+            let mut i = 0;
+            while i < words.len() {
+                let found = entries.iter().find_map(|entry| {
+                    Self::match_at(&words[i..], entry, settings.match_obfuscations)
+                        .map(|obfuscated| (entry, obfuscated))
+                });
+                let Some((entry, obfuscated)) = found else {
+                    i += 1;
+                    continue;
+                };
+
+                let matched = &words[i..i + entry.tokens.len()];
+                let (start, end) = (matched[0].0.start, matched[matched.len() - 1].0.end);
+                let written = &text[start..end];
+                i += entry.tokens.len();
+                if allowed.contains(&phrase(&entry.word)) || allowed.contains(&phrase(written)) {
+                    continue;
+                }
+                matches.push(BrandSafetyMatch {
+                    scene_index,
+                    field,
+                    category: entry.category,
+                    word: entry.word.clone(),
+                    text: written.to_string(),
+                    obfuscated,
+                });
+            }
+        }
+        matches
+    }
+
+    /// Whether `entry` starts at the first of `words`, and if so whether it
+    /// only matched after undoing obfuscations
+    fn match_at(
+        words: &[(std::ops::Range<usize>, String)],
+        entry: &Entry,
+        obfuscations: bool,
+    ) -> Option<bool> {
+        if words.len() < entry.tokens.len() {
+            return None;
+        }
+        let mut obfuscated = false;
+        for (index, (expected, (_, word))) in entry.tokens.iter().zip(words).enumerate() {
+            // Only the last word of a phrase takes an ending
+            let suffixes = if index + 1 == entry.tokens.len() {
+                SUFFIXES
+            } else {
+                &[""]
+            };
+            let forms = suffixes
+                .iter()
+                .map(|suffix| format!("{}{}", expected, suffix));
+            let mut matched = None;
+            for form in forms {
+                if *word == form {
+                    matched = Some(false);
+                    break;
+                }
+                if obfuscations && looks_obfuscated(word) && normalize(word) == normalize(&form) {
+                    matched = Some(true);
+                }
+            }
+            obfuscated |= matched?;
+        }
+        Some(obfuscated)
+    }
+}
+
+/// Lowercase words of `text` with their byte ranges. `@` and `$` count as
+/// letters, since leetspeak uses them for `a` and `s`.
+fn tokens(text: &str) -> Vec<(std::ops::Range<usize>, String)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '@' || c == '$';
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, is_word_char(c)) {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                words.push((from..i, text[from..i].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// `text` as lowercase words separated by single spaces
+fn phrase(text: &str) -> String {
+    tokens(text)
+        .into_iter()
+        .map(|(_, word)| word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Letters mixed with leetspeak digits or symbols, or a letter three times in a
+/// row. Plain words never count, so `shot` isn't read as `shoot`.
+fn looks_obfuscated(word: &str) -> bool {
+    let has_letter = word.chars().any(char::is_alphabetic);
+    let has_leet = word.chars().any(|c| leet(c).is_some());
+    let chars: Vec<char> = word.chars().collect();
+    let repeated = chars.windows(3).any(|w| w[0] == w[1] && w[1] == w[2]);
+    has_letter && (has_leet || repeated)
+}
+
+fn leet(c: char) -> Option<char> {
+    match c {
+        '0' => Some('o'),
+        '1' => Some('i'),
+        '3' => Some('e'),
+        '4' | '@' => Some('a'),
+        '5' | '$' => Some('s'),
+        '7' => Some('t'),
+        _ => None,
+    }
+}
+
+/// `word` with leetspeak replaced and runs of a letter collapsed to one
+fn normalize(word: &str) -> String {
+    let mut normalized = String::new();
+    for c in word.chars().map(|c| leet(c).unwrap_or(c)) {
+        if !normalized.ends_with(c) {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution, Scene, SceneType};
+
+    fn create_script(texts: &[&str], narration: Option<&str>) -> VideoScript {
+        let layers = texts
+            .iter()
+            .map(|text| Layer::Text {
+                content: text.to_string(),
+                font: "font.ttf".into(),
+                font_size: 48.0,
+                color: Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                },
+                position: Position { x: 0, y: 0 },
+                effects: vec![],
+            })
+            .collect();
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![
+                Scene {
+                    id: "intro".into(),
+                    scene_type: SceneType::Hook,
+                    duration: 3.0,
+                    layers: vec![],
+                    transition: None,
+                    narration: None,
+                    title: None,
+                },
+                Scene {
+                    id: "body".into(),
+                    scene_type: SceneType::Body,
+                    duration: 3.0,
+                    layers,
+                    transition: None,
+                    narration: narration.map(str::to_string),
+                    title: None,
+                },
+            ],
+            audio: None,
+        }
+    }
+
+    fn analyze(script: &VideoScript) -> Vec<BrandSafetyMatch> {
+        BrandSafetyAnalyzer::analyze(script, &BrandSafetySettings::default())
+    }
+
+    #[test]
+    fn test_flagged_words_with_location_and_category() {
+        let script = create_script(
+            &["This tea is clinically proven to work"],
+            Some("The plot kills it."),
+        );
+        let matches = analyze(&script);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].category, BrandSafetyCategory::MedicalClaim);
+        assert_eq!(matches[0].word, "clinically proven");
+        assert_eq!(matches[0].text, "clinically proven");
+        assert_eq!(matches[0].location(), "scene 2 text");
+        assert_eq!(matches[1].category, BrandSafetyCategory::Violence);
+        assert_eq!(matches[1].text, "kills");
+        assert_eq!(matches[1].location(), "scene 2 narration");
+        assert!(!matches[1].obfuscated);
+
+        // Whole words only
+        let script = create_script(&["New skills, scrappy shooting-star cocktail"], None);
+        let matches = analyze(&script);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "shooting");
+    }
+
+    #[test]
+    fn test_title_description_and_deny_list() {
+        let mut script = create_script(&[], None);
+        script.metadata.title = "Damn good coffee".into();
+        script.metadata.description = Some("Sponsored by Acme Corp".into());
+        let settings = BrandSafetySettings {
+            deny: vec!["acme corp".into()],
+            ..Default::default()
+        };
+        let matches = BrandSafetyAnalyzer::analyze(&script, &settings);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].location(), "the title");
+        assert_eq!(matches[0].category, BrandSafetyCategory::Profanity);
+        assert_eq!(matches[1].location(), "the description");
+        assert_eq!(matches[1].category, BrandSafetyCategory::Denylist);
+        assert_eq!(matches[1].text, "Acme Corp");
+    }
+
+    #[test]
+    fn test_allowlisted_word_is_not_flagged() {
+        let script = create_script(
+            &["How to shoot portraits", "Don't bomb the interview"],
+            None,
+        );
+        let settings = BrandSafetySettings {
+            allow: vec!["Shoot".into()],
+            ..Default::default()
+        };
+        let matches = BrandSafetyAnalyzer::analyze(&script, &settings);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].word, "bomb");
+    }
+
+    #[test]
+    fn test_obfuscated_matches() {
+        let script = create_script(&["What the sh1t", "Shiiiit happens", "k1ll3r app"], None);
+        let matches = analyze(&script);
+
+        let texts: Vec<&str> = matches.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, vec!["sh1t", "Shiiiit", "k1ll3r"]);
+        assert!(matches.iter().all(|m| m.obfuscated));
+
+        // Plain words that collapse to a listed one aren't obfuscations
+        assert!(analyze(&create_script(&["One shot of espresso"], None)).is_empty());
+
+        let literal = BrandSafetySettings {
+            match_obfuscations: false,
+            ..Default::default()
+        };
+        assert!(BrandSafetyAnalyzer::analyze(&script, &literal).is_empty());
+    }
+}
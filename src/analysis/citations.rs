@@ -0,0 +1,270 @@
+//! Whether URL citations still resolve: a citation that 404s is worse than none.
+//! Requests need the `citation-check` feature; other citations are never checked.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Citations checked at once
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// How citation URLs are requested
+#[derive(Debug, Clone)]
+pub struct CitationCheckSettings {
+    /// Per request, connecting included
+    pub timeout: Duration,
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// Extra attempts after a connection error or a 5xx status
+    pub retries: u32,
+}
+
+impl Default for CitationCheckSettings {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            concurrency: DEFAULT_CONCURRENCY,
+            retries: 1,
+        }
+    }
+}
+
+/// Result of requesting one citation URL
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CitationStatus {
+    pub url: String,
+    /// HTTP status of the last response, `None` when no response came
+    pub status: Option<u16>,
+    /// Where redirects ended, when that differs from `url`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// Why no response came, e.g. a timeout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CitationStatus {
+    /// Answered with a 2xx status, after following redirects
+    pub fn reachable(&self) -> bool {
+        self.status
+            .is_some_and(|status| (200..300).contains(&status))
+    }
+
+    /// The status or error, for messages
+    pub fn outcome(&self) -> String {
+        match (self.status, &self.error) {
+            (Some(status), _) => format!("HTTP {}", status),
+            (None, Some(error)) => error.clone(),
+            (None, None) => "no response".to_string(),
+        }
+    }
+}
+
+/// Whether a citation is a URL to request
+pub fn is_url(citation: &str) -> bool {
+    citation.starts_with("http://") || citation.starts_with("https://")
+}
+
+pub struct CitationChecker;
+
+impl CitationChecker {
+    /// Request every URL citation, `settings.concurrency` at a time, in citation order
+    #[cfg(feature = "citation-check")]
+    pub fn check(
+        citations: &[String],
+        settings: &CitationCheckSettings,
+    ) -> Result<Vec<CitationStatus>> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        let urls: Vec<&str> = citations
+            .iter()
+            .map(|citation| citation.trim())
+            .filter(|citation| is_url(citation))
+            .collect();
+        let agent = ureq::AgentBuilder::new()
+            .timeout(settings.timeout)
+            .redirects(5)
+            .build();
+        let next = AtomicUsize::new(0);
+        let statuses = Mutex::new(vec![None; urls.len()]);
+
+        std::thread::scope(|scope| {
+            for _ in 0..settings.concurrency.clamp(1, urls.len().max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(url) = urls.get(i) else {
+                        break;
+                    };
+                    let status = Self::request_with_retries(&agent, url, settings.retries);
+                    statuses.lock().unwrap()[i] = Some(status);
+                });
+            }
+        });
+
+        Ok(statuses
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// Without the `citation-check` feature there is no HTTP client
+    #[cfg(not(feature = "citation-check"))]
+    pub fn check(
+        citations: &[String],
+        _settings: &CitationCheckSettings,
+    ) -> Result<Vec<CitationStatus>> {
+        if citations.iter().any(|citation| is_url(citation.trim())) {
+            anyhow::bail!(
+                "Checking citations needs the `citation-check` feature; rebuild with `--features citation-check`"
+            );
+        }
+        Ok(Vec::new())
+    }
+
+    #[cfg(feature = "citation-check")]
+    fn request_with_retries(agent: &ureq::Agent, url: &str, retries: u32) -> CitationStatus {
+        let mut status = Self::request(agent, url);
+        for _ in 0..retries {
+            let transient = status.status.is_none_or(|code| code >= 500);
+            if !transient {
+                break;
+            }
+            status = Self::request(agent, url);
+        }
+        status
+    }
+
+    /// HEAD, or GET when the server doesn't answer HEAD requests
+    #[cfg(feature = "citation-check")]
+    fn request(agent: &ureq::Agent, url: &str) -> CitationStatus {
+        let mut result = agent.head(url).call();
+        if let Err(ureq::Error::Status(405 | 501, _)) = result {
+            result = agent.get(url).call();
+        }
+
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => {
+                return CitationStatus {
+                    url: url.to_string(),
+                    status: None,
+                    final_url: None,
+                    error: Some(error.to_string()),
+                }
+            }
+        };
+        let final_url = (response.get_url() != url).then(|| response.get_url().to_string());
+        CitationStatus {
+            url: url.to_string(),
+            status: Some(response.status()),
+            final_url,
+            error: None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "citation-check"))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// A server answering `/ok` with 200, `/moved` with a redirect to `/ok`,
+    /// `/slow` never, and anything else with 404. Returns its base URL.
+    fn mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    // Skip the headers
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                        line.clear();
+                    }
+                    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                    let response = match path {
+                        "/ok" => "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+                        "/moved" => "HTTP/1.1 301 Moved Permanently\r\nLocation: /ok\r\nContent-Length: 0\r\n\r\n",
+                        "/slow" => {
+                            std::thread::sleep(Duration::from_secs(5));
+                            return;
+                        }
+                        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+                    };
+                    let mut stream = stream;
+                    let _ = stream.write_all(response.as_bytes());
+                });
+            }
+        });
+        base
+    }
+
+    fn settings() -> CitationCheckSettings {
+        CitationCheckSettings {
+            timeout: Duration::from_millis(300),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reachable_and_redirected_urls() {
+        let base = mock_server();
+        let citations = vec![format!("{}/ok", base), format!("{}/moved", base)];
+        let statuses = CitationChecker::check(&citations, &settings()).unwrap();
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].status, Some(200));
+        assert_eq!(statuses[0].final_url, None);
+        assert!(statuses[0].reachable());
+        assert_eq!(statuses[1].status, Some(200));
+        assert_eq!(statuses[1].final_url, Some(format!("{}/ok", base)));
+    }
+
+    #[test]
+    fn test_missing_url() {
+        let base = mock_server();
+        let citations = vec![format!("{}/old-study", base)];
+        let statuses = CitationChecker::check(&citations, &settings()).unwrap();
+
+        assert_eq!(statuses[0].status, Some(404));
+        assert!(!statuses[0].reachable());
+        assert_eq!(statuses[0].outcome(), "HTTP 404");
+    }
+
+    #[test]
+    fn test_timeout() {
+        let base = mock_server();
+        let citations = vec![format!("{}/slow", base)];
+        let started = std::time::Instant::now();
+        let statuses = CitationChecker::check(&citations, &settings()).unwrap();
+
+        assert_eq!(statuses[0].status, None);
+        assert!(statuses[0].error.is_some());
+        assert!(!statuses[0].reachable());
+        // One try and one retry, each cut off by the timeout
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_non_url_citations_are_skipped() {
+        let base = mock_server();
+        let citations = vec![
+            "[Smith, 2020]".to_string(),
+            "Viewer Retention Studies by YouTube".to_string(),
+            format!("{}/ok", base),
+        ];
+        let statuses = CitationChecker::check(&citations, &settings()).unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert!(statuses[0].url.ends_with("/ok"));
+        assert!(CitationChecker::check(&citations[..2], &settings())
+            .unwrap()
+            .is_empty());
+    }
+}
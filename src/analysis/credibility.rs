@@ -1,8 +1,163 @@
-use crate::script::VideoScript;
+use crate::analysis::analyzer::{
+    AnalysisConfig, Analyzer, AnalyzerFindings, Finding, ScoreContribution,
+};
+use crate::analysis::brand_safety::{BrandSafetyAnalyzer, BrandSafetyMatch, BrandSafetySettings};
+use crate::analysis::citations::CitationStatus;
+use crate::analysis::narrative::Severity;
+use crate::analysis::report::CREDIBILITY_WEIGHT;
+use crate::script::{slugify, VideoScript};
+use anyhow::{Context, Result};
 use regex::Regex;
 
 use serde::Serialize;
 
+/// Vague attributions the clarity check flags
+pub const DEFAULT_WEASEL_WORDS: &[&str] = &[
+    "many people",
+    "some people",
+    "it is believed",
+    "studies show",
+    "experts agree",
+];
+
+/// Superlatives the tone check counts
+pub const DEFAULT_HYPE_WORDS: &[&str] = &[
+    "amazing",
+    "incredible",
+    "revolutionary",
+    "game-changing",
+    "miracle",
+    "best ever",
+];
+
+/// Words that make a sentence a claim needing a source
+pub const DEFAULT_CLAIM_SUPERLATIVES: &[&str] =
+    &["best", "fastest", "first", "only", "proven", "guaranteed"];
+
+/// Absolute terms that make a sentence a claim needing a source
+pub const DEFAULT_ABSOLUTES: &[&str] = &["always", "never", "everyone", "nobody"];
+
+/// Words and phrases one credibility check looks for, matched case-insensitively
+/// as whole words (on Unicode word boundaries, so `incrível` doesn't match
+/// `incrívelmente`)
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    /// Setting it's configured with, e.g. `weasel_words`
+    pub name: &'static str,
+    /// Where the words came from: `default`, `config` or a file
+    pub origin: String,
+    pub words: Vec<String>,
+    /// `None` for an empty list, which matches nothing
+    regex: Option<Regex>,
+}
+
+impl Dictionary {
+    pub fn new(name: &'static str, origin: impl Into<String>, words: Vec<String>) -> Result<Self> {
+        let mut patterns: Vec<String> = words
+            .iter()
+            .map(|word| word.trim())
+            .filter(|word| !word.is_empty())
+            .map(Self::word_pattern)
+            .collect();
+        // Longest first, so `best ever` is found rather than `best`
+        patterns.sort_by_key(|pattern| std::cmp::Reverse(pattern.len()));
+
+        let regex = if patterns.is_empty() {
+            None
+        } else {
+            let pattern = format!("(?i)(?:{})", patterns.join("|"));
+            Some(
+                Regex::new(&pattern)
+                    .with_context(|| format!("Failed to compile the {} dictionary", name))?,
+            )
+        };
+        Ok(Self {
+            name,
+            origin: origin.into(),
+            words,
+            regex,
+        })
+    }
+
+    /// One of the defaults above
+    fn builtin(name: &'static str, words: &[&str]) -> Self {
+        let words = words.iter().map(|word| word.to_string()).collect();
+        Self::new(name, "default", words).expect("default dictionaries compile")
+    }
+
+    /// `word` escaped, with any run of whitespace matching any other and word
+    /// boundaries on the ends that are word characters (`\b` before a symbol
+    /// would demand a letter in front of it)
+    fn word_pattern(word: &str) -> String {
+        let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let body = word
+            .split_whitespace()
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(r"\s+");
+        format!(
+            "{}{}{}",
+            if is_word_char(word.chars().next()) {
+                r"\b"
+            } else {
+                ""
+            },
+            body,
+            if is_word_char(word.chars().last()) {
+                r"\b"
+            } else {
+                ""
+            }
+        )
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(text))
+    }
+
+    /// Every match in `text`, as written there
+    pub fn find_all<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        self.regex.as_ref().map_or_else(Vec::new, |regex| {
+            regex.find_iter(text).map(|m| m.as_str()).collect()
+        })
+    }
+
+    /// Names the list for messages, e.g. `default weasel_words` or `weasel_words from words.txt`
+    pub fn label(&self) -> String {
+        match self.origin.as_str() {
+            "default" => format!("default {}", self.name),
+            origin => format!("{} from {}", self.name, origin),
+        }
+    }
+}
+
+/// Every word list the credibility checks use
+#[derive(Debug, Clone)]
+pub struct CredibilityDictionaries {
+    pub weasel_words: Dictionary,
+    pub hype_words: Dictionary,
+    pub claim_superlatives: Dictionary,
+    pub absolutes: Dictionary,
+    pub brand_safety: BrandSafetySettings,
+}
+
+impl Default for CredibilityDictionaries {
+    fn default() -> Self {
+        Self {
+            weasel_words: Dictionary::builtin("weasel_words", DEFAULT_WEASEL_WORDS),
+            hype_words: Dictionary::builtin("hype_words", DEFAULT_HYPE_WORDS),
+            claim_superlatives: Dictionary::builtin(
+                "claim_superlatives",
+                DEFAULT_CLAIM_SUPERLATIVES,
+            ),
+            absolutes: Dictionary::builtin("absolutes", DEFAULT_ABSOLUTES),
+            brand_safety: BrandSafetySettings::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Claim {
     pub text: String,
@@ -16,6 +171,9 @@ pub struct ChecklistItem {
     pub passed: bool,
     pub message: String,
     pub category: String,
+    /// The scene a finding is in, when it is in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -24,13 +182,95 @@ pub struct CredibilityReport {
     pub claims: Vec<Claim>,
     pub citations: Vec<String>,
     pub checklist: Vec<ChecklistItem>,
+    /// Flagged words, also listed in the checklist
+    pub brand_safety: Vec<BrandSafetyMatch>,
+    /// Responses to the citation URLs, when they were checked
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub citation_checks: Vec<CitationStatus>,
+}
+
+impl CredibilityReport {
+    /// Record the responses to the citation URLs, failing the checklist for each
+    /// one that didn't answer with a 2xx status
+    pub fn add_citation_checks(&mut self, statuses: Vec<CitationStatus>) {
+        let unreachable: Vec<&CitationStatus> = statuses
+            .iter()
+            .filter(|status| !status.reachable())
+            .collect();
+        for status in &unreachable {
+            self.checklist.push(ChecklistItem {
+                passed: false,
+                category: "Citations".to_string(),
+                scene_index: None,
+                message: format!(
+                    "Citation '{}' is unreachable ({}). Replace it or link an archived copy.",
+                    status.url,
+                    status.outcome()
+                ),
+            });
+        }
+        if unreachable.is_empty() && !statuses.is_empty() {
+            self.checklist.push(ChecklistItem {
+                passed: true,
+                category: "Citations".to_string(),
+                scene_index: None,
+                message: format!("{} citation URL(s) reachable.", statuses.len()),
+            });
+        }
+        self.citation_checks = statuses;
+    }
+
+    /// Unverified claims and failed checklist items as findings, with the score
+    pub fn findings(&self) -> AnalyzerFindings {
+        let mut findings = Vec::new();
+        for claim in self.claims.iter().filter(|c| !c.verified) {
+            let message = format!("Unverified claim \"{}\" ({})", claim.text, claim.reason);
+            findings.push(
+                Finding::new(Severity::Warning, "unverified-claim", message)
+                    .in_scene(claim.scene_index),
+            );
+        }
+        for item in self.checklist.iter().filter(|item| !item.passed) {
+            let message = format!("{}: {}", item.category, item.message);
+            findings.push(Finding {
+                scene_index: item.scene_index,
+                ..Finding::new(Severity::Warning, slugify(&item.category), message)
+            });
+        }
+        AnalyzerFindings {
+            findings,
+            score: Some(ScoreContribution {
+                score: self.score as f32,
+                weight: CREDIBILITY_WEIGHT,
+            }),
+        }
+    }
 }
 
 pub struct CredibilityAnalyzer;
 
+impl Analyzer for CredibilityAnalyzer {
+    fn name(&self) -> &str {
+        "credibility"
+    }
+
+    fn analyze(&self, script: &VideoScript, config: &AnalysisConfig) -> AnalyzerFindings {
+        Self::analyze_with_dictionaries(script, &config.dictionaries).findings()
+    }
+}
+
 impl CredibilityAnalyzer {
+    /// Analyze with the default word lists
     pub fn analyze(script: &VideoScript) -> CredibilityReport {
-        let claims = Self::detect_claims(script);
+        Self::analyze_with_dictionaries(script, &CredibilityDictionaries::default())
+    }
+
+    /// Analyze with the given word lists
+    pub fn analyze_with_dictionaries(
+        script: &VideoScript,
+        dictionaries: &CredibilityDictionaries,
+    ) -> CredibilityReport {
+        let claims = Self::detect_claims(script, dictionaries);
         let citations = script.metadata.citations.clone();
 
         let verified_claims = claims.iter().filter(|c| c.verified).count();
@@ -45,17 +285,25 @@ impl CredibilityAnalyzer {
             (base_score as i32 - (unverified_count as i32 * 10)).max(0) as u32
         };
 
-        let checklist = Self::generate_checklist(script, &citations);
+        let brand_safety = BrandSafetyAnalyzer::analyze(script, &dictionaries.brand_safety);
+        let checklist = Self::generate_checklist(script, &citations, dictionaries, &brand_safety);
 
         CredibilityReport {
             score,
             claims,
             citations,
             checklist,
+            brand_safety,
+            citation_checks: Vec::new(),
         }
     }
 
-    fn generate_checklist(script: &VideoScript, citations: &[String]) -> Vec<ChecklistItem> {
+    fn generate_checklist(
+        script: &VideoScript,
+        citations: &[String],
+        dictionaries: &CredibilityDictionaries,
+        brand_safety: &[BrandSafetyMatch],
+    ) -> Vec<ChecklistItem> {
         let mut items = Vec::new();
 
         // 1. Citation Format Check
@@ -63,6 +311,7 @@ impl CredibilityAnalyzer {
             items.push(ChecklistItem {
                 passed: false,
                 category: "Citations".to_string(),
+                scene_index: None,
                 message: "No citations provided. Add sources to build trust.".to_string(),
             });
         } else {
@@ -74,6 +323,7 @@ impl CredibilityAnalyzer {
                     items.push(ChecklistItem {
                         passed: false,
                         category: "Citations".to_string(),
+                        scene_index: None,
                         message: format!(
                             "Citation '{}' is too vague. Use a URL or academic format.",
                             citation
@@ -84,60 +334,62 @@ impl CredibilityAnalyzer {
             items.push(ChecklistItem {
                 passed: true,
                 category: "Citations".to_string(),
+                scene_index: None,
                 message: format!("{} citations provided.", citations.len()),
             });
         }
 
         // 2. Weasel Word Detection
-        let weasel_regex = Regex::new(
-            r"(?i)\b(many people|some people|it is believed|studies show|experts agree)\b",
-        )
-        .unwrap();
+        let weasel_words = &dictionaries.weasel_words;
         let mut weasel_count = 0;
+        let mut first_weasel = None;
 
         for scene in &script.scenes {
             for layer in &scene.layers {
                 if let crate::script::Layer::Text { content, .. } = layer {
-                    if weasel_regex.is_match(content) {
+                    if let Some(found) = weasel_words.find_all(content).first() {
                         weasel_count += 1;
+                        first_weasel.get_or_insert(*found);
                     }
                 }
             }
         }
 
-        if weasel_count > 0 {
+        if let Some(example) = first_weasel {
             items.push(ChecklistItem {
                 passed: false,
                 category: "Clarity".to_string(),
+                scene_index: None,
                 message: format!(
-                    "Detected {} instance(s) of weasel words (e.g., 'many people'). Be specific.",
-                    weasel_count
+                    "Detected {} instance(s) of weasel words (e.g., '{}', {}). Be specific.",
+                    weasel_count,
+                    example,
+                    weasel_words.label()
                 ),
             });
         } else {
             items.push(ChecklistItem {
                 passed: true,
                 category: "Clarity".to_string(),
+                scene_index: None,
                 message: "No vague 'weasel words' detected.".to_string(),
             });
         }
 
         // 3. Tone Consistency (Hype Check)
-        let hype_regex = Regex::new(
-            r"(?i)\b(amazing|incredible|revolutionary|game-changing|miracle|best ever)\b",
-        )
-        .unwrap();
-        let mut hype_count = 0;
+        let hype_words = &dictionaries.hype_words;
+        let mut hype_matches = Vec::new();
         let mut total_words = 0;
 
         for scene in &script.scenes {
             for layer in &scene.layers {
                 if let crate::script::Layer::Text { content, .. } = layer {
-                    hype_count += hype_regex.find_iter(content).count();
+                    hype_matches.extend(hype_words.find_all(content));
                     total_words += content.split_whitespace().count();
                 }
             }
         }
+        let hype_count = hype_matches.len();
 
         if total_words > 0 {
             let hype_ratio = hype_count as f32 / total_words as f32;
@@ -146,30 +398,65 @@ impl CredibilityAnalyzer {
                 items.push(ChecklistItem {
                     passed: false,
                     category: "Tone".to_string(),
-                    message:
-                        "High hype factor detected. Tone down superlatives for better credibility."
-                            .to_string(),
+                    scene_index: None,
+                    message: format!(
+                        "High hype factor detected ({} of {} words, e.g. '{}', {}). \
+                         Tone down superlatives for better credibility.",
+                        hype_count,
+                        total_words,
+                        hype_matches[0],
+                        hype_words.label()
+                    ),
                 });
             } else {
                 items.push(ChecklistItem {
                     passed: true,
                     category: "Tone".to_string(),
+                    scene_index: None,
                     message: "Tone appears professional and balanced.".to_string(),
                 });
             }
         }
 
+        // 4. Brand Safety
+        if brand_safety.is_empty() {
+            items.push(ChecklistItem {
+                passed: true,
+                category: "Brand Safety".to_string(),
+                scene_index: None,
+                message: "No profanity, violence or medical claims detected.".to_string(),
+            });
+        }
+        for found in brand_safety {
+            let spelling = if found.obfuscated {
+                format!(" (spelled '{}')", found.word)
+            } else {
+                String::new()
+            };
+            items.push(ChecklistItem {
+                passed: false,
+                category: "Brand Safety".to_string(),
+                scene_index: found.scene_index,
+                message: format!(
+                    "{} '{}'{} in {}. Reword it, or add it to credibility.brand_safety.allow.",
+                    found.category.label(),
+                    found.text,
+                    spelling,
+                    found.location()
+                ),
+            });
+        }
+
         items
     }
 
-    fn detect_claims(script: &VideoScript) -> Vec<Claim> {
+    fn detect_claims(script: &VideoScript, dictionaries: &CredibilityDictionaries) -> Vec<Claim> {
         let mut claims = Vec::new();
 
         // Regex patterns for claim detection
         let stat_regex = Regex::new(r"\d+%|\d+ out of \d+|\d+x faster").unwrap();
-        let superlative_regex =
-            Regex::new(r"(?i)\b(best|fastest|first|only|proven|guaranteed)\b").unwrap();
-        let absolute_regex = Regex::new(r"(?i)\b(always|never|everyone|nobody)\b").unwrap();
+        let superlative_regex = &dictionaries.claim_superlatives;
+        let absolute_regex = &dictionaries.absolutes;
 
         for (i, scene) in script.scenes.iter().enumerate() {
             for layer in &scene.layers {
@@ -223,6 +510,7 @@ mod tests {
                 duration: 0.0,
                 description: None,
                 citations,
+                watermark: None,
             },
             scenes: vec![Scene {
                 id: "test".into(),
@@ -242,6 +530,8 @@ mod tests {
                     effects: vec![],
                 }],
                 transition: None,
+                narration: None,
+                title: None,
             }],
             audio: None,
         }
@@ -337,4 +627,141 @@ mod tests {
         assert!(!tone_check.passed);
         assert!(tone_check.message.contains("High hype factor"));
     }
+
+    #[test]
+    fn test_citation_checks_in_checklist() {
+        let status = |url: &str, status: Option<u16>| CitationStatus {
+            url: url.to_string(),
+            status,
+            final_url: None,
+            error: status.is_none().then(|| "timed out".to_string()),
+        };
+        let mut report = CredibilityAnalyzer::analyze(&create_test_script(vec![], "Text"));
+        let before = report.checklist.len();
+        report.add_citation_checks(vec![
+            status("https://example.com/ok", Some(200)),
+            status("https://example.com/old-study", Some(404)),
+            status("https://example.com/slow", None),
+        ]);
+
+        let added = &report.checklist[before..];
+        assert_eq!(added.len(), 2);
+        assert!(added.iter().all(|i| !i.passed && i.category == "Citations"));
+        assert!(added[0]
+            .message
+            .contains("old-study' is unreachable (HTTP 404)"));
+        assert!(added[1].message.contains("(timed out)"));
+        assert_eq!(report.citation_checks.len(), 3);
+    }
+
+    #[test]
+    fn test_checklist_brand_safety() {
+        let clean = CredibilityAnalyzer::analyze(&create_test_script(vec![], "Plain words"));
+        let item = clean
+            .checklist
+            .iter()
+            .find(|i| i.category == "Brand Safety")
+            .unwrap();
+        assert!(item.passed);
+
+        let report =
+            CredibilityAnalyzer::analyze(&create_test_script(vec![], "This detox is a sc4m, sh1t"));
+        assert_eq!(report.brand_safety.len(), 2);
+        let failed: Vec<&ChecklistItem> = report
+            .checklist
+            .iter()
+            .filter(|i| i.category == "Brand Safety")
+            .collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed.iter().all(|i| !i.passed && i.scene_index == Some(0)));
+        assert_eq!(
+            failed[1].message,
+            "Profanity 'sh1t' (spelled 'shit') in scene 1 text. Reword it, or add it to credibility.brand_safety.allow."
+        );
+    }
+
+    fn dictionaries_with(weasel_words: &[&str], hype_words: &[&str]) -> CredibilityDictionaries {
+        let words = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        CredibilityDictionaries {
+            weasel_words: Dictionary::new("weasel_words", "config", words(weasel_words)).unwrap(),
+            hype_words: Dictionary::new("hype_words", "config", words(hype_words)).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_custom_weasel_words() {
+        let dictionaries = dictionaries_with(&["muitas pessoas dizem"], &[]);
+        let script = create_test_script(vec![], "Muitas  pessoas dizem que Rust é difícil.");
+        let report = CredibilityAnalyzer::analyze_with_dictionaries(&script, &dictionaries);
+        let clarity_check = report
+            .checklist
+            .iter()
+            .find(|i| i.category == "Clarity")
+            .unwrap();
+        assert!(!clarity_check.passed);
+        // The match as written, and the list it came from
+        assert!(clarity_check.message.contains("'Muitas  pessoas dizem'"));
+        assert!(clarity_check.message.contains("weasel_words from config"));
+
+        // The custom list replaces the English one
+        let script = create_test_script(vec![], "Many people say this is good.");
+        let report = CredibilityAnalyzer::analyze_with_dictionaries(&script, &dictionaries);
+        assert!(report
+            .checklist
+            .iter()
+            .all(|i| i.category != "Clarity" || i.passed));
+    }
+
+    #[test]
+    fn test_default_dictionaries() {
+        let dictionaries = CredibilityDictionaries::default();
+        assert_eq!(dictionaries.weasel_words.origin, "default");
+        assert_eq!(dictionaries.absolutes.words.len(), DEFAULT_ABSOLUTES.len());
+
+        let script = create_test_script(vec![], "Many people say this is good.");
+        let report = CredibilityAnalyzer::analyze(&script);
+        let clarity_check = report
+            .checklist
+            .iter()
+            .find(|i| i.category == "Clarity")
+            .unwrap();
+        assert!(clarity_check.message.contains("'Many people'"));
+        assert!(clarity_check.message.contains("default weasel_words"));
+    }
+
+    #[test]
+    fn test_unicode_word_boundaries() {
+        let dictionaries = dictionaries_with(&[], &["incrível"]);
+        let hype = &dictionaries.hype_words;
+        assert_eq!(hype.find_all("Um resultado INCRÍVEL!"), vec!["INCRÍVEL"]);
+        // Not inside a longer word
+        assert!(!hype.is_match("incrívelmente rápido"));
+        assert!(!hype.is_match("nãoincrível"));
+
+        let script = create_test_script(vec![], "Incrível, incrível, incrível produto");
+        let report = CredibilityAnalyzer::analyze_with_dictionaries(&script, &dictionaries);
+        let tone_check = report
+            .checklist
+            .iter()
+            .find(|i| i.category == "Tone")
+            .unwrap();
+        assert!(!tone_check.passed);
+        assert!(tone_check.message.contains("3 of 4 words"));
+        assert!(tone_check.message.contains("hype_words from config"));
+    }
+
+    #[test]
+    fn test_word_pattern_boundaries() {
+        // Symbols at the ends can't take a word boundary
+        let dictionary =
+            Dictionary::new("absolutes", "config", vec!["#1".into(), "100%".into()]).unwrap();
+        assert!(dictionary.is_match("We are #1 in sales"));
+        assert!(dictionary.is_match("100% sure"));
+        assert!(!dictionary.is_match("2100% sure"));
+        // An empty list matches nothing
+        assert!(!Dictionary::new("absolutes", "config", vec![" ".into()])
+            .unwrap()
+            .is_match("anything"));
+    }
 }
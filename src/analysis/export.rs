@@ -0,0 +1,374 @@
+//! Analysis findings in the formats CI systems annotate pull requests with:
+//! SARIF 2.1.0 for code scanning and JUnit XML for test reports.
+
+use crate::analysis::narrative::Severity;
+use crate::analysis::report::{AnalysisReport, Issue};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+
+/// Schema the SARIF log declares
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF `level` of a severity
+pub fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+/// `narrative/pacing-too-fast` → `Narrative: pacing too fast`
+fn rule_description(rule: &str) -> String {
+    let (source, check) = rule.split_once('/').unwrap_or(("", rule));
+    let mut source_chars = source.chars();
+    let source: String = source_chars
+        .next()
+        .map(|first| first.to_uppercase().chain(source_chars).collect())
+        .unwrap_or_default();
+    format!("{}: {}", source, check.replace('-', " "))
+}
+
+/// Rules of the report's findings, each once, in order of first appearance
+fn rules(report: &AnalysisReport) -> Vec<&str> {
+    let mut rules: Vec<&str> = Vec::new();
+    for issue in &report.issues {
+        if !rules.contains(&issue.rule.as_str()) {
+            rules.push(&issue.rule);
+        }
+    }
+    rules
+}
+
+/// A SARIF 2.1.0 log with one result per finding. Findings in a scene point at it
+/// with a logical location (`scenes/<id>`), since scripts have no line numbers
+/// yet; the script file is the physical location when the report names it.
+pub fn sarif(report: &AnalysisReport) -> Value {
+    let rules = rules(report);
+    let results: Vec<Value> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            let mut location = serde_json::Map::new();
+            if let Some(path) = &report.script_path {
+                location.insert(
+                    "physicalLocation".into(),
+                    json!({ "artifactLocation": { "uri": path } }),
+                );
+            }
+            if let Some(id) = &issue.scene_id {
+                location.insert(
+                    "logicalLocations".into(),
+                    json!([{
+                        "name": id,
+                        "fullyQualifiedName": format!("scenes/{}", id),
+                        "kind": "object"
+                    }]),
+                );
+            }
+
+            let mut result = json!({
+                "ruleId": issue.rule,
+                "ruleIndex": rules.iter().position(|rule| *rule == issue.rule),
+                "level": sarif_level(issue.severity),
+                "message": { "text": issue.message },
+            });
+            if !location.is_empty() {
+                result["locations"] = json!([location]);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|rule| json!({
+                        "id": rule,
+                        "shortDescription": { "text": rule_description(rule) },
+                    })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// A JUnit XML report with one test suite per analysis and one test case per
+/// finding. Errors and warnings are failures; notes pass.
+pub fn junit(report: &AnalysisReport) -> String {
+    let failures = |issues: &[&Issue]| {
+        issues
+            .iter()
+            .filter(|issue| issue.severity >= Severity::Warning)
+            .count()
+    };
+    let all: Vec<&Issue> = report.issues.iter().collect();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">",
+        escape_xml(&report.title),
+        all.len(),
+        failures(&all)
+    );
+    for source in &report.analyzers {
+        let issues: Vec<&Issue> = all
+            .iter()
+            .copied()
+            .filter(|issue| issue.source == *source)
+            .collect();
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            source,
+            issues.len(),
+            failures(&issues)
+        );
+        for issue in issues {
+            let name = match &issue.scene_id {
+                Some(id) => format!("{} (scene {})", issue.rule, id),
+                None => issue.rule.clone(),
+            };
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape_xml(&name),
+                escape_xml(&issue.rule.replace('/', "."))
+            );
+            let message = escape_xml(&issue.message);
+            if issue.severity >= Severity::Warning {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>",
+                    sarif_level(issue.severity),
+                    message,
+                    message
+                );
+            } else {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <system-out>{}</system-out>\n    </testcase>",
+                    message
+                );
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Escape text for XML attributes and content
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyzer::{AnalysisConfig, ExtraAnalyzers};
+    use crate::analysis::report::ReportFormat;
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+    use std::path::Path;
+    use xml::reader::{EventReader, XmlEvent};
+
+    /// Errors, warnings and scene-level notes: the template without its Payoff
+    fn report() -> AnalysisReport {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(120.0));
+        script.scenes.pop();
+        let report =
+            AnalysisReport::analyze(&script, &AnalysisConfig::default(), &ExtraAnalyzers::new())
+                .with_script_path(Path::new("demo.json"));
+        assert!(report.has_issues(Severity::Error));
+        assert!(report.issues.iter().any(|issue| issue.scene_id.is_some()));
+        report
+    }
+
+    fn string<'a>(value: &'a Value, what: &str) -> &'a str {
+        value
+            .as_str()
+            .unwrap_or_else(|| panic!("{} must be a string: {}", what, value))
+    }
+
+    fn array<'a>(value: &'a Value, what: &str) -> &'a Vec<Value> {
+        value
+            .as_array()
+            .unwrap_or_else(|| panic!("{} must be an array: {}", what, value))
+    }
+
+    /// The parts of the SARIF 2.1.0 schema a log must satisfy: required properties,
+    /// their types and the `level` enum
+    fn check_sarif_schema(log: &Value) {
+        assert_eq!(log["version"], "2.1.0");
+        string(&log["$schema"], "$schema");
+        for run in array(&log["runs"], "runs") {
+            let driver = &run["tool"]["driver"];
+            string(&driver["name"], "tool.driver.name");
+            let rules = array(&driver["rules"], "tool.driver.rules");
+            for rule in rules {
+                string(&rule["id"], "rule.id");
+                string(
+                    &rule["shortDescription"]["text"],
+                    "rule.shortDescription.text",
+                );
+            }
+            for result in array(&run["results"], "results") {
+                string(&result["message"]["text"], "result.message.text");
+                let level = string(&result["level"], "result.level");
+                assert!(["none", "note", "warning", "error"].contains(&level));
+                let index = result["ruleIndex"].as_u64().unwrap() as usize;
+                assert_eq!(rules[index]["id"], result["ruleId"]);
+                for location in array(&result["locations"], "result.locations") {
+                    string(
+                        &location["physicalLocation"]["artifactLocation"]["uri"],
+                        "artifactLocation.uri",
+                    );
+                    if let Some(logical) = location.get("logicalLocations") {
+                        for logical in array(logical, "logicalLocations") {
+                            string(&logical["name"], "logicalLocation.name");
+                            string(
+                                &logical["fullyQualifiedName"],
+                                "logicalLocation.fullyQualifiedName",
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sarif_round_trip() {
+        let report = report();
+        let log: Value =
+            serde_json::from_str(&report.render(ReportFormat::Sarif).unwrap()).unwrap();
+        check_sarif_schema(&log);
+
+        let results = array(&log["runs"][0]["results"], "results");
+        assert_eq!(results.len(), report.issues.len());
+        for (result, issue) in results.iter().zip(&report.issues) {
+            assert_eq!(result["ruleId"], issue.rule.as_str());
+            assert_eq!(result["level"], sarif_level(issue.severity));
+            let logical = &result["locations"][0]["logicalLocations"][0];
+            match &issue.scene_id {
+                Some(id) => assert_eq!(logical["fullyQualifiedName"], format!("scenes/{}", id)),
+                None => assert!(logical.is_null()),
+            }
+        }
+        let errors = results.iter().filter(|r| r["level"] == "error").count();
+        let expected = report
+            .issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error);
+        assert_eq!(errors, expected.count());
+        assert_eq!(results[0]["ruleId"], "narrative/missing-scene");
+    }
+
+    /// Check the JUnit XML against a minimal schema (testsuites > testsuite >
+    /// testcase > failure or system-out, with their required attributes and
+    /// counts) and return (tests, failures) as counted from the elements
+    fn check_junit_schema(xml: &str) -> (usize, usize) {
+        let required = |name: &str| -> &[&str] {
+            match name {
+                "testsuites" | "testsuite" => &["name", "tests", "failures"],
+                "testcase" => &["name", "classname"],
+                "failure" => &["type", "message"],
+                "system-out" => &[],
+                other => panic!("Unexpected element <{}>", other),
+            }
+        };
+        let parents = |name: &str| match name {
+            "testsuites" => None,
+            "testsuite" => Some("testsuites"),
+            "testcase" => Some("testsuite"),
+            _ => Some("testcase"),
+        };
+
+        let mut stack: Vec<(String, usize, usize, usize, usize)> = Vec::new();
+        let (mut total_tests, mut total_failures) = (0, 0);
+        for event in EventReader::from_str(xml) {
+            match event.unwrap() {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let name = name.local_name;
+                    assert_eq!(parents(&name), stack.last().map(|e| e.0.as_str()));
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == key)
+                            .map(|a| a.value.clone())
+                    };
+                    for key in required(&name) {
+                        assert!(attribute(key).is_some(), "<{}> needs {}", name, key);
+                    }
+                    let count = |key| attribute(key).map_or(0, |v| v.parse().unwrap());
+                    match name.as_str() {
+                        "testcase" => stack.iter_mut().for_each(|e| e.3 += 1),
+                        "failure" => stack.iter_mut().for_each(|e| e.4 += 1),
+                        _ => {}
+                    }
+                    stack.push((name.clone(), count("tests"), count("failures"), 0, 0));
+                }
+                XmlEvent::EndElement { .. } => {
+                    let (name, tests, failures, counted_tests, counted_failures) =
+                        stack.pop().unwrap();
+                    if name == "testsuites" || name == "testsuite" {
+                        assert_eq!(tests, counted_tests, "<{}> tests", name);
+                        assert_eq!(failures, counted_failures, "<{}> failures", name);
+                    }
+                    if name == "testsuites" {
+                        (total_tests, total_failures) = (counted_tests, counted_failures);
+                    }
+                }
+                _ => {}
+            }
+        }
+        (total_tests, total_failures)
+    }
+
+    #[test]
+    fn test_junit_round_trip() {
+        let report = report();
+        let xml = report.render(ReportFormat::Junit).unwrap();
+        let (tests, failures) = check_junit_schema(&xml);
+
+        assert_eq!(tests, report.issues.len());
+        let expected = report
+            .issues
+            .iter()
+            .filter(|i| i.severity >= Severity::Warning);
+        assert_eq!(failures, expected.count());
+        assert!(
+            xml.contains("<failure type=\"error\" message=\"Missing &apos;Payoff&apos; scene\">")
+        );
+    }
+
+    #[test]
+    fn test_rule_description() {
+        assert_eq!(
+            rule_description("narrative/pacing-too-fast"),
+            "Narrative: pacing too fast"
+        );
+        assert_eq!(escape_xml("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,330 @@
+//! How strongly the opening scene grabs attention: the signals in its on-screen
+//! text and narration that keep viewers past the first seconds.
+
+use crate::analysis::narrative::{Severity, StructureRecommendation};
+use crate::script::{Layer, Scene, SceneType, VideoScript};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Phrases that promise something the viewer only gets by watching on
+const CURIOSITY_GAPS: &[&str] = &[
+    "the one thing",
+    "what nobody tells you",
+    "no one tells you",
+    "nobody talks about",
+    "the secret",
+    "the truth about",
+    "the real reason",
+    "you won't believe",
+    "here's why",
+    "this is why",
+    "what happens when",
+    "most people don't",
+];
+
+/// Words that name what the viewer gets, when they open the hook
+const PAYOFF_WORDS: &[&str] = &[
+    "how", "why", "learn", "save", "get", "build", "make", "fix", "avoid", "stop", "double",
+    "faster", "free",
+];
+
+/// Words into the hook a payoff word must appear to count as front-loaded
+const PAYOFF_WINDOW: usize = 8;
+
+/// Below this hook score the suggestions are added to the structure recommendations
+const WEAK_HOOK_SCORE: u32 = 60;
+
+/// How much the hook counts in the narrative score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookSettings {
+    /// Share of the hook score in the narrative score (0-1)
+    #[serde(default = "default_hook_weight")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub weight: f32,
+}
+
+fn default_hook_weight() -> f32 {
+    0.2
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self {
+            weight: default_hook_weight(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookReport {
+    /// The first Hook scene, or the first scene when there is none
+    pub scene_index: usize,
+    /// 0-100, from the signals below
+    pub score: u32,
+    pub question: bool,
+    /// A digit, e.g. a statistic or a count
+    pub number: bool,
+    /// Speaks to the viewer ("you")
+    pub second_person: bool,
+    pub curiosity_gap: bool,
+    /// Says what the viewer gets within the first few words
+    pub front_loaded_payoff: bool,
+    /// Ways to strengthen the hook; reported with the structure recommendations
+    #[serde(skip)]
+    pub recommendations: Vec<StructureRecommendation>,
+}
+
+pub struct HookAnalyzer;
+
+impl HookAnalyzer {
+    /// `None` for a script without scenes
+    pub fn analyze(script: &VideoScript) -> Option<HookReport> {
+        let scene_index = script
+            .scenes
+            .iter()
+            .position(|scene| scene.scene_type == SceneType::Hook)
+            .unwrap_or(0);
+        let scene = script.scenes.get(scene_index)?;
+        let text = Self::text(scene);
+        let mut report = Self::score_text(scene_index, &text);
+
+        let has_text = scene
+            .layers
+            .iter()
+            .any(|layer| matches!(layer, Layer::Text { .. }));
+        if !has_text && scene.narration.is_none() {
+            report.recommendations = vec![recommendation(
+                Severity::Warning,
+                format!(
+                    "Scene {} opens the video without any text or narration. Say what the video is about in the first seconds.",
+                    scene_index + 1
+                ),
+            )];
+        }
+        Some(report)
+    }
+
+    /// On-screen text and narration, in that order
+    fn text(scene: &Scene) -> String {
+        let mut parts: Vec<&str> = scene
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        parts.extend(scene.narration.as_deref());
+        parts.join("\n")
+    }
+
+    fn score_text(scene_index: usize, text: &str) -> HookReport {
+        let words: Vec<String> = text.unicode_words().map(str::to_lowercase).collect();
+        let normalized = words.join(" ");
+
+        let question = text.contains(['?', '¿']);
+        let number = text.chars().any(|c| c.is_ascii_digit());
+        let second_person = words.iter().any(|word| {
+            matches!(
+                word.as_str(),
+                "you" | "your" | "you're" | "yours" | "yourself" | "you'll" | "you've"
+            )
+        });
+        // Compared word by word, so punctuation and line breaks don't matter
+        let curiosity_gap = CURIOSITY_GAPS.iter().any(|pattern| {
+            let pattern: Vec<&str> = pattern.unicode_words().collect();
+            format!(" {} ", normalized).contains(&format!(" {} ", pattern.join(" ")))
+        });
+        let front_loaded_payoff = words
+            .iter()
+            .take(PAYOFF_WINDOW)
+            .any(|word| PAYOFF_WORDS.contains(&word.as_str()));
+
+        let score = [
+            (question, 25),
+            (number, 20),
+            (second_person, 20),
+            (curiosity_gap, 20),
+            (front_loaded_payoff, 15),
+        ]
+        .iter()
+        .filter(|(found, _)| *found)
+        .map(|(_, points)| points)
+        .sum();
+
+        let mut recommendations = Vec::new();
+        if score < WEAK_HOOK_SCORE {
+            let suggestions = [
+                (
+                    question || number,
+                    "Start with a question or a concrete number",
+                ),
+                (second_person, "Speak to the viewer directly (\"you\")"),
+                (
+                    curiosity_gap,
+                    "Open a curiosity gap, e.g. \"the one thing...\" or \"what nobody tells you\"",
+                ),
+                (
+                    front_loaded_payoff,
+                    "Say what the viewer gets within the first few words",
+                ),
+            ];
+            for (_, suggestion) in suggestions.iter().filter(|(found, _)| !found) {
+                recommendations.push(recommendation(
+                    Severity::Info,
+                    format!(
+                        "Weak hook in scene {} (score {}/100). {}.",
+                        scene_index + 1,
+                        score,
+                        suggestion
+                    ),
+                ));
+            }
+        }
+
+        HookReport {
+            scene_index,
+            score,
+            question,
+            number,
+            second_person,
+            curiosity_gap,
+            front_loaded_payoff,
+            recommendations,
+        }
+    }
+}
+
+fn recommendation(severity: Severity, message: String) -> StructureRecommendation {
+    StructureRecommendation {
+        severity,
+        category: "Hook".to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution};
+
+    fn text_layer(content: &str) -> Layer {
+        Layer::Text {
+            content: content.into(),
+            font: "font.ttf".into(),
+            font_size: 24.0,
+            color: Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+        }
+    }
+
+    fn create_script(layers: Vec<Layer>, narration: Option<&str>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "hook".into(),
+                scene_type: SceneType::Hook,
+                duration: 3.0,
+                layers,
+                transition: None,
+                narration: narration.map(str::to_string),
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_strong_hook() {
+        let script = create_script(
+            vec![text_layer("Why do 90% of videos lose you in 3 seconds?")],
+            Some("Here's the one thing nobody checks."),
+        );
+        let report = HookAnalyzer::analyze(&script).unwrap();
+
+        assert!(report.question);
+        assert!(report.number);
+        assert!(report.second_person);
+        assert!(report.curiosity_gap);
+        assert!(report.front_loaded_payoff);
+        assert_eq!(report.score, 100);
+        assert!(report.recommendations.is_empty());
+    }
+
+    #[test]
+    fn test_weak_hook_gets_suggestions() {
+        let script = create_script(
+            vec![text_layer("Welcome to the channel. Today: gardening.")],
+            None,
+        );
+        let report = HookAnalyzer::analyze(&script).unwrap();
+
+        assert_eq!(report.score, 0);
+        assert!(!report.question && !report.number && !report.second_person);
+        assert_eq!(report.recommendations.len(), 4);
+        assert!(report
+            .recommendations
+            .iter()
+            .all(|r| r.category == "Hook" && r.severity == Severity::Info));
+        assert!(report.recommendations[0]
+            .message
+            .contains("Start with a question or a concrete number"));
+    }
+
+    #[test]
+    fn test_detectors_fire_individually() {
+        let analyze = |text: &str| {
+            HookAnalyzer::analyze(&create_script(vec![text_layer(text)], None)).unwrap()
+        };
+
+        assert!(analyze("Ready?").question);
+        assert!(analyze("In 2024 it changed").number);
+        assert!(analyze("YOUR budget is lying").second_person);
+        // Across punctuation and line breaks
+        assert!(analyze("This is\nthe secret, finally").curiosity_gap);
+        assert!(!analyze("The secretary spoke").curiosity_gap);
+        // Only near the start
+        assert!(analyze("Stop wasting mornings").front_loaded_payoff);
+        assert!(
+            !analyze("One two three four five six seven eight and then you learn")
+                .front_loaded_payoff
+        );
+    }
+
+    #[test]
+    fn test_hook_without_text_or_narration() {
+        let report = HookAnalyzer::analyze(&create_script(vec![], None)).unwrap();
+        assert_eq!(report.score, 0);
+        assert_eq!(report.recommendations.len(), 1);
+        assert_eq!(report.recommendations[0].severity, Severity::Warning);
+        assert!(report.recommendations[0]
+            .message
+            .contains("without any text or narration"));
+
+        // Narration alone is enough text
+        let narrated = HookAnalyzer::analyze(&create_script(vec![], Some("Ready?"))).unwrap();
+        assert!(narrated.question);
+        assert!(narrated
+            .recommendations
+            .iter()
+            .all(|r| r.severity == Severity::Info));
+
+        let mut empty = create_script(vec![], None);
+        empty.scenes.clear();
+        assert!(HookAnalyzer::analyze(&empty).is_none());
+    }
+}
@@ -0,0 +1,382 @@
+//! Checks of the title and description that go with the upload: length limits of
+//! search results, clickbait signals, and whether they match the video.
+
+use crate::analysis::narrative::Severity;
+use crate::script::{Layer, SceneType, VideoScript};
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters of a title search results show before truncating it
+pub const MAX_TITLE_LENGTH: usize = 70;
+
+/// Characters a description needs to say what the video is about
+pub const MIN_DESCRIPTION_LENGTH: usize = 100;
+
+/// Exclamation marks a title may have before it reads as clickbait
+pub const MAX_EXCLAMATION_MARKS: usize = 1;
+
+/// Letters an all-caps title needs before it is flagged, so acronyms like "NASA" pass
+const MIN_CAPS_LETTERS: usize = 8;
+
+/// Title words shorter than this aren't keywords
+const MIN_KEYWORD_LENGTH: usize = 4;
+
+/// Words too common to count as keywords
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "been", "before", "best", "from", "have", "here", "into", "just",
+    "more", "most", "only", "over", "should", "than", "that", "their", "them", "then", "there",
+    "these", "they", "this", "what", "when", "where", "which", "while", "will", "with", "your",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MetadataCheck {
+    TitleTooLong,
+    TitleAllCaps,
+    TitleExclamationMarks,
+    MissingDescription,
+    ShortDescription,
+    /// Citations exist but the description links none
+    DescriptionWithoutSources,
+    /// The title and the Hook scene's on-screen text share no keyword
+    TitleHookMismatch,
+}
+
+impl MetadataCheck {
+    /// Rule id within the `metadata` analysis
+    pub fn rule(self) -> &'static str {
+        match self {
+            MetadataCheck::TitleTooLong => "title-too-long",
+            MetadataCheck::TitleAllCaps => "title-all-caps",
+            MetadataCheck::TitleExclamationMarks => "title-exclamation-marks",
+            MetadataCheck::MissingDescription => "missing-description",
+            MetadataCheck::ShortDescription => "short-description",
+            MetadataCheck::DescriptionWithoutSources => "description-without-sources",
+            MetadataCheck::TitleHookMismatch => "title-hook-mismatch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataFinding {
+    pub check: MetadataCheck,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetadataReport {
+    /// In characters
+    pub title_length: usize,
+    /// In characters, `None` without a description
+    pub description_length: Option<usize>,
+    /// Title keywords the Hook scene's on-screen text repeats
+    pub hook_keywords: Vec<String>,
+    pub findings: Vec<MetadataFinding>,
+}
+
+pub struct MetadataAnalyzer;
+
+impl MetadataAnalyzer {
+    pub fn analyze(script: &VideoScript) -> MetadataReport {
+        let title = script.metadata.title.trim();
+        let description = script
+            .metadata
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty());
+        let title_length = title.chars().count();
+        let mut findings = Vec::new();
+        let mut flag = |check, severity, message| {
+            findings.push(MetadataFinding {
+                check,
+                severity,
+                message,
+            })
+        };
+
+        if title_length > MAX_TITLE_LENGTH {
+            flag(
+                MetadataCheck::TitleTooLong,
+                Severity::Warning,
+                format!(
+                    "The title is {} characters long; search results cut it off after {}. Put the keywords first or shorten it.",
+                    title_length, MAX_TITLE_LENGTH
+                ),
+            );
+        }
+
+        let letters: Vec<char> = title.chars().filter(|c| c.is_alphabetic()).collect();
+        let cased = letters
+            .iter()
+            .filter(|c| c.is_uppercase() || c.is_lowercase());
+        if cased.clone().count() >= MIN_CAPS_LETTERS && cased.clone().all(|c| c.is_uppercase()) {
+            flag(
+                MetadataCheck::TitleAllCaps,
+                Severity::Warning,
+                "The title is in ALL CAPS, which reads as clickbait. Use title or sentence case."
+                    .to_string(),
+            );
+        }
+
+        let exclamation_marks = title.matches('!').count();
+        if exclamation_marks > MAX_EXCLAMATION_MARKS {
+            flag(
+                MetadataCheck::TitleExclamationMarks,
+                Severity::Warning,
+                format!(
+                    "The title has {} exclamation marks, which reads as clickbait. Keep at most {}.",
+                    exclamation_marks, MAX_EXCLAMATION_MARKS
+                ),
+            );
+        }
+
+        let description_length = description.map(|description| description.chars().count());
+        match description_length {
+            None => flag(
+                MetadataCheck::MissingDescription,
+                Severity::Warning,
+                "The script has no description. Say what the video covers, for search and for viewers deciding to click.".to_string(),
+            ),
+            Some(length) if length < MIN_DESCRIPTION_LENGTH => flag(
+                MetadataCheck::ShortDescription,
+                Severity::Warning,
+                format!(
+                    "The description is only {} characters long. Write at least {} so it says what the video covers.",
+                    length, MIN_DESCRIPTION_LENGTH
+                ),
+            ),
+            _ => {}
+        }
+
+        let has_url = description.is_some_and(|description| {
+            ["http://", "https://", "www."]
+                .iter()
+                .any(|prefix| description.contains(prefix))
+        });
+        if description.is_some() && !script.metadata.citations.is_empty() && !has_url {
+            flag(
+                MetadataCheck::DescriptionWithoutSources,
+                Severity::Info,
+                format!(
+                    "The script cites {} source(s) but the description links none. Paste the sources' URLs into the description.",
+                    script.metadata.citations.len()
+                ),
+            );
+        }
+
+        let hook_text = Self::hook_text(script);
+        let title_keywords = keywords(title);
+        let hook_words: Vec<String> = keywords(&hook_text);
+        let hook_keywords: Vec<String> = title_keywords
+            .iter()
+            .filter(|keyword| hook_words.contains(keyword))
+            .cloned()
+            .collect();
+        // Without Hook text or title keywords there is nothing to compare
+        if !hook_words.is_empty() && !title_keywords.is_empty() && hook_keywords.is_empty() {
+            flag(
+                MetadataCheck::TitleHookMismatch,
+                Severity::Info,
+                format!(
+                    "The Hook's on-screen text repeats none of the title's keywords ({}). Echo one so viewers know they clicked the right video.",
+                    title_keywords.join(", ")
+                ),
+            );
+        }
+
+        MetadataReport {
+            title_length,
+            description_length,
+            hook_keywords,
+            findings,
+        }
+    }
+
+    /// Text layers of every Hook scene
+    fn hook_text(script: &VideoScript) -> String {
+        script
+            .scenes
+            .iter()
+            .filter(|scene| scene.scene_type == SceneType::Hook)
+            .flat_map(|scene| &scene.layers)
+            .filter_map(|layer| match layer {
+                Layer::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Distinct lowercase words of `text` that aren't stop words or short, with a
+/// plural `s` dropped so "Videos" matches "video"
+fn keywords(text: &str) -> Vec<String> {
+    let mut keywords = Vec::new();
+    for word in text.unicode_words() {
+        let word = word.to_lowercase();
+        if word.chars().count() < MIN_KEYWORD_LENGTH || STOP_WORDS.contains(&word.as_str()) {
+            continue;
+        }
+        let word = match word.strip_suffix('s') {
+            Some(stem) if !stem.ends_with('s') => stem.to_string(),
+            _ => word,
+        };
+        if !keywords.contains(&word) {
+            keywords.push(word);
+        }
+    }
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution, Scene};
+
+    const DESCRIPTION: &str = "How the render pipeline turns a JSON script into a finished \
+        video, step by step, with sources at https://example.com/sources";
+
+    fn create_script(title: &str, description: Option<&str>, hook_text: &str) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: title.into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: description.map(str::to_string),
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "hook".into(),
+                scene_type: SceneType::Hook,
+                duration: 3.0,
+                layers: vec![Layer::Text {
+                    content: hook_text.into(),
+                    font: "font.ttf".into(),
+                    font_size: 48.0,
+                    color: Color {
+                        r: 255,
+                        g: 255,
+                        b: 255,
+                        a: 255,
+                    },
+                    position: Position { x: 0, y: 0 },
+                    effects: vec![],
+                }],
+                transition: None,
+                narration: None,
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    fn checks(script: &VideoScript) -> Vec<MetadataCheck> {
+        MetadataAnalyzer::analyze(script)
+            .findings
+            .iter()
+            .map(|finding| finding.check)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_metadata() {
+        let script = create_script(
+            "Rendering Videos from JSON",
+            Some(DESCRIPTION),
+            "Your video, rendered from JSON",
+        );
+        let report = MetadataAnalyzer::analyze(&script);
+        assert!(report.findings.is_empty(), "{:?}", report.findings);
+        assert_eq!(report.title_length, 26);
+        assert_eq!(report.hook_keywords, vec!["video", "json"]);
+    }
+
+    #[test]
+    fn test_title_too_long() {
+        let title = "a".repeat(MAX_TITLE_LENGTH);
+        let script = create_script(&title, Some(DESCRIPTION), "");
+        assert!(checks(&script).is_empty());
+
+        let script = create_script(&format!("{}é", title), Some(DESCRIPTION), "");
+        assert_eq!(checks(&script), vec![MetadataCheck::TitleTooLong]);
+    }
+
+    #[test]
+    fn test_title_all_caps() {
+        let script = create_script("YOU WON'T BELIEVE THIS", Some(DESCRIPTION), "");
+        assert_eq!(checks(&script), vec![MetadataCheck::TitleAllCaps]);
+
+        // Acronyms and short shouts pass
+        let script = create_script("How NASA Uses Rust", Some(DESCRIPTION), "");
+        assert!(checks(&script).is_empty());
+        let script = create_script("WOW 2024", Some(DESCRIPTION), "");
+        assert!(checks(&script).is_empty());
+    }
+
+    #[test]
+    fn test_title_exclamation_marks() {
+        let script = create_script("It works!", Some(DESCRIPTION), "");
+        assert!(checks(&script).is_empty());
+
+        let script = create_script("It works!!", Some(DESCRIPTION), "");
+        assert_eq!(checks(&script), vec![MetadataCheck::TitleExclamationMarks]);
+    }
+
+    #[test]
+    fn test_missing_and_short_description() {
+        assert_eq!(
+            checks(&create_script("Title", None, "")),
+            vec![MetadataCheck::MissingDescription]
+        );
+        assert_eq!(
+            checks(&create_script("Title", Some("   "), "")),
+            vec![MetadataCheck::MissingDescription]
+        );
+
+        let report = MetadataAnalyzer::analyze(&create_script("Title", Some("Too short."), ""));
+        assert_eq!(report.description_length, Some(10));
+        assert_eq!(report.findings[0].check, MetadataCheck::ShortDescription);
+        assert_eq!(report.findings[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_description_without_sources() {
+        let long = "x".repeat(MIN_DESCRIPTION_LENGTH);
+        let mut script = create_script("Title", Some(&long), "");
+        // No citations, nothing to link
+        assert!(checks(&script).is_empty());
+
+        script.metadata.citations = vec!["[Smith, 2020]".into()];
+        let report = MetadataAnalyzer::analyze(&script);
+        assert_eq!(
+            report.findings[0].check,
+            MetadataCheck::DescriptionWithoutSources
+        );
+        assert_eq!(report.findings[0].severity, Severity::Info);
+
+        script.metadata.description = Some(format!("{} www.example.com", long));
+        assert!(checks(&script).is_empty());
+    }
+
+    #[test]
+    fn test_title_hook_keyword_mismatch() {
+        let script = create_script(
+            "Rendering Videos from JSON",
+            Some(DESCRIPTION),
+            "Welcome back, everyone",
+        );
+        let report = MetadataAnalyzer::analyze(&script);
+        assert_eq!(report.findings[0].check, MetadataCheck::TitleHookMismatch);
+        assert_eq!(report.findings[0].severity, Severity::Info);
+        assert!(report.findings[0]
+            .message
+            .contains("(rendering, video, json)"));
+
+        // Without Hook text there is nothing to compare
+        let script = create_script("Rendering Videos from JSON", Some(DESCRIPTION), "");
+        assert!(checks(&script).is_empty());
+    }
+}
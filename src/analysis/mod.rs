@@ -1,3 +1,23 @@
+pub mod accessibility;
+pub mod analyzer;
+pub mod asset_audit;
+pub mod audio_timing;
+pub mod brand_safety;
+pub mod citations;
 pub mod credibility;
+pub mod export;
+pub mod hook;
+pub mod metadata;
 pub mod narrative;
+pub mod readability;
+pub mod repetition;
+pub mod report;
 pub mod retention;
+pub mod sentiment;
+pub mod speech;
+
+/// Write an `f32` setting as the shortest decimal that reads back as it, so
+/// `0.2` doesn't come out as `0.20000000298023224`
+pub fn serialize_f32<S: serde::Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(value.to_string().parse().unwrap_or(f64::from(*value)))
+}
@@ -1,4 +1,13 @@
-use crate::script::{Scene, SceneType, VideoScript};
+use crate::analysis::analyzer::{
+    AnalysisConfig, Analyzer, AnalyzerFindings, Finding, ScoreContribution,
+};
+use crate::analysis::hook::{HookAnalyzer, HookReport, HookSettings};
+use crate::analysis::readability::{ReadabilityAnalyzer, ReadabilityReport, ReadabilitySettings};
+use crate::analysis::repetition::{RepetitionAnalyzer, RepetitionReport, RepetitionSettings};
+use crate::analysis::report::NARRATIVE_WEIGHT;
+use crate::analysis::sentiment::{Lexicon, SentimentAnalyzer, SentimentReport};
+use crate::analysis::speech::SpeechActivity;
+use crate::script::{slugify, Scene, SceneType, VideoScript};
 use unicode_segmentation::UnicodeSegmentation;
 
 use serde::Serialize;
@@ -8,6 +17,35 @@ pub struct PacingAlert {
     pub scene_index: usize,
     pub wpm: f32,
     pub message: String,
+    pub kind: PacingAlertKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PacingAlertKind {
+    TooSlow,
+    TooFast,
+    /// No narration text for a voiceover scene; pacing is judged by speech coverage instead
+    SpeechCoverageEstimate,
+}
+
+/// Data a scene's pacing was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PacingSource {
+    /// The scene's `narration` field
+    Narration,
+    /// Words in the scene's text layers
+    OnScreenText,
+    /// Fraction of the scene covered by audible voiceover
+    SpeechCoverage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenePacing {
+    pub scene_index: usize,
+    pub source: PacingSource,
+    /// Words per minute; `None` when only speech coverage is known
+    pub wpm: Option<f32>,
+    pub speech_coverage: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -16,7 +54,8 @@ pub struct RetentionWarning {
     pub message: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// Ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
 pub enum Severity {
     Info,
     Warning,
@@ -30,49 +69,261 @@ pub struct StructureRecommendation {
     pub category: String,
 }
 
+/// Narrative points lost when the Hook, Body or Payoff scene is missing
+const STRUCTURE_PENALTY: u32 = 30;
+/// Narrative points lost per structure recommendation of warning severity
+const RECOMMENDATION_PENALTY: u32 = 3;
+/// Narrative points a scene loses for pacing outside its target
+const PACING_PENALTY: u32 = 10;
+/// Narrative points a scene loses for low visual density
+const DENSITY_PENALTY: u32 = 5;
+
+/// How one scene fared in the narrative checks
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneScore {
+    pub scene_index: usize,
+    pub scene_id: String,
+    /// Not too slow or too fast for its type
+    pub pacing_ok: bool,
+    /// Enough visual layers for its length
+    pub density_ok: bool,
+    pub word_count: usize,
+    /// `None` when pacing came from speech coverage or the scene has no duration
+    pub wpm: Option<f32>,
+    /// Points lost and why, e.g. `-10 pacing: Scene 2 is too fast (300 WPM)...`
+    pub deductions: Vec<String>,
+    /// 100 minus the deductions
+    pub score: u32,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct NarrativeReport {
     pub structure_valid: bool,
     pub structure_errors: Vec<String>,
     pub structure_recommendations: Vec<StructureRecommendation>,
     pub pacing_alerts: Vec<PacingAlert>,
+    pub scene_pacing: Vec<ScenePacing>,
     pub retention_warnings: Vec<RetentionWarning>,
+    pub readability: ReadabilityReport,
+    /// Strength of the opening scene; `None` without scenes
+    pub hook: Option<HookReport>,
+    /// Emotional arc: each scene's valence
+    pub sentiment: SentimentReport,
+    /// Scenes and sentences that say the same thing twice
+    pub repetition: RepetitionReport,
+    /// The scene-level deductions behind `score`
+    pub scene_scores: Vec<SceneScore>,
     pub score: u32,
 }
 
+impl NarrativeReport {
+    /// The report's problems as findings, with its score
+    pub fn findings(&self) -> AnalyzerFindings {
+        let mut findings = Vec::new();
+        for error in &self.structure_errors {
+            findings.push(Finding::new(
+                Severity::Error,
+                "missing-scene",
+                error.clone(),
+            ));
+        }
+        for rec in &self.structure_recommendations {
+            let message = format!("{}: {}", rec.category, rec.message);
+            findings.push(Finding::new(rec.severity, slugify(&rec.category), message));
+        }
+        for alert in &self.pacing_alerts {
+            // Coverage estimates only say where the pacing came from
+            let (severity, rule) = match alert.kind {
+                PacingAlertKind::TooSlow => (Severity::Warning, "pacing-too-slow"),
+                PacingAlertKind::TooFast => (Severity::Warning, "pacing-too-fast"),
+                PacingAlertKind::SpeechCoverageEstimate => {
+                    (Severity::Info, "pacing-speech-coverage")
+                }
+            };
+            findings.push(
+                Finding::new(severity, rule, alert.message.clone()).in_scene(alert.scene_index),
+            );
+        }
+        for warning in &self.readability.warnings {
+            findings.push(
+                Finding::new(warning.severity, "readability", warning.message.clone())
+                    .in_scene(warning.scene_index),
+            );
+        }
+        for warning in &self.retention_warnings {
+            findings.push(
+                Finding::new(
+                    Severity::Warning,
+                    "retention-warning",
+                    warning.message.clone(),
+                )
+                .in_scene(warning.scene_index),
+            );
+        }
+        AnalyzerFindings {
+            findings,
+            score: Some(ScoreContribution {
+                score: self.score as f32,
+                weight: NARRATIVE_WEIGHT,
+            }),
+        }
+    }
+}
+
+/// Thresholds and weights of the narrative analysis's checks
+#[derive(Debug, Clone, Default)]
+pub struct NarrativeSettings {
+    pub readability: ReadabilitySettings,
+    pub hook: HookSettings,
+    /// Word valences of the emotional arc
+    pub lexicon: Lexicon,
+    pub repetition: RepetitionSettings,
+}
+
 pub struct NarrativeAnalyzer;
 
+impl Analyzer for NarrativeAnalyzer {
+    fn name(&self) -> &str {
+        "narrative"
+    }
+
+    fn analyze(&self, script: &VideoScript, config: &AnalysisConfig) -> AnalyzerFindings {
+        let speech = SpeechActivity::from_script(script, &config.base_path);
+        Self::analyze_with_settings(script, speech.as_ref(), &config.narrative).findings()
+    }
+}
+
 impl NarrativeAnalyzer {
     pub fn analyze(script: &VideoScript) -> NarrativeReport {
+        Self::analyze_with_speech(script, None)
+    }
+
+    /// Analyze with voiceover activity, used for pacing of scenes without narration text
+    pub fn analyze_with_speech(
+        script: &VideoScript,
+        speech: Option<&SpeechActivity>,
+    ) -> NarrativeReport {
+        Self::analyze_with_settings(script, speech, &NarrativeSettings::default())
+    }
+
+    /// Analyze with voiceover activity and the thresholds in `settings`
+    pub fn analyze_with_settings(
+        script: &VideoScript,
+        speech: Option<&SpeechActivity>,
+        settings: &NarrativeSettings,
+    ) -> NarrativeReport {
         let (structure_valid, structure_errors) = Self::validate_structure(script);
-        let structure_recommendations = Self::analyze_structure_enhancements(script);
-        let pacing_alerts = Self::analyze_pacing(script);
+        let mut structure_recommendations = Self::analyze_structure_enhancements(script);
+        let hook = HookAnalyzer::analyze(script);
+        if let Some(hook) = &hook {
+            structure_recommendations.extend(hook.recommendations.iter().cloned());
+        }
+        let sentiment = SentimentAnalyzer::analyze(script, &settings.lexicon);
+        structure_recommendations.extend(sentiment.recommendations.iter().cloned());
+        let repetition = RepetitionAnalyzer::analyze(script, &settings.repetition);
+        structure_recommendations.extend(repetition.recommendations.iter().cloned());
+        let (pacing_alerts, scene_pacing) = Self::analyze_pacing(script, speech);
         let retention_warnings = Self::analyze_visual_density(script);
+        let readability = ReadabilityAnalyzer::analyze(script, &settings.readability);
+
+        let scene_scores = Self::score_scenes(
+            script,
+            &pacing_alerts,
+            &scene_pacing,
+            &retention_warnings,
+            &readability,
+        );
 
+        // Whole-script deductions, then every scene's, so the breakdown adds up
         let mut score: i32 = 100;
         if !structure_valid {
-            score = score.saturating_sub(30);
+            score -= STRUCTURE_PENALTY as i32;
+        }
+        let warnings = structure_recommendations
+            .iter()
+            .filter(|r| r.severity == Severity::Warning)
+            .count();
+        score -= (warnings as u32 * RECOMMENDATION_PENALTY) as i32;
+        for scene in &scene_scores {
+            score -= (100 - scene.score) as i32;
+        }
+        if let Some(hook) = &hook {
+            let weight = settings.hook.weight.clamp(0.0, 1.0);
+            score =
+                ((1.0 - weight) * score.max(0) as f32 + weight * hook.score as f32).round() as i32;
         }
-        score = score.saturating_sub((pacing_alerts.len() * 10) as i32);
-        score = score.saturating_sub((retention_warnings.len() * 5) as i32);
-        score = score.saturating_sub(
-            (structure_recommendations
-                .iter()
-                .filter(|r| r.severity == Severity::Warning)
-                .count()
-                * 3) as i32,
-        );
 
         NarrativeReport {
             structure_valid,
             structure_errors,
             structure_recommendations,
             pacing_alerts,
+            scene_pacing,
             retention_warnings,
+            readability,
+            hook,
+            sentiment,
+            repetition,
+            scene_scores,
             score: score.max(0) as u32,
         }
     }
 
+    /// Each scene's share of the pacing, density and readability deductions
+    fn score_scenes(
+        script: &VideoScript,
+        pacing_alerts: &[PacingAlert],
+        scene_pacing: &[ScenePacing],
+        retention_warnings: &[RetentionWarning],
+        readability: &ReadabilityReport,
+    ) -> Vec<SceneScore> {
+        script
+            .scenes
+            .iter()
+            .enumerate()
+            .map(|(i, scene)| {
+                let mut deductions: Vec<(u32, String)> = Vec::new();
+                let mut pacing_ok = true;
+                for alert in pacing_alerts.iter().filter(|a| a.scene_index == i) {
+                    if alert.kind != PacingAlertKind::SpeechCoverageEstimate {
+                        pacing_ok = false;
+                        deductions.push((PACING_PENALTY, format!("pacing: {}", alert.message)));
+                    }
+                }
+                let mut density_ok = true;
+                for warning in retention_warnings.iter().filter(|w| w.scene_index == i) {
+                    density_ok = false;
+                    let reason = format!("density: {}", warning.message);
+                    deductions.push((DENSITY_PENALTY, reason));
+                }
+                for warning in readability.warnings.iter().filter(|w| w.scene_index == i) {
+                    if warning.penalty > 0 {
+                        let reason = format!("readability: {}", warning.message);
+                        deductions.push((warning.penalty, reason));
+                    }
+                }
+
+                let lost: u32 = deductions.iter().map(|(points, _)| points).sum();
+                SceneScore {
+                    scene_index: i,
+                    scene_id: scene.id.clone(),
+                    pacing_ok,
+                    density_ok,
+                    word_count: Self::count_words(scene),
+                    wpm: scene_pacing
+                        .iter()
+                        .find(|pacing| pacing.scene_index == i)
+                        .and_then(|pacing| pacing.wpm),
+                    deductions: deductions
+                        .into_iter()
+                        .map(|(points, reason)| format!("-{} {}", points, reason))
+                        .collect(),
+                    score: 100u32.saturating_sub(lost),
+                }
+            })
+            .collect()
+    }
+
     fn validate_structure(script: &VideoScript) -> (bool, Vec<String>) {
         let mut has_hook = false;
         let mut has_body = false;
@@ -210,18 +461,58 @@ impl NarrativeAnalyzer {
         recommendations
     }
 
-    fn analyze_pacing(script: &VideoScript) -> Vec<PacingAlert> {
+    fn analyze_pacing(
+        script: &VideoScript,
+        speech: Option<&SpeechActivity>,
+    ) -> (Vec<PacingAlert>, Vec<ScenePacing>) {
         let mut alerts = Vec::new();
+        let mut pacing = Vec::new();
+        let mut scene_start = 0.0;
 
         for (i, scene) in script.scenes.iter().enumerate() {
-            let word_count = Self::count_words(scene);
+            let start = scene_start;
+            scene_start += scene.duration;
             let duration_min = scene.duration / 60.0;
 
             if duration_min == 0.0 {
                 continue;
             }
 
+            // Without a narration text, voiceover audio is a better signal than on-screen text
+            if let (None, Some(speech)) = (&scene.narration, speech) {
+                let coverage = speech.coverage(start, start + scene.duration);
+                pacing.push(ScenePacing {
+                    scene_index: i,
+                    source: PacingSource::SpeechCoverage,
+                    wpm: None,
+                    speech_coverage: Some(coverage),
+                });
+                alerts.push(PacingAlert {
+                    scene_index: i,
+                    wpm: 0.0,
+                    message: format!(
+                        "Scene {}: estimated WPM unavailable, using speech coverage ({:.0}% voiced)",
+                        i + 1,
+                        coverage * 100.0
+                    ),
+                    kind: PacingAlertKind::SpeechCoverageEstimate,
+                });
+                continue;
+            }
+
+            let source = if scene.narration.is_some() {
+                PacingSource::Narration
+            } else {
+                PacingSource::OnScreenText
+            };
+            let word_count = Self::count_words(scene);
             let wpm = word_count as f32 / duration_min;
+            pacing.push(ScenePacing {
+                scene_index: i,
+                source,
+                wpm: Some(wpm),
+                speech_coverage: None,
+            });
             let (min_wpm, max_wpm) = match scene.scene_type {
                 SceneType::Hook => (140.0, 170.0),
                 SceneType::Body => (130.0, 150.0),
@@ -239,6 +530,7 @@ impl NarrativeAnalyzer {
                         min_wpm,
                         max_wpm
                     ),
+                    kind: PacingAlertKind::TooSlow,
                 });
             } else if wpm > max_wpm {
                 alerts.push(PacingAlert {
@@ -251,11 +543,12 @@ impl NarrativeAnalyzer {
                         min_wpm,
                         max_wpm
                     ),
+                    kind: PacingAlertKind::TooFast,
                 });
             }
         }
 
-        alerts
+        (alerts, pacing)
     }
 
     fn analyze_visual_density(script: &VideoScript) -> Vec<RetentionWarning> {
@@ -274,7 +567,12 @@ impl NarrativeAnalyzer {
         warnings
     }
 
+    /// Words spoken in a scene: the narration if present, otherwise on-screen text
     fn count_words(scene: &Scene) -> usize {
+        if let Some(narration) = &scene.narration {
+            return narration.unicode_words().count();
+        }
+
         let mut count = 0;
         for layer in &scene.layers {
             if let crate::script::Layer::Text { content, .. } = layer {
@@ -299,6 +597,7 @@ mod tests {
                 duration: 0.0,
                 description: None,
                 citations: vec![],
+                watermark: None,
             },
             scenes,
             audio: None,
@@ -324,6 +623,8 @@ mod tests {
                 effects: vec![],
             }],
             transition: None,
+            narration: None,
+            title: None,
         }
     }
 
@@ -357,4 +658,121 @@ mod tests {
         assert!(!report.pacing_alerts.is_empty());
         assert!(report.pacing_alerts[0].wpm > 170.0);
     }
+
+    #[test]
+    fn test_narration_preferred_over_text() {
+        // 2 words of on-screen text in 2s would be too slow; the narration is on pace
+        let mut hook = create_scene(SceneType::Hook, 2.0, "Only text");
+        hook.narration = Some("Most videos lose viewers fast".to_string());
+        let script = create_test_script(vec![hook]);
+
+        let report = NarrativeAnalyzer::analyze(&script);
+        assert_eq!(report.scene_pacing[0].source, PacingSource::Narration);
+        assert!((report.scene_pacing[0].wpm.unwrap() - 150.0).abs() < 0.01);
+        assert!(report.pacing_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_voiceover_without_narration_uses_speech_coverage() {
+        let mut windows = vec![true; 30];
+        windows.extend(vec![false; 70]);
+        let speech = SpeechActivity::from_windows(windows);
+
+        let mut narrated = create_scene(SceneType::Body, 2.5, "");
+        narrated.narration = Some("Words".to_string());
+        let script = create_test_script(vec![
+            create_scene(SceneType::Hook, 2.5, "Text on screen"),
+            narrated,
+        ]);
+
+        let report = NarrativeAnalyzer::analyze_with_speech(&script, Some(&speech));
+        assert_eq!(report.scene_pacing[0].source, PacingSource::SpeechCoverage);
+        assert_eq!(report.scene_pacing[0].wpm, None);
+        assert!((report.scene_pacing[0].speech_coverage.unwrap() - 0.6).abs() < 1e-6);
+        // Narration still wins where it exists
+        assert_eq!(report.scene_pacing[1].source, PacingSource::Narration);
+
+        let estimate = &report.pacing_alerts[0];
+        assert_eq!(estimate.kind, PacingAlertKind::SpeechCoverageEstimate);
+        assert!(estimate
+            .message
+            .contains("estimated WPM unavailable, using speech coverage"));
+    }
+
+    #[test]
+    fn test_hook_weight_in_score() {
+        let script = create_test_script(vec![
+            create_scene(SceneType::Hook, 2.0, "Welcome back everyone"),
+            create_scene(SceneType::Body, 5.0, "Body"),
+            create_scene(SceneType::Payoff, 5.0, "Payoff"),
+        ]);
+        let unweighted = NarrativeSettings {
+            hook: HookSettings { weight: 0.0 },
+            ..Default::default()
+        };
+        let base = NarrativeAnalyzer::analyze_with_settings(&script, None, &unweighted);
+        let weighted = NarrativeAnalyzer::analyze(&script);
+
+        let hook = weighted.hook.as_ref().unwrap();
+        assert_eq!(hook.score, 0);
+        // The weak hook's suggestions are informational, so only the weight costs points
+        assert!(weighted
+            .structure_recommendations
+            .iter()
+            .any(|r| r.category == "Hook" && r.severity == Severity::Info));
+        let expected = (0.8 * base.score as f32).round() as u32;
+        assert_eq!(weighted.score, expected);
+    }
+
+    #[test]
+    fn test_scene_scores_attribute_deductions() {
+        // Scene 2 is far too fast; the others are on pace
+        let script = create_test_script(vec![
+            create_scene(
+                SceneType::Hook,
+                5.0,
+                "Why do you lose viewers? Here is the one thing to fix now",
+            ),
+            create_scene(
+                SceneType::Body,
+                2.0,
+                "One two three four five six seven eight nine ten eleven twelve",
+            ),
+            create_scene(
+                SceneType::Payoff,
+                5.0,
+                "So now you know what to do next time you publish",
+            ),
+        ]);
+        let report = NarrativeAnalyzer::analyze(&script);
+
+        assert_eq!(report.scene_scores.len(), 3);
+        let bad = &report.scene_scores[1];
+        assert_eq!(bad.scene_index, 1);
+        assert!(!bad.pacing_ok && bad.density_ok);
+        assert_eq!(bad.word_count, 12);
+        assert!((bad.wpm.unwrap() - 360.0).abs() < 0.01);
+        assert_eq!(bad.score, 90);
+        assert_eq!(bad.deductions.len(), 1);
+        assert!(bad.deductions[0].starts_with("-10 pacing: Scene 2 is too fast"));
+        for good in [&report.scene_scores[0], &report.scene_scores[2]] {
+            assert!(good.pacing_ok, "{:?}", good);
+            assert_eq!(good.score, 100);
+            assert!(good.deductions.is_empty());
+        }
+
+        // The aggregate is built from the same deductions
+        let unweighted = NarrativeSettings {
+            hook: HookSettings { weight: 0.0 },
+            ..Default::default()
+        };
+        let report = NarrativeAnalyzer::analyze_with_settings(&script, None, &unweighted);
+        let scene_points: u32 = report.scene_scores.iter().map(|s| 100 - s.score).sum();
+        let recommendation_points = 3 * report
+            .structure_recommendations
+            .iter()
+            .filter(|r| r.severity == Severity::Warning)
+            .count() as u32;
+        assert_eq!(report.score, 100 - scene_points - recommendation_points);
+    }
 }
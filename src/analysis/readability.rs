@@ -0,0 +1,392 @@
+//! How hard a scene's on-screen text and narration are to read, as Flesch Reading
+//! Ease and Flesch-Kincaid grade level. The syllable counts are estimated with
+//! English spelling rules.
+
+use crate::analysis::narrative::Severity;
+use crate::script::{Layer, Scene, VideoScript};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Most narrative points readability can cost, however many scenes are flagged
+pub const MAX_PENALTY: u32 = 10;
+
+/// Thresholds and score impact of the readability check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadabilitySettings {
+    /// Highest Flesch-Kincaid grade level a scene's text may read at
+    #[serde(default = "default_max_grade_level")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub max_grade_level: f32,
+    /// Texts with fewer words are scored but never flagged, since a few long
+    /// words in a title skew the formulas
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+    /// Narrative points each flagged text costs (at most `MAX_PENALTY` in total)
+    #[serde(default = "default_penalty_per_scene")]
+    pub penalty_per_scene: u32,
+}
+
+fn default_max_grade_level() -> f32 {
+    9.0
+}
+
+fn default_min_words() -> usize {
+    10
+}
+
+fn default_penalty_per_scene() -> u32 {
+    2
+}
+
+impl Default for ReadabilitySettings {
+    fn default() -> Self {
+        Self {
+            max_grade_level: default_max_grade_level(),
+            min_words: default_min_words(),
+            penalty_per_scene: default_penalty_per_scene(),
+        }
+    }
+}
+
+/// Which of a scene's texts was scored
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum TextSource {
+    /// Words in the scene's text layers
+    OnScreenText,
+    /// The scene's `narration` field
+    Narration,
+}
+
+impl TextSource {
+    /// How messages name it
+    pub fn label(self) -> &'static str {
+        match self {
+            TextSource::OnScreenText => "on-screen text",
+            TextSource::Narration => "narration",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneReadability {
+    pub scene_index: usize,
+    pub source: TextSource,
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+    /// 0-100 and up, higher is easier
+    pub reading_ease: f32,
+    /// US school grade needed to follow the text
+    pub grade_level: f32,
+    /// Words per sentence
+    pub avg_sentence_length: f32,
+    /// Characters per word
+    pub avg_word_length: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadabilityWarning {
+    pub scene_index: usize,
+    pub severity: Severity,
+    pub category: String,
+    pub message: String,
+    /// Narrative points this warning costs, until `MAX_PENALTY` is used up
+    pub penalty: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadabilityReport {
+    /// One entry per scene text with words in it
+    pub scenes: Vec<SceneReadability>,
+    /// Texts reading above the grade level
+    pub warnings: Vec<ReadabilityWarning>,
+    /// Narrative points the warnings cost together
+    pub penalty: u32,
+}
+
+pub struct ReadabilityAnalyzer;
+
+impl ReadabilityAnalyzer {
+    pub fn analyze(script: &VideoScript, settings: &ReadabilitySettings) -> ReadabilityReport {
+        let mut scenes = Vec::new();
+        let mut warnings = Vec::new();
+        let mut penalty = 0;
+
+        for (i, scene) in script.scenes.iter().enumerate() {
+            let texts = [
+                (TextSource::OnScreenText, Self::on_screen_text(scene)),
+                (
+                    TextSource::Narration,
+                    scene.narration.iter().map(String::as_str).collect(),
+                ),
+            ];
+            for (source, text) in texts {
+                // Scenes without words have nothing to score
+                let Some(readability) = Self::score(i, source, &text) else {
+                    continue;
+                };
+
+                if readability.words >= settings.min_words
+                    && readability.grade_level > settings.max_grade_level
+                {
+                    let points = settings.penalty_per_scene.min(MAX_PENALTY - penalty);
+                    penalty += points;
+                    warnings.push(ReadabilityWarning {
+                        scene_index: i,
+                        severity: Severity::Warning,
+                        category: "Readability".to_string(),
+                        message: format!(
+                            "Scene {} {} reads at grade {:.1} (reading ease {:.0}). Aim for grade {:.0} or below with shorter sentences and plainer words.",
+                            i + 1,
+                            source.label(),
+                            readability.grade_level,
+                            readability.reading_ease,
+                            settings.max_grade_level
+                        ),
+                        penalty: points,
+                    });
+                }
+                scenes.push(readability);
+            }
+        }
+
+        ReadabilityReport {
+            scenes,
+            warnings,
+            penalty,
+        }
+    }
+
+    fn on_screen_text(scene: &Scene) -> Vec<&str> {
+        scene
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Flesch scores of `texts` taken together; each text ends a sentence, since
+    /// text layers rarely end in punctuation. `None` without any words.
+    fn score(scene_index: usize, source: TextSource, texts: &[&str]) -> Option<SceneReadability> {
+        let mut sentences = 0;
+        let mut words = 0;
+        let mut syllables = 0;
+        let mut characters = 0;
+
+        for text in texts {
+            for sentence in text.unicode_sentences() {
+                let mut sentence_words = 0;
+                for word in sentence.unicode_words() {
+                    sentence_words += 1;
+                    syllables += estimate_syllables(word);
+                    characters += word.chars().count();
+                }
+                if sentence_words > 0 {
+                    sentences += 1;
+                    words += sentence_words;
+                }
+            }
+        }
+        if words == 0 {
+            return None;
+        }
+
+        let avg_sentence_length = words as f32 / sentences as f32;
+        let syllables_per_word = syllables as f32 / words as f32;
+        Some(SceneReadability {
+            scene_index,
+            source,
+            sentences,
+            words,
+            syllables,
+            reading_ease: 206.835 - 1.015 * avg_sentence_length - 84.6 * syllables_per_word,
+            grade_level: (0.39 * avg_sentence_length + 11.8 * syllables_per_word - 15.59).max(0.0),
+            avg_sentence_length,
+            avg_word_length: characters as f32 / words as f32,
+        })
+    }
+}
+
+/// Syllables in an English word: groups of vowels, without a silent final `e`,
+/// `es` or `ed` (`make`, `names`, `named`, but not `table`, `boxes`, `wanted`).
+/// Words without letters, like numbers, count as one.
+pub fn estimate_syllables(word: &str) -> usize {
+    let word: String = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if word.chars().count() <= 3 {
+        return 1;
+    }
+
+    let mut stem = word.as_str();
+    if let Some(rest) = stem.strip_suffix("es") {
+        if !rest.ends_with(['s', 'x', 'z', 'h', 'c', 'g']) {
+            stem = rest;
+        }
+    } else if let Some(rest) = stem.strip_suffix("ed") {
+        if !rest.ends_with(['t', 'd']) {
+            stem = rest;
+        }
+    } else if stem.ends_with('e') && !stem.ends_with("le") {
+        stem = &stem[..stem.len() - 1];
+    }
+
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut groups = 0;
+    let mut previous_vowel = false;
+    for c in stem.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            groups += 1;
+        }
+        previous_vowel = vowel;
+    }
+    groups.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution, SceneType};
+
+    fn create_scene(text: &str) -> Scene {
+        Scene {
+            id: "test".into(),
+            scene_type: SceneType::Body,
+            duration: 5.0,
+            layers: vec![Layer::Text {
+                content: text.into(),
+                font: "font.ttf".into(),
+                font_size: 24.0,
+                color: Color {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                },
+                position: Position { x: 0, y: 0 },
+                effects: vec![],
+            }],
+            transition: None,
+            narration: None,
+            title: None,
+        }
+    }
+
+    fn create_test_script(scenes: Vec<Scene>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes,
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_syllables() {
+        assert_eq!(estimate_syllables("cat"), 1);
+        assert_eq!(estimate_syllables("make"), 1);
+        assert_eq!(estimate_syllables("named"), 1);
+        assert_eq!(estimate_syllables("wanted"), 2);
+        assert_eq!(estimate_syllables("boxes"), 2);
+        assert_eq!(estimate_syllables("table"), 2);
+        assert_eq!(estimate_syllables("People"), 2);
+        assert_eq!(estimate_syllables("readability"), 5);
+        assert_eq!(estimate_syllables("2024"), 1);
+    }
+
+    #[test]
+    fn test_simple_sentence_reads_easy() {
+        let script = create_test_script(vec![create_scene(
+            "The cat sat on the mat. We like the cat a lot.",
+        )]);
+        let report = ReadabilityAnalyzer::analyze(&script, &ReadabilitySettings::default());
+
+        let scene = &report.scenes[0];
+        assert_eq!(scene.source, TextSource::OnScreenText);
+        assert_eq!((scene.sentences, scene.words), (2, 12));
+        assert!(scene.reading_ease > 90.0, "{:?}", scene);
+        assert!(scene.grade_level < 2.0, "{:?}", scene);
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.penalty, 0);
+    }
+
+    #[test]
+    fn test_long_nested_sentence_reads_hard() {
+        let sentence = "Notwithstanding the considerable methodological limitations \
+            acknowledged by the investigators themselves, who nevertheless maintained that their \
+            longitudinal observations, which were collected across numerous and heterogeneous \
+            institutional environments, substantiated the hypothesis, the committee \
+            ultimately concluded that additional corroborating evidence was clearly indispensable.";
+        let mut scene = create_scene("Why it matters");
+        scene.narration = Some(sentence.to_string());
+        let script = create_test_script(vec![scene]);
+        let report = ReadabilityAnalyzer::analyze(&script, &ReadabilitySettings::default());
+
+        // The short title is scored, but too short to flag
+        assert_eq!(report.scenes.len(), 2);
+        let narration = &report.scenes[1];
+        assert_eq!(narration.source, TextSource::Narration);
+        assert_eq!((narration.sentences, narration.words), (1, 40));
+        assert!(narration.reading_ease < 10.0, "{:?}", narration);
+        assert!(narration.grade_level > 20.0, "{:?}", narration);
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].scene_index, 0);
+        assert!(report.warnings[0]
+            .message
+            .starts_with("Scene 1 narration reads at grade"));
+        assert_eq!(report.penalty, 2);
+
+        // The penalty and threshold are configurable
+        let lenient = ReadabilitySettings {
+            max_grade_level: 60.0,
+            ..Default::default()
+        };
+        assert!(ReadabilityAnalyzer::analyze(&script, &lenient)
+            .warnings
+            .is_empty());
+        let free = ReadabilitySettings {
+            penalty_per_scene: 0,
+            ..Default::default()
+        };
+        assert_eq!(ReadabilityAnalyzer::analyze(&script, &free).penalty, 0);
+    }
+
+    #[test]
+    fn test_scenes_without_words_are_skipped() {
+        let mut empty = create_scene("");
+        empty.layers.push(Layer::Text {
+            content: " ... ".into(),
+            font: "font.ttf".into(),
+            font_size: 24.0,
+            color: Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+        });
+        empty.narration = Some(String::new());
+        let script = create_test_script(vec![empty, create_scene("Short and sweet.")]);
+        let report = ReadabilityAnalyzer::analyze(&script, &ReadabilitySettings::default());
+
+        assert_eq!(report.scenes.len(), 1);
+        assert_eq!(report.scenes[0].scene_index, 1);
+        assert!(report.scenes[0].reading_ease.is_finite());
+    }
+}
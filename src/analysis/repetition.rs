@@ -0,0 +1,364 @@
+//! Scenes that say the same thing twice: pairs of scenes sharing most of their
+//! word trigrams, and sentences repeated word for word.
+
+use crate::analysis::narrative::{Severity, StructureRecommendation};
+use crate::script::{Layer, Scene, VideoScript};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Words of the shared phrase quoted in messages
+const MAX_SNIPPET_WORDS: usize = 12;
+
+/// Thresholds of the repetition check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepetitionSettings {
+    /// Share of two scenes' word trigrams (Jaccard similarity) above which
+    /// they are flagged as repeating each other
+    #[serde(default = "default_similarity_threshold")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub similarity_threshold: f32,
+    /// Repeated sentences with fewer words, like "Let's go.", are not flagged
+    #[serde(default = "default_min_sentence_words")]
+    pub min_sentence_words: usize,
+}
+
+fn default_similarity_threshold() -> f32 {
+    0.5
+}
+
+fn default_min_sentence_words() -> usize {
+    4
+}
+
+impl Default for RepetitionSettings {
+    fn default() -> Self {
+        Self {
+            similarity_threshold: default_similarity_threshold(),
+            min_sentence_words: default_min_sentence_words(),
+        }
+    }
+}
+
+/// Two scenes whose text overlaps above the threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarScenes {
+    pub first_scene: usize,
+    pub second_scene: usize,
+    /// Jaccard similarity of their word trigrams, 0 to 1
+    pub similarity: f32,
+    /// The longest phrase both scenes share, lowercased
+    pub snippet: String,
+}
+
+/// A sentence found word for word in more than one place
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSentence {
+    /// As first written
+    pub sentence: String,
+    /// Scenes containing it, once per occurrence in a different scene
+    pub scene_indices: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepetitionReport {
+    pub similar_scenes: Vec<SimilarScenes>,
+    pub duplicate_sentences: Vec<DuplicateSentence>,
+    #[serde(skip)]
+    pub recommendations: Vec<StructureRecommendation>,
+}
+
+/// A scene's lowercased words and the hashes of their trigrams
+struct SceneTokens {
+    words: Vec<String>,
+    trigrams: HashSet<u64>,
+}
+
+pub struct RepetitionAnalyzer;
+
+impl RepetitionAnalyzer {
+    pub fn analyze(script: &VideoScript, settings: &RepetitionSettings) -> RepetitionReport {
+        let scenes: Vec<SceneTokens> = script.scenes.iter().map(Self::tokenize).collect();
+
+        let mut similar_scenes = Vec::new();
+        for (i, first) in scenes.iter().enumerate() {
+            for (j, second) in scenes.iter().enumerate().skip(i + 1) {
+                let similarity = jaccard(&first.trigrams, &second.trigrams);
+                if similarity > settings.similarity_threshold {
+                    similar_scenes.push(SimilarScenes {
+                        first_scene: i,
+                        second_scene: j,
+                        similarity,
+                        snippet: shared_phrase(first, second),
+                    });
+                }
+            }
+        }
+
+        let duplicate_sentences = Self::duplicate_sentences(script, settings);
+
+        let mut recommendations: Vec<StructureRecommendation> = similar_scenes
+            .iter()
+            .map(|pair| {
+                recommendation(format!(
+                    "Scenes {} and {} repeat each other ({:.0}% of their phrasing is shared, e.g. \"{}\"). Cut or reword one of them.",
+                    pair.first_scene + 1,
+                    pair.second_scene + 1,
+                    pair.similarity * 100.0,
+                    pair.snippet
+                ))
+            })
+            .collect();
+        recommendations.extend(duplicate_sentences.iter().map(|duplicate| {
+            let scenes: Vec<String> = duplicate
+                .scene_indices
+                .iter()
+                .map(|i| (i + 1).to_string())
+                .collect();
+            recommendation(format!(
+                "\"{}\" is said word for word in scenes {}. Viewers notice repeated lines; say it once.",
+                duplicate.sentence,
+                scenes.join(", ")
+            ))
+        }));
+
+        RepetitionReport {
+            similar_scenes,
+            duplicate_sentences,
+            recommendations,
+        }
+    }
+
+    /// On-screen text and narration of a scene
+    fn texts(scene: &Scene) -> Vec<&str> {
+        let mut texts: Vec<&str> = scene
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        texts.extend(scene.narration.as_deref());
+        texts
+    }
+
+    fn tokenize(scene: &Scene) -> SceneTokens {
+        let mut words = Vec::new();
+        let mut trigrams = HashSet::new();
+        // Trigrams don't span texts, so a caption and the narration after it
+        // don't make up phrases
+        for text in Self::texts(scene) {
+            let start = words.len();
+            words.extend(text.unicode_words().map(str::to_lowercase));
+            trigrams.extend(words[start..].windows(3).map(hash_trigram));
+        }
+        SceneTokens { words, trigrams }
+    }
+
+    /// Sentences repeated in different scenes. A scene's caption repeating its
+    /// own narration is intended and not counted.
+    fn duplicate_sentences(
+        script: &VideoScript,
+        settings: &RepetitionSettings,
+    ) -> Vec<DuplicateSentence> {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut duplicates: Vec<DuplicateSentence> = Vec::new();
+        for (scene_index, scene) in script.scenes.iter().enumerate() {
+            let mut in_scene = HashSet::new();
+            for text in Self::texts(scene) {
+                for sentence in text.split(['.', '!', '?', '\n']) {
+                    let words: Vec<String> =
+                        sentence.unicode_words().map(str::to_lowercase).collect();
+                    if words.len() < settings.min_sentence_words.max(1) {
+                        continue;
+                    }
+                    let normalized = words.join(" ");
+                    if !in_scene.insert(normalized.clone()) {
+                        continue;
+                    }
+                    match seen.get(&normalized) {
+                        Some(&i) => duplicates[i].scene_indices.push(scene_index),
+                        None => {
+                            seen.insert(normalized, duplicates.len());
+                            duplicates.push(DuplicateSentence {
+                                sentence: sentence.trim().to_string(),
+                                scene_indices: vec![scene_index],
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        duplicates.retain(|duplicate| duplicate.scene_indices.len() > 1);
+        duplicates
+    }
+}
+
+fn hash_trigram(words: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    words.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn jaccard(first: &HashSet<u64>, second: &HashSet<u64>) -> f32 {
+    let (smaller, larger) = if first.len() <= second.len() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let shared = smaller.iter().filter(|hash| larger.contains(hash)).count();
+    let union = first.len() + second.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f32 / union as f32
+    }
+}
+
+/// The longest run of `first`'s words whose trigrams all occur in `second`
+fn shared_phrase(first: &SceneTokens, second: &SceneTokens) -> String {
+    let mut best = (0, 0);
+    let mut run_start = 0;
+    for (i, trigram) in first.words.windows(3).enumerate() {
+        if second.trigrams.contains(&hash_trigram(trigram)) {
+            if i + 1 - run_start > best.1 {
+                best = (run_start, i + 1 - run_start);
+            }
+        } else {
+            run_start = i + 1;
+        }
+    }
+    if best.1 == 0 {
+        return String::new();
+    }
+    let words = &first.words[best.0..best.0 + best.1 + 2];
+    let mut snippet = words[..words.len().min(MAX_SNIPPET_WORDS)].join(" ");
+    if words.len() > MAX_SNIPPET_WORDS {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn recommendation(message: String) -> StructureRecommendation {
+    StructureRecommendation {
+        severity: Severity::Warning,
+        category: "Repetition".to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution, SceneType};
+
+    fn create_scene(narration: &str) -> Scene {
+        Scene {
+            id: "scene".into(),
+            scene_type: SceneType::Body,
+            duration: 5.0,
+            layers: vec![],
+            transition: None,
+            narration: Some(narration.to_string()),
+            title: None,
+        }
+    }
+
+    fn create_script(narrations: &[&str]) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: narrations.iter().map(|n| create_scene(n)).collect(),
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_repeated_sentence_across_scenes() {
+        let script = create_script(&[
+            "Compound interest is the eighth wonder of the world. Start early.",
+            "Most people wait until their thirties to invest anything at all.",
+            "Never forget it. Compound interest is the eighth wonder of the world!",
+        ]);
+        let report = RepetitionAnalyzer::analyze(&script, &RepetitionSettings::default());
+
+        assert_eq!(report.duplicate_sentences.len(), 1);
+        let duplicate = &report.duplicate_sentences[0];
+        assert_eq!(
+            duplicate.sentence,
+            "Compound interest is the eighth wonder of the world"
+        );
+        assert_eq!(duplicate.scene_indices, vec![0, 2]);
+        assert!(report
+            .recommendations
+            .iter()
+            .all(|r| r.severity == Severity::Warning && r.category == "Repetition"));
+        assert!(report
+            .recommendations
+            .iter()
+            .any(|r| r.message.contains("scenes 1, 3")));
+    }
+
+    #[test]
+    fn test_similar_scenes_are_paired_with_snippet() {
+        let script = create_script(&[
+            "Your savings lose value every single year because of inflation",
+            "A quick detour through the history of money",
+            "Your savings lose value every single year because of inflation, sadly",
+        ]);
+        let report = RepetitionAnalyzer::analyze(&script, &RepetitionSettings::default());
+
+        assert_eq!(report.similar_scenes.len(), 1);
+        let pair = &report.similar_scenes[0];
+        assert_eq!((pair.first_scene, pair.second_scene), (0, 2));
+        assert!(pair.similarity > 0.8);
+        assert_eq!(
+            pair.snippet,
+            "your savings lose value every single year because of inflation"
+        );
+    }
+
+    #[test]
+    fn test_paraphrase_below_threshold() {
+        let script = create_script(&[
+            "Inflation quietly eats into the value of your savings every year",
+            "Every year, rising prices slowly shrink what your savings can buy",
+        ]);
+        let report = RepetitionAnalyzer::analyze(&script, &RepetitionSettings::default());
+
+        assert!(report.similar_scenes.is_empty());
+        assert!(report.duplicate_sentences.is_empty());
+        assert!(report.recommendations.is_empty());
+    }
+
+    #[test]
+    fn test_caption_of_own_narration_and_short_sentences_ignored() {
+        let mut script =
+            create_script(&["Let's go. Saving starts with one small habit", "Let's go."]);
+        script.scenes[0].layers.push(Layer::Text {
+            content: "Saving starts with one small habit".to_string(),
+            font: "fonts/Roboto.ttf".into(),
+            font_size: 48.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+        });
+        let report = RepetitionAnalyzer::analyze(&script, &RepetitionSettings::default());
+        assert!(report.duplicate_sentences.is_empty());
+    }
+}
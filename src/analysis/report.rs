@@ -0,0 +1,855 @@
+//! Every analysis of a script in one report with an overall score, for CI quality
+//! gates (`analyze`) and `render --export-report`.
+
+use crate::analysis::accessibility::{AccessibilityAnalyzer, AccessibilityReport};
+use crate::analysis::analyzer::{
+    AnalysisConfig, AnalyzerFindings, ExtraAnalyzers, Finding, ScoreContribution,
+};
+use crate::analysis::audio_timing::{AudioTimingAnalyzer, AudioTimingReport};
+use crate::analysis::citations::CitationStatus;
+use crate::analysis::credibility::{
+    CredibilityAnalyzer, CredibilityDictionaries, CredibilityReport,
+};
+use crate::analysis::export;
+use crate::analysis::metadata::{MetadataAnalyzer, MetadataReport};
+use crate::analysis::narrative::{NarrativeAnalyzer, NarrativeReport, NarrativeSettings, Severity};
+use crate::analysis::retention::{DropoffPrediction, RetentionAnalyzer, RetentionHeatmap};
+use crate::analysis::speech::SpeechActivity;
+use crate::assets::AssetLoader;
+use crate::audio::AudioAnalysisReport;
+use crate::script::VideoScript;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Version of the JSON layout; bumped on breaking changes to it
+pub const REPORT_VERSION: u32 = 1;
+
+/// Share of the narrative score in the overall score
+pub const NARRATIVE_WEIGHT: f32 = 0.4;
+/// Share of the credibility score in the overall score
+pub const CREDIBILITY_WEIGHT: f32 = 0.3;
+/// Share of the retention score in the overall score
+pub const RETENTION_WEIGHT: f32 = 0.3;
+
+/// The overall score: the mean of the analyzers' scores, weighted by their
+/// weights (the constants above for the built-in ones)
+fn overall_score(contributions: impl IntoIterator<Item = ScoreContribution>) -> u32 {
+    let (total, weights) = contributions
+        .into_iter()
+        .fold((0.0, 0.0), |(total, weights), part| {
+            (total + part.weight * part.score, weights + part.weight)
+        });
+    if weights <= 0.0 {
+        return 0;
+    }
+    (total / weights).round().clamp(0.0, 100.0) as u32
+}
+
+/// The overall score of a script and the scores it's made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Scores {
+    pub overall: u32,
+    pub narrative: u32,
+    pub credibility: u32,
+    pub retention: u32,
+}
+
+impl Scores {
+    /// Score `script` from its text alone, without reading any asset or audio
+    /// file. Narration pacing is estimated from word counts rather than
+    /// measured in a voiceover, so a script with one can score differently
+    /// than in the full report.
+    pub fn of(
+        script: &VideoScript,
+        dictionaries: &CredibilityDictionaries,
+        narrative: &NarrativeSettings,
+    ) -> Self {
+        let narrative = NarrativeAnalyzer::analyze_with_settings(script, None, narrative).score;
+        let credibility =
+            CredibilityAnalyzer::analyze_with_dictionaries(script, dictionaries).score;
+        let retention = RetentionAnalyzer::generate_heatmap(script).overall_retention_score;
+        let part = |score, weight| ScoreContribution { score, weight };
+        Self {
+            overall: overall_score([
+                part(narrative as f32, NARRATIVE_WEIGHT),
+                part(credibility as f32, CREDIBILITY_WEIGHT),
+                part(retention, RETENTION_WEIGHT),
+            ]),
+            narrative,
+            credibility,
+            retention: retention.round() as u32,
+        }
+    }
+}
+
+/// Threshold of `score`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreSettings {
+    /// Overall score below which `score` fails; 0 never fails
+    #[serde(default)]
+    pub min: u32,
+}
+
+/// How a report is written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+    Text,
+    /// SARIF 2.1.0, for code scanning annotations
+    Sarif,
+    /// JUnit XML, for CI test reports
+    Junit,
+}
+
+impl ReportFormat {
+    /// The format a file's extension asks for: `.json`, `.txt`, `.sarif`, `.xml`
+    /// (JUnit), or Markdown otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => ReportFormat::Json,
+            Some("txt") => ReportFormat::Text,
+            Some("sarif") => ReportFormat::Sarif,
+            Some("xml") => ReportFormat::Junit,
+            _ => ReportFormat::Markdown,
+        }
+    }
+}
+
+/// A problem any analyzer found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Analysis that found it: `narrative`, `credibility`, `retention`, `audio`,
+    /// `accessibility`, `metadata`, or the name of a plugged-in analyzer
+    pub source: String,
+    /// Stable id of the check, e.g. `narrative/pacing-too-fast`
+    pub rule: String,
+    pub scene_index: Option<usize>,
+    pub scene_id: Option<String>,
+    pub message: String,
+}
+
+/// Narrative, credibility, retention, audio, accessibility and metadata analysis
+/// of one script
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisReport {
+    pub version: u32,
+    pub title: String,
+    /// Script file the report is about, as given on the command line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_path: Option<String>,
+    /// Weighted mean of the narrative, credibility and retention scores, and
+    /// those of plugged-in analyzers that score (0-100)
+    pub score: u32,
+    /// Analyses that ran, built-in ones first
+    pub analyzers: Vec<String>,
+    /// Every problem found, most severe first
+    pub issues: Vec<Issue>,
+    pub narrative: NarrativeReport,
+    pub credibility: CredibilityReport,
+    pub retention: RetentionHeatmap,
+    pub dropoff_predictions: Vec<DropoffPrediction>,
+    pub audio_timing: AudioTimingReport,
+    pub accessibility: AccessibilityReport,
+    /// Title and description checks
+    pub metadata: MetadataReport,
+    /// Levels of the mixed audio, when it was rendered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioAnalysisReport>,
+    /// What each plugged-in analyzer found, by name; in `issues` and `score`
+    #[serde(skip)]
+    plugins: Vec<(String, AnalyzerFindings)>,
+}
+
+impl AnalysisReport {
+    /// Run every built-in analysis over `script` with the settings in `config`,
+    /// then the analyzers in `analyzers`
+    pub fn analyze(
+        script: &VideoScript,
+        config: &AnalysisConfig,
+        analyzers: &ExtraAnalyzers,
+    ) -> Self {
+        let _span = tracing::info_span!("analyze", title = %script.metadata.title).entered();
+        let base_path = &config.base_path;
+        let speech = SpeechActivity::from_script(script, base_path);
+        let mut assets = AssetLoader::new(base_path);
+        Self::new(
+            script,
+            NarrativeAnalyzer::analyze_with_settings(script, speech.as_ref(), &config.narrative),
+            CredibilityAnalyzer::analyze_with_dictionaries(script, &config.dictionaries),
+            AudioTimingAnalyzer::analyze(script, base_path),
+            AccessibilityAnalyzer::analyze(script, &mut assets, &config.accessibility),
+        )
+        .with_analyzers(script, config, analyzers)
+    }
+
+    /// Combine reports that were already made, adding the retention and metadata
+    /// analyses
+    pub fn new(
+        script: &VideoScript,
+        narrative: NarrativeReport,
+        credibility: CredibilityReport,
+        audio_timing: AudioTimingReport,
+        accessibility: AccessibilityReport,
+    ) -> Self {
+        let mut report = Self {
+            version: REPORT_VERSION,
+            title: script.metadata.title.clone(),
+            script_path: None,
+            score: 0,
+            analyzers: [
+                "narrative",
+                "credibility",
+                "retention",
+                "audio",
+                "accessibility",
+                "metadata",
+            ]
+            .map(String::from)
+            .to_vec(),
+            issues: Vec::new(),
+            narrative,
+            credibility,
+            retention: RetentionAnalyzer::generate_heatmap(script),
+            dropoff_predictions: RetentionAnalyzer::predict_dropoff(script),
+            audio_timing,
+            accessibility,
+            metadata: MetadataAnalyzer::analyze(script),
+            audio: None,
+            plugins: Vec::new(),
+        };
+        report
+            .retention
+            .overlay_sentiment(&report.narrative.sentiment);
+        report.score = report.overall_score();
+        report.issues = report.collect_issues(script);
+        report
+    }
+
+    /// Run `analyzers` over `script` too, adding their issues and scores
+    pub fn with_analyzers(
+        mut self,
+        script: &VideoScript,
+        config: &AnalysisConfig,
+        analyzers: &ExtraAnalyzers,
+    ) -> Self {
+        if analyzers.is_empty() {
+            return self;
+        }
+        for analyzer in analyzers.iter() {
+            let name = analyzer.name().to_string();
+            let _span = tracing::info_span!("analyzer", name = %name).entered();
+            let findings = analyzer.analyze(script, config);
+            self.analyzers.push(name.clone());
+            self.plugins.push((name, findings));
+        }
+        self.score = self.overall_score();
+        self.issues = self.collect_issues(script);
+        self
+    }
+
+    /// Include the responses to the citation URLs, and the issues of unreachable ones
+    pub fn with_citation_checks(
+        mut self,
+        script: &VideoScript,
+        statuses: Vec<CitationStatus>,
+    ) -> Self {
+        self.credibility.add_citation_checks(statuses);
+        self.issues = self.collect_issues(script);
+        self
+    }
+
+    /// Include the levels of the rendered audio mix
+    pub fn with_audio(mut self, audio: Option<AudioAnalysisReport>) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    /// Name the script file the report is about, e.g. for SARIF locations
+    pub fn with_script_path(mut self, path: &Path) -> Self {
+        self.script_path = Some(path.to_string_lossy().replace('\\', "/"));
+        self
+    }
+
+    /// Whether any issue is at least as severe as `severity`
+    pub fn has_issues(&self, severity: Severity) -> bool {
+        self.issues.iter().any(|issue| issue.severity >= severity)
+    }
+
+    /// The report in `format`
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        Ok(match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Text => self.to_text(),
+            ReportFormat::Sarif => serde_json::to_string_pretty(&export::sarif(self))?,
+            ReportFormat::Junit => export::junit(self),
+        })
+    }
+
+    /// Every analysis's findings as issues, most severe first
+    fn collect_issues(&self, script: &VideoScript) -> Vec<Issue> {
+        let mut analyses = vec![
+            ("narrative", self.narrative.findings().findings),
+            ("credibility", self.credibility.findings().findings),
+            (
+                "retention",
+                RetentionAnalyzer::findings(&self.retention, &self.dropoff_predictions).findings,
+            ),
+        ];
+
+        let audio = self
+            .audio_timing
+            .warnings
+            .iter()
+            .map(|warning| Finding::new(Severity::Warning, "track-timing", warning.clone()));
+        analyses.push(("audio", audio.collect()));
+
+        let accessibility = self.accessibility.warnings.iter().map(|warning| {
+            Finding::new(
+                Severity::Warning,
+                warning.check.rule(),
+                warning.message.clone(),
+            )
+            .in_scene(warning.scene_index)
+        });
+        analyses.push(("accessibility", accessibility.collect()));
+
+        let metadata = self.metadata.findings.iter().map(|finding| {
+            Finding::new(
+                finding.severity,
+                finding.check.rule(),
+                finding.message.clone(),
+            )
+        });
+        analyses.push(("metadata", metadata.collect()));
+
+        for (name, findings) in &self.plugins {
+            analyses.push((name, findings.findings.clone()));
+        }
+
+        let mut issues: Vec<Issue> = analyses
+            .into_iter()
+            .flat_map(|(source, findings)| {
+                findings.into_iter().map(move |finding| Issue {
+                    severity: finding.severity,
+                    source: source.to_string(),
+                    rule: format!("{}/{}", source, finding.rule),
+                    scene_index: finding.scene_index,
+                    scene_id: finding
+                        .scene_index
+                        .and_then(|index| script.scenes.get(index))
+                        .map(|scene| scene.id.clone()),
+                    message: finding.message,
+                })
+            })
+            .collect();
+        // Stable, so each analysis keeps its order within a severity
+        issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+        issues
+    }
+
+    /// The built-in analyses' scores and the plugged-in analyzers'
+    fn overall_score(&self) -> u32 {
+        let built_in = [
+            self.narrative.findings().score,
+            self.credibility.findings().score,
+            Some(ScoreContribution {
+                score: self.retention.overall_retention_score,
+                weight: RETENTION_WEIGHT,
+            }),
+        ];
+        let plugins = self.plugins.iter().map(|(_, findings)| findings.score);
+        overall_score(built_in.into_iter().chain(plugins).flatten())
+    }
+
+    fn score_breakdown(&self) -> String {
+        let mut breakdown = format!(
+            "narrative {}, credibility {}, retention {:.0}",
+            self.narrative.score, self.credibility.score, self.retention.overall_retention_score
+        );
+        for (name, findings) in &self.plugins {
+            if let Some(part) = findings.score {
+                let _ = write!(breakdown, ", {} {:.0}", name, part.score);
+            }
+        }
+        breakdown
+    }
+
+    fn issue_line(issue: &Issue) -> String {
+        let scene = issue
+            .scene_index
+            .map(|index| format!(" (scene {})", index + 1))
+            .unwrap_or_default();
+        format!("[{}] {}{}", issue.rule, issue.message, scene)
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "# Analysis Report: {}\n\n**Score**: {}/100 ({})\n\n**Analyzers**: {}\n\n## Issues\n",
+            self.title,
+            self.score,
+            self.score_breakdown(),
+            self.analyzers.join(", ")
+        );
+        if self.issues.is_empty() {
+            md.push_str("- None\n");
+        }
+        for issue in &self.issues {
+            let _ = writeln!(md, "- **{:?}** {}", issue.severity, Self::issue_line(issue));
+        }
+
+        let _ = write!(
+            md,
+            "\n## Structure\n- Valid: {}\n- Errors: {:?}\n",
+            self.narrative.structure_valid, self.narrative.structure_errors
+        );
+
+        let verified = self
+            .credibility
+            .claims
+            .iter()
+            .filter(|c| c.verified)
+            .count();
+        if !self.narrative.scene_scores.is_empty() {
+            md.push_str(
+                "\n## Scenes\n| # | Scene | WPM | Score | Deductions |\n|---|---|---|---|---|\n",
+            );
+        }
+        for scene in &self.narrative.scene_scores {
+            let wpm = scene
+                .wpm
+                .map_or("-".to_string(), |wpm| format!("{:.0}", wpm));
+            let deductions = if scene.deductions.is_empty() {
+                "-".to_string()
+            } else {
+                scene.deductions.join("; ")
+            };
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                scene.scene_index + 1,
+                scene.scene_id,
+                wpm,
+                scene.score,
+                deductions
+            );
+        }
+
+        if !self.narrative.readability.scenes.is_empty() {
+            md.push_str("\n## Readability\n");
+        }
+        for scene in &self.narrative.readability.scenes {
+            let _ = writeln!(
+                md,
+                "- Scene {} {}: grade {:.1}, reading ease {:.0}",
+                scene.scene_index + 1,
+                scene.source.label(),
+                scene.grade_level,
+                scene.reading_ease
+            );
+        }
+
+        let description = self
+            .metadata
+            .description_length
+            .map_or("none".to_string(), |length| {
+                format!("{} characters", length)
+            });
+        let _ = write!(
+            md,
+            "\n## Metadata\n- Title: {} characters\n- Description: {}\n",
+            self.metadata.title_length, description
+        );
+
+        let _ = write!(
+            md,
+            "\n## Credibility\n- Claims verified: {}/{}\n- Citations: {}\n",
+            verified,
+            self.credibility.claims.len(),
+            self.credibility.citations.len()
+        );
+
+        let _ = write!(
+            md,
+            "\n## Retention\n- Overall: {:.1}/100\n",
+            self.retention.overall_retention_score
+        );
+        // The emotional arc next to the curve, so drop-offs read against it
+        if !self.retention.scene_scores.is_empty() {
+            md.push_str("\n| # | Retention | Momentum | Valence |\n|---|---|---|---|\n");
+        }
+        for scene in &self.retention.scene_scores {
+            let _ = writeln!(
+                md,
+                "| {} | {:.1} | {:.1} | {} |",
+                scene.scene_index + 1,
+                scene.retention_score,
+                scene.momentum,
+                Self::valence_label(scene.valence)
+            );
+        }
+        if !self.retention.critical_moments.is_empty() {
+            md.push('\n');
+        }
+        for &index in &self.retention.critical_moments {
+            let scene = &self.retention.scene_scores[index];
+            let _ = writeln!(
+                md,
+                "- Critical: scene {} ({:.1} momentum, {:.1} retention)",
+                index + 1,
+                scene.momentum,
+                scene.retention_score
+            );
+        }
+
+        if let Some(audio) = &self.audio {
+            let _ = write!(
+                md,
+                "\n## Audio\n- Peak before clipping: {:.1} dBFS\n- Clipped samples: {}\n",
+                audio.peak_db(),
+                audio.clipped_samples
+            );
+            for track in &audio.track_peaks {
+                let _ = writeln!(
+                    md,
+                    "- Track `{}` peak: {:.1} dBFS",
+                    track.name,
+                    track.peak_db()
+                );
+            }
+            for gap in &audio.silent_gaps {
+                let _ = writeln!(md, "- Silence: {:.2}s - {:.2}s", gap.start, gap.end);
+            }
+        }
+        md
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "Analysis report: {}\nScore: {}/100 ({})\nAnalyzers: {}\n",
+            self.title,
+            self.score,
+            self.score_breakdown(),
+            self.analyzers.join(", ")
+        );
+        if self.issues.is_empty() {
+            text.push_str("No issues\n");
+        }
+        for issue in &self.issues {
+            let severity = format!("{:?}", issue.severity).to_lowercase();
+            let _ = writeln!(text, "{:<8} {}", severity, Self::issue_line(issue));
+        }
+        let curve: Vec<String> = self
+            .retention
+            .scene_scores
+            .iter()
+            .map(|scene| {
+                format!(
+                    "{:.0} ({})",
+                    scene.retention_score,
+                    Self::valence_label(scene.valence)
+                )
+            })
+            .collect();
+        let _ = writeln!(
+            text,
+            "Retention (valence) by scene: {}",
+            if curve.is_empty() {
+                "none".to_string()
+            } else {
+                curve.join(", ")
+            }
+        );
+        if let Some(audio) = &self.audio {
+            let _ = writeln!(text, "Audio: {}", audio.summary());
+        }
+        text
+    }
+
+    /// A scene's valence in the emotional arc, `-` without scored words
+    fn valence_label(valence: Option<f32>) -> String {
+        valence.map_or("-".to_string(), |valence| format!("{:+.2}", valence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyzer::Analyzer;
+    #[cfg(feature = "render")]
+    use crate::audio::{SilentGap, TrackPeak};
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+    #[cfg(feature = "render")]
+    use serde_json::Value;
+
+    /// `value` with every leaf replaced by its type name and arrays by the shape
+    /// of their first element, so the snapshot only changes with the layout
+    #[cfg(feature = "render")]
+    fn shape(value: &Value) -> Value {
+        match value {
+            Value::Null => "null".into(),
+            Value::Bool(_) => "bool".into(),
+            Value::Number(_) => "number".into(),
+            Value::String(_) => "string".into(),
+            Value::Array(items) => Value::Array(items.iter().take(1).map(shape).collect()),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), shape(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_json_shape_matches_snapshot() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let json = std::fs::read_to_string(root.join("examples/simple.json")).unwrap();
+        let mut script: VideoScript = serde_json::from_str(&json).unwrap();
+        // A long, sparse scene, so the retention lists aren't empty
+        let mut slow = script.scenes[1].clone();
+        slow.duration = 30.0;
+        slow.layers.truncate(1);
+        script.scenes.insert(2, slow);
+
+        let audio = AudioAnalysisReport {
+            peak: 1.2,
+            clipped_samples: 3,
+            track_peaks: vec![TrackPeak {
+                name: "music.mp3".into(),
+                peak: 0.9,
+            }],
+            silent_gaps: vec![SilentGap {
+                start: 1.0,
+                end: 2.5,
+            }],
+        };
+        let config = AnalysisConfig {
+            base_path: root.join("examples"),
+            ..AnalysisConfig::default()
+        };
+        let report = AnalysisReport::analyze(&script, &config, &ExtraAnalyzers::new())
+            .with_audio(Some(audio))
+            .with_script_path(Path::new("examples/simple.json"));
+
+        let actual =
+            shape(&serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap());
+        let snapshot = root.join("tests/snapshots/analysis_report.json");
+        if crate::testing::updating_goldens() {
+            let pretty = serde_json::to_string_pretty(&actual).unwrap();
+            std::fs::write(&snapshot, pretty + "\n").unwrap();
+            return;
+        }
+        let expected: Value =
+            serde_json::from_str(&std::fs::read_to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(
+            actual, expected,
+            "The report's JSON layout changed; if intended, run with UPDATE_GOLDENS=1 \
+             (and bump REPORT_VERSION when fields were removed or changed)"
+        );
+    }
+
+    #[test]
+    fn test_score_is_weighted_and_issues_sorted() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        script.scenes.pop();
+        let report =
+            AnalysisReport::analyze(&script, &AnalysisConfig::default(), &ExtraAnalyzers::new());
+
+        let expected = NARRATIVE_WEIGHT * report.narrative.score as f32
+            + CREDIBILITY_WEIGHT * report.credibility.score as f32
+            + RETENTION_WEIGHT * report.retention.overall_retention_score;
+        assert_eq!(report.score, expected.round() as u32);
+
+        // Without its Payoff the structure is broken
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert_eq!(report.issues[0].message, "Missing 'Payoff' scene");
+        assert!(report
+            .issues
+            .windows(2)
+            .all(|pair| pair[0].severity >= pair[1].severity));
+        assert!(report.has_issues(Severity::Error));
+        assert!(report.has_issues(Severity::Warning));
+
+        let text = report.render(ReportFormat::Text).unwrap();
+        assert!(text.contains("error    [narrative/missing-scene] Missing 'Payoff' scene"));
+        let markdown = report.render(ReportFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("# Analysis Report: Explainer Video"));
+        assert!(markdown.contains("\n## Scenes\n| # | Scene | WPM | Score | Deductions |\n"));
+        let first = &report.narrative.scene_scores[0];
+        assert!(markdown.contains(&format!("| 1 | {} |", first.scene_id)));
+    }
+
+    #[test]
+    fn test_retention_carries_the_emotional_arc() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        script.scenes[0].narration = Some("Your renders crash. It is a disaster.".to_string());
+        script.scenes[1].narration = Some("Rendering frames on a timeline.".to_string());
+        script.scenes[1].layers.clear();
+        let report =
+            AnalysisReport::analyze(&script, &AnalysisConfig::default(), &ExtraAnalyzers::new());
+
+        let retention = &report.retention.scene_scores;
+        assert_eq!(retention[0].valence, Some(-0.5));
+        assert_eq!(retention[1].valence, None);
+
+        let markdown = report.render(ReportFormat::Markdown).unwrap();
+        assert!(markdown.contains("| # | Retention | Momentum | Valence |"));
+        assert!(markdown.contains(&format!(
+            "| 1 | {:.1} | {:.1} | -0.50 |",
+            retention[0].retention_score, retention[0].momentum
+        )));
+        assert!(markdown.contains(&format!("| 2 | {:.1} |", retention[1].retention_score)));
+        let text = report.render(ReportFormat::Text).unwrap();
+        assert!(text.contains(&format!(
+            "Retention (valence) by scene: {:.0} (-0.50), {:.0} (-)",
+            retention[0].retention_score, retention[1].retention_score
+        )));
+        let json = report.render(ReportFormat::Json).unwrap();
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(document["retention"]["scene_scores"][0]["valence"], -0.5);
+        assert!(document["retention"]["scene_scores"][1]
+            .get("valence")
+            .is_none());
+    }
+
+    #[test]
+    fn test_text_only_scores_match_the_report() {
+        let script = ScriptTemplate::generate(TemplateType::Tutorial, &TemplateOptions::new(60.0));
+        let config = AnalysisConfig::default();
+        let report = AnalysisReport::analyze(&script, &config, &ExtraAnalyzers::new());
+
+        let scores = Scores::of(&script, &config.dictionaries, &config.narrative);
+        assert_eq!(scores.overall, report.score);
+        assert_eq!(scores.narrative, report.narrative.score);
+        assert_eq!(scores.credibility, report.credibility.score);
+    }
+
+    #[test]
+    fn test_readability_warnings_are_issues() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        script.scenes[1].narration = Some(
+            "Notwithstanding considerable methodological limitations, the investigators \
+             maintained that longitudinal observations substantiated their hypothesis."
+                .to_string(),
+        );
+        let strict =
+            AnalysisReport::analyze(&script, &AnalysisConfig::default(), &ExtraAnalyzers::new());
+        let issue = strict
+            .issues
+            .iter()
+            .find(|issue| issue.rule == "narrative/readability")
+            .unwrap();
+        assert_eq!(issue.severity, Severity::Warning);
+        assert_eq!(issue.scene_index, Some(1));
+        assert!(issue.message.contains("narration reads at grade"));
+
+        // Without the penalty only the score changes
+        let mut free = AnalysisConfig::default();
+        free.narrative.readability.penalty_per_scene = 0;
+        let lenient = AnalysisReport::analyze(&script, &free, &ExtraAnalyzers::new());
+        assert_eq!(
+            lenient.narrative.score,
+            strict.narrative.score + strict.narrative.readability.penalty
+        );
+        assert_eq!(lenient.issues.len(), strict.issues.len());
+    }
+
+    /// Flags every scene whose narration still has a TODO in it
+    struct TodoAnalyzer;
+
+    impl Analyzer for TodoAnalyzer {
+        fn name(&self) -> &str {
+            "todo"
+        }
+
+        fn analyze(&self, script: &VideoScript, _config: &AnalysisConfig) -> AnalyzerFindings {
+            let findings = script
+                .scenes
+                .iter()
+                .enumerate()
+                .filter(|(_, scene)| scene.narration.as_deref().unwrap_or("").contains("TODO"))
+                .map(|(i, _)| {
+                    Finding::new(Severity::Error, "unfinished", "Narration has a TODO").in_scene(i)
+                })
+                .collect::<Vec<_>>();
+            let score = if findings.is_empty() { 100.0 } else { 0.0 };
+            AnalyzerFindings {
+                findings,
+                score: Some(ScoreContribution { score, weight: 1.0 }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_plugged_in_analyzers_add_issues_and_scores() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        let config = AnalysisConfig::default();
+        let mut analyzers = ExtraAnalyzers::new();
+        analyzers.push(TodoAnalyzer);
+
+        let clean = AnalysisReport::analyze(&script, &config, &analyzers);
+        let built_in = AnalysisReport::analyze(&script, &config, &ExtraAnalyzers::new());
+        assert_eq!(clean.analyzers.last().map(String::as_str), Some("todo"));
+        assert!(!built_in.analyzers.contains(&"todo".to_string()));
+        assert!(!clean.issues.iter().any(|issue| issue.source == "todo"));
+        // Half the weight is the plugin's perfect score
+        assert_eq!(
+            clean.score,
+            ((built_in.score as f32 + 100.0) / 2.0).round() as u32
+        );
+
+        script.scenes[1].narration = Some("TODO: write the body".to_string());
+        let report = AnalysisReport::analyze(&script, &config, &analyzers);
+        let issue = report
+            .issues
+            .iter()
+            .find(|issue| issue.source == "todo")
+            .unwrap();
+        assert_eq!(issue.rule, "todo/unfinished");
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(
+            issue.scene_id.as_deref(),
+            Some(script.scenes[1].id.as_str())
+        );
+        assert!(report.has_issues(Severity::Error));
+        assert!(report.score < clean.score);
+
+        let text = report.render(ReportFormat::Text).unwrap();
+        assert!(text.contains(
+            "Analyzers: narrative, credibility, retention, audio, accessibility, metadata, todo\n"
+        ));
+        assert!(text.contains(", todo 0)"));
+        assert!(text.contains("[todo/unfinished] Narration has a TODO (scene 2)"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.json")),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.txt")),
+            ReportFormat::Text
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.md")),
+            ReportFormat::Markdown
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report")),
+            ReportFormat::Markdown
+        );
+    }
+}
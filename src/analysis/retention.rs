@@ -1,3 +1,9 @@
+use crate::analysis::analyzer::{
+    AnalysisConfig, Analyzer, AnalyzerFindings, Finding, ScoreContribution,
+};
+use crate::analysis::narrative::Severity;
+use crate::analysis::report::RETENTION_WEIGHT;
+use crate::analysis::sentiment::SentimentReport;
 use crate::script::{Scene, VideoScript};
 
 #[derive(Debug, Clone)]
@@ -19,10 +25,40 @@ pub struct SceneRetention {
     pub scene_index: usize,
     pub momentum: f32,        // 0-100
     pub retention_score: f32, // 0-100
+    /// Valence of the scene in the emotional arc (-1 to 1), once overlaid with
+    /// `RetentionHeatmap::overlay_sentiment` and when it has scored words
+    pub valence: Option<f32>,
+}
+
+impl RetentionHeatmap {
+    /// Overlay the emotional arc on the retention curve, so where viewers drop
+    /// off can be read against how each scene feels
+    pub fn overlay_sentiment(&mut self, sentiment: &SentimentReport) {
+        for scene in &mut self.scene_scores {
+            scene.valence = sentiment
+                .scenes
+                .get(scene.scene_index)
+                .filter(|arc| arc.scored_words > 0)
+                .map(|arc| arc.valence);
+        }
+    }
 }
 
 pub struct RetentionAnalyzer;
 
+impl Analyzer for RetentionAnalyzer {
+    fn name(&self) -> &str {
+        "retention"
+    }
+
+    fn analyze(&self, script: &VideoScript, _config: &AnalysisConfig) -> AnalyzerFindings {
+        Self::findings(
+            &Self::generate_heatmap(script),
+            &Self::predict_dropoff(script),
+        )
+    }
+}
+
 impl RetentionAnalyzer {
     /// Calculate momentum for a scene based on pacing and visual density
     pub fn calculate_momentum(scene: &Scene, _fps: u32) -> f32 {
@@ -86,6 +122,30 @@ impl RetentionAnalyzer {
         predictions
     }
 
+    /// The predicted drop-offs as findings, with the heatmap's score
+    pub fn findings(
+        heatmap: &RetentionHeatmap,
+        predictions: &[DropoffPrediction],
+    ) -> AnalyzerFindings {
+        let findings = predictions
+            .iter()
+            .map(|prediction| {
+                let message = format!(
+                    "{:.0}% predicted drop-off ({})",
+                    prediction.predicted_dropoff_percent, prediction.reason
+                );
+                Finding::new(Severity::Info, "dropoff", message).in_scene(prediction.scene_index)
+            })
+            .collect();
+        AnalyzerFindings {
+            findings,
+            score: Some(ScoreContribution {
+                score: heatmap.overall_retention_score,
+                weight: RETENTION_WEIGHT,
+            }),
+        }
+    }
+
     /// Generate a retention heatmap for the entire script
     pub fn generate_heatmap(script: &VideoScript) -> RetentionHeatmap {
         let mut scene_scores = Vec::new();
@@ -103,6 +163,7 @@ impl RetentionAnalyzer {
                 scene_index: i,
                 momentum,
                 retention_score,
+                valence: None,
             });
 
             total_retention += retention_score;
@@ -131,16 +192,19 @@ impl RetentionAnalyzer {
 
     /// Export heatmap to CSV format
     pub fn export_csv(heatmap: &RetentionHeatmap) -> String {
-        let mut csv = String::from("Scene,Momentum,Retention Score,Critical\n");
+        let mut csv = String::from("Scene,Momentum,Retention Score,Critical,Valence\n");
 
         for scene in &heatmap.scene_scores {
             let is_critical = heatmap.critical_moments.contains(&scene.scene_index);
             csv.push_str(&format!(
-                "{},{:.2},{:.2},{}\n",
+                "{},{:.2},{:.2},{},{}\n",
                 scene.scene_index + 1,
                 scene.momentum,
                 scene.retention_score,
-                if is_critical { "YES" } else { "NO" }
+                if is_critical { "YES" } else { "NO" },
+                scene
+                    .valence
+                    .map_or(String::new(), |valence| format!("{:.2}", valence))
             ));
         }
 
@@ -173,16 +237,34 @@ impl Serialize for RetentionHeatmap {
     }
 }
 
+impl Serialize for DropoffPrediction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("DropoffPrediction", 3)?;
+        state.serialize_field("scene_index", &self.scene_index)?;
+        state.serialize_field("predicted_dropoff_percent", &self.predicted_dropoff_percent)?;
+        state.serialize_field("reason", &self.reason)?;
+        state.end()
+    }
+}
+
 impl Serialize for SceneRetention {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("SceneRetention", 3)?;
+        let mut state = serializer.serialize_struct("SceneRetention", 4)?;
         state.serialize_field("scene_index", &self.scene_index)?;
         state.serialize_field("momentum", &self.momentum)?;
         state.serialize_field("retention_score", &self.retention_score)?;
+        match self.valence {
+            Some(valence) => state.serialize_field("valence", &valence)?,
+            None => state.skip_field("valence")?,
+        }
         state.end()
     }
 }
@@ -199,6 +281,7 @@ mod tests {
                 source: format!("image_{}.png", i).into(),
                 effects: vec![],
                 transform: Default::default(),
+                crop: None,
             });
         }
 
@@ -208,6 +291,8 @@ mod tests {
             duration,
             layers,
             transition: None,
+            narration: None,
+            title: None,
         }
     }
 
@@ -240,6 +325,7 @@ mod tests {
                 duration: 30.0,
                 description: None,
                 citations: vec![],
+                watermark: None,
             },
             scenes: vec![
                 create_test_scene(5.0, 3),  // Good momentum
@@ -269,6 +355,7 @@ mod tests {
                 duration: 20.0,
                 description: None,
                 citations: vec![],
+                watermark: None,
             },
             scenes: vec![
                 create_test_scene(5.0, 3),
@@ -290,13 +377,14 @@ mod tests {
                 scene_index: 0,
                 momentum: 80.0,
                 retention_score: 85.0,
+                valence: Some(-0.5),
             }],
             overall_retention_score: 85.0,
             critical_moments: vec![],
         };
 
         let csv = RetentionAnalyzer::export_csv(&heatmap);
-        assert!(csv.contains("Scene,Momentum,Retention Score"));
-        assert!(csv.contains("1,80.00,85.00"));
+        assert!(csv.contains("Scene,Momentum,Retention Score,Critical,Valence"));
+        assert!(csv.contains("1,80.00,85.00,NO,-0.50"));
     }
 }
@@ -0,0 +1,482 @@
+//! The emotional arc of a script: each scene's valence from a word list, and
+//! the shapes that lose viewers, like a flat line or a payoff that lets down.
+
+use crate::analysis::narrative::{Severity, StructureRecommendation};
+use crate::script::{Layer, Scene, SceneType, VideoScript};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Valence of words, from -5 (very negative) to 5 (very positive), in the style
+/// of the AFINN list
+pub const DEFAULT_LEXICON: &[(&str, i32)] = &[
+    ("abandon", -2),
+    ("afraid", -2),
+    ("amazing", 4),
+    ("angry", -3),
+    ("annoying", -2),
+    ("anxious", -2),
+    ("awesome", 4),
+    ("awful", -3),
+    ("bad", -3),
+    ("beautiful", 3),
+    ("benefit", 2),
+    ("best", 3),
+    ("better", 2),
+    ("boring", -3),
+    ("brilliant", 4),
+    ("broken", -2),
+    ("bug", -2),
+    ("calm", 2),
+    ("celebrate", 3),
+    ("chaos", -2),
+    ("cheap", -1),
+    ("clean", 2),
+    ("confident", 2),
+    ("confused", -2),
+    ("crash", -2),
+    ("crisis", -3),
+    ("danger", -2),
+    ("dead", -3),
+    ("delight", 3),
+    ("destroy", -3),
+    ("difficult", -1),
+    ("disaster", -3),
+    ("easy", 1),
+    ("effective", 2),
+    ("enjoy", 2),
+    ("error", -2),
+    ("excellent", 3),
+    ("excited", 3),
+    ("fail", -2),
+    ("failure", -2),
+    ("fantastic", 4),
+    ("fast", 1),
+    ("fear", -2),
+    ("fix", 1),
+    ("free", 1),
+    ("frustrating", -2),
+    ("fun", 4),
+    ("good", 3),
+    ("great", 3),
+    ("grow", 1),
+    ("happy", 3),
+    ("hard", -1),
+    ("hate", -3),
+    ("help", 2),
+    ("hope", 2),
+    ("hurt", -2),
+    ("ideal", 2),
+    ("improve", 2),
+    ("love", 3),
+    ("lose", -3),
+    ("loss", -3),
+    ("mess", -2),
+    ("mistake", -2),
+    ("nice", 3),
+    ("pain", -2),
+    ("panic", -3),
+    ("perfect", 3),
+    ("poor", -2),
+    ("powerful", 2),
+    ("problem", -2),
+    ("proud", 2),
+    ("risk", -2),
+    ("sad", -2),
+    ("safe", 1),
+    ("save", 2),
+    ("scary", -2),
+    ("simple", 1),
+    ("slow", -1),
+    ("smart", 1),
+    ("solve", 1),
+    ("strong", 2),
+    ("struggle", -2),
+    ("stuck", -2),
+    ("success", 2),
+    ("terrible", -3),
+    ("threat", -2),
+    ("tired", -2),
+    ("trouble", -2),
+    ("ugly", -3),
+    ("waste", -1),
+    ("win", 4),
+    ("wonderful", 4),
+    ("worried", -3),
+    ("worse", -3),
+    ("worst", -3),
+    ("wrong", -2),
+];
+
+/// Words that flip the valence of the word after them
+const NEGATORS: &[&str] = &[
+    "not", "no", "never", "don't", "doesn't", "isn't", "wasn't", "can't", "won't", "nothing",
+];
+
+/// Largest valence a word can have; scene valences are divided by it
+const MAX_WORD_VALENCE: f32 = 5.0;
+
+/// A Hook at or below this valence sets up tension
+pub const NEUTRAL_VALENCE: f32 = 0.1;
+
+/// How much a later scene must rise above a tense Hook to resolve it
+pub const CONTRAST: f32 = 0.3;
+
+/// Scenes whose valences all lie within this range of each other are a flat run
+pub const FLAT_TOLERANCE: f32 = 0.1;
+
+/// Consecutive scenes it takes to flag a flat arc
+pub const MIN_FLAT_SCENES: usize = 3;
+
+/// Word valences the arc is scored with
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    /// Where the words came from, for messages: `default`, `config` or a file path
+    pub origin: String,
+    words: HashMap<String, i32>,
+}
+
+impl Lexicon {
+    pub fn new(origin: impl Into<String>, words: HashMap<String, i32>) -> Self {
+        Self {
+            origin: origin.into(),
+            words: words
+                .into_iter()
+                .map(|(word, valence)| (word.to_lowercase(), valence))
+                .collect(),
+        }
+    }
+
+    /// Entries of an AFINN-style file: a word, whitespace, and an integer valence
+    /// per line. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(content: &str) -> Result<HashMap<String, i32>> {
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                let (word, valence) = line
+                    .split_once(char::is_whitespace)
+                    .with_context(|| format!("Line {}: expected a word and a valence", i + 1))?;
+                let valence = valence
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Line {}: invalid valence '{}'", i + 1, valence))?;
+                Ok((word.trim().to_lowercase(), valence))
+            })
+            .collect()
+    }
+
+    pub fn valence(&self, word: &str) -> Option<i32> {
+        self.words.get(word).copied()
+    }
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Self::new(
+            "default",
+            DEFAULT_LEXICON
+                .iter()
+                .map(|(word, valence)| (word.to_string(), *valence))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneSentiment {
+    pub scene_index: usize,
+    /// Mean valence of the scene's scored words, from -1 to 1; 0 without any
+    pub valence: f32,
+    /// Words of the on-screen text and narration found in the lexicon
+    pub scored_words: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SentimentReport {
+    /// The arc, one entry per scene
+    pub scenes: Vec<SceneSentiment>,
+    /// Problems with the arc; reported with the structure recommendations
+    #[serde(skip)]
+    pub recommendations: Vec<StructureRecommendation>,
+}
+
+pub struct SentimentAnalyzer;
+
+impl SentimentAnalyzer {
+    pub fn analyze(script: &VideoScript, lexicon: &Lexicon) -> SentimentReport {
+        let scenes: Vec<SceneSentiment> = script
+            .scenes
+            .iter()
+            .enumerate()
+            .map(|(i, scene)| Self::score(i, scene, lexicon))
+            .collect();
+        let recommendations = Self::check_arc(script, &scenes);
+        SentimentReport {
+            scenes,
+            recommendations,
+        }
+    }
+
+    fn score(scene_index: usize, scene: &Scene, lexicon: &Lexicon) -> SceneSentiment {
+        let mut texts: Vec<&str> = scene
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        texts.extend(scene.narration.as_deref());
+
+        let mut total = 0;
+        let mut scored_words = 0;
+        for text in texts {
+            let mut negated = false;
+            for word in text.unicode_words() {
+                let word = word.to_lowercase();
+                if let Some(valence) = lexicon.valence(&word) {
+                    total += if negated { -valence } else { valence };
+                    scored_words += 1;
+                }
+                negated = NEGATORS.contains(&word.as_str());
+            }
+        }
+
+        let valence = if scored_words == 0 {
+            0.0
+        } else {
+            (total as f32 / scored_words as f32 / MAX_WORD_VALENCE).clamp(-1.0, 1.0)
+        };
+        SceneSentiment {
+            scene_index,
+            valence,
+            scored_words,
+        }
+    }
+
+    /// Scenes without scored words have no known valence and are left out
+    fn check_arc(script: &VideoScript, scenes: &[SceneSentiment]) -> Vec<StructureRecommendation> {
+        let mut recommendations = Vec::new();
+        let scored: Vec<&SceneSentiment> = scenes.iter().filter(|s| s.scored_words > 0).collect();
+        let scene_type = |s: &SceneSentiment| &script.scenes[s.scene_index].scene_type;
+
+        // A tense opening needs a release later on
+        if let Some(position) = scored
+            .iter()
+            .position(|s| *scene_type(s) == SceneType::Hook)
+        {
+            let hook = scored[position];
+            let later = &scored[position + 1..];
+            let resolved = later.iter().any(|s| s.valence >= hook.valence + CONTRAST);
+            if hook.valence <= NEUTRAL_VALENCE && !later.is_empty() && !resolved {
+                recommendations.push(recommendation(
+                    Severity::Info,
+                    format!(
+                        "The Hook in scene {} opens on a negative or neutral note ({:+.2}) and no later scene lifts it. Resolve the tension with a clearly more positive turn.",
+                        hook.scene_index + 1,
+                        hook.valence
+                    ),
+                ));
+            }
+        }
+
+        // Runs of consecutive scenes within the tolerance of each other
+        let mut start = 0;
+        while start < scored.len() {
+            let mut end = start + 1;
+            let (mut low, mut high) = (scored[start].valence, scored[start].valence);
+            while end < scored.len()
+                && scored[end].scene_index == scored[end - 1].scene_index + 1
+                && scored[end].valence.max(high) - scored[end].valence.min(low) <= FLAT_TOLERANCE
+            {
+                low = low.min(scored[end].valence);
+                high = high.max(scored[end].valence);
+                end += 1;
+            }
+            if end - start >= MIN_FLAT_SCENES {
+                recommendations.push(recommendation(
+                    Severity::Warning,
+                    format!(
+                        "Scenes {}-{} stay at the same emotional level ({:+.2} to {:+.2}). Alternate tension and relief to keep viewers watching.",
+                        scored[start].scene_index + 1,
+                        scored[end - 1].scene_index + 1,
+                        low,
+                        high
+                    ),
+                ));
+            }
+            start = end;
+        }
+
+        let mean = |wanted: SceneType| {
+            let valences: Vec<f32> = scored
+                .iter()
+                .filter(|s| *scene_type(s) == wanted)
+                .map(|s| s.valence)
+                .collect();
+            (!valences.is_empty()).then(|| valences.iter().sum::<f32>() / valences.len() as f32)
+        };
+        if let (Some(body), Some(payoff)) = (mean(SceneType::Body), mean(SceneType::Payoff)) {
+            if payoff < body {
+                recommendations.push(recommendation(
+                    Severity::Info,
+                    format!(
+                        "The Payoff ({:+.2}) is less positive than the Body on average ({:+.2}). End on the high point.",
+                        payoff, body
+                    ),
+                ));
+            }
+        }
+
+        recommendations
+    }
+}
+
+fn recommendation(severity: Severity, message: String) -> StructureRecommendation {
+    StructureRecommendation {
+        severity,
+        category: "Emotional Arc".to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Metadata, Resolution};
+
+    fn create_scene(scene_type: SceneType, narration: &str) -> Scene {
+        Scene {
+            id: format!("{:?}", scene_type).to_lowercase(),
+            scene_type,
+            duration: 5.0,
+            layers: vec![],
+            transition: None,
+            narration: Some(narration.to_string()),
+            title: None,
+        }
+    }
+
+    fn create_script(scenes: Vec<Scene>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 0.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes,
+            audio: None,
+        }
+    }
+
+    fn analyze(script: &VideoScript) -> SentimentReport {
+        SentimentAnalyzer::analyze(script, &Lexicon::default())
+    }
+
+    #[test]
+    fn test_arc_series() {
+        let script = create_script(vec![
+            create_scene(SceneType::Hook, "Your renders crash. It is a disaster."),
+            create_scene(SceneType::Body, "The fix is simple."),
+            create_scene(SceneType::Body, "Rendering frames on a timeline."),
+            create_scene(
+                SceneType::Payoff,
+                "Now it is fast and the results are amazing!",
+            ),
+        ]);
+        let report = analyze(&script);
+
+        let valences: Vec<f32> = report.scenes.iter().map(|s| s.valence).collect();
+        // (-2 - 3) / 2 / 5, (1 + 1) / 2 / 5, no scored words, (1 + 4) / 2 / 5
+        let expected = [-0.5, 0.2, 0.0, 0.5];
+        for (valence, expected) in valences.iter().zip(expected) {
+            assert!((valence - expected).abs() < 1e-6, "{:?}", valences);
+        }
+        assert_eq!(report.scenes[2].scored_words, 0);
+        // Tension, relief, and a payoff on top
+        assert!(
+            report.recommendations.is_empty(),
+            "{:?}",
+            report.recommendations
+        );
+    }
+
+    #[test]
+    fn test_negation_flips_valence() {
+        let script = create_script(vec![create_scene(SceneType::Body, "This is not good.")]);
+        assert!((analyze(&script).scenes[0].valence + 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_flat_arc() {
+        let script = create_script(vec![
+            create_scene(SceneType::Hook, "A good start."),
+            create_scene(SceneType::Body, "Great tools."),
+            create_scene(SceneType::Body, "Nice results."),
+            create_scene(SceneType::Payoff, "Amazing!"),
+        ]);
+        let report = analyze(&script);
+
+        assert_eq!(report.recommendations.len(), 1);
+        let flat = &report.recommendations[0];
+        assert_eq!(flat.severity, Severity::Warning);
+        assert_eq!(flat.category, "Emotional Arc");
+        assert!(flat
+            .message
+            .starts_with("Scenes 1-3 stay at the same emotional level"));
+
+        // Two alike scenes aren't a flat arc yet
+        let mut short = script.clone();
+        short.scenes.remove(1);
+        assert!(analyze(&short).recommendations.is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_hook_and_weak_payoff() {
+        let script = create_script(vec![
+            create_scene(SceneType::Hook, "The worst mistake."),
+            create_scene(SceneType::Body, "A great and wonderful way out."),
+            create_scene(SceneType::Payoff, "Still a problem, sadly."),
+        ]);
+        let messages: Vec<String> = analyze(&script)
+            .recommendations
+            .into_iter()
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(messages.len(), 1, "{:?}", messages);
+        assert!(messages[0].starts_with("The Payoff (-0.40) is less positive than the Body"));
+
+        let script = create_script(vec![
+            create_scene(SceneType::Hook, "The worst mistake."),
+            create_scene(SceneType::Body, "Still a problem."),
+        ]);
+        let recommendations = analyze(&script).recommendations;
+        assert_eq!(recommendations.len(), 1);
+        assert!(recommendations[0]
+            .message
+            .starts_with("The Hook in scene 1 opens"));
+    }
+
+    #[test]
+    fn test_custom_lexicon() {
+        let words = Lexicon::parse("# Portuguese\nótimo 3\n\nruim\t-3\n").unwrap();
+        assert_eq!(words.get("ruim"), Some(&-3));
+        let lexicon = Lexicon::new("config", words);
+
+        let script = create_script(vec![create_scene(SceneType::Body, "Ótimo, good")]);
+        let report = SentimentAnalyzer::analyze(&script, &lexicon);
+        // Only the custom words count
+        assert_eq!(report.scenes[0].scored_words, 1);
+        assert!((report.scenes[0].valence - 0.6).abs() < 1e-6);
+
+        assert!(Lexicon::parse("ótimo great").is_err());
+    }
+}
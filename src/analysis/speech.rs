@@ -0,0 +1,158 @@
+#[cfg(feature = "render")]
+use crate::renderer::WaveformAnalysis;
+use crate::script::{AudioTrackType, VideoScript};
+use std::path::Path;
+
+/// Analysis windows per second of voiceover audio
+const WINDOWS_PER_SECOND: u32 = 20;
+/// Window level above which the voiceover counts as speech (about -40 dBFS RMS)
+const SPEECH_THRESHOLD: f32 = 0.014;
+
+/// Where the voiceover is audible along the video timeline
+#[derive(Debug, Clone)]
+pub struct SpeechActivity {
+    /// One entry per 1/20s window of the video
+    active: Vec<bool>,
+}
+
+impl SpeechActivity {
+    /// Decode the script's voiceover tracks (relative to `base_path`) and mark the
+    /// windows where any of them is above the speech threshold.
+    /// Returns `None` when the script has no readable voiceover.
+    pub fn from_script(script: &VideoScript, base_path: &Path) -> Option<Self> {
+        let audio = script.audio.as_ref()?;
+        let window_count = (script.metadata.duration * WINDOWS_PER_SECOND as f32).ceil() as usize;
+        let mut active = vec![false; window_count];
+        let mut found = false;
+
+        for track in audio
+            .tracks
+            .iter()
+            .filter(|t| t.track_type == AudioTrackType::Voiceover)
+        {
+            let path = if track.source.is_absolute() {
+                track.source.clone()
+            } else {
+                base_path.join(&track.source)
+            };
+            let Some(levels) = voice_levels(&path) else {
+                continue;
+            };
+            found = true;
+
+            // Map source windows to timeline windows, honoring start time and trim
+            let first = (track.trim_start * WINDOWS_PER_SECOND as f32) as usize;
+            let last = track
+                .trim_end
+                .map_or(usize::MAX, |end| (end * WINDOWS_PER_SECOND as f32) as usize);
+            let offset = (track.start_time * WINDOWS_PER_SECOND as f32) as usize;
+
+            for (index, level) in levels.iter().enumerate() {
+                if index < first || index >= last {
+                    continue;
+                }
+                if let Some(slot) = active.get_mut(offset + index - first) {
+                    *slot |= *level > SPEECH_THRESHOLD;
+                }
+            }
+        }
+
+        found.then_some(Self { active })
+    }
+
+    /// Build from per-window activity (20 windows per second)
+    pub fn from_windows(active: Vec<bool>) -> Self {
+        Self { active }
+    }
+
+    /// Fraction of `[start, end)` seconds in which speech is present
+    pub fn coverage(&self, start: f32, end: f32) -> f32 {
+        let from = (start * WINDOWS_PER_SECOND as f32).round() as usize;
+        let to = ((end * WINDOWS_PER_SECOND as f32).round() as usize).max(from);
+        if to == from {
+            return 0.0;
+        }
+        let spoken = (from..to)
+            .filter(|&i| self.active.get(i).copied().unwrap_or(false))
+            .count();
+        spoken as f32 / (to - from) as f32
+    }
+}
+
+/// RMS level of each window of the audio file at `path`, `None` when it can't
+/// be decoded (always, without the `render` feature)
+#[cfg(feature = "render")]
+fn voice_levels(path: &Path) -> Option<Vec<f32>> {
+    let analysis = WaveformAnalysis::from_file(path, WINDOWS_PER_SECOND).ok()?;
+    Some(analysis.levels().to_vec())
+}
+
+#[cfg(not(feature = "render"))]
+fn voice_levels(_path: &Path) -> Option<Vec<f32>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        // 1s of speech followed by 1s of silence
+        let mut windows = vec![true; 20];
+        windows.extend(vec![false; 20]);
+        let activity = SpeechActivity::from_windows(windows);
+
+        assert_eq!(activity.coverage(0.0, 1.0), 1.0);
+        assert_eq!(activity.coverage(1.0, 2.0), 0.0);
+        assert!((activity.coverage(0.5, 1.5) - 0.5).abs() < 1e-6);
+        // Past the end counts as silence
+        assert!((activity.coverage(0.0, 4.0) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_from_script_places_voiceover_on_timeline() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        // 1s of tone, then 1s of silence
+        let mut writer = hound::WavWriter::create(dir.path().join("vo.wav"), spec).unwrap();
+        for i in 0..16000 {
+            let sample = if i < 8000 {
+                0.3 * (i as f32 * 0.2).sin()
+            } else {
+                0.0
+            };
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let script: VideoScript = serde_json::from_value(serde_json::json!({
+            "metadata": {"title": "T", "resolution": "1280x720", "fps": 30, "duration": 4.0},
+            "scenes": [{"id": "s1", "duration": 4.0, "layers": [{"type": "image", "source": "t.png"}]}],
+            "audio": {"tracks": [{"source": "vo.wav", "track_type": "voiceover", "start_time": 1.0}]}
+        }))
+        .unwrap();
+
+        let activity = SpeechActivity::from_script(&script, dir.path()).unwrap();
+        assert_eq!(activity.coverage(0.0, 1.0), 0.0);
+        assert!(activity.coverage(1.0, 2.0) > 0.9);
+        assert_eq!(activity.coverage(2.0, 4.0), 0.0);
+    }
+
+    #[test]
+    fn test_no_voiceover() {
+        let script: VideoScript = serde_json::from_value(serde_json::json!({
+            "metadata": {"title": "T", "resolution": "1280x720", "fps": 30, "duration": 4.0},
+            "scenes": [{"id": "s1", "duration": 4.0, "layers": [{"type": "image", "source": "t.png"}]}],
+            "audio": {"tracks": [{"source": "music.wav"}]}
+        }))
+        .unwrap();
+        assert!(SpeechActivity::from_script(&script, Path::new(".")).is_none());
+    }
+}
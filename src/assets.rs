@@ -1,6 +1,7 @@
-use anyhow::{Context, Result};
+use crate::error::{Result, VideoEngineError};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Asset types that can be loaded
 #[derive(Debug, Clone)]
@@ -16,6 +17,8 @@ pub struct ImageAsset {
     pub path: PathBuf,
     pub width: u32,
     pub height: u32,
+    /// Decoded RGBA pixels, `None` when the file is not a readable image
+    pub pixels: Option<Arc<image::RgbaImage>>,
 }
 
 /// Video asset (placeholder for now, will use FFmpeg later)
@@ -28,7 +31,7 @@ pub struct VideoAsset {
     pub duration: f32,
 }
 
-/// Font asset (placeholder for now)
+/// Font asset (raw TrueType/OpenType bytes, parsed when text is drawn)
 #[derive(Debug, Clone)]
 pub struct FontAsset {
     pub path: PathBuf,
@@ -55,22 +58,26 @@ impl AssetLoader {
         &self.base_path
     }
 
-    /// Load an image asset (stub for now)
+    /// Load and decode an image asset
     pub fn load_image(&mut self, path: &Path) -> Result<&ImageAsset> {
         let full_path = self.resolve_path(path);
 
         if !self.assets.contains_key(&full_path) {
             // Verify file exists
             if !full_path.exists() {
-                anyhow::bail!("Image file not found: {}", full_path.display());
+                return Err(VideoEngineError::AssetNotFound(full_path));
             }
 
-            // TODO: Use actual image library to load pixels
-            // For now, create a stub with placeholder dimensions
+            // Undecodable files stay loadable so renderers can draw a placeholder
+            let pixels = image::open(&full_path)
+                .ok()
+                .map(|img| Arc::new(img.to_rgba8()));
+            let (width, height) = pixels.as_ref().map_or((0, 0), |img| img.dimensions());
             let asset = Asset::Image(ImageAsset {
                 path: full_path.clone(),
-                width: 1920,
-                height: 1080,
+                width,
+                height,
+                pixels,
             });
 
             self.assets.insert(full_path.clone(), asset);
@@ -78,7 +85,10 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Image(img) => Ok(img),
-            _ => anyhow::bail!("Asset is not an image"),
+            _ => Err(VideoEngineError::decode(
+                &full_path,
+                "already loaded as another kind of asset, not an image",
+            )),
         }
     }
 
@@ -89,7 +99,7 @@ impl AssetLoader {
         if !self.assets.contains_key(&full_path) {
             // Verify file exists
             if !full_path.exists() {
-                anyhow::bail!("Video file not found: {}", full_path.display());
+                return Err(VideoEngineError::AssetNotFound(full_path));
             }
 
             // TODO: Use FFmpeg to get actual video properties
@@ -107,7 +117,10 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Video(vid) => Ok(vid),
-            _ => anyhow::bail!("Asset is not a video"),
+            _ => Err(VideoEngineError::decode(
+                &full_path,
+                "already loaded as another kind of asset, not a video",
+            )),
         }
     }
 
@@ -116,8 +129,10 @@ impl AssetLoader {
         let full_path = self.resolve_path(path);
 
         if !self.assets.contains_key(&full_path) {
-            let data = std::fs::read(&full_path)
-                .with_context(|| format!("Failed to load font: {}", full_path.display()))?;
+            let data = std::fs::read(&full_path).map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => VideoEngineError::AssetNotFound(full_path.clone()),
+                _ => VideoEngineError::io("load font", &full_path)(e),
+            })?;
 
             let asset = Asset::Font(FontAsset {
                 path: full_path.clone(),
@@ -129,12 +144,15 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Font(font) => Ok(font),
-            _ => anyhow::bail!("Asset is not a font"),
+            _ => Err(VideoEngineError::decode(
+                &full_path,
+                "already loaded as another kind of asset, not a font",
+            )),
         }
     }
 
     /// Resolve a path relative to the base path
-    fn resolve_path(&self, path: &Path) -> PathBuf {
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
         if path.is_absolute() {
             path.to_path_buf()
         } else {
@@ -214,13 +232,36 @@ mod tests {
         assert_eq!(stats.total, 1);
     }
 
+    #[test]
+    fn test_load_image_decodes_pixels() {
+        let temp_dir = TempDir::new().unwrap();
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([1, 2, 3, 255]))
+            .save(temp_dir.path().join("real.png"))
+            .unwrap();
+        fs::write(temp_dir.path().join("fake.png"), b"not an image").unwrap();
+
+        let mut loader = AssetLoader::new(temp_dir.path());
+        let real = loader.load_image(Path::new("real.png")).unwrap();
+        assert_eq!((real.width, real.height), (3, 2));
+        assert_eq!(
+            real.pixels.as_ref().unwrap().get_pixel(2, 1).0,
+            [1, 2, 3, 255]
+        );
+
+        let fake = loader.load_image(Path::new("fake.png")).unwrap();
+        assert!(fake.pixels.is_none());
+    }
+
     #[test]
     fn test_load_nonexistent_image() {
         let temp_dir = TempDir::new().unwrap();
         let mut loader = AssetLoader::new(temp_dir.path());
 
         let result = loader.load_image(Path::new("nonexistent.png"));
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(VideoEngineError::AssetNotFound(path)) if path.ends_with("nonexistent.png")
+        ));
     }
 
     #[test]
@@ -0,0 +1,184 @@
+use serde::Serialize;
+
+/// Thresholds for the mix analysis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioAnalysisSettings {
+    /// Frames quieter than this (dBFS, on every channel) count as silent
+    pub silence_threshold_db: f32,
+    /// Silent stretches shorter than this (seconds) are not reported
+    pub min_silence_seconds: f32,
+}
+
+impl Default for AudioAnalysisSettings {
+    fn default() -> Self {
+        Self {
+            silence_threshold_db: -60.0,
+            min_silence_seconds: 1.0,
+        }
+    }
+}
+
+/// Peak contribution of a single track to the mix
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackPeak {
+    pub name: String,
+    /// Peak sample value after volume, fades, ducking and normalization gain
+    pub peak: f32,
+}
+
+impl TrackPeak {
+    /// Peak in dBFS
+    pub fn peak_db(&self) -> f32 {
+        20.0 * self.peak.max(1e-10).log10()
+    }
+}
+
+/// A stretch of the mix below the silence threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SilentGap {
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Levels of the mix before the soft clipper, and silent stretches after it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AudioAnalysisReport {
+    /// Peak sample value before soft clipping (1.0 = 0 dBFS)
+    pub peak: f32,
+    /// Samples that exceeded ±1.0 and were squashed by the soft clipper
+    pub clipped_samples: u64,
+    pub track_peaks: Vec<TrackPeak>,
+    pub silent_gaps: Vec<SilentGap>,
+}
+
+impl AudioAnalysisReport {
+    /// Peak before soft clipping in dBFS
+    pub fn peak_db(&self) -> f32 {
+        20.0 * self.peak.max(1e-10).log10()
+    }
+
+    /// One-line human-readable summary
+    pub fn summary(&self) -> String {
+        let mut summary = format!("peak {:.1} dBFS", self.peak_db());
+        if self.clipped_samples > 0 {
+            summary.push_str(&format!(", {} samples clipped", self.clipped_samples));
+        }
+        if !self.silent_gaps.is_empty() {
+            summary.push_str(&format!(", {} silent gaps", self.silent_gaps.len()));
+        }
+        summary
+    }
+}
+
+/// Finds silent stretches in an interleaved stream, one chunk at a time
+#[cfg(feature = "render")]
+pub(crate) struct SilenceDetector {
+    threshold: f32,
+    min_frames: usize,
+    sample_rate: f32,
+    channels: usize,
+    frame: usize,
+    silent_since: Option<usize>,
+    gaps: Vec<SilentGap>,
+}
+
+#[cfg(feature = "render")]
+impl SilenceDetector {
+    pub(crate) fn new(settings: &AudioAnalysisSettings, sample_rate: u32, channels: u32) -> Self {
+        Self {
+            threshold: 10f32.powf(settings.silence_threshold_db / 20.0),
+            min_frames: (settings.min_silence_seconds.max(0.0) * sample_rate as f32) as usize,
+            sample_rate: sample_rate as f32,
+            channels: channels.max(1) as usize,
+            frame: 0,
+            silent_since: None,
+            gaps: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_samples(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            let silent = frame.iter().all(|s| s.abs() < self.threshold);
+            match (silent, self.silent_since) {
+                (true, None) => self.silent_since = Some(self.frame),
+                (false, Some(start)) => {
+                    self.close_gap(start);
+                    self.silent_since = None;
+                }
+                _ => {}
+            }
+            self.frame += 1;
+        }
+    }
+
+    fn close_gap(&mut self, start: usize) {
+        if self.frame - start >= self.min_frames.max(1) {
+            self.gaps.push(SilentGap {
+                start: start as f32 / self.sample_rate,
+                end: self.frame as f32 / self.sample_rate,
+            });
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<SilentGap> {
+        if let Some(start) = self.silent_since.take() {
+            self.close_gap(start);
+        }
+        self.gaps
+    }
+}
+
+#[cfg(all(test, feature = "render"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gap_spanning_chunks() {
+        let settings = AudioAnalysisSettings {
+            silence_threshold_db: -60.0,
+            min_silence_seconds: 0.5,
+        };
+        let mut detector = SilenceDetector::new(&settings, 100, 1);
+        let mut signal = vec![0.5; 100];
+        signal.extend(vec![0.0; 80]);
+        signal.extend(vec![0.5; 100]);
+        // A short dip is ignored
+        signal.extend(vec![0.0; 10]);
+        signal.extend(vec![0.5; 10]);
+
+        for chunk in signal.chunks(7) {
+            detector.add_samples(chunk);
+        }
+        assert_eq!(
+            detector.finish(),
+            vec![SilentGap {
+                start: 1.0,
+                end: 1.8
+            }]
+        );
+    }
+
+    #[test]
+    fn test_trailing_silence_is_reported() {
+        let mut detector = SilenceDetector::new(&AudioAnalysisSettings::default(), 10, 2);
+        detector.add_samples(&[0.5, 0.5]);
+        detector.add_samples(&[0.0; 40]);
+        assert_eq!(
+            detector.finish(),
+            vec![SilentGap {
+                start: 0.1,
+                end: 2.1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let report = AudioAnalysisReport {
+            peak: 2.0,
+            clipped_samples: 12,
+            ..Default::default()
+        };
+        assert_eq!(report.summary(), "peak 6.0 dBFS, 12 samples clipped");
+    }
+}
@@ -0,0 +1,254 @@
+use crate::error::{Result, VideoEngineError};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tracing::warn;
+
+/// Decodes audio files into raw samples (f32, interleaved)
+pub struct AudioDecoder;
+
+/// Interleaved f32 samples decoded from a single packet
+#[derive(Debug, Clone)]
+pub struct PacketSamples {
+    pub samples: Vec<f32>,
+}
+
+/// Packet-by-packet decoder over an audio file. Only one packet is held in memory at a time.
+pub struct AudioStream {
+    path: PathBuf,
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    channels: u32,
+    frames: Option<u64>,
+    finished: bool,
+}
+
+impl AudioStream {
+    /// Sample rate of the decoded audio
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Channel count of the decoded audio
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Total frame count if the container header provides it
+    pub fn frames(&self) -> Option<u64> {
+        self.frames
+    }
+
+    /// The file being decoded
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Decode the rest of the stream into one buffer. A packet that fails to
+    /// decode ends it with a warning.
+    pub fn read_to_end(self) -> Vec<f32> {
+        let mut all_samples = Vec::new();
+        for packet in self {
+            match packet {
+                Ok(packet) => all_samples.extend_from_slice(&packet.samples),
+                Err(e) => {
+                    warn!("{}", e);
+                    break;
+                }
+            }
+        }
+        all_samples
+    }
+
+    /// Total frame count, from the header when available, otherwise by
+    /// decoding the rest of the stream without keeping the samples
+    pub fn frame_count(self) -> Result<u64> {
+        if let Some(frames) = self.frames {
+            return Ok(frames);
+        }
+
+        let channels = self.channels.max(1) as u64;
+        let mut frames = 0;
+        for packet in self {
+            frames += packet?.samples.len() as u64 / channels;
+        }
+        Ok(frames)
+    }
+}
+
+impl Iterator for AudioStream {
+    type Item = Result<PacketSamples>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // End of stream (or an unreadable packet) ends iteration
+        while let Ok(packet) = self.format.next_packet() {
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            return match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let mut sample_buf =
+                        SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+                    sample_buf.copy_interleaved_ref(decoded);
+                    Some(Ok(PacketSamples {
+                        samples: sample_buf.samples().to_vec(),
+                    }))
+                }
+                Err(e) => {
+                    self.finished = true;
+                    Some(Err(VideoEngineError::decode(
+                        &self.path,
+                        format!("Error decoding packet: {}", e),
+                    )))
+                }
+            };
+        }
+
+        self.finished = true;
+        None
+    }
+}
+
+impl AudioDecoder {
+    /// Open an audio file for packet-by-packet decoding
+    pub fn stream(path: &Path) -> Result<AudioStream> {
+        let src = File::open(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => VideoEngineError::AssetNotFound(path.to_path_buf()),
+            _ => VideoEngineError::io("open audio file", path)(e),
+        })?;
+        let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+
+        let meta_opts: MetadataOptions = Default::default();
+        let fmt_opts: FormatOptions = Default::default();
+
+        let probed = symphonia::default::get_probe()
+            .format(&hint, mss, &fmt_opts, &meta_opts)
+            .map_err(|e| {
+                VideoEngineError::decode(path, format!("Unsupported audio format: {}", e))
+            })?;
+
+        let format = probed.format;
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| VideoEngineError::decode(path, "No supported audio track found"))?;
+
+        let dec_opts: DecoderOptions = Default::default();
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &dec_opts)
+            .map_err(|e| VideoEngineError::decode(path, format!("Unsupported codec: {}", e)))?;
+
+        let track_id = track.id;
+        let sample_rate = track.codec_params.sample_rate.unwrap_or(44100);
+        let channels = track.codec_params.channels.unwrap_or_default().count() as u32;
+        let frames = track.codec_params.n_frames;
+
+        Ok(AudioStream {
+            path: path.to_path_buf(),
+            format,
+            decoder,
+            track_id,
+            sample_rate,
+            channels,
+            frames,
+            finished: false,
+        })
+    }
+
+    /// Decode an audio file to a vector of samples (f32)
+    /// Returns (samples, sample_rate, channels)
+    pub fn decode(path: &Path) -> Result<(Vec<f32>, u32, u32)> {
+        let stream = Self::stream(path)?;
+        let sample_rate = stream.sample_rate();
+        let channels = stream.channels();
+        Ok((stream.read_to_end(), sample_rate, channels))
+    }
+
+    /// Duration of an audio file in seconds, read from the header when available
+    pub fn duration(path: &Path) -> Result<f32> {
+        let stream = Self::stream(path)?;
+        let sample_rate = stream.sample_rate().max(1);
+        Ok(stream.frame_count()? as f32 / sample_rate as f32)
+    }
+
+    /// Total frame count of an audio file, from the header when available,
+    /// otherwise by decoding the stream without keeping the samples
+    pub fn frame_count(path: &Path) -> Result<u64> {
+        Self::stream(path)?.frame_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_wav(path: &Path, samples: &[f32], sample_rate: u32, channels: u16) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_stream_matches_decode() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("ramp.wav");
+        let samples: Vec<f32> = (0..20_000).map(|i| (i % 100) as f32 / 100.0).collect();
+        write_wav(&path, &samples, 8000, 2);
+
+        let stream = AudioDecoder::stream(&path).unwrap();
+        assert_eq!(stream.sample_rate(), 8000);
+        assert_eq!(stream.channels(), 2);
+        assert_eq!(stream.frames(), Some(10_000));
+
+        let packets: Vec<_> = stream.map(|p| p.unwrap()).collect();
+        assert!(packets.len() > 1, "expected several packets");
+        let streamed: Vec<f32> = packets.into_iter().flat_map(|p| p.samples).collect();
+
+        let (decoded, rate, channels) = AudioDecoder::decode(&path).unwrap();
+        assert_eq!((rate, channels), (8000, 2));
+        assert_eq!(streamed, decoded);
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("short.wav");
+        write_wav(&path, &[0.0; 4410], 44100, 1);
+        assert_eq!(AudioDecoder::frame_count(&path).unwrap(), 4410);
+        assert!((AudioDecoder::duration(&path).unwrap() - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stream_missing_file() {
+        assert!(AudioDecoder::stream(Path::new("/nonexistent/audio.wav")).is_err());
+    }
+}
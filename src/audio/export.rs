@@ -0,0 +1,193 @@
+use crate::error::{Result, VideoEngineError};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File format for the exported audio mix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioExportFormat {
+    /// 16-bit signed PCM WAV
+    Wav16,
+    /// 32-bit float WAV (lossless with respect to the mix)
+    #[default]
+    Wav32Float,
+    /// FLAC, encoded with ffmpeg
+    Flac,
+}
+
+impl AudioExportFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioExportFormat::Wav16 | AudioExportFormat::Wav32Float => "wav",
+            AudioExportFormat::Flac => "flac",
+        }
+    }
+}
+
+/// Incremental writer for mixed audio in any export format
+pub struct AudioWriter {
+    writer: hound::WavWriter<BufWriter<File>>,
+    format: AudioExportFormat,
+    /// Final output path
+    path: PathBuf,
+    /// Path of the WAV being written (an intermediate file for FLAC)
+    wav_path: PathBuf,
+}
+
+impl AudioWriter {
+    pub fn create(
+        path: &Path,
+        sample_rate: u32,
+        channels: u32,
+        format: AudioExportFormat,
+    ) -> Result<Self> {
+        let (bits_per_sample, sample_format) = match format {
+            AudioExportFormat::Wav16 => (16, hound::SampleFormat::Int),
+            AudioExportFormat::Wav32Float | AudioExportFormat::Flac => {
+                (32, hound::SampleFormat::Float)
+            }
+        };
+
+        let wav_path = if format == AudioExportFormat::Flac {
+            // Fail before mixing rather than after
+            if !crate::renderer::VideoEncoder::is_available() {
+                return Err(VideoEngineError::EncoderUnavailable(
+                    "FFmpeg not found; FLAC export requires it".to_string(),
+                ));
+            }
+            path.with_extension("tmp.wav")
+        } else {
+            path.to_path_buf()
+        };
+
+        let spec = hound::WavSpec {
+            channels: channels as u16,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        };
+        let writer =
+            hound::WavWriter::create(&wav_path, spec).map_err(wav_error("create", &wav_path))?;
+
+        Ok(Self {
+            writer,
+            format,
+            path: path.to_path_buf(),
+            wav_path,
+        })
+    }
+
+    /// Append interleaved samples
+    pub fn write(&mut self, samples: &[f32]) -> Result<()> {
+        for &sample in samples {
+            match self.format {
+                AudioExportFormat::Wav16 => self.writer.write_sample(to_i16(sample)),
+                AudioExportFormat::Wav32Float | AudioExportFormat::Flac => {
+                    self.writer.write_sample(sample)
+                }
+            }
+            .map_err(wav_error("write", &self.wav_path))?;
+        }
+        Ok(())
+    }
+
+    /// Flush the file, encoding it to the target format if needed
+    pub fn finalize(self) -> Result<()> {
+        self.writer
+            .finalize()
+            .map_err(wav_error("finalize", &self.wav_path))?;
+
+        if self.format == AudioExportFormat::Flac {
+            let output = Command::new("ffmpeg")
+                .arg("-y")
+                .arg("-loglevel")
+                .arg("error")
+                .arg("-i")
+                .arg(&self.wav_path)
+                .arg("-c:a")
+                .arg("flac")
+                .arg(&self.path)
+                .output()
+                .map_err(|e| {
+                    VideoEngineError::EncoderUnavailable(format!("Failed to execute ffmpeg: {}", e))
+                })?;
+            std::fs::remove_file(&self.wav_path).ok();
+
+            if !output.status.success() {
+                return Err(VideoEngineError::external(
+                    "FFmpeg FLAC encoding",
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors of the WAV writer as I/O errors on `path`
+fn wav_error(action: &'static str, path: &Path) -> impl FnOnce(hound::Error) -> VideoEngineError {
+    let path = path.to_path_buf();
+    move |error| {
+        let source = match error {
+            hound::Error::IoError(error) => error,
+            error => std::io::Error::other(error),
+        };
+        VideoEngineError::io(action, path)(source)
+    }
+}
+
+/// Scale a float sample to 16-bit PCM, clamping out-of-range values
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_wav16_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mix.wav");
+        let samples: Vec<f32> = (0..1000).map(|i| (i as f32 / 37.0).sin() * 0.9).collect();
+
+        let mut writer = AudioWriter::create(&path, 8000, 2, AudioExportFormat::Wav16).unwrap();
+        writer.write(&samples).unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        assert_eq!(spec.channels, 2);
+
+        let decoded: Vec<f32> = reader
+            .samples::<i16>()
+            .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+            .collect();
+        assert_eq!(decoded.len(), samples.len());
+        for (original, decoded) in samples.iter().zip(&decoded) {
+            // Within half a quantization step
+            assert!((original - decoded).abs() <= 0.5 / i16::MAX as f32 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_i16_clamps_out_of_range() {
+        assert_eq!(to_i16(1.5), i16::MAX);
+        assert_eq!(to_i16(-1.5), -i16::MAX);
+        assert_eq!(to_i16(0.0), 0);
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(AudioExportFormat::Wav16.extension(), "wav");
+        assert_eq!(AudioExportFormat::Flac.extension(), "flac");
+    }
+}
@@ -0,0 +1,358 @@
+use crate::script::LoudnessNormalization;
+use serde::Serialize;
+
+/// Absolute gating threshold from ITU-R BS.1770-4
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Relative gating threshold (below the ungated loudness)
+const RELATIVE_GATE_LU: f64 = -10.0;
+/// Gating block length in seconds
+const BLOCK_SECONDS: f64 = 0.4;
+/// Gating block step (75% overlap)
+const STEP_SECONDS: f64 = 0.1;
+/// Oversampling factor used for true-peak estimation
+const TRUE_PEAK_OVERSAMPLE: usize = 4;
+
+/// Result of a loudness normalization pass
+#[derive(Debug, Clone, Serialize)]
+pub struct LoudnessReport {
+    /// Integrated loudness of the mix before normalization (LUFS)
+    pub measured_lufs: f32,
+    /// Integrated loudness after gain was applied (LUFS)
+    pub final_lufs: f32,
+    /// Gain applied to the mix (dB)
+    pub gain_db: f32,
+    /// Estimated true peak after gain was applied (dBTP)
+    pub true_peak_db: f32,
+    /// Whether the gain was reduced to respect the true-peak ceiling
+    pub peak_limited: bool,
+}
+
+/// Second-order IIR section (direct form I)
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[1] * self.y[0]
+            - self.a[2] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// K-weighting filter: high-shelf "head" filter followed by the RLB high-pass
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+
+    // Stage 1: high shelf (coefficients derived as in libebur128)
+    let f0 = 1_681.974_450_955_533;
+    let gain = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+
+    // Stage 2: RLB high-pass
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+
+    [shelf, highpass]
+}
+
+/// Incremental integrated-loudness and true-peak meter for interleaved audio.
+/// Samples can be fed in chunks of any size, so long mixes are measured in O(duration / 100ms) memory.
+pub struct LoudnessMeter {
+    channels: usize,
+    block_len: usize,
+    step_len: usize,
+    filters: Vec<[Biquad; 2]>,
+    /// K-weighted energy per completed 100ms step (summed over channels)
+    step_energy: Vec<f64>,
+    current_energy: f64,
+    frames_in_step: usize,
+    sample_peak: f32,
+    /// Last three samples per channel for Catmull-Rom true-peak interpolation
+    history: Vec<Option<[f32; 3]>>,
+    interpolated_peak: f32,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        Self {
+            channels,
+            block_len: (BLOCK_SECONDS * sample_rate as f64) as usize,
+            step_len: (STEP_SECONDS * sample_rate as f64) as usize,
+            filters: vec![k_weighting(sample_rate); channels],
+            step_energy: Vec::new(),
+            current_energy: 0.0,
+            frames_in_step: 0,
+            sample_peak: 0.0,
+            history: vec![None; channels],
+            interpolated_peak: 0.0,
+        }
+    }
+
+    /// Feed interleaved samples into the meter
+    pub fn add_samples(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                let mut value = sample as f64;
+                for filter in self.filters[channel].iter_mut() {
+                    value = filter.process(value);
+                }
+                // Channel weights are 1.0 for L/R/C; surround channels are not used here
+                self.current_energy += value * value;
+
+                self.sample_peak = self.sample_peak.max(sample.abs());
+                self.track_true_peak(channel, sample);
+            }
+
+            self.frames_in_step += 1;
+            if self.step_len > 0 && self.frames_in_step == self.step_len {
+                self.step_energy.push(self.current_energy);
+                self.current_energy = 0.0;
+                self.frames_in_step = 0;
+            }
+        }
+    }
+
+    fn track_true_peak(&mut self, channel: usize, p3: f32) {
+        let [p0, p1, p2] = self.history[channel].unwrap_or([p3; 3]);
+        for step in 1..TRUE_PEAK_OVERSAMPLE {
+            let t = step as f32 / TRUE_PEAK_OVERSAMPLE as f32;
+            let value = 0.5
+                * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t);
+            self.interpolated_peak = self.interpolated_peak.max(value.abs());
+        }
+        self.history[channel] = Some([p1, p2, p3]);
+    }
+
+    /// Integrated loudness (LUFS) of everything fed so far.
+    /// Returns `f32::NEG_INFINITY` for silence or audio shorter than one gating block.
+    pub fn integrated_lufs(&self) -> f32 {
+        if self.block_len == 0 || self.step_len == 0 {
+            return f32::NEG_INFINITY;
+        }
+
+        // Combine four consecutive steps into overlapping 400ms blocks
+        let steps_per_block = self.block_len / self.step_len;
+        if self.step_energy.len() < steps_per_block {
+            return f32::NEG_INFINITY;
+        }
+        let blocks: Vec<f64> = self
+            .step_energy
+            .windows(steps_per_block)
+            .map(|window| window.iter().sum::<f64>() / self.block_len as f64)
+            .collect();
+
+        let to_lufs = |mean_square: f64| -0.691 + 10.0 * mean_square.log10();
+
+        let absolute: Vec<f64> = blocks
+            .into_iter()
+            .filter(|&z| z > 0.0 && to_lufs(z) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if absolute.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+
+        let ungated = absolute.iter().sum::<f64>() / absolute.len() as f64;
+        let relative_gate = to_lufs(ungated) + RELATIVE_GATE_LU;
+
+        let gated: Vec<f64> = absolute
+            .into_iter()
+            .filter(|&z| to_lufs(z) > relative_gate)
+            .collect();
+        if gated.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+
+        to_lufs(gated.iter().sum::<f64>() / gated.len() as f64) as f32
+    }
+
+    /// Estimated true peak (linear) using 4x Catmull-Rom oversampling per channel
+    pub fn true_peak(&self) -> f32 {
+        self.sample_peak.max(self.interpolated_peak)
+    }
+
+    /// Gain needed to reach the target loudness without exceeding the true-peak ceiling.
+    /// Returns `None` when nothing measurable has been fed.
+    pub fn normalization_gain(&self, settings: &LoudnessNormalization) -> Option<LoudnessReport> {
+        let measured = self.integrated_lufs();
+        if !measured.is_finite() {
+            return None;
+        }
+
+        let mut gain_db = settings.target_lufs - measured;
+        let mut peak_limited = false;
+
+        let peak = self.true_peak();
+        if peak > 0.0 {
+            let peak_db = 20.0 * peak.log10();
+            if peak_db + gain_db > settings.true_peak_db {
+                gain_db = settings.true_peak_db - peak_db;
+                peak_limited = true;
+            }
+        }
+
+        let gain = 10f32.powf(gain_db / 20.0);
+        Some(LoudnessReport {
+            measured_lufs: measured,
+            final_lufs: measured + gain_db,
+            gain_db,
+            true_peak_db: 20.0 * (peak * gain).max(f32::MIN_POSITIVE).log10(),
+            peak_limited,
+        })
+    }
+
+    /// Integrated loudness in LUFS of a complete interleaved buffer
+    pub fn integrated_loudness(samples: &[f32], sample_rate: u32, channels: u32) -> f32 {
+        let mut meter = Self::new(sample_rate, channels);
+        meter.add_samples(samples);
+        meter.integrated_lufs()
+    }
+
+    /// Estimated true peak (linear) of a complete interleaved buffer
+    pub fn measure_true_peak(samples: &[f32], sample_rate: u32, channels: u32) -> f32 {
+        let mut meter = Self::new(sample_rate, channels);
+        meter.add_samples(samples);
+        meter.true_peak()
+    }
+
+    /// Apply gain in place so the buffer hits the target loudness without exceeding the
+    /// true-peak ceiling. Returns `None` when the input is too short or silent to measure.
+    pub fn normalize(
+        samples: &mut [f32],
+        sample_rate: u32,
+        channels: u32,
+        settings: &LoudnessNormalization,
+    ) -> Option<LoudnessReport> {
+        let mut meter = Self::new(sample_rate, channels);
+        meter.add_samples(samples);
+        let report = meter.normalization_gain(settings)?;
+
+        let gain = 10f32.powf(report.gain_db / 20.0);
+        for sample in samples.iter_mut() {
+            *sample *= gain;
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mono 997 Hz sine with the given amplitude
+    fn sine(amplitude: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+        let count = (seconds * sample_rate as f32) as usize;
+        (0..count)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_scale_sine_reference() {
+        // BS.1770: a 0 dBFS 997 Hz sine on a single channel reads -3.01 LUFS
+        let samples = sine(1.0, 2.0, 48000);
+        let lufs = LoudnessMeter::integrated_loudness(&samples, 48000, 1);
+        assert!((lufs - -3.01).abs() < 0.1, "got {}", lufs);
+    }
+
+    #[test]
+    fn test_normalize_tone_to_target() {
+        // -20 LUFS tone: amplitude = 10^((-20 + 3.01) / 20)
+        let amplitude = 10f32.powf((-20.0 + 3.01) / 20.0);
+        let mut samples = sine(amplitude, 3.0, 44100);
+        let measured = LoudnessMeter::integrated_loudness(&samples, 44100, 1);
+        assert!((measured - -20.0).abs() < 0.5, "got {}", measured);
+
+        let report =
+            LoudnessMeter::normalize(&mut samples, 44100, 1, &LoudnessNormalization::default())
+                .unwrap();
+        assert!(
+            (report.gain_db - 6.0).abs() < 0.5,
+            "gain {}",
+            report.gain_db
+        );
+        assert!(!report.peak_limited);
+
+        let after = LoudnessMeter::integrated_loudness(&samples, 44100, 1);
+        assert!((after - -14.0).abs() < 0.5, "got {}", after);
+    }
+
+    #[test]
+    fn test_true_peak_ceiling() {
+        // A loud target would push the sine past -1 dBTP; gain must be limited
+        let mut samples = sine(0.5, 2.0, 48000);
+        let settings = LoudnessNormalization {
+            target_lufs: 0.0,
+            true_peak_db: -1.0,
+        };
+        let report = LoudnessMeter::normalize(&mut samples, 48000, 1, &settings).unwrap();
+        assert!(report.peak_limited);
+        assert!(
+            LoudnessMeter::measure_true_peak(&samples, 48000, 1) <= 10f32.powf(-1.0 / 20.0) + 1e-3
+        );
+    }
+
+    #[test]
+    fn test_chunked_measurement_matches_batch() {
+        let samples = sine(0.3, 2.0, 44100);
+        let batch = LoudnessMeter::integrated_loudness(&samples, 44100, 1);
+
+        let mut meter = LoudnessMeter::new(44100, 1);
+        for chunk in samples.chunks(1000) {
+            meter.add_samples(chunk);
+        }
+        assert!((meter.integrated_lufs() - batch).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_silence_is_not_measurable() {
+        let mut samples = vec![0.0; 48000 * 2];
+        assert_eq!(
+            LoudnessMeter::integrated_loudness(&samples, 48000, 2),
+            f32::NEG_INFINITY
+        );
+        assert!(LoudnessMeter::normalize(
+            &mut samples,
+            48000,
+            2,
+            &LoudnessNormalization::default()
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,1169 @@
+use crate::audio::analysis::{
+    AudioAnalysisReport, AudioAnalysisSettings, SilenceDetector, TrackPeak,
+};
+use crate::audio::decoder::{AudioDecoder, AudioStream};
+use crate::audio::export::{AudioExportFormat, AudioWriter};
+use crate::audio::loudness::{LoudnessMeter, LoudnessReport};
+use crate::error::{Result, VideoEngineError};
+use crate::script::{AudioTrack, AudioTrackType, DuckingConfig, LoudnessNormalization};
+use std::path::{Path, PathBuf};
+
+/// Output frames mixed per chunk; peak mixing memory is proportional to this, not to the duration
+pub const MIX_CHUNK_FRAMES: usize = 8192;
+
+/// Envelope level above which a voiceover counts as present (-40 dBFS)
+const DUCKING_THRESHOLD: f32 = 0.01;
+/// Decay of the voice peak detector, long enough to bridge waveform zero crossings
+const DUCKING_DETECTOR_MS: f32 = 10.0;
+
+/// Mixes multiple audio tracks
+pub struct AudioMixer {
+    output_sample_rate: u32,
+    output_channels: u32,
+    tracks: Vec<MixedTrack>,
+    normalization: Option<LoudnessNormalization>,
+    ducking: Option<DuckingConfig>,
+    analysis: AudioAnalysisSettings,
+}
+
+/// Reports produced by a mix
+#[derive(Debug, Clone, Default)]
+pub struct MixReport {
+    /// Present when loudness normalization is enabled
+    pub loudness: Option<LoudnessReport>,
+    pub analysis: AudioAnalysisReport,
+}
+
+/// Levels observed during one mix pass, before the soft clipper
+struct PassLevels {
+    peak: f32,
+    clipped_samples: u64,
+    track_peaks: Vec<f32>,
+}
+
+/// Where a track's samples come from during mixing
+enum TrackSource {
+    /// Fully decoded samples, already trimmed
+    Buffered(Vec<f32>),
+    /// Decoded packet by packet on every mix pass
+    Streamed(PathBuf),
+}
+
+struct MixedTrack {
+    /// Label used in analysis reports
+    name: String,
+    source: TrackSource,
+    sample_rate: u32,
+    channels: u32,
+    start_time: f32,
+    volume: f32,
+    track_type: AudioTrackType,
+    fade_in: f32,
+    fade_out: f32,
+    looping: bool,
+    /// Crossfade length at loop points, in input frames
+    loop_crossfade: usize,
+    /// Stereo position, -1.0 (left) to 1.0 (right)
+    pan: f32,
+    /// First source frame that is played (trim start)
+    first_frame: usize,
+    /// Number of source frames played per pass (after trimming)
+    length: usize,
+}
+
+impl MixedTrack {
+    /// Left/right gains for stereo output. Mono sources use a constant-power pan law
+    /// (scaled to unity at the center); stereo sources get a balance control that only
+    /// attenuates the opposite side.
+    fn stereo_gains(&self) -> [f32; 2] {
+        if self.channels == 1 {
+            let angle = (self.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+            [
+                angle.cos() * std::f32::consts::SQRT_2,
+                angle.sin() * std::f32::consts::SQRT_2,
+            ]
+        } else {
+            let attenuate = |amount: f32| (amount.max(0.0) * std::f32::consts::FRAC_PI_2).cos();
+            [attenuate(self.pan), attenuate(-self.pan)]
+        }
+    }
+
+    /// Sample at an input frame, wrapping around (with crossfade) for looping tracks.
+    /// Returns `None` once a non-looping track has run out.
+    fn buffered_sample(&self, samples: &[f32], frame: usize, channel: usize) -> Option<f32> {
+        let channels = self.channels.max(1) as usize;
+        let frames = self.length;
+        if !self.looping {
+            return samples.get(frame * channels + channel).copied();
+        }
+        if frames == 0 {
+            return None;
+        }
+
+        // Each repetition starts `loop_crossfade` frames before the previous one ends
+        let crossfade = self.loop_crossfade.min(frames / 2);
+        let period = frames - crossfade;
+        let offset = frame % period;
+        let head = samples[offset * channels + channel];
+
+        if frame < period || offset >= crossfade {
+            return Some(head);
+        }
+
+        let tail = samples[(period + offset) * channels + channel];
+        let weight = offset as f32 / crossfade as f32;
+        Some(head * weight + tail * (1.0 - weight))
+    }
+
+    /// Open a decoder for streamed tracks; buffered tracks need no per-pass state
+    fn open_cursor(&self) -> Result<Option<StreamCursor>> {
+        match &self.source {
+            TrackSource::Buffered(_) => Ok(None),
+            TrackSource::Streamed(path) => {
+                let stream = AudioDecoder::stream(path)?;
+                Ok(Some(StreamCursor {
+                    channels: stream.channels().max(1) as usize,
+                    stream,
+                    window: Vec::new(),
+                    window_start: 0,
+                }))
+            }
+        }
+    }
+
+    /// Sample at an input frame, `None` once the track has run out. Streamed
+    /// tracks fail when their file can't be decoded.
+    fn sample_at(
+        &self,
+        cursor: &mut Option<StreamCursor>,
+        frame: usize,
+        channel: usize,
+    ) -> Result<Option<f32>> {
+        match (&self.source, cursor) {
+            (TrackSource::Buffered(samples), _) => {
+                Ok(self.buffered_sample(samples, frame, channel))
+            }
+            (TrackSource::Streamed(_), Some(cursor)) if frame < self.length => {
+                cursor.sample_at(self.first_frame + frame, channel)
+            }
+            (TrackSource::Streamed(_), _) => Ok(None),
+        }
+    }
+}
+
+/// Forward-only reader over a decoded stream, holding a single packet at a time
+struct StreamCursor {
+    stream: AudioStream,
+    channels: usize,
+    window: Vec<f32>,
+    /// Source frame index of the first frame in `window`
+    window_start: usize,
+}
+
+impl StreamCursor {
+    fn sample_at(&mut self, frame: usize, channel: usize) -> Result<Option<f32>> {
+        while frame >= self.window_start + self.window.len() / self.channels {
+            let Some(packet) = self.stream.next() else {
+                return Ok(None);
+            };
+            let packet = packet?;
+            self.window_start += self.window.len() / self.channels;
+            self.window = packet.samples;
+        }
+
+        if frame < self.window_start {
+            return Ok(None);
+        }
+        Ok(self
+            .window
+            .get((frame - self.window_start) * self.channels + channel)
+            .copied())
+    }
+}
+
+/// Attack/release smoothed gain follower driven by the voiceover level
+struct Ducker {
+    attack: f32,
+    release: f32,
+    detector: f32,
+    ducked_gain: f32,
+    envelope: f32,
+    gain: f32,
+}
+
+impl Ducker {
+    fn new(ducking: &DuckingConfig, sample_rate: u32) -> Self {
+        let rate = sample_rate as f32;
+        let coefficient = |ms: f32| {
+            if ms <= 0.0 {
+                0.0
+            } else {
+                (-1.0 / (ms / 1000.0 * rate)).exp()
+            }
+        };
+        Self {
+            attack: coefficient(ducking.attack_ms),
+            release: coefficient(ducking.release_ms),
+            detector: coefficient(DUCKING_DETECTOR_MS),
+            ducked_gain: 10f32.powf(-ducking.amount_db.abs() / 20.0),
+            envelope: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Per-frame music gains for an interleaved chunk of voiceover audio
+    fn process(&mut self, voice: &[f32], channels: usize) -> Vec<f32> {
+        voice
+            .chunks(channels)
+            .map(|frame| {
+                let level = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+                // Peak detector feeding an attack/release smoothed gain to avoid pumping
+                self.envelope = level.max(self.envelope * self.detector);
+                let target = if self.envelope > DUCKING_THRESHOLD {
+                    self.ducked_gain
+                } else {
+                    1.0
+                };
+                let coeff = if target < self.gain {
+                    self.attack
+                } else {
+                    self.release
+                };
+                self.gain = target + (self.gain - target) * coeff;
+                self.gain
+            })
+            .collect()
+    }
+}
+
+/// Source frame range `[start, end)` selected by a track's trim points.
+/// Fails if the fades do not fit in the trimmed length.
+fn trim_window(
+    total_frames: usize,
+    sample_rate: u32,
+    track: &AudioTrack,
+) -> Result<(usize, usize)> {
+    let to_frame = |seconds: f32| ((seconds * sample_rate as f32) as usize).min(total_frames);
+
+    let start_frame = to_frame(track.trim_start);
+    let end_frame = track
+        .trim_end
+        .map_or(total_frames, to_frame)
+        .max(start_frame);
+    let effective_length = (end_frame - start_frame) as f32 / sample_rate as f32;
+
+    // Looping tracks fade out at the end of the video, not at the end of one pass
+    if !track.looping && track.fade_in + track.fade_out > effective_length + f32::EPSILON {
+        return Err(VideoEngineError::invalid(format!(
+            "Fades ({:.2}s) are longer than the track ({:.2}s)",
+            track.fade_in + track.fade_out,
+            effective_length
+        )));
+    }
+
+    Ok((start_frame, end_frame))
+}
+
+impl AudioMixer {
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            output_sample_rate: sample_rate,
+            output_channels: channels,
+            tracks: Vec::new(),
+            normalization: None,
+            ducking: None,
+            analysis: AudioAnalysisSettings::default(),
+        }
+    }
+
+    /// Enable loudness normalization of the final mix
+    pub fn set_normalization(&mut self, normalization: Option<LoudnessNormalization>) {
+        self.normalization = normalization;
+    }
+
+    /// Enable sidechain ducking of music tracks under voiceover tracks
+    pub fn set_ducking(&mut self, ducking: Option<DuckingConfig>) {
+        self.ducking = ducking;
+    }
+
+    /// Thresholds used for the clipping and silence analysis
+    pub fn set_analysis(&mut self, analysis: AudioAnalysisSettings) {
+        self.analysis = analysis;
+    }
+
+    pub fn add_track(
+        &mut self,
+        samples: Vec<f32>,
+        sample_rate: u32,
+        channels: u32,
+        start_time: f32,
+        volume: f32,
+    ) {
+        let length = samples.len() / channels.max(1) as usize;
+        self.tracks.push(MixedTrack {
+            name: format!("track {}", self.tracks.len() + 1),
+            source: TrackSource::Buffered(samples),
+            sample_rate,
+            channels,
+            start_time,
+            volume,
+            track_type: AudioTrackType::Music,
+            fade_in: 0.0,
+            fade_out: 0.0,
+            looping: false,
+            loop_crossfade: 0,
+            pan: 0.0,
+            first_frame: 0,
+            length,
+        });
+    }
+
+    /// Add decoded samples using the settings of a script audio track.
+    /// Samples outside the trim window are dropped; fails if the fades do not fit.
+    pub fn add_script_track(
+        &mut self,
+        samples: Vec<f32>,
+        sample_rate: u32,
+        channels: u32,
+        track: &AudioTrack,
+    ) -> Result<()> {
+        let channels_usize = channels.max(1) as usize;
+        let total_frames = samples.len() / channels_usize;
+        let (start_frame, end_frame) = trim_window(total_frames, sample_rate, track)?;
+
+        let samples = if start_frame == 0 && end_frame == total_frames {
+            samples
+        } else {
+            samples[start_frame * channels_usize..end_frame * channels_usize].to_vec()
+        };
+
+        self.push_script_track(
+            TrackSource::Buffered(samples),
+            sample_rate,
+            channels,
+            track,
+            0,
+            end_frame - start_frame,
+        );
+        Ok(())
+    }
+
+    /// Add a script audio track from an opened `stream`. The file is decoded again
+    /// incrementally while mixing, so memory stays bounded regardless of its length.
+    /// Looping tracks are decoded up front because every repetition needs random
+    /// access to the trimmed region.
+    pub fn add_streamed_track(&mut self, stream: AudioStream, track: &AudioTrack) -> Result<()> {
+        let sample_rate = stream.sample_rate();
+        let channels = stream.channels();
+        if track.looping {
+            let samples = stream.read_to_end();
+            return self.add_script_track(samples, sample_rate, channels, track);
+        }
+
+        let path = stream.path().to_path_buf();
+        let total_frames = stream.frame_count()? as usize;
+        let (start_frame, end_frame) = trim_window(total_frames, sample_rate, track)?;
+
+        self.push_script_track(
+            TrackSource::Streamed(path),
+            sample_rate,
+            channels,
+            track,
+            start_frame,
+            end_frame - start_frame,
+        );
+        Ok(())
+    }
+
+    fn push_script_track(
+        &mut self,
+        source: TrackSource,
+        sample_rate: u32,
+        channels: u32,
+        track: &AudioTrack,
+        first_frame: usize,
+        length: usize,
+    ) {
+        self.tracks.push(MixedTrack {
+            name: track.source.display().to_string(),
+            source,
+            sample_rate,
+            channels,
+            start_time: track.start_time,
+            volume: track.volume,
+            track_type: track.track_type.clone(),
+            fade_in: track.fade_in,
+            fade_out: track.fade_out,
+            looping: track.looping,
+            loop_crossfade: (track.loop_crossfade_ms / 1000.0 * sample_rate as f32) as usize,
+            pan: track.pan.clamp(-1.0, 1.0),
+            first_frame,
+            length,
+        });
+    }
+
+    /// Mix all tracks into a single buffer
+    pub fn mix(&self, duration_seconds: f32) -> Result<Vec<f32>> {
+        Ok(self.mix_with_report(duration_seconds)?.0)
+    }
+
+    /// Mix all tracks, returning the loudness and level analysis reports
+    pub fn mix_with_report(&self, duration_seconds: f32) -> Result<(Vec<f32>, MixReport)> {
+        let mut mixed_buffer = Vec::new();
+        let report = self.mix_into(duration_seconds, MIX_CHUNK_FRAMES, |chunk| {
+            mixed_buffer.extend_from_slice(chunk);
+            Ok(())
+        })?;
+        Ok((mixed_buffer, report))
+    }
+
+    /// Mix straight into an audio file in the given format, one chunk at a time
+    pub fn mix_to_file(
+        &self,
+        path: &Path,
+        duration_seconds: f32,
+        format: AudioExportFormat,
+    ) -> Result<MixReport> {
+        let mut writer =
+            AudioWriter::create(path, self.output_sample_rate, self.output_channels, format)?;
+        let report = self.mix_into(duration_seconds, MIX_CHUNK_FRAMES, |chunk| {
+            writer.write(chunk)
+        })?;
+        writer.finalize()?;
+        Ok(report)
+    }
+
+    /// Mix all tracks in chunks of at most `chunk_frames` output frames, handing each
+    /// finished (normalized and clipped) chunk to `sink`. When normalization is enabled a
+    /// measurement pass runs first, so streamed tracks are decoded twice.
+    pub fn mix_into(
+        &self,
+        duration_seconds: f32,
+        chunk_frames: usize,
+        mut sink: impl FnMut(&[f32]) -> Result<()>,
+    ) -> Result<MixReport> {
+        let total_frames = (duration_seconds * self.output_sample_rate as f32) as usize;
+        let chunk_frames = chunk_frames.max(1);
+
+        // Loudness normalization is measured on the raw mix, before the clipper
+        let mut loudness = match &self.normalization {
+            Some(settings) => {
+                let mut meter = LoudnessMeter::new(self.output_sample_rate, self.output_channels);
+                self.mix_pass(total_frames, chunk_frames, 1.0, false, &mut |chunk| {
+                    meter.add_samples(chunk);
+                    Ok(())
+                })?;
+                meter.normalization_gain(settings)
+            }
+            None => None,
+        };
+
+        let gain = loudness
+            .as_ref()
+            .map_or(1.0, |report| 10f32.powf(report.gain_db / 20.0));
+        let mut output_meter = loudness
+            .as_ref()
+            .map(|_| LoudnessMeter::new(self.output_sample_rate, self.output_channels));
+        let mut silence = SilenceDetector::new(
+            &self.analysis,
+            self.output_sample_rate,
+            self.output_channels,
+        );
+
+        let levels = self.mix_pass(total_frames, chunk_frames, gain, true, &mut |chunk| {
+            if let Some(meter) = output_meter.as_mut() {
+                meter.add_samples(chunk);
+            }
+            silence.add_samples(chunk);
+            sink(chunk)
+        })?;
+
+        if let (Some(report), Some(meter)) = (loudness.as_mut(), output_meter) {
+            report.final_lufs = meter.integrated_lufs();
+        }
+
+        let analysis = AudioAnalysisReport {
+            peak: levels.peak,
+            clipped_samples: levels.clipped_samples,
+            track_peaks: self
+                .tracks
+                .iter()
+                .zip(levels.track_peaks)
+                .map(|(track, peak)| TrackPeak {
+                    name: track.name.clone(),
+                    peak,
+                })
+                .collect(),
+            silent_gaps: silence.finish(),
+        };
+
+        Ok(MixReport { loudness, analysis })
+    }
+
+    /// One pass over the timeline: render every track chunk by chunk, apply `gain`
+    /// and optionally the soft clipper, and hand each chunk to `sink`
+    fn mix_pass(
+        &self,
+        total_frames: usize,
+        chunk_frames: usize,
+        gain: f32,
+        clip: bool,
+        sink: &mut dyn FnMut(&[f32]) -> Result<()>,
+    ) -> Result<PassLevels> {
+        let channels = self.output_channels as usize;
+        let mut cursors = self
+            .tracks
+            .iter()
+            .map(|t| t.open_cursor())
+            .collect::<Result<Vec<_>>>()?;
+
+        let has_voice = self
+            .tracks
+            .iter()
+            .any(|t| t.track_type == AudioTrackType::Voiceover);
+        let mut ducker = self
+            .ducking
+            .as_ref()
+            .filter(|_| has_voice)
+            .map(|ducking| Ducker::new(ducking, self.output_sample_rate));
+
+        let mut chunk = Vec::with_capacity(chunk_frames * channels);
+        let mut voice = Vec::with_capacity(chunk_frames * channels);
+        let mut levels = PassLevels {
+            peak: 0.0,
+            clipped_samples: 0,
+            track_peaks: vec![0.0; self.tracks.len()],
+        };
+
+        for chunk_start in (0..total_frames).step_by(chunk_frames) {
+            let frames = chunk_frames.min(total_frames - chunk_start);
+            chunk.clear();
+            chunk.resize(frames * channels, 0.0);
+            voice.clear();
+            voice.resize(frames * channels, 0.0);
+
+            // Voiceover first: it drives the ducking envelope for this chunk
+            for (index, (track, cursor)) in self.tracks.iter().zip(cursors.iter_mut()).enumerate() {
+                if track.track_type == AudioTrackType::Voiceover {
+                    let peak = self.render_track(
+                        track,
+                        cursor,
+                        chunk_start,
+                        total_frames,
+                        &mut voice,
+                        None,
+                    )?;
+                    levels.track_peaks[index] = levels.track_peaks[index].max(peak * gain);
+                }
+            }
+            let duck_gains = ducker
+                .as_mut()
+                .map(|ducker| ducker.process(&voice, channels));
+
+            for (index, (track, cursor)) in self.tracks.iter().zip(cursors.iter_mut()).enumerate() {
+                let gains = match track.track_type {
+                    AudioTrackType::Voiceover => continue,
+                    AudioTrackType::Music => duck_gains.as_deref(),
+                    _ => None,
+                };
+                let peak =
+                    self.render_track(track, cursor, chunk_start, total_frames, &mut chunk, gains)?;
+                levels.track_peaks[index] = levels.track_peaks[index].max(peak * gain);
+            }
+
+            for (sample, voice_sample) in chunk.iter_mut().zip(&voice) {
+                *sample = (*sample + voice_sample) * gain;
+                levels.peak = levels.peak.max(sample.abs());
+                if sample.abs() > 1.0 {
+                    levels.clipped_samples += 1;
+                }
+                if clip {
+                    // Hard clipping prevention (tanh soft clipping)
+                    *sample = sample.tanh();
+                }
+            }
+
+            sink(&chunk)?;
+        }
+
+        Ok(levels)
+    }
+
+    /// Resample and accumulate one track into an interleaved output chunk starting at
+    /// output frame `chunk_start`, optionally scaling each frame by `frame_gains`.
+    /// Returns the peak of the track's contribution to the chunk, or the error
+    /// of a streamed track that fails to decode.
+    fn render_track(
+        &self,
+        track: &MixedTrack,
+        cursor: &mut Option<StreamCursor>,
+        chunk_start: usize,
+        total_frames: usize,
+        output: &mut [f32],
+        frame_gains: Option<&[f32]>,
+    ) -> Result<f32> {
+        // Simple resampling (nearest neighbor) and mixing
+        // NOTE: For production, use a proper resampler like `rubato`
+        let output_channels = self.output_channels as usize;
+        let output_rate = self.output_sample_rate as f32;
+        let start_frame = (track.start_time * output_rate) as usize;
+
+        // Ratio between track sample rate and output sample rate
+        let rate_ratio = track.sample_rate as f32 / output_rate;
+
+        // The track stops at its own end or the end of the video, whichever comes first;
+        // the fade-out window is anchored to that point
+        let output_seconds = total_frames as f32 / output_rate;
+        let play_end = if track.looping {
+            output_seconds - track.start_time
+        } else {
+            (track.length as f32 / track.sample_rate as f32).min(output_seconds - track.start_time)
+        };
+        let fade_gain = |t: f32| {
+            let fade_in = if track.fade_in > 0.0 {
+                (t / track.fade_in).min(1.0)
+            } else {
+                1.0
+            };
+            let fade_out = if track.fade_out > 0.0 {
+                ((play_end - t) / track.fade_out).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            fade_in * fade_out
+        };
+
+        // Panning only applies to stereo output
+        let channel_gains = if output_channels == 2 {
+            track.stereo_gains()
+        } else {
+            [1.0, 1.0]
+        };
+
+        let mut peak = 0.0f32;
+        for (local_frame, frame) in output.chunks_mut(output_channels).enumerate() {
+            let global_frame = chunk_start + local_frame;
+            if global_frame < start_frame {
+                continue;
+            }
+
+            // Map output frame index to input frame index based on rate
+            let frame_index = global_frame - start_frame;
+            let input_frame_index = (frame_index as f32 * rate_ratio) as usize;
+            let gain = frame_gains.map_or(1.0, |g| g[local_frame])
+                * fade_gain(frame_index as f32 / output_rate);
+
+            for (channel_index, sample) in frame.iter_mut().enumerate() {
+                // Handle channel mapping (mono to stereo, etc.)
+                let input_channel_index = if track.channels == 1 {
+                    0 // Use the single channel for all output channels
+                } else {
+                    channel_index % track.channels as usize
+                };
+
+                match track.sample_at(cursor, input_frame_index, input_channel_index)? {
+                    Some(value) => {
+                        let pan_gain = channel_gains.get(channel_index).copied().unwrap_or(1.0);
+                        let contribution = value * track.volume * gain * pan_gain;
+                        peak = peak.max(contribution.abs());
+                        *sample += contribution;
+                    }
+                    None => return Ok(peak),
+                }
+            }
+        }
+        Ok(peak)
+    }
+
+    /// Export mixed audio to a 32-bit float WAV file
+    pub fn export(&self, path: &Path, samples: &[f32]) -> Result<()> {
+        self.export_as(path, samples, AudioExportFormat::Wav32Float)
+    }
+
+    /// Export mixed audio in the given format
+    pub fn export_as(&self, path: &Path, samples: &[f32], format: AudioExportFormat) -> Result<()> {
+        let mut writer =
+            AudioWriter::create(path, self.output_sample_rate, self.output_channels, format)?;
+        writer.write(samples)?;
+        writer.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tone(amplitude: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+        let count = (seconds * sample_rate as f32) as usize;
+        (0..count)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mix_without_normalization_has_no_report() {
+        let mut mixer = AudioMixer::new(44100, 1);
+        mixer.add_track(tone(0.1, 1.0, 44100), 44100, 1, 0.0, 1.0);
+        let (samples, report) = mixer.mix_with_report(1.0).unwrap();
+        assert_eq!(samples.len(), 44100);
+        assert!(report.loudness.is_none());
+    }
+
+    #[test]
+    fn test_mix_normalizes_to_target() {
+        let amplitude = 10f32.powf((-20.0 + 3.01) / 20.0);
+        let mut mixer = AudioMixer::new(44100, 1);
+        mixer.add_track(tone(amplitude, 3.0, 44100), 44100, 1, 0.0, 1.0);
+        mixer.set_normalization(Some(LoudnessNormalization::default()));
+
+        let (_, report) = mixer.mix_with_report(3.0).unwrap();
+        let report = report.loudness.unwrap();
+        assert!((report.measured_lufs - -20.0).abs() < 0.5);
+        assert!((report.gain_db - 6.0).abs() < 0.5);
+        assert!((report.final_lufs - -14.0).abs() < 0.5);
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_ducking_music_under_voiceover() {
+        let rate = 8000;
+        let music = tone(0.3, 4.0, rate);
+        // Voice burst from 1.0s to 2.0s
+        let voice = tone(0.5, 1.0, rate);
+
+        let mut voice_mixer = AudioMixer::new(rate, 1);
+        voice_mixer
+            .add_script_track(voice, rate, 1, &track(AudioTrackType::Voiceover, 1.0))
+            .unwrap();
+        let voice_mix = voice_mixer.mix(4.0).unwrap();
+
+        // Feed the detector in small chunks, as the mixer does
+        let mut ducker = Ducker::new(&DuckingConfig::default(), rate);
+        let gains: Vec<f32> = voice_mix
+            .chunks(256)
+            .flat_map(|chunk| ducker.process(chunk, 1))
+            .collect();
+        let ducked: Vec<f32> = music.iter().zip(&gains).map(|(m, g)| m * g).collect();
+
+        let window = |from: f32, to: f32| {
+            &ducked[(from * rate as f32) as usize..(to * rate as f32) as usize]
+        };
+        let before = rms(window(0.2, 0.8));
+        let during = rms(window(1.3, 1.9));
+        let after = rms(window(3.5, 4.0));
+
+        // 12 dB of ducking is roughly a factor of 4
+        assert!(during < before * 0.3, "before {} during {}", before, during);
+        assert!((after - before).abs() < before * 0.05, "after {}", after);
+    }
+
+    #[test]
+    fn test_ducking_without_voiceover_is_noop() {
+        let mut mixer = AudioMixer::new(8000, 1);
+        mixer.add_track(tone(0.3, 1.0, 8000), 8000, 1, 0.0, 1.0);
+        let dry = mixer.mix(1.0).unwrap();
+        mixer.set_ducking(Some(DuckingConfig::default()));
+        assert_eq!(mixer.mix(1.0).unwrap(), dry);
+    }
+
+    fn track(track_type: AudioTrackType, start_time: f32) -> AudioTrack {
+        AudioTrack {
+            source: "unused.wav".into(),
+            track_type,
+            volume: 1.0,
+            start_time,
+            fade_in: 0.0,
+            fade_out: 0.0,
+            trim_start: 0.0,
+            trim_end: None,
+            looping: false,
+            loop_crossfade_ms: 0.0,
+            pan: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_loop_fills_duration_without_clicks() {
+        let rate = 8000;
+        // 440.25 Hz does not complete a whole number of cycles in 1s, so a naive
+        // loop would jump by a quarter cycle at every boundary
+        let source: Vec<f32> = (0..rate)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.25 * i as f32 / rate as f32).sin())
+            .collect();
+        let max_step = source
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0f32, f32::max);
+
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.looping = true;
+        config.loop_crossfade_ms = 20.0;
+
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer.add_script_track(source, rate, 1, &config).unwrap();
+        let mixed = mixer.mix(3.5).unwrap();
+        assert_eq!(mixed.len(), 3 * rate as usize + rate as usize / 2);
+
+        // Audible in every 50ms window, including the tail after the last full loop
+        for window in mixed.chunks(rate as usize / 20) {
+            assert!(rms(window) > 0.05, "silent window, rms {}", rms(window));
+        }
+
+        let worst_step = mixed
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(
+            worst_step < max_step * 1.5,
+            "discontinuity {} vs {}",
+            worst_step,
+            max_step
+        );
+    }
+
+    #[test]
+    fn test_loop_only_repeats_trimmed_region() {
+        let rate = 1000;
+        // First half silent, second half constant
+        let mut samples = vec![0.0; 1000];
+        samples.extend(vec![0.25; 1000]);
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.trim_start = 1.0;
+        config.looping = true;
+
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer.add_script_track(samples, rate, 1, &config).unwrap();
+        let mixed = mixer.mix(5.0).unwrap();
+        assert!(mixed.iter().all(|s| (s - 0.25f32.tanh()).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_fade_in_starts_silent() {
+        let rate = 8000;
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.fade_in = 0.5;
+        config.fade_out = 0.5;
+
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer
+            .add_script_track(vec![0.5; 2 * rate as usize], rate, 1, &config)
+            .unwrap();
+        let mixed = mixer.mix(2.0).unwrap();
+
+        assert!(mixed[..10].iter().all(|s| s.abs() < 0.01));
+        // Fully faded in by the middle of the track
+        assert!((mixed[rate as usize] - 0.5f32.tanh()).abs() < 1e-4);
+        // Fade-out ends at the track end
+        assert!(mixed[2 * rate as usize - 1].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_trim_removes_expected_frames() {
+        let rate = 1000;
+        let samples: Vec<f32> = (0..3000).map(|i| i as f32 / 10_000.0).collect();
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.trim_start = 0.5;
+        config.trim_end = Some(2.0);
+
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer.add_script_track(samples, rate, 1, &config).unwrap();
+        assert_eq!(mixer.tracks[0].length, 1500);
+        match &mixer.tracks[0].source {
+            TrackSource::Buffered(samples) => assert_eq!(samples[0], 500.0 / 10_000.0),
+            TrackSource::Streamed(_) => panic!("expected a buffered track"),
+        }
+
+        // Nothing plays after the trimmed region ends
+        let mixed = mixer.mix(3.0).unwrap();
+        assert!(mixed[1500..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn test_fades_longer_than_track_rejected() {
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.fade_in = 1.0;
+        config.fade_out = 1.0;
+
+        let mut mixer = AudioMixer::new(1000, 1);
+        assert!(mixer
+            .add_script_track(vec![0.1; 1500], 1000, 1, &config)
+            .is_err());
+    }
+
+    fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_streamed_track_mixes_in_bounded_chunks() {
+        let rate = 8000;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("long.wav");
+        let source = tone(0.3, 60.0, rate);
+        write_wav(&path, &source, rate);
+
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.trim_start = 1.0;
+
+        let mut streamed = AudioMixer::new(rate, 1);
+        streamed
+            .add_streamed_track(AudioDecoder::stream(&path).unwrap(), &config)
+            .unwrap();
+        assert!(matches!(
+            streamed.tracks[0].source,
+            TrackSource::Streamed(_)
+        ));
+
+        let mut chunks = 0;
+        let mut mixed = Vec::new();
+        streamed
+            .mix_into(59.0, 1024, |chunk| {
+                assert!(chunk.len() <= 1024);
+                chunks += 1;
+                mixed.extend_from_slice(chunk);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(chunks, (59 * rate as usize).div_ceil(1024));
+
+        // Identical to mixing the fully decoded track
+        let mut buffered = AudioMixer::new(rate, 1);
+        buffered.add_script_track(source, rate, 1, &config).unwrap();
+        assert_eq!(mixed, buffered.mix(59.0).unwrap());
+    }
+
+    #[test]
+    fn test_unreadable_streamed_track_fails_the_mix() {
+        let rate = 8000;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("gone.wav");
+        write_wav(&path, &tone(0.3, 1.0, rate), rate);
+
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer
+            .add_streamed_track(
+                AudioDecoder::stream(&path).unwrap(),
+                &track(AudioTrackType::Music, 0.0),
+            )
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Rather than an empty mix and report
+        assert!(mixer.mix_with_report(1.0).is_err());
+    }
+
+    /// A mono 8kHz IMA ADPCM WAV with a block per step index in `steps`; an
+    /// index above 88 makes its block fail to decode
+    fn write_adpcm_wav(path: &Path, steps: &[u8]) {
+        const BLOCK_ALIGN: u32 = 256;
+        const FRAMES_PER_BLOCK: u32 = 505;
+        let mut data = Vec::new();
+        for &step in steps {
+            data.extend_from_slice(&[0, 0, step, 0]);
+            data.extend_from_slice(&[0x17; BLOCK_ALIGN as usize - 4]);
+        }
+
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&0x11u16.to_le_bytes());
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&8000u32.to_le_bytes());
+        fmt.extend_from_slice(&(8000 * BLOCK_ALIGN / FRAMES_PER_BLOCK).to_le_bytes());
+        fmt.extend_from_slice(&(BLOCK_ALIGN as u16).to_le_bytes());
+        fmt.extend_from_slice(&4u16.to_le_bytes());
+        fmt.extend_from_slice(&2u16.to_le_bytes());
+        fmt.extend_from_slice(&(FRAMES_PER_BLOCK as u16).to_le_bytes());
+
+        let mut body = b"WAVE".to_vec();
+        let frames = FRAMES_PER_BLOCK * steps.len() as u32;
+        for (id, chunk) in [
+            (b"fmt ", fmt),
+            (b"fact", frames.to_le_bytes().to_vec()),
+            (b"data", data),
+        ] {
+            body.extend_from_slice(id);
+            body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            body.extend_from_slice(&chunk);
+        }
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        file.extend_from_slice(&body);
+        std::fs::write(path, file).unwrap();
+    }
+
+    #[test]
+    fn test_decode_error_partway_fails_the_mix() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("corrupt.wav");
+        // The fifth of nine blocks is corrupt
+        write_adpcm_wav(&path, &[0, 0, 0, 0, 200, 0, 0, 0, 0]);
+
+        let mut mixer = AudioMixer::new(8000, 1);
+        mixer
+            .add_streamed_track(
+                AudioDecoder::stream(&path).unwrap(),
+                &track(AudioTrackType::Music, 0.0),
+            )
+            .unwrap();
+
+        // Rather than the rest of the track mixed as silence
+        let error = mixer.mix_with_report(1.0).unwrap_err().to_string();
+        assert!(error.contains("invalid step index"), "{}", error);
+    }
+
+    #[test]
+    fn test_mix_to_file_normalizes_streamed_track() {
+        let rate = 8000;
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("tone.wav");
+        let output = dir.path().join("mix.wav");
+        write_wav(&input, &tone(0.1, 5.0, rate), rate);
+
+        let mut mixer = AudioMixer::new(rate, 2);
+        mixer
+            .add_streamed_track(
+                AudioDecoder::stream(&input).unwrap(),
+                &track(AudioTrackType::Music, 0.0),
+            )
+            .unwrap();
+        mixer.set_normalization(Some(LoudnessNormalization::default()));
+
+        let report = mixer
+            .mix_to_file(&output, 5.0, AudioExportFormat::Wav16)
+            .unwrap()
+            .loudness
+            .unwrap();
+        assert!((report.final_lufs - -14.0).abs() < 0.5);
+
+        let reader = hound::WavReader::open(&output).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        assert_eq!(reader.duration(), 5 * rate);
+    }
+
+    #[test]
+    fn test_hot_mix_reports_clipping() {
+        let rate = 8000;
+        let mut mixer = AudioMixer::new(rate, 1);
+        mixer.add_track(tone(0.8, 1.0, rate), rate, 1, 0.0, 1.0);
+        mixer.add_track(tone(0.8, 1.0, rate), rate, 1, 0.0, 1.0);
+
+        let (mixed, report) = mixer.mix_with_report(1.0).unwrap();
+        let analysis = report.analysis;
+        assert!((analysis.peak - 1.6).abs() < 0.01, "peak {}", analysis.peak);
+        assert!(analysis.peak_db() > 4.0);
+        assert!(analysis.clipped_samples > 0);
+        // The output itself is soft clipped
+        assert!(mixed.iter().all(|s| s.abs() < 1.0));
+
+        assert_eq!(analysis.track_peaks.len(), 2);
+        assert_eq!(analysis.track_peaks[0].name, "track 1");
+        assert!((analysis.track_peaks[0].peak - 0.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_silent_gap_detected() {
+        let rate = 8000;
+        let mut mixer = AudioMixer::new(rate, 2);
+        mixer.set_analysis(AudioAnalysisSettings {
+            silence_threshold_db: -50.0,
+            min_silence_seconds: 1.0,
+        });
+        // Audio for 0-2s and 4.5-6s, silence in between
+        mixer.add_track(tone(0.5, 2.0, rate), rate, 1, 0.0, 1.0);
+        mixer.add_track(tone(0.5, 1.5, rate), rate, 1, 4.5, 1.0);
+
+        let (_, report) = mixer.mix_with_report(6.0).unwrap();
+        let gaps = report.analysis.silent_gaps;
+        assert_eq!(gaps.len(), 1, "{:?}", gaps);
+        assert!((gaps[0].start - 2.0).abs() < 0.01, "{:?}", gaps[0]);
+        assert!((gaps[0].end - 4.5).abs() < 0.01, "{:?}", gaps[0]);
+        assert_eq!(report.analysis.clipped_samples, 0);
+    }
+
+    fn channel_energy(mixed: &[f32], channel: usize) -> f32 {
+        mixed.iter().skip(channel).step_by(2).map(|s| s * s).sum()
+    }
+
+    #[test]
+    fn test_mono_panned_hard_left() {
+        let rate = 8000;
+        let mut config = track(AudioTrackType::SoundEffect, 0.0);
+        config.pan = -1.0;
+
+        let mut mixer = AudioMixer::new(rate, 2);
+        mixer
+            .add_script_track(tone(0.3, 1.0, rate), rate, 1, &config)
+            .unwrap();
+        let mixed = mixer.mix(1.0).unwrap();
+
+        assert!(mixed.iter().skip(1).step_by(2).all(|s| s.abs() < 1e-6));
+        assert!(channel_energy(&mixed, 0) > 0.0);
+    }
+
+    #[test]
+    fn test_center_pan_is_balanced_and_constant_power() {
+        let rate = 8000;
+        let energies = |pan: f32| {
+            let mut config = track(AudioTrackType::SoundEffect, 0.0);
+            config.pan = pan;
+            let mut mixer = AudioMixer::new(rate, 2);
+            mixer
+                .add_script_track(tone(0.1, 1.0, rate), rate, 1, &config)
+                .unwrap();
+            let mixed = mixer.mix(1.0).unwrap();
+            (channel_energy(&mixed, 0), channel_energy(&mixed, 1))
+        };
+
+        let (left, right) = energies(0.0);
+        assert!((left - right).abs() < left * 1e-4);
+
+        // Total power stays the same wherever the track is placed
+        let (left_30, right_30) = energies(0.3);
+        assert!(right_30 > left_30);
+        assert!(((left_30 + right_30) - (left + right)).abs() < (left + right) * 0.01);
+    }
+
+    #[test]
+    fn test_stereo_balance_and_mono_output() {
+        let rate = 8000;
+        let stereo: Vec<f32> = tone(0.2, 1.0, rate)
+            .into_iter()
+            .flat_map(|s| [s, s])
+            .collect();
+        let mut config = track(AudioTrackType::Music, 0.0);
+        config.pan = 1.0;
+
+        let mut mixer = AudioMixer::new(rate, 2);
+        mixer
+            .add_script_track(stereo.clone(), rate, 2, &config)
+            .unwrap();
+        let mixed = mixer.mix(1.0).unwrap();
+        assert!(channel_energy(&mixed, 0) < 1e-6);
+        // The right channel is left untouched (apart from the soft clipper)
+        let expected: Vec<f32> = stereo.iter().map(|s| s.tanh()).collect();
+        assert!((channel_energy(&mixed, 1) - channel_energy(&expected, 1)).abs() < 1e-3);
+
+        // Mono output ignores pan
+        let mut mono = AudioMixer::new(rate, 1);
+        mono.add_script_track(tone(0.2, 1.0, rate), rate, 1, &config)
+            .unwrap();
+        assert!(mono.mix(1.0).unwrap().iter().any(|s| s.abs() > 0.1));
+    }
+}
@@ -0,0 +1,22 @@
+//! Decoding, mixing and measuring audio. Only the `analysis` report types are
+//! built without the `render` feature.
+
+pub mod analysis;
+#[cfg(feature = "render")]
+pub mod decoder;
+#[cfg(feature = "render")]
+pub mod export;
+#[cfg(feature = "render")]
+pub mod loudness;
+#[cfg(feature = "render")]
+pub mod mixer;
+
+pub use analysis::{AudioAnalysisReport, AudioAnalysisSettings, SilentGap, TrackPeak};
+#[cfg(feature = "render")]
+pub use decoder::{AudioDecoder, AudioStream, PacketSamples};
+#[cfg(feature = "render")]
+pub use export::{AudioExportFormat, AudioWriter};
+#[cfg(feature = "render")]
+pub use loudness::{LoudnessMeter, LoudnessReport};
+#[cfg(feature = "render")]
+pub use mixer::{AudioMixer, MixReport};
@@ -0,0 +1,242 @@
+//! Portable copies of a project: every asset a script references gathered
+//! under one directory, next to the script rewritten to point at them.
+
+use crate::analysis::asset_audit::{AssetAuditAnalyzer, AssetKind};
+use crate::assets::AssetLoader;
+use crate::script::VideoScript;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A bundle written by [`AssetBundle::create`]
+#[derive(Debug, Clone)]
+pub struct AssetBundle {
+    /// The rewritten script
+    pub script: PathBuf,
+    /// Each copied file, from where it was to where it is now, relative to the bundle
+    pub files: Vec<(PathBuf, PathBuf)>,
+    /// Size of the copied files
+    pub bytes: u64,
+}
+
+impl AssetBundle {
+    /// Copy the files `script` references, resolved like `assets` does, into
+    /// `dest/assets/{images,videos,fonts,audio}/` and write the script as
+    /// `dest/script_name` with relative paths to the copies. A file referenced
+    /// twice is copied once; different files with the same name are told
+    /// apart by a number. Nothing is written when an asset is missing.
+    pub fn create(
+        script: &VideoScript,
+        assets: &AssetLoader,
+        dest: &Path,
+        script_name: &str,
+    ) -> Result<Self> {
+        let paths = AssetAuditAnalyzer::paths(script);
+        let missing: Vec<String> = paths
+            .iter()
+            .map(|(path, _, _)| assets.resolve_path(path))
+            .filter(|resolved| !resolved.is_file())
+            .map(|resolved| resolved.display().to_string())
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Missing assets:\n  {}", missing.join("\n  "));
+        }
+        if let (Ok(dest), Ok(base)) = (fs::canonicalize(dest), fs::canonicalize(assets.base_path()))
+        {
+            if dest == base {
+                anyhow::bail!(
+                    "{} is the script's own directory; copy to another one",
+                    dest.display()
+                );
+            }
+        }
+
+        // Where each file goes, by its canonical path so `a/../x.png` and
+        // `x.png` are one copy
+        let mut targets: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let mut files = Vec::new();
+        let mut rewritten = Vec::new();
+        for (path, kind, _) in &paths {
+            let resolved = assets.resolve_path(path);
+            let file = fs::canonicalize(&resolved)
+                .with_context(|| format!("Failed to resolve {}", resolved.display()))?;
+            let target = match targets.get(&file) {
+                Some(target) => target.clone(),
+                None => {
+                    let target = unique_target(&file, *kind, &files);
+                    targets.insert(file.clone(), target.clone());
+                    files.push((file, target.clone()));
+                    target
+                }
+            };
+            rewritten.push(target);
+        }
+
+        let mut bytes = 0;
+        for (file, target) in &files {
+            let to = dest.join(target);
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            bytes += fs::copy(file, &to).with_context(|| {
+                format!("Failed to copy {} to {}", file.display(), to.display())
+            })?;
+        }
+
+        let mut bundled = script.clone();
+        for (path, target) in AssetAuditAnalyzer::paths_mut(&mut bundled)
+            .into_iter()
+            .zip(rewritten)
+        {
+            *path = target;
+        }
+        let script_path = dest.join(script_name);
+        fs::write(&script_path, serde_json::to_string_pretty(&bundled)? + "\n")
+            .with_context(|| format!("Failed to write {}", script_path.display()))?;
+
+        Ok(Self {
+            script: script_path,
+            files,
+            bytes,
+        })
+    }
+}
+
+/// `assets/<kind>/<file name>`, numbered when another file already took the name
+fn unique_target(file: &Path, kind: AssetKind, taken: &[(PathBuf, PathBuf)]) -> PathBuf {
+    let directory = Path::new("assets").join(match kind {
+        AssetKind::Image => "images",
+        AssetKind::Video => "videos",
+        AssetKind::Font => "fonts",
+        AssetKind::Audio => "audio",
+    });
+    let name = file.file_name().unwrap_or_default();
+    let mut target = directory.join(name);
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = file
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut n = 2;
+    while taken.iter().any(|(_, known)| *known == target) {
+        target = directory.join(format!("{}-{}{}", stem, n, extension));
+        n += 1;
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Layer, Metadata, Position, Resolution, Scene, SceneType};
+    use tempfile::TempDir;
+
+    fn image(source: &str) -> Layer {
+        Layer::Image {
+            source: source.into(),
+            effects: vec![],
+            transform: Default::default(),
+            crop: None,
+        }
+    }
+
+    fn create_script(layers: Vec<Layer>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 3.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "intro".into(),
+                scene_type: SceneType::Hook,
+                duration: 3.0,
+                layers,
+                transition: None,
+                narration: None,
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_bundle_rewrites_relative_paths() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("art")).unwrap();
+        fs::write(project.join("logo.png"), [1u8; 20]).unwrap();
+        fs::write(project.join("art/logo.png"), [2u8; 30]).unwrap();
+        let font = dir.path().join("shared.ttf");
+        fs::write(&font, [3u8; 10]).unwrap();
+
+        let script = create_script(vec![
+            image("logo.png"),
+            image("art/logo.png"),
+            // The first logo again
+            image("art/../logo.png"),
+            Layer::Text {
+                content: "Hello".into(),
+                font: font.clone(),
+                font_size: 48.0,
+                color: Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                },
+                position: Position { x: 0, y: 0 },
+                effects: vec![],
+            },
+        ]);
+        let dest = dir.path().join("bundle");
+        let bundle =
+            AssetBundle::create(&script, &AssetLoader::new(&project), &dest, "video.json").unwrap();
+
+        assert_eq!(bundle.files.len(), 3);
+        assert_eq!(bundle.bytes, 60);
+        let bundled = crate::ScriptParser::parse_json(&dest.join("video.json")).unwrap();
+        let paths: Vec<PathBuf> = AssetAuditAnalyzer::paths(&bundled)
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("assets/images/logo.png"),
+                PathBuf::from("assets/images/logo-2.png"),
+                PathBuf::from("assets/images/logo.png"),
+                PathBuf::from("assets/fonts/shared.ttf"),
+            ]
+        );
+        assert_eq!(fs::read(dest.join(&paths[1])).unwrap(), [2u8; 30]);
+
+        // The bundle resolves on its own
+        let report = AssetAuditAnalyzer::analyze(&bundled, &AssetLoader::new(&dest));
+        assert!(report.references.iter().all(|r| r.bytes.is_some()));
+    }
+
+    #[test]
+    fn test_missing_asset_writes_nothing() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("logo.png"), [1u8; 20]).unwrap();
+        let script = create_script(vec![image("logo.png"), image("gone.png")]);
+        let dest = dir.path().join("bundle");
+
+        let error = AssetBundle::create(&script, &AssetLoader::new(dir.path()), &dest, "s.json")
+            .unwrap_err();
+        assert!(error.to_string().contains("gone.png"));
+        assert!(!dest.exists());
+
+        // Nor into the script's own directory
+        let script = create_script(vec![image("logo.png")]);
+        let loader = AssetLoader::new(dir.path());
+        assert!(AssetBundle::create(&script, &loader, dir.path(), "s.json").is_err());
+    }
+}
@@ -0,0 +1,537 @@
+//! The command line: every subcommand and flag, here rather than in the
+//! binary so completions and tests can build the same `clap::Command`.
+
+use crate::analysis::narrative::Severity;
+use crate::analysis::report::ReportFormat;
+use crate::exit;
+use crate::renderer::{contact_sheet::SceneFrame, OutputFormat, VideoCodec};
+use crate::templates::TemplateType;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::Write;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Parser)]
+#[command(name = "interstellar-triangulum")]
+#[command(about = "Digital Artisan Video Engine", long_about = None)]
+#[command(after_long_help = exit::help_text())]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// List the GPU adapters the native engine can use and exit
+    #[arg(long)]
+    pub list_gpus: bool,
+
+    /// Print one JSON document with the results on stdout, and messages on stderr
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Print errors only
+    #[arg(long, global = true, conflicts_with = "json")]
+    pub quiet: bool,
+
+    /// Print diagnostics on stderr: -v for debug events and span timings, -vv for everything
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+}
+
+#[derive(Subcommand)]
+// Parsed once, so the size of `Render` doesn't matter
+#[allow(clippy::large_enum_variant)]
+pub enum Commands {
+    /// Render a script to video
+    Render {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Renderer engine to use
+        #[arg(long)]
+        renderer: Option<String>,
+
+        /// Output directory
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Video file to write (default: <output dir>/<slugified title>.mp4)
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Export analysis report
+        #[arg(long)]
+        export_report: Option<String>,
+
+        /// Fail when the overall score (as printed by `score`) is below this
+        #[arg(long)]
+        fail_on_low_score: Option<u32>,
+
+        /// Force CPU rendering (disable GPU)
+        #[arg(long)]
+        force_cpu: bool,
+
+        /// Render on the GPU adapter whose name contains this (see --list-gpus)
+        #[arg(long, value_name = "NAME", conflicts_with = "force_cpu")]
+        gpu_adapter: Option<String>,
+
+        /// Normalize the audio mix to this integrated loudness (LUFS)
+        #[arg(long, allow_hyphen_values = true)]
+        audio_target_lufs: Option<f32>,
+
+        /// Render at this resolution instead of the script's, e.g. 1280x720
+        #[arg(long, value_name = "WxH", value_parser = parse_size)]
+        resolution: Option<(u32, u32)>,
+
+        /// Render at this frame rate instead of the script's
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        fps: Option<u32>,
+
+        /// With --resolution, scale positions, font sizes and transforms to match
+        #[arg(long, requires = "resolution")]
+        scale_layout: bool,
+
+        /// Render only frames START..END (end exclusive) to files, without encoding
+        #[arg(long, value_name = "START..END", value_parser = parse_frame_range)]
+        frames: Option<Range<u32>>,
+
+        /// Render only the frame shown at this time (seconds) to a PNG
+        #[arg(long, value_name = "SECONDS", conflicts_with = "frames")]
+        at: Option<f32>,
+
+        /// Fast low-resolution, reduced-frame-rate render to <title>_preview.mp4
+        #[arg(long, conflicts_with_all = ["frames", "at"])]
+        preview: bool,
+
+        /// Re-render a preview whenever the script or its assets change, until Ctrl-C
+        #[arg(long, conflicts_with_all = ["frames", "at", "fail_on_low_score"])]
+        watch: bool,
+
+        /// Check the script and print what the render would do, without rendering or writing anything
+        #[arg(long, conflicts_with_all = ["frames", "at", "watch"])]
+        dry_run: bool,
+
+        /// Print render progress as one JSON object per line on stdout instead of a
+        /// progress bar, even with --quiet
+        #[arg(long)]
+        json_progress: bool,
+
+        /// Write YouTube chapter timestamps to this file and embed chapters in the video
+        #[arg(long, value_name = "PATH")]
+        export_chapters: Option<String>,
+
+        /// Give every scene its own chapter, even those under YouTube's 10 second minimum
+        #[arg(long, requires = "export_chapters")]
+        keep_short_chapters: bool,
+
+        /// Keep the frames a failed or interrupted Blender render saved and render the rest
+        #[arg(long)]
+        resume: bool,
+
+        /// Keep the frame files an earlier native render of the same script and settings
+        /// left in the output directory, and render only the missing ones
+        #[arg(long)]
+        incremental: bool,
+
+        /// Render without the configured or script's watermark, e.g. for clean masters
+        #[arg(long)]
+        no_watermark: bool,
+
+        /// Draw safe areas, a thirds grid, text layer boxes and the timecode over
+        /// every frame, as configured in renderer.debug
+        #[arg(long)]
+        debug_overlay: bool,
+
+        /// Number of Blender processes rendering at once
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+
+        /// Video codec of the encoded video
+        #[arg(long, value_enum)]
+        codec: Option<VideoCodec>,
+
+        /// Constant quality of the encoded video (lower is better)
+        #[arg(long)]
+        crf: Option<u32>,
+
+        /// Encoder speed preset (e.g. medium, or hq for ProRes)
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Output format: mp4, webm, webm-alpha, gif or png-sequence
+        #[arg(long, value_parser = OutputFormat::from_str)]
+        format: Option<OutputFormat>,
+
+        /// Most colors in a GIF's palette (2-256)
+        #[arg(long, requires = "format", value_name = "N")]
+        gif_colors: Option<u32>,
+
+        /// Highest frame rate of a GIF
+        #[arg(long, requires = "format", value_name = "FPS")]
+        gif_fps: Option<u32>,
+
+        /// SRT subtitles to add to the video as a subtitle stream
+        #[arg(long, value_name = "FILE")]
+        subtitles: Option<PathBuf>,
+
+        /// Burn the subtitles into the frames instead
+        #[arg(long, requires = "subtitles")]
+        burn_subtitles: bool,
+    },
+
+    /// Render a thumbnail frame to PNG and JPEG (no FFmpeg needed)
+    Thumbnail {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Time of the frame in seconds (default: 1 second into the Hook scene)
+        #[arg(long, value_name = "SECONDS")]
+        at: Option<f32>,
+
+        /// Also save a copy scaled and cropped to this size, e.g. 1280x720
+        #[arg(long, value_name = "WxH", value_parser = parse_size)]
+        size: Option<(u32, u32)>,
+
+        /// Draw the script's title over the frame
+        #[arg(long)]
+        with_title: bool,
+
+        /// Font for --with-title (default: the font of the first text layer)
+        #[arg(long, value_name = "FILE", requires = "with_title")]
+        title_font: Option<PathBuf>,
+
+        /// Output directory
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Render on the CPU even when a GPU is available
+        #[arg(long)]
+        force_cpu: bool,
+    },
+
+    /// Render one frame of every scene into a labeled grid, preview.png
+    Preview {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Thumbnails per row
+        #[arg(long, default_value_t = 4)]
+        columns: u32,
+
+        /// Width of each thumbnail in pixels
+        #[arg(long, default_value_t = 320)]
+        thumb_width: u32,
+
+        /// Which frame of each scene to show
+        #[arg(long, value_enum, default_value_t = SceneFrame::Mid)]
+        frame_per_scene: SceneFrame,
+
+        /// Font for the labels (default: the font of the first text layer)
+        #[arg(long, value_name = "FILE")]
+        label_font: Option<PathBuf>,
+
+        /// Render at this resolution instead of the script's, e.g. 1280x720
+        #[arg(long, value_name = "WxH", value_parser = parse_size)]
+        resolution: Option<(u32, u32)>,
+
+        /// Render at this frame rate instead of the script's
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        fps: Option<u32>,
+
+        /// With --resolution, scale positions, font sizes and transforms to match
+        #[arg(long, requires = "resolution")]
+        scale_layout: bool,
+
+        /// Draw safe areas, a thirds grid, text layer boxes and the timecode over
+        /// every thumbnail, as configured in renderer.debug
+        #[arg(long)]
+        debug_overlay: bool,
+
+        /// Output directory
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Render on the CPU even when a GPU is available
+        #[arg(long)]
+        force_cpu: bool,
+    },
+
+    /// Mix the script's audio to a file without rendering any frames
+    Audio {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// File to write (default: the script's title in the output directory)
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Audio file format (overrides config)
+        #[arg(long, value_parser = ["wav", "flac"])]
+        format: Option<String>,
+
+        /// Output directory
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Show what changed between two versions of a script, and how the scores moved
+    Diff {
+        /// The earlier script
+        #[arg(value_name = "OLD")]
+        old: String,
+
+        /// The later script
+        #[arg(value_name = "NEW")]
+        new: String,
+    },
+
+    /// Print a script's overall score, failing below a minimum; reads no assets
+    Score {
+        /// Path to the script JSON file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Also print the narrative, credibility and retention scores
+        #[arg(long)]
+        breakdown: bool,
+
+        /// Exit with an error when the overall score is below this [default: score.min]
+        #[arg(long, value_name = "N")]
+        min: Option<u32>,
+    },
+
+    /// Run every analysis and print one machine-readable report, without rendering
+    Analyze {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Write the report to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value = "json")]
+        format: ReportFormat,
+
+        /// Exit with an error when the overall score is below this
+        #[arg(long, value_name = "SCORE")]
+        fail_below: Option<u32>,
+
+        /// Exit with an error when an issue is at least this severe
+        #[arg(long, value_enum, value_name = "SEVERITY")]
+        fail_on: Option<Severity>,
+
+        /// Request every URL citation and report the unreachable ones
+        #[arg(long)]
+        check_citations: bool,
+
+        /// Seconds to wait for each citation URL
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 5.0,
+            requires = "check_citations"
+        )]
+        citation_timeout: f32,
+    },
+
+    /// List the files a script uses and find unused and duplicate ones
+    Assets {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        #[command(subcommand)]
+        action: Option<AssetsAction>,
+    },
+
+    /// Validate script without rendering
+    Validate {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Fail on warnings
+        #[arg(long)]
+        fail_on_warnings: bool,
+
+        /// Also write the findings as a report in this format, e.g. sarif or junit
+        #[arg(long, value_enum, requires = "report_out")]
+        report_format: Option<ReportFormat>,
+
+        /// File to write the --report-format report to
+        #[arg(long, value_name = "FILE", requires = "report_format")]
+        report_out: Option<PathBuf>,
+
+        /// Request every URL citation and report the unreachable ones
+        #[arg(long)]
+        check_citations: bool,
+
+        /// Seconds to wait for each citation URL
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 5.0,
+            requires = "check_citations"
+        )]
+        citation_timeout: f32,
+    },
+
+    /// Generate a script template
+    Template {
+        /// Type of template to generate
+        #[arg(value_enum)]
+        #[arg(name = "type")]
+        template_type: TemplateType,
+
+        /// Total duration in seconds
+        #[arg(short, long, default_value_t = 60.0)]
+        duration: f32,
+
+        /// Scenes in all; the body is split evenly into the ones between the first and last
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u16).range(3..))]
+        scenes: u16,
+
+        /// Add a music and a voiceover track with placeholder paths to replace
+        #[arg(long)]
+        with_audio: bool,
+
+        /// Write the script to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Overwrite the --out file when it exists
+        #[arg(long, requires = "out")]
+        force: bool,
+    },
+
+    /// Create a project directory with a script, the default config and an assets folder
+    Init {
+        /// Template of the generated script
+        #[arg(long, value_enum, default_value = "explainer")]
+        template: TemplateType,
+
+        /// Directory to create; its last component is the video's title
+        #[arg(long, default_value = "my-video")]
+        name: String,
+
+        /// Write into the directory even when it isn't empty
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Clean output and cache directories
+    Clean,
+
+    /// Show the configuration in effect, or write the default one
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Print a completion script for a shell
+    Completions {
+        /// Shell to complete for
+        #[arg(value_enum)]
+        shell: Shell,
+
+        /// Write the script to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Every resolved value, with whether it came from the default, the file or the environment
+    Show,
+
+    /// Write interstellar.toml with every default and a comment on each, in the working directory
+    Init {
+        /// Overwrite interstellar.toml when it exists
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print the configuration file that was loaded
+    Path,
+}
+
+#[derive(Subcommand)]
+pub enum AssetsAction {
+    /// Table of every referenced file with where it resolves to, its size and type
+    List,
+
+    /// Fail if any referenced file is missing, printing each one
+    Verify,
+
+    /// Copy every referenced file and the script, pointing at the copies, into a directory
+    Copy {
+        /// Directory to write the bundle to
+        #[arg(long, value_name = "DIR")]
+        dest: PathBuf,
+    },
+}
+
+/// Parse `WIDTHxHEIGHT`
+pub fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got '{}'", value))?;
+    let parse = |side: &str| match side.parse::<u32>() {
+        Ok(side) if side > 0 => Ok(side),
+        _ => Err(format!("invalid size '{}'", value)),
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+/// Parse `START..END` (end exclusive)
+pub fn parse_frame_range(value: &str) -> Result<Range<u32>, String> {
+    let (start, end) = value
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, got '{}'", value))?;
+    let start: u32 = start
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid start frame '{}'", start))?;
+    let end: u32 = end
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid end frame '{}'", end))?;
+    if start >= end {
+        return Err(format!("range {}..{} is empty", start, end));
+    }
+    Ok(start..end)
+}
+
+/// Write the completion script of every subcommand and flag for `shell`
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash_completions_cover_subcommands_flags_and_values() {
+        let mut script = Vec::new();
+        write_completions(Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("render"));
+        assert!(script.contains("--force-cpu"));
+        assert!(script.contains("explainer"));
+    }
+
+    #[test]
+    fn test_command_is_consistent() {
+        Cli::command().debug_assert();
+    }
+}
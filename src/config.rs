@@ -1,68 +1,787 @@
-use serde::Deserialize;
-use std::path::PathBuf;
+use crate::analysis::accessibility::AccessibilitySettings;
+use crate::analysis::analyzer::AnalysisConfig;
+use crate::analysis::brand_safety::BrandSafetySettings;
+use crate::analysis::credibility::{
+    CredibilityDictionaries, Dictionary, DEFAULT_ABSOLUTES, DEFAULT_CLAIM_SUPERLATIVES,
+    DEFAULT_HYPE_WORDS, DEFAULT_WEASEL_WORDS,
+};
+use crate::analysis::hook::HookSettings;
+use crate::analysis::narrative::NarrativeSettings;
+use crate::analysis::readability::ReadabilitySettings;
+use crate::analysis::repetition::RepetitionSettings;
+use crate::analysis::report::ScoreSettings;
+use crate::analysis::sentiment::Lexicon;
+use crate::audio::{AudioAnalysisSettings, AudioExportFormat};
+use crate::exit::{ExitCode, WithExitCode};
+use crate::renderer::engine::DEFAULT_TEXTURE_BUDGET_MB;
+use crate::renderer::{
+    BitDepth, BlenderOptions, DebugOverlaySettings, EncodeSettings, FrameFormat, GpuOptions,
+    PreviewSettings,
+};
+use crate::script::{VideoConfig, VideoScript, Watermark};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     pub renderer: RendererConfig,
     pub video: VideoConfig,
     pub assets: AssetsConfig,
+    #[serde(default)]
+    pub audio: AudioSettings,
+    /// GPU adapter selection for the native engine
+    #[serde(default)]
+    pub gpu: GpuOptions,
+    /// Blender executable and render settings for the Blender engine
+    #[serde(default)]
+    pub blender: BlenderOptions,
+    /// Codec and quality of the encoded video
+    #[serde(default)]
+    pub encoder: EncodeSettings,
+    /// Word lists of the credibility analysis
+    #[serde(default)]
+    pub credibility: CredibilitySettings,
+    /// Reading level thresholds of the narrative analysis
+    #[serde(default)]
+    pub readability: ReadabilitySettings,
+    /// Weight of the hook score in the narrative score
+    #[serde(default)]
+    pub hook: HookSettings,
+    /// Word valences of the emotional arc
+    #[serde(default)]
+    pub sentiment: SentimentSettings,
+    /// Similarity threshold of the scene repetition check
+    #[serde(default)]
+    pub repetition: RepetitionSettings,
+    /// Contrast, font size and safe margin thresholds for on-screen text
+    #[serde(default)]
+    pub accessibility: AccessibilitySettings,
+    /// Threshold of the `score` command
+    #[serde(default)]
+    pub score: ScoreSettings,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RendererConfig {
     pub engine: String, // "native" or "blender"
     pub output_dir: PathBuf,
+    /// Image format of the native engine's intermediate frames
+    #[serde(default)]
+    pub frame_format: FrameFormat,
+    /// Resolution scale and frame step used by `render --preview`
+    #[serde(default)]
+    pub preview: PreviewSettings,
+    /// GPU memory (MiB) the native engine may spend on cached image textures
+    #[serde(default = "default_texture_budget_mb")]
+    pub texture_budget_mb: u64,
+    /// MSAA samples per pixel on the GPU (1, 2 or 4)
+    #[serde(default = "default_samples")]
+    pub samples: u32,
+    /// Bits per channel of the native engine's frames (8 or 16)
+    #[serde(default)]
+    pub bit_depth: BitDepth,
+    /// Seeds everything random in the native engine's frames, like the
+    /// dithering of 16-bit frames
+    #[serde(default)]
+    pub seed: u64,
+    /// Drawn over every frame of scripts without their own `metadata.watermark`
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
+    /// Render without any watermark, for clean masters. Set by
+    /// `render --no-watermark`.
+    #[serde(skip)]
+    pub no_watermark: bool,
+    /// What the layout debugging overlay of `--debug-overlay` draws
+    #[serde(default)]
+    pub debug: DebugOverlaySettings,
+    /// Keep the frame files an earlier native render of the same script with
+    /// the same settings left in `output_dir`, and render only the missing
+    /// ones. Set by `render --incremental`.
+    #[serde(skip)]
+    pub incremental: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct VideoConfig {
-    pub default_resolution: String,
-    pub default_fps: u32,
+fn default_samples() -> u32 {
+    1
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_texture_budget_mb() -> u64 {
+    DEFAULT_TEXTURE_BUDGET_MB
+}
+
+impl RendererConfig {
+    /// `texture_budget_mb` in bytes
+    pub fn texture_budget_bytes(&self) -> u64 {
+        self.texture_budget_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Give `script` the watermark it's rendered with: its own, or else the
+    /// configured one, and none at all with `no_watermark`
+    pub fn apply_watermark(&self, script: &mut VideoScript) {
+        if self.no_watermark {
+            script.metadata.watermark = None;
+        } else if script.metadata.watermark.is_none() {
+            script.metadata.watermark = self.watermark.clone();
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AssetsConfig {
     pub base_path: PathBuf,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioSettings {
+    /// Format of the intermediate mixed audio file
+    #[serde(default)]
+    pub export_format: AudioExportFormat,
+    /// Level (dBFS) below which the mix counts as silent
+    #[serde(default = "default_silence_threshold_db")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub silence_threshold_db: f32,
+    /// Shortest silent gap (seconds) worth reporting
+    #[serde(default = "default_min_silence_seconds")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub min_silence_seconds: f32,
+}
+
+fn default_silence_threshold_db() -> f32 {
+    AudioAnalysisSettings::default().silence_threshold_db
+}
+
+fn default_min_silence_seconds() -> f32 {
+    AudioAnalysisSettings::default().min_silence_seconds
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            export_format: AudioExportFormat::default(),
+            silence_threshold_db: default_silence_threshold_db(),
+            min_silence_seconds: default_min_silence_seconds(),
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Thresholds for the mixer's clipping and silence analysis
+    pub fn analysis(&self) -> AudioAnalysisSettings {
+        AudioAnalysisSettings {
+            silence_threshold_db: self.silence_threshold_db,
+            min_silence_seconds: self.min_silence_seconds,
+        }
+    }
+}
+
+/// Custom word lists for the credibility checks. A list set here replaces the
+/// default one; a `*_file` (one word or phrase per line, `#` starting comment lines)
+/// does too, or adds to the list when both are set. Files are read relative to
+/// the working directory.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CredibilitySettings {
+    pub weasel_words: Option<Vec<String>>,
+    pub weasel_words_file: Option<PathBuf>,
+    pub hype_words: Option<Vec<String>>,
+    pub hype_words_file: Option<PathBuf>,
+    pub claim_superlatives: Option<Vec<String>>,
+    pub claim_superlatives_file: Option<PathBuf>,
+    pub absolutes: Option<Vec<String>>,
+    pub absolutes_file: Option<PathBuf>,
+    /// Allow and deny lists for the brand-safety screen
+    #[serde(default)]
+    pub brand_safety: BrandSafetySettings,
+}
+
+impl CredibilitySettings {
+    /// The word lists to analyze with, reading any files
+    pub fn dictionaries(&self) -> anyhow::Result<CredibilityDictionaries> {
+        Ok(CredibilityDictionaries {
+            weasel_words: Self::dictionary(
+                "weasel_words",
+                DEFAULT_WEASEL_WORDS,
+                &self.weasel_words,
+                &self.weasel_words_file,
+            )?,
+            hype_words: Self::dictionary(
+                "hype_words",
+                DEFAULT_HYPE_WORDS,
+                &self.hype_words,
+                &self.hype_words_file,
+            )?,
+            claim_superlatives: Self::dictionary(
+                "claim_superlatives",
+                DEFAULT_CLAIM_SUPERLATIVES,
+                &self.claim_superlatives,
+                &self.claim_superlatives_file,
+            )?,
+            absolutes: Self::dictionary(
+                "absolutes",
+                DEFAULT_ABSOLUTES,
+                &self.absolutes,
+                &self.absolutes_file,
+            )?,
+            brand_safety: self.brand_safety.clone(),
+        })
+    }
+
+    fn dictionary(
+        name: &'static str,
+        defaults: &[&str],
+        words: &Option<Vec<String>>,
+        file: &Option<PathBuf>,
+    ) -> anyhow::Result<Dictionary> {
+        let mut origins = Vec::new();
+        let mut all = Vec::new();
+        if let Some(words) = words {
+            origins.push("config".to_string());
+            all.extend(words.iter().cloned());
+        }
+        if let Some(file) = file {
+            origins.push(file.display().to_string());
+            all.extend(Self::read_word_file(file).exit_code(ExitCode::ConfigError)?);
+        }
+
+        if origins.is_empty() {
+            let defaults = defaults.iter().map(|word| word.to_string()).collect();
+            return Dictionary::new(name, "default", defaults);
+        }
+        Dictionary::new(name, origins.join(" and "), all).exit_code(ExitCode::ConfigError)
+    }
+
+    /// Lines of a word file, without blank lines and `#` comments
+    fn read_word_file(path: &Path) -> anyhow::Result<Vec<String>> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read word list {}", path.display()))?;
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+/// A custom word list for the emotional arc, e.g. for scripts in another
+/// language. `lexicon` maps words to valences from -5 to 5; a `lexicon_file` has
+/// one word and its valence per line (AFINN format). Either replaces the
+/// built-in English list, and both together are combined.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SentimentSettings {
+    pub lexicon: Option<HashMap<String, i32>>,
+    pub lexicon_file: Option<PathBuf>,
+}
+
+impl SentimentSettings {
+    pub fn lexicon(&self) -> anyhow::Result<Lexicon> {
+        let mut origins = Vec::new();
+        let mut words = HashMap::new();
+        if let Some(lexicon) = &self.lexicon {
+            origins.push("config".to_string());
+            words.extend(lexicon.clone());
+        }
+        if let Some(file) = &self.lexicon_file {
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read lexicon {}", file.display()))
+                .exit_code(ExitCode::ConfigError)?;
+            let entries = Lexicon::parse(&content)
+                .with_context(|| format!("Invalid lexicon {}", file.display()))
+                .exit_code(ExitCode::ConfigError)?;
+            origins.push(file.display().to_string());
+            words.extend(entries);
+        }
+
+        if origins.is_empty() {
+            return Ok(Lexicon::default());
+        }
+        Ok(Lexicon::new(origins.join(" and "), words))
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             renderer: RendererConfig {
                 engine: "native".to_string(),
                 output_dir: PathBuf::from("output"),
+                frame_format: FrameFormat::default(),
+                preview: PreviewSettings::default(),
+                texture_budget_mb: default_texture_budget_mb(),
+                samples: default_samples(),
+                bit_depth: BitDepth::default(),
+                seed: 0,
+                watermark: None,
+                no_watermark: false,
+                debug: DebugOverlaySettings::default(),
+                incremental: false,
             },
-            video: VideoConfig {
-                default_resolution: "1920x1080".to_string(),
-                default_fps: 30,
-            },
+            video: VideoConfig::default(),
             assets: AssetsConfig {
                 base_path: PathBuf::from("."),
             },
+            audio: AudioSettings::default(),
+            gpu: GpuOptions::default(),
+            blender: BlenderOptions::default(),
+            encoder: EncodeSettings::default(),
+            credibility: CredibilitySettings::default(),
+            readability: ReadabilitySettings::default(),
+            hook: HookSettings::default(),
+            sentiment: SentimentSettings::default(),
+            repetition: RepetitionSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            score: ScoreSettings::default(),
         }
     }
 }
 
 impl AppConfig {
+    /// Thresholds, weights and word valences of the narrative analysis, reading
+    /// any lexicon file
+    pub fn narrative(&self) -> anyhow::Result<NarrativeSettings> {
+        Ok(NarrativeSettings {
+            readability: self.readability.clone(),
+            hook: self.hook.clone(),
+            lexicon: self.sentiment.lexicon()?,
+            repetition: self.repetition.clone(),
+        })
+    }
+
+    /// Settings of every analysis, for a script whose files are in `base_path`,
+    /// reading any word list or lexicon file
+    pub fn analysis(&self, base_path: &Path) -> anyhow::Result<AnalysisConfig> {
+        Ok(AnalysisConfig {
+            base_path: base_path.to_path_buf(),
+            dictionaries: self.credibility.dictionaries()?,
+            narrative: self.narrative()?,
+            accessibility: self.accessibility.clone(),
+        })
+    }
+
     pub fn load() -> Result<Self, config::ConfigError> {
-        let builder = config::Config::builder()
+        Ok(Self::load_from(Path::new("."))?.config)
+    }
+
+    /// Load `interstellar.toml` (or `.json`, `.yaml`, ...) from `dir` when there
+    /// is one, then the `INTERSTELLAR_` environment variables over it, keeping
+    /// track of which values each of them set
+    pub fn load_from(dir: &Path) -> Result<LoadedConfig, config::ConfigError> {
+        let file = CONFIG_EXTENSIONS
+            .iter()
+            .map(|extension| dir.join(format!("{}.{}", CONFIG_FILE_STEM, extension)))
+            .find(|path| path.is_file());
+
+        let mut builder = config::Config::builder()
             .set_default("renderer.engine", "native")?
             .set_default("renderer.output_dir", "output")?
+            .set_default("renderer.frame_format", "png")?
             .set_default("video.default_resolution", "1920x1080")?
             .set_default("video.default_fps", 30)?
             .set_default("assets.base_path", ".")?
-            // Load from file if exists
-            .add_source(config::File::with_name("interstellar").required(false))
-            // Allow env var overrides (e.g. INTERSTELLAR_RENDERER__ENGINE=blender)
-            .add_source(config::Environment::with_prefix("INTERSTELLAR").separator("__"));
+            .set_default("audio.export_format", "wav32_float")?;
+        let mut file_keys = HashSet::new();
+        if let Some(path) = &file {
+            file_keys = source_keys(config::File::from(path.as_path()))?;
+            builder = builder.add_source(config::File::from(path.as_path()));
+        }
+        // Allow env var overrides (e.g. INTERSTELLAR_RENDERER__ENGINE=blender)
+        let env_keys = source_keys(environment())?;
+        let config = builder
+            .add_source(environment())
+            .build()?
+            .try_deserialize()?;
+
+        Ok(LoadedConfig {
+            config,
+            file,
+            file_keys,
+            env_keys,
+        })
+    }
+
+    /// The default configuration as TOML, with a comment on every table and key
+    pub fn commented_defaults() -> anyhow::Result<String> {
+        let defaults = toml::to_string_pretty(&Self::default())?;
+        let mut commented = String::from(
+            "# Interstellar Triangulum configuration. Every value here is the default;\n\
+             # INTERSTELLAR_<TABLE>__<KEY> environment variables override them\n\
+             # (e.g. INTERSTELLAR_RENDERER__ENGINE=blender).\n\n",
+        );
+        let mut table = String::new();
+        for line in defaults.lines() {
+            let key = if let Some(header) = line.strip_prefix('[') {
+                table = header.trim_end_matches(']').to_string();
+                Some(table.clone())
+            } else {
+                line.split_once(" = ")
+                    .map(|(key, _)| format!("{}.{}", table, key))
+            };
+            if let Some(comment) = key.and_then(|key| {
+                CONFIG_COMMENTS
+                    .iter()
+                    .find(|(known, _)| *known == key)
+                    .map(|(_, comment)| comment)
+            }) {
+                for comment_line in comment.lines() {
+                    commented.push_str("# ");
+                    commented.push_str(comment_line);
+                    commented.push('\n');
+                }
+            }
+            commented.push_str(line);
+            commented.push('\n');
+        }
+        Ok(commented)
+    }
+}
+
+/// Name of the configuration file, without its extension
+pub const CONFIG_FILE_STEM: &str = "interstellar";
 
-        builder.build()?.try_deserialize()
+/// Extensions of the configuration file, in the order they're looked for
+const CONFIG_EXTENSIONS: [&str; 7] = ["toml", "json", "yaml", "yml", "ini", "ron", "json5"];
+
+/// The `INTERSTELLAR_` environment variables, `__` separating nested keys
+fn environment() -> config::Environment {
+    config::Environment::with_prefix("INTERSTELLAR")
+        .prefix_separator("_")
+        .separator("__")
+}
+
+/// Dotted keys of the values `source` sets
+fn source_keys(
+    source: impl config::Source + Send + Sync + 'static,
+) -> Result<HashSet<String>, config::ConfigError> {
+    let value: serde_json::Value = config::Config::builder()
+        .add_source(source)
+        .build()?
+        .try_deserialize()?;
+    let mut keys = HashSet::new();
+    for (key, _) in flatten(&value) {
+        keys.insert(key);
+    }
+    Ok(keys)
+}
+
+/// Leaf values of `value` by dotted key; arrays are leaves, nulls are left out
+fn flatten(value: &serde_json::Value) -> Vec<(String, &serde_json::Value)> {
+    fn walk<'a>(
+        prefix: &str,
+        value: &'a serde_json::Value,
+        leaves: &mut Vec<(String, &'a serde_json::Value)>,
+    ) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, leaves);
+                }
+            }
+            serde_json::Value::Null => {}
+            _ => leaves.push((prefix.to_string(), value)),
+        }
+    }
+    let mut leaves = Vec::new();
+    walk("", value, &mut leaves);
+    leaves
+}
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::File => "file",
+            Self::Env => "env",
+        })
     }
 }
 
+/// One value of the resolved configuration
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigValue {
+    /// Dotted key, e.g. `renderer.engine`
+    pub key: String,
+    pub value: serde_json::Value,
+    pub source: ConfigSource,
+}
+
+impl ConfigValue {
+    /// The environment variable that sets this value
+    pub fn env_var(&self) -> String {
+        format!(
+            "INTERSTELLAR_{}",
+            self.key.to_uppercase().replace('.', "__")
+        )
+    }
+}
+
+/// A configuration and where its values came from, from [`AppConfig::load_from`]
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    /// The configuration file read, if there was one
+    pub file: Option<PathBuf>,
+    file_keys: HashSet<String>,
+    env_keys: HashSet<String>,
+}
+
+impl LoadedConfig {
+    /// Where the value at the dotted `key` came from; the environment wins over the file
+    pub fn source(&self, key: &str) -> ConfigSource {
+        if self.env_keys.contains(key) {
+            ConfigSource::Env
+        } else if self.file_keys.contains(key) {
+            ConfigSource::File
+        } else {
+            ConfigSource::Default
+        }
+    }
+
+    /// Every set value of the resolved configuration, sorted by key
+    pub fn values(&self) -> anyhow::Result<Vec<ConfigValue>> {
+        let value = serde_json::to_value(&self.config)?;
+        Ok(flatten(&value)
+            .into_iter()
+            .map(|(key, value)| ConfigValue {
+                source: self.source(&key),
+                value: value.clone(),
+                key,
+            })
+            .collect())
+    }
+}
+
+/// What `commented_defaults` says above each table and key of the default configuration
+const CONFIG_COMMENTS: &[(&str, &str)] = &[
+    ("renderer", "How and where videos are rendered"),
+    ("renderer.engine", "\"native\" (CPU/GPU) or \"blender\""),
+    (
+        "renderer.output_dir",
+        "Where rendered frames and videos are written",
+    ),
+    (
+        "renderer.frame_format",
+        "Image format of frame files, written only when FFmpeg is missing: \"png\" or \"ppm\"",
+    ),
+    (
+        "renderer.texture_budget_mb",
+        "GPU memory (MiB) the native engine may spend on cached image textures",
+    ),
+    (
+        "renderer.samples",
+        "MSAA samples per pixel on the GPU: 1, 2 or 4",
+    ),
+    (
+        "renderer.bit_depth",
+        "Bits per channel of the native engine's frames: 8 or 16",
+    ),
+    (
+        "renderer.seed",
+        "Seeds everything random in the native engine's frames, like 16-bit dithering",
+    ),
+    ("renderer.preview", "What `render --preview` renders"),
+    ("renderer.preview.scale", "Resolution factor"),
+    ("renderer.preview.frame_step", "Render every Nth frame"),
+    (
+        "renderer.debug",
+        "What `--debug-overlay` draws over frames, and in which RGBA colors",
+    ),
+    (
+        "renderer.debug.safe_areas",
+        "The 5% and 10% safe-area rectangles",
+    ),
+    (
+        "renderer.debug.thirds",
+        "Lines splitting the frame into thirds",
+    ),
+    (
+        "renderer.debug.text_bounds",
+        "Each text layer's box and anchor point, labeled <scene id>#<layer index>",
+    ),
+    (
+        "renderer.debug.timecode",
+        "Frame number and time in the top left corner",
+    ),
+    ("renderer.debug.safe_area_color", "Color of the safe areas"),
+    ("renderer.debug.thirds_color", "Color of the thirds grid"),
+    ("renderer.debug.text_bounds_color", "Color of text boxes"),
+    (
+        "renderer.debug.label_color",
+        "Color of the labels and the timecode",
+    ),
+    (
+        "renderer.debug.label_font",
+        "Font of the labels (default: the font of the script's first text layer)",
+    ),
+    ("video", "Metadata of scripts that leave it out"),
+    (
+        "video.default_resolution",
+        "Resolution of scripts without metadata.resolution",
+    ),
+    (
+        "video.default_fps",
+        "Frame rate of scripts without metadata.fps",
+    ),
+    ("assets", "Where assets are looked up"),
+    (
+        "assets.base_path",
+        "Directory relative asset paths are resolved from",
+    ),
+    ("audio", "Audio mixing and analysis"),
+    (
+        "audio.export_format",
+        "Format of the mixed audio: \"wav32_float\", \"wav16\" or \"flac\"",
+    ),
+    (
+        "audio.silence_threshold_db",
+        "Level below which the mix counts as silent",
+    ),
+    (
+        "audio.min_silence_seconds",
+        "Shortest silent gap the analysis reports",
+    ),
+    (
+        "gpu",
+        "GPU adapter of the native engine; also backend_filter (e.g. \"vulkan,gl\")\n\
+         and adapter_name_substring",
+    ),
+    (
+        "gpu.power_preference",
+        "\"high_performance\", \"low_power\" or \"none\"",
+    ),
+    (
+        "blender",
+        "The Blender engine; also parallel_jobs (default: one per four CPUs)",
+    ),
+    (
+        "blender.executable",
+        "Blender binary, overridden by BLENDER_PATH",
+    ),
+    ("blender.min_version", "Oldest Blender release accepted"),
+    (
+        "blender.stall_warning_minutes",
+        "Warn when a Blender job hasn't saved a frame for this long",
+    ),
+    ("blender.engine", "\"BLENDER_EEVEE_NEXT\" or \"CYCLES\""),
+    (
+        "blender.samples",
+        "EEVEE's anti-aliasing samples or Cycles' path samples",
+    ),
+    (
+        "blender.use_transparent_background",
+        "Render frames with an alpha channel",
+    ),
+    (
+        "blender.device",
+        "What Cycles renders on: \"CPU\" or \"GPU\"",
+    ),
+    ("blender.retries", "Times a failed Blender job is run again"),
+    (
+        "encoder",
+        "Codec and quality of the encoded video; also crf, bitrate, preset and pixel_format",
+    ),
+    (
+        "encoder.codec",
+        "\"h264\", \"h265\", \"vp9\", \"prores\" or \"av1\"",
+    ),
+    (
+        "encoder.hardware",
+        "Encode H.264 and H.265 with a hardware encoder when FFmpeg has one",
+    ),
+    (
+        "encoder.stall_timeout_seconds",
+        "Stop FFmpeg when it reports no progress for this long",
+    ),
+    (
+        "encoder.burn_subtitles",
+        "Burn --subtitles into the frames instead of adding a subtitle stream",
+    ),
+    (
+        "encoder.subtitle_style",
+        "font_size and margin of burned-in subtitles (default: libass' own)",
+    ),
+    (
+        "credibility.brand_safety",
+        "Words the brand-safety screen flags on top of its own, and words it never flags.\n\
+         The credibility word lists (weasel_words, hype_words, claim_superlatives,\n\
+         absolutes, and *_file variants) go in a [credibility] table.",
+    ),
+    (
+        "credibility.brand_safety.deny",
+        "Extra words and phrases to flag",
+    ),
+    ("credibility.brand_safety.allow", "Words never flagged"),
+    (
+        "credibility.brand_safety.match_obfuscations",
+        "Also flag leetspeak and stretched spellings",
+    ),
+    (
+        "readability",
+        "Reading level thresholds of the narrative analysis",
+    ),
+    (
+        "readability.max_grade_level",
+        "Highest Flesch-Kincaid grade level",
+    ),
+    ("readability.min_words", "Shorter texts are never flagged"),
+    (
+        "readability.penalty_per_scene",
+        "Narrative score points each flagged text costs",
+    ),
+    ("hook", "Hook strength"),
+    (
+        "hook.weight",
+        "Share of the hook score in the narrative score, from 0 to 1",
+    ),
+    (
+        "sentiment",
+        "Word valences of the emotional arc: lexicon = { \"word\" = 3 } and/or lexicon_file",
+    ),
+    ("repetition", "Scenes that repeat each other"),
+    (
+        "repetition.similarity_threshold",
+        "Share of shared word trigrams above which two scenes are flagged",
+    ),
+    (
+        "repetition.min_sentence_words",
+        "Repeated sentences are flagged from this many words",
+    ),
+    ("accessibility", "On-screen text checks"),
+    ("accessibility.min_contrast", "Lowest WCAG contrast ratio"),
+    (
+        "accessibility.min_font_size",
+        "Smallest font size in pixels at 1080p",
+    ),
+    (
+        "accessibility.safe_margin",
+        "Fraction of the frame text must keep from each edge",
+    ),
+    ("score", "CI gate of the score command"),
+    (
+        "score.min",
+        "Overall score below which `score` exits with an error; 0 never fails",
+    ),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::renderer::GpuPowerPreference;
 
     #[test]
     fn test_default_config() {
@@ -70,6 +789,190 @@ mod tests {
         assert_eq!(config.renderer.engine, "native");
         assert_eq!(config.video.default_resolution, "1920x1080");
         assert_eq!(config.video.default_fps, 30);
+        assert_eq!(config.audio.export_format, AudioExportFormat::Wav32Float);
+        assert_eq!(config.renderer.frame_format, FrameFormat::Png);
+        assert_eq!(config.renderer.texture_budget_mb, DEFAULT_TEXTURE_BUDGET_MB);
+        assert_eq!(config.renderer.samples, 1);
+        assert_eq!(config.renderer.bit_depth, BitDepth::Eight);
+    }
+
+    #[test]
+    fn test_script_watermark_replaces_the_configured_one() {
+        let mut config: AppConfig = toml::from_str(
+            r#"
+            [renderer]
+            engine = "native"
+            output_dir = "output"
+            [renderer.watermark]
+            source = "brand/bug.png"
+            position = "top-left"
+            opacity = 0.8
+            [video]
+            default_resolution = "1920x1080"
+            default_fps = 30
+            [assets]
+            base_path = "."
+            "#,
+        )
+        .unwrap();
+        let configured = config.renderer.watermark.clone().unwrap();
+        assert_eq!(configured.position, crate::script::Anchor::TopLeft);
+        assert_eq!((configured.scale, configured.margin_px), (1.0, 24));
+
+        let mut script = crate::templates::ScriptTemplate::generate(
+            crate::templates::TemplateType::Explainer,
+            &crate::templates::TemplateOptions::new(10.0),
+        );
+        config.renderer.apply_watermark(&mut script);
+        assert_eq!(script.metadata.watermark.as_ref(), Some(&configured));
+
+        let own = Watermark::new("logo.png");
+        script.metadata.watermark = Some(own.clone());
+        config.renderer.apply_watermark(&mut script);
+        assert_eq!(script.metadata.watermark, Some(own));
+
+        config.renderer.no_watermark = true;
+        config.renderer.apply_watermark(&mut script);
+        assert_eq!(script.metadata.watermark, None);
+    }
+
+    #[test]
+    fn test_audio_export_format_from_config() {
+        let config: AppConfig = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                [renderer]
+                engine = "native"
+                output_dir = "output"
+                frame_format = "ppm"
+                texture_budget_mb = 256
+                samples = 4
+                bit_depth = 16
+                [renderer.preview]
+                frame_step = 5
+                [video]
+                default_resolution = "1920x1080"
+                default_fps = 30
+                [assets]
+                base_path = "."
+                [audio]
+                export_format = "wav16"
+                min_silence_seconds = 2.5
+                [gpu]
+                power_preference = "low_power"
+                adapter_name_substring = "intel"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.audio.export_format, AudioExportFormat::Wav16);
+        assert_eq!(config.renderer.frame_format, FrameFormat::Ppm);
+        assert_eq!(config.renderer.preview.frame_step, 5);
+        assert_eq!(config.renderer.texture_budget_bytes(), 256 * 1024 * 1024);
+        assert_eq!(config.renderer.samples, 4);
+        assert_eq!(config.renderer.bit_depth, BitDepth::Sixteen);
+        assert_eq!(config.renderer.preview.scale, 0.5);
+        assert_eq!(config.audio.analysis().min_silence_seconds, 2.5);
+        assert_eq!(config.audio.analysis().silence_threshold_db, -60.0);
+        assert_eq!(config.gpu.power_preference, GpuPowerPreference::LowPower);
+        assert_eq!(config.gpu.adapter_name_substring.as_deref(), Some("intel"));
+        assert_eq!(config.gpu.backend_filter, None);
+    }
+
+    #[test]
+    fn test_credibility_dictionaries_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let hype_file = dir.path().join("hype.txt");
+        std::fs::write(&hype_file, "# Hype words\nincrível\n\n  demais  \n").unwrap();
+
+        let settings: CredibilitySettings = config::Config::builder()
+            .add_source(config::File::from_str(
+                &format!(
+                    r#"
+                    [credibility]
+                    weasel_words = ["muitas pessoas dizem", "especialistas afirmam"]
+                    hype_words = ["épico"]
+                    hype_words_file = "{}"
+                    "#,
+                    hype_file.display()
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .get("credibility")
+            .unwrap();
+        let dictionaries = settings.dictionaries().unwrap();
+        assert_eq!(dictionaries.weasel_words.origin, "config");
+        assert_eq!(dictionaries.weasel_words.words.len(), 2);
+        // A list and a file combine
+        assert_eq!(dictionaries.hype_words.words.len(), 3);
+        assert!(dictionaries.hype_words.is_match("Demais!"));
+        assert!(dictionaries.hype_words.origin.starts_with("config and "));
+        // Lists that aren't set keep the defaults
+        assert_eq!(dictionaries.absolutes.origin, "default");
+
+        let missing = CredibilitySettings {
+            absolutes_file: Some(dir.path().join("missing.txt")),
+            ..Default::default()
+        };
+        let error = missing.dictionaries().err().unwrap();
+        assert!(format!("{:#}", error).contains("missing.txt"));
+    }
+
+    #[test]
+    fn test_repetition_settings_from_config() {
+        let settings: RepetitionSettings = config::Config::builder()
+            .add_source(config::File::from_str(
+                "[repetition]\nsimilarity_threshold = 0.3",
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .get("repetition")
+            .unwrap();
+        assert_eq!(settings.similarity_threshold, 0.3);
+        assert_eq!(settings.min_sentence_words, 4);
+    }
+
+    #[test]
+    fn test_sentiment_lexicon_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let lexicon_file = dir.path().join("pt.txt");
+        std::fs::write(&lexicon_file, "# AFINN-pt\nruim\t-3\nincrível 4\n").unwrap();
+
+        let settings: SentimentSettings = config::Config::builder()
+            .add_source(config::File::from_str(
+                &format!(
+                    r#"
+                    [sentiment]
+                    lexicon = {{ "ótimo" = 3 }}
+                    lexicon_file = "{}"
+                    "#,
+                    lexicon_file.display()
+                ),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .get("sentiment")
+            .unwrap();
+        let lexicon = settings.lexicon().unwrap();
+        assert!(lexicon.origin.starts_with("config and "));
+        assert_eq!(lexicon.valence("ótimo"), Some(3));
+        assert_eq!(lexicon.valence("ruim"), Some(-3));
+        // The default English words are replaced
+        assert_eq!(lexicon.valence("good"), None);
+
+        assert_eq!(
+            SentimentSettings::default().lexicon().unwrap().origin,
+            "default"
+        );
+        std::fs::write(&lexicon_file, "ruim bad").unwrap();
+        assert!(settings.lexicon().is_err());
     }
 
     #[test]
@@ -79,4 +982,63 @@ mod tests {
         let config = AppConfig::default();
         assert_eq!(config.assets.base_path, PathBuf::from("."));
     }
+
+    #[test]
+    fn test_default_config_round_trips_through_toml() {
+        let written = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let config: AppConfig = config::Config::builder()
+            .add_source(config::File::from_str(&written, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        assert_eq!(toml::to_string_pretty(&config).unwrap(), written);
+        assert_eq!(config.renderer.bit_depth, BitDepth::Eight);
+        assert!(written.contains("bit_depth = 8"));
+    }
+
+    #[test]
+    fn test_commented_defaults_cover_every_key() {
+        let commented = AppConfig::commented_defaults().unwrap();
+        let config: AppConfig = toml::from_str(&commented).unwrap();
+        assert_eq!(
+            toml::to_string_pretty(&config).unwrap(),
+            toml::to_string_pretty(&AppConfig::default()).unwrap()
+        );
+
+        let lines: Vec<&str> = commented.lines().collect();
+        for (i, line) in lines.iter().enumerate().skip(1) {
+            if !line.is_empty() && !line.starts_with('#') {
+                assert!(lines[i - 1].starts_with('#'), "no comment on {}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_from_reports_file_and_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let loaded = AppConfig::load_from(dir.path()).unwrap();
+        assert_eq!(loaded.file, None);
+        assert_eq!(loaded.source("renderer.output_dir"), ConfigSource::Default);
+
+        std::fs::write(
+            dir.path().join("interstellar.toml"),
+            "[renderer]\nengine = \"native\"\noutput_dir = \"renders\"\n",
+        )
+        .unwrap();
+        let loaded = AppConfig::load_from(dir.path()).unwrap();
+        assert_eq!(loaded.file, Some(dir.path().join("interstellar.toml")));
+        assert_eq!(loaded.config.renderer.output_dir, PathBuf::from("renders"));
+        assert_eq!(loaded.source("renderer.output_dir"), ConfigSource::File);
+        assert_eq!(loaded.source("video.default_fps"), ConfigSource::Default);
+
+        let values = loaded.values().unwrap();
+        let output_dir = values
+            .iter()
+            .find(|value| value.key == "renderer.output_dir")
+            .unwrap();
+        assert_eq!(output_dir.value, "renders");
+        assert_eq!(output_dir.env_var(), "INTERSTELLAR_RENDERER__OUTPUT_DIR");
+    }
 }
@@ -0,0 +1,35 @@
+use crate::analysis::accessibility::{
+    AccessibilityAnalyzer, AccessibilityReport, AccessibilitySettings,
+};
+use crate::assets::AssetLoader;
+use crate::script::VideoScript;
+
+pub struct AccessibilityContext;
+
+impl AccessibilityContext {
+    /// Check the contrast, size and placement of every text layer
+    pub fn run(
+        script: &VideoScript,
+        assets: &mut AssetLoader,
+        settings: &AccessibilitySettings,
+    ) -> AccessibilityReport {
+        say!("\n👓 Checking Text Accessibility...");
+        let report = AccessibilityAnalyzer::analyze(script, assets, settings);
+
+        if report.texts.is_empty() {
+            say!("   ✅ No text layers");
+        } else if report.warnings.is_empty() {
+            say!(
+                "   ✅ {} text layer(s) readable (contrast, size, safe margin)",
+                report.texts.len()
+            );
+        } else {
+            say!("   ⚠️ Accessibility Warnings:");
+            for warning in &report.warnings {
+                say!("      - {}", warning.message);
+            }
+        }
+
+        report
+    }
+}
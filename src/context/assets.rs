@@ -0,0 +1,46 @@
+use crate::analysis::asset_audit::{format_bytes, AssetAuditAnalyzer, AssetAuditReport};
+use crate::assets::AssetLoader;
+use crate::script::VideoScript;
+
+pub struct AssetAuditContext;
+
+impl AssetAuditContext {
+    /// Look for unused and duplicate asset files and print what could be removed
+    pub fn run(script: &VideoScript, assets: &AssetLoader) -> AssetAuditReport {
+        say!("\n📦 Auditing Assets...");
+        let report = AssetAuditAnalyzer::analyze(script, assets);
+
+        if report.removable.is_empty() {
+            say!(
+                "   ✅ {} asset reference(s), no unused or duplicate files",
+                report.references.len()
+            );
+            return report;
+        }
+        for asset in &report.unused {
+            say!(
+                "   ⚠️  Unused {}: {} ({})",
+                asset.kind.label(),
+                asset.path.display(),
+                format_bytes(asset.bytes)
+            );
+        }
+        for duplicate in &report.duplicates {
+            for copy in &duplicate.copies {
+                say!(
+                    "   ⚠️  Duplicate: {} has the same contents as {} ({})",
+                    copy.display(),
+                    duplicate.kept.display(),
+                    format_bytes(duplicate.bytes)
+                );
+            }
+        }
+        say!(
+            "   🗑️  {} removable in {} file(s)",
+            format_bytes(report.wasted_bytes),
+            report.removable.len()
+        );
+
+        report
+    }
+}
@@ -0,0 +1,123 @@
+use crate::analysis::audio_timing::{AudioTimingAnalyzer, AudioTimingReport};
+use crate::audio::{AudioAnalysisReport, MixReport};
+use crate::config::AudioSettings;
+use crate::script::VideoScript;
+use crate::{AssetLoader, AudioDecoder, AudioMixer};
+use anyhow::Result;
+use std::path::Path;
+
+pub struct AudioContext;
+
+impl AudioContext {
+    /// Check audio track lengths against the timeline, resolving sources relative to `base_path`
+    pub fn validate(script: &VideoScript, base_path: &Path) -> AudioTimingReport {
+        let report = AudioTimingAnalyzer::analyze(script, base_path);
+        if report.tracks.is_empty() {
+            return report;
+        }
+
+        say!("\n🎧 Checking Audio Timing...");
+        for track in &report.tracks {
+            match track.end_time() {
+                Some(_) if track.looping => say!(
+                    "   - {}: {:.2}s → end of video (looping)",
+                    track.source,
+                    track.start_time
+                ),
+                Some(end) => say!(
+                    "   - {}: {:.2}s → {:.2}s",
+                    track.source,
+                    track.start_time,
+                    end
+                ),
+                None => say!("   - {}: unknown length", track.source),
+            }
+        }
+
+        if report.warnings.is_empty() {
+            say!("   ✅ All tracks fit the timeline");
+        } else {
+            for warning in &report.warnings {
+                say!("   ⚠️  {}", warning);
+            }
+        }
+
+        report
+    }
+
+    /// Mix the script's audio tracks (resampled, ducked and normalized as the
+    /// script asks) over its whole duration into `out`, in the format `settings`
+    /// names, and print the levels. Track sources resolve against the loader's
+    /// base path; tracks that can't be read are skipped with a warning.
+    pub fn mix_script(
+        script: &VideoScript,
+        loader: &AssetLoader,
+        settings: &AudioSettings,
+        out: &Path,
+    ) -> Result<MixReport> {
+        let Some(audio_config) = &script.audio else {
+            anyhow::bail!("The script has no audio");
+        };
+
+        say!("\n🎵 Processing audio...");
+        let mut mixer = AudioMixer::new(44100, 2);
+        mixer.set_normalization(audio_config.normalization.clone());
+        mixer.set_ducking(audio_config.ducking.clone());
+        mixer.set_analysis(settings.analysis());
+
+        for track in &audio_config.tracks {
+            say!("  Loading track: {}", track.source.display());
+            let track_path = loader.base_path().join(&track.source);
+
+            // Tracks are decoded while mixing, so long files never sit in memory
+            match AudioDecoder::stream(&track_path) {
+                Ok(stream) => {
+                    if let Err(e) = mixer.add_streamed_track(stream, track) {
+                        say!("  ⚠️  Skipping audio track: {}", e);
+                    }
+                }
+                Err(e) => say!("  ⚠️  Failed to load audio track: {}", e),
+            }
+        }
+
+        if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mix_report =
+            mixer.mix_to_file(out, script.metadata.duration, settings.export_format)?;
+        if let Some(report) = &mix_report.loudness {
+            say!(
+                "  🔊 Loudness: {:.1} LUFS → {:.1} LUFS (gain {:+.1} dB, peak {:.1} dBTP{})",
+                report.measured_lufs,
+                report.final_lufs,
+                report.gain_db,
+                report.true_peak_db,
+                if report.peak_limited {
+                    ", peak-limited"
+                } else {
+                    ""
+                }
+            );
+        }
+        Self::print_analysis(&mix_report.analysis);
+        say!("  ✓ Mixed audio exported to: {}", out.display());
+
+        Ok(mix_report)
+    }
+
+    fn print_analysis(analysis: &AudioAnalysisReport) {
+        say!("  📈 Levels: {}", analysis.summary());
+        if analysis.clipped_samples > 0 {
+            say!(
+                "  ⚠️  Mix peaked at {:+.1} dBFS and was soft-clipped; lower the track volumes",
+                analysis.peak_db()
+            );
+            for track in &analysis.track_peaks {
+                say!("     - {}: peak {:.1} dBFS", track.name, track.peak_db());
+            }
+        }
+        for gap in &analysis.silent_gaps {
+            say!("  🔇 Silence from {:.2}s to {:.2}s", gap.start, gap.end);
+        }
+    }
+}
@@ -1,45 +1,78 @@
-use crate::analysis::credibility::CredibilityReport;
+use crate::analysis::citations::{CitationCheckSettings, CitationChecker};
+use crate::analysis::credibility::{CredibilityDictionaries, CredibilityReport};
 use crate::script::VideoScript;
+use anyhow::Result;
 
 pub struct CredibilityContext;
 
 impl CredibilityContext {
-    pub fn run(script: &VideoScript) -> CredibilityReport {
+    /// Run the credibility analysis with the given word lists
+    pub fn run(script: &VideoScript, dictionaries: &CredibilityDictionaries) -> CredibilityReport {
         // Pillar 3: Credibility (Trustworthy) - Analysis
-        println!("\n🛡️ Analyzing Credibility...");
-        let report = crate::analysis::credibility::CredibilityAnalyzer::analyze(script);
+        say!("\n🛡️ Analyzing Credibility...");
+        let report = crate::analysis::credibility::CredibilityAnalyzer::analyze_with_dictionaries(
+            script,
+            dictionaries,
+        );
 
-        println!("   Score: {}/100", report.score);
+        say!("   Score: {}/100", report.score);
 
         if !report.claims.is_empty() {
-            println!("   🔍 Detected {} claims:", report.claims.len());
+            say!("   🔍 Detected {} claims:", report.claims.len());
             for claim in &report.claims {
                 let status = if claim.verified {
                     "✅ Verified"
                 } else {
                     "⚠️ Unverified"
                 };
-                println!("      - [{}] \"{}\" ({})", status, claim.text, claim.reason);
+                say!("      - [{}] \"{}\" ({})", status, claim.text, claim.reason);
             }
         } else {
-            println!("   ✅ No specific claims detected");
+            say!("   ✅ No specific claims detected");
         }
 
         if !report.citations.is_empty() {
-            println!("   📚 Citations:");
+            say!("   📚 Citations:");
             for citation in &report.citations {
-                println!("      - {}", citation);
+                say!("      - {}", citation);
             }
         } else {
-            println!("   ⚠️  No citations provided");
+            say!("   ⚠️  No citations provided");
         }
 
-        println!("\n   ✅ Quality Checklist:");
+        say!("\n   ✅ Quality Checklist:");
         for item in &report.checklist {
             let icon = if item.passed { "✓" } else { "❌" };
-            println!("      {} [{}] {}", icon, item.category, item.message);
+            say!("      {} [{}] {}", icon, item.category, item.message);
         }
 
         report
     }
+
+    /// Request every URL citation and add the unreachable ones to the checklist
+    pub fn check_citations(
+        report: &mut CredibilityReport,
+        settings: &CitationCheckSettings,
+    ) -> Result<()> {
+        say!("\n🔗 Checking Citation URLs...");
+        let statuses = CitationChecker::check(&report.citations, settings)?;
+        if statuses.is_empty() {
+            say!("   No URL citations to check");
+        }
+        for status in &statuses {
+            let icon = if status.reachable() { "✓" } else { "❌" };
+            match &status.final_url {
+                Some(final_url) => say!(
+                    "   {} {} → {} ({})",
+                    icon,
+                    status.url,
+                    final_url,
+                    status.outcome()
+                ),
+                None => say!("   {} {} ({})", icon, status.url, status.outcome()),
+            }
+        }
+        report.add_citation_checks(statuses);
+        Ok(())
+    }
 }
@@ -0,0 +1,26 @@
+use crate::analysis::metadata::{MetadataAnalyzer, MetadataReport};
+use crate::analysis::narrative::Severity;
+use crate::script::VideoScript;
+
+pub struct MetadataContext;
+
+impl MetadataContext {
+    /// Check the title and description the video is uploaded with
+    pub fn run(script: &VideoScript) -> MetadataReport {
+        say!("\n🏷️  Checking Title and Description...");
+        let report = MetadataAnalyzer::analyze(script);
+
+        if report.findings.is_empty() {
+            say!("   ✅ Title and description look good");
+        }
+        for finding in &report.findings {
+            let icon = match finding.severity {
+                Severity::Info => "ℹ️ ",
+                _ => "⚠️ ",
+            };
+            say!("   {} {}", icon, finding.message);
+        }
+
+        report
+    }
+}
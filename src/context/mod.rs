@@ -1,3 +1,7 @@
+pub mod accessibility;
+pub mod assets;
+pub mod audio;
 pub mod credibility;
+pub mod metadata;
 pub mod narrative;
 pub mod performance;
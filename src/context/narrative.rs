@@ -1,71 +1,172 @@
 use crate::script::VideoScript;
+use std::path::Path;
 
-use crate::analysis::narrative::NarrativeReport;
+use crate::analysis::narrative::{NarrativeReport, NarrativeSettings, PacingSource};
+use crate::analysis::speech::SpeechActivity;
 
 pub struct NarrativeContext;
 
 impl NarrativeContext {
-    pub fn run(script: &VideoScript) -> NarrativeReport {
+    /// Run the narrative analysis; voiceover tracks are resolved relative to `base_path`
+    pub fn run(
+        script: &VideoScript,
+        base_path: &Path,
+        settings: &NarrativeSettings,
+    ) -> NarrativeReport {
         // Pillar 2: Narrative (Engaging) - Analysis
-        println!("\n📊 Analyzing Narrative Structure...");
-        let report = crate::analysis::narrative::NarrativeAnalyzer::analyze(script);
+        say!("\n📊 Analyzing Narrative Structure...");
+        let speech = SpeechActivity::from_script(script, base_path);
+        let report = crate::analysis::narrative::NarrativeAnalyzer::analyze_with_settings(
+            script,
+            speech.as_ref(),
+            settings,
+        );
 
-        println!("   Score: {}/100", report.score);
+        say!("   Score: {}/100", report.score);
 
         // Structure validation
         if !report.structure_valid {
-            println!("   ❌ Structure Issues:");
+            say!("   ❌ Structure Issues:");
             for error in &report.structure_errors {
-                println!("      - {}", error);
+                say!("      - {}", error);
             }
         } else {
-            println!("   ✅ Structure: Valid (Hook → Body → Payoff)");
+            say!("   ✅ Structure: Valid (Hook → Body → Payoff)");
         }
 
         // Structure recommendations
         if !report.structure_recommendations.is_empty() {
-            println!("   💡 Structure Recommendations:");
+            say!("   💡 Structure Recommendations:");
             for rec in &report.structure_recommendations {
                 let emoji = match rec.severity {
                     crate::analysis::narrative::Severity::Error => "❌",
                     crate::analysis::narrative::Severity::Warning => "⚠️",
                     crate::analysis::narrative::Severity::Info => "ℹ️",
                 };
-                println!("      {} [{}] {}", emoji, rec.category, rec.message);
+                say!("      {} [{}] {}", emoji, rec.category, rec.message);
             }
         }
 
+        if let Some(hook) = &report.hook {
+            say!(
+                "   🪝 Hook strength (scene {}): {}/100",
+                hook.scene_index + 1,
+                hook.score
+            );
+        }
+
+        // Emotional arc, as a sparkline-like series of valences
+        if report.sentiment.scenes.iter().any(|s| s.scored_words > 0) {
+            let arc: Vec<String> = report
+                .sentiment
+                .scenes
+                .iter()
+                .map(|s| {
+                    if s.scored_words > 0 {
+                        format!("{:+.2}", s.valence)
+                    } else {
+                        "·".to_string()
+                    }
+                })
+                .collect();
+            say!(
+                "   🎭 Emotional arc ({} lexicon): {}",
+                settings.lexicon.origin,
+                arc.join(" → ")
+            );
+        }
+
         // Pacing alerts
         if !report.pacing_alerts.is_empty() {
-            println!("   ⚠️ Pacing Alerts:");
+            say!("   ⚠️ Pacing Alerts:");
             for alert in &report.pacing_alerts {
-                println!("      - {}", alert.message);
+                say!("      - {}", alert.message);
             }
         } else {
-            println!("   ✅ Pacing: Optimal");
+            say!("   ✅ Pacing: Optimal");
+        }
+
+        // Where each scene's pacing came from
+        if !report.scene_pacing.is_empty() {
+            say!("   🗣️  Pacing Sources:");
+            for pacing in &report.scene_pacing {
+                let detail = match (pacing.source, pacing.wpm, pacing.speech_coverage) {
+                    (PacingSource::SpeechCoverage, _, Some(coverage)) => {
+                        format!("voiceover coverage {:.0}%", coverage * 100.0)
+                    }
+                    (PacingSource::Narration, Some(wpm), _) => format!("narration, {:.0} WPM", wpm),
+                    (_, Some(wpm), _) => format!("on-screen text, {:.0} WPM", wpm),
+                    _ => "unknown".to_string(),
+                };
+                say!("      - Scene {}: {}", pacing.scene_index + 1, detail);
+            }
+        }
+
+        // Reading level of each scene's text
+        if !report.readability.scenes.is_empty() {
+            say!("   📖 Readability:");
+            for scene in &report.readability.scenes {
+                say!(
+                    "      - Scene {} {}: grade {:.1}, reading ease {:.0}, {:.1} words/sentence",
+                    scene.scene_index + 1,
+                    scene.source.label(),
+                    scene.grade_level,
+                    scene.reading_ease,
+                    scene.avg_sentence_length
+                );
+            }
+            for warning in &report.readability.warnings {
+                say!("      ⚠️ {}", warning.message);
+            }
+        }
+
+        // Which scenes lost points
+        if !report.scene_scores.is_empty() {
+            say!("   📋 Scene Scores:");
+            say!(
+                "      {:>3}  {:<7} {:>8} {:>5} {:>5}",
+                "#",
+                "Type",
+                "Duration",
+                "WPM",
+                "Score"
+            );
+            for (scene, scene_score) in script.scenes.iter().zip(&report.scene_scores) {
+                let wpm = scene_score
+                    .wpm
+                    .map_or("-".to_string(), |wpm| format!("{:.0}", wpm));
+                say!(
+                    "      {:>3}  {:<7} {:>7.1}s {:>5} {:>5}",
+                    scene_score.scene_index + 1,
+                    format!("{:?}", scene.scene_type),
+                    scene.duration,
+                    wpm,
+                    scene_score.score
+                );
+            }
         }
 
         // Retention warnings
         if !report.retention_warnings.is_empty() {
-            println!("   ⚠️ Retention Warnings:");
+            say!("   ⚠️ Retention Warnings:");
             for warning in &report.retention_warnings {
-                println!("      - {}", warning.message);
+                say!("      - {}", warning.message);
             }
         }
 
         // Advanced Retention Analysis
-        println!("\n🎯 Analyzing Retention Metrics...");
+        say!("\n🎯 Analyzing Retention Metrics...");
         let heatmap = crate::analysis::retention::RetentionAnalyzer::generate_heatmap(script);
-        println!(
+        say!(
             "   Overall Retention Score: {:.1}/100",
             heatmap.overall_retention_score
         );
 
         if !heatmap.critical_moments.is_empty() {
-            println!("   ⚠️  Critical Moments (Low Retention):");
+            say!("   ⚠️  Critical Moments (Low Retention):");
             for scene_idx in &heatmap.critical_moments {
                 let scene_retention = &heatmap.scene_scores[*scene_idx];
-                println!(
+                say!(
                     "      - Scene {}: {:.1} momentum, {:.1} retention",
                     scene_idx + 1,
                     scene_retention.momentum,
@@ -73,15 +174,15 @@ impl NarrativeContext {
                 );
             }
         } else {
-            println!("   ✅ No critical retention drop-offs detected");
+            say!("   ✅ No critical retention drop-offs detected");
         }
 
         let dropoff_predictions =
             crate::analysis::retention::RetentionAnalyzer::predict_dropoff(script);
         if !dropoff_predictions.is_empty() {
-            println!("   📉 Drop-off Predictions:");
+            say!("   📉 Drop-off Predictions:");
             for pred in dropoff_predictions.iter().take(3) {
-                println!(
+                say!(
                     "      - Scene {}: {:.0}% predicted drop-off ({})",
                     pred.scene_index + 1,
                     pred.predicted_dropoff_percent,
@@ -1,105 +1,768 @@
+use crate::analysis::asset_audit::format_bytes;
+use crate::audio::AudioAnalysisReport;
+use crate::config::{AudioSettings, RendererConfig};
+use crate::context::audio::AudioContext;
+use crate::error::VideoEngineError;
+use crate::exit::{ExitCode, WithExitCode};
+use crate::renderer::chapters::{self, Chapter};
+use crate::renderer::contact_sheet::{self, ContactSheetLayout, SceneFrame};
+use crate::renderer::thumbnail;
+use crate::renderer::{
+    CancellationToken, EncodeSettings, FrameFileSink, FrameFormat, FrameOutput, GpuOptions,
+    NativeRenderer, ProgressKind, RenderProgress, Renderer, Timeline, VideoEncoder,
+};
 use crate::script::VideoScript;
 use crate::AssetLoader;
-use anyhow::Result;
-use std::path::Path;
+use ab_glyph::FontRef;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Name of the chapters file handed to ffmpeg, in the output directory
+const CHAPTERS_FILE: &str = "chapters.ffmetadata";
+
+/// What `PerformanceContext::run` produced
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenderSummary {
+    /// Frames in the rendered video
+    pub frames: u32,
+    /// The encoded video, absolute; `None` when nothing was encoded
+    pub video: Option<PathBuf>,
+    /// Where the frame files were left when they weren't encoded
+    pub frame_dir: Option<PathBuf>,
+    /// The exported audio mix
+    pub audio: Option<PathBuf>,
+    pub audio_analysis: Option<AudioAnalysisReport>,
+    /// Time taken to mix, render and encode
+    pub seconds: f64,
+}
+
+/// What `PerformanceContext::run` would do, worked out without doing it
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderPlan {
+    pub backend: &'static str,
+    /// What the backend says about how it renders
+    pub details: Vec<String>,
+    pub preview: bool,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub frames: u32,
+    pub output_dir: PathBuf,
+    /// ffmpeg input pattern of the frame files written to `output_dir`, `None`
+    /// when frames are streamed to the encoder
+    pub frame_pattern: Option<String>,
+    /// Disk space the frame files take at most, uncompressed
+    pub frame_bytes: u64,
+    /// Audio tracks mixed, as written in the script
+    pub audio_tracks: Vec<PathBuf>,
+    /// Where the mix goes, when there are tracks
+    pub audio_output: Option<PathBuf>,
+    /// The encoded video, `None` for a PNG sequence
+    pub output_file: Option<PathBuf>,
+    /// The ffmpeg command line, `None` when nothing is encoded
+    pub encoder_command: Option<String>,
+}
+
+impl fmt::Display for RenderPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🧪 Dry run: nothing is rendered or written")?;
+        writeln!(f, "   Renderer: {}", self.backend)?;
+        for detail in &self.details {
+            writeln!(f, "     {}", detail)?;
+        }
+        writeln!(
+            f,
+            "   Frames: {} at {}x{}, {}fps{}",
+            self.frames,
+            self.width,
+            self.height,
+            self.fps,
+            if self.preview { " (preview)" } else { "" }
+        )?;
+        match &self.frame_pattern {
+            Some(pattern) => writeln!(
+                f,
+                "   Frame files: {} (up to {})",
+                self.output_dir.join(pattern).display(),
+                format_bytes(self.frame_bytes)
+            )?,
+            None => writeln!(f, "   Frame files: none, frames are streamed to ffmpeg")?,
+        }
+        if let Some(audio_output) = &self.audio_output {
+            writeln!(
+                f,
+                "   Audio: {} track(s) mixed to {}",
+                self.audio_tracks.len(),
+                audio_output.display()
+            )?;
+            for track in &self.audio_tracks {
+                writeln!(f, "     {}", track.display())?;
+            }
+        } else {
+            writeln!(f, "   Audio: none")?;
+        }
+        match (&self.output_file, &self.encoder_command) {
+            (Some(output), Some(command)) => {
+                writeln!(f, "   Output: {}", output.display())?;
+                writeln!(f, "   Encoder: {}", command)?;
+            }
+            (Some(output), None) => {
+                writeln!(f, "   Output: {}", output.display())?;
+                writeln!(
+                    f,
+                    "   Encoder: none, FFmpeg not found; only the frame files would be written"
+                )?;
+            }
+            (None, _) => writeln!(f, "   Output: the frame files, not encoded")?,
+        }
+        Ok(())
+    }
+}
 
 pub struct PerformanceContext;
 
 impl PerformanceContext {
+    /// Where the video of `script` goes unless told otherwise: its slugified title
+    /// in `output_dir`, with a `_preview` suffix for previews and the extension of
+    /// the format `encoder` writes
+    pub fn default_output_file(
+        script: &VideoScript,
+        output_dir: &Path,
+        encoder: &EncodeSettings,
+        preview: bool,
+    ) -> PathBuf {
+        output_dir.join(format!(
+            "{}{}.{}",
+            script.metadata.slug(),
+            if preview { "_preview" } else { "" },
+            encoder.extension().unwrap_or("mp4")
+        ))
+    }
+
+    /// Render, mix and encode the video to `output_file`. Returns what was written,
+    /// with the audio analysis when the script has audio. With `preview`, a scaled-down, reduced-frame-rate
+    /// miniature is encoded instead. Frame and encode events are reported to `progress`.
+    /// Frames are drawn by `backend` (see `renderer::create`); backends that draw in
+    /// process stream them straight into the encoder, which writes the format and codec
+    /// `encoder` names; a PNG sequence is left as frame files in the output directory.
+    /// `chapters` are embedded in the encoded video, and the SRT file `subtitles`
+    /// burned in or added as a subtitle stream.
+    #[allow(clippy::too_many_arguments)]
     pub fn run(
         script: &VideoScript,
         loader: &mut AssetLoader,
-        output_dir: &Path,
-        use_blender: bool,
-        use_gpu: bool,
-    ) -> Result<()> {
-        // 1. Rendering
-        println!("\n🎬 Rendering frames...");
+        backend: &mut dyn Renderer,
+        renderer: &RendererConfig,
+        preview: bool,
+        audio_settings: &AudioSettings,
+        encoder: &EncodeSettings,
+        output_file: &Path,
+        chapters: Option<&[Chapter]>,
+        subtitles: Option<&Path>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<RenderSummary> {
+        let start = Instant::now();
+        // Bad encoder settings would otherwise only fail after rendering
+        encoder.validate()?;
+        if let Some(subtitles) = subtitles {
+            if !subtitles.is_file() {
+                anyhow::bail!("Subtitles file not found: {}", subtitles.display());
+            }
+            if encoder.burn_subtitles && VideoEncoder::is_available() {
+                VideoEncoder::check_subtitle_burning()?;
+            }
+        }
+        let output_dir = renderer.output_dir.as_path();
+
+        let preview_script;
+        let script = if preview {
+            preview_script = renderer.preview.apply(script);
+            let (width, height) = preview_script.metadata.resolution.dimensions();
+            say!(
+                "\n👀 Preview mode: {}x{} at {}fps",
+                width,
+                height,
+                preview_script.metadata.fps
+            );
+            &preview_script
+        } else {
+            script
+        };
 
         if !output_dir.exists() {
             std::fs::create_dir_all(output_dir)?;
         }
 
-        if use_blender {
-            println!("🎨 Using Blender Backend");
-            let renderer =
-                crate::renderer::BlenderRenderer::new(script.clone(), output_dir.to_path_buf());
-            renderer.render()?;
-        } else {
-            println!("🎨 Using Native Engine (CPU/GPU)");
-            let mut engine = crate::renderer::RenderEngine::new(script.clone(), use_gpu);
-            engine.render(output_dir, loader)?;
+        // 1. Audio Processing (first, so a streaming encoder can mux it in)
+        let mut audio_path_opt = None;
+        let mut audio_analysis = None;
+        if script.audio.is_some() {
+            let output_audio = Self::audio_file(output_dir, audio_settings);
+            match AudioContext::mix_script(script, loader, audio_settings, &output_audio) {
+                Ok(mix_report) => {
+                    audio_path_opt = Some(output_audio);
+                    audio_analysis = Some(mix_report.analysis);
+                }
+                Err(e) => say!("  ⚠️  Failed to export mixed audio: {}", e),
+            }
         }
 
-        // 2. Audio Processing
-        let mut audio_path_opt = None;
-        if let Some(audio_config) = &script.audio {
-            println!("\n🎵 Processing audio...");
-            let mut mixer = crate::AudioMixer::new(44100, 2);
-
-            for track in &audio_config.tracks {
-                println!("  Loading track: {}", track.source.display());
-                // Resolve path relative to script (using loader's base path would be better, but script paths are relative to script file)
-                // We need the base path here. Loader has it.
-                let base_path = loader.base_path();
-                let track_path = if track.source.is_absolute() {
-                    track.source.clone()
-                } else {
-                    base_path.join(&track.source)
-                };
-
-                match crate::AudioDecoder::decode(&track_path) {
-                    Ok((samples, rate, channels)) => {
-                        mixer.add_track(samples, rate, channels, track.start_time, track.volume);
+        let mut summary = RenderSummary {
+            frames: Timeline::from_script(script).total_frames(),
+            audio: audio_path_opt.clone(),
+            audio_analysis,
+            ..Default::default()
+        };
+
+        // 2. Rendering and Video Encoding
+        say!("\n🎬 Rendering frames...");
+        if !encoder.format.encodes() {
+            // A PNG sequence is just the frame files
+            backend.render(script, loader, FrameOutput::Directory(output_dir), progress)?;
+            say!("🖼️  Frames saved in: {}", output_dir.display());
+            summary.frame_dir = Some(output_dir.to_path_buf());
+            summary.seconds = start.elapsed().as_secs_f64();
+            return Ok(summary);
+        }
+        let output_video = output_file;
+        if let Some(dir) = output_video
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        let (width, height) = script.metadata.resolution.dimensions();
+        let ffmpeg_available = VideoEncoder::is_available();
+        let encode_output = output_video.display().to_string();
+
+        let mut chapters_path = None;
+        if let (Some(chapters), true) = (chapters, ffmpeg_available) {
+            let path = output_dir.join(CHAPTERS_FILE);
+            let end_seconds = Timeline::from_script(script).duration_seconds();
+            chapters::write_file(&path, &chapters::ffmetadata(chapters, end_seconds))?;
+            chapters_path = Some(path);
+        }
+
+        if Self::streams(backend, ffmpeg_available) {
+            // Frames go straight into ffmpeg's stdin, nothing is written to disk
+            progress.emit(ProgressKind::EncodeStarted {
+                output: encode_output.clone(),
+            });
+            let mut settings = encoder.clone();
+            loop {
+                check_cancelled(backend)?;
+                let mut stream = VideoEncoder::start_stream(
+                    output_video,
+                    script.metadata.fps,
+                    width,
+                    height,
+                    audio_path_opt.as_deref(),
+                    chapters_path.as_deref(),
+                    subtitles,
+                    &settings,
+                )
+                .exit_code(ExitCode::EncodeFailed)?;
+                let hardware = stream.hardware();
+                let rendered =
+                    backend.render(script, loader, FrameOutput::Sink(&mut stream), progress);
+                if let Err(VideoEngineError::Cancelled) = rendered {
+                    // Stops ffmpeg, whose output is cut short
+                    drop(stream);
+                    let _ = std::fs::remove_file(output_video);
+                    return Err(VideoEngineError::Cancelled.into());
+                }
+                let result = rendered
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| stream.finish().exit_code(ExitCode::EncodeFailed));
+                match (hardware, result) {
+                    // Streamed frames are gone, so they're rendered again
+                    (Some(_), Err(e)) => {
+                        say!(
+                            "  ⚠️  Hardware encoding failed ({:#}), rendering again with software encoding",
+                            e
+                        );
+                        settings = settings.software();
                     }
-                    Err(e) => println!("  ⚠️  Failed to load audio track: {}", e),
+                    (_, result) => break result?,
                 }
             }
+            progress.emit(ProgressKind::EncodeFinished {
+                output: encode_output,
+            });
+        } else {
+            let output =
+                backend.render(script, loader, FrameOutput::Directory(output_dir), progress)?;
 
-            let mixed_audio = mixer.mix(script.metadata.duration);
-            let output_audio = output_dir.join("audio.wav");
-            if let Err(e) = mixer.export(&output_audio, &mixed_audio) {
-                println!("  ⚠️  Failed to export mixed audio: {}", e);
-            } else {
-                println!("  ✓ Mixed audio exported to: {}", output_audio.display());
-                audio_path_opt = Some(output_audio);
+            if let (true, Some(files)) = (ffmpeg_available, output.files) {
+                check_cancelled(backend)?;
+                progress.emit(ProgressKind::EncodeStarted {
+                    output: encode_output.clone(),
+                });
+                VideoEncoder::encode(
+                    output_dir.join(&files.pattern).to_str().unwrap(),
+                    output_video,
+                    script.metadata.fps,
+                    width,
+                    height,
+                    audio_path_opt.as_deref(),
+                    chapters_path.as_deref(),
+                    subtitles,
+                    encoder,
+                    progress,
+                )
+                .exit_code(ExitCode::EncodeFailed)?;
+                progress.emit(ProgressKind::EncodeFinished {
+                    output: encode_output,
+                });
             }
         }
 
-        // 3. Video Encoding
-        if crate::renderer::VideoEncoder::is_available() {
-            let output_video = Path::new("output.mp4");
-            let frame_pattern = if use_blender {
-                output_dir.join("frame_%04d.png")
-            } else {
-                output_dir.join("frame_%d.ppm")
-            };
-
-            crate::renderer::VideoEncoder::encode(
-                frame_pattern.to_str().unwrap(),
-                output_video,
-                script.metadata.fps,
-                script.metadata.resolution.dimensions().0,
-                script.metadata.resolution.dimensions().1,
-                audio_path_opt.as_deref(),
-            )?;
-
-            println!("✨ Video created successfully: {}", output_video.display());
+        if ffmpeg_available {
+            let output_video = std::path::absolute(output_video)?;
+            say!("✨ Video created successfully: {}", output_video.display());
+            summary.video = Some(output_video);
         } else {
-            println!("⚠️  FFmpeg not found. Skipping video encoding.");
-            println!("   Frames are saved in: {}", output_dir.display());
-            println!("\n💡 To enable video generation, install FFmpeg:");
+            summary.frame_dir = Some(output_dir.to_path_buf());
+            say!("⚠️  FFmpeg not found. Skipping video encoding.");
+            say!("   Frames are saved in: {}", output_dir.display());
+            say!("\n💡 To enable video generation, install FFmpeg:");
             if cfg!(target_os = "macos") {
-                println!("   brew install ffmpeg");
+                say!("   brew install ffmpeg");
             } else if cfg!(target_os = "windows") {
-                println!("   choco install ffmpeg");
+                say!("   choco install ffmpeg");
             } else if cfg!(target_os = "linux") {
-                println!("   sudo apt-get install ffmpeg");
+                say!("   sudo apt-get install ffmpeg");
             } else {
-                println!("   Install FFmpeg from https://ffmpeg.org/download.html");
+                say!("   Install FFmpeg from https://ffmpeg.org/download.html");
+            }
+        }
+
+        summary.seconds = start.elapsed().as_secs_f64();
+        Ok(summary)
+    }
+
+    /// What `run` would do with the same arguments, for `render --dry-run`: the
+    /// backend, frames, files and encoder command. Settings are checked as `run`
+    /// checks them, but nothing is rendered, mixed, encoded or written.
+    #[allow(clippy::too_many_arguments)]
+    pub fn plan(
+        script: &VideoScript,
+        backend: &dyn Renderer,
+        renderer: &RendererConfig,
+        preview: bool,
+        audio_settings: &AudioSettings,
+        encoder: &EncodeSettings,
+        output_file: &Path,
+        chapters: bool,
+        subtitles: Option<&Path>,
+    ) -> Result<RenderPlan> {
+        encoder.validate()?;
+        if let Some(subtitles) = subtitles {
+            if !subtitles.is_file() {
+                anyhow::bail!("Subtitles file not found: {}", subtitles.display());
             }
         }
+        let output_dir = renderer.output_dir.as_path();
+        let preview_script;
+        let script = if preview {
+            preview_script = renderer.preview.apply(script);
+            &preview_script
+        } else {
+            script
+        };
+
+        let (width, height) = script.metadata.resolution.dimensions();
+        let frames = Timeline::from_script(script).total_frames();
+        let backend_plan = backend.plan(script);
+        let audio_tracks: Vec<PathBuf> = script
+            .audio
+            .iter()
+            .flat_map(|audio| audio.tracks.iter().map(|track| track.source.clone()))
+            .collect();
+        let audio_output = script
+            .audio
+            .is_some()
+            .then(|| Self::audio_file(output_dir, audio_settings));
+
+        let ffmpeg_available = VideoEncoder::is_available();
+        let encodes = encoder.format.encodes();
+        let streamed = encodes && Self::streams(backend, ffmpeg_available);
+        let frame_pattern = backend_plan
+            .files
+            .map(|files| files.pattern)
+            .filter(|_| !streamed);
+        let encoder_command = if encodes && ffmpeg_available {
+            let pattern = frame_pattern
+                .as_ref()
+                .map(|pattern| output_dir.join(pattern).display().to_string());
+            let chapters_path = chapters.then(|| output_dir.join(CHAPTERS_FILE));
+            Some(VideoEncoder::command_line(
+                pattern.as_deref(),
+                output_file,
+                script.metadata.fps,
+                width,
+                height,
+                audio_output.as_deref(),
+                chapters_path.as_deref(),
+                subtitles,
+                encoder,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(RenderPlan {
+            backend: backend.name(),
+            details: backend_plan.details,
+            preview,
+            width,
+            height,
+            fps: script.metadata.fps,
+            frames,
+            output_dir: output_dir.to_path_buf(),
+            frame_bytes: if frame_pattern.is_some() {
+                frames as u64 * width as u64 * height as u64 * 4
+            } else {
+                0
+            },
+            frame_pattern,
+            audio_tracks,
+            audio_output,
+            output_file: encodes.then(|| output_file.to_path_buf()),
+            encoder_command,
+        })
+    }
+
+    /// Whether `run` streams frames from `backend` straight into ffmpeg
+    /// rather than encoding frame files. Incremental renders keep frames on
+    /// disk, so they can't be streamed.
+    fn streams(backend: &dyn Renderer, ffmpeg_available: bool) -> bool {
+        ffmpeg_available && backend.in_process() && !backend.incremental()
+    }
+
+    /// Where the audio mix is exported before it's muxed in
+    fn audio_file(output_dir: &Path, audio_settings: &AudioSettings) -> PathBuf {
+        output_dir.join(format!(
+            "audio.{}",
+            audio_settings.export_format.extension()
+        ))
+    }
+
+    /// Render only `frames` to numbered files in `output_dir`, skipping audio and encoding.
+    /// Files keep their true frame numbers so they line up with a later full render.
+    pub fn render_range(
+        script: &VideoScript,
+        loader: &mut AssetLoader,
+        renderer: &RendererConfig,
+        gpu: Option<&GpuOptions>,
+        frames: Range<u32>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<()> {
+        let output_dir = renderer.output_dir.as_path();
+        say!("\n🎬 Rendering frames {}..{}...", frames.start, frames.end);
+        std::fs::create_dir_all(output_dir)?;
+
+        let mut engine = NativeRenderer::new(renderer, gpu).engine(script);
+        let mut sink =
+            FrameFileSink::new(output_dir, renderer.frame_format).starting_at(frames.start);
+        engine.render_range(frames, &mut sink, loader, progress)?;
 
+        say!("✨ Frames saved in: {}", output_dir.display());
         Ok(())
     }
+
+    /// Render the single frame shown at `seconds` to a PNG in `output_dir` and return its path
+    pub fn render_at(
+        script: &VideoScript,
+        loader: &mut AssetLoader,
+        renderer: &RendererConfig,
+        gpu: Option<&GpuOptions>,
+        seconds: f32,
+    ) -> Result<PathBuf> {
+        let output_dir = renderer.output_dir.as_path();
+        std::fs::create_dir_all(output_dir)?;
+
+        let mut engine = NativeRenderer::new(renderer, gpu).engine(script);
+        let frame = engine.render_at_time(seconds, loader)?;
+        let path = output_dir.join(FrameFormat::Png.file_name(frame));
+        engine.frame_buffer().save_png(path.to_str().unwrap())?;
+        Ok(path)
+    }
+
+    /// Render the frame at `seconds` (by default `thumbnail::default_time`) to
+    /// `<title>_thumbnail.png` and `.jpg` in the output directory, plus copies
+    /// scaled and cropped to `size` when given. With `title_font`, the script's
+    /// title is drawn over the frame. Returns the files written.
+    pub fn render_thumbnail(
+        script: &VideoScript,
+        loader: &mut AssetLoader,
+        renderer: &RendererConfig,
+        gpu: Option<&GpuOptions>,
+        seconds: Option<f32>,
+        size: Option<(u32, u32)>,
+        title_font: Option<&Path>,
+    ) -> Result<Vec<PathBuf>> {
+        let output_dir = renderer.output_dir.as_path();
+        std::fs::create_dir_all(output_dir)?;
+
+        let seconds = seconds.unwrap_or_else(|| thumbnail::default_time(script));
+        let mut engine = NativeRenderer::new(renderer, gpu).engine(script);
+        engine.render_at_time(seconds, loader)?;
+        let mut frame = engine.frame_buffer().clone();
+
+        if let Some(path) = title_font {
+            let asset = loader.load_font(path)?;
+            let font = FontRef::try_from_slice(&asset.data)
+                .with_context(|| format!("Failed to parse font: {}", path.display()))?;
+            thumbnail::draw_title(&mut frame, &font, &script.metadata.title);
+        }
+
+        let stem = format!("{}_thumbnail", script.metadata.slug());
+        let mut variants = vec![(stem.clone(), frame.clone())];
+        if let Some((width, height)) = size {
+            let scaled = thumbnail::cover(&frame, width, height);
+            variants.push((format!("{}_{}x{}", stem, width, height), scaled));
+        }
+
+        let mut paths = Vec::new();
+        for (name, frame) in variants {
+            let png = output_dir.join(format!("{}.png", name));
+            frame.save_png(png.to_str().context("Output path is not UTF-8")?)?;
+            let jpeg = output_dir.join(format!("{}.jpg", name));
+            frame.save_jpeg(
+                jpeg.to_str().context("Output path is not UTF-8")?,
+                thumbnail::JPEG_QUALITY,
+            )?;
+            paths.extend([png, jpeg]);
+        }
+        Ok(paths)
+    }
+
+    /// Render one frame of every scene and save them as a labeled grid to
+    /// `preview.png`. Scenes without frames are left out. Returns the path and
+    /// the number of cells.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_contact_sheet(
+        script: &VideoScript,
+        loader: &mut AssetLoader,
+        renderer: &RendererConfig,
+        gpu: Option<&GpuOptions>,
+        columns: u32,
+        thumb_width: u32,
+        position: SceneFrame,
+        label_font: Option<&Path>,
+    ) -> Result<(PathBuf, usize)> {
+        let output_dir = renderer.output_dir.as_path();
+        std::fs::create_dir_all(output_dir)?;
+
+        let timeline = Timeline::from_script(script);
+        let mut engine = NativeRenderer::new(renderer, gpu).engine(script);
+        let mut frames = Vec::new();
+        for (scene, range) in script.scenes.iter().zip(timeline.scene_ranges()) {
+            let Some(frame) = position.pick(range) else {
+                continue;
+            };
+            engine.render_at_time(contact_sheet::frame_time(&timeline, frame), loader)?;
+            let label = contact_sheet::label(&scene.id, timeline.frame_to_time(frame));
+            frames.push((engine.frame_buffer().clone(), label));
+        }
+
+        let font = match label_font {
+            Some(path) => {
+                let asset = loader.load_font(path)?;
+                Some(
+                    FontRef::try_from_slice(&asset.data)
+                        .with_context(|| format!("Failed to parse font: {}", path.display()))?,
+                )
+            }
+            None => None,
+        };
+
+        let layout = ContactSheetLayout::new(
+            frames.len() as u32,
+            columns,
+            thumb_width,
+            script.metadata.resolution.dimensions(),
+        );
+        let sheet = contact_sheet::compose(&frames, &layout, font.as_ref());
+        let path = output_dir.join("preview.png");
+        sheet.save_png(path.to_str().context("Output path is not UTF-8")?)?;
+        Ok((path, frames.len()))
+    }
+}
+
+/// Fail once the render `backend` is doing was cancelled, so no encoder
+/// starts after it
+fn check_cancelled(backend: &dyn Renderer) -> crate::error::Result<()> {
+    backend
+        .cancellation()
+        .map_or(Ok(()), CancellationToken::check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::renderer::{ProgressEvent, RenderOutput};
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+
+    /// Records what it was asked to render instead of rendering
+    #[derive(Default)]
+    struct MockRenderer {
+        rendered: Vec<(u32, (u32, u32), bool)>,
+    }
+
+    impl Renderer for MockRenderer {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn render(
+            &mut self,
+            script: &VideoScript,
+            _assets: &mut AssetLoader,
+            output: FrameOutput<'_>,
+            _progress: &mut dyn RenderProgress,
+        ) -> crate::error::Result<RenderOutput> {
+            let to_directory = matches!(output, FrameOutput::Directory(_));
+            self.rendered.push((
+                script.metadata.fps,
+                script.metadata.resolution.dimensions(),
+                to_directory,
+            ));
+            Ok(RenderOutput {
+                frames: Timeline::from_script(script).total_frames(),
+                files: None,
+                kept: 0,
+            })
+        }
+    }
+
+    #[test]
+    fn test_plan_writes_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = AppConfig::default();
+        config.renderer.output_dir = dir.path().join("out");
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(10.0));
+        let backend = crate::renderer::create("native", &config, None).unwrap();
+
+        let plan = PerformanceContext::plan(
+            &script,
+            backend.as_ref(),
+            &config.renderer,
+            false,
+            &AudioSettings::default(),
+            &EncodeSettings::default(),
+            &dir.path().join("video.mp4"),
+            true,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(plan.backend, "native");
+        assert_eq!(plan.frames, Timeline::from_script(&script).total_frames());
+        assert_eq!((plan.width, plan.height), (1920, 1080));
+        assert_eq!(plan.output_file, Some(dir.path().join("video.mp4")));
+        assert!(plan
+            .to_string()
+            .contains(&format!("Frames: {}", plan.frames)));
+        assert!(!config.renderer.output_dir.exists());
+        assert!(!dir.path().join("video.mp4").exists());
+
+        // A PNG sequence is only the frame files
+        let png = EncodeSettings {
+            format: "png-sequence".parse().unwrap(),
+            ..Default::default()
+        };
+        let plan = PerformanceContext::plan(
+            &script,
+            backend.as_ref(),
+            &config.renderer,
+            true,
+            &AudioSettings::default(),
+            &png,
+            &dir.path().join("video.mp4"),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.output_file, None);
+        assert_eq!(plan.encoder_command, None);
+        assert_eq!(plan.frame_pattern.as_deref(), Some("frame_%06d.png"));
+        assert_eq!(
+            plan.frame_bytes,
+            plan.frames as u64 * plan.width as u64 * plan.height as u64 * 4
+        );
+    }
+
+    #[test]
+    fn test_plan_keeps_frame_files_for_incremental_renders() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = AppConfig::default();
+        config.renderer.output_dir = dir.path().join("out");
+        config.renderer.incremental = true;
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(1.0));
+        let backend = crate::renderer::create("native", &config, None).unwrap();
+        assert!(!PerformanceContext::streams(backend.as_ref(), true));
+        let streaming = crate::renderer::create("native", &AppConfig::default(), None).unwrap();
+        assert!(PerformanceContext::streams(streaming.as_ref(), true));
+
+        // Encoded from the frame files whether or not ffmpeg is installed
+        let plan = PerformanceContext::plan(
+            &script,
+            backend.as_ref(),
+            &config.renderer,
+            false,
+            &AudioSettings::default(),
+            &EncodeSettings::default(),
+            &dir.path().join("video.mp4"),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.frame_pattern.as_deref(), Some("frame_%06d.png"));
+        assert!(plan.frame_bytes > 0);
+        if let Some(command) = &plan.encoder_command {
+            assert!(command.contains("frame_%06d.png"), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_run_hands_the_preview_script_to_the_backend() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut renderer = AppConfig::default().renderer;
+        renderer.output_dir = dir.path().join("out");
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(10.0));
+        let mut loader = AssetLoader::new(dir.path());
+        let mut backend = MockRenderer::default();
+
+        let summary = PerformanceContext::run(
+            &script,
+            &mut loader,
+            &mut backend,
+            &renderer,
+            true,
+            &AudioSettings::default(),
+            &EncodeSettings::default(),
+            &dir.path().join("video.mp4"),
+            None,
+            None,
+            &mut |_: ProgressEvent| {},
+        )
+        .unwrap();
+
+        assert!(summary.audio.is_none());
+        assert_eq!(
+            summary.frames,
+            Timeline::from_script(&script).total_frames() / 3
+        );
+        assert!(renderer.output_dir.exists());
+        // Half resolution at a third of the frame rate; a backend that can't
+        // stream always renders to the output directory
+        assert_eq!(backend.rendered, vec![(10, (960, 540), true)]);
+    }
 }
@@ -0,0 +1,147 @@
+//! The errors of the parser, asset loader, audio and renderer, so programs
+//! embedding them can tell a missing asset from an invalid script from a GPU
+//! that won't start without reading messages.
+
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Result<T, E = VideoEngineError> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum VideoEngineError {
+    /// The script isn't valid JSON, or doesn't have the fields a script has
+    #[error("Failed to parse JSON script{}: {source}", in_file(path))]
+    Parse {
+        /// Empty for a script parsed from a string
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The script parses but breaks the rules in [`ValidationError`]s
+    #[error("{}", join(.0))]
+    Validation(Vec<ValidationError>),
+    #[error("Asset file not found: {}", .0.display())]
+    AssetNotFound(PathBuf),
+    /// A file exists but can't be read as what it should be
+    #[error("Failed to decode {}: {message}", path.display())]
+    Decode { path: PathBuf, message: String },
+    /// No GPU adapter or device could be set up, or the GPU failed mid-render
+    #[error("GPU error: {0}")]
+    GpuInit(String),
+    /// FFmpeg can't be run, or lacks what the settings ask for
+    #[error("{0}")]
+    EncoderUnavailable(String),
+    /// Blender can't be run, or is too old
+    #[error("{0}")]
+    RendererUnavailable(String),
+    #[error("Failed to {action} {}: {source}", path.display())]
+    Io {
+        /// What was being done, e.g. "read" or "create"
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A tool the engine ran exited with an error
+    #[error("{tool} failed: {stderr}")]
+    External { tool: String, stderr: String },
+    /// A setting or argument out of range, or a combination that can't work
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The render was cancelled through its [`CancellationToken`](crate::renderer::CancellationToken)
+    #[error("Render cancelled")]
+    Cancelled,
+}
+
+impl VideoEngineError {
+    /// An I/O error on `path`, reported as "Failed to `action` `path`"
+    pub fn io(action: &'static str, path: impl AsRef<Path>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        move |source| Self::Io {
+            action,
+            path,
+            source,
+        }
+    }
+
+    /// An `image` error on `path`: decoding errors as [`Self::Decode`], the
+    /// rest as I/O errors reported as "Failed to `action` `path`"
+    pub fn image(
+        action: &'static str,
+        path: impl AsRef<Path>,
+    ) -> impl FnOnce(image::ImageError) -> Self {
+        let path = path.as_ref().to_path_buf();
+        move |error| match error {
+            image::ImageError::IoError(source) => Self::io(action, path)(source),
+            image::ImageError::Decoding(error) => Self::decode(path, error),
+            error => Self::io(action, path)(io::Error::other(error)),
+        }
+    }
+
+    pub fn decode(path: impl AsRef<Path>, message: impl fmt::Display) -> Self {
+        Self::Decode {
+            path: path.as_ref().to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn external(tool: impl Into<String>, stderr: impl Into<String>) -> Self {
+        Self::External {
+            tool: tool.into(),
+            stderr: stderr.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::InvalidInput(message.into())
+    }
+}
+
+/// One rule a script breaks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// Id of the scene it's in, when it's in one
+    pub scene: Option<String>,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            scene: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn in_scene(scene: &str, message: impl Into<String>) -> Self {
+        Self {
+            scene: Some(scene.to_string()),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// ` path`, or nothing when `path` is empty
+fn in_file(path: &Path) -> String {
+    if path.as_os_str().is_empty() {
+        String::new()
+    } else {
+        format!(" {}", path.display())
+    }
+}
+
+fn join(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(ValidationError::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,200 @@
+//! Exit codes of the CLI, so scripts can tell an invalid script from a missing
+//! FFmpeg from a low score. An error carries its code in a [`CliError`]
+//! somewhere in its chain, or is a [`VideoEngineError`] whose kind implies one;
+//! errors without either exit with [`ExitCode::Failure`].
+
+use crate::error::VideoEngineError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ExitCode {
+    Success = 0,
+    /// Any other error, including invalid arguments
+    Failure = 1,
+    /// The script can't be parsed or breaks a rule, or `validate` failed it
+    ValidationFailed = 2,
+    /// A score is below its threshold, or the analysis found issues it was told to fail on
+    AnalysisBelowThreshold = 3,
+    RenderFailed = 4,
+    EncodeFailed = 5,
+    /// FFmpeg or Blender is needed but can't be run
+    MissingDependency = 6,
+    /// The configuration file, an environment variable or a file they name is invalid
+    ConfigError = 7,
+}
+
+impl ExitCode {
+    /// Every code, in order
+    pub const ALL: [ExitCode; 8] = [
+        ExitCode::Success,
+        ExitCode::Failure,
+        ExitCode::ValidationFailed,
+        ExitCode::AnalysisBelowThreshold,
+        ExitCode::RenderFailed,
+        ExitCode::EncodeFailed,
+        ExitCode::MissingDependency,
+        ExitCode::ConfigError,
+    ];
+
+    /// The code `error` exits with: the one closest to where it happened
+    pub fn of(error: &anyhow::Error) -> Self {
+        error
+            .chain()
+            .find_map(Self::carried_by)
+            .unwrap_or(ExitCode::Failure)
+    }
+
+    /// The code `cause` itself carries, without looking at its sources
+    fn carried_by(cause: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        if let Some(error) = cause.downcast_ref::<CliError>() {
+            return Some(error.code);
+        }
+        if cause.is::<config::ConfigError>() {
+            return Some(ExitCode::ConfigError);
+        }
+        match cause.downcast_ref::<VideoEngineError>()? {
+            VideoEngineError::Parse { .. } | VideoEngineError::Validation(_) => {
+                Some(ExitCode::ValidationFailed)
+            }
+            VideoEngineError::EncoderUnavailable(_) | VideoEngineError::RendererUnavailable(_) => {
+                Some(ExitCode::MissingDependency)
+            }
+            VideoEngineError::Cancelled => Some(ExitCode::Failure),
+            // Which step failed says more than what failed in it
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// What the code means, for `--help`
+    pub fn description(self) -> &'static str {
+        match self {
+            ExitCode::Success => "success",
+            ExitCode::Failure => "any other error, including invalid arguments",
+            ExitCode::ValidationFailed => "the script is invalid, or validation failed",
+            ExitCode::AnalysisBelowThreshold => {
+                "a score is below its threshold, or issues of a --fail-on severity were found"
+            }
+            ExitCode::RenderFailed => "rendering failed",
+            ExitCode::EncodeFailed => "encoding the video failed",
+            ExitCode::MissingDependency => "FFmpeg or Blender is needed but can't be run",
+            ExitCode::ConfigError => "the configuration is invalid",
+        }
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code.code())
+    }
+}
+
+/// The exit codes and what they mean, one per line
+pub fn help_text() -> String {
+    let mut text = String::from("Exit codes:\n");
+    for code in ExitCode::ALL {
+        text.push_str(&format!("  {}  {}\n", code.code(), code.description()));
+    }
+    text
+}
+
+/// An error that exits with `code`. It prints as the error it wraps, whose
+/// causes it keeps.
+#[derive(Debug)]
+pub struct CliError {
+    pub code: ExitCode,
+    error: anyhow::Error,
+}
+
+impl CliError {
+    pub fn new(code: ExitCode, error: impl Into<anyhow::Error>) -> Self {
+        Self {
+            code,
+            error: error.into(),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// `.exit_code(code)` on results, giving their errors an exit code
+pub trait WithExitCode<T> {
+    /// Exit with `code` on error, unless the error already has a code of its own
+    fn exit_code(self, code: ExitCode) -> anyhow::Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> WithExitCode<T> for Result<T, E> {
+    fn exit_code(self, code: ExitCode) -> anyhow::Result<T> {
+        self.map_err(|error| {
+            let error = error.into();
+            if error
+                .chain()
+                .any(|cause| ExitCode::carried_by(cause).is_some())
+            {
+                error
+            } else {
+                CliError::new(code, error).into()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_innermost_code_wins_and_messages_are_kept() {
+        let error = Err::<(), _>(anyhow::anyhow!("ffmpeg not found"))
+            .exit_code(ExitCode::MissingDependency)
+            .context("Failed to encode")
+            .exit_code(ExitCode::EncodeFailed)
+            .unwrap_err();
+        assert_eq!(ExitCode::of(&error), ExitCode::MissingDependency);
+        assert_eq!(format!("{:#}", error), "Failed to encode: ffmpeg not found");
+
+        let error = Err::<(), _>(anyhow::anyhow!("disk full"))
+            .context("Failed to write")
+            .exit_code(ExitCode::RenderFailed)
+            .unwrap_err();
+        assert_eq!(ExitCode::of(&error), ExitCode::RenderFailed);
+        assert_eq!(format!("{:#}", error), "Failed to write: disk full");
+
+        assert_eq!(ExitCode::of(&anyhow::anyhow!("other")), ExitCode::Failure);
+        let error = anyhow::Error::new(config::ConfigError::Message("bad".into()));
+        assert_eq!(ExitCode::of(&error), ExitCode::ConfigError);
+    }
+
+    #[test]
+    fn test_engine_errors_carry_their_own_codes() {
+        let error = Err::<(), _>(VideoEngineError::EncoderUnavailable("no ffmpeg".into()))
+            .exit_code(ExitCode::EncodeFailed)
+            .unwrap_err();
+        assert_eq!(ExitCode::of(&error), ExitCode::MissingDependency);
+        assert!(error.is::<VideoEngineError>());
+
+        let error = Err::<(), _>(VideoEngineError::external("FFmpeg", "broken pipe"))
+            .exit_code(ExitCode::EncodeFailed)
+            .unwrap_err();
+        assert_eq!(ExitCode::of(&error), ExitCode::EncodeFailed);
+
+        let error = anyhow::Error::new(VideoEngineError::Validation(vec![]))
+            .context("Failed to load script");
+        assert_eq!(ExitCode::of(&error), ExitCode::ValidationFailed);
+    }
+}
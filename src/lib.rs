@@ -1,15 +1,41 @@
+#[macro_use]
+pub mod output;
+
 pub mod analysis;
 pub mod assets;
 pub mod audio;
+#[cfg(feature = "render")]
+pub mod bundle;
+#[cfg(feature = "render")]
+pub mod cli;
+#[cfg(feature = "render")]
 pub mod config;
+#[cfg(feature = "render")]
 pub mod context;
+pub mod error;
+#[cfg(feature = "render")]
+pub mod exit;
 pub mod parser;
 pub mod renderer;
 pub mod script;
 pub mod templates;
+#[cfg(all(feature = "render", any(test, feature = "test-utils")))]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "render")]
+pub mod watch;
+
+/// The `image` version frames are returned in (e.g. by
+/// [`RenderEngine::render_frame_image`]), so callers needn't depend on a matching one
+pub use image;
 
 pub use assets::AssetLoader;
+#[cfg(feature = "render")]
 pub use audio::{AudioDecoder, AudioMixer};
+pub use error::{ValidationError, VideoEngineError};
 pub use parser::ScriptParser;
-pub use renderer::{Compositor, FrameBuffer, RenderEngine, Timeline};
+pub use renderer::{Compositor, FrameBuffer};
+#[cfg(feature = "render")]
+pub use renderer::{RenderEngine, Timeline};
 pub use script::VideoScript;
@@ -1,126 +1,621 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use interstellar_triangulum::config::AppConfig;
-use interstellar_triangulum::templates::{ScriptTemplate, TemplateType};
-use interstellar_triangulum::{AssetLoader, ScriptParser};
-use std::path::Path;
-
-#[derive(Parser)]
-#[command(name = "interstellar-triangulum")]
-#[command(about = "Digital Artisan Video Engine", long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Option<Commands>,
+use anyhow::{Context, Result};
+use clap::Parser;
+use indicatif::{ProgressBar, ProgressStyle};
+use interstellar_triangulum::analysis::analyzer::ExtraAnalyzers;
+use interstellar_triangulum::analysis::asset_audit::{format_bytes, AssetAuditAnalyzer};
+use interstellar_triangulum::analysis::citations::{CitationCheckSettings, CitationChecker};
+use interstellar_triangulum::analysis::narrative::{PacingAlertKind, Severity};
+use interstellar_triangulum::analysis::report::{AnalysisReport, ReportFormat, Scores};
+use interstellar_triangulum::audio::AudioExportFormat;
+use interstellar_triangulum::bundle::AssetBundle;
+use interstellar_triangulum::cli::{self, AssetsAction, Cli, Commands, ConfigAction};
+use interstellar_triangulum::config::{AppConfig, ConfigSource, CONFIG_FILE_STEM};
+use interstellar_triangulum::context::accessibility::AccessibilityContext;
+use interstellar_triangulum::context::assets::AssetAuditContext;
+use interstellar_triangulum::context::audio::AudioContext;
+use interstellar_triangulum::context::metadata::MetadataContext;
+use interstellar_triangulum::context::performance::{PerformanceContext, RenderSummary};
+use interstellar_triangulum::exit::{CliError, ExitCode, WithExitCode};
+use interstellar_triangulum::output::{self, OutputMode};
+use interstellar_triangulum::renderer::{
+    self, chapters, thumbnail, CancellationToken, FrameFormat, MetadataOverrides, OutputFormat,
+    ProgressEvent, ProgressKind, Timeline,
+};
+use interstellar_triangulum::say;
+use interstellar_triangulum::script::diff::ScriptDiff;
+use interstellar_triangulum::templates::project::ProjectScaffold;
+use interstellar_triangulum::templates::{ScriptTemplate, TemplateOptions};
+use interstellar_triangulum::watch::{self, ScriptSnapshot, ScriptWatcher, WatchEvent};
+use interstellar_triangulum::{AssetLoader, ScriptParser, VideoEngineError, VideoScript};
+use serde::Serialize;
+use serde_json::json;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+fn main() -> std::process::ExitCode {
+    // Usage errors exit with 1, keeping 2 for invalid scripts
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            let _ = error.print();
+            return if error.use_stderr() {
+                ExitCode::Failure
+            } else {
+                ExitCode::Success
+            }
+            .into();
+        }
+    };
+    match run(cli) {
+        Ok(()) => ExitCode::Success.into(),
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::of(&error).into()
+        }
+    }
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    /// Render a script to video
-    Render {
-        /// Path to the script file
-        #[arg(value_name = "SCRIPT")]
-        script: String,
-
-        /// Renderer engine to use
-        #[arg(long)]
-        renderer: Option<String>,
-
-        /// Output directory
-        #[arg(long)]
-        output: Option<String>,
-
-        /// Export analysis report
-        #[arg(long)]
-        export_report: Option<String>,
-
-        /// Fail on low narrative score
-        #[arg(long)]
-        fail_on_low_score: Option<u32>,
-
-        /// Force CPU rendering (disable GPU)
-        #[arg(long)]
-        force_cpu: bool,
-    },
-
-    /// Validate script without rendering
-    Validate {
-        /// Path to the script file
-        #[arg(value_name = "SCRIPT")]
-        script: String,
-
-        /// Fail on warnings
-        #[arg(long)]
-        fail_on_warnings: bool,
-    },
-
-    /// Generate a script template
-    Template {
-        /// Type of template to generate
-        #[arg(value_enum)]
-        #[arg(name = "type")]
-        template_type: TemplateType,
-
-        /// Total duration in seconds
-        #[arg(short, long, default_value_t = 60.0)]
-        duration: f32,
-    },
-
-    /// Clean output and cache directories
-    Clean,
-}
+fn run(cli: Cli) -> Result<()> {
+    output::init(
+        if cli.json {
+            OutputMode::Json
+        } else if cli.quiet {
+            OutputMode::Quiet
+        } else {
+            OutputMode::Human
+        },
+        cli.verbose,
+    )?;
 
-fn main() -> Result<()> {
-    // Load configuration
-    let config = AppConfig::load().unwrap_or_default();
-    let cli = Cli::parse();
+    if cli.list_gpus {
+        return list_gpus();
+    }
+    // `config` reads the file itself, so a broken one can still be shown or replaced
+    let config = if matches!(cli.command, Some(Commands::Config { .. })) {
+        AppConfig::default()
+    } else {
+        AppConfig::load()?
+    };
 
     match cli.command {
         Some(Commands::Template {
             template_type,
             duration,
+            scenes,
+            with_audio,
+            out,
+            force,
+        }) => {
+            let options = TemplateOptions {
+                duration,
+                scenes: scenes.into(),
+                with_audio,
+            };
+            let script = ScriptTemplate::generate(template_type, &options);
+            let json = serde_json::to_string_pretty(&script)?;
+            match out {
+                Some(path) => {
+                    if path.exists() && !force {
+                        anyhow::bail!(
+                            "{} already exists; pass --force to overwrite it",
+                            path.display()
+                        );
+                    }
+                    std::fs::write(&path, json + "\n")
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    say!(
+                        "✓ {} second script with {} scenes written to {}",
+                        script.metadata.duration,
+                        script.scenes.len(),
+                        path.display()
+                    );
+                    output::emit(&json!({ "script": path }))?;
+                }
+                // The script is the output, in every mode
+                None => println!("{}", json),
+            }
+        }
+        Some(Commands::Init {
+            template,
+            name,
+            force,
         }) => {
-            let script = ScriptTemplate::generate(template_type, duration);
-            println!("{}", serde_json::to_string_pretty(&script)?);
+            let dir = PathBuf::from(&name);
+            let title = dir
+                .file_name()
+                .map_or(name.clone(), |n| n.to_string_lossy().into_owned());
+            let files = ProjectScaffold::create(&dir, template, &title, force)?;
+            for path in &files {
+                say!("  ✓ Created {}", path.display());
+            }
+            say!(
+                "\n✨ Project ready. Next: cd {} && interstellar-triangulum render script.json",
+                dir.display()
+            );
+            output::emit(&json!({ "directory": dir, "files": files }))?;
         }
         Some(Commands::Clean) => {
             let output_dir = &config.renderer.output_dir;
             let cache_dir = Path::new(".cache");
+            let mut removed = Vec::new();
             if output_dir.exists() {
                 std::fs::remove_dir_all(output_dir)?;
-                println!("🗑️  Cleaned output directory: {}", output_dir.display());
+                say!("🗑️  Cleaned output directory: {}", output_dir.display());
+                removed.push(output_dir.as_path());
             }
             if cache_dir.exists() {
                 std::fs::remove_dir_all(cache_dir)?;
-                println!("🗑️  Cleaned cache directory: {}", cache_dir.display());
+                say!("🗑️  Cleaned cache directory: {}", cache_dir.display());
+                removed.push(cache_dir);
+            }
+            output::emit(&json!({ "removed": removed }))?;
+        }
+        Some(Commands::Config { action }) => run_config_action(action)?,
+        Some(Commands::Completions { shell, out }) => match out {
+            Some(path) => {
+                let mut file = std::fs::File::create(&path)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                cli::write_completions(shell, &mut file);
+                say!("✓ {} completions written to {}", shell, path.display());
+                output::emit(&json!({ "completions": path }))?;
+            }
+            // The script is the output, in every mode
+            None => cli::write_completions(shell, &mut std::io::stdout()),
+        },
+        Some(Commands::Thumbnail {
+            script,
+            at,
+            size,
+            with_title,
+            title_font,
+            output,
+            force_cpu,
+        }) => {
+            let mut config = config;
+            if let Some(output) = output {
+                config.renderer.output_dir = PathBuf::from(output);
+            }
+            let script_path = Path::new(&script);
+            let script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+            let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+            let mut loader = AssetLoader::new(base_path);
+
+            // A font given on the command line is relative to the working directory,
+            // the script's fonts to the script
+            let title_font = match (with_title, title_font) {
+                (false, _) => None,
+                (true, Some(font)) => Some(std::path::absolute(font)?),
+                (true, None) => Some(
+                    thumbnail::title_font(&script)
+                        .context("The script has no text layer to take a title font from; pass --title-font")?
+                        .to_path_buf(),
+                ),
+            };
+            let paths = PerformanceContext::render_thumbnail(
+                &script,
+                &mut loader,
+                &config.renderer,
+                (!force_cpu).then_some(&config.gpu),
+                at,
+                size,
+                title_font.as_deref(),
+            )
+            .exit_code(ExitCode::RenderFailed)?;
+            for path in &paths {
+                say!("🖼️  Thumbnail saved: {}", path.display());
+            }
+            output::emit(&json!({ "thumbnails": paths }))?;
+        }
+        Some(Commands::Preview {
+            script,
+            columns,
+            thumb_width,
+            frame_per_scene,
+            label_font,
+            resolution,
+            fps,
+            scale_layout,
+            debug_overlay,
+            output,
+            force_cpu,
+        }) => {
+            let mut config = config;
+            config.renderer.debug.enabled = debug_overlay;
+            if let Some(output) = output {
+                config.renderer.output_dir = PathBuf::from(output);
+            }
+            let script_path = Path::new(&script);
+            let mut script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+            let overrides = MetadataOverrides {
+                resolution,
+                fps,
+                scale_layout,
+            };
+            if let Some(warning) = overrides.apply(&mut script) {
+                say!("⚠️  {}", warning);
+            }
+            let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+            let mut loader = AssetLoader::new(base_path);
+
+            // A font given on the command line is relative to the working directory,
+            // the script's fonts to the script
+            let label_font = match label_font {
+                Some(font) => Some(std::path::absolute(font)?),
+                None => thumbnail::title_font(&script).map(Path::to_path_buf),
+            };
+            if label_font.is_none() {
+                say!("ℹ️  The script has no text layer to take a label font from; pass --label-font for labels");
+            }
+            let (path, cells) = PerformanceContext::render_contact_sheet(
+                &script,
+                &mut loader,
+                &config.renderer,
+                (!force_cpu).then_some(&config.gpu),
+                columns,
+                thumb_width,
+                frame_per_scene,
+                label_font.as_deref(),
+            )
+            .exit_code(ExitCode::RenderFailed)?;
+            say!("🖼️  Preview of {} scenes saved: {}", cells, path.display());
+            output::emit(&json!({ "preview": path, "scenes": cells }))?;
+        }
+        Some(Commands::Audio {
+            script,
+            out,
+            format,
+            output,
+        }) => {
+            let mut config = config;
+            if let Some(output) = output {
+                config.renderer.output_dir = PathBuf::from(output);
+            }
+            // `wav` keeps a configured WAV sample format
+            match format.as_deref() {
+                Some("flac") => config.audio.export_format = AudioExportFormat::Flac,
+                Some("wav") if config.audio.export_format == AudioExportFormat::Flac => {
+                    config.audio.export_format = AudioExportFormat::Wav32Float
+                }
+                _ => {}
+            }
+
+            let script_path = Path::new(&script);
+            let script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+            let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+            let loader = AssetLoader::new(base_path);
+            let out = out.unwrap_or_else(|| {
+                config.renderer.output_dir.join(format!(
+                    "{}.{}",
+                    script.metadata.slug(),
+                    config.audio.export_format.extension()
+                ))
+            });
+            let report = AudioContext::mix_script(&script, &loader, &config.audio, &out)?;
+            output::emit(&json!({
+                "output": out,
+                "loudness": report.loudness,
+                "analysis": report.analysis,
+            }))?;
+        }
+        Some(Commands::Diff { old, new }) => {
+            let analyze = |path: &str| -> Result<(VideoScript, AnalysisReport)> {
+                let path = Path::new(path);
+                let script = ScriptParser::parse_json_with_defaults(path, &config.video)?;
+                let base_path = path.parent().unwrap_or_else(|| Path::new("."));
+                let report = AnalysisReport::analyze(
+                    &script,
+                    &config.analysis(base_path)?,
+                    &ExtraAnalyzers::new(),
+                );
+                Ok((script, report))
+            };
+            let (old_script, old_report) = analyze(&old)?;
+            let (new_script, new_report) = analyze(&new)?;
+            let diff = ScriptDiff::between(&old_script, &new_script);
+            let scores = [
+                ("overall", old_report.score, new_report.score),
+                (
+                    "narrative",
+                    old_report.narrative.score,
+                    new_report.narrative.score,
+                ),
+                (
+                    "credibility",
+                    old_report.credibility.score,
+                    new_report.credibility.score,
+                ),
+            ];
+
+            if cli.json {
+                let scores: serde_json::Map<String, serde_json::Value> = scores
+                    .iter()
+                    .map(|(name, old, new)| {
+                        let delta = i64::from(*new) - i64::from(*old);
+                        (
+                            name.to_string(),
+                            json!({ "old": old, "new": new, "delta": delta }),
+                        )
+                    })
+                    .collect();
+                output::emit(&json!({ "old": old, "new": new, "diff": diff, "scores": scores }))?;
+            } else {
+                // The diff is the output, in every mode
+                print!("{}", diff);
+                println!("Scores:");
+                for (name, old, new) in scores {
+                    println!(
+                        "  {}: {} → {} ({:+})",
+                        name,
+                        old,
+                        new,
+                        i64::from(new) - i64::from(old)
+                    );
+                }
+            }
+        }
+        Some(Commands::Score {
+            script,
+            breakdown,
+            min,
+        }) => {
+            let min = min.unwrap_or(config.score.min);
+            let parsed = ScriptParser::parse_json_with_defaults(Path::new(&script), &config.video)?;
+            let scores = Scores::of(
+                &parsed,
+                &config.credibility.dictionaries()?,
+                &config.narrative()?,
+            );
+
+            if cli.json {
+                let mut document = json!({
+                    "script": script,
+                    "score": scores.overall,
+                    "min": min,
+                    "passed": scores.overall >= min,
+                });
+                if breakdown {
+                    document["breakdown"] = json!(scores);
+                }
+                output::emit(&document)?;
+            } else if breakdown {
+                println!("overall: {}", scores.overall);
+                println!("narrative: {}", scores.narrative);
+                println!("credibility: {}", scores.credibility);
+                println!("retention: {}", scores.retention);
+            } else {
+                println!("{}", scores.overall);
+            }
+            check_min_score("Score", scores.overall, min)?;
+        }
+        Some(Commands::Analyze {
+            script,
+            out,
+            format,
+            fail_below,
+            fail_on,
+            check_citations,
+            citation_timeout,
+        }) => {
+            let script_path = Path::new(&script);
+            let script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+            let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+            let report = AnalysisReport::analyze(
+                &script,
+                &config.analysis(base_path)?,
+                &ExtraAnalyzers::new(),
+            )
+            .with_script_path(script_path);
+            let report = match citation_checks(check_citations, citation_timeout) {
+                Some(settings) => {
+                    let statuses = CitationChecker::check(&script.metadata.citations, &settings)?;
+                    report.with_citation_checks(&script, statuses)
+                }
+                None => report,
+            };
+
+            let content = report.render(format)?;
+            match &out {
+                Some(path) => {
+                    std::fs::write(path, content)?;
+                    eprintln!("📄 Report exported to: {}", path.display());
+                }
+                // The report is the output, in every mode
+                None if !cli.json => print!("{}", content),
+                None => {}
+            }
+            output::emit(&report)?;
+
+            // Messages go to stderr so stdout stays parseable
+            if let Some(threshold) = fail_below {
+                check_min_score("Score", report.score, threshold)?;
+            }
+            if let Some(severity) = fail_on.filter(|&severity| report.has_issues(severity)) {
+                return Err(CliError::new(
+                    ExitCode::AnalysisBelowThreshold,
+                    anyhow::anyhow!("Found issues of severity {:?} or worse", severity),
+                )
+                .into());
+            }
+        }
+        Some(Commands::Assets { script, action }) => {
+            let script_path = Path::new(&script);
+            let script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+            let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+            let loader = AssetLoader::new(base_path);
+            if let Some(action) = action {
+                run_assets_action(action, script_path, &script, &loader)?;
+            } else if cli.json {
+                output::emit(&AssetAuditAnalyzer::analyze(&script, &loader))?;
+            } else {
+                say!("📁 Assets of {}:", script_path.display());
+                for reference in AssetAuditAnalyzer::analyze(&script, &loader).references {
+                    let scene = reference
+                        .scene_index
+                        .map_or("audio track".to_string(), |i| format!("scene {}", i + 1));
+                    let size = reference.bytes.map_or("missing".to_string(), format_bytes);
+                    say!(
+                        "   - {} ({}, {}): {}",
+                        reference.path.display(),
+                        reference.kind.label(),
+                        scene,
+                        size
+                    );
+                }
+                AssetAuditContext::run(&script, &loader);
             }
         }
         Some(Commands::Validate {
             script,
             fail_on_warnings,
+            report_format,
+            report_out,
+            check_citations,
+            citation_timeout,
         }) => {
-            run_validation(&script, fail_on_warnings)?;
+            let report = report_format.zip(report_out);
+            let citations = citation_checks(check_citations, citation_timeout);
+            run_validation(&script, &config, fail_on_warnings, report, citations)?;
         }
         Some(Commands::Render {
             script,
             renderer,
             output,
+            out,
             export_report,
             fail_on_low_score,
             force_cpu,
+            gpu_adapter,
+            audio_target_lufs,
+            resolution,
+            fps,
+            scale_layout,
+            frames,
+            at,
+            preview,
+            watch,
+            dry_run,
+            json_progress,
+            export_chapters,
+            keep_short_chapters,
+            resume,
+            incremental,
+            no_watermark,
+            debug_overlay,
+            jobs,
+            codec,
+            crf,
+            preset,
+            format,
+            gif_colors,
+            gif_fps,
+            subtitles,
+            burn_subtitles,
         }) => {
-            let renderer_engine = renderer.unwrap_or(config.renderer.engine.clone());
-            let output_dir = output
-                .map(std::path::PathBuf::from)
-                .unwrap_or(config.renderer.output_dir.clone());
+            // CLI flags override the configured renderer settings
+            let mut config = config;
+            if let Some(renderer) = renderer {
+                config.renderer.engine = renderer;
+            }
+            if let Some(adapter) = gpu_adapter {
+                config.gpu.adapter_name_substring = Some(adapter);
+            }
+            if let Some(output) = output {
+                config.renderer.output_dir = std::path::PathBuf::from(output);
+            }
+            config.blender.resume = resume;
+            config.renderer.incremental = incremental;
+            config.renderer.no_watermark = no_watermark;
+            config.renderer.debug.enabled = debug_overlay;
+            if let Some(jobs) = jobs {
+                config.blender.parallel_jobs = Some(jobs.into());
+            }
+            if let Some(codec) = codec {
+                config.encoder.codec = codec;
+            }
+            if let Some(crf) = crf {
+                // A CRF on the command line replaces a configured bitrate
+                config.encoder.crf = Some(crf);
+                config.encoder.bitrate = None;
+            }
+            if let Some(preset) = preset {
+                config.encoder.preset = Some(preset);
+            }
+            if burn_subtitles {
+                config.encoder.burn_subtitles = true;
+            }
+            config.encoder.format = match format.unwrap_or_default() {
+                OutputFormat::Gif {
+                    max_colors,
+                    fps_cap,
+                } => OutputFormat::Gif {
+                    max_colors: gif_colors.unwrap_or(max_colors),
+                    fps_cap: gif_fps.unwrap_or(fps_cap),
+                },
+                OutputFormat::PngSequence => {
+                    config.renderer.frame_format = FrameFormat::Png;
+                    OutputFormat::PngSequence
+                }
+                other => other,
+            };
 
-            run_render(
-                &script,
-                &renderer_engine,
-                &output_dir,
-                export_report,
-                fail_on_low_score,
-                force_cpu,
-            )?;
+            let mode = match (frames, at) {
+                (Some(frames), _) => RenderMode::Range(frames),
+                (None, Some(seconds)) => RenderMode::At(seconds),
+                (None, None) if preview || watch => RenderMode::Preview,
+                (None, None) => RenderMode::Full,
+            };
+            let overrides = MetadataOverrides {
+                resolution,
+                fps,
+                scale_layout,
+            };
+            let export_chapters = export_chapters.map(|path| ChapterExport {
+                path: PathBuf::from(path),
+                keep_short: keep_short_chapters,
+            });
+
+            if watch {
+                // Every render would be its own document
+                if cli.json {
+                    anyhow::bail!("--watch can't be combined with --json");
+                }
+                run_watch(&script, &config, |cancellation| {
+                    run_render(
+                        &script,
+                        &config,
+                        export_report.clone(),
+                        None,
+                        force_cpu,
+                        audio_target_lufs,
+                        overrides,
+                        RenderMode::Preview,
+                        false,
+                        json_progress,
+                        out.clone(),
+                        subtitles.clone(),
+                        export_chapters.clone(),
+                        Some(cancellation),
+                    )
+                })?;
+            } else {
+                run_render(
+                    &script,
+                    &config,
+                    export_report,
+                    fail_on_low_score,
+                    force_cpu,
+                    audio_target_lufs,
+                    overrides,
+                    mode,
+                    dry_run,
+                    json_progress,
+                    out,
+                    subtitles,
+                    export_chapters,
+                    Some(cancel_on_ctrl_c()?),
+                )?;
+            }
         }
         None => {
             // Default behavior if no subcommand: try to render examples/simple.json
@@ -135,147 +630,724 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_validation(script_path: &str, fail_on_warnings: bool) -> Result<()> {
+fn run_validation(
+    script_path: &str,
+    config: &AppConfig,
+    fail_on_warnings: bool,
+    report: Option<(ReportFormat, PathBuf)>,
+    citation_checks: Option<CitationCheckSettings>,
+) -> Result<()> {
     let script_path = Path::new(script_path);
-    println!("🔍 Validating script: {}", script_path.display());
+    say!("🔍 Validating script: {}", script_path.display());
+    let dictionaries = config.credibility.dictionaries()?;
 
-    let script = ScriptParser::parse_json(script_path)?;
-    println!("\n📋 Script Summary:");
-    println!("{}", ScriptParser::summarize(&script));
+    let script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+    // The command line draws no plugin layers
+    ScriptParser::check_layer_types(&script, &[])?;
+    say!("\n📋 Script Summary:");
+    say!("{}", ScriptParser::summarize(&script));
 
     // Run Analysis
-    let narrative_report =
-        interstellar_triangulum::context::narrative::NarrativeContext::run(&script);
-    let credibility_report =
-        interstellar_triangulum::context::credibility::CredibilityContext::run(&script);
-
-    if fail_on_warnings {
-        let has_warnings = !narrative_report.structure_valid
-            || !narrative_report.structure_recommendations.is_empty()
-            || !narrative_report.pacing_alerts.is_empty()
-            || !narrative_report.retention_warnings.is_empty()
-            || credibility_report.score < 100; // Strict check
-
-        if has_warnings {
-            eprintln!("\n❌ Validation failed due to warnings (strict mode).");
-            std::process::exit(1);
+    let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+    let narrative_report = interstellar_triangulum::context::narrative::NarrativeContext::run(
+        &script,
+        base_path,
+        &config.narrative()?,
+    );
+    let mut credibility_report =
+        interstellar_triangulum::context::credibility::CredibilityContext::run(
+            &script,
+            &dictionaries,
+        );
+    if let Some(settings) = &citation_checks {
+        interstellar_triangulum::context::credibility::CredibilityContext::check_citations(
+            &mut credibility_report,
+            settings,
+        )?;
+    }
+    let audio_report = AudioContext::validate(&script, base_path);
+    let accessibility_report = AccessibilityContext::run(
+        &script,
+        &mut AssetLoader::new(base_path),
+        &config.accessibility,
+    );
+    let asset_report = AssetAuditContext::run(&script, &AssetLoader::new(base_path));
+    let metadata_report = MetadataContext::run(&script);
+
+    let has_warnings = !narrative_report.structure_valid
+        || !narrative_report.structure_recommendations.is_empty()
+        || narrative_report
+            .pacing_alerts
+            .iter()
+            .any(|a| a.kind != PacingAlertKind::SpeechCoverageEstimate)
+        || !narrative_report.retention_warnings.is_empty()
+        || !narrative_report.readability.warnings.is_empty()
+        || !audio_report.warnings.is_empty()
+        || !accessibility_report.warnings.is_empty()
+        || metadata_report
+            .findings
+            .iter()
+            .any(|finding| finding.severity != Severity::Info)
+        || credibility_report
+            .citation_checks
+            .iter()
+            .any(|status| !status.reachable())
+        || credibility_report.score < 100; // Strict check
+    let analysis = AnalysisReport::new(
+        &script,
+        narrative_report,
+        credibility_report,
+        audio_report,
+        accessibility_report,
+    )
+    .with_script_path(script_path);
+
+    // Written before failing on warnings, so CI can annotate them
+    if let Some((format, path)) = report {
+        std::fs::write(&path, analysis.render(format)?)?;
+        say!("\n📄 Report exported to: {}", path.display());
+    }
+
+    let failed = fail_on_warnings && has_warnings;
+    output::emit(&json!({
+        "script": script_path,
+        "passed": !failed,
+        "has_warnings": has_warnings,
+        "report": analysis,
+        "metadata": metadata_report,
+        "assets": asset_report,
+    }))?;
+    if failed {
+        return Err(CliError::new(
+            ExitCode::ValidationFailed,
+            anyhow::anyhow!("Validation failed due to warnings (strict mode)"),
+        )
+        .into());
+    }
+
+    say!("\n✅ Validation complete.");
+    Ok(())
+}
+
+/// A token Ctrl-C cancels, so a render stops after its current frame and
+/// cleans up after itself. A second Ctrl-C quits at once.
+fn cancel_on_ctrl_c() -> Result<CancellationToken> {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    ctrlc::set_handler(move || {
+        if cancel.is_cancelled() {
+            std::process::exit(ExitCode::Failure.code().into());
+        }
+        say!("\n⏹️  Stopping the render (Ctrl-C again to quit now)...");
+        cancel.cancel();
+    })
+    .context("Failed to handle Ctrl-C")?;
+    Ok(token)
+}
+
+/// Validate the script and `render` it, then again after every change to the
+/// script or its assets until Ctrl-C. A change arriving mid-render cancels that
+/// render through the token `render` gets.
+fn run_watch(
+    script_path: &str,
+    config: &AppConfig,
+    render: impl Fn(CancellationToken) -> Result<()>,
+) -> Result<()> {
+    let current = Arc::new(Mutex::new(CancellationToken::new()));
+    let cancel = current.clone();
+    let mut watcher = ScriptWatcher::new(watch::DEBOUNCE, move || {
+        cancel.lock().unwrap().cancel();
+    })?;
+    let (cancel, stop) = (current.clone(), watcher.sender());
+    ctrlc::set_handler(move || {
+        cancel.lock().unwrap().cancel();
+        let _ = stop.send(WatchEvent::Stop);
+    })
+    .context("Failed to handle Ctrl-C")?;
+
+    let path = Path::new(script_path);
+    let mut snapshot: Option<ScriptSnapshot> = None;
+    loop {
+        // A script that doesn't parse is reported by the validation below
+        let script = ScriptParser::parse_json_with_defaults(path, &config.video).ok();
+        watcher.watch(watch::watched_files(path, script.as_ref()))?;
+        if let Some(script) = &script {
+            let newer = ScriptSnapshot::new(script);
+            if let Some(older) = &snapshot {
+                for line in older.diff(&newer) {
+                    say!("  • {}", line);
+                }
+            }
+            snapshot = Some(newer);
+        }
+
+        let token = CancellationToken::new();
+        *current.lock().unwrap() = token.clone();
+        match run_validation(script_path, config, false, None, None).and_then(|()| render(token)) {
+            Ok(()) => {}
+            Err(error) if is_cancelled(&error) => say!("\n⏹️  Render cancelled"),
+            Err(error) => eprintln!("\n❌ {:#}", error),
+        }
+
+        say!(
+            "\n👀 Watching {} for changes (Ctrl-C to stop)...",
+            path.display()
+        );
+        match watcher.next_event() {
+            WatchEvent::Stop => break,
+            WatchEvent::Changed(paths) => {
+                let names: Vec<String> = paths
+                    .iter()
+                    .map(|path| {
+                        path.file_name()
+                            .unwrap_or(path.as_os_str())
+                            .to_string_lossy()
+                            .into_owned()
+                    })
+                    .collect();
+                say!("\n🔄 Changed: {}", names.join(", "));
+            }
         }
     }
 
-    println!("\n✅ Validation complete.");
+    say!("\n👋 Stopped watching");
+    Ok(())
+}
+
+/// How much of the video to render, and at what quality
+enum RenderMode {
+    Full,
+    /// The whole video, scaled down and at a reduced frame rate
+    Preview,
+    /// Only these frames, written as files
+    Range(Range<u32>),
+    /// Only the frame shown at this time, written as a PNG
+    At(f32),
+}
+
+/// Where to write the chapter list, and whether short scenes keep their own chapters
+#[derive(Clone)]
+struct ChapterExport {
+    path: PathBuf,
+    keep_short: bool,
+}
+
+/// How to request citation URLs, when `--check-citations` asks to
+fn citation_checks(check: bool, timeout: f32) -> Option<CitationCheckSettings> {
+    check.then(|| CitationCheckSettings {
+        timeout: Duration::from_secs_f32(timeout.max(0.1)),
+        ..Default::default()
+    })
+}
+
+/// Whether `error` comes from a render stopped through its cancellation token
+fn is_cancelled(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| matches!(cause.downcast_ref(), Some(VideoEngineError::Cancelled)))
+}
+
+/// Fail when `score` is below `min`, for `score`, `analyze --fail-below` and
+/// `render --fail-on-low-score`
+fn check_min_score(what: &str, score: u32, min: u32) -> Result<()> {
+    if score < min {
+        return Err(CliError::new(
+            ExitCode::AnalysisBelowThreshold,
+            anyhow::anyhow!("{} {} is below threshold {}", what, score, min),
+        )
+        .into());
+    }
     Ok(())
 }
 
+fn run_config_action(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Show => {
+            let loaded = AppConfig::load_from(Path::new("."))?;
+            let values = loaded.values()?;
+            if output::mode() == OutputMode::Json {
+                return output::emit(&json!({ "file": loaded.file, "values": values }));
+            }
+            // The values are the output, in every mode
+            match &loaded.file {
+                Some(path) => println!("# Loaded {}", path.display()),
+                None => println!("# No {}.toml found", CONFIG_FILE_STEM),
+            }
+            for value in &values {
+                let source = match value.source {
+                    ConfigSource::Env => format!("env {}", value.env_var()),
+                    source => source.to_string(),
+                };
+                println!("{} = {}  # {}", value.key, value.value, source);
+            }
+        }
+        ConfigAction::Init { force } => {
+            let path = PathBuf::from(format!("{}.toml", CONFIG_FILE_STEM));
+            if path.exists() && !force {
+                anyhow::bail!(
+                    "{} already exists; pass --force to overwrite it",
+                    path.display()
+                );
+            }
+            std::fs::write(&path, AppConfig::commented_defaults()?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            say!("✓ Default configuration written to {}", path.display());
+            output::emit(&json!({ "config": path }))?;
+        }
+        ConfigAction::Path => {
+            let loaded = AppConfig::load_from(Path::new("."))?;
+            if output::mode() == OutputMode::Json {
+                return output::emit(&json!({ "file": loaded.file }));
+            }
+            match &loaded.file {
+                Some(path) => println!("{}", path.display()),
+                None => say!("No {}.toml found; using defaults", CONFIG_FILE_STEM),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_assets_action(
+    action: AssetsAction,
+    script_path: &Path,
+    script: &VideoScript,
+    loader: &AssetLoader,
+) -> Result<()> {
+    let references = AssetAuditAnalyzer::analyze(script, loader).references;
+    match action {
+        AssetsAction::List if output::mode() == OutputMode::Json => output::emit(&references)?,
+        // The table is the output, in every mode
+        AssetsAction::List => {
+            println!("{:<6} {:<7} {:>10}  PATH", "TYPE", "EXISTS", "SIZE");
+            for reference in &references {
+                let (exists, size) = match reference.bytes {
+                    Some(bytes) => ("yes", format_bytes(bytes)),
+                    None => ("no", "-".to_string()),
+                };
+                println!(
+                    "{:<6} {:<7} {:>10}  {}",
+                    reference.kind.label(),
+                    exists,
+                    size,
+                    reference.resolved.display()
+                );
+            }
+        }
+        AssetsAction::Verify => {
+            let missing: Vec<_> = references
+                .iter()
+                .filter(|reference| reference.bytes.is_none())
+                .collect();
+            output::emit(&json!({
+                "total": references.len(),
+                "missing": missing.iter().map(|reference| &reference.resolved).collect::<Vec<_>>(),
+            }))?;
+            if !missing.is_empty() {
+                for reference in &missing {
+                    eprintln!(
+                        "❌ Missing {}: {}",
+                        reference.kind.label(),
+                        reference.resolved.display()
+                    );
+                }
+                return Err(CliError::new(
+                    ExitCode::ValidationFailed,
+                    anyhow::anyhow!("{} of {} asset(s) missing", missing.len(), references.len()),
+                )
+                .into());
+            }
+            say!("✅ All {} asset(s) present", references.len());
+        }
+        AssetsAction::Copy { dest } => {
+            let script_name = script_path
+                .file_name()
+                .map_or("script.json".into(), |name| name.to_string_lossy());
+            let bundle = AssetBundle::create(script, loader, &dest, &script_name)?;
+            for (from, to) in &bundle.files {
+                say!("  ✓ {} -> {}", from.display(), to.display());
+            }
+            say!(
+                "📦 Copied {} file(s) ({}) and {}",
+                bundle.files.len(),
+                format_bytes(bundle.bytes),
+                bundle.script.display()
+            );
+            let files: Vec<_> = bundle
+                .files
+                .iter()
+                .map(|(from, to)| json!({ "from": from, "to": to }))
+                .collect();
+            output::emit(&json!({
+                "script": bundle.script,
+                "files": files,
+                "bytes": bundle.bytes,
+            }))?;
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_render(
     script_path: &str,
-    renderer_engine: &str,
-    output_dir: &Path,
+    config: &AppConfig,
     export_report: Option<String>,
     fail_on_low_score: Option<u32>,
     force_cpu: bool,
+    audio_target_lufs: Option<f32>,
+    overrides: MetadataOverrides,
+    mode: RenderMode,
+    dry_run: bool,
+    json_progress: bool,
+    out: Option<PathBuf>,
+    subtitles: Option<PathBuf>,
+    export_chapters: Option<ChapterExport>,
+    cancellation: Option<CancellationToken>,
 ) -> Result<()> {
     let script_path = Path::new(script_path);
-    println!("🎬 Video Engine - Digital Artisan PoC\n");
-    println!("Parsing script: {}", script_path.display());
+    say!("🎬 Video Engine - Digital Artisan PoC\n");
+    let dictionaries = config.credibility.dictionaries()?;
+    say!("Parsing script: {}", script_path.display());
+
+    let mut script = ScriptParser::parse_json_with_defaults(script_path, &config.video)?;
+    // The command line draws no plugin layers
+    ScriptParser::check_layer_types(&script, &[])?;
+    config.renderer.apply_watermark(&mut script);
+    if let Some(warning) = overrides.apply(&mut script) {
+        say!("⚠️  {}", warning);
+    }
 
-    let script = ScriptParser::parse_json(script_path)?;
+    // CLI loudness target overrides (or enables) the script's normalization block
+    if let (Some(target), Some(audio)) = (audio_target_lufs, script.audio.as_mut()) {
+        let normalization = audio.normalization.get_or_insert_with(Default::default);
+        normalization.target_lufs = target;
+    }
 
-    println!("\n📋 Script Summary:");
-    println!("{}", ScriptParser::summarize(&script));
+    say!("\n📋 Script Summary:");
+    say!("{}", ScriptParser::summarize(&script));
 
     // Load assets
     let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
     let mut loader = AssetLoader::new(base_path);
 
     // Pillar 2: Narrative (Engaging)
-    let narrative_report =
-        interstellar_triangulum::context::narrative::NarrativeContext::run(&script);
+    let narrative_report = interstellar_triangulum::context::narrative::NarrativeContext::run(
+        &script,
+        base_path,
+        &config.narrative()?,
+    );
 
     // Pillar 3: Credibility (Trustworthy)
-    interstellar_triangulum::context::credibility::CredibilityContext::run(&script);
-
-    // Export Report
-    if let Some(path) = export_report {
-        let path = Path::new(&path);
-        let content = if path.extension().is_some_and(|ext| ext == "json") {
-            // JSON Export
-            serde_json::to_string_pretty(&narrative_report)?
-        } else {
-            // Markdown Export
-            let mut md = format!(
-                "# Narrative Analysis Report\n\n**Score**: {}/100\n\n## Structure\n- Valid: {}\n- Errors: {:?}\n\n## Recommendations\n",
-                narrative_report.score,
-                narrative_report.structure_valid,
-                narrative_report.structure_errors
-            );
+    let credibility_report = interstellar_triangulum::context::credibility::CredibilityContext::run(
+        &script,
+        &dictionaries,
+    );
 
-            for rec in &narrative_report.structure_recommendations {
-                md.push_str(&format!(
-                    "- **[{:?}]** {}: {}\n",
-                    rec.severity, rec.category, rec.message
-                ));
-            }
-            md
-        };
-        std::fs::write(path, content)?;
-        println!("\n📄 Report exported to: {}", path.display());
-    }
+    let audio_timing = AudioContext::validate(&script, base_path);
+    let accessibility = AccessibilityContext::run(&script, &mut loader, &config.accessibility);
 
-    // Fail on low score
+    let analysis = AnalysisReport::new(
+        &script,
+        narrative_report,
+        credibility_report,
+        audio_timing,
+        accessibility,
+    )
+    .with_script_path(script_path);
+
+    // Written before failing on the score or rendering, so a failed render
+    // still leaves it; rewritten with the audio analysis once the mix is done
+    if let (Some(path), false) = (&export_report, dry_run) {
+        write_report(Path::new(path), &analysis)?;
+    }
+    // The same overall score `score` prints and checks
     if let Some(threshold) = fail_on_low_score {
-        if narrative_report.score < threshold {
-            eprintln!(
-                "\n❌ Narrative score {} is below threshold {}",
-                narrative_report.score, threshold
-            );
-            std::process::exit(1);
-        }
+        let scores = Scores::of(&script, &dictionaries, &config.narrative()?);
+        check_min_score("Score", scores.overall, threshold)?;
     }
 
     // Pillar 1: Performance (Fast) - Asset Loading & Rendering
-    println!("\n🎨 Loading assets...");
+    say!("\n🎨 Loading assets...");
     // Pre-load assets for statistics and validation
     for scene in &script.scenes {
         for layer in &scene.layers {
             match layer {
                 interstellar_triangulum::script::Layer::Image { source, .. } => {
                     if let Err(e) = loader.load_image(source) {
-                        println!("  ✗ Failed to load image {}: {}", source.display(), e);
+                        say!("  ✗ Failed to load image {}: {}", source.display(), e);
                     } else {
-                        println!("  ✓ Loaded image: {}", source.display());
+                        say!("  ✓ Loaded image: {}", source.display());
                     }
                 }
                 interstellar_triangulum::script::Layer::Video { source, .. } => {
                     if let Err(e) = loader.load_video(source) {
-                        println!("  ✗ Failed to load video {}: {}", source.display(), e);
+                        say!("  ✗ Failed to load video {}: {}", source.display(), e);
                     } else {
-                        println!("  ✓ Loaded video: {}", source.display());
+                        say!("  ✓ Loaded video: {}", source.display());
                     }
                 }
                 interstellar_triangulum::script::Layer::Text { font, .. } => {
                     if let Err(e) = loader.load_font(font) {
-                        println!("  ✗ Failed to load font {}: {}", font.display(), e);
+                        say!("  ✗ Failed to load font {}: {}", font.display(), e);
+                    } else {
+                        say!("  ✓ Loaded font: {}", font.display());
+                    }
+                }
+                interstellar_triangulum::script::Layer::Waveform { audio_source, .. } => {
+                    // Levels are analyzed by the render engine; only probe the file here
+                    let path = base_path.join(audio_source);
+                    if let Err(e) = interstellar_triangulum::AudioDecoder::stream(&path) {
+                        say!(
+                            "  ✗ Failed to load waveform audio {}: {}",
+                            audio_source.display(),
+                            e
+                        );
                     } else {
-                        println!("  ✓ Loaded font: {}", font.display());
+                        say!("  ✓ Found waveform audio: {}", audio_source.display());
                     }
                 }
+                interstellar_triangulum::script::Layer::Custom { kind, .. } => {
+                    say!("  • {} layer, drawn by its plugin", kind);
+                }
             }
         }
     }
 
-    let use_blender = renderer_engine == "blender";
-    let use_gpu = !force_cpu;
+    let chapters_path = export_chapters.as_ref().map(|export| export.path.clone());
+    let chapters = match export_chapters {
+        Some(export) => {
+            let timeline = Timeline::from_script(&script);
+            let mut chapters = timeline.chapters();
+            if !export.keep_short {
+                chapters = chapters::merge_short_chapters(
+                    &chapters,
+                    timeline.duration_seconds(),
+                    chapters::MIN_CHAPTER_SECONDS,
+                );
+            }
+            if dry_run {
+                say!(
+                    "\n📑 {} chapters would be exported to {}",
+                    chapters.len(),
+                    export.path.display()
+                );
+            } else {
+                chapters::write_file(&export.path, &chapters::youtube_description(&chapters))?;
+                say!(
+                    "\n📑 {} chapters exported to {}",
+                    chapters.len(),
+                    export.path.display()
+                );
+            }
+            Some(chapters)
+        }
+        None => None,
+    };
+
+    let gpu = (!force_cpu).then_some(&config.gpu);
+    let mut backend =
+        renderer::create(&config.renderer.engine, config, gpu).exit_code(ExitCode::RenderFailed)?;
+    if let Some(token) = cancellation {
+        backend.set_cancellation(token);
+    }
+    let mut progress = progress_reporter(json_progress);
+
+    // Partial renders are for inspecting frames: no audio, no encoding, no report
+    if !backend.in_process() && matches!(mode, RenderMode::Range(_) | RenderMode::At(_)) {
+        anyhow::bail!("--frames and --at are only supported by the native renderer");
+    }
+    if !backend.in_process() && config.renderer.debug.enabled {
+        say!("⚠️  --debug-overlay is only drawn by the native renderer");
+    }
+    match mode {
+        RenderMode::Range(frames) => {
+            PerformanceContext::render_range(
+                &script,
+                &mut loader,
+                &config.renderer,
+                gpu,
+                frames.clone(),
+                &mut progress,
+            )
+            .exit_code(ExitCode::RenderFailed)?;
+            return output::emit(&json!({
+                "script": script_path,
+                "frames": frames,
+                "frame_dir": config.renderer.output_dir,
+            }));
+        }
+        RenderMode::At(seconds) => {
+            let path =
+                PerformanceContext::render_at(&script, &mut loader, &config.renderer, gpu, seconds)
+                    .exit_code(ExitCode::RenderFailed)?;
+            say!("🖼️  Frame saved: {}", path.display());
+            return output::emit(&json!({ "script": script_path, "frame": path }));
+        }
+        RenderMode::Full | RenderMode::Preview => {}
+    }
+
+    let preview = matches!(mode, RenderMode::Preview);
+    let output_file = out.unwrap_or_else(|| {
+        PerformanceContext::default_output_file(
+            &script,
+            &config.renderer.output_dir,
+            &config.encoder,
+            preview,
+        )
+    });
 
-    interstellar_triangulum::context::performance::PerformanceContext::run(
+    if dry_run {
+        let plan = PerformanceContext::plan(
+            &script,
+            backend.as_ref(),
+            &config.renderer,
+            preview,
+            &config.audio,
+            &config.encoder,
+            &output_file,
+            chapters.is_some(),
+            subtitles.as_deref(),
+        )?;
+        say!("\n{}", plan);
+        if let Some(path) = export_report {
+            say!("   Report: would be exported to {}", path);
+        }
+        return output::emit(&plan);
+    }
+    let summary = PerformanceContext::run(
         &script,
         &mut loader,
-        output_dir,
-        use_blender,
-        use_gpu,
-    )?;
+        backend.as_mut(),
+        &config.renderer,
+        preview,
+        &config.audio,
+        &config.encoder,
+        &output_file,
+        chapters.as_deref(),
+        subtitles.as_deref(),
+        &mut progress,
+    )
+    .exit_code(ExitCode::RenderFailed)?;
 
-    println!("\n📊 Asset Statistics:");
-    println!("  {}", loader.stats());
+    if let Some(path) = &export_report {
+        let report = analysis.with_audio(summary.audio_analysis.clone());
+        write_report(Path::new(path), &report)?;
+        say!("\n📄 Report exported to: {}", path);
+    }
+
+    say!("\n📊 Asset Statistics:");
+    say!("  {}", loader.stats());
+
+    output::emit(&RenderDocument {
+        script: script_path,
+        summary,
+        report: export_report.as_deref().map(Path::new),
+        chapters: chapters_path.as_deref(),
+    })
+}
 
+/// Write `report` to `path`, in the format its extension names
+fn write_report(path: &Path, report: &AnalysisReport) -> Result<()> {
+    std::fs::write(path, report.render(ReportFormat::from_path(path))?)?;
     Ok(())
 }
+
+/// `render --json`
+#[derive(Serialize)]
+struct RenderDocument<'a> {
+    script: &'a Path,
+    #[serde(flatten)]
+    summary: RenderSummary,
+    report: Option<&'a Path>,
+    chapters: Option<&'a Path>,
+}
+
+/// Print every GPU adapter wgpu can see, across all backends
+fn list_gpus() -> Result<()> {
+    let adapters = interstellar_triangulum::renderer::GpuContext::available_adapters();
+    if output::mode() == OutputMode::Json {
+        let adapters: Vec<_> = adapters
+            .iter()
+            .map(|adapter| {
+                json!({
+                    "name": adapter.name,
+                    "backend": adapter.backend.to_string(),
+                    "device_type": format!("{:?}", adapter.device_type),
+                    "driver": format!("{} {}", adapter.driver, adapter.driver_info).trim(),
+                })
+            })
+            .collect();
+        return output::emit(&adapters);
+    }
+    if adapters.is_empty() {
+        say!("No GPU adapters found; rendering will use the CPU");
+        return Ok(());
+    }
+    // The list is the output, in every mode
+    for adapter in adapters {
+        let driver = format!("{} {}", adapter.driver, adapter.driver_info);
+        println!(
+            "{} ({}, {:?}) {}",
+            adapter.name,
+            adapter.backend,
+            adapter.device_type,
+            driver.trim()
+        );
+    }
+    Ok(())
+}
+
+/// Report render progress as JSON lines on stdout, whatever `--quiet` and
+/// `--json` say about the other messages, or as a progress bar with frame rate
+/// and ETA (hidden with `--quiet`)
+fn progress_reporter(json: bool) -> impl FnMut(ProgressEvent) {
+    let bar = match output::mode() {
+        OutputMode::Quiet => ProgressBar::hidden(),
+        _ => ProgressBar::new(0),
+    };
+    bar.set_style(
+        ProgressStyle::with_template(
+            "  {bar:40.cyan/blue} {pos}/{len} frames ({per_sec}, ETA {eta}) {msg}",
+        )
+        .expect("valid progress template"),
+    );
+
+    move |event| {
+        if json {
+            if let Ok(line) = serde_json::to_string(&event) {
+                println!("{}", line);
+            }
+            return;
+        }
+
+        match event.kind {
+            ProgressKind::SceneStarted { scene_id, .. } => bar.set_message(scene_id),
+            ProgressKind::FrameCompleted {
+                completed, total, ..
+            } => {
+                if bar.length() != Some(total as u64) {
+                    // First frame: measure speed from here, not from audio mixing
+                    bar.reset();
+                    bar.set_length(total as u64);
+                }
+                bar.set_position(completed as u64);
+            }
+            ProgressKind::ScenePrepared {
+                scene_id,
+                textures,
+                seconds,
+            } if textures > 0 => bar.println(format!(
+                "  🖼️  {}: {} textures uploaded in {:.0}ms",
+                scene_id,
+                textures,
+                seconds * 1000.0
+            )),
+            ProgressKind::ScenePrepared { .. } => {}
+            ProgressKind::RenderFinished { .. } => bar.finish(),
+            ProgressKind::EncodeStarted { .. }
+            | ProgressKind::EncodeProgress { .. }
+            | ProgressKind::EncodeFinished { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Where messages go. People get them on stdout; with `--json` they go to
+//! stderr so stdout holds only the command's JSON document; with `--quiet`
+//! they're dropped. Errors are always printed to stderr.
+//!
+//! Messages are `tracing` info events with the [`SAY_TARGET`] target, printed
+//! as they are. Every other event is a diagnostic, printed to stderr at the
+//! level `-v` asks for.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::{filter_fn, EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::format::{FmtSpan, Writer};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+#[doc(hidden)]
+pub use tracing;
+
+/// Target of the events `say!` sends
+pub const SAY_TARGET: &str = "interstellar_triangulum::say";
+
+/// How the CLI talks, set once from `--json` and `--quiet`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    #[default]
+    Human,
+    /// One JSON document on stdout, messages on stderr
+    Json,
+    /// Errors only
+    Quiet,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_mode(mode: OutputMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn mode() -> OutputMode {
+    match MODE.load(Ordering::Relaxed) {
+        1 => OutputMode::Json,
+        2 => OutputMode::Quiet,
+        _ => OutputMode::Human,
+    }
+}
+
+/// Set the output mode and install the subscriber printing messages for it
+/// and diagnostics: warnings and errors, with `verbosity` 1 also this crate's
+/// debug events and spans with their timings, with 2 everything down to trace.
+/// `RUST_LOG` replaces those levels, except under `--quiet`, which prints
+/// errors only.
+pub fn init(mode: OutputMode, verbosity: u8) -> Result<()> {
+    set_mode(mode);
+
+    let messages = tracing_subscriber::fmt::layer()
+        .event_format(Message)
+        .with_ansi(false)
+        .with_writer(match mode {
+            OutputMode::Json => BoxMakeWriter::new(io::stderr),
+            _ => BoxMakeWriter::new(io::stdout),
+        })
+        .with_filter(filter_fn(move |metadata| {
+            mode != OutputMode::Quiet && metadata.target() == SAY_TARGET
+        }));
+
+    let directives = match (mode, std::env::var("RUST_LOG")) {
+        (OutputMode::Quiet, _) => "error".to_string(),
+        (_, Ok(directives)) if !directives.is_empty() => directives,
+        _ => match verbosity {
+            0 => "warn".to_string(),
+            1 => "warn,interstellar_triangulum=debug".to_string(),
+            _ => "trace".to_string(),
+        },
+    };
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .parse_lossy(directives)
+        .add_directive(format!("{}=off", SAY_TARGET).parse()?);
+    let diagnostics = tracing_subscriber::fmt::layer()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_target(verbosity > 0)
+        .with_span_events(FmtSpan::CLOSE);
+    // Timestamps, and with them the spans' timings, only when asked for detail
+    let diagnostics = if verbosity == 0 {
+        diagnostics.without_time().with_filter(filter).boxed()
+    } else {
+        diagnostics.with_filter(filter).boxed()
+    };
+
+    tracing_subscriber::registry()
+        .with(diagnostics)
+        .with(messages)
+        .try_init()?;
+    Ok(())
+}
+
+/// A message as `say!` wrote it, without level, target or spans
+struct Message;
+
+impl<S, N> FormatEvent<S, N> for Message
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        context: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        context
+            .field_format()
+            .format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Print `document` on stdout as the command's JSON document, with `--json` only
+pub fn emit(document: &impl Serialize) -> Result<()> {
+    if mode() == OutputMode::Json {
+        println!("{}", serde_json::to_string_pretty(document)?);
+    }
+    Ok(())
+}
+
+/// `println!` for informational messages, following the output mode
+#[macro_export]
+macro_rules! say {
+    () => {
+        $crate::output::tracing::info!(target: $crate::output::SAY_TARGET, "")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::tracing::info!(target: $crate::output::SAY_TARGET, $($arg)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_round_trips() {
+        for mode in [OutputMode::Json, OutputMode::Quiet, OutputMode::Human] {
+            set_mode(mode);
+            assert_eq!(super::mode(), mode);
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use crate::script::VideoScript;
-use anyhow::{Context, Result};
+#[cfg(feature = "render")]
+use crate::audio::AudioDecoder;
+use crate::error::{Result, ValidationError, VideoEngineError};
+use crate::script::VideoConfig;
+use crate::script::{Layer, VideoScript};
 use std::path::Path;
+use tracing::warn;
 
 /// Script parser that handles JSON/TOML video scripts
 pub struct ScriptParser;
@@ -8,48 +12,232 @@ pub struct ScriptParser;
 impl ScriptParser {
     /// Parse a JSON script file
     pub fn parse_json(path: &Path) -> Result<VideoScript> {
+        Self::parse_json_with_defaults(path, &VideoConfig::default())
+    }
+
+    /// Parse a JSON script file, taking the resolution and frame rate from
+    /// `defaults` when its metadata leaves them out
+    pub fn parse_json_with_defaults(path: &Path, defaults: &VideoConfig) -> Result<VideoScript> {
+        let _span = tracing::info_span!("parse", path = %path.display()).entered();
         let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read script file: {}", path.display()))?;
+            .map_err(VideoEngineError::io("read script file", path))?;
 
-        let script: VideoScript = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse JSON script: {}", path.display()))?;
+        let script = Self::from_json(&content, path, defaults)?;
+        #[cfg(feature = "render")]
+        Self::validate_waveform_sources(&script, path.parent().unwrap_or(Path::new(".")))?;
 
-        Self::validate_script(&script)?;
+        Ok(script)
+    }
+
+    /// Parse a JSON script held in a string. Waveform audio isn't checked, as
+    /// there is no directory to find it in.
+    pub fn parse_json_str(json: &str) -> Result<VideoScript> {
+        Self::parse_json_str_with_defaults(json, &VideoConfig::default())
+    }
 
+    /// `parse_json_str`, taking the resolution and frame rate from `defaults`
+    /// when the metadata leaves them out
+    pub fn parse_json_str_with_defaults(json: &str, defaults: &VideoConfig) -> Result<VideoScript> {
+        Self::from_json(json, Path::new(""), defaults)
+    }
+
+    /// The valid script `content`, read from `path` (empty for a string)
+    fn from_json(content: &str, path: &Path, defaults: &VideoConfig) -> Result<VideoScript> {
+        let parse_error = |source| VideoEngineError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut value: serde_json::Value = serde_json::from_str(content).map_err(parse_error)?;
+        if let Some(metadata) = value
+            .get_mut("metadata")
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            metadata
+                .entry("resolution")
+                .or_insert_with(|| defaults.default_resolution.clone().into());
+            metadata
+                .entry("fps")
+                .or_insert_with(|| defaults.default_fps.into());
+        }
+        let script: VideoScript = serde_json::from_value(value).map_err(parse_error)?;
+
+        Self::validate_script(&script)?;
         Ok(script)
     }
 
-    /// Validate the script structure
-    fn validate_script(script: &VideoScript) -> Result<()> {
+    /// Check that every waveform layer's audio exists (relative to `base_path`)
+    /// and lasts until the end of its scene
+    #[cfg(feature = "render")]
+    fn validate_waveform_sources(script: &VideoScript, base_path: &Path) -> Result<()> {
+        let mut errors = Vec::new();
+        let mut scene_end = 0.0;
+        for scene in &script.scenes {
+            scene_end += scene.duration;
+            for layer in &scene.layers {
+                let Layer::Waveform { audio_source, .. } = layer else {
+                    continue;
+                };
+
+                let path = base_path.join(audio_source);
+                let frames = AudioDecoder::stream(&path).and_then(|stream| {
+                    let sample_rate = stream.sample_rate();
+                    Ok((stream.frame_count()?, sample_rate))
+                });
+                let (frames, sample_rate) = match frames {
+                    Ok(frames) => frames,
+                    Err(e) => {
+                        errors.push(ValidationError::in_scene(
+                            &scene.id,
+                            format!(
+                                "Waveform audio '{}' in scene '{}' could not be opened: {}",
+                                audio_source.display(),
+                                scene.id,
+                                e
+                            ),
+                        ));
+                        continue;
+                    }
+                };
+                let duration = frames as f32 / sample_rate as f32;
+
+                // Allow one video frame of rounding
+                if duration + 1.0 / (script.metadata.fps as f32) < scene_end {
+                    errors.push(ValidationError::in_scene(
+                        &scene.id,
+                        format!(
+                            "Waveform audio '{}' ({:.2}s) ends before scene '{}' does ({:.2}s)",
+                            audio_source.display(),
+                            duration,
+                            scene.id,
+                            scene_end
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(VideoEngineError::Validation(errors));
+        }
+        Ok(())
+    }
+
+    /// Validate the script structure, reporting every rule it breaks
+    pub(crate) fn validate_script(script: &VideoScript) -> Result<()> {
+        let mut errors = Vec::new();
+
         // Validate metadata
         if script.metadata.title.is_empty() {
-            anyhow::bail!("Script title cannot be empty");
+            errors.push(ValidationError::new("Script title cannot be empty"));
         }
 
         if script.metadata.fps == 0 {
-            anyhow::bail!("FPS must be greater than 0");
+            errors.push(ValidationError::new("FPS must be greater than 0"));
         }
 
         if script.metadata.duration <= 0.0 {
-            anyhow::bail!("Duration must be positive");
+            errors.push(ValidationError::new("Duration must be positive"));
+        }
+
+        if let Some(watermark) = &script.metadata.watermark {
+            if watermark.scale <= 0.0 {
+                errors.push(ValidationError::new("Watermark scale must be positive"));
+            }
+            if !(0.0..=1.0).contains(&watermark.opacity) {
+                errors.push(ValidationError::new(
+                    "Watermark opacity must be between 0 and 1",
+                ));
+            }
         }
 
         // Validate scenes
         if script.scenes.is_empty() {
-            anyhow::bail!("Script must contain at least one scene");
+            errors.push(ValidationError::new(
+                "Script must contain at least one scene",
+            ));
         }
 
         for (idx, scene) in script.scenes.iter().enumerate() {
             if scene.id.is_empty() {
-                anyhow::bail!("Scene {} has empty ID", idx);
+                errors.push(ValidationError::new(format!("Scene {} has empty ID", idx)));
             }
 
             if scene.duration <= 0.0 {
-                anyhow::bail!("Scene '{}' duration must be positive", scene.id);
+                errors.push(ValidationError::in_scene(
+                    &scene.id,
+                    format!("Scene '{}' duration must be positive", scene.id),
+                ));
             }
 
             if scene.layers.is_empty() {
-                anyhow::bail!("Scene '{}' must have at least one layer", scene.id);
+                errors.push(ValidationError::in_scene(
+                    &scene.id,
+                    format!("Scene '{}' must have at least one layer", scene.id),
+                ));
+            }
+
+            for (layer_idx, layer) in scene.layers.iter().enumerate() {
+                let (Layer::Image { transform, .. }
+                | Layer::Video { transform, .. }
+                | Layer::Custom { transform, .. }) = layer
+                else {
+                    continue;
+                };
+                if !(0.0..=1.0).contains(&transform.opacity) {
+                    warn!(
+                        "Layer {} in scene '{}' has opacity {} outside [0, 1]; it will be clamped",
+                        layer_idx, scene.id, transform.opacity
+                    );
+                }
+            }
+        }
+
+        // Validate audio tracks
+        if let Some(audio) = &script.audio {
+            for track in &audio.tracks {
+                let name = track.source.display();
+                if track.fade_in < 0.0 || track.fade_out < 0.0 {
+                    errors.push(ValidationError::new(format!(
+                        "Audio track '{}' fades must not be negative",
+                        name
+                    )));
+                }
+                if track.loop_crossfade_ms < 0.0 {
+                    errors.push(ValidationError::new(format!(
+                        "Audio track '{}' loop_crossfade_ms must not be negative",
+                        name
+                    )));
+                }
+                if !(-1.0..=1.0).contains(&track.pan) {
+                    errors.push(ValidationError::new(format!(
+                        "Audio track '{}' pan must be between -1.0 and 1.0, got {}",
+                        name, track.pan
+                    )));
+                }
+                if track.trim_start < 0.0 {
+                    errors.push(ValidationError::new(format!(
+                        "Audio track '{}' trim_start must not be negative",
+                        name
+                    )));
+                }
+                if let Some(trim_end) = track.trim_end {
+                    if trim_end <= track.trim_start {
+                        errors.push(ValidationError::new(format!(
+                            "Audio track '{}' trim_end must be greater than trim_start",
+                            name
+                        )));
+                    }
+                    if !track.looping
+                        && track.fade_in + track.fade_out > trim_end - track.trim_start
+                    {
+                        errors.push(ValidationError::new(format!(
+                            "Audio track '{}' fades ({:.2}s) exceed the trimmed length ({:.2}s)",
+                            name,
+                            track.fade_in + track.fade_out,
+                            trim_end - track.trim_start
+                        )));
+                    }
+                }
             }
         }
 
@@ -58,15 +246,48 @@ impl ScriptParser {
         let duration_diff = (total_scene_duration - script.metadata.duration).abs();
 
         if duration_diff > 0.1 {
-            eprintln!(
-                "Warning: Total scene duration ({:.2}s) differs from metadata duration ({:.2}s)",
+            warn!(
+                "Total scene duration ({:.2}s) differs from metadata duration ({:.2}s)",
                 total_scene_duration, script.metadata.duration
             );
         }
 
+        if !errors.is_empty() {
+            return Err(VideoEngineError::Validation(errors));
+        }
         Ok(())
     }
 
+    /// Check that every custom layer's `type` is one of `registered`, the
+    /// kinds plugins draw, so a typo like `"txt"` isn't taken for a plugin's
+    /// layer by programs that register none
+    pub fn check_layer_types(script: &VideoScript, registered: &[&str]) -> Result<()> {
+        let errors: Vec<_> = script
+            .scenes
+            .iter()
+            .flat_map(|scene| scene.layers.iter().map(move |layer| (scene, layer)))
+            .filter_map(|(scene, layer)| match layer {
+                Layer::Custom { kind, .. } if !registered.contains(&kind.as_str()) => {
+                    Some(ValidationError::in_scene(
+                        &scene.id,
+                        format!(
+                            "Unknown layer type '{}' in scene '{}' (built in: {})",
+                            kind,
+                            scene.id,
+                            Layer::BUILTIN_KINDS.join(", ")
+                        ),
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(VideoEngineError::Validation(errors))
+        }
+    }
+
     /// Get a summary of the script structure
     pub fn summarize(script: &VideoScript) -> String {
         let mut summary = String::new();
@@ -104,6 +325,27 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_unregistered_layer_types_are_reported() {
+        let script = ScriptParser::parse_json_str(
+            r#"{
+                "metadata": {"title": "Typo", "resolution": "64x64", "fps": 10, "duration": 1.0},
+                "scenes": [{"id": "intro", "duration": 1.0, "layers": [
+                    {"type": "image", "source": "bg.png"},
+                    {"type": "txt", "content": "Hi"}
+                ]}]
+            }"#,
+        )
+        .unwrap();
+
+        let error = ScriptParser::check_layer_types(&script, &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown layer type 'txt' in scene 'intro' (built in: video, image, text, waveform)"
+        );
+        assert!(ScriptParser::check_layer_types(&script, &["txt"]).is_ok());
+    }
+
     #[test]
     fn test_parse_valid_json() {
         let json = r#"
@@ -136,6 +378,31 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_missing_resolution_and_fps_fall_back_to_config() {
+        let json = r#"
+        {
+            "metadata": {"title": "Test Video", "duration": 2.0},
+            "scenes": [{"id": "scene1", "duration": 2.0,
+                        "layers": [{"type": "image", "source": "test.png"}]}]
+        }
+        "#;
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(json.as_bytes()).unwrap();
+
+        let script = ScriptParser::parse_json(file.path()).unwrap();
+        assert_eq!(script.metadata.resolution.dimensions(), (1920, 1080));
+        assert_eq!(script.metadata.fps, 30);
+
+        let defaults = VideoConfig {
+            default_resolution: "1280x720".to_string(),
+            default_fps: 24,
+        };
+        let script = ScriptParser::parse_json_with_defaults(file.path(), &defaults).unwrap();
+        assert_eq!(script.metadata.resolution.dimensions(), (1280, 720));
+        assert_eq!(script.metadata.fps, 24);
+    }
+
     #[test]
     fn test_parse_invalid_json() {
         let json = r#"
@@ -153,8 +420,43 @@ mod tests {
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(json.as_bytes()).unwrap();
 
+        // Every broken rule is reported, not just the first
+        match ScriptParser::parse_json(file.path()) {
+            Err(VideoEngineError::Validation(errors)) => assert!(errors.len() > 1, "{:?}", errors),
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+
+        file.write_all(b"{").unwrap();
         let result = ScriptParser::parse_json(file.path());
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VideoEngineError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_parse_json_str() {
+        let json = r#"{
+            "metadata": {"title": "In memory", "duration": 2.0},
+            "scenes": [{"id": "only", "duration": 2.0, "layers": [
+                {"type": "waveform", "audio_source": "not-there.wav", "color": {"r": 255, "g": 255, "b": 255}}
+            ]}]
+        }"#;
+        // Defaults fill in the metadata, and the audio isn't looked for
+        let script = ScriptParser::parse_json_str(json).unwrap();
+        assert_eq!(script.metadata.resolution.dimensions(), (1920, 1080));
+        assert_eq!(script.metadata.fps, 30);
+
+        let error = ScriptParser::parse_json_str("{").unwrap_err();
+        assert!(matches!(error, VideoEngineError::Parse { .. }));
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to parse JSON script: "),
+            "{}",
+            error
+        );
+        assert!(matches!(
+            ScriptParser::parse_json_str(&json.replace("In memory", "")),
+            Err(VideoEngineError::Validation(_))
+        ));
     }
 
     #[test]
@@ -197,12 +499,12 @@ mod tests {
             "scenes": [{"id": "s1", "duration": 5.0, "layers": []}]
         }"#;
         let script: VideoScript = serde_json::from_str(json).unwrap();
-        let result = ScriptParser::validate_script(&script);
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("must have at least one layer"));
+        let Err(VideoEngineError::Validation(errors)) = ScriptParser::validate_script(&script)
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(errors[0].scene.as_deref(), Some("s1"));
+        assert!(errors[0].message.contains("must have at least one layer"));
     }
 
     #[test]
@@ -218,6 +520,19 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_script_opacity_out_of_range() {
+        // Out-of-range opacity is clamped at render time, so it only warns
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1920x1080", "fps": 30, "duration": 5.0},
+            "scenes": [{"id": "s1", "duration": 5.0, "layers": [
+                {"type": "image", "source": "t.png", "transform": {"opacity": 1.5}}
+            ]}]
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        assert!(ScriptParser::validate_script(&script).is_ok());
+    }
+
     #[test]
     fn test_summarize_with_audio() {
         let json = r#"{
@@ -233,12 +548,95 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_nonexistent_file() {
-        let result = ScriptParser::parse_json(Path::new("/nonexistent/file.json"));
-        assert!(result.is_err());
+    fn test_validate_audio_fades_exceed_trim() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1280x720", "fps": 24, "duration": 3.0},
+            "scenes": [{"id": "s1", "duration": 3.0, "layers": [{"type": "image", "source": "t.png"}]}],
+            "audio": {"tracks": [{"source": "music.mp3", "trim_start": 1.0, "trim_end": 2.0, "fade_in": 0.6, "fade_out": 0.6}]}
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = ScriptParser::validate_script(&script);
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("Failed to read script file"));
+            .contains("exceed the trimmed length"));
+    }
+
+    #[cfg(feature = "render")]
+    fn waveform_script_json(audio_source: &str) -> String {
+        format!(
+            r#"{{
+            "metadata": {{"title": "Test", "resolution": "1280x720", "fps": 24, "duration": 4.0}},
+            "scenes": [
+                {{"id": "intro", "duration": 2.0, "layers": [{{"type": "image", "source": "t.png"}}]}},
+                {{"id": "talk", "duration": 2.0, "layers": [{{
+                    "type": "waveform",
+                    "audio_source": "{}",
+                    "color": {{"r": 255, "g": 255, "b": 255}}
+                }}]}}
+            ]
+        }}"#,
+            audio_source
+        )
+    }
+
+    #[cfg(feature = "render")]
+    fn write_silence(path: &Path, seconds: f32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for _ in 0..(seconds * 8000.0) as usize {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_validate_waveform_sources() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script_path = dir.path().join("script.json");
+        std::fs::write(&script_path, waveform_script_json("voice.wav")).unwrap();
+
+        // Missing audio
+        let err = ScriptParser::parse_json(&script_path).unwrap_err();
+        assert!(err.to_string().contains("could not be opened"));
+
+        // Too short: the waveform's scene ends at 4s
+        write_silence(&dir.path().join("voice.wav"), 3.0);
+        let err = ScriptParser::parse_json(&script_path).unwrap_err();
+        assert!(err.to_string().contains("ends before scene 'talk'"));
+
+        write_silence(&dir.path().join("voice.wav"), 4.0);
+        assert!(ScriptParser::parse_json(&script_path).is_ok());
+    }
+
+    #[test]
+    fn test_validate_audio_pan_range() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1280x720", "fps": 24, "duration": 3.0},
+            "scenes": [{"id": "s1", "duration": 3.0, "layers": [{"type": "image", "source": "t.png"}]}],
+            "audio": {"tracks": [{"source": "whoosh.wav", "pan": -1.5}]}
+        }"#;
+        let mut script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = ScriptParser::validate_script(&script);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("pan must be between"));
+
+        script.audio.as_mut().unwrap().tracks[0].pan = -1.0;
+        assert!(ScriptParser::validate_script(&script).is_ok());
+    }
+
+    #[test]
+    fn test_parse_nonexistent_file() {
+        let error = ScriptParser::parse_json(Path::new("/nonexistent/file.json")).unwrap_err();
+        assert!(matches!(error, VideoEngineError::Io { .. }));
+        assert!(error.to_string().contains("Failed to read script file"));
     }
 }
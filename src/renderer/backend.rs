@@ -0,0 +1,392 @@
+//! Rendering backends behind one interface, so the render pipeline (audio, frames,
+//! encoding) doesn't care which one draws the frames.
+
+use crate::assets::AssetLoader;
+use crate::config::{AppConfig, RendererConfig};
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{
+    BitDepth, BlenderRenderer, CancellationToken, DebugOverlaySettings, FrameFileSink, FrameFormat,
+    FrameSink, GpuOptions, RenderEngine, RenderProgress, RenderState,
+};
+use crate::script::VideoScript;
+use std::path::Path;
+
+/// Where a backend puts the frames it renders
+pub enum FrameOutput<'a> {
+    /// Numbered image files in this directory
+    Directory(&'a Path),
+    /// Handed over one by one, e.g. to a streaming encoder. Only for backends
+    /// that draw frames in process.
+    Sink(&'a mut dyn FrameSink),
+}
+
+/// Image files a render left on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameFiles {
+    /// ffmpeg input pattern, relative to the output directory (e.g. `frame_%06d.png`)
+    pub pattern: String,
+    pub format: FrameFormat,
+}
+
+/// What a render produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOutput {
+    /// Frames in the video
+    pub frames: u32,
+    /// Set when frames were written to files rather than a sink
+    pub files: Option<FrameFiles>,
+    /// Frame files kept from an earlier render rather than drawn again
+    pub kept: u32,
+}
+
+/// How a backend would render a script, worked out without rendering
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackendPlan {
+    /// The files frames are written to when they go to a directory
+    pub files: Option<FrameFiles>,
+    /// What else is particular to the backend, one line each
+    pub details: Vec<String>,
+}
+
+/// A backend that turns a script into frames
+pub trait Renderer {
+    /// Name used in the `renderer.engine` setting
+    fn name(&self) -> &'static str;
+
+    /// Whether frames are drawn in this process, so they can go to a
+    /// `FrameOutput::Sink` and single frames or ranges can be rendered
+    fn in_process(&self) -> bool {
+        false
+    }
+
+    /// Stop rendering with a [`VideoEngineError::Cancelled`] error once `token` is cancelled.
+    /// Backends that can't stop midway ignore it and finish the render.
+    fn set_cancellation(&mut self, _token: CancellationToken) {}
+
+    /// The token given to `set_cancellation`, when the backend heeds it
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    /// Whether frame files an earlier render left are kept and only the
+    /// missing ones drawn, so frames must go to a `FrameOutput::Directory`
+    fn incremental(&self) -> bool {
+        false
+    }
+
+    /// How `script` would be rendered, for `render --dry-run`. Nothing is
+    /// rendered or written.
+    fn plan(&self, _script: &VideoScript) -> BackendPlan {
+        BackendPlan::default()
+    }
+
+    /// Render every frame of `script`, with assets resolved through `assets`
+    fn render(
+        &mut self,
+        script: &VideoScript,
+        assets: &mut AssetLoader,
+        output: FrameOutput<'_>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<RenderOutput>;
+}
+
+/// The backend `renderer.engine` names: `native` or `blender`. The native engine
+/// renders on the GPU adapter `gpu` selects, or on the CPU when `None`.
+pub fn create(
+    engine_name: &str,
+    config: &AppConfig,
+    gpu: Option<&GpuOptions>,
+) -> Result<Box<dyn Renderer>> {
+    match engine_name {
+        "native" => Ok(Box::new(NativeRenderer::new(&config.renderer, gpu))),
+        "blender" => Ok(Box::new(
+            BlenderRenderer::new().with_options(config.blender.clone()),
+        )),
+        other => Err(VideoEngineError::invalid(format!(
+            "Unknown renderer '{}' (expected 'native' or 'blender')",
+            other
+        ))),
+    }
+}
+
+/// The built-in CPU/GPU engine
+pub struct NativeRenderer {
+    gpu: Option<GpuOptions>,
+    samples: u32,
+    bit_depth: BitDepth,
+    texture_budget: u64,
+    frame_format: FrameFormat,
+    seed: u64,
+    debug_overlay: Option<DebugOverlaySettings>,
+    incremental: bool,
+    cancellation: Option<CancellationToken>,
+}
+
+impl NativeRenderer {
+    /// Render on the GPU adapter `gpu` selects, or on the CPU when `None`
+    pub fn new(config: &RendererConfig, gpu: Option<&GpuOptions>) -> Self {
+        Self {
+            gpu: gpu.cloned(),
+            samples: config.samples,
+            bit_depth: config.bit_depth,
+            texture_budget: config.texture_budget_bytes(),
+            frame_format: config.frame_format,
+            seed: config.seed,
+            debug_overlay: config.debug.enabled.then(|| config.debug.clone()),
+            incremental: config.incremental,
+            cancellation: None,
+        }
+    }
+
+    /// An engine for `script` with these settings
+    pub fn engine(&self, script: &VideoScript) -> RenderEngine {
+        self.configure(RenderEngine::with_gpu(script.clone(), self.gpu.as_ref()))
+    }
+
+    /// `engine` with these settings, apart from the GPU it was created with
+    pub fn configure(&self, engine: RenderEngine) -> RenderEngine {
+        let engine = engine
+            .with_samples(self.samples)
+            .with_bit_depth(self.bit_depth)
+            .with_seed(self.seed)
+            .with_texture_budget(self.texture_budget);
+        let engine = match &self.debug_overlay {
+            Some(overlay) => engine.with_debug_overlay(overlay.clone()),
+            None => engine,
+        };
+        match &self.cancellation {
+            Some(token) => engine.with_cancellation(token.clone()),
+            None => engine,
+        }
+    }
+}
+
+impl Renderer for NativeRenderer {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn in_process(&self) -> bool {
+        true
+    }
+
+    fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    fn incremental(&self) -> bool {
+        self.incremental
+    }
+
+    fn plan(&self, _script: &VideoScript) -> BackendPlan {
+        let device = match &self.gpu {
+            None => "Draws on the CPU".to_string(),
+            Some(GpuOptions {
+                adapter_name_substring: Some(name),
+                ..
+            }) => format!(
+                "Draws on the GPU adapter matching '{}', or the CPU without one",
+                name
+            ),
+            Some(_) => "Draws on the GPU, or the CPU without one".to_string(),
+        };
+        BackendPlan {
+            files: Some(FrameFiles {
+                pattern: self.frame_format.pattern(),
+                format: self.frame_format,
+            }),
+            details: vec![
+                device,
+                format!(
+                    "{} sample(s) per pixel, {}-bit channels",
+                    self.samples,
+                    u8::from(self.bit_depth)
+                ),
+            ],
+        }
+    }
+
+    fn render(
+        &mut self,
+        script: &VideoScript,
+        assets: &mut AssetLoader,
+        output: FrameOutput<'_>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<RenderOutput> {
+        say!("🎨 Using Native Engine (CPU/GPU)");
+        self.render_with(self.engine(script), assets, output, progress)
+    }
+}
+
+impl NativeRenderer {
+    /// Render with `engine`, made by `engine` or `configure`, as `render` does
+    pub fn render_with(
+        &self,
+        mut engine: RenderEngine,
+        assets: &mut AssetLoader,
+        output: FrameOutput<'_>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<RenderOutput> {
+        let frames = engine.timeline().total_frames();
+
+        let mut kept = 0;
+        let files = match output {
+            FrameOutput::Sink(sink) => {
+                engine.render(sink, assets, progress)?;
+                None
+            }
+            FrameOutput::Directory(dir) => {
+                let mut sink = FrameFileSink::new(dir, self.frame_format);
+                if self.incremental {
+                    self.render_state(&engine).prepare(dir)?;
+                    sink = sink.keeping_existing();
+                    kept = (0..frames).filter(|&frame| sink.has_frame(frame)).count() as u32;
+                }
+                engine.render(&mut sink, assets, progress)?;
+                Some(FrameFiles {
+                    pattern: self.frame_format.pattern(),
+                    format: self.frame_format,
+                })
+            }
+        };
+
+        if let Some(adapter) = engine.gpu_adapter_info() {
+            say!("  🖥️  Rendered on {} ({})", adapter.name, adapter.backend);
+            say!("  🖼️  Texture cache: {}", engine.texture_stats().summary());
+        }
+        if self.incremental && files.is_some() {
+            say!(
+                "  ♻️  {} frame(s) rendered, {} kept from the last render",
+                frames - kept,
+                kept
+            );
+        }
+
+        Ok(RenderOutput {
+            frames,
+            files,
+            kept,
+        })
+    }
+
+    /// What frames drawn by `engine` with these settings depend on. Those
+    /// drawn on the CPU differ slightly from those drawn on a GPU, and GPUs
+    /// from each other, so the device is part of it.
+    fn render_state(&self, engine: &RenderEngine) -> RenderState {
+        let backend = match engine.gpu_adapter_info() {
+            Some(adapter) => format!("native/gpu/{}", adapter.name),
+            None => "native/cpu".to_string(),
+        };
+        RenderState::new(
+            engine.script(),
+            backend,
+            self.frame_format,
+            self.bit_depth,
+            self.samples,
+            self.seed,
+        )
+        .with_debug_overlay(self.debug_overlay.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_by_engine_name() {
+        let config = AppConfig::default();
+
+        let native = create("native", &config, None).unwrap();
+        assert_eq!(native.name(), "native");
+        assert!(native.in_process());
+
+        let blender = create("blender", &config, None).unwrap();
+        assert_eq!(blender.name(), "blender");
+        assert!(!blender.in_process());
+
+        // Both say where their frame files go
+        let script = crate::templates::ScriptTemplate::generate(
+            crate::templates::TemplateType::Explainer,
+            &crate::templates::TemplateOptions::new(10.0),
+        );
+        assert_eq!(
+            native.plan(&script).files.unwrap().pattern,
+            "frame_%06d.png"
+        );
+        assert_eq!(
+            blender.plan(&script).files.unwrap().pattern,
+            "frame_%04d.png"
+        );
+
+        let error = create("povray", &config, None).err().unwrap();
+        assert!(error.to_string().contains("povray"));
+    }
+
+    /// A one second, 160x90 script drawing its `title` in the vendored test font
+    fn titled_script(title: &str) -> VideoScript {
+        let script = serde_json::json!({
+            "metadata": {"title": title, "resolution": "160x90", "fps": 10, "duration": 1.0},
+            "scenes": [{"id": "title", "duration": 1.0, "layers": [{
+                "type": "text", "content": title, "font": crate::renderer::TEST_FONT_PATH,
+                "font_size": 16.0, "color": {"r": 255, "g": 255, "b": 255},
+                "position": {"x": 20, "y": 15}
+            }]}]
+        });
+        crate::ScriptParser::parse_json_str(&script.to_string()).unwrap()
+    }
+
+    /// Renders `script` incrementally into `dir`, returning the output and the
+    /// frames drawn
+    fn render_incrementally(script: &VideoScript, dir: &Path) -> (RenderOutput, Vec<u32>) {
+        let mut config = AppConfig::default().renderer;
+        config.incremental = true;
+        let mut backend = NativeRenderer::new(&config, None);
+        let mut drawn = Vec::new();
+        let output = backend
+            .render(
+                script,
+                &mut AssetLoader::new("."),
+                FrameOutput::Directory(dir),
+                &mut |event: crate::renderer::ProgressEvent| {
+                    if let crate::renderer::ProgressKind::FrameCompleted { frame, .. } = event.kind
+                    {
+                        drawn.push(frame);
+                    }
+                },
+            )
+            .unwrap();
+        (output, drawn)
+    }
+
+    #[test]
+    fn test_incremental_render_draws_only_missing_frames() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let frame = |n: u32| dir.path().join(FrameFormat::Png.file_name(n));
+        let script = titled_script("Waves");
+
+        let (output, drawn) = render_incrementally(&script, dir.path());
+        assert_eq!((output.frames, output.kept), (10, 0));
+        assert_eq!(drawn.len(), 10);
+
+        // Deleted and empty frames are drawn again, the rest kept as they are
+        std::fs::remove_file(frame(3)).unwrap();
+        std::fs::write(frame(7), "").unwrap();
+        std::fs::write(frame(0), "kept").unwrap();
+        let (output, drawn) = render_incrementally(&script, dir.path());
+        assert_eq!(output.kept, 8);
+        assert_eq!(drawn, [3, 7]);
+        assert_eq!(std::fs::read(frame(0)).unwrap(), b"kept");
+        assert!(image::open(frame(7)).is_ok());
+
+        // Another script invalidates every frame
+        let (output, drawn) = render_incrementally(&titled_script("Other waves"), dir.path());
+        assert_eq!(output.kept, 0);
+        assert_eq!(drawn.len(), 10);
+        assert!(image::open(frame(0)).is_ok());
+    }
+}
@@ -1,36 +1,305 @@
-use crate::script::{Layer, VideoScript};
-use anyhow::{Context, Result};
+use crate::assets::AssetLoader;
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::backend::{BackendPlan, FrameFiles, FrameOutput, RenderOutput, Renderer};
+use crate::renderer::{CancellationToken, FrameFormat, ProgressKind, RenderProgress, Timeline};
+use crate::script::{Effect, Layer, Transform, Transition, VideoScript, Watermark};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use sysinfo::System;
+use tracing::error;
+
+/// ffmpeg input pattern of the frames Blender saves
+const FRAME_PATTERN: &str = "frame_%04d.png";
+
+/// Environment variable that overrides `blender.executable`
+pub const BLENDER_PATH_ENV: &str = "BLENDER_PATH";
+
+/// Lines of a failed job's stderr included in the error
+const STDERR_TAIL_LINES: usize = 20;
+
+/// How often running jobs are checked for stalls
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a render that can be cancelled checks its token
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Memory a Blender job is expected to need, for the default job count
+const JOB_MEMORY_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// CPUs per job for the default job count: Blender renders on several threads
+const CPUS_PER_JOB: usize = 4;
+
+/// Memory left free per running job below which the render is stopped
+const MEMORY_RESERVE_PER_JOB: u64 = 256 * 1024 * 1024;
+
+/// Share of memory in use from which no more Blender processes are started
+/// while another one is running
+const MEMORY_SOFT_LIMIT: f64 = 0.9;
+
+/// How often the memory monitor reads the memory use
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Which Blender to run, and how it renders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlenderOptions {
+    /// Path or name of the Blender executable
+    #[serde(default = "default_executable")]
+    pub executable: PathBuf,
+    /// Oldest version the generated scripts work with, e.g. `4.0`
+    #[serde(default = "default_min_version")]
+    pub min_version: String,
+    /// Minutes a job may go without rendering or saving a frame before it is
+    /// reported as stalled
+    #[serde(default = "default_stall_warning_minutes")]
+    pub stall_warning_minutes: f64,
+    #[serde(default)]
+    pub engine: BlenderEngine,
+    /// Samples per pixel: EEVEE's anti-aliasing samples, or Cycles' path samples
+    #[serde(default = "default_blender_samples")]
+    pub samples: u32,
+    /// Render an alpha channel instead of the world background
+    #[serde(default)]
+    pub use_transparent_background: bool,
+    /// What Cycles renders on. EEVEE always uses the GPU.
+    #[serde(default)]
+    pub device: BlenderDevice,
+    /// Times a failed job is run again for the frames it didn't save
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Keep the frames an earlier failed or interrupted render saved for
+    /// scenes that still need rendering. Set by `render --resume`.
+    #[serde(skip)]
+    pub resume: bool,
+    /// Blender processes rendering at once. By default one per four CPUs, as
+    /// far as the available memory allows.
+    #[serde(default)]
+    pub parallel_jobs: Option<usize>,
+}
+
+/// Blender's render engines, named as in `scene.render.engine`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlenderEngine {
+    /// EEVEE, the fast rasterizer
+    #[default]
+    #[serde(rename = "BLENDER_EEVEE_NEXT", alias = "BLENDER_EEVEE")]
+    Eevee,
+    /// Cycles, the path tracer
+    #[serde(rename = "CYCLES")]
+    Cycles,
+}
+
+/// Where Cycles renders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlenderDevice {
+    #[default]
+    #[serde(rename = "CPU")]
+    Cpu,
+    /// The first GPU backend with devices (OptiX, CUDA, HIP, Metal, oneAPI),
+    /// falling back to the CPU with a warning when there is none
+    #[serde(rename = "GPU")]
+    Gpu,
+}
+
+fn default_executable() -> PathBuf {
+    PathBuf::from("blender")
+}
+
+fn default_min_version() -> String {
+    // Node socket names such as 'Emission Color' are from 4.0 on
+    "4.0".to_string()
+}
+
+fn default_stall_warning_minutes() -> f64 {
+    5.0
+}
+
+fn default_blender_samples() -> u32 {
+    64
+}
+
+fn default_retries() -> u32 {
+    1
+}
+
+impl Default for BlenderOptions {
+    fn default() -> Self {
+        Self {
+            executable: default_executable(),
+            min_version: default_min_version(),
+            stall_warning_minutes: default_stall_warning_minutes(),
+            engine: BlenderEngine::default(),
+            samples: default_blender_samples(),
+            use_transparent_background: false,
+            device: BlenderDevice::default(),
+            retries: default_retries(),
+            resume: false,
+            parallel_jobs: None,
+        }
+    }
+}
+
+impl BlenderOptions {
+    /// `BLENDER_PATH` if set, otherwise `executable`
+    pub fn executable(&self) -> PathBuf {
+        std::env::var_os(BLENDER_PATH_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.executable.clone())
+    }
+}
+
+/// A Blender release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlenderVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl BlenderVersion {
+    /// Parse `4.1` or `4.1.2`
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = match parts.next() {
+            Some(patch) => patch.parse().ok()?,
+            None => 0,
+        };
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// The version in `blender --version` output ("Blender 4.1.2", maybe followed
+    /// by "LTS" and build details)
+    fn from_version_output(output: &str) -> Option<Self> {
+        output.lines().find_map(|line| {
+            let version = line.trim().strip_prefix("Blender ")?;
+            Self::parse(version.split_whitespace().next()?)
+        })
+    }
+}
+
+impl std::fmt::Display for BlenderVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
 
 pub struct BlenderRenderer {
-    script: VideoScript,
-    output_dir: PathBuf,
+    options: BlenderOptions,
     cache_dir: PathBuf,
-    parallel_jobs: usize,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Default for BlenderRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BlenderRenderer {
-    pub fn new(script: VideoScript, output_dir: PathBuf) -> Self {
+    pub fn new() -> Self {
         let cache_dir = PathBuf::from(".cache/blender");
-        let parallel_jobs = std::cmp::min(num_cpus::get(), 2).max(1);
         Self {
-            script,
-            output_dir,
+            options: BlenderOptions::default(),
             cache_dir,
-            parallel_jobs,
+            cancellation: None,
+        }
+    }
+
+    /// Run the Blender these options point to
+    pub fn with_options(mut self, options: BlenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Fail with [`VideoEngineError::Cancelled`] once the render's token is cancelled
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancellation {
+            Some(token) => token.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `blender --version`, failing with install instructions when Blender
+    /// can't be run and when it is older than `min_version`
+    pub fn check_installation(&self) -> Result<BlenderVersion> {
+        let executable = self.options.executable();
+        let output = Command::new(&executable)
+            .arg("--version")
+            .output()
+            .map_err(|e| {
+                VideoEngineError::RendererUnavailable(format!(
+                    "Blender not found at '{}' ({}). {}",
+                    executable.display(),
+                    e,
+                    install_hint()
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(VideoEngineError::external(
+                format!("'{} --version'", executable.display()),
+                format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ));
+        }
+
+        let version = BlenderVersion::from_version_output(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| {
+                VideoEngineError::RendererUnavailable(format!(
+                    "Could not read the version of '{}'",
+                    executable.display()
+                ))
+            })?;
+        let minimum = BlenderVersion::parse(&self.options.min_version).ok_or_else(|| {
+            VideoEngineError::invalid(format!(
+                "Invalid blender.min_version '{}'",
+                self.options.min_version
+            ))
+        })?;
+        if version < minimum {
+            return Err(VideoEngineError::RendererUnavailable(format!(
+                "Blender {} at '{}' is too old; version {} or newer is required. {}",
+                version,
+                executable.display(),
+                minimum,
+                install_hint()
+            )));
         }
+        Ok(version)
+    }
+
+    /// The Python script Blender runs to render frames `start_frame..=end_frame`
+    /// of `script`, unless given other frames on its command line
+    pub fn generate_python_script(
+        &self,
+        script: &VideoScript,
+        start_frame: u32,
+        end_frame: u32,
+    ) -> String {
+        let prelude = self.python_prelude(script, start_frame, end_frame);
+        assemble_python(&prelude, &scene_sections(script))
     }
 
-    /// Generate the Python script for Blender
-    fn generate_python_script(&self, start_frame: u32, end_frame: u32) -> String {
+    /// Python setting up Blender: helper functions, render settings, camera
+    /// and compositor
+    fn python_prelude(&self, script: &VideoScript, start_frame: u32, end_frame: u32) -> String {
         let mut py = String::new();
 
         // Imports and setup
@@ -44,11 +313,15 @@ impl BlenderRenderer {
         // Helper functions
         py.push_str(
             r#"
-def create_image_material(name, image_path):
+def report_error(name, message):
+    # Picked up from stdout by the renderer
+    print(f"INTERSTELLAR_ERROR {name}: {message}", flush=True)
+
+def create_image_material(name, image_path, opacity=1.0):
     try:
         img = bpy.data.images.load(image_path)
     except:
-        print(f"Could not load image: {image_path}")
+        report_error(name, f"could not load image {image_path}")
         return None, 1.0, 1.0
 
     mat = bpy.data.materials.new(name=name)
@@ -65,13 +338,35 @@ def create_image_material(name, image_path):
 
     out = nodes.new('ShaderNodeOutputMaterial')
 
+    # Layer opacity scales the image's own alpha
+    fade = nodes.new('ShaderNodeMath')
+    fade.operation = 'MULTIPLY'
+    fade.inputs[1].default_value = opacity
+
     links.new(tex.outputs['Color'], shader.inputs['Base Color'])
-    links.new(tex.outputs['Alpha'], shader.inputs['Alpha'])
+    links.new(tex.outputs['Alpha'], fade.inputs[0])
+    links.new(fade.outputs['Value'], shader.inputs['Alpha'])
     links.new(shader.outputs['BSDF'], out.inputs['Surface'])
     
     mat.blend_method = 'BLEND'
     return mat, img.size[0], img.size[1]
 
+def create_movie_material(name, movie_path, opacity, frame_start, frame_duration):
+    mat, width, height = create_image_material(name, movie_path, opacity)
+    if not mat:
+        return None, 1.0, 1.0
+    tex = next(node for node in mat.node_tree.nodes if node.type == 'TEX_IMAGE')
+    tex.image.source = 'MOVIE'
+    if width == 0 or height == 0:
+        report_error(name, f"could not decode movie {movie_path}")
+        return None, 1.0, 1.0
+    # The clip starts with its scene, one clip frame per output frame
+    tex.image_user.frame_start = frame_start
+    tex.image_user.frame_duration = frame_duration
+    tex.image_user.frame_offset = 0
+    tex.image_user.use_auto_refresh = True
+    return mat, width, height
+
 def create_text_material(name, color):
     mat = bpy.data.materials.new(name=name)
     mat.use_nodes = True
@@ -148,6 +443,39 @@ def keyframe_visibility(obj, start_frame, end_frame):
     obj.keyframe_insert(data_path="hide_render", frame=end_frame)
     obj.keyframe_insert(data_path="hide_viewport", frame=end_frame)
 
+def keyframe_alpha(mat, keys):
+    # Image materials scale their alpha with a Math node; text sets the shader's
+    tree = mat.node_tree
+    math_nodes = [node for node in tree.nodes if node.type == 'MATH']
+    if math_nodes:
+        node, index = math_nodes[0], 1
+    else:
+        node = next(node for node in tree.nodes if node.type == 'BSDF_PRINCIPLED')
+        index = node.inputs.find('Alpha')
+    for frame, alpha in keys:
+        node.inputs[index].default_value = alpha
+        tree.keyframe_insert(data_path=f'nodes["{node.name}"].inputs[{index}].default_value', frame=frame)
+    # Fade linearly, like the native engine
+    for fcurve in tree.animation_data.action.fcurves:
+        for point in fcurve.keyframe_points:
+            point.interpolation = 'LINEAR'
+
+def use_cycles_gpu():
+    prefs = bpy.context.preferences.addons['cycles'].preferences
+    for device_type in ('OPTIX', 'CUDA', 'HIP', 'METAL', 'ONEAPI'):
+        try:
+            prefs.compute_device_type = device_type
+        except TypeError:
+            continue
+        prefs.get_devices()
+        devices = [device for device in prefs.devices if device.type != 'CPU']
+        if devices:
+            for device in devices:
+                device.use = True
+            return True
+    report_error('Cycles', 'no GPU found, rendering on the CPU')
+    return False
+
 def to_blender_coords(x, y, res_x, res_y):
     # Map 0,0 (top-left) to -W/2, H/2
     # Scale: 100px = 1 unit
@@ -158,14 +486,12 @@ def to_blender_coords(x, y, res_x, res_y):
         );
 
         // Render settings
-        let (width, height) = self.script.metadata.resolution.dimensions();
+        let (width, height) = script.metadata.resolution.dimensions();
         py.push_str("scene = bpy.context.scene\n");
         py.push_str(&format!("scene.render.resolution_x = {}\n", width));
         py.push_str(&format!("scene.render.resolution_y = {}\n", height));
-        py.push_str(&format!(
-            "scene.render.fps = {}\n",
-            self.script.metadata.fps
-        ));
+        py.push_str(&format!("scene.render.fps = {}\n", script.metadata.fps));
+        push_render_settings(&mut py, &self.options);
 
         // Parse args for start/end frame override
         py.push_str("import sys\n");
@@ -201,119 +527,12 @@ def to_blender_coords(x, y, res_x, res_y):
         // Apply Camera Animation
         py.push_str(&format!("animate_camera(cam_obj, {})\n", end_frame));
 
-        // Setup Compositor
-        py.push_str("setup_compositor()\n");
-
-        // Process scenes and layers
-        let mut current_frame = 0;
-        for scene in &self.script.scenes {
-            let scene_duration_frames = (scene.duration * self.script.metadata.fps as f32) as u32;
-            let scene_end_frame = current_frame + scene_duration_frames;
-
-            for (layer_idx, layer) in scene.layers.iter().enumerate() {
-                match layer {
-                    Layer::Image {
-                        source, transform, ..
-                    } => {
-                        let name = format!("Image_{}_{}", scene.id, layer_idx);
-                        py.push_str(&format!("\n# Layer: {}\n", name));
-
-                        // Resolve absolute path
-                        let abs_path = std::fs::canonicalize(source).unwrap_or(source.clone());
-                        let path_str = abs_path.to_string_lossy().replace("\\", "/"); // Fix Windows paths
-
-                        py.push_str(&format!(
-                            "mat, img_w, img_h = create_image_material('Mat_{}', '{}')\n",
-                            name, path_str
-                        ));
-
-                        py.push_str("if mat:\n");
-                        py.push_str("    bpy.ops.mesh.primitive_plane_add(size=1)\n");
-                        py.push_str("    obj = bpy.context.active_object\n");
-                        py.push_str(&format!("    obj.name = '{}'\n", name));
-                        py.push_str("    obj.data.materials.append(mat)\n");
-
-                        // Scale to match image dimensions (100px = 1 unit)
-                        py.push_str("    obj.scale.x = img_w / 100.0\n");
-                        py.push_str("    obj.scale.y = img_h / 100.0\n");
-
-                        // Position
-                        py.push_str(&format!(
-                            "    bx, by = to_blender_coords({}, {}, {}, {})\n",
-                            transform.position.x, transform.position.y, width, height
-                        ));
-                        py.push_str("    obj.location.x = bx\n");
-                        py.push_str("    obj.location.y = by\n");
-
-                        // Apply extra scale
-                        py.push_str(&format!("    obj.scale.x *= {}\n", transform.scale));
-                        py.push_str(&format!("    obj.scale.y *= {}\n", transform.scale));
-
-                        // Visibility Keyframing
-                        py.push_str(&format!(
-                            "    keyframe_visibility(obj, {}, {})\n",
-                            current_frame, scene_end_frame
-                        ));
-                    }
-                    Layer::Text {
-                        content,
-                        font,
-                        font_size,
-                        color,
-                        position,
-                        ..
-                    } => {
-                        let name = format!("Text_{}_{}", scene.id, layer_idx);
-                        py.push_str(&format!("\n# Layer: {}\n", name));
-
-                        py.push_str("bpy.ops.object.text_add()\n");
-                        py.push_str("obj = bpy.context.active_object\n");
-                        py.push_str(&format!("obj.name = '{}'\n", name));
-                        py.push_str(&format!("obj.data.body = '{}'\n", content));
-
-                        // Font
-                        let abs_font_path = std::fs::canonicalize(font).unwrap_or(font.clone());
-                        let font_path_str = abs_font_path.to_string_lossy().replace("\\", "/");
-                        py.push_str(&format!("try:\n    fnt = bpy.data.fonts.load('{}')\n    obj.data.font = fnt\nexcept:\n    pass\n", font_path_str));
-
-                        // Size (approximate mapping)
-                        py.push_str(&format!("obj.data.size = {} / 100.0\n", font_size));
-
-                        // Material (Color)
-                        py.push_str(&format!(
-                            "mat = create_text_material('Mat_{}', ({}, {}, {}, {}))\n",
-                            name,
-                            color.r as f32 / 255.0,
-                            color.g as f32 / 255.0,
-                            color.b as f32 / 255.0,
-                            color.a as f32 / 255.0
-                        ));
-                        py.push_str("obj.data.materials.append(mat)\n");
-
-                        // Position
-                        // Text origin is bottom-left usually, might need adjustment.
-                        // For now, using same mapping.
-                        py.push_str(&format!(
-                            "bx, by = to_blender_coords({}, {}, {}, {})\n",
-                            position.x, position.y, width, height
-                        ));
-                        py.push_str("obj.location.x = bx\n");
-                        py.push_str("obj.location.y = by\n");
-
-                        // Visibility Keyframing
-                        py.push_str(&format!(
-                            "keyframe_visibility(obj, {}, {})\n",
-                            current_frame, scene_end_frame
-                        ));
-                    }
-                    _ => {}
-                }
-            }
-            current_frame += scene_duration_frames;
+        if let Some(watermark) = &script.metadata.watermark {
+            push_watermark(&mut py, watermark, (width, height));
         }
 
-        py.push_str("\n# Render animation\n");
-        py.push_str("bpy.ops.render.render(animation=True)\n");
+        // Setup Compositor
+        py.push_str("setup_compositor()\n");
 
         py
     }
@@ -325,173 +544,2477 @@ def to_blender_coords(x, y, res_x, res_y):
         format!("{:x}", hasher.finalize())
     }
 
-    /// Render the video using Blender
-    pub fn render(&self) -> Result<()> {
-        fs::create_dir_all(&self.cache_dir)?;
-        fs::create_dir_all(&self.output_dir)?;
-
-        let total_frames = (self.script.metadata.duration * self.script.metadata.fps as f32) as u32;
-        let python_script = self.generate_python_script(0, total_frames);
-        let script_hash = self.calculate_hash(&python_script);
+    /// Render the video using Blender, saving frames into `output_dir`. Scenes
+    /// whose frames an earlier render left in the cache aren't rendered again.
+    fn render_to(
+        &self,
+        script: &VideoScript,
+        output_dir: &Path,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<()> {
+        self.check_cancelled()?;
+        let scenes_dir = self.cache_dir.join(SCENES_DIR);
+        fs::create_dir_all(&scenes_dir).map_err(VideoEngineError::io("create", &scenes_dir))?;
+        fs::create_dir_all(output_dir).map_err(VideoEngineError::io("create", output_dir))?;
+
+        let total_frames = Timeline::from_script(script).total_frames();
+        let prelude = self.python_prelude(script, 0, total_frames);
+        let sections = scene_sections(script);
+        let hashes = scene_hashes(&prelude, &sections);
+        let manifest_path = self.cache_dir.join(MANIFEST_FILE);
+
+        let stale = stale_scenes(
+            &sections,
+            &hashes,
+            &Manifest::load(&manifest_path),
+            &scenes_dir,
+        );
+        if stale.is_empty() {
+            say!("✨ Cache hit! Skipping Blender rendering.");
+            progress.emit(ProgressKind::RenderFinished { frames: 0 });
+        } else {
+            if stale.len() < sections.len() {
+                say!(
+                    "✨ Reusing {} of {} scenes from the cache",
+                    sections.len() - stale.len(),
+                    sections.len()
+                );
+            }
 
-        let cache_file = self.cache_dir.join(format!("{}.py", script_hash));
-        let hash_file = self.cache_dir.join("last_render.sha256");
+            let python_script = assemble_python(&prelude, &sections);
+            let script_file = self
+                .cache_dir
+                .join(format!("{}.py", self.calculate_hash(&python_script)));
+            fs::write(&script_file, &python_script)
+                .map_err(VideoEngineError::io("write", &script_file))?;
+
+            let work = self.schedule(&sections, &hashes, &stale, &scenes_dir)?;
+            if work.is_empty() {
+                say!("✨ Every frame was saved by the interrupted render");
+                progress.emit(ProgressKind::RenderFinished { frames: 0 });
+            } else {
+                self.run_jobs(&script_file, &work, progress)?;
+            }
+        }
 
-        // Check cache
-        if cache_file.exists() && hash_file.exists() {
-            let last_hash = fs::read_to_string(&hash_file).unwrap_or_default();
-            if last_hash.trim() == script_hash {
-                println!("✨ Cache hit! Skipping Blender rendering.");
-                return Ok(());
+        // The encoder wants one contiguous sequence
+        for (section, hash) in sections.iter().zip(&hashes) {
+            let scene_dir = scenes_dir.join(hash);
+            for frame in section.frames.clone() {
+                link_frame(
+                    &scene_dir.join(frame_file(frame)),
+                    &output_dir.join(frame_file(frame)),
+                )?;
             }
         }
+        remove_frames_from(output_dir, total_frames)?;
 
-        println!("🎨 Starting Blender rendering...");
+        let manifest = Manifest::new(&sections, &hashes);
+        manifest.save(&manifest_path)?;
+        manifest.prune(&scenes_dir);
+        Ok(())
+    }
+
+    /// The frames of the `stale` scenes to render, with the directory each
+    /// goes in. When resuming, frames already saved there are skipped; otherwise
+    /// the directories are emptied first.
+    fn schedule(
+        &self,
+        sections: &[SceneSection],
+        hashes: &[String],
+        stale: &[usize],
+        scenes_dir: &Path,
+    ) -> Result<Vec<(Range<u32>, PathBuf)>> {
+        let (work, kept) = self.pending_work(sections, hashes, stale, scenes_dir);
+        if !self.options.resume {
+            for (_, dir) in &work {
+                if dir.exists() {
+                    fs::remove_dir_all(dir).map_err(VideoEngineError::io("remove", dir))?;
+                }
+            }
+        }
+        if kept > 0 {
+            say!(
+                "♻️  Resuming: keeping {} frames saved by an earlier render",
+                kept
+            );
+        }
+        Ok(work)
+    }
 
-        // Write script to file
-        fs::write(&cache_file, &python_script)?;
+    /// What `schedule` would return, without emptying any directory, and the
+    /// number of frames resuming keeps
+    fn pending_work(
+        &self,
+        sections: &[SceneSection],
+        hashes: &[String],
+        stale: &[usize],
+        scenes_dir: &Path,
+    ) -> (Vec<(Range<u32>, PathBuf)>, usize) {
+        let mut work = Vec::new();
+        let mut kept = 0;
+        for &i in stale {
+            let dir = scenes_dir.join(&hashes[i]);
+            let frames = sections[i].frames.clone();
+            if self.options.resume {
+                let missing = missing_frames(frames.clone(), &dir);
+                kept += frames.len() - missing.iter().map(|range| range.len()).sum::<usize>();
+                work.extend(missing.into_iter().map(|range| (range, dir.clone())));
+            } else {
+                work.push((frames, dir));
+            }
+        }
+        (work, kept)
+    }
 
-        println!(
-            "🚀 Launching {} parallel Blender jobs...",
-            self.parallel_jobs
+    /// Render each `(frames, directory)` of `work` with the Python script in
+    /// `script_file`, spread over the parallel jobs. A failed job is run again,
+    /// up to `retries` times, for the frames it didn't save.
+    fn run_jobs(
+        &self,
+        script_file: &Path,
+        work: &[(Range<u32>, PathBuf)],
+        progress: &mut dyn RenderProgress,
+    ) -> Result<()> {
+        let version = self.check_installation()?;
+        say!(
+            "🎨 Starting Blender {} rendering ({})...",
+            version,
+            self.options.executable().display()
         );
 
-        let frames_per_job = (total_frames as f32 / self.parallel_jobs as f32).ceil() as u32;
+        let parallel_jobs = self
+            .options
+            .parallel_jobs
+            .unwrap_or_else(default_parallel_jobs)
+            .max(1);
+        let jobs = split_jobs(work, parallel_jobs);
+        say!("🚀 Launching {} parallel Blender jobs...", jobs.len());
+
+        let total_frames = work.iter().map(|(frames, _)| frames.len() as u32).sum();
         let mut handles = vec![];
-        let completed_frames = Arc::new(Mutex::new(0));
+        // Jobs report what Blender prints; progress is emitted from this thread
+        let (events_tx, events_rx) = mpsc::channel::<JobEvent>();
         let start_time = Instant::now();
 
-        // Safety Vault: Memory Monitor
-        let _monitor_handle = thread::spawn(|| {
-            let mut sys = System::new_all();
-            loop {
-                sys.refresh_memory();
-                let used_memory = sys.used_memory();
-                let total_memory = sys.total_memory();
-                let usage_percent = (used_memory as f64 / total_memory as f64) * 100.0;
-
-                if usage_percent > 99.0 {
-                    eprintln!(
-                        "🚨 CRITICAL: Memory usage at {:.1}%! Killing process to prevent crash.",
-                        usage_percent
-                    );
-                    std::process::exit(1);
+        // Safety Vault: Memory Monitor, running until the sender is dropped
+        let children = Arc::new(Children::default());
+        let (stop_monitor, monitor_stopped) = mpsc::channel::<()>();
+        let monitor = MemoryMonitor {
+            reader: SystemMemory(System::new()),
+            jobs: jobs.len(),
+            children: Arc::clone(&children),
+        };
+        let monitor_handle = thread::spawn(move || monitor.run(monitor_stopped));
+
+        let job_frames: Vec<String> = jobs
+            .iter()
+            .map(|pieces| {
+                let ranges: Vec<String> = pieces
+                    .iter()
+                    .map(|(frames, _)| format!("{}..{}", frames.start, frames.end))
+                    .collect();
+                ranges.join(", ")
+            })
+            .collect();
+        let mut stalls = StallMonitor::new(
+            jobs.len(),
+            Duration::from_secs_f64(self.options.stall_warning_minutes.max(0.0) * 60.0),
+            start_time,
+        );
+
+        for (i, pieces) in jobs.into_iter().enumerate() {
+            let script_file = script_file.to_path_buf();
+            let events = events_tx.clone();
+            let executable = self.options.executable();
+            let retries = self.options.retries;
+            let children = Arc::clone(&children);
+
+            let handle = thread::spawn(move || -> Result<()> {
+                let started = Instant::now();
+                let mut frames_rendered = 0;
+                for (frames, output_dir) in pieces {
+                    frames_rendered += frames.len() as u32;
+                    let mut remaining = vec![frames.clone()];
+                    for attempt in 0..=retries {
+                        let result = remaining.iter().try_for_each(|range| {
+                            children.wait_to_launch()?;
+                            run_job(
+                                &executable,
+                                &script_file,
+                                range.clone(),
+                                &output_dir,
+                                i,
+                                &events,
+                                &children,
+                            )
+                        });
+                        let Err(error) = result else {
+                            break;
+                        };
+                        if attempt == retries || children.stop_reason().is_some() {
+                            return Err(error);
+                        }
+                        remaining = missing_frames(frames.clone(), &output_dir);
+                        say!(
+                            "  ⚠️  Blender job for frames {}..{} failed; retrying its {} unsaved frames ({} of {})",
+                            frames.start,
+                            frames.end,
+                            remaining.iter().map(|range| range.len()).sum::<usize>(),
+                            attempt + 1,
+                            retries
+                        );
+                    }
+                }
+                let _ = events.send(JobEvent::Finished {
+                    job: i,
+                    frames: frames_rendered,
+                    elapsed: started.elapsed(),
+                });
+                Ok(())
+            });
+            handles.push(handle);
+        }
+
+        // Receives until every job has dropped its sender
+        drop(events_tx);
+        let mut completed = 0;
+        let mut timings = vec![None; job_frames.len()];
+        let poll_interval = match self.cancellation {
+            Some(_) => CANCEL_CHECK_INTERVAL,
+            None => STALL_CHECK_INTERVAL,
+        };
+        loop {
+            // Killed processes end their jobs, which ends this loop
+            if self.check_cancelled().is_err() && children.stop_reason().is_none() {
+                children.stop("Render cancelled".to_string());
+            }
+            match events_rx.recv_timeout(poll_interval) {
+                Ok(JobEvent::Rendering { job, frame }) => {
+                    stalls.progress(job, Some(frame), Instant::now())
+                }
+                Ok(JobEvent::Saved { job, frame }) => {
+                    stalls.progress(job, frame, Instant::now());
+                    completed += 1;
+                    progress.emit(ProgressKind::FrameCompleted {
+                        frame: frame.unwrap_or(completed - 1),
+                        completed,
+                        total: total_frames,
+                    });
                 }
+                Ok(JobEvent::Finished {
+                    job,
+                    frames,
+                    elapsed,
+                }) => {
+                    stalls.finish(job);
+                    timings[job] = Some((frames, elapsed));
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
 
-                thread::sleep(Duration::from_secs(1));
+            for (job, frame) in stalls.newly_stalled(Instant::now()) {
+                say!(
+                    "  ⚠️  Blender job {} (frames {}) has made no progress for {:.0} minutes{}",
+                    job + 1,
+                    job_frames[job],
+                    self.options.stall_warning_minutes,
+                    frame
+                        .map(|frame| format!(", last at frame {}", frame))
+                        .unwrap_or_default()
+                );
             }
-        });
+        }
 
-        for i in 0..self.parallel_jobs {
-            let start = i as u32 * frames_per_job;
-            let end = ((i + 1) as u32 * frames_per_job).min(total_frames);
+        // Wait for all jobs
+        let failures: Vec<String> = handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap().err())
+            .map(|e| e.to_string())
+            .collect();
+        drop(stop_monitor);
+        let _ = monitor_handle.join();
+        self.check_cancelled()?;
+        // Jobs the monitor stopped only report being killed
+        if let Some(reason) = children.stop_reason() {
+            return Err(VideoEngineError::external(
+                "Blender rendering",
+                format!(
+                    "{}\n\nRun again with --resume to keep the frames rendered so far",
+                    reason
+                ),
+            ));
+        }
+        if !failures.is_empty() {
+            return Err(VideoEngineError::external(
+                "Blender rendering",
+                format!(
+                    "{}\n\nRun again with --resume to keep the frames rendered so far",
+                    failures.join("\n\n")
+                ),
+            ));
+        }
 
-            if start >= end {
-                break;
+        let duration = start_time.elapsed();
+        say!(
+            "✅ Blender rendering complete in {:.2}s",
+            duration.as_secs_f32()
+        );
+        for (job, timing) in timings.iter().enumerate() {
+            if let Some((frames, elapsed)) = timing {
+                say!(
+                    "  ⏱️  Job {} (frames {}): {} frames in {:.2}s ({:.2} fps)",
+                    job + 1,
+                    job_frames[job],
+                    frames,
+                    elapsed.as_secs_f32(),
+                    *frames as f32 / elapsed.as_secs_f32().max(f32::EPSILON)
+                );
             }
+        }
+        progress.emit(ProgressKind::RenderFinished { frames: completed });
 
-            let cache_file = cache_file.clone();
-            let output_dir = self.output_dir.clone();
-            let completed = Arc::clone(&completed_frames);
+        Ok(())
+    }
+}
 
-            let handle = thread::spawn(move || -> Result<()> {
-                let mut child = Command::new("blender")
-                    .arg("-b")
-                    .arg("-P")
-                    .arg(&cache_file)
-                    .arg("--")
-                    .arg("--start")
-                    .arg(start.to_string())
-                    .arg("--end")
-                    .arg(end.to_string())
-                    .arg("--output")
-                    .arg(output_dir.join("frame_").to_str().unwrap())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .spawn()
-                    .context("Failed to spawn Blender process")?;
-
-                // Monitor progress
-                if let Some(stdout) = child.stdout.take() {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines().map_while(Result::ok) {
-                        if line.contains("Saved:") {
-                            let mut count = completed.lock().unwrap();
-                            *count += 1;
-                        }
+impl Renderer for BlenderRenderer {
+    fn name(&self) -> &'static str {
+        "blender"
+    }
+
+    fn plan(&self, script: &VideoScript) -> BackendPlan {
+        let scenes_dir = self.cache_dir.join(SCENES_DIR);
+        let total_frames = Timeline::from_script(script).total_frames();
+        let prelude = self.python_prelude(script, 0, total_frames);
+        let sections = scene_sections(script);
+        let hashes = scene_hashes(&prelude, &sections);
+        let stale = stale_scenes(
+            &sections,
+            &hashes,
+            &Manifest::load(&self.cache_dir.join(MANIFEST_FILE)),
+            &scenes_dir,
+        );
+
+        let mut details = vec![format!(
+            "Runs {} with {:?} at {} samples",
+            self.options.executable().display(),
+            self.options.engine,
+            self.options.samples
+        )];
+        if stale.len() < sections.len() {
+            details.push(format!(
+                "Reuses {} of {} scenes from {}",
+                sections.len() - stale.len(),
+                sections.len(),
+                self.cache_dir.display()
+            ));
+        }
+        let (work, kept) = self.pending_work(&sections, &hashes, &stale, &scenes_dir);
+        if kept > 0 {
+            details.push(format!("Resumes, keeping {} saved frames", kept));
+        }
+        let parallel_jobs = self
+            .options
+            .parallel_jobs
+            .unwrap_or_else(default_parallel_jobs)
+            .max(1);
+        let jobs = split_jobs(&work, parallel_jobs);
+        if jobs.is_empty() {
+            details.push("Nothing to render, every frame is cached".to_string());
+        }
+        for (i, pieces) in jobs.iter().enumerate() {
+            let ranges: Vec<String> = pieces
+                .iter()
+                .map(|(frames, _)| format!("{}..{}", frames.start, frames.end))
+                .collect();
+            let frames: usize = pieces.iter().map(|(frames, _)| frames.len()).sum();
+            details.push(format!(
+                "Job {}: frames {} ({} frames)",
+                i + 1,
+                ranges.join(", "),
+                frames
+            ));
+        }
+
+        BackendPlan {
+            files: Some(FrameFiles {
+                pattern: FRAME_PATTERN.to_string(),
+                format: FrameFormat::Png,
+            }),
+            details,
+        }
+    }
+
+    fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    fn render(
+        &mut self,
+        script: &VideoScript,
+        _assets: &mut AssetLoader,
+        output: FrameOutput<'_>,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<RenderOutput> {
+        let FrameOutput::Directory(output_dir) = output else {
+            return Err(VideoEngineError::invalid(
+                "Blender renders frames to files and can't stream them",
+            ));
+        };
+
+        say!("🎨 Using Blender Backend");
+        self.render_to(script, output_dir, progress)?;
+        Ok(RenderOutput {
+            frames: Timeline::from_script(script).total_frames(),
+            files: Some(FrameFiles {
+                pattern: FRAME_PATTERN.to_string(),
+                format: FrameFormat::Png,
+            }),
+            kept: 0,
+        })
+    }
+}
+
+/// What a job saw Blender do, sent to the thread reporting progress
+#[derive(Debug, Clone, PartialEq)]
+enum JobEvent {
+    Rendering {
+        job: usize,
+        frame: u32,
+    },
+    Saved {
+        job: usize,
+        frame: Option<u32>,
+    },
+    /// All of the job's frames are saved
+    Finished {
+        job: usize,
+        frames: u32,
+        elapsed: Duration,
+    },
+}
+
+/// Run Blender on `script_file` to render `frames` into `output_dir`, sending
+/// what it does to `events` as job number `job`. The process is registered in
+/// `children` while it runs.
+fn run_job(
+    executable: &Path,
+    script_file: &Path,
+    frames: Range<u32>,
+    output_dir: &Path,
+    job: usize,
+    events: &mpsc::Sender<JobEvent>,
+    children: &Children,
+) -> Result<()> {
+    fs::create_dir_all(output_dir).map_err(VideoEngineError::io("create", output_dir))?;
+    let mut child = Command::new(executable)
+        .arg("-b")
+        .arg("-P")
+        .arg(script_file)
+        .arg("--")
+        .arg("--start")
+        .arg(frames.start.to_string())
+        // Blender's end frame is inclusive
+        .arg("--end")
+        .arg((frames.end - 1).to_string())
+        .arg("--output")
+        .arg(output_dir.join("frame_").to_str().unwrap())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            VideoEngineError::RendererUnavailable(format!(
+                "Failed to spawn Blender ({}): {}",
+                executable.display(),
+                e
+            ))
+        })?;
+
+    // Drained alongside stdout so Blender never blocks on a full pipe
+    let stderr = child.stderr.take().map(|pipe| {
+        thread::spawn(move || {
+            let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+            for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+            Vec::from(tail).join("\n")
+        })
+    });
+    let stdout = child.stdout.take();
+    children.add(job, child)?;
+
+    // Monitor progress
+    let mut saved = Vec::new();
+    if let Some(stdout) = stdout {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines().map_while(Result::ok) {
+            let event = match parse_blender_line(&line) {
+                Some(BlenderLine::Rendering(frame)) => JobEvent::Rendering { job, frame },
+                Some(BlenderLine::Saved(frame)) => {
+                    saved.extend(frame);
+                    JobEvent::Saved { job, frame }
+                }
+                // Every job loads every asset; one report is enough
+                Some(BlenderLine::Error(error)) => {
+                    if job == 0 {
+                        say!("  ⚠️  Blender: {}", error);
                     }
+                    continue;
                 }
+                None => continue,
+            };
+            let _ = events.send(event);
+        }
+    }
+
+    // Blender closed its output: it is exiting, or was killed
+    let mut child = children
+        .take(job)
+        .ok_or_else(|| VideoEngineError::external("Blender", "process went missing"))?;
+    let status = child
+        .wait()
+        .map_err(VideoEngineError::io("wait for", executable))?;
+    let stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    if !status.success() {
+        // A frame being written when Blender died may be cut short
+        for frame in frames.clone().filter(|frame| !saved.contains(frame)) {
+            let _ = fs::remove_file(output_dir.join(frame_file(frame)));
+        }
+        return Err(VideoEngineError::external(
+            format!("Blender job for frames {}..{}", frames.start, frames.end),
+            format!("{}\n{}", status, stderr),
+        ));
+    }
+    Ok(())
+}
+
+/// Python for one scene's layers
+struct SceneSection {
+    id: String,
+    /// The scene's own frames
+    frames: Range<u32>,
+    /// Frames its layers show in, wider than `frames` with dissolves
+    visible: Range<u32>,
+    python: String,
+}
 
-                let status = child.wait()?;
-                if !status.success() {
-                    anyhow::bail!("Blender job failed");
+/// Python for the layers of every scene of `script`
+fn scene_sections(script: &VideoScript) -> Vec<SceneSection> {
+    let (width, height) = script.metadata.resolution.dimensions();
+    let timeline = Timeline::from_script(script);
+    let fps = script.metadata.fps;
+    let mut sections = Vec::with_capacity(script.scenes.len());
+    for (scene_idx, (scene, frames)) in script
+        .scenes
+        .iter()
+        .zip(timeline.scene_ranges())
+        .enumerate()
+    {
+        let mut py = String::new();
+        if let Some(Transition::Wipe { .. }) = scene.transition {
+            say!(
+                "  ⚠️  Blender can't render the wipe after scene '{}'; it cuts instead",
+                scene.id
+            );
+        }
+        let timing = SceneTiming::new(script, &timeline, scene_idx, &frames);
+
+        for (layer_idx, layer) in scene.layers.iter().enumerate() {
+            match layer {
+                Layer::Image {
+                    source,
+                    transform,
+                    effects,
+                    ..
+                } => {
+                    let name = format!("Image_{}_{}", scene.id, layer_idx);
+                    py.push_str(&format!("\n# Layer: {}\n", python_str(&name)));
+                    py.push_str(&format!(
+                        "mat, img_w, img_h = create_image_material({}, {}, {})\n",
+                        python_str(&format!("Mat_{}", name)),
+                        python_path(source),
+                        transform.opacity.clamp(0.0, 1.0)
+                    ));
+                    let ramps = timing.layer_ramps(&name, effects, fps);
+                    push_plane(&mut py, &name, transform, (width, height), &timing.visible);
+                    push_alpha_keys(&mut py, "    ", &ramps, transform.opacity);
                 }
-                Ok(())
-            });
-            handles.push(handle);
+                Layer::Video {
+                    source,
+                    transform,
+                    effects,
+                } => {
+                    let name = format!("Video_{}_{}", scene.id, layer_idx);
+                    py.push_str(&format!("\n# Layer: {}\n", python_str(&name)));
+                    py.push_str(&format!(
+                        "mat, img_w, img_h = create_movie_material({}, {}, {}, {}, {})\n",
+                        python_str(&format!("Mat_{}", name)),
+                        python_path(source),
+                        transform.opacity.clamp(0.0, 1.0),
+                        frames.start,
+                        frames.len()
+                    ));
+                    let ramps = timing.layer_ramps(&name, effects, fps);
+                    push_plane(&mut py, &name, transform, (width, height), &timing.visible);
+                    push_alpha_keys(&mut py, "    ", &ramps, transform.opacity);
+                }
+                Layer::Text {
+                    content,
+                    font,
+                    font_size,
+                    color,
+                    position,
+                    effects,
+                } => {
+                    let name = format!("Text_{}_{}", scene.id, layer_idx);
+                    py.push_str(&format!("\n# Layer: {}\n", python_str(&name)));
+
+                    py.push_str("bpy.ops.object.text_add()\n");
+                    py.push_str("obj = bpy.context.active_object\n");
+                    py.push_str(&format!("obj.name = {}\n", python_str(&name)));
+                    py.push_str(&format!("obj.data.body = {}\n", python_str(content)));
+
+                    // Font
+                    py.push_str(&format!("try:\n    fnt = bpy.data.fonts.load({})\n    obj.data.font = fnt\nexcept:\n    pass\n", python_path(font)));
+
+                    // Size (approximate mapping)
+                    py.push_str(&format!("obj.data.size = {} / 100.0\n", font_size));
+
+                    // Material (Color)
+                    py.push_str(&format!(
+                        "mat = create_text_material({}, ({}, {}, {}, {}))\n",
+                        python_str(&format!("Mat_{}", name)),
+                        color.r as f32 / 255.0,
+                        color.g as f32 / 255.0,
+                        color.b as f32 / 255.0,
+                        color.a as f32 / 255.0
+                    ));
+                    py.push_str("obj.data.materials.append(mat)\n");
+
+                    // Position
+                    // Text origin is bottom-left usually, might need adjustment.
+                    // For now, using same mapping.
+                    py.push_str(&format!(
+                        "bx, by = to_blender_coords({}, {}, {}, {})\n",
+                        position.x, position.y, width, height
+                    ));
+                    py.push_str("obj.location.x = bx\n");
+                    py.push_str("obj.location.y = by\n");
+
+                    // Visibility Keyframing
+                    py.push_str(&format!(
+                        "keyframe_visibility(obj, {}, {})\n",
+                        timing.visible.start, timing.visible.end
+                    ));
+                    let ramps = timing.layer_ramps(&name, effects, fps);
+                    push_alpha_keys(&mut py, "", &ramps, 1.0);
+                }
+                Layer::Waveform { .. } => {
+                    say!(
+                            "  ⚠️  Blender can't render the waveform layer {} of scene '{}'; it is left out",
+                            layer_idx, scene.id
+                        );
+                }
+                Layer::Custom { kind, .. } => {
+                    say!(
+                        "  ⚠️  Blender can't render the {} layer {} of scene '{}'; it is left out",
+                        kind,
+                        layer_idx,
+                        scene.id
+                    );
+                }
+            }
         }
+        sections.push(SceneSection {
+            id: scene.id.clone(),
+            frames: frames.clone(),
+            visible: timing.visible.clone(),
+            python: py,
+        });
+    }
+    sections
+}
 
-        // Wait for all jobs
-        let mut success = true;
-        for handle in handles {
-            if let Err(e) = handle.join().unwrap() {
-                println!("❌ Job failed: {}", e);
-                success = false;
+/// The whole script: `prelude`, every scene's layers, and the render call
+fn assemble_python(prelude: &str, sections: &[SceneSection]) -> String {
+    let mut py = prelude.to_string();
+    for section in sections {
+        py.push_str(&section.python);
+    }
+    py.push_str("\n# Render animation\n");
+    py.push_str("bpy.ops.render.render(animation=True)\n");
+    py
+}
+
+/// A cache key for each scene's frames: the prelude, the frame range, and the
+/// Python of every scene shown during it (neighbors dissolving in or out too)
+fn scene_hashes(prelude: &str, sections: &[SceneSection]) -> Vec<String> {
+    sections
+        .iter()
+        .map(|section| {
+            let mut hasher = Sha256::new();
+            hasher.update(prelude);
+            hasher.update(format!("{:?}", section.frames));
+            for other in sections.iter().filter(|other| {
+                other.visible.start < section.frames.end && section.frames.start < other.visible.end
+            }) {
+                hasher.update(&other.python);
+            }
+            format!("{:x}", hasher.finalize())
+        })
+        .collect()
+}
+
+/// Cache manifest, in the cache directory
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory of the cache holding one directory of frames per scene hash
+const SCENES_DIR: &str = "scenes";
+
+/// The scenes whose frames are in the cache, by scene id
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    scenes: BTreeMap<String, CachedScene>,
+}
+
+/// Frames of a scene, kept in `scenes/<hash>` under their video frame numbers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedScene {
+    hash: String,
+    start_frame: u32,
+    end_frame: u32,
+}
+
+impl Manifest {
+    fn new(sections: &[SceneSection], hashes: &[String]) -> Self {
+        let scenes = sections
+            .iter()
+            .zip(hashes)
+            .map(|(section, hash)| {
+                let cached = CachedScene {
+                    hash: hash.clone(),
+                    start_frame: section.frames.start,
+                    end_frame: section.frames.end,
+                };
+                (section.id.clone(), cached)
+            })
+            .collect();
+        Self { scenes }
+    }
+
+    /// The manifest at `path`, or an empty one when it's missing or unreadable
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| VideoEngineError::io("serialize", path)(std::io::Error::other(e)))?;
+        fs::write(path, json).map_err(VideoEngineError::io("write", path))
+    }
+
+    /// Delete the frames of scenes that aren't in the manifest anymore
+    fn prune(&self, scenes_dir: &Path) {
+        let Ok(entries) = fs::read_dir(scenes_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let listed = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| self.scenes.values().any(|cached| cached.hash == name));
+            if !listed {
+                let _ = fs::remove_dir_all(entry.path());
             }
         }
+    }
+}
 
-        if success {
-            // Update cache
-            fs::write(&hash_file, &script_hash)?;
-            let duration = start_time.elapsed();
-            println!(
-                "✅ Blender rendering complete in {:.2}s",
-                duration.as_secs_f32()
-            );
-        } else {
-            anyhow::bail!("One or more Blender jobs failed");
+/// Indices of the scenes to render: those the manifest doesn't list with the
+/// same hash and frames, or whose frame files have gone missing
+fn stale_scenes(
+    sections: &[SceneSection],
+    hashes: &[String],
+    manifest: &Manifest,
+    scenes_dir: &Path,
+) -> Vec<usize> {
+    sections
+        .iter()
+        .zip(hashes)
+        .enumerate()
+        .filter(|(_, (section, hash))| {
+            let listed = manifest.scenes.get(&section.id).is_some_and(|cached| {
+                cached.hash == **hash
+                    && cached.start_frame == section.frames.start
+                    && cached.end_frame == section.frames.end
+            });
+            let dir = scenes_dir.join(hash);
+            !(listed
+                && section
+                    .frames
+                    .clone()
+                    .all(|frame| dir.join(frame_file(frame)).is_file()))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Divide `work` between up to `jobs` jobs with about as many frames each.
+/// Ranges longer than a fair share are cut, then the longest go first to the
+/// job with the fewest frames so far, so a long scene doesn't leave one job
+/// working alone. Each job's ranges are in frame order.
+fn split_jobs(work: &[(Range<u32>, PathBuf)], jobs: usize) -> Vec<Vec<(Range<u32>, PathBuf)>> {
+    let total: u32 = work.iter().map(|(frames, _)| frames.len() as u32).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let jobs = jobs.clamp(1, total as usize);
+    let share = total.div_ceil(jobs as u32);
+
+    let mut chunks: Vec<(Range<u32>, PathBuf)> = work
+        .iter()
+        .flat_map(|(frames, dir)| {
+            frames
+                .clone()
+                .step_by(share as usize)
+                .map(move |start| (start..(start + share).min(frames.end), dir.clone()))
+        })
+        .collect();
+    chunks.sort_by_key(|(frames, _)| std::cmp::Reverse(frames.len()));
+
+    let mut split = vec![Vec::new(); jobs];
+    let mut loads = vec![0; jobs];
+    for chunk in chunks {
+        let job = (0..jobs).min_by_key(|&job| loads[job]).unwrap_or(0);
+        loads[job] += chunk.0.len();
+        split[job].push(chunk);
+    }
+    split.retain(|pieces| !pieces.is_empty());
+    for pieces in &mut split {
+        pieces.sort_by_key(|(frames, _)| frames.start);
+    }
+    split
+}
+
+/// One job per `CPUS_PER_JOB` CPUs, as far as the available memory leaves each
+/// `JOB_MEMORY_BYTES`
+fn default_parallel_jobs() -> usize {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    let by_memory = match sys.total_memory() {
+        // Memory can't be read on this platform
+        0 => usize::MAX,
+        _ => (sys.available_memory() / JOB_MEMORY_BYTES) as usize,
+    };
+    (num_cpus::get() / CPUS_PER_JOB).min(by_memory).max(1)
+}
+
+/// Whether `available` bytes of memory are too few to leave `jobs` Blender jobs
+/// running
+fn memory_critical(available: u64, total: u64, jobs: usize) -> bool {
+    total > 0 && available < MEMORY_RESERVE_PER_JOB * jobs as u64
+}
+
+/// Runs of `frames` without a non-empty frame file in `dir`
+fn missing_frames(frames: Range<u32>, dir: &Path) -> Vec<Range<u32>> {
+    let mut missing: Vec<Range<u32>> = Vec::new();
+    for frame in frames {
+        let saved = fs::metadata(dir.join(frame_file(frame))).is_ok_and(|file| file.len() > 0);
+        if saved {
+            continue;
+        }
+        match missing.last_mut() {
+            Some(run) if run.end == frame => run.end += 1,
+            _ => missing.push(frame..frame + 1),
         }
+    }
+    missing
+}
 
-        Ok(())
+/// Name Blender saves `frame` under, matching `FRAME_PATTERN`
+fn frame_file(frame: u32) -> String {
+    format!("frame_{:04}.png", frame)
+}
+
+/// Put the cached frame `from` at `to`, as a hard link where the file system
+/// allows it
+fn link_frame(from: &Path, to: &Path) -> Result<()> {
+    let _ = fs::remove_file(to);
+    if fs::hard_link(from, to).is_err() {
+        fs::copy(from, to).map_err(VideoEngineError::io("copy cached frame", from))?;
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::script::{Metadata, Resolution, Scene};
+/// Delete the frames in `dir` numbered `first` and up, left by a longer
+/// earlier render, which the encoder would pick up
+fn remove_frames_from(dir: &Path, first: u32) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(VideoEngineError::io("read", dir))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let frame = name.to_str().and_then(|name| {
+            name.strip_prefix("frame_")?
+                .strip_suffix(".png")?
+                .parse::<u32>()
+                .ok()
+        });
+        if frame.is_some_and(|frame| frame >= first) {
+            fs::remove_file(entry.path()).map_err(VideoEngineError::io("remove", entry.path()))?;
+        }
+    }
+    Ok(())
+}
 
-    #[test]
-    fn test_generate_python_script() {
-        let script = VideoScript {
-            metadata: Metadata {
-                title: "Test".into(),
-                resolution: Resolution::Named("1920x1080".into()),
-                fps: 60,
-                duration: 10.0,
-                description: None,
-                citations: vec![],
-            },
-            scenes: vec![Scene {
-                id: "test".into(),
-                duration: 5.0,
-                scene_type: Default::default(),
-                layers: vec![Layer::Image {
-                    source: "test.png".into(),
-                    effects: vec![],
-                    transform: Default::default(),
-                }],
-                transition: None,
-            }],
-            audio: None,
+/// Length of the `fade_in` and `fade_out` effects
+const EFFECT_FADE_SECONDS: f32 = 0.5;
+
+/// A linear change of a layer's opacity between two frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Ramp {
+    from: u32,
+    to: u32,
+    fade_in: bool,
+}
+
+impl Ramp {
+    /// Factor the layer's opacity is multiplied by at `frame`
+    fn at(&self, frame: u32) -> f32 {
+        let progress = if frame <= self.from {
+            0.0
+        } else if frame >= self.to {
+            1.0
+        } else {
+            (frame - self.from) as f32 / (self.to - self.from) as f32
         };
+        if self.fade_in {
+            progress
+        } else {
+            1.0 - progress
+        }
+    }
+}
 
-        let renderer = BlenderRenderer::new(script, PathBuf::from("output"));
-        let py_script = renderer.generate_python_script(0, 150);
+/// When a scene's layers are shown and how its transitions fade them, using
+/// the native `Timeline`'s transition windows so both backends agree
+struct SceneTiming {
+    /// Scene frames, widened into the neighbors' by dissolves
+    visible: Range<u32>,
+    /// Scene frames without the transitions
+    frames: Range<u32>,
+    ramps: Vec<Ramp>,
+}
 
-        assert!(py_script.contains("import bpy"));
-        assert!(py_script.contains("scene.render.resolution_x = 1920"));
-        assert!(py_script.contains("scene.render.resolution_y = 1080"));
-        assert!(py_script.contains("scene.frame_end = 150"));
+impl SceneTiming {
+    fn new(script: &VideoScript, timeline: &Timeline, index: usize, frames: &Range<u32>) -> Self {
+        let mut visible = frames.clone();
+        let mut ramps = Vec::new();
+
+        // A dissolve fades the incoming scene in over the whole window, on top
+        // of the outgoing one fading out; a fade goes through black at the cut
+        let previous = index.checked_sub(1);
+        let incoming = previous.and_then(|previous| {
+            let window = timeline.transition_frames(previous)?;
+            Some((script.scenes[previous].transition.as_ref()?, window))
+        });
+        match incoming {
+            Some((Transition::Dissolve { .. }, window)) => {
+                visible.start = window.start;
+                ramps.push(Ramp {
+                    from: window.start,
+                    to: window.end,
+                    fade_in: true,
+                });
+            }
+            Some((Transition::Fade { .. }, window)) => ramps.push(Ramp {
+                from: frames.start,
+                to: window.end,
+                fade_in: true,
+            }),
+            _ => {}
+        }
+
+        let outgoing = timeline
+            .transition_frames(index)
+            .and_then(|window| Some((script.scenes[index].transition.as_ref()?, window)));
+        match outgoing {
+            Some((Transition::Dissolve { .. }, window)) => {
+                visible.end = window.end;
+                ramps.push(Ramp {
+                    from: window.start,
+                    to: window.end,
+                    fade_in: false,
+                });
+            }
+            Some((Transition::Fade { .. }, window)) => ramps.push(Ramp {
+                from: window.start,
+                to: frames.end,
+                fade_in: false,
+            }),
+            _ => {}
+        }
+
+        Self {
+            visible,
+            frames: frames.clone(),
+            ramps,
+        }
+    }
+
+    /// The scene's ramps plus those of the layer's fade effects. Other effects
+    /// are reported and left out.
+    fn layer_ramps(&self, name: &str, effects: &[Effect], fps: u32) -> Vec<Ramp> {
+        let length = (EFFECT_FADE_SECONDS * fps as f32).round() as u32;
+        let length = length.min(self.frames.len() as u32 / 2);
+
+        let mut ramps = self.ramps.clone();
+        for effect in effects {
+            match effect {
+                Effect::FadeIn => ramps.push(Ramp {
+                    from: self.frames.start,
+                    to: self.frames.start + length,
+                    fade_in: true,
+                }),
+                Effect::FadeOut => ramps.push(Ramp {
+                    from: self.frames.end - length,
+                    to: self.frames.end,
+                    fade_in: false,
+                }),
+                Effect::Blur { .. } | Effect::ColorGrade { .. } | Effect::KenBurns { .. } => say!(
+                    "  ⚠️  Blender can't render the {:?} effect of {}; it is left out",
+                    effect,
+                    name
+                ),
+            }
+        }
+        ramps
+    }
+}
+
+/// Python selecting the render engine, its samples and device, and the background
+fn push_render_settings(py: &mut String, options: &BlenderOptions) {
+    let samples = options.samples.max(1);
+    match options.engine {
+        BlenderEngine::Eevee => {
+            // EEVEE Next replaced EEVEE in 4.2 and took its name back in 5.0
+            py.push_str("engines = [item.identifier for item in bpy.types.RenderSettings.bl_rna.properties['engine'].enum_items]\n");
+            py.push_str("scene.render.engine = 'BLENDER_EEVEE_NEXT' if 'BLENDER_EEVEE_NEXT' in engines else 'BLENDER_EEVEE'\n");
+            py.push_str(&format!("scene.eevee.taa_render_samples = {}\n", samples));
+        }
+        BlenderEngine::Cycles => {
+            py.push_str("scene.render.engine = 'CYCLES'\n");
+            py.push_str(&format!("scene.cycles.samples = {}\n", samples));
+            match options.device {
+                BlenderDevice::Cpu => py.push_str("scene.cycles.device = 'CPU'\n"),
+                BlenderDevice::Gpu => {
+                    py.push_str("scene.cycles.device = 'GPU' if use_cycles_gpu() else 'CPU'\n")
+                }
+            }
+        }
+    }
+    py.push_str(&format!(
+        "scene.render.film_transparent = {}\n",
+        if options.use_transparent_background {
+            "True"
+        } else {
+            "False"
+        }
+    ));
+}
+
+/// Python keyframing the opacity of `mat` along `ramps`, scaled by `opacity`.
+/// Nothing when the layer doesn't fade.
+fn push_alpha_keys(py: &mut String, indent: &str, ramps: &[Ramp], opacity: f32) {
+    let mut frames: Vec<u32> = ramps.iter().flat_map(|ramp| [ramp.from, ramp.to]).collect();
+    if frames.is_empty() {
+        return;
+    }
+    frames.sort_unstable();
+    frames.dedup();
+
+    let opacity = opacity.clamp(0.0, 1.0);
+    let keys: Vec<String> = frames
+        .iter()
+        .map(|&frame| {
+            let alpha: f32 = ramps.iter().map(|ramp| ramp.at(frame)).product();
+            format!("({}, {})", frame, opacity * alpha)
+        })
+        .collect();
+    py.push_str(&format!(
+        "{}keyframe_alpha(mat, [{}])\n",
+        indent,
+        keys.join(", ")
+    ));
+}
+
+/// `value` as a single-quoted Python string literal. Non-ASCII text passes
+/// through; the script is UTF-8 like all Python 3 source.
+fn python_str(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('\'');
+    for c in value.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\x{:02x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// Absolute path of an asset as a Python string literal
+fn python_path(path: &Path) -> String {
+    let abs_path = std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    python_str(&abs_path.to_string_lossy().replace("\\", "/")) // Fix Windows paths
+}
+
+/// Python that puts the material created into `mat` (`img_w` by `img_h` pixels)
+/// on a plane placed by `transform` and shown during `frames`
+fn push_plane(
+    py: &mut String,
+    name: &str,
+    transform: &Transform,
+    (width, height): (u32, u32),
+    frames: &Range<u32>,
+) {
+    py.push_str("if mat:\n");
+    py.push_str("    bpy.ops.mesh.primitive_plane_add(size=1)\n");
+    py.push_str("    obj = bpy.context.active_object\n");
+    py.push_str(&format!("    obj.name = {}\n", python_str(name)));
+    py.push_str("    obj.data.materials.append(mat)\n");
+
+    // Scale to match image dimensions (100px = 1 unit)
+    py.push_str("    obj.scale.x = img_w / 100.0\n");
+    py.push_str("    obj.scale.y = img_h / 100.0\n");
+
+    // Position
+    py.push_str(&format!(
+        "    bx, by = to_blender_coords({}, {}, {}, {})\n",
+        transform.position.x, transform.position.y, width, height
+    ));
+    py.push_str("    obj.location.x = bx\n");
+    py.push_str("    obj.location.y = by\n");
+
+    // Apply extra scale, and the clockwise rotation (Blender's is counter-clockwise)
+    py.push_str(&format!("    obj.scale.x *= {}\n", transform.scale));
+    py.push_str(&format!("    obj.scale.y *= {}\n", transform.scale));
+    if transform.rotation != 0.0 {
+        py.push_str(&format!(
+            "    obj.rotation_euler.z = math.radians({})\n",
+            -transform.rotation
+        ));
+    }
+
+    // Visibility Keyframing
+    py.push_str(&format!(
+        "    keyframe_visibility(obj, {}, {})\n",
+        frames.start, frames.end
+    ));
+}
+
+/// A plane showing `watermark` on every frame: parented to the camera just in
+/// front of its lens, so it stays put and covers every scene's layers
+fn push_watermark(py: &mut String, watermark: &Watermark, (width, height): (u32, u32)) {
+    let (x, y) = watermark.position.fractions();
+    let margin = watermark.margin_px;
+    py.push_str("\n# Watermark\n");
+    py.push_str(&format!(
+        "mat, img_w, img_h = create_image_material('Mat_Watermark', {}, {})\n",
+        python_path(&watermark.source),
+        watermark.opacity.clamp(0.0, 1.0)
+    ));
+    py.push_str("if mat:\n");
+    py.push_str("    bpy.ops.mesh.primitive_plane_add(size=1)\n");
+    py.push_str("    obj = bpy.context.active_object\n");
+    py.push_str("    obj.name = 'Watermark'\n");
+    py.push_str("    obj.data.materials.append(mat)\n");
+    py.push_str(&format!(
+        "    w, h = img_w * {0}, img_h * {0}\n",
+        watermark.scale
+    ));
+    py.push_str("    obj.scale.x = w / 100.0\n");
+    py.push_str("    obj.scale.y = h / 100.0\n");
+    // Same placement as `Watermark::top_left`, for the plane's center
+    py.push_str(&format!(
+        "    x = {} * ({} - w) + {} * {} + w / 2\n",
+        x,
+        width,
+        1.0 - 2.0 * x,
+        margin
+    ));
+    py.push_str(&format!(
+        "    y = {} * ({} - h) + {} * {} + h / 2\n",
+        y,
+        height,
+        1.0 - 2.0 * y,
+        margin
+    ));
+    py.push_str(&format!(
+        "    bx, by = to_blender_coords(x, y, {}, {})\n",
+        width, height
+    ));
+    py.push_str("    obj.parent = cam_obj\n");
+    py.push_str("    obj.location = (bx, by, -1)\n");
+}
+
+/// The Blender processes of a render, which the memory monitor can pause and stop
+#[derive(Default)]
+struct Children {
+    /// Running processes, by job
+    running: Mutex<HashMap<usize, Child>>,
+    /// Set while memory is short, so no more processes start
+    paused: Mutex<bool>,
+    unpaused: Condvar,
+    /// Why the render was stopped; no more processes start after it is set
+    stopped: Mutex<Option<String>>,
+}
+
+impl Children {
+    /// Track `child` as job `job`'s process, killing it if the render stopped
+    fn add(&self, job: usize, mut child: Child) -> Result<()> {
+        let mut running = self.running.lock().unwrap();
+        if let Some(reason) = self.stop_reason() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(VideoEngineError::external("Blender", reason));
+        }
+        running.insert(job, child);
+        Ok(())
+    }
+
+    fn take(&self, job: usize) -> Option<Child> {
+        self.running.lock().unwrap().remove(&job)
+    }
+
+    /// Block while paused with another process still running, so memory frees
+    /// up before the next one starts. Fails once the render is stopped.
+    fn wait_to_launch(&self) -> Result<()> {
+        let mut paused = self.paused.lock().unwrap();
+        while *paused && !self.running.lock().unwrap().is_empty() {
+            // Woken when unpaused; timed so finished processes are noticed
+            paused = self
+                .unpaused
+                .wait_timeout(paused, MEMORY_CHECK_INTERVAL)
+                .unwrap()
+                .0;
+        }
+        drop(paused);
+        match self.stop_reason() {
+            Some(reason) => Err(VideoEngineError::external("Blender", reason)),
+            None => Ok(()),
+        }
+    }
+
+    fn set_paused(&self, pause: bool) {
+        *self.paused.lock().unwrap() = pause;
+        if !pause {
+            self.unpaused.notify_all();
+        }
+    }
+
+    /// Kill every running process and keep new ones from starting
+    fn stop(&self, reason: String) {
+        let mut running = self.running.lock().unwrap();
+        *self.stopped.lock().unwrap() = Some(reason);
+        for child in running.values_mut() {
+            let _ = child.kill();
+        }
+        drop(running);
+        self.set_paused(false);
+    }
+
+    fn stop_reason(&self) -> Option<String> {
+        self.stopped.lock().unwrap().clone()
+    }
+}
+
+/// Memory use at one moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MemoryReading {
+    available: u64,
+    total: u64,
+}
+
+/// Where the memory monitor reads memory use from
+trait MemoryReader {
+    /// `None` when memory use can't be read on this platform
+    fn read(&mut self) -> Option<MemoryReading>;
+}
+
+struct SystemMemory(System);
+
+impl MemoryReader for SystemMemory {
+    fn read(&mut self) -> Option<MemoryReading> {
+        self.0.refresh_memory();
+        let total = self.0.total_memory();
+        (total > 0).then(|| MemoryReading {
+            available: self.0.available_memory(),
+            total,
+        })
+    }
+}
+
+/// Pauses launching Blender processes while memory is short, and stops the
+/// render before the system runs out
+struct MemoryMonitor<R> {
+    reader: R,
+    jobs: usize,
+    children: Arc<Children>,
+}
+
+impl<R: MemoryReader> MemoryMonitor<R> {
+    /// Check memory every `MEMORY_CHECK_INTERVAL` until the sender of `stop`
+    /// is dropped or the render is stopped
+    fn run(mut self, stop: mpsc::Receiver<()>) {
+        while self.check() {
+            if stop.recv_timeout(MEMORY_CHECK_INTERVAL) != Err(mpsc::RecvTimeoutError::Timeout) {
+                break;
+            }
+        }
+    }
+
+    /// Read memory use once and pause, unpause or stop the jobs. False once
+    /// the render is stopped.
+    fn check(&mut self) -> bool {
+        let Some(reading) = self.reader.read() else {
+            return true;
+        };
+        if memory_critical(reading.available, reading.total, self.jobs) {
+            let reason = format!(
+                "🚨 CRITICAL: Only {} MiB of memory left for {} Blender jobs; stopped Blender to prevent a crash",
+                reading.available / (1024 * 1024),
+                self.jobs
+            );
+            error!("{}", reason);
+            self.children.stop(reason);
+            return false;
+        }
+
+        let used = 1.0 - reading.available as f64 / reading.total as f64;
+        let pause = used >= MEMORY_SOFT_LIMIT;
+        if pause != *self.children.paused.lock().unwrap() {
+            if pause {
+                say!(
+                    "  ⚠️  Memory {:.0}% used; waiting for running Blender jobs before starting more",
+                    used * 100.0
+                );
+            }
+            self.children.set_paused(pause);
+        }
+        true
+    }
+}
+
+/// A line of Blender's output the renderer acts on
+#[derive(Debug, Clone, PartialEq)]
+enum BlenderLine<'a> {
+    /// A status line printed while rendering this frame
+    Rendering(u32),
+    /// A frame was saved, with its number when the file name has one
+    Saved(Option<u32>),
+    /// A problem the generated script reported
+    Error(&'a str),
+}
+
+fn parse_blender_line(line: &str) -> Option<BlenderLine<'_>> {
+    if line.contains("Saved:") {
+        Some(BlenderLine::Saved(saved_frame_number(line)))
+    } else if let Some(error) = reported_error(line) {
+        Some(BlenderLine::Error(error))
+    } else {
+        rendering_frame(line).map(BlenderLine::Rendering)
+    }
+}
+
+/// Frame number from a Blender "Fra:12 Mem:45.12M (Peak 46.00M) | Time:00:01.20 | ..."
+/// status line
+fn rendering_frame(line: &str) -> Option<u32> {
+    let rest = line.trim_start().strip_prefix("Fra:")?;
+    let digits = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+    digits.parse().ok()
+}
+
+/// Notices jobs that stop making progress
+struct StallMonitor {
+    timeout: Duration,
+    jobs: Vec<JobActivity>,
+}
+
+struct JobActivity {
+    last_progress: Instant,
+    /// Frame Blender last said it was on
+    frame: Option<u32>,
+    /// Finished, or already reported since its last progress
+    quiet: bool,
+}
+
+impl StallMonitor {
+    fn new(jobs: usize, timeout: Duration, now: Instant) -> Self {
+        let jobs = (0..jobs)
+            .map(|_| JobActivity {
+                last_progress: now,
+                frame: None,
+                quiet: false,
+            })
+            .collect();
+        Self { timeout, jobs }
+    }
+
+    fn progress(&mut self, job: usize, frame: Option<u32>, now: Instant) {
+        let activity = &mut self.jobs[job];
+        activity.last_progress = now;
+        activity.frame = frame.or(activity.frame);
+        activity.quiet = false;
+    }
+
+    fn finish(&mut self, job: usize) {
+        self.jobs[job].quiet = true;
+    }
+
+    /// Jobs that have made no progress for the timeout, with the frame they
+    /// were last on. Each stall is reported once.
+    fn newly_stalled(&mut self, now: Instant) -> Vec<(usize, Option<u32>)> {
+        let mut stalled = Vec::new();
+        for (job, activity) in self.jobs.iter_mut().enumerate() {
+            if !activity.quiet && now.duration_since(activity.last_progress) >= self.timeout {
+                activity.quiet = true;
+                stalled.push((job, activity.frame));
+            }
+        }
+        stalled
+    }
+}
+
+/// The message of an `INTERSTELLAR_ERROR <layer>: <message>` line printed by the
+/// generated script when it can't load an asset
+fn reported_error(line: &str) -> Option<&str> {
+    line.split_once("INTERSTELLAR_ERROR ")
+        .map(|(_, error)| error.trim())
+}
+
+/// How to install Blender on this platform, or point the engine at it
+fn install_hint() -> String {
+    let install = if cfg!(target_os = "macos") {
+        "brew install --cask blender"
+    } else if cfg!(target_os = "windows") {
+        "choco install blender"
+    } else if cfg!(target_os = "linux") {
+        "sudo snap install blender --classic"
+    } else {
+        "download it from https://www.blender.org/download/"
+    };
+    format!(
+        "Install Blender ({}) or set blender.executable or {} to its path.",
+        install, BLENDER_PATH_ENV
+    )
+}
+
+/// Frame number from a Blender "Saved: '/out/frame_0012.png'" line
+fn saved_frame_number(line: &str) -> Option<u32> {
+    let (_, path) = line.split_once("Saved:")?;
+    let path = path.trim().trim_matches(|c| c == '\'' || c == '"');
+    let stem = Path::new(path).file_stem()?.to_str()?;
+    let digits = stem.trim_start_matches(|c: char| !c.is_ascii_digit());
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::ProgressEvent;
+    use crate::script::{Color, Metadata, Resolution, Scene};
+
+    fn test_script() -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 60,
+                duration: 10.0,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes: vec![Scene {
+                id: "test".into(),
+                duration: 5.0,
+                scene_type: Default::default(),
+                layers: vec![Layer::Image {
+                    source: "test.png".into(),
+                    effects: vec![],
+                    transform: Default::default(),
+                    crop: None,
+                }],
+                transition: None,
+                narration: None,
+                title: None,
+            }],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_python_script() {
+        let script = test_script();
+        let renderer = BlenderRenderer::new();
+        let py_script = renderer.generate_python_script(&script, 0, 150);
+
+        assert!(py_script.contains("import bpy"));
+        assert!(py_script.contains("scene.render.resolution_x = 1920"));
+        assert!(py_script.contains("scene.render.resolution_y = 1080"));
+        assert!(py_script.contains("scene.frame_end = 150"));
+        // Default opacity is passed to the image material
+        assert!(py_script.contains("create_image_material('Mat_Image_test_0', 'test.png', 1)"));
+    }
+
+    #[test]
+    fn test_watermark_follows_the_camera() {
+        let mut script = test_script();
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 150);
+        assert!(!py_script.contains("Watermark"));
+
+        script.metadata.watermark = Some(Watermark {
+            opacity: 0.5,
+            ..Watermark::new("logo.png")
+        });
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 150);
+        assert!(py_script.contains("create_image_material('Mat_Watermark', 'logo.png', 0.5)"));
+        assert!(py_script.contains("obj.parent = cam_obj"));
+        // Bottom-right, 24 pixels from the edges
+        assert!(py_script.contains("x = 1 * (1920 - w) + -1 * 24 + w / 2"));
+        assert!(py_script.contains("y = 1 * (1080 - h) + -1 * 24 + h / 2"));
+    }
+
+    #[test]
+    fn test_saved_frame_number() {
+        assert_eq!(
+            saved_frame_number("Saved: '/tmp/out/frame_0012.png'"),
+            Some(12)
+        );
+        assert_eq!(
+            saved_frame_number(" Time: 00:01.20 (Saving: 00:00.01) Saved: \"frame_0100.png\""),
+            Some(100)
+        );
+        assert_eq!(saved_frame_number("Saved: 'untitled.png'"), None);
+    }
+
+    #[test]
+    fn test_video_layer_becomes_movie_plane() {
+        let mut script = test_script();
+        script.scenes[0].duration = 2.0;
+        script.scenes.push(Scene {
+            id: "clip".into(),
+            duration: 3.0,
+            layers: vec![Layer::Video {
+                source: "clip.mp4".into(),
+                effects: vec![],
+                transform: Transform {
+                    scale: 0.5,
+                    rotation: 90.0,
+                    opacity: 0.8,
+                    ..Default::default()
+                },
+            }],
+            ..script.scenes[0].clone()
+        });
+
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 300);
+        // Scene "clip" covers frames 120..300 at 60fps
+        assert!(py_script
+            .contains("create_movie_material('Mat_Video_clip_0', 'clip.mp4', 0.8, 120, 180)"));
+        assert!(py_script.contains("obj.name = 'Video_clip_0'"));
+        assert!(py_script.contains("obj.scale.x *= 0.5"));
+        assert!(py_script.contains("obj.rotation_euler.z = math.radians(-90)"));
+        assert!(py_script.contains("keyframe_visibility(obj, 120, 300)"));
+        assert!(py_script.contains("tex.image.source = 'MOVIE'"));
+    }
+
+    /// Value of a single-quoted Python literal at the start of `code`, and the rest
+    fn parse_python_str(code: &str) -> (String, &str) {
+        let mut chars = code.char_indices();
+        assert_eq!(chars.next(), Some((0, '\'')));
+        let mut value = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\'' => return (value, &code[i + 1..]),
+                '\n' => panic!("Unterminated literal in {:?}", code),
+                '\\' => match chars.next().unwrap().1 {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'x' => {
+                        let hex: String = (0..2).map(|_| chars.next().unwrap().1).collect();
+                        value.push(char::from(u8::from_str_radix(&hex, 16).unwrap()));
+                    }
+                    escaped => value.push(escaped),
+                },
+                c => value.push(c),
+            }
+        }
+        panic!("Unterminated literal in {:?}", code);
+    }
+
+    #[test]
+    fn test_python_strings_round_trip() {
+        for value in [
+            "don't miss this",
+            "say \"hi\"",
+            "C:\\path\\to",
+            "two\nlines\r\t",
+            "bell\u{7}",
+            "café ✨",
+        ] {
+            let literal = python_str(value);
+            assert!(!literal.contains('\n'));
+            assert_eq!(parse_python_str(&literal), (value.to_string(), ""));
+        }
+    }
+
+    #[test]
+    fn test_text_and_names_are_escaped() {
+        let mut script = test_script();
+        script.scenes[0].id = "it's".into();
+        script.scenes[0].layers.push(Layer::Text {
+            content: "don't \"quote\" me\\\nplease".into(),
+            font: "fonts/it's.ttf".into(),
+            font_size: 48.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Default::default(),
+            effects: vec![],
+        });
+
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 300);
+        let body = py_script
+            .lines()
+            .find_map(|line| line.strip_prefix("obj.data.body = "))
+            .unwrap();
+        assert_eq!(
+            parse_python_str(body),
+            ("don't \"quote\" me\\\nplease".to_string(), "")
+        );
+
+        let name = py_script
+            .lines()
+            .find_map(|line| line.strip_prefix("obj.name = "))
+            .unwrap();
+        assert_eq!(parse_python_str(name).0, "Text_it's_1");
+        assert!(py_script.contains("create_image_material('Mat_Image_it\\'s_0', "));
+        assert!(py_script.contains("fonts.load('fonts/it\\'s.ttf')"));
+    }
+
+    /// `test_script`'s 5s scene followed by another, at 60fps
+    fn two_scenes(transition: Transition) -> VideoScript {
+        let mut script = test_script();
+        script.scenes[0].transition = Some(transition);
+        script.scenes.push(Scene {
+            id: "next".into(),
+            transition: None,
+            ..script.scenes[0].clone()
+        });
+        script
+    }
+
+    #[test]
+    fn test_dissolve_overlaps_scenes() {
+        let script = two_scenes(Transition::Dissolve { duration: 1.0 });
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 600);
+
+        // One second centered on the cut at frame 300, as in the native timeline
+        assert_eq!(
+            Timeline::from_script(&script).transition_frames(0),
+            Some(270..330)
+        );
+        assert!(py_script.contains(
+            "    keyframe_visibility(obj, 0, 330)\n    keyframe_alpha(mat, [(270, 1), (330, 0)])"
+        ));
+        assert!(py_script.contains(
+            "    keyframe_visibility(obj, 270, 600)\n    keyframe_alpha(mat, [(270, 0), (330, 1)])"
+        ));
+        assert!(py_script.contains("tree.keyframe_insert(data_path="));
+    }
+
+    #[test]
+    fn test_fade_goes_through_the_cut() {
+        let script = two_scenes(Transition::Fade { duration: 1.0 });
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 600);
+
+        assert!(py_script.contains(
+            "    keyframe_visibility(obj, 0, 300)\n    keyframe_alpha(mat, [(270, 1), (300, 0)])"
+        ));
+        assert!(py_script.contains(
+            "    keyframe_visibility(obj, 300, 600)\n    keyframe_alpha(mat, [(300, 0), (330, 1)])"
+        ));
+    }
+
+    #[test]
+    fn test_fade_effects_combine_with_transitions() {
+        let mut script = two_scenes(Transition::Dissolve { duration: 1.0 });
+        script.scenes[0].layers = vec![Layer::Image {
+            source: "test.png".into(),
+            effects: vec![Effect::FadeOut, Effect::Blur { radius: 4.0 }],
+            transform: Transform {
+                opacity: 0.5,
+                ..Default::default()
+            },
+            crop: None,
+        }];
+        script.scenes[1].layers[0] = Layer::Image {
+            source: "test.png".into(),
+            effects: vec![Effect::FadeIn],
+            transform: Default::default(),
+            crop: None,
+        };
+        script.scenes[1].transition = Some(Transition::Cut);
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 600);
+
+        // Half a second of fade out, under the dissolve, at half opacity
+        assert!(py_script.contains("keyframe_alpha(mat, [(270, 0.5), (300, 0), (330, 0)])"));
+        assert!(py_script.contains("keyframe_alpha(mat, [(270, 0), (300, 0), (330, 1)])"));
+    }
+
+    #[test]
+    fn test_version_parsing() {
+        let output = "Blender 4.2.1 LTS\n\tbuild date: 2024-08-19\n\tbuild hash: 396f546c9d82\n";
+        let version = BlenderVersion::from_version_output(output).unwrap();
+        assert_eq!(version, BlenderVersion::parse("4.2.1").unwrap());
+        assert_eq!(version.to_string(), "4.2.1");
+
+        assert_eq!(
+            BlenderVersion::parse("4.0"),
+            Some(BlenderVersion {
+                major: 4,
+                minor: 0,
+                patch: 0
+            })
+        );
+        assert!(BlenderVersion::parse("3.6.9").unwrap() < BlenderVersion::parse("4.0").unwrap());
+        assert!(BlenderVersion::parse("4.10").unwrap() > BlenderVersion::parse("4.2").unwrap());
+        assert_eq!(BlenderVersion::parse("four"), None);
+        assert_eq!(BlenderVersion::from_version_output("Not Blender"), None);
+    }
+
+    /// A stand-in for Blender: prints `version_line` for `--version`, and fails
+    /// a render after writing 30 lines to stderr
+    #[cfg(unix)]
+    fn fake_blender(dir: &Path, version_line: &str) -> BlenderOptions {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("blender");
+        fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\n\
+                 if [ \"$1\" = \"--version\" ]; then echo '{}'; exit 0; fi\n\
+                 for i in $(seq 1 29); do echo \"warning $i\" >&2; done\n\
+                 echo 'Error: Python script failed' >&2\n\
+                 exit 3\n",
+                version_line
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        BlenderOptions {
+            executable: path,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_installation() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let renderer =
+            BlenderRenderer::new().with_options(fake_blender(dir.path(), "Blender 4.2.1 LTS"));
+        assert_eq!(
+            renderer.check_installation().unwrap(),
+            BlenderVersion::parse("4.2.1").unwrap()
+        );
+
+        let renderer =
+            BlenderRenderer::new().with_options(fake_blender(dir.path(), "Blender 3.6.2"));
+        let error = renderer.check_installation().unwrap_err().to_string();
+        assert!(error.contains("3.6.2 at"), "{}", error);
+        assert!(error.contains("too old"), "{}", error);
+
+        let renderer = BlenderRenderer::new().with_options(BlenderOptions {
+            executable: dir.path().join("missing"),
+            ..Default::default()
+        });
+        let error = renderer.check_installation().unwrap_err().to_string();
+        assert!(error.contains("Blender not found"), "{}", error);
+        assert!(error.contains(BLENDER_PATH_ENV), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_job_reports_stderr_tail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut renderer =
+            BlenderRenderer::new().with_options(fake_blender(dir.path(), "Blender 4.2.1"));
+        renderer.cache_dir = dir.path().join("cache");
+        renderer.options.parallel_jobs = Some(1);
+
+        let error = renderer
+            .render_to(
+                &test_script(),
+                &dir.path().join("out"),
+                &mut |_: ProgressEvent| {},
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("frames 0..300 failed"), "{}", error);
+        assert!(error.contains("Error: Python script failed"), "{}", error);
+        // Only the last lines
+        assert!(error.contains("warning 11\n"), "{}", error);
+        assert!(!error.contains("warning 10\n"), "{}", error);
+    }
+
+    /// `test_script` with three 5s scenes, `first`, `second` and `third`
+    fn three_scenes() -> VideoScript {
+        let mut script = test_script();
+        let scene = script.scenes[0].clone();
+        script.scenes = ["first", "second", "third"]
+            .into_iter()
+            .map(|id| Scene {
+                id: id.into(),
+                ..scene.clone()
+            })
+            .collect();
+        script
+    }
+
+    /// Sections and hashes of `script` as `render_to` computes them
+    fn plan(script: &VideoScript) -> (Vec<SceneSection>, Vec<String>) {
+        let total_frames = Timeline::from_script(script).total_frames();
+        let prelude = BlenderRenderer::new().python_prelude(script, 0, total_frames);
+        let sections = scene_sections(script);
+        let hashes = scene_hashes(&prelude, &sections);
+        (sections, hashes)
+    }
+
+    /// Fill `cache_dir` as if every scene of `script` had been rendered
+    fn fill_cache(cache_dir: &Path, script: &VideoScript) {
+        let (sections, hashes) = plan(script);
+        for (section, hash) in sections.iter().zip(&hashes) {
+            let dir = cache_dir.join(SCENES_DIR).join(hash);
+            fs::create_dir_all(&dir).unwrap();
+            for frame in section.frames.clone() {
+                fs::write(dir.join(frame_file(frame)), frame.to_string()).unwrap();
+            }
+        }
+        Manifest::new(&sections, &hashes)
+            .save(&cache_dir.join(MANIFEST_FILE))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_only_changed_scenes_are_scheduled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scenes_dir = dir.path().join(SCENES_DIR);
+        let mut script = three_scenes();
+        let stale = |script: &VideoScript| {
+            let (sections, hashes) = plan(script);
+            let manifest = Manifest::load(&dir.path().join(MANIFEST_FILE));
+            stale_scenes(&sections, &hashes, &manifest, &scenes_dir)
+        };
+
+        assert_eq!(stale(&script), vec![0, 1, 2]);
+        fill_cache(dir.path(), &script);
+        assert_eq!(stale(&script), Vec::<usize>::new());
+
+        // Editing the second scene's layer only schedules its frames
+        script.scenes[1].layers[0] = Layer::Image {
+            source: "other.png".into(),
+            effects: vec![],
+            transform: Default::default(),
+            crop: None,
+        };
+        let (sections, _) = plan(&script);
+        assert_eq!(stale(&script), vec![1]);
+        assert_eq!(sections[1].frames, 300..600);
+
+        // A dissolve into it shows its layers in the first scene's frames too
+        script.scenes[0].transition = Some(Transition::Dissolve { duration: 1.0 });
+        fill_cache(dir.path(), &script);
+        script.scenes[1].layers[0] = Layer::Image {
+            source: "third.png".into(),
+            effects: vec![],
+            transform: Default::default(),
+            crop: None,
+        };
+        assert_eq!(stale(&script), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_missing_frames_invalidate_the_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = three_scenes();
+        fill_cache(dir.path(), &script);
+
+        let (sections, hashes) = plan(&script);
+        let scenes_dir = dir.path().join(SCENES_DIR);
+        fs::remove_file(scenes_dir.join(&hashes[2]).join(frame_file(700))).unwrap();
+        let manifest = Manifest::load(&dir.path().join(MANIFEST_FILE));
+        assert_eq!(
+            stale_scenes(&sections, &hashes, &manifest, &scenes_dir),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_cached_frames_are_reassembled_without_blender() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = three_scenes();
+        let mut renderer = BlenderRenderer::new().with_options(BlenderOptions {
+            executable: dir.path().join("missing"),
+            ..Default::default()
+        });
+        renderer.cache_dir = dir.path().join("cache");
+        fill_cache(&renderer.cache_dir, &script);
+        // An unrelated scene directory, left by an older render
+        fs::create_dir_all(renderer.cache_dir.join(SCENES_DIR).join("old")).unwrap();
+
+        // Frames of a longer earlier render
+        let output_dir = dir.path().join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+        fs::write(output_dir.join(frame_file(950)), "stale").unwrap();
+
+        renderer
+            .render_to(&script, &output_dir, &mut |_: ProgressEvent| {})
+            .unwrap();
+        let mut frames: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        frames.sort();
+        assert_eq!(frames.len(), 900);
+        assert_eq!(frames[0], "frame_0000.png");
+        assert_eq!(frames[899], "frame_0899.png");
+        assert_eq!(
+            fs::read_to_string(output_dir.join(frame_file(450))).unwrap(),
+            "450"
+        );
+        assert!(!renderer.cache_dir.join(SCENES_DIR).join("old").exists());
+
+        // Without its frames, the scene needs Blender again
+        let (_, hashes) = plan(&script);
+        fs::remove_dir_all(renderer.cache_dir.join(SCENES_DIR).join(&hashes[0])).unwrap();
+        let error = renderer
+            .render_to(&script, &output_dir, &mut |_: ProgressEvent| {})
+            .unwrap_err();
+        assert!(error.to_string().contains("Blender not found"), "{}", error);
+    }
+
+    #[test]
+    fn test_plan_splits_stale_scenes_into_jobs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = three_scenes();
+        let mut renderer = BlenderRenderer::new().with_options(BlenderOptions {
+            parallel_jobs: Some(2),
+            ..Default::default()
+        });
+        renderer.cache_dir = dir.path().join("cache");
+
+        let plan = Renderer::plan(&renderer, &script);
+        assert_eq!(plan.files.unwrap().pattern, FRAME_PATTERN);
+        // Three 300 frame scenes, each under a fair share of 450
+        assert!(plan
+            .details
+            .contains(&"Job 1: frames 0..300, 600..900 (600 frames)".to_string()));
+        assert!(plan
+            .details
+            .contains(&"Job 2: frames 300..600 (300 frames)".to_string()));
+        // Planning creates no cache
+        assert!(!renderer.cache_dir.exists());
+
+        fill_cache(&renderer.cache_dir, &script);
+        let plan = Renderer::plan(&renderer, &script);
+        assert!(plan
+            .details
+            .iter()
+            .any(|line| line.starts_with("Reuses 3 of 3 scenes")));
+        assert!(plan.details.iter().all(|line| !line.starts_with("Job")));
+    }
+
+    #[test]
+    fn test_resume_schedules_only_missing_frames() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scenes_dir = dir.path().join(SCENES_DIR);
+        let script = three_scenes();
+        let (sections, hashes) = plan(&script);
+
+        // A job died in the second scene after saving 300..450 and 500..520;
+        // frame 449 was cut short
+        let scene_dir = scenes_dir.join(&hashes[1]);
+        fs::create_dir_all(&scene_dir).unwrap();
+        for frame in (300..449).chain(500..520) {
+            fs::write(scene_dir.join(frame_file(frame)), "png").unwrap();
+        }
+        fs::write(scene_dir.join(frame_file(449)), "").unwrap();
+        assert_eq!(
+            missing_frames(300..600, &scene_dir),
+            vec![449..500, 520..600]
+        );
+
+        let mut renderer = BlenderRenderer::new();
+        renderer.options.resume = true;
+        let work = renderer
+            .schedule(&sections, &hashes, &[1, 2], &scenes_dir)
+            .unwrap();
+        assert_eq!(
+            work,
+            vec![
+                (449..500, scene_dir.clone()),
+                (520..600, scene_dir.clone()),
+                (600..900, scenes_dir.join(&hashes[2]))
+            ]
+        );
+
+        // Without resuming, the scene starts over
+        renderer.options.resume = false;
+        let work = renderer
+            .schedule(&sections, &hashes, &[1], &scenes_dir)
+            .unwrap();
+        assert_eq!(work, vec![(300..600, scene_dir.clone())]);
+        assert!(!scene_dir.exists());
+    }
+
+    /// A stand-in for Blender that saves the frames it is asked for, but the
+    /// first time it runs dies after two of them. Every run's frames are
+    /// appended to `runs.log`.
+    #[cfg(unix)]
+    fn flaky_blender(dir: &Path) -> BlenderOptions {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("blender");
+        fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\n\
+                 if [ \"$1\" = \"--version\" ]; then echo 'Blender 4.2.1'; exit 0; fi\n\
+                 while [ $# -gt 0 ]; do\n\
+                   case \"$1\" in\n\
+                     --start) start=$2; shift;;\n\
+                     --end) end=$2; shift;;\n\
+                     --output) out=$2; shift;;\n\
+                   esac\n\
+                   shift\n\
+                 done\n\
+                 echo \"$start..$end\" >> '{log}'\n\
+                 i=$start\n\
+                 while [ $i -le $end ]; do\n\
+                   if [ ! -e '{crashed}' ] && [ $i -ge $((start + 2)) ]; then\n\
+                     touch '{crashed}'; echo 'Error: out of memory' >&2; exit 1\n\
+                   fi\n\
+                   file=$(printf '%s%04d.png' \"$out\" $i)\n\
+                   echo $i > \"$file\"\n\
+                   echo \"Saved: '$file'\"\n\
+                   i=$((i + 1))\n\
+                 done\n",
+                log = dir.join("runs.log").display(),
+                crashed = dir.join("crashed").display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        BlenderOptions {
+            executable: path,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_job_is_retried_for_its_unsaved_frames() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut renderer = BlenderRenderer::new().with_options(flaky_blender(dir.path()));
+        renderer.cache_dir = dir.path().join("cache");
+        renderer.options.parallel_jobs = Some(1);
+
+        let output_dir = dir.path().join("out");
+        renderer
+            .render_to(&test_script(), &output_dir, &mut |_: ProgressEvent| {})
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("runs.log")).unwrap(),
+            "0..299\n2..299\n"
+        );
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 300);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resume_after_a_failed_render() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut options = flaky_blender(dir.path());
+        options.retries = 0;
+        let mut renderer = BlenderRenderer::new().with_options(options);
+        renderer.cache_dir = dir.path().join("cache");
+        renderer.options.parallel_jobs = Some(1);
+
+        let output_dir = dir.path().join("out");
+        let error = renderer
+            .render_to(&test_script(), &output_dir, &mut |_: ProgressEvent| {})
+            .unwrap_err();
+        assert!(error.to_string().contains("--resume"), "{}", error);
+
+        renderer.options.resume = true;
+        renderer
+            .render_to(&test_script(), &output_dir, &mut |_: ProgressEvent| {})
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("runs.log")).unwrap(),
+            "0..299\n2..299\n"
+        );
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 300);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancelled_render_stops_blender() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Saves a frame every 50ms, noting its process id first
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("blender");
+        fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\n\
+                 if [ \"$1\" = \"--version\" ]; then echo 'Blender 4.2.1'; exit 0; fi\n\
+                 echo $$ >> '{pids}'\n\
+                 while [ $# -gt 0 ]; do\n\
+                   case \"$1\" in\n\
+                     --start) start=$2; shift;;\n\
+                     --end) end=$2; shift;;\n\
+                     --output) out=$2; shift;;\n\
+                   esac\n\
+                   shift\n\
+                 done\n\
+                 i=$start\n\
+                 while [ $i -le $end ]; do\n\
+                   sleep 0.05\n\
+                   file=$(printf '%s%04d.png' \"$out\" $i)\n\
+                   echo $i > \"$file\"\n\
+                   echo \"Saved: '$file'\"\n\
+                   i=$((i + 1))\n\
+                 done\n",
+                pids = dir.path().join("pids").display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut renderer = BlenderRenderer::new().with_options(BlenderOptions {
+            executable: path,
+            parallel_jobs: Some(2),
+            ..Default::default()
+        });
+        renderer.cache_dir = dir.path().join("cache");
+        let token = CancellationToken::new();
+        renderer.set_cancellation(token.clone());
+
+        let started = Instant::now();
+        let mut saved = 0;
+        let error = renderer
+            .render_to(
+                &test_script(),
+                &dir.path().join("out"),
+                &mut |event: ProgressEvent| {
+                    if let ProgressKind::FrameCompleted { completed, .. } = event.kind {
+                        saved = completed;
+                        if completed == 3 {
+                            token.cancel();
+                        }
+                    }
+                },
+            )
+            .unwrap_err();
+
+        assert!(matches!(error, VideoEngineError::Cancelled), "{}", error);
+        // All 300 frames would take 7.5s
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert!(saved < 300);
+        let pids = fs::read_to_string(dir.path().join("pids")).unwrap();
+        assert_eq!(pids.lines().count(), 2);
+        for pid in pids.lines() {
+            let alive = Command::new("kill")
+                .args(["-0", pid])
+                .stderr(Stdio::null())
+                .status()
+                .unwrap()
+                .success();
+            assert!(!alive, "Blender process {} is still running", pid);
+        }
+    }
+
+    #[test]
+    fn test_split_jobs() {
+        let (a, b) = (PathBuf::from("a"), PathBuf::from("b"));
+        let work = [(0..100, a.clone()), (200..250, b.clone())];
+        assert_eq!(
+            split_jobs(&work, 2),
+            vec![
+                vec![(0..75, a.clone())],
+                vec![(75..100, a.clone()), (200..250, b.clone())]
+            ]
+        );
+        assert_eq!(split_jobs(&work, 1), vec![work.to_vec()]);
+        assert!(split_jobs(&[], 2).is_empty());
+        // Never more jobs than frames
+        assert_eq!(split_jobs(&[(0..2, a.clone())], 8).len(), 2);
+    }
+
+    #[test]
+    fn test_split_jobs_balances_uneven_scenes() {
+        // One long scene among short ones
+        let mut start = 0;
+        let work: Vec<(Range<u32>, PathBuf)> = [1800, 120, 90, 300, 60, 240, 150]
+            .iter()
+            .enumerate()
+            .map(|(i, &frames)| {
+                start += frames;
+                (start - frames..start, PathBuf::from(i.to_string()))
+            })
+            .collect();
+        let total: u32 = work.iter().map(|(frames, _)| frames.len() as u32).sum();
+
+        for jobs in [2, 3, 4, 8] {
+            let split = split_jobs(&work, jobs);
+            assert_eq!(split.len(), jobs);
+            let loads: Vec<u32> = split
+                .iter()
+                .map(|pieces| pieces.iter().map(|(frames, _)| frames.len() as u32).sum())
+                .collect();
+            assert_eq!(loads.iter().sum::<u32>(), total);
+            // Within a tenth of an even split
+            let share = total.div_ceil(jobs as u32);
+            assert!(
+                loads.iter().all(|&load| load.abs_diff(share) <= share / 10),
+                "{:?}",
+                loads
+            );
+        }
+
+        // The long scene is shared between jobs
+        let split = split_jobs(&work, 3);
+        let holding_first = split
+            .iter()
+            .filter(|pieces| pieces.iter().any(|(_, dir)| dir == Path::new("0")))
+            .count();
+        assert_eq!(holding_first, 2);
+    }
+
+    /// Readings handed out in order, then the last one forever
+    struct FakeMemory(VecDeque<MemoryReading>);
+
+    impl MemoryReader for FakeMemory {
+        fn read(&mut self) -> Option<MemoryReading> {
+            match self.0.len() {
+                0 => None,
+                1 => self.0.front().copied(),
+                _ => self.0.pop_front(),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_memory_monitor_pauses_then_stops_blender() {
+        let gib = 1024 * 1024 * 1024;
+        let reading = |available_gib: f64| MemoryReading {
+            available: (available_gib * gib as f64) as u64,
+            total: 16 * gib,
+        };
+        let children = Arc::new(Children::default());
+        let mut monitor = MemoryMonitor {
+            reader: FakeMemory([reading(8.0), reading(1.0), reading(4.0), reading(0.25)].into()),
+            jobs: 2,
+            children: Arc::clone(&children),
+        };
+        children
+            .add(0, Command::new("sleep").arg("30").spawn().unwrap())
+            .unwrap();
+
+        assert!(monitor.check());
+        assert!(!*children.paused.lock().unwrap());
+        // Over 90% used: new processes wait for the running one
+        assert!(monitor.check());
+        assert!(*children.paused.lock().unwrap());
+        assert!(monitor.check());
+        assert!(!*children.paused.lock().unwrap());
+        children.wait_to_launch().unwrap();
+
+        // Under 256 MiB per job: Blender is killed and nothing starts again
+        assert!(!monitor.check());
+        let status = children.take(0).unwrap().wait().unwrap();
+        assert!(!status.success());
+        let reason = children.stop_reason().unwrap();
+        assert!(
+            reason.contains("256 MiB of memory left for 2 Blender jobs"),
+            "{}",
+            reason
+        );
+        assert!(children.wait_to_launch().is_err());
+        let late = Command::new("sleep").arg("30").spawn().unwrap();
+        assert!(children.add(1, late).is_err());
+        assert!(children.take(1).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_paused_launch_waits_for_running_jobs() {
+        let children = Arc::new(Children::default());
+        // Nothing running: a paused render still makes progress
+        children.set_paused(true);
+        children.wait_to_launch().unwrap();
+
+        children
+            .add(0, Command::new("sleep").arg("30").spawn().unwrap())
+            .unwrap();
+        let waiting = {
+            let children = Arc::clone(&children);
+            thread::spawn(move || children.wait_to_launch())
+        };
+        thread::sleep(Duration::from_millis(100));
+        assert!(!waiting.is_finished());
+
+        children.set_paused(false);
+        waiting.join().unwrap().unwrap();
+        let mut child = children.take(0).unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_memory_threshold_scales_with_jobs() {
+        let mib = 1024 * 1024;
+        let total = 16 * 1024 * mib;
+        assert!(!memory_critical(600 * mib, total, 2));
+        assert!(memory_critical(600 * mib, total, 4));
+        // Platforms without memory information are never critical
+        assert!(!memory_critical(0, 0, 4));
+    }
+
+    #[test]
+    fn test_parse_blender_output() {
+        let stdout = "Blender 4.2.1 LTS (hash 396f546c9d82 built 2024-08-19 11:21:12)\n\
+            Read prefs: \"/home/user/.config/blender/4.2/config/userpref.blend\"\n\
+            INTERSTELLAR_ERROR Image_intro_0: could not load image /assets/missing.png\n\
+            Fra:12 Mem:45.12M (Peak 46.00M) | Time:00:00.45 | Syncing Text_intro_1\n\
+            Fra:12 Mem:52.80M (Peak 60.31M) | Time:00:01.20 | Rendering 32 / 64 samples\n\
+            Saved: '/cache/blender/scenes/ab12/frame_0012.png'\n\
+             Time: 00:01.31 (Saving: 00:00.10)\n";
+        let parsed: Vec<BlenderLine> = stdout.lines().filter_map(parse_blender_line).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                BlenderLine::Error("Image_intro_0: could not load image /assets/missing.png"),
+                BlenderLine::Rendering(12),
+                BlenderLine::Rendering(12),
+                BlenderLine::Saved(Some(12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stall_monitor_reports_each_stall_once() {
+        let start = Instant::now();
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        let mut monitor = StallMonitor::new(3, Duration::from_secs(5 * 60), start);
+
+        monitor.progress(0, Some(12), minutes(3));
+        monitor.progress(1, None, minutes(1));
+        monitor.finish(2);
+        assert_eq!(monitor.newly_stalled(minutes(4)), vec![]);
+        assert_eq!(monitor.newly_stalled(minutes(6)), vec![(1, None)]);
+        assert_eq!(monitor.newly_stalled(minutes(7)), vec![]);
+
+        // Saved lines without a number keep the last frame seen
+        monitor.progress(0, None, minutes(7));
+        assert_eq!(monitor.newly_stalled(minutes(12)), vec![(0, Some(12))]);
+
+        // A stalled job that moves again can stall again
+        monitor.progress(1, Some(400), minutes(12));
+        assert_eq!(monitor.newly_stalled(minutes(17)), vec![(1, Some(400))]);
+    }
+
+    #[test]
+    fn test_render_settings_in_script() {
+        let script = test_script();
+        let py_script = BlenderRenderer::new().generate_python_script(&script, 0, 300);
+        assert!(py_script.contains("else 'BLENDER_EEVEE'\nscene.eevee.taa_render_samples = 64\n"));
+        assert!(py_script.contains("scene.render.film_transparent = False\n"));
+
+        let options: BlenderOptions = toml::from_str(
+            r#"
+            engine = "CYCLES"
+            samples = 256
+            use_transparent_background = true
+            device = "GPU"
+            "#,
+        )
+        .unwrap();
+        let cycles = BlenderRenderer::new().with_options(options);
+        let py_script = cycles.generate_python_script(&script, 0, 300);
+        assert!(py_script.contains("scene.render.engine = 'CYCLES'\n"));
+        assert!(py_script.contains("scene.cycles.samples = 256\n"));
+        assert!(py_script.contains("scene.cycles.device = 'GPU' if use_cycles_gpu() else 'CPU'\n"));
+        assert!(py_script.contains("scene.render.film_transparent = True\n"));
+        assert!(!py_script.contains("taa_render_samples"));
+
+        // Other quality settings are other frames
+        let sections = scene_sections(&script);
+        assert_ne!(
+            scene_hashes(
+                &BlenderRenderer::new().python_prelude(&script, 0, 300),
+                &sections
+            ),
+            scene_hashes(&cycles.python_prelude(&script, 0, 300), &sections)
+        );
+    }
+
+    #[test]
+    fn test_unknown_engine_is_rejected() {
+        let options: BlenderOptions = toml::from_str("engine = \"BLENDER_EEVEE\"").unwrap();
+        assert_eq!(options.engine, BlenderEngine::Eevee);
+        assert_eq!(options.device, BlenderDevice::Cpu);
+
+        let error = toml::from_str::<BlenderOptions>("engine = \"WORKBENCH\"").unwrap_err();
+        assert!(error.to_string().contains("WORKBENCH"), "{}", error);
+        assert!(toml::from_str::<BlenderOptions>("device = \"TPU\"").is_err());
+    }
+
+    #[test]
+    fn test_reported_error() {
+        assert_eq!(
+            reported_error("INTERSTELLAR_ERROR Mat_Video_a_0: could not decode movie a.mp4"),
+            Some("Mat_Video_a_0: could not decode movie a.mp4")
+        );
+        assert_eq!(reported_error("Fra:1 Mem:12.00M"), None);
     }
 }
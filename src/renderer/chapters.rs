@@ -0,0 +1,181 @@
+//! Chapter markers built from scene boundaries, written as YouTube description
+//! timestamps or as an FFmpeg metadata file that embeds them in the video.
+
+use crate::error::{Result, VideoEngineError};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// YouTube ignores chapter lists with chapters shorter than this
+pub const MIN_CHAPTER_SECONDS: f64 = 10.0;
+
+/// A titled point in the video
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start_seconds: f64,
+}
+
+/// Merge chapters shorter than `min_seconds` into the one before them, so every
+/// chapter of a video ending at `end_seconds` is at least that long (unless the
+/// whole video isn't). A short first chapter absorbs the chapters after it.
+pub fn merge_short_chapters(
+    chapters: &[Chapter],
+    end_seconds: f64,
+    min_seconds: f64,
+) -> Vec<Chapter> {
+    let mut merged: Vec<Chapter> = Vec::new();
+
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters
+            .get(i + 1)
+            .map_or(end_seconds, |next| next.start_seconds);
+        let long_enough = end - chapter.start_seconds >= min_seconds;
+        let previous_long_enough = merged
+            .last()
+            .is_none_or(|previous| chapter.start_seconds - previous.start_seconds >= min_seconds);
+
+        if merged.is_empty() || (long_enough && previous_long_enough) {
+            merged.push(chapter.clone());
+        }
+    }
+
+    merged
+}
+
+/// `M:SS`, or `H:MM:SS` from an hour on, as YouTube writes timestamps
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0).floor() as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// One `0:00 Title` line per chapter, ready to paste into a video description
+pub fn youtube_description(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .map(|chapter| {
+            format!(
+                "{} {}\n",
+                format_timestamp(chapter.start_seconds),
+                chapter.title
+            )
+        })
+        .collect()
+}
+
+/// An `FFMETADATA1` file describing the chapters, for FFmpeg's `-map_chapters`
+pub fn ffmetadata(chapters: &[Chapter], end_seconds: f64) -> String {
+    let mut out = String::from(";FFMETADATA1\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters
+            .get(i + 1)
+            .map_or(end_seconds, |next| next.start_seconds);
+        let _ = write!(
+            out,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (chapter.start_seconds * 1000.0).round() as u64,
+            (end * 1000.0).round() as u64,
+            escape_metadata(&chapter.title)
+        );
+    }
+    out
+}
+
+/// Write `content` to `path`, creating its directory
+pub fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(VideoEngineError::io("create", dir))?;
+    }
+    std::fs::write(path, content).map_err(VideoEngineError::io("write chapters to", path))
+}
+
+/// FFMETADATA values escape `=`, `;`, `#`, `\` and newlines with a backslash
+fn escape_metadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(title: &str, start_seconds: f64) -> Chapter {
+        Chapter {
+            title: title.into(),
+            start_seconds,
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0), "0:00");
+        assert_eq!(format_timestamp(65.9), "1:05");
+        assert_eq!(format_timestamp(599.0), "9:59");
+        assert_eq!(format_timestamp(3599.0), "59:59");
+        // Hours roll over into H:MM:SS
+        assert_eq!(format_timestamp(3600.0), "1:00:00");
+        assert_eq!(format_timestamp(3600.0 * 10.0 + 61.0), "10:01:01");
+    }
+
+    #[test]
+    fn test_short_chapters_merge_into_previous() {
+        let chapters = [
+            chapter("Intro", 0.0),
+            chapter("Setup", 30.0),
+            chapter("Aside", 45.0),
+            chapter("Payoff", 50.0),
+            chapter("Outro", 80.0),
+        ];
+        let merged = merge_short_chapters(&chapters, 85.0, MIN_CHAPTER_SECONDS);
+        // The 5s aside joins Setup, the 5s outro joins Payoff
+        assert_eq!(
+            merged,
+            vec![
+                chapter("Intro", 0.0),
+                chapter("Setup", 30.0),
+                chapter("Payoff", 50.0)
+            ]
+        );
+
+        // Nothing merges with a zero minimum
+        assert_eq!(
+            merge_short_chapters(&chapters, 85.0, 0.0),
+            chapters.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_short_first_chapter_absorbs_the_next() {
+        let chapters = [
+            chapter("Hook", 0.0),
+            chapter("Body", 4.0),
+            chapter("End", 30.0),
+        ];
+        let merged = merge_short_chapters(&chapters, 60.0, MIN_CHAPTER_SECONDS);
+        assert_eq!(merged, vec![chapter("Hook", 0.0), chapter("End", 30.0)]);
+    }
+
+    #[test]
+    fn test_description_and_ffmetadata() {
+        let chapters = [chapter("Intro", 0.0), chapter("Q&A; part=1", 3725.5)];
+        assert_eq!(
+            youtube_description(&chapters),
+            "0:00 Intro\n1:02:05 Q&A; part=1\n"
+        );
+
+        let metadata = ffmetadata(&chapters, 3800.0);
+        assert!(metadata.starts_with(";FFMETADATA1\n"));
+        assert!(metadata.contains("START=0\nEND=3725500\ntitle=Intro\n"));
+        assert!(metadata.contains("START=3725500\nEND=3800000\ntitle=Q&A\\; part\\=1\n"));
+    }
+}
@@ -0,0 +1,151 @@
+//! sRGB encoding helpers.
+//!
+//! Frames store sRGB-encoded 8-bit channels, but blending happens in linear light,
+//! the same way the GPU's `Rgba8UnormSrgb` targets blend, so both backends agree.
+
+use std::sync::LazyLock;
+
+// Everything is tabulated up front in f64, so encoding is only comparisons. The
+// tables are built with `pow` below rather than the platform's `powf`, whose last
+// bits differ between libms, so they're the same everywhere and the CPU path renders
+// bit-identical frames on every platform.
+
+/// Linear value of every 8-bit sRGB channel value
+static SRGB_TO_LINEAR: LazyLock<Vec<f32>> = LazyLock::new(|| decode_table(255));
+
+/// `ROUNDING[i]`: the linear value from which 8-bit sRGB rounds up to `i + 1`
+static SRGB_ROUNDING: LazyLock<Vec<f32>> = LazyLock::new(|| rounding_table(255));
+
+/// Linear value of every 16-bit sRGB channel value
+static SRGB16_TO_LINEAR: LazyLock<Vec<f32>> = LazyLock::new(|| decode_table(65535));
+
+/// `ROUNDING[i]`: the linear value from which 16-bit sRGB rounds up to `i + 1`
+static SRGB16_ROUNDING: LazyLock<Vec<f32>> = LazyLock::new(|| rounding_table(65535));
+
+fn decode(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        pow((c + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// `x` to the power `y`, for a positive normal `x` and a result in the normal
+/// range, made of additions, multiplications and divisions only. IEEE 754 rounds
+/// those exactly, so unlike `powf` the result is the same on every platform.
+fn pow(x: f64, y: f64) -> f64 {
+    exp(y * ln(x))
+}
+
+fn ln(x: f64) -> f64 {
+    // x = m * 2^e with m in [sqrt(1/2), sqrt(2)), then ln(m) = 2 atanh(t) for
+    // t = (m - 1) / (m + 1), whose series converges fast for |t| < 0.18
+    let bits = x.to_bits();
+    let mut e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if m > std::f64::consts::SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    let t = (m - 1.0) / (m + 1.0);
+    let t2 = t * t;
+    let mut term = t;
+    let mut sum = 0.0;
+    for k in 0..12 {
+        sum += term / (2 * k + 1) as f64;
+        term *= t2;
+    }
+    2.0 * sum + e as f64 * std::f64::consts::LN_2
+}
+
+fn exp(x: f64) -> f64 {
+    // x = n ln(2) + r with |r| <= ln(2) / 2, then e^x = 2^n e^r
+    let n = (x / std::f64::consts::LN_2).round();
+    let r = x - n * std::f64::consts::LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..20 {
+        term *= r / k as f64;
+        sum += term;
+    }
+    sum * f64::from_bits(((n as i64 + 1023) as u64) << 52)
+}
+
+fn decode_table(max: u32) -> Vec<f32> {
+    (0..=max)
+        .map(|value| decode(value as f64 / max as f64) as f32)
+        .collect()
+}
+
+fn rounding_table(max: u32) -> Vec<f32> {
+    (0..max)
+        .map(|value| decode((value as f64 + 0.5) / max as f64) as f32)
+        .collect()
+}
+
+/// Decode an sRGB channel to linear light (0.0-1.0)
+pub fn srgb_to_linear(value: u8) -> f32 {
+    SRGB_TO_LINEAR[value as usize]
+}
+
+/// Encode a linear-light channel (clamped to 0.0-1.0) as 8-bit sRGB, rounded to nearest
+pub fn linear_to_srgb(value: f32) -> u8 {
+    SRGB_ROUNDING.partition_point(|&start| start <= value) as u8
+}
+
+/// Decode a 16-bit sRGB channel to linear light (0.0-1.0)
+pub fn srgb16_to_linear(value: u16) -> f32 {
+    SRGB16_TO_LINEAR[value as usize]
+}
+
+/// Encode a linear-light channel (clamped to 0.0-1.0) as 16-bit sRGB, rounded to nearest
+pub fn linear_to_srgb16(value: f32) -> u16 {
+    SRGB16_ROUNDING.partition_point(|&start| start <= value) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_is_exact() {
+        for value in 0..=255u8 {
+            assert_eq!(linear_to_srgb(srgb_to_linear(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_16_bit_round_trip_and_agreement() {
+        for value in (0..=u16::MAX).step_by(97) {
+            assert_eq!(linear_to_srgb16(srgb16_to_linear(value)), value);
+        }
+        // 8-bit codes widen by 257 to the same light
+        for value in 0..=255u8 {
+            assert_eq!(srgb16_to_linear(value as u16 * 257), srgb_to_linear(value));
+        }
+    }
+
+    #[test]
+    fn test_pow_matches_std() {
+        for i in 1..=1000 {
+            let x = i as f64 / 1000.0;
+            let expected = x.powf(2.4);
+            assert!((pow(x, 2.4) - expected).abs() <= expected * 1e-14, "{}", x);
+        }
+        assert_eq!(pow(1.0, 2.4), 1.0);
+        assert_eq!(pow(2.0, 3.0), 8.0);
+    }
+
+    #[test]
+    fn test_known_values() {
+        assert_eq!(srgb_to_linear(0), 0.0);
+        assert_eq!(srgb_to_linear(255), 1.0);
+        assert!((srgb_to_linear(128) - 0.2158).abs() < 0.001);
+        // Half the light is well above half the code value
+        assert_eq!(linear_to_srgb(0.5), 188);
+        // Out of range values clamp
+        assert_eq!(linear_to_srgb(-0.5), 0);
+        assert_eq!(linear_to_srgb(1.5), 255);
+        assert_eq!(linear_to_srgb16(2.0), 65535);
+    }
+}
@@ -1,5 +1,139 @@
 use crate::renderer::FrameBuffer;
-use crate::script::Transform;
+use crate::script::{Transform, WaveformStyle};
+use ab_glyph::{point, Font, Glyph, PxScale, ScaleFont};
+use image::{GenericImageView, Rgba};
+use std::ops::Range;
+
+/// Rectangle as (x, y, width, height)
+pub type Rect = (i32, i32, u32, u32);
+
+/// Affine mapping from layer-local pixels to frame pixels:
+/// `x' = a*x + c*y + tx`, `y' = b*x + d*y + ty`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Affine {
+    /// Map a layer-local point into the frame
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.tx,
+            self.b * x + self.d * y + self.ty,
+        )
+    }
+
+    /// Inverse mapping (frame to layer-local), or `None` for a zero scale
+    pub fn inverse(&self) -> Option<Affine> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Affine {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    /// Rotation by `degrees` clockwise on screen about `pivot`, the same
+    /// convention as `Transform.rotation`
+    pub fn rotation_about(degrees: f32, pivot: (f32, f32)) -> Affine {
+        let (sin, cos) = sin_cos_degrees(degrees);
+        let (px, py) = pivot;
+        Affine {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            tx: px - (cos * px - sin * py),
+            ty: py - (sin * px + cos * py),
+        }
+    }
+
+    /// Frame positions of a `width` x `height` layer's corners:
+    /// top-left, top-right, bottom-right, bottom-left
+    pub fn corners(&self, width: u32, height: u32) -> [(f32, f32); 4] {
+        let (w, h) = (width as f32, height as f32);
+        [
+            self.apply(0.0, 0.0),
+            self.apply(w, 0.0),
+            self.apply(w, h),
+            self.apply(0.0, h),
+        ]
+    }
+}
+
+/// Sine and cosine of an angle in degrees, made of additions, multiplications
+/// and divisions only. IEEE 754 rounds those exactly, so unlike `sin_cos` the
+/// result is the same on every platform, and right angles are exact.
+fn sin_cos_degrees(degrees: f32) -> (f32, f32) {
+    // Reduced to a quadrant and an angle in it, both exactly
+    let degrees = (degrees as f64).rem_euclid(360.0);
+    let quadrant = (degrees / 90.0).floor();
+    let x = (degrees - quadrant * 90.0).to_radians();
+    // Taylor series, converging fast for x in [0, pi/2)
+    let (mut sin, mut cos) = (0.0, 0.0);
+    let (mut sin_term, mut cos_term) = (x, 1.0);
+    for k in 1..=12 {
+        sin += sin_term;
+        cos += cos_term;
+        let n = (2 * k) as f64;
+        sin_term *= -x * x / (n * (n + 1.0));
+        cos_term *= -x * x / ((n - 1.0) * n);
+    }
+    let (sin, cos) = match quadrant as u8 {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    };
+    (sin as f32, cos as f32)
+}
+
+/// The part of `bounds` (`[left, top, right, bottom]` in frame pixels) inside a
+/// `frame_width` x `frame_height` frame, or `None` when none of it is visible.
+/// The CPU and GPU paths both clip with this, so they agree on what gets drawn.
+pub fn clip_to_frame(bounds: [f32; 4], frame_width: u32, frame_height: u32) -> Option<[f32; 4]> {
+    let [left, top, right, bottom] = bounds;
+    let clipped = [
+        left.max(0.0),
+        top.max(0.0),
+        right.min(frame_width as f32),
+        bottom.min(frame_height as f32),
+    ];
+    (clipped[0] < clipped[2] && clipped[1] < clipped[3]).then_some(clipped)
+}
+
+/// Pixel columns and rows of a rectangle that lie inside the buffer
+fn visible_pixels(
+    buffer: &FrameBuffer,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(Range<u32>, Range<u32>)> {
+    let (buf_width, buf_height) = buffer.dimensions();
+    let (left, top) = (x as f32, y as f32);
+    let [x0, y0, x1, y1] = clip_to_frame(
+        [left, top, left + width as f32, top + height as f32],
+        buf_width,
+        buf_height,
+    )?;
+    Some((x0 as u32..x1 as u32, y0 as u32..y1 as u32))
+}
 
 /// Layer compositor
 pub struct Compositor;
@@ -13,22 +147,113 @@ impl Compositor {
         width: u32,
         height: u32,
         color: [u8; 4],
+    ) {
+        if let Some((columns, rows)) = visible_pixels(buffer, x, y, width, height) {
+            buffer.fill_region(columns, rows, color);
+        }
+    }
+
+    /// Alpha-blend a rectangle of `color` over the buffer
+    pub fn blend_rect(
+        buffer: &mut FrameBuffer,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) {
+        let Some((columns, rows)) = visible_pixels(buffer, x, y, width, height) else {
+            return;
+        };
+
+        for py in rows {
+            for px in columns.clone() {
+                buffer.blend_pixel(px, py, color);
+            }
+        }
+    }
+
+    /// Multiply a layer opacity (clamped to 0.0-1.0) into a color's alpha
+    pub fn apply_opacity(color: [u8; 4], opacity: f32) -> [u8; 4] {
+        let alpha = (color[3] as f32 * opacity.clamp(0.0, 1.0)).round() as u8;
+        [color[0], color[1], color[2], alpha]
+    }
+
+    /// Rasterize `text` at `font_size` pixels and alpha-blend it into the buffer.
+    /// `(x, y)` is the top-left of the first line box; the baseline sits one ascent
+    /// below `y`. Kerning is applied between glyph pairs and `\n` starts a new line.
+    pub fn draw_text(
+        buffer: &mut FrameBuffer,
+        font: &impl Font,
+        text: &str,
+        font_size: f32,
+        x: i32,
+        y: i32,
+        color: [u8; 4],
     ) {
         let (buf_width, buf_height) = buffer.dimensions();
+        let scaled = font.as_scaled(PxScale::from(font_size));
+        let line_height = scaled.height() + scaled.line_gap();
 
-        for dy in 0..height {
-            for dx in 0..width {
-                let px = x + dx as i32;
-                let py = y + dy as i32;
+        for (line_index, line) in text.lines().enumerate() {
+            let baseline = y as f32 + scaled.ascent() + line_index as f32 * line_height;
+            let mut caret = x as f32;
+            let mut previous = None;
 
-                if px >= 0 && py >= 0 && (px as u32) < buf_width && (py as u32) < buf_height {
-                    buffer.set_pixel(px as u32, py as u32, color);
+            for c in line.chars() {
+                let id = scaled.glyph_id(c);
+                if let Some(prev) = previous {
+                    caret += scaled.kern(prev, id);
                 }
+                let glyph: Glyph = id.with_scale_and_position(font_size, point(caret, baseline));
+                caret += scaled.h_advance(id);
+                previous = Some(id);
+
+                let Some(outlined) = font.outline_glyph(glyph) else {
+                    // Whitespace has no outline
+                    continue;
+                };
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let px = bounds.min.x as i32 + gx as i32;
+                    let py = bounds.min.y as i32 + gy as i32;
+                    let alpha = (coverage.clamp(0.0, 1.0) * color[3] as f32).round() as u8;
+                    if alpha > 0
+                        && px >= 0
+                        && py >= 0
+                        && (px as u32) < buf_width
+                        && (py as u32) < buf_height
+                    {
+                        buffer.blend_pixel(
+                            px as u32,
+                            py as u32,
+                            [color[0], color[1], color[2], alpha],
+                        );
+                    }
+                });
             }
         }
     }
 
-    /// Draw text (placeholder - simple rectangle for now)
+    /// Width in pixels of the widest line of `text` as `draw_text` lays it out
+    pub fn text_width(font: &impl Font, text: &str, font_size: f32) -> f32 {
+        let scaled = font.as_scaled(PxScale::from(font_size));
+        text.lines()
+            .map(|line| {
+                let mut previous = None;
+                line.chars()
+                    .map(|c| {
+                        let id = scaled.glyph_id(c);
+                        let kern = previous.map_or(0.0, |prev| scaled.kern(prev, id));
+                        previous = Some(id);
+                        kern + scaled.h_advance(id)
+                    })
+                    .sum::<f32>()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Draw text (placeholder - simple rectangle, used when the font cannot be parsed)
     pub fn draw_text_placeholder(
         buffer: &mut FrameBuffer,
         text: &str,
@@ -42,16 +267,188 @@ impl Compositor {
         Self::fill_rect(buffer, x, y, width, height, color);
     }
 
-    /// Apply transform to coordinates
-    pub fn apply_transform(x: i32, y: i32, transform: &Transform) -> (i32, i32) {
-        // Apply position offset
-        let tx = x + transform.position.x;
-        let ty = y + transform.position.y;
+    /// Rectangles that draw `levels` (0.0-1.0) as a waveform inside the given box.
+    /// Shared by the CPU compositor and the GPU quad batcher.
+    pub fn waveform_rects(
+        levels: &[f32],
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        style: WaveformStyle,
+    ) -> Vec<Rect> {
+        if levels.is_empty() || width == 0 || height == 0 {
+            return Vec::new();
+        }
+
+        let level_height = |level: f32| (level.clamp(0.0, 1.0) * height as f32) as u32;
+
+        match style {
+            WaveformStyle::Bars => {
+                // Bars take three quarters of their slot, mirrored around the center line
+                let slot = (width / levels.len() as u32).max(1);
+                let bar_width = (slot * 3 / 4).max(1);
+                levels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &level)| {
+                        let bar_height = level_height(level).max(2).min(height);
+                        (
+                            x + (i as u32 * slot) as i32,
+                            y + ((height - bar_height) / 2) as i32,
+                            bar_width,
+                            bar_height,
+                        )
+                    })
+                    .collect()
+            }
+            WaveformStyle::Line => {
+                // Trace the top of the level envelope, one column at a time
+                let top = |column: u32| {
+                    let position = column as f32 / width as f32 * (levels.len() - 1) as f32;
+                    let index = position as usize;
+                    let next = levels[(index + 1).min(levels.len() - 1)];
+                    let level = levels[index] + (next - levels[index]) * position.fract();
+                    y + ((height - level_height(level)) / 2) as i32
+                };
+
+                let mut previous = top(0);
+                (0..width)
+                    .map(|column| {
+                        let current = top(column);
+                        // Stretch the segment vertically so steep slopes stay connected
+                        let (from, to) = (previous.min(current), previous.max(current));
+                        previous = current;
+                        (x + column as i32, from, 1, (to - from) as u32 + 2)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Draw a waveform of `levels` (0.0-1.0) inside the given box
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_waveform(
+        buffer: &mut FrameBuffer,
+        levels: &[f32],
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        style: WaveformStyle,
+        color: [u8; 4],
+    ) {
+        for (rx, ry, rw, rh) in Self::waveform_rects(levels, x, y, width, height, style) {
+            Self::fill_rect(buffer, rx, ry, rw, rh, color);
+        }
+    }
+
+    /// Mapping for a layer with the given transform. `position` is the layer's
+    /// top-left corner and also the anchor it is scaled and rotated about;
+    /// `rotation` is in degrees, clockwise on screen.
+    pub fn apply_transform(transform: &Transform) -> Affine {
+        let (sin, cos) = sin_cos_degrees(transform.rotation);
+        let scale = transform.scale;
+        Affine {
+            a: scale * cos,
+            b: scale * sin,
+            c: -scale * sin,
+            d: scale * cos,
+            tx: transform.position.x as f32,
+            ty: transform.position.y as f32,
+        }
+    }
 
-        // TODO: Apply scale and rotation
-        // For now, just position offset
+    /// Draw an image through `transform` with bilinear filtering.
+    /// Source alpha is multiplied by `opacity` (clamped to 0.0-1.0) and blended with `blend_pixel`.
+    /// `image` may be a view (e.g. a crop), which keeps filtering inside its bounds.
+    pub fn draw_image<I: GenericImageView<Pixel = Rgba<u8>>>(
+        buffer: &mut FrameBuffer,
+        image: &I,
+        transform: &Affine,
+        opacity: f32,
+    ) {
+        let (img_width, img_height) = image.dimensions();
+        let Some(inverse) = transform.inverse() else {
+            return;
+        };
+        let opacity = opacity.clamp(0.0, 1.0);
+        if img_width == 0 || img_height == 0 || opacity == 0.0 {
+            return;
+        }
 
-        (tx, ty)
+        // Only visit frame pixels inside the transformed bounding box
+        let corners = transform.corners(img_width, img_height);
+        let (buf_width, buf_height) = buffer.dimensions();
+        let min_x = corners
+            .iter()
+            .map(|p| p.0)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let min_y = corners
+            .iter()
+            .map(|p| p.1)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_x = corners
+            .iter()
+            .map(|p| p.0)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .max(0.0) as u32;
+        let max_y = corners
+            .iter()
+            .map(|p| p.1)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .max(0.0) as u32;
+
+        for py in min_y..max_y.min(buf_height) {
+            for px in min_x..max_x.min(buf_width) {
+                // Sample at the pixel center
+                let (u, v) = inverse.apply(px as f32 + 0.5, py as f32 + 0.5);
+                if u < 0.0 || v < 0.0 || u >= img_width as f32 || v >= img_height as f32 {
+                    continue;
+                }
+                let mut color = Self::sample_bilinear(image, u, v);
+                color[3] = (color[3] as f32 * opacity).round() as u8;
+                if color[3] > 0 {
+                    buffer.blend_pixel(px, py, color);
+                }
+            }
+        }
+    }
+
+    /// Bilinear sample at continuous source coordinates (texel centers at +0.5)
+    fn sample_bilinear<I: GenericImageView<Pixel = Rgba<u8>>>(
+        image: &I,
+        u: f32,
+        v: f32,
+    ) -> [u8; 4] {
+        let (width, height) = image.dimensions();
+        let fx = (u - 0.5).max(0.0);
+        let fy = (v - 0.5).max(0.0);
+        let x0 = (fx as u32).min(width - 1);
+        let y0 = (fy as u32).min(height - 1);
+        let x1 = (x0 + 1).min(width - 1);
+        let y1 = (y0 + 1).min(height - 1);
+        let wx = fx - x0 as f32;
+        let wy = fy - y0 as f32;
+
+        let p00 = image.get_pixel(x0, y0).0;
+        let p10 = image.get_pixel(x1, y0).0;
+        let p01 = image.get_pixel(x0, y1).0;
+        let p11 = image.get_pixel(x1, y1).0;
+
+        let mut out = [0u8; 4];
+        for i in 0..4 {
+            let top = p00[i] as f32 * (1.0 - wx) + p10[i] as f32 * wx;
+            let bottom = p01[i] as f32 * (1.0 - wx) + p11[i] as f32 * wx;
+            out[i] = (top * (1.0 - wy) + bottom * wy).round() as u8;
+        }
+        out
     }
 }
 
@@ -60,6 +457,36 @@ mod tests {
     use super::*;
     use crate::script::Position;
 
+    #[test]
+    fn test_sin_cos_matches_std_and_is_exact_at_right_angles() {
+        for tenth in -3600..=3600 {
+            let degrees = tenth as f32 / 10.0;
+            let (sin, cos) = sin_cos_degrees(degrees);
+            let (expected_sin, expected_cos) = (degrees as f64).to_radians().sin_cos();
+            assert!((sin as f64 - expected_sin).abs() < 1e-6, "{}", degrees);
+            assert!((cos as f64 - expected_cos).abs() < 1e-6, "{}", degrees);
+        }
+        assert_eq!(sin_cos_degrees(90.0), (1.0, 0.0));
+        assert_eq!(sin_cos_degrees(180.0), (0.0, -1.0));
+        assert_eq!(sin_cos_degrees(-90.0), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_rotation_about_pivot_is_clockwise() {
+        let rotation = Affine::rotation_about(90.0, (10.0, 10.0));
+        let close = |(x, y): (f32, f32), (ex, ey): (f32, f32)| {
+            assert!(
+                (x - ex).abs() < 1e-4 && (y - ey).abs() < 1e-4,
+                "{:?}",
+                (x, y)
+            );
+        };
+        close(rotation.apply(10.0, 10.0), (10.0, 10.0));
+        // Right of the pivot turns to below it (y grows downwards)
+        close(rotation.apply(20.0, 10.0), (10.0, 20.0));
+        close(rotation.apply(10.0, 20.0), (0.0, 10.0));
+    }
+
     #[test]
     fn test_fill_rect() {
         let mut fb = FrameBuffer::new(100, 100);
@@ -74,16 +501,282 @@ mod tests {
         assert_eq!(fb.get_pixel(5, 5), Some([0, 0, 0, 255]));
     }
 
+    #[test]
+    fn test_clip_to_frame() {
+        assert_eq!(
+            clip_to_frame([-50.0, -50.0, 50.0, 50.0], 100, 100),
+            Some([0.0, 0.0, 50.0, 50.0])
+        );
+        assert_eq!(
+            clip_to_frame([90.0, 10.0, 130.0, 20.0], 100, 100),
+            Some([90.0, 10.0, 100.0, 20.0])
+        );
+        // Entirely off-screen, or only touching an edge
+        assert_eq!(clip_to_frame([-20.0, 0.0, -10.0, 10.0], 100, 100), None);
+        assert_eq!(clip_to_frame([100.0, 0.0, 120.0, 10.0], 100, 100), None);
+        assert_eq!(clip_to_frame([10.0, 10.0, 10.0, 20.0], 100, 100), None);
+    }
+
+    /// `fill_rect` as it was, one bounds-checked pixel at a time
+    fn fill_rect_per_pixel(
+        buffer: &mut FrameBuffer,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) {
+        for dy in 0..height {
+            for dx in 0..width {
+                let (px, py) = (x + dx as i32, y + dy as i32);
+                if px >= 0 && py >= 0 {
+                    buffer.set_pixel(px as u32, py as u32, color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_rect_matches_per_pixel_fill_at_edges() {
+        let rects = [
+            (-5, -5, 10, 10),
+            (15, -3, 10, 6),
+            (-2, 12, 30, 4),
+            (18, 14, 4, 4),
+            (0, 0, 20, 16),
+            (-10, -10, 40, 40),
+            (25, 3, 5, 5),
+            (3, 3, 0, 7),
+        ];
+        for (i, &(x, y, width, height)) in rects.iter().enumerate() {
+            let mut fast = FrameBuffer::new(20, 16);
+            fast.clear([0, 0, 0, 255]);
+            let mut reference = fast.clone();
+            let color = [i as u8 * 30, 200, 100, 255];
+
+            Compositor::fill_rect(&mut fast, x, y, width, height, color);
+            fill_rect_per_pixel(&mut reference, x, y, width, height, color);
+            assert_eq!(
+                fast.as_bytes(),
+                reference.as_bytes(),
+                "{:?}",
+                (x, y, width, height)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fill_rect_clips_negative_position() {
+        let mut fb = FrameBuffer::new(100, 100);
+        fb.clear([0, 0, 0, 255]);
+
+        Compositor::fill_rect(&mut fb, -50, -50, 100, 100, [255, 0, 0, 255]);
+        Compositor::fill_rect(&mut fb, 200, -500, 10, 10, [0, 255, 0, 255]);
+
+        for y in 0..100 {
+            for x in 0..100 {
+                let expected = if x < 50 && y < 50 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 255]
+                };
+                assert_eq!(fb.get_pixel(x, y), Some(expected), "({}, {})", x, y);
+            }
+        }
+    }
+
+    fn test_font() -> ab_glyph::FontRef<'static> {
+        ab_glyph::FontRef::try_from_slice(crate::renderer::TEST_FONT).unwrap()
+    }
+
+    /// Pixels that differ from the black background, and their bounding box
+    fn ink(fb: &FrameBuffer) -> (usize, Option<(u32, u32, u32, u32)>) {
+        let (width, height) = fb.dimensions();
+        let mut count = 0;
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for y in 0..height {
+            for x in 0..width {
+                if fb.get_pixel(x, y) != Some([0, 0, 0, 255]) {
+                    count += 1;
+                    bounds = Some(match bounds {
+                        None => (x, y, x, y),
+                        Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    });
+                }
+            }
+        }
+        (count, bounds)
+    }
+
+    #[test]
+    fn test_draw_text_ink_coverage() {
+        let font = test_font();
+        let mut fb = FrameBuffer::new(300, 80);
+        fb.clear([0, 0, 0, 255]);
+        Compositor::draw_text(&mut fb, &font, "Hello", 32.0, 10, 10, [255, 255, 255, 255]);
+
+        let (count, bounds) = ink(&fb);
+        assert!((500..2500).contains(&count), "ink pixels {}", count);
+
+        // y is the top of the line box: nothing above it, and the glyphs fit one line
+        let (x0, y0, x1, y1) = bounds.unwrap();
+        assert!(x0 >= 10 && y0 >= 10, "bounds {:?}", bounds);
+        assert!(y1 < 10 + 38, "bounds {:?}", bounds);
+        assert!(x1 - x0 > 60 && x1 - x0 < 110, "bounds {:?}", bounds);
+    }
+
+    #[test]
+    fn test_draw_text_honors_alpha() {
+        let font = test_font();
+        let mut opaque = FrameBuffer::new(100, 60);
+        opaque.clear([0, 0, 0, 255]);
+        Compositor::draw_text(&mut opaque, &font, "I", 40.0, 10, 0, [255, 0, 0, 255]);
+        let mut faded = FrameBuffer::new(100, 60);
+        faded.clear([0, 0, 0, 255]);
+        Compositor::draw_text(&mut faded, &font, "I", 40.0, 10, 0, [255, 0, 0, 128]);
+
+        let brightest = |fb: &FrameBuffer| {
+            (0..60)
+                .flat_map(|y| (0..100).map(move |x| (x, y)))
+                .map(|(x, y)| fb.get_pixel(x, y).unwrap()[0])
+                .max()
+                .unwrap()
+        };
+        assert_eq!(brightest(&opaque), 255);
+        // Half the light of full red, blended in linear light
+        assert!((180..=192).contains(&brightest(&faded)));
+    }
+
+    #[test]
+    fn test_draw_text_applies_kerning() {
+        let font = test_font();
+        let size = 48.0;
+        let mut kerned = FrameBuffer::new(200, 80);
+        kerned.clear([0, 0, 0, 255]);
+        Compositor::draw_text(&mut kerned, &font, "Vo", size, 0, 0, [255, 255, 255, 255]);
+
+        // The same pair placed by advance width alone
+        let advance = font
+            .as_scaled(PxScale::from(size))
+            .h_advance(font.glyph_id('V'));
+        let mut unkerned = FrameBuffer::new(200, 80);
+        unkerned.clear([0, 0, 0, 255]);
+        Compositor::draw_text(&mut unkerned, &font, "V", size, 0, 0, [255, 255, 255, 255]);
+        Compositor::draw_text(
+            &mut unkerned,
+            &font,
+            "o",
+            size,
+            advance.round() as i32,
+            0,
+            [255, 255, 255, 255],
+        );
+
+        let kerned_right = ink(&kerned).1.unwrap().2;
+        let unkerned_right = ink(&unkerned).1.unwrap().2;
+        assert!(
+            kerned_right + 1 < unkerned_right,
+            "kerned {} vs unkerned {}",
+            kerned_right,
+            unkerned_right
+        );
+    }
+
+    #[test]
+    fn test_waveform_bars_scale_with_level() {
+        let rects =
+            Compositor::waveform_rects(&[0.0, 0.5, 1.0], 0, 0, 300, 100, WaveformStyle::Bars);
+        assert_eq!(rects.len(), 3);
+        let heights: Vec<u32> = rects.iter().map(|r| r.3).collect();
+        assert_eq!(heights, vec![2, 50, 100]);
+        // Bars are centered vertically
+        assert_eq!(rects[1].1, 25);
+    }
+
+    #[test]
+    fn test_waveform_line_is_connected() {
+        let rects = Compositor::waveform_rects(&[0.0, 1.0], 10, 0, 50, 100, WaveformStyle::Line);
+        assert_eq!(rects.len(), 50);
+        for pair in rects.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(b.0, a.0 + 1);
+            // Each column overlaps the previous one vertically
+            assert!(b.1 <= a.1 + a.3 as i32 && a.1 <= b.1 + b.3 as i32);
+        }
+    }
+
     #[test]
     fn test_apply_transform() {
         let transform = Transform {
             position: Position { x: 100, y: 50 },
-            scale: 1.0,
+            scale: 2.0,
             rotation: 0.0,
             opacity: 1.0,
         };
 
-        let (tx, ty) = Compositor::apply_transform(10, 20, &transform);
-        assert_eq!((tx, ty), (110, 70));
+        let affine = Compositor::apply_transform(&transform);
+        assert_eq!(affine.apply(10.0, 20.0), (120.0, 90.0));
+
+        let inverse = affine.inverse().unwrap();
+        let (x, y) = inverse.apply(120.0, 90.0);
+        assert!((x - 10.0).abs() < 1e-4 && (y - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_draw_image_rotated_90() {
+        // Red then green, side by side
+        let image = image::RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([0, 255, 0, 255])
+            }
+        });
+        let transform = Transform {
+            position: Position { x: 10, y: 10 },
+            scale: 1.0,
+            rotation: 90.0,
+            opacity: 1.0,
+        };
+
+        let mut fb = FrameBuffer::new(20, 20);
+        fb.clear([0, 0, 0, 255]);
+        Compositor::draw_image(
+            &mut fb,
+            &image,
+            &Compositor::apply_transform(&transform),
+            1.0,
+        );
+
+        // Rotated clockwise about the top-left anchor, the row becomes a column left of it
+        assert_eq!(fb.get_pixel(9, 10), Some([255, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(9, 11), Some([0, 255, 0, 255]));
+        assert_eq!(ink(&fb).0, 2);
+    }
+
+    #[test]
+    fn test_draw_image_scale_and_opacity() {
+        let image = image::RgbaImage::from_pixel(2, 2, image::Rgba([200, 200, 200, 255]));
+        let transform = Transform {
+            position: Position { x: 4, y: 4 },
+            scale: 3.0,
+            rotation: 0.0,
+            opacity: 0.5,
+        };
+
+        let mut fb = FrameBuffer::new(20, 20);
+        fb.clear([0, 0, 0, 255]);
+        Compositor::draw_image(
+            &mut fb,
+            &image,
+            &Compositor::apply_transform(&transform),
+            transform.opacity,
+        );
+
+        let (count, bounds) = ink(&fb);
+        assert_eq!(count, 36);
+        assert_eq!(bounds, Some((4, 4, 9, 9)));
+        // Half of gray 200's light over black
+        assert_eq!(fb.get_pixel(6, 6), Some([147, 147, 147, 255]));
     }
 }
@@ -0,0 +1,205 @@
+//! Contact sheets: one frame of every scene in a labeled grid, to check a
+//! script before a full render.
+
+use crate::renderer::{Compositor, FrameBuffer, ResizeFilter, Timeline};
+use ab_glyph::Font;
+use std::ops::Range;
+
+/// Gap around and between cells, in pixels
+pub const PADDING: u32 = 8;
+/// Height of the label strip under each thumbnail, in pixels
+pub const LABEL_HEIGHT: u32 = 20;
+
+const BACKGROUND: [u8; 4] = [24, 24, 24, 255];
+const LABEL_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// Which frame of a scene its cell shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SceneFrame {
+    Start,
+    #[default]
+    Mid,
+    End,
+}
+
+impl SceneFrame {
+    /// The frame of `frames` to show, `None` for a scene without frames
+    pub fn pick(self, frames: Range<u32>) -> Option<u32> {
+        if frames.is_empty() {
+            return None;
+        }
+        Some(match self {
+            SceneFrame::Start => frames.start,
+            SceneFrame::Mid => frames.start + (frames.end - frames.start) / 2,
+            SceneFrame::End => frames.end - 1,
+        })
+    }
+}
+
+/// Where each cell of a contact sheet goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactSheetLayout {
+    pub cells: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub thumb_width: u32,
+    pub thumb_height: u32,
+}
+
+impl ContactSheetLayout {
+    /// A grid of `cells` thumbnails `thumb_width` wide, keeping the video's
+    /// aspect ratio. No more columns than cells are used.
+    pub fn new(cells: u32, columns: u32, thumb_width: u32, video_size: (u32, u32)) -> Self {
+        let columns = columns.clamp(1, cells.max(1));
+        let thumb_width = thumb_width.max(1);
+        let (width, height) = video_size;
+        let thumb_height =
+            ((thumb_width as f32 * height as f32 / width.max(1) as f32).round() as u32).max(1);
+        Self {
+            cells,
+            columns,
+            rows: cells.div_ceil(columns),
+            thumb_width,
+            thumb_height,
+        }
+    }
+
+    /// Size of the whole sheet
+    pub fn dimensions(&self) -> (u32, u32) {
+        (
+            self.columns * self.thumb_width + (self.columns + 1) * PADDING,
+            self.rows * (self.thumb_height + LABEL_HEIGHT) + (self.rows + 1) * PADDING,
+        )
+    }
+
+    /// Top-left corner of cell `index`'s thumbnail, filling rows left to right
+    pub fn cell_origin(&self, index: u32) -> (u32, u32) {
+        let (column, row) = (index % self.columns, index / self.columns);
+        (
+            PADDING + column * (self.thumb_width + PADDING),
+            PADDING + row * (self.thumb_height + LABEL_HEIGHT + PADDING),
+        )
+    }
+}
+
+/// Time to render `frame` at: the middle of the frame, so rounding can't land
+/// on the one before
+pub fn frame_time(timeline: &Timeline, frame: u32) -> f32 {
+    timeline.frame_to_time(frame) + 0.5 * timeline.frame_to_time(1)
+}
+
+/// `{scene id} @ {seconds}s`, the label under a cell
+pub fn label(scene_id: &str, seconds: f32) -> String {
+    format!("{} @ {:.1}s", scene_id, seconds)
+}
+
+/// Lay out `frames` with their labels. Labels are left out without a font.
+pub fn compose(
+    frames: &[(FrameBuffer, String)],
+    layout: &ContactSheetLayout,
+    font: Option<&impl Font>,
+) -> FrameBuffer {
+    let (width, height) = layout.dimensions();
+    let mut sheet = FrameBuffer::new(width, height);
+    sheet.clear(BACKGROUND);
+
+    for (index, (frame, text)) in frames.iter().enumerate() {
+        let (x, y) = layout.cell_origin(index as u32);
+        let thumb = frame.resize(
+            layout.thumb_width,
+            layout.thumb_height,
+            ResizeFilter::Bilinear,
+        );
+        sheet.blit(&thumb, x as i32, y as i32);
+
+        if let Some(font) = font {
+            // Shrunk to fit under its thumbnail
+            let mut font_size = LABEL_HEIGHT as f32 * 0.7;
+            let text_width = Compositor::text_width(font, text, font_size);
+            if text_width > layout.thumb_width as f32 {
+                font_size *= layout.thumb_width as f32 / text_width;
+            }
+            let label_y = y + layout.thumb_height + (LABEL_HEIGHT - font_size as u32) / 2;
+            Compositor::draw_text(
+                &mut sheet,
+                font,
+                text,
+                font_size,
+                x as i32,
+                label_y as i32,
+                LABEL_COLOR,
+            );
+        }
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_frame_pick() {
+        assert_eq!(SceneFrame::Start.pick(10..20), Some(10));
+        assert_eq!(SceneFrame::Mid.pick(10..20), Some(15));
+        assert_eq!(SceneFrame::End.pick(10..20), Some(19));
+        assert_eq!(SceneFrame::Mid.pick(10..11), Some(10));
+        assert_eq!(SceneFrame::Mid.pick(10..10), None);
+    }
+
+    #[test]
+    fn test_layout_dimensions() {
+        // 5 scenes in 3 columns of 16:9 thumbnails make 2 rows
+        let layout = ContactSheetLayout::new(5, 3, 160, (1920, 1080));
+        assert_eq!((layout.columns, layout.rows), (3, 2));
+        assert_eq!(layout.thumb_height, 90);
+        assert_eq!(
+            layout.dimensions(),
+            (3 * 160 + 4 * PADDING, 2 * (90 + LABEL_HEIGHT) + 3 * PADDING)
+        );
+        assert_eq!(layout.cell_origin(0), (PADDING, PADDING));
+        assert_eq!(
+            layout.cell_origin(4),
+            (2 * PADDING + 160, 2 * PADDING + 90 + LABEL_HEIGHT)
+        );
+
+        // Never more columns than scenes
+        let layout = ContactSheetLayout::new(2, 4, 160, (1920, 1080));
+        assert_eq!((layout.columns, layout.rows), (2, 1));
+    }
+
+    #[test]
+    fn test_compose_places_every_cell() {
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+        let frames: Vec<(FrameBuffer, String)> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let mut frame = FrameBuffer::new(64, 36);
+                frame.clear(*color);
+                (frame, label(&format!("scene{}", i), i as f32))
+            })
+            .collect();
+        let layout = ContactSheetLayout::new(frames.len() as u32, 2, 32, (64, 36));
+        let font = ab_glyph::FontRef::try_from_slice(crate::renderer::TEST_FONT).unwrap();
+        let sheet = compose(&frames, &layout, Some(&font));
+
+        assert_eq!(sheet.dimensions(), layout.dimensions());
+        for (i, color) in colors.iter().enumerate() {
+            let (x, y) = layout.cell_origin(i as u32);
+            assert_eq!(sheet.get_pixel(x + 16, y + 9), Some(*color));
+        }
+        // The fourth cell of the grid stays empty
+        let (x, y) = layout.cell_origin(3);
+        assert_eq!(sheet.get_pixel(x + 16, y + 9), Some(BACKGROUND));
+
+        // Labels are drawn under the thumbnails
+        let (x, y) = layout.cell_origin(0);
+        let label_row = y + layout.thumb_height..y + layout.thumb_height + LABEL_HEIGHT;
+        let lit = label_row
+            .flat_map(|y| (x..x + layout.thumb_width).map(move |x| (x, y)))
+            .filter(|&(x, y)| sheet.get_pixel(x, y) != Some(BACKGROUND))
+            .count();
+        assert!(lit > 0);
+    }
+}
@@ -0,0 +1,230 @@
+//! Layer types drawn by plugins. A layer whose `type` isn't built in parses
+//! as a [`Layer::Custom`](crate::script::Layer::Custom), and the engine draws
+//! it with the [`LayerRenderer`] registered for that type:
+//!
+//! ```
+//! use interstellar_triangulum::renderer::{ProgressBarLayer, RenderEngine};
+//! use interstellar_triangulum::ScriptParser;
+//!
+//! let script = ScriptParser::parse_json_str(r#"{
+//!     "metadata": {"title": "Plugins", "resolution": "320x180", "fps": 10, "duration": 2.0},
+//!     "scenes": [{
+//!         "id": "intro",
+//!         "duration": 2.0,
+//!         "layers": [{"type": "progress_bar", "height": 12, "color": {"r": 255, "g": 0, "b": 0}}]
+//!     }]
+//! }"#)
+//! .unwrap();
+//!
+//! let mut engine = RenderEngine::new(script, false);
+//! engine.register_layer_renderer(ProgressBarLayer);
+//! engine.validate_layers().unwrap();
+//! ```
+
+use crate::assets::AssetLoader;
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{Compositor, FrameBuffer};
+use crate::script::{Color, Transform};
+use serde::Deserialize;
+
+/// Draws the custom layers of one `type`
+pub trait LayerRenderer: Send + Sync {
+    /// The layer `type` this draws
+    fn kind(&self) -> &str;
+
+    /// Draw the layer over the frame drawn so far. Plugins draw on the CPU,
+    /// after the GPU layers below them are read back.
+    fn render_cpu(&self, ctx: &mut LayerRenderContext) -> Result<()>;
+
+    /// Check a layer's params before rendering starts, so a bad layer fails
+    /// the render before its first frame rather than on it
+    fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        let _ = value;
+        Ok(())
+    }
+}
+
+/// What a [`LayerRenderer`] draws with
+pub struct LayerRenderContext<'a> {
+    /// The frame, with the layers below this one already drawn
+    pub frame: &'a mut FrameBuffer,
+    /// The layer's fields other than `type` and `transform`
+    pub params: &'a serde_json::Value,
+    pub transform: &'a Transform,
+    /// Seconds since the scene started
+    pub time: f32,
+    /// How far through the scene the frame is (0.0 on its first frame)
+    pub progress: f32,
+    pub assets: &'a mut AssetLoader,
+}
+
+/// The `progress_bar` layer: a bar filling up over its scene, from the
+/// transform's position. Its params are `color`, an optional `background`
+/// drawn under the unfilled part, `height` (8 pixels unless set) and `width`
+/// (the frame's unless set), both scaled by the transform.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgressBarLayer;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProgressBarParams {
+    #[serde(default = "white")]
+    color: Color,
+    #[serde(default)]
+    background: Option<Color>,
+    #[serde(default = "default_bar_height")]
+    height: u32,
+    #[serde(default)]
+    width: Option<u32>,
+}
+
+fn white() -> Color {
+    Color::WHITE
+}
+
+fn default_bar_height() -> u32 {
+    8
+}
+
+impl ProgressBarLayer {
+    fn params(value: &serde_json::Value) -> Result<ProgressBarParams> {
+        let params = ProgressBarParams::deserialize(value)
+            .map_err(|e| VideoEngineError::invalid(format!("Invalid progress_bar layer: {}", e)))?;
+        if params.height == 0 || params.width == Some(0) {
+            return Err(VideoEngineError::invalid(
+                "Invalid progress_bar layer: width and height must be greater than 0",
+            ));
+        }
+        Ok(params)
+    }
+}
+
+impl LayerRenderer for ProgressBarLayer {
+    fn kind(&self) -> &str {
+        "progress_bar"
+    }
+
+    fn render_cpu(&self, ctx: &mut LayerRenderContext) -> Result<()> {
+        let params = Self::params(ctx.params)?;
+        let transform = ctx.transform;
+        let scaled = |pixels: u32| (pixels as f32 * transform.scale).round() as u32;
+        let width = scaled(params.width.unwrap_or_else(|| ctx.frame.dimensions().0));
+        let height = scaled(params.height);
+        let filled = (width as f32 * ctx.progress.clamp(0.0, 1.0)).round() as u32;
+        let (x, y) = (transform.position.x, transform.position.y);
+
+        if let Some(background) = params.background {
+            let rgba = [background.r, background.g, background.b, background.a];
+            Compositor::blend_rect(
+                ctx.frame,
+                x + filled as i32,
+                y,
+                width - filled,
+                height,
+                Compositor::apply_opacity(rgba, transform.opacity),
+            );
+        }
+        let color = params.color;
+        let rgba = [color.r, color.g, color.b, color.a];
+        Compositor::blend_rect(
+            ctx.frame,
+            x,
+            y,
+            filled,
+            height,
+            Compositor::apply_opacity(rgba, transform.opacity),
+        );
+        Ok(())
+    }
+
+    fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        Self::params(value).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::RenderEngine;
+    use crate::script::{Layer, VideoScript};
+    use crate::ScriptParser;
+
+    fn script(layer: &str) -> VideoScript {
+        ScriptParser::parse_json_str(&format!(
+            r#"{{
+                "metadata": {{"title": "Bar", "resolution": "200x100", "fps": 10, "duration": 2.0}},
+                "scenes": [{{"id": "intro", "duration": 2.0, "layers": [{}]}}]
+            }}"#,
+            layer
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_progress_bar_fills_over_the_scene() {
+        let script = script(
+            r#"{"type": "progress_bar", "height": 10, "color": {"r": 255, "g": 0, "b": 0},
+                "background": {"r": 0, "g": 0, "b": 255}, "transform": {"position": {"x": 0, "y": 90}}}"#,
+        );
+        let mut engine = RenderEngine::new(script, false);
+        engine.register_layer_renderer(ProgressBarLayer);
+        let mut loader = AssetLoader::new(".");
+
+        // Half way through the 20 frame scene, half the bar is filled
+        let image = engine.render_frame_image(10, &mut loader).unwrap();
+        assert_eq!(image.get_pixel(50, 95).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(150, 95).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(50, 50).0, [0, 0, 0, 255]);
+
+        let image = engine.render_frame_image(0, &mut loader).unwrap();
+        assert_eq!(image.get_pixel(0, 95).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_layers_without_a_renderer_fail_validation() {
+        let mut engine = RenderEngine::new(script(r#"{"type": "chart", "bars": 3}"#), false);
+        let error = engine.validate_layers().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No renderer for layer type 'chart' in scene 'intro' (none are registered)"
+        );
+        let mut loader = AssetLoader::new(".");
+        assert!(matches!(
+            engine.render_frame(0, &mut loader),
+            Err(VideoEngineError::Validation(_))
+        ));
+
+        engine.register_layer_renderer(ProgressBarLayer);
+        let Err(VideoEngineError::Validation(errors)) = engine.validate_layers() else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(errors[0].scene.as_deref(), Some("intro"));
+        assert_eq!(
+            errors[0].message,
+            "No renderer for layer type 'chart' in scene 'intro' (registered: progress_bar)"
+        );
+    }
+
+    #[test]
+    fn test_plugins_validate_their_params() {
+        let mut engine =
+            RenderEngine::new(script(r#"{"type": "progress_bar", "height": 0}"#), false);
+        engine.register_layer_renderer(ProgressBarLayer);
+        let error = engine.validate_layers().unwrap_err();
+        assert!(
+            error.to_string().contains("must be greater than 0"),
+            "{}",
+            error
+        );
+
+        let script = script(r#"{"type": "progress_bar", "colour": "red"}"#);
+        assert!(matches!(&script.scenes[0].layers[0], Layer::Custom { .. }));
+        let mut engine = RenderEngine::new(script, false);
+        engine.register_layer_renderer(ProgressBarLayer);
+        assert!(engine
+            .validate_layers()
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `colour`"));
+    }
+}
@@ -0,0 +1,319 @@
+//! The layout debugging overlay of `--debug-overlay`: safe areas, a rule of
+//! thirds grid, the boxes of text layers and the frame's timecode, drawn over
+//! everything else so a layout can be checked frame by frame.
+
+use crate::renderer::{Compositor, FrameBuffer};
+use crate::script::Position;
+use ab_glyph::{Font, PxScale, ScaleFont};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Insets (percent of the frame) of the safe-area rectangles: action safe,
+/// then title safe
+pub const SAFE_AREA_PERCENTS: [u32; 2] = [5, 10];
+
+/// What the overlay draws, and in which colors. Colors are RGBA, written in
+/// configuration files as `#RRGGBB` or `#RRGGBBAA`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebugOverlaySettings {
+    /// Draw the overlay at all. Set by `--debug-overlay`, so frames are never
+    /// encoded with it by accident.
+    #[serde(skip)]
+    pub enabled: bool,
+    /// The 5% and 10% safe-area rectangles
+    #[serde(default = "yes")]
+    pub safe_areas: bool,
+    /// Lines splitting the frame into thirds
+    #[serde(default = "yes")]
+    pub thirds: bool,
+    /// Each text layer's box and anchor point, labeled `<scene id>#<layer index>`
+    #[serde(default = "yes")]
+    pub text_bounds: bool,
+    /// Frame number and time in the top left corner
+    #[serde(default = "yes")]
+    pub timecode: bool,
+    #[serde(default = "default_safe_area_color")]
+    #[serde(with = "hex_color")]
+    pub safe_area_color: [u8; 4],
+    #[serde(default = "default_thirds_color")]
+    #[serde(with = "hex_color")]
+    pub thirds_color: [u8; 4],
+    #[serde(default = "default_text_bounds_color")]
+    #[serde(with = "hex_color")]
+    pub text_bounds_color: [u8; 4],
+    /// Color of the labels and the timecode
+    #[serde(default = "default_label_color")]
+    #[serde(with = "hex_color")]
+    pub label_color: [u8; 4],
+    /// Font of the labels and the timecode, resolved like the script's fonts
+    /// (default: the font of the script's first text layer)
+    #[serde(default)]
+    pub label_font: Option<PathBuf>,
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn default_safe_area_color() -> [u8; 4] {
+    [255, 200, 0, 200]
+}
+
+fn default_thirds_color() -> [u8; 4] {
+    [0, 255, 255, 160]
+}
+
+fn default_text_bounds_color() -> [u8; 4] {
+    [255, 0, 255, 220]
+}
+
+fn default_label_color() -> [u8; 4] {
+    [255, 255, 255, 255]
+}
+
+impl Default for DebugOverlaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            safe_areas: true,
+            thirds: true,
+            text_bounds: true,
+            timecode: true,
+            safe_area_color: default_safe_area_color(),
+            thirds_color: default_thirds_color(),
+            text_bounds_color: default_text_bounds_color(),
+            label_color: default_label_color(),
+            label_font: None,
+        }
+    }
+}
+
+/// Where a text layer is drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBounds {
+    pub label: String,
+    /// The layer's position, its top left corner
+    pub anchor: Position,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TextBounds {
+    /// The box `content` takes at `position` in `font`, or the placeholder
+    /// block drawn when the font can't be read
+    pub fn measure(
+        label: impl Into<String>,
+        content: &str,
+        font: Option<&impl Font>,
+        font_size: f32,
+        position: &Position,
+    ) -> Self {
+        let (width, height) = match font {
+            Some(font) => {
+                let scaled = font.as_scaled(PxScale::from(font_size));
+                let lines = content.lines().count().max(1) as f32;
+                let height = lines * scaled.height() + (lines - 1.0) * scaled.line_gap();
+                (
+                    Compositor::text_width(font, content, font_size).ceil() as u32,
+                    height.ceil() as u32,
+                )
+            }
+            None => ((content.len() as u32 * 8).min(200), 16),
+        };
+        Self {
+            label: label.into(),
+            anchor: position.clone(),
+            width,
+            height,
+        }
+    }
+}
+
+/// The rectangle `percent` of the frame's width and height in from its edges
+pub fn safe_area(width: u32, height: u32, percent: u32) -> (i32, i32, u32, u32) {
+    let (x, y) = (width * percent / 100, height * percent / 100);
+    (x as i32, y as i32, width - 2 * x, height - 2 * y)
+}
+
+/// `frame 42  00:01.400` for frame 42 at 30fps
+pub fn timecode(frame_number: u32, seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "frame {}  {:02}:{:02}.{:03}",
+        frame_number,
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+impl DebugOverlaySettings {
+    /// Draw the overlay over `frame`. Labels and the timecode need `font`.
+    pub fn draw(
+        &self,
+        frame: &mut FrameBuffer,
+        text: &[TextBounds],
+        timecode: &str,
+        font: Option<&impl Font>,
+    ) {
+        let (width, height) = frame.dimensions();
+        let label_size = (height as f32 / 45.0).max(12.0);
+
+        if self.safe_areas {
+            for percent in SAFE_AREA_PERCENTS {
+                let (x, y, w, h) = safe_area(width, height, percent);
+                outline(frame, x, y, w, h, self.safe_area_color);
+            }
+        }
+
+        if self.thirds {
+            for third in 1..3 {
+                let x = (width * third / 3) as i32;
+                let y = (height * third / 3) as i32;
+                Compositor::blend_rect(frame, x, 0, 1, height, self.thirds_color);
+                Compositor::blend_rect(frame, 0, y, width, 1, self.thirds_color);
+            }
+        }
+
+        if self.text_bounds {
+            for bounds in text {
+                let Position { x, y } = bounds.anchor;
+                let color = self.text_bounds_color;
+                outline(frame, x, y, bounds.width, bounds.height, color);
+                // A cross on the anchor point
+                Compositor::blend_rect(frame, x - 4, y, 9, 1, color);
+                Compositor::blend_rect(frame, x, y - 4, 1, 9, color);
+                if let Some(font) = font {
+                    // Above the box, or inside it when it's at the top of the frame
+                    let label_y = if y as f32 >= label_size + 2.0 {
+                        y - label_size.ceil() as i32 - 2
+                    } else {
+                        y + 2
+                    };
+                    Compositor::draw_text(
+                        frame,
+                        font,
+                        &bounds.label,
+                        label_size,
+                        x + 2,
+                        label_y,
+                        self.label_color,
+                    );
+                }
+            }
+        }
+
+        if let (true, Some(font)) = (self.timecode, font) {
+            let text_width = Compositor::text_width(font, timecode, label_size).ceil() as u32;
+            let padding = 4;
+            // A dark backdrop keeps it readable over any frame
+            Compositor::blend_rect(
+                frame,
+                8,
+                8,
+                text_width + 2 * padding,
+                label_size.ceil() as u32 + 2 * padding,
+                [0, 0, 0, 160],
+            );
+            Compositor::draw_text(
+                frame,
+                font,
+                timecode,
+                label_size,
+                8 + padding as i32,
+                8 + padding as i32,
+                self.label_color,
+            );
+        }
+    }
+}
+
+/// RGBA colors as `#RRGGBBAA`, read from `#RRGGBB` too
+mod hex_color {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &[u8; 4], serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, a] = color;
+        serializer.serialize_str(&format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 4], D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| {
+            D::Error::custom(format!(
+                "invalid color '{}' (expected #RRGGBB or #RRGGBBAA)",
+                text
+            ))
+        })
+    }
+
+    pub fn parse(text: &str) -> Option<[u8; 4]> {
+        let hex = text.strip_prefix('#')?;
+        if !matches!(hex.len(), 6 | 8) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok();
+        Some([
+            channel(0)?,
+            channel(1)?,
+            channel(2)?,
+            channel(3).unwrap_or(255),
+        ])
+    }
+}
+
+/// A 1 pixel outline of the rectangle
+fn outline(frame: &mut FrameBuffer, x: i32, y: i32, width: u32, height: u32, color: [u8; 4]) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    Compositor::blend_rect(frame, x, y, width, 1, color);
+    Compositor::blend_rect(frame, x, y + height as i32 - 1, width, 1, color);
+    // The sides leave out the corners the top and bottom already drew
+    let sides = height.saturating_sub(2);
+    Compositor::blend_rect(frame, x, y + 1, 1, sides, color);
+    Compositor::blend_rect(frame, x + width as i32 - 1, y + 1, 1, sides, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_areas_and_timecode() {
+        assert_eq!(safe_area(1920, 1080, 5), (96, 54, 1728, 972));
+        assert_eq!(safe_area(1920, 1080, 10), (192, 108, 1536, 864));
+        assert_eq!(timecode(42, 1.4), "frame 42  00:01.400");
+        assert_eq!(timecode(5400, 180.0), "frame 5400  03:00.000");
+    }
+
+    #[test]
+    fn test_colors_are_hex() {
+        assert_eq!(hex_color::parse("#FFC800"), Some([255, 200, 0, 255]));
+        assert_eq!(hex_color::parse("#00ff0080"), Some([0, 255, 0, 128]));
+        for invalid in ["FFC800", "#FFC8", "#GGGGGG", "#FFC800C8FF"] {
+            assert_eq!(hex_color::parse(invalid), None, "{}", invalid);
+        }
+
+        let settings = DebugOverlaySettings::default();
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains(r##""thirds_color":"#00FFFFA0""##), "{}", json);
+        let parsed: DebugOverlaySettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn test_outline_leaves_the_inside_alone() {
+        let mut frame = FrameBuffer::new(10, 10);
+        frame.clear([0, 0, 0, 255]);
+        outline(&mut frame, 2, 2, 5, 4, [255, 0, 0, 255]);
+        let image = frame.to_image();
+        for (x, y) in [(2, 2), (6, 2), (2, 5), (6, 5), (4, 2), (2, 3)] {
+            assert_eq!(image.get_pixel(x, y).0, [255, 0, 0, 255], "({}, {})", x, y);
+        }
+        for (x, y) in [(3, 3), (5, 4), (7, 2), (2, 6)] {
+            assert_eq!(image.get_pixel(x, y).0, [0, 0, 0, 255], "({}, {})", x, y);
+        }
+    }
+}
@@ -1,6 +1,830 @@
-use anyhow::{Context, Result};
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{FrameBuffer, FrameSink, ProgressKind, RenderProgress};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{mpsc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::debug;
+
+/// Presets of x264 and x265, fastest first
+const X26X_PRESETS: [&str; 10] = [
+    "ultrafast",
+    "superfast",
+    "veryfast",
+    "faster",
+    "fast",
+    "medium",
+    "slow",
+    "slower",
+    "veryslow",
+    "placebo",
+];
+
+/// libvpx-vp9 deadlines, used as its presets
+const VP9_PRESETS: [&str; 3] = ["realtime", "good", "best"];
+
+/// ProRes profiles by `prores_ks` profile number, used as its presets
+const PRORES_PROFILES: [&str; 6] = ["proxy", "lt", "standard", "hq", "4444", "4444xq"];
+
+/// NVENC preset for each x264 preset, `p1` (fastest) to `p7`
+const NVENC_PRESETS: [&str; 10] = ["p1", "p1", "p2", "p3", "p3", "p4", "p5", "p6", "p7", "p7"];
+
+/// Render node VAAPI encodes on
+const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+/// Encoders the installed ffmpeg was built with, probed once
+static FFMPEG_ENCODERS: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Filters the installed ffmpeg was built with, probed once
+static FFMPEG_FILTERS: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Lines of ffmpeg's error output quoted when an encode fails
+const STDERR_TAIL_LINES: usize = 30;
+
+fn default_stall_timeout_seconds() -> u64 {
+    120
+}
+
+/// Video codecs the encoder can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    /// HEVC, tagged so QuickTime plays it
+    H265,
+    Vp9,
+    /// Apple ProRes, for masters; written to `.mov`
+    Prores,
+    Av1,
+}
+
+impl VideoCodec {
+    /// Name used in the configuration and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "h264",
+            VideoCodec::H265 => "h265",
+            VideoCodec::Vp9 => "vp9",
+            VideoCodec::Prores => "prores",
+            VideoCodec::Av1 => "av1",
+        }
+    }
+
+    /// File extension of a container that holds this codec
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoCodec::Prores => "mov",
+            _ => "mp4",
+        }
+    }
+
+    /// The ffmpeg encoder
+    fn encoder(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+            VideoCodec::Vp9 => "libvpx-vp9",
+            VideoCodec::Prores => "prores_ks",
+            VideoCodec::Av1 => "libsvtav1",
+        }
+    }
+
+    fn max_crf(&self) -> Option<u32> {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => Some(51),
+            VideoCodec::Vp9 | VideoCodec::Av1 => Some(63),
+            VideoCodec::Prores => None,
+        }
+    }
+
+    fn default_pixel_format(&self) -> &'static str {
+        match self {
+            VideoCodec::Prores => "yuv422p10le",
+            _ => "yuv420p",
+        }
+    }
+}
+
+/// What a render produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// MP4 with the configured codec (`.mov` for ProRes)
+    #[default]
+    Mp4,
+    /// WebM with VP9, or AV1 when that's the configured codec. With `alpha`,
+    /// the frames' transparency is kept (VP9 only).
+    Webm { alpha: bool },
+    /// Looping GIF without audio, its palette of up to `max_colors` fitted to
+    /// the whole video, at no more than `fps_cap` frames per second
+    Gif { max_colors: u32, fps_cap: u32 },
+    /// Numbered PNG frames, not encoded; the audio is still exported
+    PngSequence,
+}
+
+impl OutputFormat {
+    pub const DEFAULT_GIF_COLORS: u32 = 256;
+    pub const DEFAULT_GIF_FPS: u32 = 15;
+
+    /// Whether frames are encoded into a video file
+    pub fn encodes(&self) -> bool {
+        *self != OutputFormat::PngSequence
+    }
+
+    /// Audio codec of the container, or `None` when it has no audio
+    fn audio_codec(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Mp4 => Some("aac"),
+            OutputFormat::Webm { .. } => Some("libopus"),
+            OutputFormat::Gif { .. } | OutputFormat::PngSequence => None,
+        }
+    }
+
+    /// Whether the container holds chapters
+    fn has_chapters(&self) -> bool {
+        matches!(self, OutputFormat::Mp4 | OutputFormat::Webm { .. })
+    }
+
+    /// Codec of a subtitle stream in the container, or `None` when it has none
+    fn subtitle_codec(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Mp4 => Some("mov_text"),
+            OutputFormat::Webm { .. } => Some("webvtt"),
+            OutputFormat::Gif { .. } | OutputFormat::PngSequence => None,
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    /// `mp4`, `webm`, `webm-alpha`, `gif` (default palette and frame rate) or `png-sequence`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "mp4" => Ok(OutputFormat::Mp4),
+            "webm" => Ok(OutputFormat::Webm { alpha: false }),
+            "webm-alpha" => Ok(OutputFormat::Webm { alpha: true }),
+            "gif" => Ok(OutputFormat::Gif {
+                max_colors: Self::DEFAULT_GIF_COLORS,
+                fps_cap: Self::DEFAULT_GIF_FPS,
+            }),
+            "png-sequence" => Ok(OutputFormat::PngSequence),
+            other => Err(format!(
+                "unknown format '{}' (expected mp4, webm, webm-alpha, gif or png-sequence)",
+                other
+            )),
+        }
+    }
+}
+
+/// Families of hardware video encoders ffmpeg can drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareEncoder {
+    /// Apple's, on macOS
+    VideoToolbox,
+    /// NVIDIA GPUs
+    Nvenc,
+    /// Intel and AMD GPUs on Linux
+    Vaapi,
+}
+
+impl HardwareEncoder {
+    /// The hardware encoder for `codec` to try on `platform` (as in
+    /// `std::env::consts::OS`), given the encoders ffmpeg lists. Returns the
+    /// family and the ffmpeg encoder name.
+    pub fn select(
+        codec: VideoCodec,
+        platform: &str,
+        available: &HashSet<String>,
+    ) -> Option<(HardwareEncoder, &'static str)> {
+        let candidates: &[HardwareEncoder] = match platform {
+            "macos" => &[HardwareEncoder::VideoToolbox],
+            "linux" => &[HardwareEncoder::Nvenc, HardwareEncoder::Vaapi],
+            _ => &[HardwareEncoder::Nvenc],
+        };
+        candidates.iter().find_map(|family| {
+            let name = family.encoder(codec)?;
+            available.contains(name).then_some((*family, name))
+        })
+    }
+
+    /// This family's ffmpeg encoder for `codec`, if it has one
+    fn encoder(&self, codec: VideoCodec) -> Option<&'static str> {
+        match (self, codec) {
+            (HardwareEncoder::VideoToolbox, VideoCodec::H264) => Some("h264_videotoolbox"),
+            (HardwareEncoder::VideoToolbox, VideoCodec::H265) => Some("hevc_videotoolbox"),
+            (HardwareEncoder::Nvenc, VideoCodec::H264) => Some("h264_nvenc"),
+            (HardwareEncoder::Nvenc, VideoCodec::H265) => Some("hevc_nvenc"),
+            (HardwareEncoder::Vaapi, VideoCodec::H264) => Some("h264_vaapi"),
+            (HardwareEncoder::Vaapi, VideoCodec::H265) => Some("hevc_vaapi"),
+            _ => None,
+        }
+    }
+}
+
+/// Encoder names in the output of `ffmpeg -encoders`
+pub fn parse_encoders(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Filter names in the output of `ffmpeg -filters`
+pub fn parse_filters(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_flags, name, io) = (fields.next()?, fields.next()?, fields.next()?);
+            io.contains("->").then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// The filters the installed ffmpeg lists; probed on first use
+fn ffmpeg_filters() -> &'static HashSet<String> {
+    FFMPEG_FILTERS.get_or_init(|| {
+        Command::new("ffmpeg")
+            .args(["-hide_banner", "-filters"])
+            .output()
+            .map(|output| parse_filters(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    })
+}
+
+/// How burned-in subtitles look; unset fields keep libass' defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleStyle {
+    /// Font size, relative to a 288 pixel high video as libass scales it
+    #[serde(default)]
+    pub font_size: Option<u32>,
+    /// Distance of the text from the bottom edge, on the same scale
+    #[serde(default)]
+    pub margin: Option<u32>,
+}
+
+impl SubtitleStyle {
+    /// The `force_style` of the `subtitles` filter, or `None` when nothing is set
+    fn force_style(&self) -> Option<String> {
+        let fields: Vec<String> = [("FontSize", self.font_size), ("MarginV", self.margin)]
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{}={}", name, value?)))
+            .collect();
+        (!fields.is_empty()).then(|| fields.join(","))
+    }
+}
+
+/// Escape a value for an option of a filter (`key=value:key=value`)
+fn escape_filter_option(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '\'' | ':') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape a filter's options for a filter graph (`a=1,b=2;...`)
+fn escape_filter_graph(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '\'' | '[' | ']' | ',' | ';') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The `subtitles` filter that burns the SRT file at `path` into the frames. On
+/// `windows`, backslashes become slashes, which ffmpeg accepts there too, so only
+/// the drive's colon needs escaping.
+pub fn subtitles_filter(path: &str, style: &SubtitleStyle, windows: bool) -> String {
+    let path = if windows {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    };
+    let mut options = format!("filename={}", escape_filter_option(&path));
+    if let Some(force_style) = style.force_style() {
+        options.push_str(&format!(
+            ":force_style={}",
+            escape_filter_option(&force_style)
+        ));
+    }
+    format!("subtitles={}", escape_filter_graph(&options))
+}
+
+/// The encoders the installed ffmpeg lists; probed on first use
+fn ffmpeg_encoders() -> &'static HashSet<String> {
+    FFMPEG_ENCODERS.get_or_init(|| {
+        Command::new("ffmpeg")
+            .args(["-hide_banner", "-encoders"])
+            .output()
+            .map(|output| parse_encoders(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    })
+}
+
+/// ffmpeg options that pick and configure the video encoder
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoArgs {
+    /// Options that go before the inputs
+    pub global: Vec<String>,
+    /// Options for the video stream of the output
+    pub output: Vec<String>,
+    /// Set when a hardware encoder is used
+    pub hardware: Option<HardwareEncoder>,
+}
+
+impl VideoArgs {
+    fn software(output: Vec<String>) -> Self {
+        Self {
+            global: Vec::new(),
+            output,
+            hardware: None,
+        }
+    }
+}
+
+/// How the video stream is encoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncodeSettings {
+    #[serde(default)]
+    pub codec: VideoCodec,
+    /// Constant quality, lower is better: 0-51 for H.264 and H.265, 0-63 for
+    /// VP9 and AV1. The encoder's default when neither this nor `bitrate` is set.
+    #[serde(default)]
+    pub crf: Option<u32>,
+    /// Average bitrate in ffmpeg's notation (e.g. `8M`), instead of `crf`
+    #[serde(default)]
+    pub bitrate: Option<String>,
+    /// Speed against size: an x264/x265 preset (`medium`), a VP9 deadline
+    /// (`good`), an SVT-AV1 preset (`0`-`13`) or a ProRes profile (`hq`)
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// ffmpeg pixel format; `yuv420p` by default, `yuv422p10le` for ProRes
+    #[serde(default)]
+    pub pixel_format: Option<String>,
+    /// Encode H.264 and H.265 on the GPU or media engine when ffmpeg has an
+    /// encoder for it, falling back to software
+    #[serde(default)]
+    pub hardware: bool,
+    /// What the render produces; set by `render --format`
+    #[serde(skip)]
+    pub format: OutputFormat,
+    /// Stop ffmpeg when it reports no progress for this long while encoding
+    /// frame files
+    #[serde(default = "default_stall_timeout_seconds")]
+    pub stall_timeout_seconds: u64,
+    /// Render subtitles into the frames instead of adding a subtitle stream
+    #[serde(default)]
+    pub burn_subtitles: bool,
+    /// Look of burned-in subtitles
+    #[serde(default)]
+    pub subtitle_style: SubtitleStyle,
+}
+
+impl Default for EncodeSettings {
+    fn default() -> Self {
+        Self {
+            codec: VideoCodec::default(),
+            crf: None,
+            bitrate: None,
+            preset: None,
+            pixel_format: None,
+            hardware: false,
+            format: OutputFormat::default(),
+            stall_timeout_seconds: default_stall_timeout_seconds(),
+            burn_subtitles: false,
+            subtitle_style: SubtitleStyle::default(),
+        }
+    }
+}
+
+/// A snapshot of ffmpeg's `-progress` report
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FfmpegProgress {
+    /// Frames encoded so far
+    pub frame: u32,
+    /// Frames encoded per second
+    pub fps: f64,
+    /// How far into the video the encode is
+    pub out_time_seconds: f64,
+    /// Encoding speed as a multiple of real time; unknown at the start
+    pub speed: Option<f64>,
+    /// Set on the last report, once the encode is done
+    pub finished: bool,
+}
+
+/// Collects the `key=value` lines of ffmpeg's `-progress` output into a report
+/// each time a block ends with `progress=continue` or `progress=end`
+#[derive(Debug, Default)]
+pub struct ProgressParser {
+    current: FfmpegProgress,
+}
+
+impl ProgressParser {
+    /// Feed one line; returns the report when it completes a block
+    pub fn parse_line(&mut self, line: &str) -> Option<FfmpegProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+        match key {
+            "frame" => self.current.frame = value.parse().unwrap_or(self.current.frame),
+            "fps" => self.current.fps = value.parse().unwrap_or(self.current.fps),
+            // Both are in microseconds, despite the name of the older `out_time_ms`
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(micros) = value.parse::<i64>() {
+                    self.current.out_time_seconds = micros.max(0) as f64 / 1_000_000.0;
+                }
+            }
+            "speed" => self.current.speed = value.trim_end_matches('x').parse().ok(),
+            "progress" => {
+                self.current.finished = value == "end";
+                return Some(self.current.clone());
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// The last `count` lines of `output`, joined back together
+fn tail_lines(output: &str, count: usize) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+/// Run an ffmpeg `cmd` that writes `-progress` reports to stdout, passing them
+/// to `progress`. ffmpeg is killed when no report arrives for `stall_timeout`.
+/// Errors quote the end of its stderr.
+fn run_with_progress(
+    mut cmd: Command,
+    stall_timeout: Duration,
+    progress: &mut dyn RenderProgress,
+) -> Result<()> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+
+    let stderr = child.stderr.take().map(|pipe| {
+        std::thread::spawn(move || {
+            let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+            for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+            Vec::from(tail).join("\n")
+        })
+    });
+    let (lines_tx, lines_rx) = mpsc::channel();
+    if let Some(pipe) = child.stdout.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                if lines_tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let mut parser = ProgressParser::default();
+    let mut stalled = false;
+    loop {
+        match lines_rx.recv_timeout(stall_timeout) {
+            Ok(line) => {
+                if let Some(report) = parser.parse_line(&line) {
+                    progress.emit(ProgressKind::EncodeProgress {
+                        frame: report.frame,
+                        fps: report.fps,
+                        out_time_seconds: report.out_time_seconds,
+                        speed: report.speed,
+                    });
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                stalled = true;
+                let _ = child.kill();
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(VideoEngineError::io("wait for", "ffmpeg"))?;
+    let stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    if stalled {
+        return Err(VideoEngineError::external(
+            "FFmpeg",
+            format!(
+                "made no progress for {}s and was stopped: {}",
+                stall_timeout.as_secs_f64(),
+                stderr
+            ),
+        ));
+    }
+    if !status.success() {
+        return Err(encoding_failed(status, &stderr));
+    }
+    Ok(())
+}
+
+/// ffmpeg couldn't be started
+fn spawn_error(error: std::io::Error) -> VideoEngineError {
+    VideoEngineError::EncoderUnavailable(format!("Failed to execute ffmpeg: {}", error))
+}
+
+/// ffmpeg exited with `status`, having written `stderr`
+fn encoding_failed(status: std::process::ExitStatus, stderr: &str) -> VideoEngineError {
+    VideoEngineError::external(
+        "FFmpeg",
+        format!("encoding exited with {}: {}", status, stderr),
+    )
+}
+
+impl EncodeSettings {
+    /// Fail on settings the codec or format can't use, before ffmpeg is started
+    pub fn validate(&self) -> Result<()> {
+        self.format_args(1).map(|_| ())
+    }
+
+    /// Extension of the encoded file, or `None` when nothing is encoded
+    pub fn extension(&self) -> Option<&'static str> {
+        match self.format {
+            OutputFormat::Mp4 => Some(self.codec.extension()),
+            OutputFormat::Webm { .. } => Some("webm"),
+            OutputFormat::Gif { .. } => Some("gif"),
+            OutputFormat::PngSequence => None,
+        }
+    }
+
+    /// Software encoder options for the output format at `fps`
+    fn format_args(&self, fps: u32) -> Result<Vec<String>> {
+        match self.format {
+            OutputFormat::Mp4 => self.video_args(),
+            OutputFormat::Webm { alpha } => {
+                let codec = match self.codec {
+                    // H.264 is the default, so WebM's own codec takes over
+                    VideoCodec::H264 | VideoCodec::Vp9 => VideoCodec::Vp9,
+                    VideoCodec::Av1 if !alpha => VideoCodec::Av1,
+                    VideoCodec::Av1 => {
+                        return Err(VideoEngineError::invalid(
+                            "WebM with alpha needs the VP9 codec",
+                        ))
+                    }
+                    other => {
+                        return Err(VideoEngineError::invalid(format!(
+                            "WebM can't hold {} video",
+                            other.name()
+                        )))
+                    }
+                };
+                let settings = EncodeSettings {
+                    codec,
+                    pixel_format: self
+                        .pixel_format
+                        .clone()
+                        .or(alpha.then(|| "yuva420p".to_string())),
+                    ..self.clone()
+                };
+                let mut args = settings.video_args()?;
+                if alpha {
+                    // libvpx drops the alpha plane of alt-ref frames
+                    args.extend(["-auto-alt-ref".into(), "0".into()]);
+                }
+                Ok(args)
+            }
+            OutputFormat::Gif {
+                max_colors,
+                fps_cap,
+            } => {
+                if !(2..=256).contains(&max_colors) {
+                    return Err(VideoEngineError::invalid(format!(
+                        "GIF palettes hold 2 to 256 colors, not {}",
+                        max_colors
+                    )));
+                }
+                if fps_cap == 0 {
+                    return Err(VideoEngineError::invalid(
+                        "The GIF frame rate cap must be at least 1",
+                    ));
+                }
+                // The palette is fitted to every frame, then used to dither them
+                let graph = format!(
+                    "fps={},split[frames][copy];[copy]palettegen=max_colors={}[palette];\
+                     [frames][palette]paletteuse",
+                    fps.min(fps_cap),
+                    max_colors
+                );
+                Ok(vec!["-vf".into(), graph, "-loop".into(), "0".into()])
+            }
+            OutputFormat::PngSequence => Ok(Vec::new()),
+        }
+    }
+
+    /// The same settings, encoding in software
+    pub fn software(&self) -> Self {
+        Self {
+            hardware: false,
+            ..self.clone()
+        }
+    }
+
+    /// ffmpeg options for the video stream at `fps`, using a hardware encoder
+    /// when one is asked for, the installed ffmpeg has it and the output is MP4
+    pub fn encoder_args(&self, fps: u32) -> Result<VideoArgs> {
+        if !self.format.encodes() {
+            return Err(VideoEngineError::invalid("PNG sequences aren't encoded"));
+        }
+        let software = VideoArgs::software(self.format_args(fps)?);
+        if !self.hardware || self.format != OutputFormat::Mp4 {
+            return Ok(software);
+        }
+        match HardwareEncoder::select(self.codec, std::env::consts::OS, ffmpeg_encoders()) {
+            Some((family, name)) => self.hardware_args(family, name),
+            None => {
+                say!(
+                    "  ⚠️  No hardware {} encoder available, encoding in software",
+                    self.codec.name()
+                );
+                Ok(software)
+            }
+        }
+    }
+
+    /// Options for the hardware encoder `name` of `family`, with the quality
+    /// settings mapped onto its own: VideoToolbox takes a 1-100 quality
+    /// (`-q:v`, higher is better), NVENC a constant quality (`-cq`) and VAAPI a
+    /// fixed quantizer (`-qp`). A bitrate is passed as it is.
+    pub fn hardware_args(&self, family: HardwareEncoder, name: &str) -> Result<VideoArgs> {
+        // Settings the software encoder rejects are rejected here too
+        self.video_args()?;
+
+        let mut global = Vec::new();
+        let mut output: Vec<String> = Vec::new();
+        if family == HardwareEncoder::Vaapi {
+            global.extend(["-vaapi_device".into(), VAAPI_DEVICE.into()]);
+            // Frames are uploaded to the GPU, which picks its own pixel format
+            output.extend(["-vf".into(), "format=nv12,hwupload".into()]);
+        }
+        output.extend(["-c:v".into(), name.into()]);
+
+        if family == HardwareEncoder::Nvenc {
+            if let Some(preset) = &self.preset {
+                let index = X26X_PRESETS.iter().position(|p| p == preset).unwrap_or(5);
+                output.extend(["-preset".into(), NVENC_PRESETS[index].into()]);
+            }
+        }
+        if let (Some(crf), Some(max)) = (self.crf, self.codec.max_crf()) {
+            match family {
+                HardwareEncoder::VideoToolbox => {
+                    let quality = (100 - crf * 100 / max).clamp(1, 100);
+                    output.extend(["-q:v".into(), quality.to_string()]);
+                }
+                HardwareEncoder::Nvenc => {
+                    output.extend(["-rc".into(), "vbr".into(), "-cq".into(), crf.to_string()]);
+                }
+                HardwareEncoder::Vaapi => {
+                    output.extend(["-qp".into(), crf.to_string()]);
+                }
+            }
+        }
+        if let Some(bitrate) = &self.bitrate {
+            output.extend(["-b:v".into(), bitrate.clone()]);
+        }
+        if self.codec == VideoCodec::H265 {
+            output.extend(["-tag:v".into(), "hvc1".into()]);
+        }
+        if family != HardwareEncoder::Vaapi {
+            let pixel_format = self
+                .pixel_format
+                .as_deref()
+                .unwrap_or(self.codec.default_pixel_format());
+            output.extend(["-pix_fmt".into(), pixel_format.into()]);
+        }
+
+        Ok(VideoArgs {
+            global,
+            output,
+            hardware: Some(family),
+        })
+    }
+
+    /// ffmpeg output options for the video stream
+    pub fn video_args(&self) -> Result<Vec<String>> {
+        let codec = self.codec;
+        let mut args: Vec<String> = vec!["-c:v".into(), codec.encoder().into()];
+        if self.crf.is_some() && self.bitrate.is_some() {
+            return Err(VideoEngineError::invalid(
+                "Set either a CRF or a bitrate for the encoder, not both",
+            ));
+        }
+
+        let preset = self.preset.as_deref();
+        let check_preset = |presets: &[&str]| -> Result<()> {
+            match preset {
+                Some(preset) if !presets.contains(&preset) => {
+                    Err(VideoEngineError::invalid(format!(
+                        "Unknown {} preset '{}' (expected one of: {})",
+                        codec.name(),
+                        preset,
+                        presets.join(", ")
+                    )))
+                }
+                _ => Ok(()),
+            }
+        };
+        match codec {
+            VideoCodec::H264 | VideoCodec::H265 => {
+                check_preset(&X26X_PRESETS)?;
+                if let Some(preset) = preset {
+                    args.extend(["-preset".into(), preset.into()]);
+                }
+                if codec == VideoCodec::H265 {
+                    args.extend(["-tag:v".into(), "hvc1".into()]);
+                }
+            }
+            VideoCodec::Vp9 => {
+                check_preset(&VP9_PRESETS)?;
+                if let Some(preset) = preset {
+                    args.extend(["-deadline".into(), preset.into()]);
+                }
+                // Constant quality needs the bitrate limit off
+                if self.bitrate.is_none() {
+                    args.extend(["-b:v".into(), "0".into()]);
+                    if self.crf.is_none() {
+                        args.extend(["-crf".into(), "31".into()]);
+                    }
+                }
+            }
+            VideoCodec::Prores => {
+                if self.crf.is_some() || self.bitrate.is_some() {
+                    return Err(VideoEngineError::invalid(format!(
+                        "ProRes quality is set by its profile: use the preset ({}) instead of a CRF or bitrate",
+                        PRORES_PROFILES.join(", ")
+                    )));
+                }
+                check_preset(&PRORES_PROFILES)?;
+                let profile = PRORES_PROFILES
+                    .iter()
+                    .position(|profile| *profile == preset.unwrap_or("hq"))
+                    .unwrap_or(3);
+                args.extend(["-profile:v".into(), profile.to_string()]);
+            }
+            VideoCodec::Av1 => {
+                if let Some(preset) = preset {
+                    if !preset.parse::<u32>().is_ok_and(|preset| preset <= 13) {
+                        return Err(VideoEngineError::invalid(format!(
+                            "Unknown av1 preset '{}' (expected 0 to 13)",
+                            preset
+                        )));
+                    }
+                    args.extend(["-preset".into(), preset.into()]);
+                }
+            }
+        }
+
+        if let (Some(crf), Some(max)) = (self.crf, codec.max_crf()) {
+            if crf > max {
+                return Err(VideoEngineError::invalid(format!(
+                    "CRF {} is out of range for {} (0 to {})",
+                    crf,
+                    codec.name(),
+                    max
+                )));
+            }
+            args.extend(["-crf".into(), crf.to_string()]);
+        }
+        if let Some(bitrate) = &self.bitrate {
+            args.extend(["-b:v".into(), bitrate.clone()]);
+        }
+        let pixel_format = self
+            .pixel_format
+            .as_deref()
+            .unwrap_or(codec.default_pixel_format());
+        args.extend(["-pix_fmt".into(), pixel_format.into()]);
+        Ok(args)
+    }
+}
 
 /// Handles video encoding using external FFmpeg process
 pub struct VideoEncoder;
@@ -11,14 +835,45 @@ impl VideoEncoder {
         Command::new("ffmpeg").arg("-version").output().is_ok()
     }
 
+    /// Fail with [`VideoEngineError::EncoderUnavailable`] unless FFmpeg can be run
+    pub fn check_available() -> Result<()> {
+        if !Self::is_available() {
+            return Err(VideoEngineError::EncoderUnavailable(
+                "FFmpeg not found. Please install ffmpeg to enable video encoding.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail unless ffmpeg can burn in subtitles, which takes its libass-based
+    /// `subtitles` filter
+    pub fn check_subtitle_burning() -> Result<()> {
+        if !ffmpeg_filters().contains("subtitles") {
+            return Err(VideoEngineError::EncoderUnavailable(
+                "FFmpeg was built without libass, so subtitles can't be burned in \
+                 (turn off encoder.burn_subtitles to add them as a subtitle stream)"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Encode a sequence of frames to a video file
     ///
     /// # Arguments
-    /// * `frame_pattern` - Pattern for input frames (e.g., "output/frame_%d.ppm")
+    /// * `frame_pattern` - Pattern for input frames (e.g., "output/frame_%06d.png")
     /// * `output_path` - Path for the output video (e.g., "output.mp4")
     /// * `fps` - Frames per second
     /// * `width` - Video width
     /// * `height` - Video height
+    /// * `audio_path` - Audio muxed into the video
+    /// * `chapters_path` - `FFMETADATA1` file whose chapters are embedded in the video
+    /// * `subtitles_path` - SRT file burned into the frames or added as a subtitle stream
+    /// * `settings` - Codec and quality of the video stream
+    /// * `progress` - Receives ffmpeg's progress reports
+    ///
+    /// When a hardware encoder fails, the video is encoded again in software.
+    #[allow(clippy::too_many_arguments)]
     pub fn encode(
         frame_pattern: &str,
         output_path: &Path,
@@ -26,15 +881,69 @@ impl VideoEncoder {
         width: u32,
         height: u32,
         audio_path: Option<&Path>,
+        chapters_path: Option<&Path>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+        progress: &mut dyn RenderProgress,
     ) -> Result<()> {
-        if !Self::is_available() {
-            anyhow::bail!("FFmpeg not found. Please install ffmpeg to enable video encoding.");
-        }
+        let video_args = settings.encoder_args(fps)?;
+        Self::check_available()?;
+
+        say!("🎥 Encoding video to {}...", output_path.display());
+        let _span = tracing::info_span!("encode", output = %output_path.display()).entered();
 
-        println!("🎥 Encoding video to {}...", output_path.display());
+        let mut run = |video_args: VideoArgs| {
+            let cmd = Self::encode_command(
+                frame_pattern,
+                output_path,
+                fps,
+                (width, height),
+                audio_path,
+                chapters_path,
+                subtitles_path,
+                settings,
+                video_args,
+            );
+            debug!("ffmpeg command: {:?}", cmd);
+            run_with_progress(
+                cmd,
+                Duration::from_secs(settings.stall_timeout_seconds.max(1)),
+                progress,
+            )
+        };
+        match (video_args.hardware, run(video_args.clone())) {
+            (Some(_), Err(e)) => {
+                say!(
+                    "  ⚠️  Hardware encoding failed ({:#}), encoding again in software",
+                    e
+                );
+                run(settings.software().encoder_args(fps)?)
+            }
+            (_, result) => result,
+        }
+    }
 
+    /// The ffmpeg command that encodes the frame files, reporting its progress
+    /// on stdout
+    #[allow(clippy::too_many_arguments)]
+    fn encode_command(
+        frame_pattern: &str,
+        output_path: &Path,
+        fps: u32,
+        (width, height): (u32, u32),
+        audio_path: Option<&Path>,
+        chapters_path: Option<&Path>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+        video_args: VideoArgs,
+    ) -> Command {
         let mut cmd = Command::new("ffmpeg");
         cmd.arg("-y") // Overwrite output
+            .arg("-hide_banner")
+            .arg("-nostats")
+            .arg("-progress")
+            .arg("pipe:1")
+            .args(&video_args.global)
             .arg("-f")
             .arg("image2") // Input format
             .arg("-framerate")
@@ -42,32 +951,947 @@ impl VideoEncoder {
             .arg("-i")
             .arg(frame_pattern);
 
-        if let Some(audio) = audio_path {
-            cmd.arg("-i").arg(audio);
+        let audio = Self::add_audio(&mut cmd, audio_path, settings.format);
+        let subtitles = Self::add_subtitles(&mut cmd, subtitles_path, settings);
+        let inputs = 1 + usize::from(audio.is_some()) + usize::from(subtitles.is_some());
+        Self::add_chapters(&mut cmd, chapters_path, inputs, settings.format);
+
+        let video_output = Self::burn_subtitles(video_args.output, subtitles_path, settings);
+        // Filtered frames (uploaded to the GPU, paletted for a GIF, or with
+        // subtitles burned in) can't be scaled afterwards; they already have
+        // the right size
+        let filtered = video_output.iter().any(|arg| arg == "-vf");
+        cmd.args(video_output);
+        if !filtered {
+            cmd.arg("-s").arg(format!("{}x{}", width, height));
         }
 
-        cmd.arg("-c:v")
-            .arg("libx264") // Video codec
+        if let Some(codec) = audio {
+            cmd.arg("-c:a")
+                .arg(codec) // Audio codec
+                .arg("-shortest"); // Finish when shortest stream ends (video)
+        }
+        if let Some(codec) = subtitles {
+            cmd.arg("-c:s").arg(codec);
+        }
+
+        cmd.arg(output_path);
+        cmd
+    }
+
+    /// Start an ffmpeg process that encodes raw RGBA frames written to it, so
+    /// no intermediate frame files are needed. The frames can't be replayed, so
+    /// when a hardware encoder fails (see `StreamingEncoder::hardware`) it's up
+    /// to the caller to stream them again in software. `subtitles_path` is burned
+    /// in or added as a subtitle stream, as `settings` say.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_stream(
+        output_path: &Path,
+        fps: u32,
+        width: u32,
+        height: u32,
+        audio_path: Option<&Path>,
+        chapters_path: Option<&Path>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+    ) -> Result<StreamingEncoder> {
+        let video_args = settings.encoder_args(fps)?;
+        Self::check_available()?;
+
+        say!("🎥 Streaming frames to {}...", output_path.display());
+
+        let hardware = video_args.hardware;
+        let cmd = Self::stream_command(
+            output_path,
+            fps,
+            (width, height),
+            audio_path,
+            chapters_path,
+            subtitles_path,
+            settings,
+            video_args,
+        );
+        debug!("ffmpeg command: {:?}", cmd);
+        StreamingEncoder::spawn(cmd, width, height, hardware)
+    }
+
+    /// The ffmpeg command `encode` (with `frame_pattern`) or `start_stream`
+    /// (without) would run, as a shell command line, to show rather than run it
+    #[allow(clippy::too_many_arguments)]
+    pub fn command_line(
+        frame_pattern: Option<&str>,
+        output_path: &Path,
+        fps: u32,
+        width: u32,
+        height: u32,
+        audio_path: Option<&Path>,
+        chapters_path: Option<&Path>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+    ) -> Result<String> {
+        let video_args = settings.encoder_args(fps)?;
+        let cmd = match frame_pattern {
+            Some(pattern) => Self::encode_command(
+                pattern,
+                output_path,
+                fps,
+                (width, height),
+                audio_path,
+                chapters_path,
+                subtitles_path,
+                settings,
+                video_args,
+            ),
+            None => Self::stream_command(
+                output_path,
+                fps,
+                (width, height),
+                audio_path,
+                chapters_path,
+                subtitles_path,
+                settings,
+                video_args,
+            ),
+        };
+        let words: Vec<String> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|word| shell_quote(&word.to_string_lossy()))
+            .collect();
+        Ok(words.join(" "))
+    }
+
+    /// The ffmpeg command that encodes raw RGBA frames from stdin
+    #[allow(clippy::too_many_arguments)]
+    fn stream_command(
+        output_path: &Path,
+        fps: u32,
+        (width, height): (u32, u32),
+        audio_path: Option<&Path>,
+        chapters_path: Option<&Path>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+        video_args: VideoArgs,
+    ) -> Command {
+        let mut cmd = Command::new("ffmpeg");
+        cmd.arg("-y")
+            .arg("-hide_banner")
+            .arg("-loglevel")
+            .arg("error")
+            .args(&video_args.global)
+            .arg("-f")
+            .arg("rawvideo")
             .arg("-pix_fmt")
-            .arg("yuv420p") // Pixel format for compatibility
+            .arg("rgba")
             .arg("-s")
-            .arg(format!("{}x{}", width, height));
+            .arg(format!("{}x{}", width, height))
+            .arg("-framerate")
+            .arg(fps.to_string())
+            .arg("-i")
+            .arg("-");
 
-        if audio_path.is_some() {
-            cmd.arg("-c:a")
-                .arg("aac") // Audio codec
-                .arg("-shortest"); // Finish when shortest stream ends (video)
+        let audio = Self::add_audio(&mut cmd, audio_path, settings.format);
+        let subtitles = Self::add_subtitles(&mut cmd, subtitles_path, settings);
+        let inputs = 1 + usize::from(audio.is_some()) + usize::from(subtitles.is_some());
+        Self::add_chapters(&mut cmd, chapters_path, inputs, settings.format);
+
+        cmd.args(Self::burn_subtitles(
+            video_args.output,
+            subtitles_path,
+            settings,
+        ));
+
+        if let Some(codec) = audio {
+            cmd.arg("-c:a").arg(codec).arg("-shortest");
+        }
+        if let Some(codec) = subtitles {
+            cmd.arg("-c:s").arg(codec);
+        }
+
+        cmd.arg(output_path);
+        cmd
+    }
+
+    /// Add the audio as the second input when `format` has audio, returning
+    /// the codec to encode it with
+    fn add_audio(
+        cmd: &mut Command,
+        audio_path: Option<&Path>,
+        format: OutputFormat,
+    ) -> Option<&'static str> {
+        let codec = format.audio_codec()?;
+        let audio = audio_path?;
+        cmd.arg("-i").arg(audio);
+        Some(codec)
+    }
+
+    /// Add the subtitles as the next input when they're added as a stream
+    /// rather than burned in and the format holds one, returning its codec
+    fn add_subtitles(
+        cmd: &mut Command,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+    ) -> Option<&'static str> {
+        if settings.burn_subtitles {
+            return None;
+        }
+        let codec = settings.format.subtitle_codec()?;
+        let subtitles = subtitles_path?;
+        cmd.arg("-i").arg(subtitles);
+        Some(codec)
+    }
+
+    /// `video_output` with the subtitles burned in first, when they are
+    fn burn_subtitles(
+        mut video_output: Vec<String>,
+        subtitles_path: Option<&Path>,
+        settings: &EncodeSettings,
+    ) -> Vec<String> {
+        let Some(path) = subtitles_path.filter(|_| settings.burn_subtitles) else {
+            return video_output;
+        };
+        let filter = subtitles_filter(
+            &path.to_string_lossy(),
+            &settings.subtitle_style,
+            cfg!(windows),
+        );
+        match video_output.iter().position(|arg| arg == "-vf") {
+            Some(i) => video_output[i + 1] = format!("{},{}", filter, video_output[i + 1]),
+            None => {
+                video_output.insert(0, filter);
+                video_output.insert(0, "-vf".to_string());
+            }
         }
+        video_output
+    }
+
+    /// Add the chapters file as the last input (the `input`th, after the frames,
+    /// audio and subtitles) and take the video's chapters from it, when `format`
+    /// holds chapters
+    fn add_chapters(
+        cmd: &mut Command,
+        chapters_path: Option<&Path>,
+        input: usize,
+        format: OutputFormat,
+    ) {
+        if let (Some(chapters), true) = (chapters_path, format.has_chapters()) {
+            cmd.arg("-f")
+                .arg("ffmetadata")
+                .arg("-i")
+                .arg(chapters)
+                .arg("-map_chapters")
+                .arg(input.to_string());
+        }
+    }
+}
 
-        let status = cmd
-            .arg(output_path)
-            .status()
-            .context("Failed to execute ffmpeg")?;
+/// `word` as a POSIX shell reads it back: as is when it's only safe characters,
+/// single-quoted otherwise
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+%@".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// A running ffmpeg process fed with raw frames over stdin
+pub struct StreamingEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    /// Drains stderr so ffmpeg never blocks on a full pipe
+    stderr: Option<JoinHandle<String>>,
+    width: u32,
+    height: u32,
+    hardware: Option<HardwareEncoder>,
+}
+
+impl StreamingEncoder {
+    fn spawn(
+        mut cmd: Command,
+        width: u32,
+        height: u32,
+        hardware: Option<HardwareEncoder>,
+    ) -> Result<Self> {
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+
+        let stdin = child.stdin.take();
+        let stderr = child.stderr.take().map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut output = String::new();
+                let _ = pipe.read_to_string(&mut output);
+                output
+            })
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            stderr,
+            width,
+            height,
+            hardware,
+        })
+    }
 
+    /// The hardware encoder in use, if any
+    pub fn hardware(&self) -> Option<HardwareEncoder> {
+        self.hardware
+    }
+
+    /// Close stdin and wait for ffmpeg to finish writing the video
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self
+            .child
+            .wait()
+            .map_err(VideoEngineError::io("wait for", "ffmpeg"))?;
         if !status.success() {
-            anyhow::bail!("FFmpeg encoding failed");
+            return Err(encoding_failed(status, &self.collect_stderr()));
+        }
+        Ok(())
+    }
+
+    /// The end of ffmpeg's error output; only complete once the process has exited
+    fn collect_stderr(&mut self) -> String {
+        self.stderr
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .map(|output| tail_lines(&output, STDERR_TAIL_LINES))
+            .unwrap_or_default()
+    }
+}
+
+impl FrameSink for StreamingEncoder {
+    fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()> {
+        if frame.dimensions() != (self.width, self.height) {
+            return Err(VideoEngineError::invalid(format!(
+                "Frame is {}x{} but the encoder expects {}x{}",
+                frame.dimensions().0,
+                frame.dimensions().1,
+                self.width,
+                self.height
+            )));
         }
 
+        let Some(stdin) = self.stdin.as_mut() else {
+            return Err(VideoEngineError::invalid("Encoder input is already closed"));
+        };
+        // The encoder takes 8-bit RGBA; 16-bit frames are dithered down
+        if let Err(e) = stdin.write_all(&frame.to_rgba8_dithered()) {
+            // ffmpeg exited early (usually a broken pipe); report why
+            drop(self.stdin.take());
+            let status = self
+                .child
+                .wait()
+                .map_err(VideoEngineError::io("wait for", "ffmpeg"))?;
+            return Err(VideoEngineError::external(
+                "FFmpeg",
+                format!(
+                    "stopped accepting frames ({}, {}): {}",
+                    e,
+                    status,
+                    self.collect_stderr()
+                ),
+            ));
+        }
         Ok(())
     }
 }
+
+impl Drop for StreamingEncoder {
+    fn drop(&mut self) {
+        // Abandoned without `finish` (e.g. a render error): don't leave ffmpeg running
+        if self.stdin.take().is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::renderer::ProgressEvent;
+
+    fn settings(codec: VideoCodec) -> EncodeSettings {
+        EncodeSettings {
+            codec,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_settings_match_the_old_encoder() {
+        assert_eq!(
+            EncodeSettings::default().video_args().unwrap(),
+            ["-c:v", "libx264", "-pix_fmt", "yuv420p"]
+        );
+    }
+
+    #[test]
+    fn test_codec_arguments() {
+        let h265 = EncodeSettings {
+            crf: Some(20),
+            preset: Some("slow".into()),
+            ..settings(VideoCodec::H265)
+        };
+        assert_eq!(
+            h265.video_args().unwrap().join(" "),
+            "-c:v libx265 -preset slow -tag:v hvc1 -crf 20 -pix_fmt yuv420p"
+        );
+
+        assert_eq!(
+            settings(VideoCodec::Vp9).video_args().unwrap().join(" "),
+            "-c:v libvpx-vp9 -b:v 0 -crf 31 -pix_fmt yuv420p"
+        );
+        let vp9 = EncodeSettings {
+            bitrate: Some("2M".into()),
+            preset: Some("good".into()),
+            ..settings(VideoCodec::Vp9)
+        };
+        assert_eq!(
+            vp9.video_args().unwrap().join(" "),
+            "-c:v libvpx-vp9 -deadline good -b:v 2M -pix_fmt yuv420p"
+        );
+
+        assert_eq!(
+            settings(VideoCodec::Prores).video_args().unwrap().join(" "),
+            "-c:v prores_ks -profile:v 3 -pix_fmt yuv422p10le"
+        );
+        let proxy = EncodeSettings {
+            preset: Some("proxy".into()),
+            ..settings(VideoCodec::Prores)
+        };
+        assert!(proxy
+            .video_args()
+            .unwrap()
+            .join(" ")
+            .contains("-profile:v 0"));
+
+        let av1 = EncodeSettings {
+            crf: Some(35),
+            preset: Some("8".into()),
+            pixel_format: Some("yuv420p10le".into()),
+            ..settings(VideoCodec::Av1)
+        };
+        assert_eq!(
+            av1.video_args().unwrap().join(" "),
+            "-c:v libsvtav1 -preset 8 -crf 35 -pix_fmt yuv420p10le"
+        );
+        assert_eq!(VideoCodec::Prores.extension(), "mov");
+        assert_eq!(VideoCodec::Av1.extension(), "mp4");
+    }
+
+    fn encoders(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_encoders() {
+        let output = "Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ V....D h264_nvenc           NVIDIA NVENC H.264 encoder (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+";
+        assert_eq!(
+            parse_encoders(output),
+            encoders(&["libx264", "h264_nvenc", "aac"])
+        );
+        assert!(parse_encoders("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let output = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  A = Audio input/output
+  | = Source or sink filter
+ ... abench            A->A       Benchmark part of a filtergraph.
+ T.C subtitles         V->V       Render text subtitles onto input video using the libass library.
+ ... color             |->V       Provide an uniformly colored input.
+";
+        assert_eq!(
+            parse_filters(output),
+            encoders(&["abench", "subtitles", "color"])
+        );
+    }
+
+    #[test]
+    fn test_subtitles_are_burned_or_muxed() {
+        let subtitles = Path::new("subs.srt");
+        let burn = EncodeSettings {
+            burn_subtitles: true,
+            ..Default::default()
+        };
+        let burned =
+            VideoEncoder::burn_subtitles(burn.video_args().unwrap(), Some(subtitles), &burn);
+        assert_eq!(burned[..2], ["-vf", "subtitles=filename=subs.srt"]);
+
+        // Ahead of the format's own filters
+        let gif = EncodeSettings {
+            format: "gif".parse().unwrap(),
+            ..burn.clone()
+        };
+        let burned =
+            VideoEncoder::burn_subtitles(gif.format_args(30).unwrap(), Some(subtitles), &gif);
+        assert!(burned[1].starts_with("subtitles=filename=subs.srt,fps=15,"));
+
+        // A burned-in file isn't also added as a stream
+        let mut cmd = Command::new("ffmpeg");
+        assert_eq!(
+            VideoEncoder::add_subtitles(&mut cmd, Some(subtitles), &burn),
+            None
+        );
+        assert_eq!(cmd.get_args().count(), 0);
+
+        let soft = EncodeSettings::default();
+        assert_eq!(
+            VideoEncoder::burn_subtitles(soft.video_args().unwrap(), Some(subtitles), &soft),
+            soft.video_args().unwrap()
+        );
+        assert_eq!(
+            VideoEncoder::add_subtitles(&mut cmd, Some(subtitles), &soft),
+            Some("mov_text")
+        );
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-i", "subs.srt"]);
+    }
+
+    #[test]
+    fn test_command_line() {
+        let settings = EncodeSettings::default();
+        let streamed = VideoEncoder::command_line(
+            None,
+            Path::new("my video.mp4"),
+            30,
+            1920,
+            1080,
+            Some(Path::new("output/audio.wav")),
+            None,
+            None,
+            &settings,
+        )
+        .unwrap();
+        assert!(streamed.starts_with("ffmpeg -y "));
+        assert!(streamed.contains("-f rawvideo -pix_fmt rgba -s 1920x1080 -framerate 30 -i -"));
+        assert!(streamed.contains("-i output/audio.wav"));
+        assert!(streamed.ends_with(" 'my video.mp4'"));
+
+        let from_files = VideoEncoder::command_line(
+            Some("output/frame_%06d.png"),
+            Path::new("out.mp4"),
+            30,
+            1920,
+            1080,
+            None,
+            None,
+            None,
+            &settings,
+        )
+        .unwrap();
+        assert!(from_files.contains("-f image2 -framerate 30 -i output/frame_%06d.png"));
+        assert!(!from_files.contains("-c:a"));
+
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_hardware_encoder_selection() {
+        let all = encoders(&[
+            "libx264",
+            "h264_videotoolbox",
+            "hevc_videotoolbox",
+            "h264_nvenc",
+            "hevc_nvenc",
+            "h264_vaapi",
+        ]);
+        let select = |codec, platform, available: &HashSet<String>| {
+            HardwareEncoder::select(codec, platform, available).map(|(_, name)| name)
+        };
+
+        assert_eq!(
+            select(VideoCodec::H264, "macos", &all),
+            Some("h264_videotoolbox")
+        );
+        assert_eq!(
+            select(VideoCodec::H265, "macos", &all),
+            Some("hevc_videotoolbox")
+        );
+        // NVENC is preferred over VAAPI on Linux
+        assert_eq!(select(VideoCodec::H264, "linux", &all), Some("h264_nvenc"));
+        assert_eq!(
+            select(VideoCodec::H265, "windows", &all),
+            Some("hevc_nvenc")
+        );
+
+        let vaapi = encoders(&["libx264", "h264_vaapi"]);
+        assert_eq!(
+            select(VideoCodec::H264, "linux", &vaapi),
+            Some("h264_vaapi")
+        );
+        assert_eq!(select(VideoCodec::H265, "linux", &vaapi), None);
+        // VAAPI is Linux only
+        assert_eq!(select(VideoCodec::H264, "windows", &vaapi), None);
+        assert_eq!(
+            select(VideoCodec::H264, "macos", &encoders(&["libx264"])),
+            None
+        );
+        // Other codecs are always encoded in software
+        assert_eq!(select(VideoCodec::Vp9, "linux", &all), None);
+        assert_eq!(select(VideoCodec::Prores, "macos", &all), None);
+    }
+
+    #[test]
+    fn test_hardware_quality_mapping() {
+        let h264 = EncodeSettings {
+            crf: Some(23),
+            preset: Some("slow".into()),
+            hardware: true,
+            ..Default::default()
+        };
+
+        let toolbox = h264
+            .hardware_args(HardwareEncoder::VideoToolbox, "h264_videotoolbox")
+            .unwrap();
+        assert!(toolbox.global.is_empty());
+        assert_eq!(
+            toolbox.output.join(" "),
+            "-c:v h264_videotoolbox -q:v 55 -pix_fmt yuv420p"
+        );
+        assert_eq!(toolbox.hardware, Some(HardwareEncoder::VideoToolbox));
+
+        let nvenc = h264
+            .hardware_args(HardwareEncoder::Nvenc, "h264_nvenc")
+            .unwrap();
+        assert_eq!(
+            nvenc.output.join(" "),
+            "-c:v h264_nvenc -preset p5 -rc vbr -cq 23 -pix_fmt yuv420p"
+        );
+
+        let vaapi = h264
+            .hardware_args(HardwareEncoder::Vaapi, "h264_vaapi")
+            .unwrap();
+        assert_eq!(vaapi.global, ["-vaapi_device", VAAPI_DEVICE]);
+        assert_eq!(
+            vaapi.output.join(" "),
+            "-vf format=nv12,hwupload -c:v h264_vaapi -qp 23"
+        );
+
+        let bitrate = EncodeSettings {
+            codec: VideoCodec::H265,
+            bitrate: Some("20M".into()),
+            hardware: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            bitrate
+                .hardware_args(HardwareEncoder::VideoToolbox, "hevc_videotoolbox")
+                .unwrap()
+                .output
+                .join(" "),
+            "-c:v hevc_videotoolbox -b:v 20M -tag:v hvc1 -pix_fmt yuv420p"
+        );
+
+        // Software settings still apply without hardware
+        assert_eq!(
+            EncodeSettings::default().encoder_args(30).unwrap(),
+            VideoArgs::software(EncodeSettings::default().video_args().unwrap())
+        );
+        assert!(!h264.software().hardware);
+    }
+
+    fn format(format: &str) -> EncodeSettings {
+        EncodeSettings {
+            format: format.parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_output_formats() {
+        assert_eq!(format("mp4").extension(), Some("mp4"));
+        assert_eq!(format("png-sequence").extension(), None);
+        assert!(format("png-sequence").validate().is_ok());
+        assert!("mkv".parse::<OutputFormat>().is_err());
+
+        let webm = format("webm");
+        assert_eq!(webm.extension(), Some("webm"));
+        assert_eq!(
+            webm.encoder_args(30).unwrap().output.join(" "),
+            "-c:v libvpx-vp9 -b:v 0 -crf 31 -pix_fmt yuv420p"
+        );
+        let alpha = format("webm-alpha");
+        assert_eq!(
+            alpha.encoder_args(30).unwrap().output.join(" "),
+            "-c:v libvpx-vp9 -b:v 0 -crf 31 -pix_fmt yuva420p -auto-alt-ref 0"
+        );
+        let av1 = EncodeSettings {
+            codec: VideoCodec::Av1,
+            ..format("webm")
+        };
+        assert!(av1
+            .encoder_args(30)
+            .unwrap()
+            .output
+            .contains(&"libsvtav1".to_string()));
+        for codec in [VideoCodec::H265, VideoCodec::Prores] {
+            assert!(EncodeSettings {
+                codec,
+                ..format("webm")
+            }
+            .validate()
+            .is_err());
+        }
+        assert!(EncodeSettings {
+            codec: VideoCodec::Av1,
+            ..format("webm-alpha")
+        }
+        .validate()
+        .is_err());
+
+        let gif = format("gif");
+        assert_eq!(gif.extension(), Some("gif"));
+        let args = gif.encoder_args(30).unwrap();
+        assert_eq!(
+            args.output[1],
+            "fps=15,split[frames][copy];[copy]palettegen=max_colors=256[palette];\
+             [frames][palette]paletteuse"
+        );
+        assert_eq!(args.output[2..], ["-loop", "0"]);
+        // Slow videos keep their frame rate
+        assert!(gif.encoder_args(10).unwrap().output[1].starts_with("fps=10,"));
+        let tiny = EncodeSettings {
+            format: OutputFormat::Gif {
+                max_colors: 1,
+                fps_cap: 15,
+            },
+            ..Default::default()
+        };
+        assert!(tiny.validate().is_err());
+
+        // Only MP4 is encoded in hardware
+        let hardware_gif = EncodeSettings {
+            hardware: true,
+            ..format("gif")
+        };
+        assert_eq!(hardware_gif.encoder_args(30).unwrap().hardware, None);
+        assert!(!OutputFormat::PngSequence.encodes());
+        assert_eq!(
+            OutputFormat::Gif {
+                max_colors: 2,
+                fps_cap: 1
+            }
+            .audio_codec(),
+            None
+        );
+        assert_eq!(
+            OutputFormat::Webm { alpha: true }.audio_codec(),
+            Some("libopus")
+        );
+    }
+
+    #[test]
+    fn test_incompatible_settings_are_rejected() {
+        let invalid = [
+            EncodeSettings {
+                crf: Some(18),
+                ..settings(VideoCodec::Prores)
+            },
+            EncodeSettings {
+                bitrate: Some("50M".into()),
+                ..settings(VideoCodec::Prores)
+            },
+            EncodeSettings {
+                crf: Some(20),
+                bitrate: Some("4M".into()),
+                ..Default::default()
+            },
+            EncodeSettings {
+                crf: Some(52),
+                ..Default::default()
+            },
+            EncodeSettings {
+                preset: Some("medium".into()),
+                ..settings(VideoCodec::Vp9)
+            },
+            EncodeSettings {
+                preset: Some("fastest".into()),
+                ..Default::default()
+            },
+            EncodeSettings {
+                preset: Some("14".into()),
+                ..settings(VideoCodec::Av1)
+            },
+        ];
+        for settings in invalid {
+            assert!(settings.validate().is_err(), "{:?}", settings);
+        }
+
+        let error = EncodeSettings {
+            crf: Some(18),
+            ..settings(VideoCodec::Prores)
+        }
+        .validate()
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("profile"), "{}", error);
+        // VP9 and AV1 go up to 63
+        assert!(EncodeSettings {
+            crf: Some(63),
+            ..settings(VideoCodec::Vp9)
+        }
+        .validate()
+        .is_ok());
+    }
+
+    const PROGRESS_OUTPUT: &str = "frame=24
+fps=47.95
+stream_0_0_q=28.0
+out_time_us=800000
+out_time_ms=800000
+out_time=00:00:00.800000
+speed=N/A
+progress=continue
+frame=60
+fps=58.10
+out_time_us=2000000
+speed=1.93x
+progress=end
+";
+
+    #[test]
+    fn test_progress_parser() {
+        let mut parser = ProgressParser::default();
+        let reports: Vec<FfmpegProgress> = PROGRESS_OUTPUT
+            .lines()
+            .filter_map(|line| parser.parse_line(line))
+            .collect();
+        assert_eq!(
+            reports,
+            vec![
+                FfmpegProgress {
+                    frame: 24,
+                    fps: 47.95,
+                    out_time_seconds: 0.8,
+                    speed: None,
+                    finished: false,
+                },
+                FfmpegProgress {
+                    frame: 60,
+                    fps: 58.1,
+                    out_time_seconds: 2.0,
+                    speed: Some(1.93),
+                    finished: true,
+                },
+            ]
+        );
+
+        // Lines that aren't key=value, and unknown keys, are ignored
+        assert_eq!(parser.parse_line("Input #0, image2"), None);
+        assert_eq!(parser.parse_line("bitrate=N/A"), None);
+    }
+
+    #[test]
+    fn test_tail_lines() {
+        let output: String = (1..=40).map(|i| format!("line {}\n", i)).collect();
+        let tail = tail_lines(&output, STDERR_TAIL_LINES);
+        assert!(tail.starts_with("line 11\n"));
+        assert!(tail.ends_with("line 40"));
+        assert_eq!(tail_lines("only\n", 30), "only");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_progress_reports_and_quotes_stderr() {
+        let script = format!(
+            "printf '{}'; for i in $(seq 1 40); do echo \"noise $i\" >&2; done; \
+             echo 'frame_%06d.png: No such file or directory' >&2; exit 1",
+            PROGRESS_OUTPUT.replace('\n', "\\n")
+        );
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+
+        let mut events = Vec::new();
+        let error = run_with_progress(cmd, Duration::from_secs(10), &mut |event: ProgressEvent| {
+            events.push(event.kind)
+        })
+        .unwrap_err()
+        .to_string();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[1],
+            ProgressKind::EncodeProgress { frame: 60, .. }
+        ));
+        assert!(error.contains("No such file or directory"), "{}", error);
+        // Only the last 30 lines of stderr are quoted
+        assert!(
+            error.contains("noise 40") && !error.contains("noise 10\n"),
+            "{}",
+            error
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_progress_stops_stalled_ffmpeg() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo frame=1; exec sleep 30");
+        let started = std::time::Instant::now();
+
+        let error = run_with_progress(cmd, Duration::from_millis(300), &mut |_: ProgressEvent| {})
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("no progress"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_early_exit_reports_stderr() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo 'bad encoder settings' >&2; exit 3");
+        let mut encoder = StreamingEncoder::spawn(cmd, 512, 512, None).unwrap();
+
+        // 1 MiB frames overflow the pipe buffer once the reader is gone
+        let frame = FrameBuffer::new(512, 512);
+        let error = (0..64)
+            .find_map(|_| encoder.write_frame(&frame).err())
+            .expect("writes to an exited process should fail");
+        let message = error.to_string();
+        assert!(message.contains("bad encoder settings"), "{}", message);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_exit_reported_on_finish() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg("cat > /dev/null; echo 'muxer failed' >&2; exit 1");
+        let mut encoder = StreamingEncoder::spawn(cmd, 2, 2, None).unwrap();
+        encoder.write_frame(&FrameBuffer::new(2, 2)).unwrap();
+
+        let error = encoder.finish().unwrap_err().to_string();
+        assert!(error.contains("muxer failed"), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_wrong_frame_size() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("cat > /dev/null");
+        let mut encoder = StreamingEncoder::spawn(cmd, 4, 4, None).unwrap();
+        assert!(encoder.write_frame(&FrameBuffer::new(2, 2)).is_err());
+        encoder.finish().unwrap();
+    }
+}
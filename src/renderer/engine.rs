@@ -1,120 +1,620 @@
 use crate::assets::AssetLoader;
-use crate::renderer::{Compositor, FrameBuffer, GpuRenderer, Timeline};
-use crate::script::{Layer, VideoScript};
-use anyhow::Result;
+use crate::error::{Result, ValidationError, VideoEngineError};
+use crate::renderer::debug_overlay::{self, TextBounds};
+use crate::renderer::thumbnail::title_font;
+use crate::renderer::{
+    BitDepth, CancellationToken, Compositor, DebugOverlaySettings, FrameBuffer, FrameContext,
+    FrameSink, GpuOptions, GpuRenderer, LayerRenderContext, LayerRenderer, PendingFrame,
+    ProgressKind, RenderProgress, Timeline, TransitionContext, VideoFrameSource, VideoTexture,
+    WaveformAnalysis,
+};
+use crate::script::{Crop, Effect, Layer, Position, Transform, Transition, VideoScript};
+use ab_glyph::FontRef;
 use dashmap::DashMap;
 use image::GenericImageView;
 use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+#[cfg(feature = "async")]
+pub mod r#async;
 
 /// Cached texture entry: (BindGroup, width, height)
 type TextureCacheEntry = (Arc<wgpu::BindGroup>, u32, u32);
 
+/// GPU memory image textures may use unless configured otherwise
+pub const DEFAULT_TEXTURE_BUDGET_MB: u64 = 1024;
+
+/// Image texture cache activity over a render
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCacheStats {
+    /// Image layers drawn from an already uploaded texture
+    pub hits: u64,
+    /// Textures uploaded because they weren't cached
+    pub misses: u64,
+    /// Textures dropped to stay within the budget
+    pub evictions: u64,
+}
+
+impl TextureCacheStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} hits, {} misses, {} evicted",
+            self.hits, self.misses, self.evictions
+        )
+    }
+}
+
+/// Approximate GPU memory of an RGBA8 texture
+fn texture_bytes(width: u32, height: u32) -> u64 {
+    width as u64 * height as u64 * 4
+}
+
 /// Main rendering engine
 pub struct RenderEngine {
     script: VideoScript,
     timeline: Timeline,
     frame_buffer: FrameBuffer,
+    /// Receives GPU frames read back while the next frame is drawn
+    readback_buffer: FrameBuffer,
     #[allow(dead_code)]
     gpu_renderer: Option<GpuRenderer>,
     texture_cache: Arc<DashMap<std::path::PathBuf, TextureCacheEntry>>,
+    /// Approximate bytes held by `texture_cache`
+    texture_bytes: u64,
+    texture_budget: u64,
+    texture_stats: TextureCacheStats,
+    /// Audio levels for waveform layers, analyzed once per render
+    waveform_cache: HashMap<PathBuf, WaveformAnalysis>,
+    /// Decoders for video layers; `None` when the file could not be opened
+    video_sources: HashMap<PathBuf, Option<VideoFrameSource>>,
+    /// One GPU texture per video source, refilled every frame
+    video_textures: HashMap<PathBuf, VideoTexture>,
+    /// Checked before every frame of `render` and `render_range`
+    cancellation: Option<CancellationToken>,
+    /// Plugins drawing custom layers, by layer type
+    layer_renderers: HashMap<String, Arc<dyn LayerRenderer>>,
+    /// Drawn over every frame when set
+    debug_overlay: Option<DebugOverlaySettings>,
 }
 
 impl RenderEngine {
     /// Create new render engine from script
     pub fn new(script: VideoScript, use_gpu: bool) -> Self {
-        let (width, height) = script.metadata.resolution.dimensions();
-        let timeline = Timeline::from_script(&script);
-        let frame_buffer = FrameBuffer::new(width, height);
+        Self::with_gpu(script, use_gpu.then(GpuOptions::default).as_ref())
+    }
 
+    /// Create a render engine that renders on the GPU adapter `gpu` selects,
+    /// or on the CPU when `gpu` is `None` or no adapter can be used
+    pub fn with_gpu(script: VideoScript, gpu: Option<&GpuOptions>) -> Self {
+        let (width, height) = script.metadata.resolution.dimensions();
         // Try to initialize GPU renderer (optional - falls back to CPU if fails)
-        let gpu_renderer = if use_gpu {
-            pollster::block_on(async { GpuRenderer::new(width, height).await.ok() })
-        } else {
-            None
+        let gpu_renderer = match gpu {
+            Some(options) => match pollster::block_on(GpuRenderer::new(width, height, options)) {
+                Ok(gpu) => Some(gpu),
+                Err(e) => {
+                    say!("ℹ️  GPU unavailable: {:#}", e);
+                    None
+                }
+            },
+            None => None,
         };
+        Self::with_gpu_renderer(script, gpu_renderer)
+    }
+
+    /// Create a render engine drawing with `gpu_renderer`, created at the
+    /// script's resolution, or on the CPU when `None`
+    pub fn with_gpu_renderer(script: VideoScript, gpu_renderer: Option<GpuRenderer>) -> Self {
+        let (width, height) = script.metadata.resolution.dimensions();
+        let timeline = Timeline::from_script(&script);
+        let frame_buffer = FrameBuffer::new(width, height);
 
         if gpu_renderer.is_some() {
-            println!("✨ GPU renderer initialized successfully");
+            say!("✨ GPU renderer initialized successfully");
         } else {
-            println!(
-                "ℹ️  Using CPU rendering (GPU unavailable, initialization failed, or disabled)"
-            );
+            say!("ℹ️  Using CPU rendering");
         }
 
         Self {
             script,
             timeline,
+            readback_buffer: FrameBuffer::new(width, height),
             frame_buffer,
             gpu_renderer,
             texture_cache: Arc::new(DashMap::new()),
+            texture_bytes: 0,
+            texture_budget: DEFAULT_TEXTURE_BUDGET_MB * 1024 * 1024,
+            texture_stats: TextureCacheStats::default(),
+            waveform_cache: HashMap::new(),
+            video_sources: HashMap::new(),
+            video_textures: HashMap::new(),
+            cancellation: None,
+            layer_renderers: HashMap::new(),
+            debug_overlay: None,
+        }
+    }
+
+    /// Antialias GPU-drawn layers with `samples` MSAA samples per pixel (1, 2 or 4)
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        if let Some(gpu) = &mut self.gpu_renderer {
+            gpu.set_sample_count(samples);
+        }
+        self
+    }
+
+    /// Composite frames with `depth` bits per channel. GPU-drawn layers are
+    /// still 8-bit; the extra precision applies to CPU blending and output.
+    pub fn with_bit_depth(mut self, depth: BitDepth) -> Self {
+        let (width, height) = self.frame_buffer.dimensions();
+        let seed = self.frame_buffer.dither_seed();
+        self.frame_buffer =
+            FrameBuffer::with_bit_depth(width, height, depth).with_dither_seed(seed);
+        self.readback_buffer =
+            FrameBuffer::with_bit_depth(width, height, depth).with_dither_seed(seed);
+        self
+    }
+
+    /// Seed everything random in the output, so far the dither pattern of
+    /// 16-bit frames narrowed to 8 bits. The same script, settings and seed
+    /// always give the same CPU-drawn frames.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.frame_buffer = self.frame_buffer.with_dither_seed(seed);
+        self.readback_buffer = self.readback_buffer.with_dither_seed(seed);
+        self
+    }
+
+    /// Draw the layout debugging overlay `settings` describes over every
+    /// frame, above the watermark
+    pub fn with_debug_overlay(mut self, settings: DebugOverlaySettings) -> Self {
+        if settings.label_font.is_none() && title_font(&self.script).is_none() {
+            say!("ℹ️  The script has no text layer to take a label font from; set renderer.debug.label_font for the overlay's labels");
         }
+        self.debug_overlay = Some(settings);
+        self
+    }
+
+    /// Limit the GPU memory used by cached image textures to roughly `bytes`
+    pub fn with_texture_budget(mut self, bytes: u64) -> Self {
+        self.texture_budget = bytes;
+        self
+    }
+
+    /// Stop rendering with a [`VideoEngineError::Cancelled`] error once `token` is cancelled.
+    /// Frames already written to the sink stay there.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Draw custom layers of `renderer.kind()` with `renderer`, replacing any
+    /// renderer registered for that type before
+    pub fn register_layer_renderer(&mut self, renderer: impl LayerRenderer + 'static) {
+        self.layer_renderers
+            .insert(renderer.kind().to_string(), Arc::new(renderer));
+    }
+
+    /// Check every custom layer has a registered renderer that accepts its
+    /// params. `render` and `render_range` check this before their first frame.
+    pub fn validate_layers(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        for scene in &self.script.scenes {
+            for layer in &scene.layers {
+                let Layer::Custom { kind, params, .. } = layer else {
+                    continue;
+                };
+                match self.layer_renderers.get(kind) {
+                    Some(renderer) => {
+                        if let Err(e) = renderer.validate(params) {
+                            errors.push(ValidationError::in_scene(
+                                &scene.id,
+                                format!("{} in scene '{}'", e, scene.id),
+                            ));
+                        }
+                    }
+                    None => errors.push(self.missing_renderer(kind, &scene.id)),
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(VideoEngineError::Validation(errors))
+        }
+    }
+
+    /// The error for a custom layer of `kind` in `scene_id` nothing draws
+    fn missing_renderer(&self, kind: &str, scene_id: &str) -> ValidationError {
+        let mut registered: Vec<_> = self.layer_renderers.keys().map(String::as_str).collect();
+        registered.sort_unstable();
+        let registered = if registered.is_empty() {
+            "none are registered".to_string()
+        } else {
+            format!("registered: {}", registered.join(", "))
+        };
+        ValidationError::in_scene(
+            scene_id,
+            format!(
+                "No renderer for layer type '{}' in scene '{}' ({})",
+                kind, scene_id, registered
+            ),
+        )
     }
 
     /// Render a single frame
     pub fn render_frame(
         &mut self,
         frame_number: u32,
-        _asset_loader: &mut AssetLoader,
+        asset_loader: &mut AssetLoader,
     ) -> Result<()> {
+        let pending = self.draw_frame(frame_number, asset_loader)?;
+        if let (Some(pending), Some(gpu)) = (pending, &mut self.gpu_renderer) {
+            gpu.wait(pending, &mut self.frame_buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Draw a frame, leaving its final GPU layers queued rather than read back.
+    /// With `Some`, the finished frame comes from `GpuRenderer::wait`; otherwise
+    /// it is already in the frame buffer.
+    fn draw_frame(
+        &mut self,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<Option<PendingFrame>> {
         // Clear frame
         self.frame_buffer.clear([0, 0, 0, 255]);
 
-        // Get current scene ID
-        if let Some(scene_id) = self.timeline.get_scene_at_frame(frame_number) {
-            // Clone the scene ID to avoid borrow checker issues
-            let scene_id = scene_id.to_string();
-
-            // Find and render the scene
-            if let Some(scene) = self.script.scenes.iter().find(|s| s.id == scene_id) {
-                // Collect layers to avoid borrowing issues
-                let layers: Vec<_> = scene.layers.clone();
-
-                // Parallel: Load all images from disk concurrently
-                // This is the IO-bound bottleneck, so parallelizing it helps significantly
-                let loaded_images: Vec<_> = layers
-                    .par_iter()
-                    .filter_map(|layer| {
-                        if let Layer::Image { source, .. } = layer {
-                            if !self.texture_cache.contains_key(source) {
-                                let full_path = if source.is_absolute() {
-                                    source.clone()
-                                } else {
-                                    _asset_loader.base_path().join(source)
-                                };
-
-                                if full_path.exists() {
-                                    if let Ok(img) = image::open(&full_path) {
-                                        return Some((source.clone(), img));
-                                    }
-                                }
-                            }
-                        }
-                        None
-                    })
-                    .collect();
-
-                // Sequential: Create GPU textures from loaded images
-                // GPURenderer is not Sync, so this must be done sequentially
-                if let Some(gpu) = &self.gpu_renderer {
-                    for (source, img) in loaded_images {
-                        let dims = img.dimensions();
-                        let bind_group = gpu.create_texture(&img);
-                        self.texture_cache
-                            .insert(source, (bind_group, dims.0, dims.1));
+        // Render the scene shown at this frame
+        let Some(context) = self.timeline.frame_context(frame_number) else {
+            return Ok(None);
+        };
+        let Some(scene) = self.script.scenes.get(context.scene_index) else {
+            return Ok(None);
+        };
+        // Collect layers to avoid borrowing issues
+        let layers: Vec<_> = scene.layers.clone();
+        let scene_id = scene.id.clone();
+
+        match &context.transition {
+            Some(transition) => {
+                self.draw_transition(transition, &context, frame_number, asset_loader)?
+            }
+            None => self.draw_layers(&layers, frame_number, &context, asset_loader)?,
+        }
+        if let Some(watermark) = self.watermark_layer(asset_loader) {
+            self.render_layer(&watermark, frame_number, &context, asset_loader)?;
+        }
+        if let Some(overlay) = self.debug_overlay.clone() {
+            self.draw_debug_overlay(&overlay, &scene_id, &layers, frame_number, asset_loader)?;
+        }
+
+        // Submit GPU commands after rendering all layers
+        if let Some(gpu) = &mut self.gpu_renderer {
+            return gpu.submit(&self.frame_buffer);
+        }
+        Ok(None)
+    }
+
+    /// Draw a scene's `layers` over the frame buffer, as `context` places them
+    fn draw_layers(
+        &mut self,
+        layers: &[Layer],
+        frame_number: u32,
+        context: &FrameContext,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
+        // Count cache lookups and find the textures that must be uploaded
+        let mut missing: Vec<PathBuf> = Vec::new();
+        if self.gpu_renderer.is_some() {
+            for layer in layers {
+                if let Layer::Image { source, .. } = layer {
+                    if self.texture_cache.contains_key(source) {
+                        debug!(texture = %source.display(), "texture cache hit");
+                        self.texture_stats.hits += 1;
+                    } else if !missing.contains(source) {
+                        missing.push(source.clone());
                     }
                 }
+            }
+        }
+
+        // Parallel: Load all images from disk concurrently
+        // This is the IO-bound bottleneck, so parallelizing it helps significantly
+        // (the CPU path reads decoded images from the AssetLoader instead)
+        let loaded_images: Vec<_> = missing
+            .par_iter()
+            .filter_map(|source| {
+                let full_path = if source.is_absolute() {
+                    source.clone()
+                } else {
+                    asset_loader.base_path().join(source)
+                };
 
-                // Sequential: Render each layer (GPU command submission) (GPU command submission)
-                for layer in &layers {
-                    self.render_layer(layer, _asset_loader)?;
+                if full_path.exists() {
+                    if let Ok(img) = image::open(&full_path) {
+                        return Some((source.clone(), img.to_rgba8()));
+                    }
                 }
+                None
+            })
+            .collect();
 
-                // Flush GPU commands after rendering all layers
-                self.flush_gpu()?;
+        // Sequential: Create GPU textures from loaded images
+        // GPURenderer is not Sync, so this must be done sequentially
+        for (source, img) in loaded_images {
+            self.upload_texture(source, &img, frame_number);
+        }
+
+        // Sequential: Render each layer (GPU command submission)
+        for layer in layers {
+            self.render_layer(layer, frame_number, context, asset_loader)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the scene at `scene_index` alone, as it looks at `frame_number`,
+    /// into the cleared frame buffer and finish it there
+    fn draw_scene_alone(
+        &mut self,
+        scene_index: usize,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
+        self.frame_buffer.clear([0, 0, 0, 255]);
+        let Some(context) = self.timeline.scene_context(scene_index, frame_number) else {
+            return Ok(());
+        };
+        let layers = self.script.scenes[scene_index].layers.clone();
+        self.draw_layers(&layers, frame_number, &context, asset_loader)?;
+        self.flush_gpu()
+    }
+
+    /// Draw a frame inside a transition window the way the outgoing scene's
+    /// transition asks: a dissolve mixes the two scenes by the transition's
+    /// progress, a fade darkens the scene shown to black at the cut, and a wipe
+    /// uncovers the incoming scene from one side
+    fn draw_transition(
+        &mut self,
+        transition: &TransitionContext,
+        context: &FrameContext,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
+        let outgoing = transition.outgoing_scene_index;
+        let progress = transition.progress;
+
+        match self.script.scenes[outgoing].transition.clone() {
+            Some(Transition::Fade { .. }) => {
+                self.draw_scene_alone(context.scene_index, frame_number, asset_loader)?;
+                let window = self
+                    .timeline
+                    .transition_frames(outgoing)
+                    .unwrap_or(frame_number..frame_number + 1);
+                let cut = self
+                    .timeline
+                    .scene_start(&transition.incoming_scene_id)
+                    .unwrap_or(window.start);
+                // Black takes over towards the cut and gives way after it
+                let darkness = if frame_number < cut {
+                    (frame_number - window.start) as f32 / (cut - window.start) as f32
+                } else {
+                    1.0 - (frame_number - cut) as f32 / (window.end - cut) as f32
+                };
+                let (width, height) = self.frame_buffer.dimensions();
+                let mut black = FrameBuffer::new(width, height);
+                black.clear([0, 0, 0, 255]);
+                self.frame_buffer.blend_buffer(&black, darkness);
+            }
+            Some(Transition::Dissolve { .. }) => {
+                self.draw_scene_alone(outgoing, frame_number, asset_loader)?;
+                let outgoing_frame = self.frame_buffer.clone();
+                self.draw_scene_alone(outgoing + 1, frame_number, asset_loader)?;
+                let incoming_frame = std::mem::replace(&mut self.frame_buffer, outgoing_frame);
+                self.frame_buffer.blend_buffer(&incoming_frame, progress);
+            }
+            Some(Transition::Wipe { direction, .. }) => {
+                self.draw_scene_alone(outgoing, frame_number, asset_loader)?;
+                let outgoing_frame = self.frame_buffer.clone();
+                self.draw_scene_alone(outgoing + 1, frame_number, asset_loader)?;
+                let incoming_frame = std::mem::replace(&mut self.frame_buffer, outgoing_frame);
+
+                let (width, height) = self.frame_buffer.dimensions();
+                let covered = |size: u32| (size as f32 * progress).round() as u32;
+                let (x, y, w, h) = match direction.as_str() {
+                    "left" => (width - covered(width), 0, covered(width), height),
+                    "up" => (0, height - covered(height), width, covered(height)),
+                    "down" => (0, 0, width, covered(height)),
+                    _ => (0, 0, covered(width), height),
+                };
+                let uncovered = incoming_frame.crop(x, y, w, h);
+                self.frame_buffer.blit(&uncovered, x as i32, y as i32);
+            }
+            Some(Transition::Cut) | None => {
+                let layers = self.script.scenes[context.scene_index].layers.clone();
+                self.draw_layers(&layers, frame_number, context, asset_loader)?;
             }
         }
+        Ok(())
+    }
+
+    /// Upload `img` as the texture for `source`, first evicting textures the
+    /// current and next scene don't use if the budget would be exceeded
+    fn upload_texture(&mut self, source: PathBuf, img: &image::RgbaImage, frame_number: u32) {
+        let Some(gpu) = &self.gpu_renderer else {
+            return;
+        };
+        let (width, height) = img.dimensions();
+        let bytes = texture_bytes(width, height);
 
+        if self.texture_bytes + bytes > self.texture_budget {
+            let upcoming = [
+                self.timeline.get_scene_at_frame(frame_number),
+                self.timeline.next_scene_after(frame_number),
+            ];
+            let needed: Vec<&PathBuf> = self
+                .script
+                .scenes
+                .iter()
+                .filter(|scene| upcoming.contains(&Some(scene.id.as_str())))
+                .flat_map(|scene| &scene.layers)
+                .filter_map(|layer| match layer {
+                    Layer::Image { source, .. } => Some(source),
+                    _ => None,
+                })
+                // The watermark is on every frame
+                .chain(self.script.metadata.watermark.as_ref().map(|w| &w.source))
+                .collect();
+            let evictable: Vec<PathBuf> = self
+                .texture_cache
+                .iter()
+                .map(|entry| entry.key().clone())
+                .filter(|cached| !needed.contains(&cached))
+                .collect();
+
+            for cached in evictable {
+                if self.texture_bytes + bytes <= self.texture_budget {
+                    break;
+                }
+                if let Some((_, (_, w, h))) = self.texture_cache.remove(&cached) {
+                    self.texture_bytes -= texture_bytes(w, h);
+                    self.texture_stats.evictions += 1;
+                }
+            }
+            // Textures the coming scenes need are uploaded even over budget
+        }
+
+        let bind_group = gpu.create_rgba_texture(img);
+        let source_name = source.display().to_string();
+        self.texture_cache
+            .insert(source, (bind_group, width, height));
+        self.texture_bytes += bytes;
+        self.texture_stats.misses += 1;
+        debug!(texture = %source_name, bytes, "texture uploaded");
+    }
+
+    /// Upload the textures of `scene_id`'s image layers that aren't cached yet,
+    /// using the images the loader already decoded. Returns how many were uploaded.
+    pub fn prepare_scene(&mut self, scene_id: &str, asset_loader: &mut AssetLoader) -> usize {
+        let (Some(start), Some(scene)) = (
+            self.timeline.scene_start(scene_id),
+            self.script.scenes.iter().find(|scene| scene.id == scene_id),
+        ) else {
+            return 0;
+        };
+        if self.gpu_renderer.is_none() {
+            return 0;
+        }
+
+        let sources: Vec<PathBuf> = scene
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Image { source, .. } => Some(source.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut uploaded = 0;
+        for source in sources {
+            if self.texture_cache.contains_key(&source) {
+                continue;
+            }
+            // Unreadable images were already reported while preloading assets
+            let pixels = asset_loader
+                .load_image(&source)
+                .ok()
+                .and_then(|asset| asset.pixels.clone());
+            if let Some(pixels) = pixels {
+                self.upload_texture(source, &pixels, start);
+                uploaded += 1;
+            }
+        }
+        uploaded
+    }
+
+    /// The script's watermark as an image layer at its anchored position, drawn
+    /// last so it's above everything. A watermark that can't be loaded is
+    /// drawn as a missing image would be, at the placeholder's size.
+    fn watermark_layer(&self, asset_loader: &mut AssetLoader) -> Option<Layer> {
+        let watermark = self.script.metadata.watermark.as_ref()?;
+        let (width, height) = asset_loader
+            .load_image(&watermark.source)
+            .map(|image| (image.width, image.height))
+            .unwrap_or((100, 100));
+        let scaled = |size: u32| (size as f32 * watermark.scale).round() as u32;
+        let (x, y) = watermark.top_left(
+            self.frame_buffer.dimensions(),
+            (scaled(width), scaled(height)),
+        );
+        Some(Layer::Image {
+            source: watermark.source.clone(),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x, y },
+                scale: watermark.scale,
+                rotation: 0.0,
+                opacity: watermark.opacity.clamp(0.0, 1.0),
+            },
+            crop: None,
+        })
+    }
+
+    /// Draw `overlay` over the frame on the CPU, for the scene `scene_id`
+    /// with `layers`
+    fn draw_debug_overlay(
+        &mut self,
+        overlay: &DebugOverlaySettings,
+        scene_id: &str,
+        layers: &[Layer],
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
+        self.flush_gpu()?;
+
+        let text: Vec<TextBounds> = layers
+            .iter()
+            .enumerate()
+            .filter(|_| overlay.text_bounds)
+            .filter_map(|(index, layer)| match layer {
+                Layer::Text {
+                    content,
+                    font,
+                    font_size,
+                    position,
+                    ..
+                } => {
+                    let font = asset_loader
+                        .load_font(font)
+                        .ok()
+                        .and_then(|asset| FontRef::try_from_slice(&asset.data).ok());
+                    let label = format!("{}#{}", scene_id, index);
+                    Some(TextBounds::measure(
+                        label,
+                        content,
+                        font.as_ref(),
+                        *font_size,
+                        position,
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let timecode =
+            debug_overlay::timecode(frame_number, self.timeline.frame_to_time(frame_number));
+        let label_font = overlay
+            .label_font
+            .clone()
+            .or_else(|| title_font(&self.script).map(PathBuf::from));
+        let font = label_font
+            .and_then(|path| asset_loader.load_font(&path).ok())
+            .and_then(|asset| FontRef::try_from_slice(&asset.data).ok());
+        overlay.draw(&mut self.frame_buffer, &text, &timecode, font.as_ref());
         Ok(())
     }
 
@@ -127,146 +627,566 @@ impl RenderEngine {
     }
 
     /// Render a single layer
-    fn render_layer(&mut self, layer: &Layer, asset_loader: &AssetLoader) -> Result<()> {
+    fn render_layer(
+        &mut self,
+        layer: &Layer,
+        frame_number: u32,
+        context: &FrameContext,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
         match layer {
             Layer::Image {
-                source, transform, ..
+                source,
+                transform,
+                effects,
+                crop,
             } => {
-                let (x, y) = Compositor::apply_transform(0, 0, transform);
-                let color = [255, 255, 255, 255];
+                let transform = &animate(
+                    transform,
+                    effects,
+                    context.scene_progress,
+                    self.frame_buffer.dimensions(),
+                );
+                let affine = Compositor::apply_transform(transform);
+                let (x, y) = (transform.position.x, transform.position.y);
+                let color = Compositor::apply_opacity([255, 255, 255, 255], transform.opacity);
+                let placeholder =
+                    Compositor::apply_opacity([100, 100, 200, 255], transform.opacity);
+                // The drawn part of a `width`x`height` image
+                let region = |width: u32, height: u32| match crop {
+                    Some(crop) => crop.clamp_to(width, height),
+                    None => Some(Crop {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    }),
+                };
 
-                if let Some(gpu) = &mut self.gpu_renderer {
-                    // Load texture if not in cache
-                    if !self.texture_cache.contains_key(source) {
-                        // Resolve path using AssetLoader (hacky access to private method or just join)
-                        // AssetLoader::resolve_path is private. But we can use base_path.
-                        let full_path = if source.is_absolute() {
-                            source.clone()
+                // Load texture if not in cache
+                if self.gpu_renderer.is_some() && !self.texture_cache.contains_key(source) {
+                    // Resolve path using AssetLoader (hacky access to private method or just join)
+                    // AssetLoader::resolve_path is private. But we can use base_path.
+                    let full_path = if source.is_absolute() {
+                        source.clone()
+                    } else {
+                        asset_loader.base_path().join(source)
+                    };
+
+                    if full_path.exists() {
+                        if let Ok(img) = image::open(&full_path) {
+                            self.upload_texture(source.clone(), &img.to_rgba8(), frame_number);
                         } else {
-                            asset_loader.base_path().join(source)
-                        };
-
-                        if full_path.exists() {
-                            if let Ok(img) = image::open(&full_path) {
-                                let dims = img.dimensions();
-                                let bind_group = gpu.create_texture(&img);
-                                self.texture_cache
-                                    .insert(source.clone(), (bind_group, dims.0, dims.1));
-                            } else {
-                                println!(
-                                    "Failed to load image for texture: {}",
-                                    full_path.display()
-                                );
-                            }
+                            warn!("Failed to load image for texture: {}", full_path.display());
                         }
                     }
+                }
 
+                if let Some(gpu) = &mut self.gpu_renderer {
                     if let Some(entry) = self.texture_cache.get(source) {
                         let (bind_group, w, h) = entry.value();
-                        // Apply scale from transform
-                        let scale = transform.scale;
-                        let draw_w = (*w as f32 * scale) as u32;
-                        let draw_h = (*h as f32 * scale) as u32;
-
-                        gpu.draw_texture(bind_group.clone(), x, y, draw_w, draw_h, color)?;
+                        if let Some(region) = region(*w, *h) {
+                            // Inset crops by half a texel so linear filtering never
+                            // blends in pixels from outside the region
+                            let inset = if crop.is_some() { 0.5 } else { 0.0 };
+                            let src_rect = [
+                                (region.x as f32 + inset) / *w as f32,
+                                (region.y as f32 + inset) / *h as f32,
+                                (region.width as f32 - 2.0 * inset) / *w as f32,
+                                (region.height as f32 - 2.0 * inset) / *h as f32,
+                            ];
+                            // Same corner math as the CPU path so both backends agree
+                            gpu.draw_texture_region(
+                                bind_group.clone(),
+                                src_rect,
+                                affine.corners(region.width, region.height),
+                                color,
+                            )?;
+                        }
                     } else {
                         // Fallback to colored rect if texture failed
-                        gpu.fill_rect(
+                        gpu.fill_rect(x, y, 100, 100, placeholder)?;
+                    }
+                } else {
+                    let pixels = asset_loader
+                        .load_image(source)
+                        .ok()
+                        .and_then(|asset| asset.pixels.clone());
+
+                    match pixels {
+                        Some(pixels) => {
+                            if let Some(region) = region(pixels.width(), pixels.height()) {
+                                let view =
+                                    pixels.view(region.x, region.y, region.width, region.height);
+                                Compositor::draw_image(
+                                    &mut self.frame_buffer,
+                                    &*view,
+                                    &affine,
+                                    transform.opacity,
+                                );
+                            }
+                        }
+                        None => Compositor::blend_rect(
                             &mut self.frame_buffer,
                             x,
                             y,
                             100,
                             100,
-                            [100, 100, 200, 255],
-                        )?;
+                            placeholder,
+                        ),
                     }
-                } else {
-                    Compositor::fill_rect(
-                        &mut self.frame_buffer,
-                        x,
-                        y,
-                        100,
-                        100,
-                        [100, 100, 200, 255],
-                    );
                 }
             }
-            Layer::Video { transform, .. } => {
-                // Placeholder: draw colored rectangle for video
-                let (x, y) = Compositor::apply_transform(0, 0, transform);
-                let color = [200, 100, 100, 255];
+            Layer::Video {
+                source,
+                transform,
+                effects,
+            } => {
+                let transform = &animate(
+                    transform,
+                    effects,
+                    context.scene_progress,
+                    self.frame_buffer.dimensions(),
+                );
+                let affine = Compositor::apply_transform(transform);
+                let (x, y) = (transform.position.x, transform.position.y);
+                let color = Compositor::apply_opacity([255, 255, 255, 255], transform.opacity);
+                let placeholder =
+                    Compositor::apply_opacity([200, 100, 100, 255], transform.opacity);
 
-                if let Some(gpu) = &self.gpu_renderer {
-                    gpu.fill_rect(&mut self.frame_buffer, x, y, 100, 100, color)?;
-                } else {
-                    Compositor::fill_rect(&mut self.frame_buffer, x, y, 100, 100, color);
+                // Clips start with their scene
+                let video_frame = Self::video_frame(
+                    &mut self.video_sources,
+                    source,
+                    context.scene_local_time,
+                    asset_loader,
+                );
+
+                match (video_frame, &mut self.gpu_renderer) {
+                    (Some(pixels), Some(gpu)) => {
+                        let texture =
+                            self.video_textures
+                                .entry(source.clone())
+                                .or_insert_with(|| {
+                                    gpu.create_video_texture(pixels.width(), pixels.height())
+                                });
+                        gpu.update_texture(texture, pixels);
+                        gpu.draw_texture_quad(
+                            texture.bind_group.clone(),
+                            affine.corners(texture.width, texture.height),
+                            color,
+                        )?;
+                    }
+                    (Some(pixels), None) => Compositor::draw_image(
+                        &mut self.frame_buffer,
+                        pixels,
+                        &affine,
+                        transform.opacity,
+                    ),
+                    (None, Some(gpu)) => gpu.fill_rect(x, y, 100, 100, placeholder)?,
+                    (None, None) => {
+                        Compositor::blend_rect(&mut self.frame_buffer, x, y, 100, 100, placeholder)
+                    }
                 }
             }
             Layer::Text {
                 content,
+                font,
+                font_size,
                 position,
                 color,
                 ..
             } => {
+                // Text is drawn on the CPU: composite pending GPU layers first so
+                // the text lands on top of them
+                self.flush_gpu()?;
+
                 let rgba = [color.r, color.g, color.b, color.a];
-                Compositor::draw_text_placeholder(
-                    &mut self.frame_buffer,
-                    content,
-                    position.x,
-                    position.y,
-                    rgba,
+                // Unreadable fonts were already reported while preloading assets
+                let parsed = asset_loader
+                    .load_font(font)
+                    .ok()
+                    .and_then(|asset| FontRef::try_from_slice(&asset.data).ok());
+
+                match parsed {
+                    Some(font) => Compositor::draw_text(
+                        &mut self.frame_buffer,
+                        &font,
+                        content,
+                        *font_size,
+                        position.x,
+                        position.y,
+                        rgba,
+                    ),
+                    None => Compositor::draw_text_placeholder(
+                        &mut self.frame_buffer,
+                        content,
+                        position.x,
+                        position.y,
+                        rgba,
+                    ),
+                }
+            }
+            Layer::Waveform {
+                audio_source,
+                style,
+                color,
+                bar_count,
+                width,
+                height,
+                position,
+            } => {
+                let levels = self
+                    .waveform_analysis(audio_source, asset_loader)
+                    .window(frame_number, *bar_count);
+                let rgba = [color.r, color.g, color.b, color.a];
+                let rects = Compositor::waveform_rects(
+                    &levels, position.x, position.y, *width, *height, *style,
                 );
+
+                if let Some(gpu) = &mut self.gpu_renderer {
+                    for (x, y, w, h) in rects {
+                        gpu.fill_rect(x, y, w, h, rgba)?;
+                    }
+                } else {
+                    for (x, y, w, h) in rects {
+                        Compositor::fill_rect(&mut self.frame_buffer, x, y, w, h, rgba);
+                    }
+                }
+            }
+            Layer::Custom {
+                kind,
+                params,
+                transform,
+            } => {
+                let Some(renderer) = self.layer_renderers.get(kind).cloned() else {
+                    return Err(VideoEngineError::Validation(vec![
+                        self.missing_renderer(kind, &context.scene_id)
+                    ]));
+                };
+                // Plugins draw on the CPU, over the GPU layers below them
+                self.flush_gpu()?;
+                renderer.render_cpu(&mut LayerRenderContext {
+                    frame: &mut self.frame_buffer,
+                    params,
+                    transform,
+                    time: context.scene_local_time,
+                    progress: context.scene_progress,
+                    assets: asset_loader,
+                })?;
             }
         }
 
         Ok(())
     }
 
+    /// Audio levels for a waveform layer, decoded on first use
+    fn waveform_analysis(
+        &mut self,
+        source: &std::path::Path,
+        asset_loader: &AssetLoader,
+    ) -> &WaveformAnalysis {
+        let fps = self.timeline.fps();
+        self.waveform_cache
+            .entry(source.to_path_buf())
+            .or_insert_with(|| {
+                let full_path = if source.is_absolute() {
+                    source.to_path_buf()
+                } else {
+                    asset_loader.base_path().join(source)
+                };
+
+                WaveformAnalysis::from_file(&full_path, fps).unwrap_or_else(|e| {
+                    warn!(
+                        "Failed to analyze waveform audio {}: {}",
+                        full_path.display(),
+                        e
+                    );
+                    WaveformAnalysis::default()
+                })
+            })
+    }
+
+    /// Frame of a video layer's clip at `time` seconds, opening its decoder on
+    /// first use. Failures are reported once and leave the placeholder in place.
+    fn video_frame<'a>(
+        video_sources: &'a mut HashMap<PathBuf, Option<VideoFrameSource>>,
+        source: &std::path::Path,
+        time: f32,
+        asset_loader: &AssetLoader,
+    ) -> Option<&'a image::RgbaImage> {
+        let full_path = if source.is_absolute() {
+            source.to_path_buf()
+        } else {
+            asset_loader.base_path().join(source)
+        };
+
+        let entry = video_sources
+            .entry(source.to_path_buf())
+            .or_insert_with(|| {
+                VideoFrameSource::open(&full_path)
+                    .map_err(|e| warn!("Failed to open video {}: {}", full_path.display(), e))
+                    .ok()
+            });
+
+        if let Err(e) = entry.as_mut()?.frame_at(time) {
+            warn!("Failed to decode video {}: {}", full_path.display(), e);
+            *entry = None;
+            return None;
+        }
+        // Served from the decoder's cache this time
+        entry.as_mut()?.frame_at(time).ok()
+    }
+
     /// Save current frame as PPM
     pub fn save_frame(&self, path: &str) -> Result<()> {
         self.frame_buffer.save_ppm(path)
     }
 
-    /// Render all frames to the output directory
+    /// Render all frames in order into `sink` (image files or a streaming encoder)
     pub fn render(
         &mut self,
-        output_dir: &std::path::Path,
+        sink: &mut dyn FrameSink,
+        asset_loader: &mut AssetLoader,
+        progress: &mut dyn RenderProgress,
+    ) -> Result<()> {
+        let total_frames = self.timeline.total_frames();
+        self.render_range(0..total_frames, sink, asset_loader, progress)
+    }
+
+    /// Render only `frames` (end exclusive) into `sink`, leaving out those the
+    /// sink already has
+    pub fn render_range(
+        &mut self,
+        frames: Range<u32>,
+        sink: &mut dyn FrameSink,
         asset_loader: &mut AssetLoader,
+        progress: &mut dyn RenderProgress,
     ) -> Result<()> {
         let total_frames = self.timeline.total_frames();
+        if frames.is_empty() || frames.end > total_frames {
+            return Err(VideoEngineError::invalid(format!(
+                "Frame range {}..{} is outside the timeline (0..{})",
+                frames.start, frames.end, total_frames
+            )));
+        }
+
+        self.validate_layers()?;
 
-        for frame in 0..total_frames {
-            if frame % 30 == 0 {
-                println!("  Rendering frame {}/{}", frame, total_frames);
+        let _span = tracing::info_span!("render", start = frames.start, end = frames.end).entered();
+        // Frames the sink kept from an earlier render aren't drawn again
+        let frames: Vec<u32> = frames.filter(|&frame| !sink.has_frame(frame)).collect();
+        let total = frames.len() as u32;
+        let mut current_scene: Option<String> = None;
+        let mut in_flight: Option<(u32, u32, PendingFrame)> = None;
+        for (completed, frame) in (1..).zip(frames) {
+            if let Some(token) = &self.cancellation {
+                token.check()?;
             }
+            let scene = self.timeline.get_scene_at_frame(frame);
+            if let Some(scene_id) = scene.filter(|id| Some(*id) != current_scene.as_deref()) {
+                let scene_id = scene_id.to_string();
+                progress.emit(ProgressKind::SceneStarted {
+                    scene_id: scene_id.clone(),
+                    frame,
+                });
 
-            self.render_frame(frame, asset_loader)?;
+                // Upload the scene's textures now rather than stalling its first frame
+                if self.gpu_renderer.is_some() {
+                    let started = Instant::now();
+                    let textures = self.prepare_scene(&scene_id, asset_loader);
+                    progress.emit(ProgressKind::ScenePrepared {
+                        scene_id: scene_id.clone(),
+                        textures,
+                        seconds: started.elapsed().as_secs_f64(),
+                    });
+                }
+                current_scene = Some(scene_id);
+            }
 
-            let filename = format!("frame_{}.ppm", frame);
-            let path = output_dir.join(filename);
-            self.save_frame(path.to_str().unwrap())?;
+            // The previous frame reads back while this one is drawn
+            let started = Instant::now();
+            let pending = self.draw_frame(frame, asset_loader)?;
+            debug!(
+                frame,
+                "frame drawn in {:.3}ms",
+                started.elapsed().as_secs_f64() * 1000.0
+            );
+            if let Some((frame, completed, previous)) = in_flight.take() {
+                self.finish_pending(previous, sink)?;
+                progress.emit(ProgressKind::FrameCompleted {
+                    frame,
+                    completed,
+                    total,
+                });
+            }
+            match pending {
+                Some(pending) => in_flight = Some((frame, completed, pending)),
+                None => {
+                    sink.write_frame(&self.frame_buffer)?;
+                    progress.emit(ProgressKind::FrameCompleted {
+                        frame,
+                        completed,
+                        total,
+                    });
+                }
+            }
+        }
+        if let Some((frame, completed, last)) = in_flight {
+            // The last frame lands in the frame buffer, as with `render_frame`
+            if let Some(gpu) = &mut self.gpu_renderer {
+                gpu.wait(last, &mut self.frame_buffer)?;
+            }
+            sink.write_frame(&self.frame_buffer)?;
+            progress.emit(ProgressKind::FrameCompleted {
+                frame,
+                completed,
+                total,
+            });
         }
 
+        progress.emit(ProgressKind::RenderFinished { frames: total });
         Ok(())
     }
 
+    /// Read back a frame submitted earlier and write it to `sink`
+    fn finish_pending(&mut self, pending: PendingFrame, sink: &mut dyn FrameSink) -> Result<()> {
+        let Some(gpu) = &mut self.gpu_renderer else {
+            return Err(VideoEngineError::invalid(
+                "Pending GPU frame without a GPU renderer",
+            ));
+        };
+        gpu.wait(pending, &mut self.readback_buffer)?;
+        sink.write_frame(&self.readback_buffer)
+    }
+
+    /// Render the frame shown at `seconds` into the frame buffer and return its number
+    pub fn render_at_time(&mut self, seconds: f32, asset_loader: &mut AssetLoader) -> Result<u32> {
+        let frame = self.timeline.time_to_frame(seconds);
+        if !seconds.is_finite() || seconds < 0.0 || frame >= self.timeline.total_frames() {
+            return Err(VideoEngineError::invalid(format!(
+                "Time {:.3}s is outside the video (0-{:.3}s)",
+                seconds,
+                self.timeline.frame_to_time(self.timeline.total_frames())
+            )));
+        }
+        self.render_frame(frame, asset_loader)?;
+        Ok(frame)
+    }
+
+    /// Render frame `frame_number` and return it as an image, without touching
+    /// the file system. GPU frames are read back before this returns.
+    pub fn render_frame_image(
+        &mut self,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<image::RgbaImage> {
+        self.render_frame(frame_number, asset_loader)?;
+        Ok(self.frame_buffer.to_image())
+    }
+
+    /// Render the frame shown at `seconds` and return it as an image, like
+    /// `render_frame_image`
+    pub fn render_image_at_time(
+        &mut self,
+        seconds: f32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<image::RgbaImage> {
+        self.render_at_time(seconds, asset_loader)?;
+        Ok(self.frame_buffer.to_image())
+    }
+
+    /// Render subsequent frames at a different output size
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let depth = self.frame_buffer.bit_depth();
+        let seed = self.frame_buffer.dither_seed();
+        self.frame_buffer =
+            FrameBuffer::with_bit_depth(width, height, depth).with_dither_seed(seed);
+        self.readback_buffer =
+            FrameBuffer::with_bit_depth(width, height, depth).with_dither_seed(seed);
+        if let Some(gpu) = &mut self.gpu_renderer {
+            gpu.resize(width, height);
+        }
+    }
+
+    /// Most recently rendered frame
+    pub fn frame_buffer(&self) -> &FrameBuffer {
+        &self.frame_buffer
+    }
+
+    /// SHA-256 of the most recently rendered frame, as the 8-bit bytes written
+    /// to files and the encoder, for checking that two renders match without
+    /// keeping their frames. Frames drawn on the CPU hash the same on every
+    /// platform; GPU-drawn ones can differ between devices and drivers.
+    pub fn content_hash(&self) -> String {
+        let (width, height) = self.frame_buffer.dimensions();
+        let mut hasher = Sha256::new();
+        hasher.update(width.to_le_bytes());
+        hasher.update(height.to_le_bytes());
+        hasher.update(self.frame_buffer.to_rgba8_dithered());
+        format!("{:x}", hasher.finalize())
+    }
+
     /// Get timeline
     pub fn timeline(&self) -> &Timeline {
         &self.timeline
     }
+
+    /// The script being rendered
+    pub fn script(&self) -> &VideoScript {
+        &self.script
+    }
+
+    /// Whether frames are drawn on the GPU
+    pub fn uses_gpu(&self) -> bool {
+        self.gpu_renderer.is_some()
+    }
+
+    /// The GPU adapter frames are drawn on, if any
+    pub fn gpu_adapter_info(&self) -> Option<&wgpu::AdapterInfo> {
+        self.gpu_renderer.as_ref().map(GpuRenderer::adapter_info)
+    }
+
+    /// Image texture cache hits, misses and evictions so far
+    pub fn texture_stats(&self) -> TextureCacheStats {
+        self.texture_stats
+    }
+}
+
+/// `transform` as moved by `effects` at `progress` (0.0-1.0) through the scene,
+/// in a `width`x`height` frame
+fn animate(
+    transform: &Transform,
+    effects: &[Effect],
+    progress: f32,
+    (width, height): (u32, u32),
+) -> Transform {
+    let mut animated = transform.clone();
+    for effect in effects {
+        if let Effect::KenBurns { zoom } = effect {
+            let factor = 1.0 + (zoom - 1.0) * progress.clamp(0.0, 1.0);
+            let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+            animated.position.x = (cx + (animated.position.x as f32 - cx) * factor).round() as i32;
+            animated.position.y = (cy + (animated.position.y as f32 - cy) * factor).round() as i32;
+            animated.scale *= factor;
+        }
+    }
+    animated
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::renderer::{FrameFileSink, FrameFormat, ProgressEvent};
     use crate::script::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_render_engine_creation() {
         let script = create_test_script();
         let engine = RenderEngine::new(script, false); // Default to CPU for basic test
-        assert_eq!(engine.timeline().total_frames(), 600);
+                                                       // The video ends with its only (5 second) scene
+        assert_eq!(engine.timeline().total_frames(), 300);
     }
 
     fn create_test_script() -> VideoScript {
@@ -278,6 +1198,7 @@ mod tests {
                 duration: 10.0,
                 description: None,
                 citations: vec![],
+                watermark: None,
             },
             scenes: vec![Scene {
                 id: "test".into(),
@@ -287,8 +1208,11 @@ mod tests {
                     source: PathBuf::from("test.png"),
                     effects: vec![],
                     transform: Default::default(),
+                    crop: None,
                 }],
                 transition: None,
+                narration: None,
+                title: None,
             }],
             audio: None,
         }
@@ -300,12 +1224,104 @@ mod tests {
         let engine = RenderEngine::new(script, true); // Try GPU
 
         // Engine should be created successfully regardless of GPU availability
-        assert_eq!(engine.timeline().total_frames(), 600);
+        assert_eq!(engine.timeline().total_frames(), 300);
 
         // GPU renderer field exists (even if None)
         // This test verifies the integration compiles and runs
     }
 
+    /// A 480x270, 30fps script whose one 5 second scene draws `layers`
+    fn image_script(layers: Vec<Layer>) -> VideoScript {
+        let mut script = create_test_script();
+        script.metadata.resolution = Resolution::Named("480x270".into());
+        script.metadata.fps = 30;
+        script.scenes[0].layers = layers;
+        script
+    }
+
+    /// `source` drawn untransformed in the top left corner
+    fn image_layer(source: &str) -> Layer {
+        Layer::Image {
+            source: PathBuf::from(source),
+            effects: vec![],
+            transform: Transform::default(),
+            crop: None,
+        }
+    }
+
+    /// Save a `width`x`height` PNG of a single color as `dir/name`
+    fn solid_png(dir: &Path, name: &str, width: u32, height: u32, rgba: [u8; 4]) {
+        image::RgbaImage::from_pixel(width, height, image::Rgba(rgba))
+            .save(dir.join(name))
+            .unwrap();
+    }
+
+    fn waveform_script(audio_source: &str) -> VideoScript {
+        image_script(vec![Layer::Waveform {
+            audio_source: PathBuf::from(audio_source),
+            style: WaveformStyle::Bars,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            bar_count: 16,
+            width: 320,
+            height: 200,
+            position: Position { x: 0, y: 0 },
+        }])
+    }
+
+    fn write_wav(path: &std::path::Path, samples: &[f32]) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn lit_pixels(engine: &RenderEngine) -> usize {
+        let (width, height) = engine.frame_buffer.dimensions();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| engine.frame_buffer.get_pixel(x, y) != Some([0, 0, 0, 255]))
+            .count()
+    }
+
+    #[test]
+    fn test_waveform_sine_taller_than_silence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let sine: Vec<f32> = (0..8000 * 5)
+            .map(|i| 0.8 * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / 8000.0).sin())
+            .collect();
+        write_wav(&dir.path().join("sine.wav"), &sine);
+        write_wav(&dir.path().join("silence.wav"), &vec![0.0; 8000 * 5]);
+
+        let mut loader = AssetLoader::new(dir.path());
+        let mut render = |source: &str| {
+            let mut engine = RenderEngine::new(waveform_script(source), false);
+            engine.render_frame(45, &mut loader).unwrap();
+            assert_eq!(engine.waveform_cache.len(), 1);
+            lit_pixels(&engine)
+        };
+
+        let sine_pixels = render("sine.wav");
+        let silence_pixels = render("silence.wav");
+        assert!(
+            sine_pixels > silence_pixels * 10,
+            "sine {} vs silence {}",
+            sine_pixels,
+            silence_pixels
+        );
+    }
+
     #[test]
     fn test_render_frame_with_gpu() {
         let script = create_test_script();
@@ -316,4 +1332,795 @@ mod tests {
         // If GPU is available, it exercises the flush() logic
         engine.render_frame(0, &mut asset_loader).unwrap();
     }
+
+    #[test]
+    fn test_text_layer_uses_font_from_loader() {
+        let font = std::path::Path::new(crate::renderer::TEST_FONT_PATH);
+        let text_script = |font: &std::path::Path| {
+            image_script(vec![Layer::Text {
+                content: "Hi".into(),
+                font: font.to_path_buf(),
+                font_size: 40.0,
+                color: Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                },
+                position: Position { x: 20, y: 20 },
+                effects: vec![],
+            }])
+        };
+
+        let mut loader = AssetLoader::new(".");
+        let mut engine = RenderEngine::new(text_script(font), false);
+        engine.render_frame(0, &mut loader).unwrap();
+        let glyph_pixels = lit_pixels(&engine);
+
+        // A missing font falls back to the placeholder block (16x16 for two characters)
+        let mut engine = RenderEngine::new(text_script(std::path::Path::new("missing.ttf")), false);
+        engine.render_frame(0, &mut loader).unwrap();
+        assert_eq!(lit_pixels(&engine), 256);
+
+        assert!(
+            glyph_pixels > 100 && glyph_pixels != 256,
+            "{}",
+            glyph_pixels
+        );
+    }
+
+    #[test]
+    fn test_gpu_layers_keep_cpu_text_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "blue.png", 48, 48, [0, 0, 255, 255]);
+        let image = image_layer("blue.png");
+        // A missing font draws a 16x16 placeholder block on the CPU
+        let text = Layer::Text {
+            content: "Hi".into(),
+            font: PathBuf::from("missing.ttf"),
+            font_size: 40.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x: 8, y: 8 },
+            effects: vec![],
+        };
+
+        let render = |layers: Vec<Layer>| {
+            // Falls back to the CPU without a GPU adapter, where order holds trivially
+            let mut engine = RenderEngine::new(image_script(layers), true);
+            engine
+                .render_frame(0, &mut AssetLoader::new(dir.path()))
+                .unwrap();
+            engine.frame_buffer().get_pixel(12, 12)
+        };
+
+        // Text above a GPU image survives the flush
+        assert_eq!(
+            render(vec![image.clone(), text.clone()]),
+            Some([255, 255, 255, 255])
+        );
+        // An image above the text still covers it
+        assert_eq!(render(vec![text, image]), Some([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_render_frame_image() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "red.png", 480, 270, [200, 40, 10, 255]);
+        let script = image_script(vec![image_layer("red.png")]);
+
+        // On the GPU when there is one, read back before returning
+        let mut engine = RenderEngine::new(script, true);
+        let mut loader = AssetLoader::new(dir.path());
+        let image = engine.render_frame_image(0, &mut loader).unwrap();
+        assert_eq!(image.dimensions(), (480, 270));
+        assert_eq!(image.get_pixel(240, 135).0, [200, 40, 10, 255]);
+
+        let image = engine.render_image_at_time(0.5, &mut loader).unwrap();
+        assert_eq!(image.get_pixel(10, 260).0, [200, 40, 10, 255]);
+        assert!(engine.render_image_at_time(-1.0, &mut loader).is_err());
+    }
+
+    #[test]
+    fn test_image_layer_composited_on_cpu() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "blue.png", 10, 10, [0, 0, 255, 255]);
+
+        let script = image_script(vec![Layer::Image {
+            source: PathBuf::from("blue.png"),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x: 50, y: 40 },
+                scale: 2.0,
+                ..Default::default()
+            },
+            crop: None,
+        }]);
+
+        let mut loader = AssetLoader::new(dir.path());
+        let mut engine = RenderEngine::new(script, false);
+        engine.render_frame(0, &mut loader).unwrap();
+
+        assert_eq!(lit_pixels(&engine), 400);
+        assert_eq!(
+            engine.frame_buffer.get_pixel(50, 40),
+            Some([0, 0, 255, 255])
+        );
+        assert_eq!(
+            engine.frame_buffer.get_pixel(69, 59),
+            Some([0, 0, 255, 255])
+        );
+        assert_eq!(engine.frame_buffer.get_pixel(70, 60), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_ken_burns_zooms_about_frame_center() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "blue.png", 10, 10, [0, 0, 255, 255]);
+
+        // Centered in the 480x270 frame
+        let script = image_script(vec![Layer::Image {
+            source: PathBuf::from("blue.png"),
+            effects: vec![Effect::KenBurns { zoom: 2.0 }],
+            transform: Transform {
+                position: Position { x: 235, y: 130 },
+                ..Default::default()
+            },
+            crop: None,
+        }]);
+
+        let mut loader = AssetLoader::new(dir.path());
+        let mut engine = RenderEngine::new(script, false);
+        engine.render_frame(0, &mut loader).unwrap();
+        assert_eq!(lit_pixels(&engine), 100);
+
+        let halfway = engine.timeline().total_frames() / 2;
+        engine.render_frame(halfway, &mut loader).unwrap();
+        assert_eq!(lit_pixels(&engine), 225);
+        assert_eq!(
+            engine.frame_buffer.get_pixel(233, 128),
+            Some([0, 0, 255, 255])
+        );
+        assert_eq!(
+            engine.frame_buffer.get_pixel(247, 142),
+            Some([0, 0, 255, 255])
+        );
+    }
+
+    #[test]
+    fn test_cropped_image_draws_only_the_region() {
+        // The GPU path falls back to the CPU when no adapter is available
+        for use_gpu in [false, true] {
+            render_cropped_image(use_gpu);
+        }
+    }
+
+    fn render_cropped_image(use_gpu: bool) {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Left half red, right half blue
+        image::RgbaImage::from_fn(20, 10, |x, _| {
+            if x < 10 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([0, 0, 255, 255])
+            }
+        })
+        .save(dir.path().join("halves.png"))
+        .unwrap();
+
+        let script = image_script(vec![Layer::Image {
+            source: PathBuf::from("halves.png"),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x: 30, y: 30 },
+                scale: 2.0,
+                ..Default::default()
+            },
+            crop: Some(Crop {
+                x: 10,
+                y: 0,
+                width: 10,
+                height: 10,
+            }),
+        }]);
+
+        let mut loader = AssetLoader::new(dir.path());
+        let mut engine = RenderEngine::new(script, use_gpu);
+        engine.render_frame(0, &mut loader).unwrap();
+
+        // The 10x10 crop drawn at 2x covers 20x20 pixels, all blue
+        assert_eq!(lit_pixels(&engine), 400);
+        for y in 30..50 {
+            for x in 30..50 {
+                assert_eq!(
+                    engine.frame_buffer.get_pixel(x, y),
+                    Some([0, 0, 255, 255]),
+                    "({}, {}) with use_gpu={}",
+                    x,
+                    y,
+                    use_gpu
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_texture_budget_evicts_finished_scenes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let colors = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 0, 255],
+        ];
+        let mut script = image_script(vec![]);
+        let template = script.scenes[0].clone();
+        script.scenes.clear();
+        for (i, color) in colors.iter().enumerate() {
+            let name = format!("scene{}.png", i);
+            solid_png(dir.path(), &name, 10, 10, *color);
+            let mut scene = template.clone();
+            scene.id = format!("scene{}", i);
+            scene.duration = 0.2;
+            scene.layers = vec![image_layer(&name)];
+            script.scenes.push(scene);
+        }
+        script.metadata.duration = 0.8;
+
+        // Room for a single 10x10 texture
+        let mut engine = RenderEngine::new(script, true).with_texture_budget(400);
+        if !engine.uses_gpu() {
+            println!("No GPU adapter, skipping texture budget test");
+            return;
+        }
+
+        let mut loader = AssetLoader::new(dir.path());
+        for frame in 0..24 {
+            engine.render_frame(frame, &mut loader).unwrap();
+            assert_eq!(
+                engine.frame_buffer.get_pixel(5, 5),
+                Some(colors[frame as usize / 6]),
+                "frame {}",
+                frame
+            );
+        }
+
+        // Each scene's texture is uploaded once and dropped when the next one arrives
+        assert_eq!(
+            engine.texture_stats(),
+            TextureCacheStats {
+                hits: 20,
+                misses: 4,
+                evictions: 3,
+            }
+        );
+        assert_eq!(engine.texture_cache.len(), 1);
+        assert_eq!(engine.texture_bytes, 400);
+    }
+
+    #[test]
+    fn test_prepare_scene_uploads_textures_before_first_frame() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "green.png", 8, 8, [0, 255, 0, 255]);
+
+        let mut script = image_script(vec![]);
+        script.scenes[0].duration = 0.2;
+        let mut second = script.scenes[0].clone();
+        second.id = "second".into();
+        second.layers = vec![image_layer("green.png")];
+        script.scenes.push(second);
+        script.metadata.duration = 0.4;
+
+        let mut engine = RenderEngine::new(script, true);
+        if !engine.uses_gpu() {
+            println!("No GPU adapter, skipping scene preparation test");
+            return;
+        }
+
+        let mut loader = AssetLoader::new(dir.path());
+        assert_eq!(engine.prepare_scene("second", &mut loader), 1);
+        assert!(engine
+            .texture_cache
+            .contains_key(&PathBuf::from("green.png")));
+        // Already cached: nothing left to upload
+        assert_eq!(engine.prepare_scene("second", &mut loader), 0);
+
+        // The scene's first frame draws from the cache
+        engine.render_frame(6, &mut loader).unwrap();
+        assert_eq!(engine.frame_buffer.get_pixel(4, 4), Some([0, 255, 0, 255]));
+        assert_eq!(engine.texture_stats().hits, 1);
+        assert_eq!(engine.texture_stats().misses, 1);
+    }
+
+    #[test]
+    fn test_half_opacity_blends_over_background() {
+        let cpu = render_half_red_over_gray(false);
+        // Half the light of red plus half of gray 128's, in sRGB
+        assert!((202..=206).contains(&cpu[0]), "{:?}", cpu);
+        assert!((90..=94).contains(&cpu[1]), "{:?}", cpu);
+        assert_eq!(cpu[1], cpu[2]);
+
+        // The GPU path falls back to the CPU when no adapter is available
+        let gpu = render_half_red_over_gray(true);
+        for channel in 0..4 {
+            assert!(
+                (cpu[channel] as i32 - gpu[channel] as i32).abs() <= 1,
+                "CPU {:?} vs GPU {:?}",
+                cpu,
+                gpu
+            );
+        }
+    }
+
+    /// Content hash of the first frame of a half-transparent gradient, drawn
+    /// on the CPU with 16-bit channels so the output is dithered
+    fn dithered_gradient_hash(seed: u64) -> String {
+        let dir = tempfile::TempDir::new().unwrap();
+        image::RgbaImage::from_fn(64, 16, |x, _| image::Rgba([x as u8 * 4, 96, 200, 255]))
+            .save(dir.path().join("gradient.png"))
+            .unwrap();
+        let script = image_script(vec![Layer::Image {
+            source: PathBuf::from("gradient.png"),
+            effects: vec![],
+            transform: Transform {
+                opacity: 0.5,
+                rotation: 30.0,
+                ..Default::default()
+            },
+            crop: None,
+        }]);
+
+        let mut engine = RenderEngine::new(script, false)
+            .with_bit_depth(BitDepth::Sixteen)
+            .with_seed(seed);
+        engine
+            .render_frame(0, &mut AssetLoader::new(dir.path()))
+            .unwrap();
+        engine.content_hash()
+    }
+
+    #[test]
+    fn test_cpu_render_is_deterministic() {
+        let hash = dithered_gradient_hash(0);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(dithered_gradient_hash(0), hash);
+    }
+
+    #[test]
+    fn test_seed_changes_the_dither_pattern() {
+        // Seeds 0-3 select four different phases of the pattern
+        let hashes: Vec<String> = (0..4).map(dithered_gradient_hash).collect();
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[..i].contains(hash), "seed {}", i);
+        }
+        assert_eq!(dithered_gradient_hash(1), hashes[1]);
+    }
+
+    /// A red scene, then a blue one, a second each, joined by `transition`
+    fn transition_script(transition: Option<Transition>) -> VideoScript {
+        let mut script = image_script(vec![image_layer("red.png")]);
+        script.scenes[0].duration = 1.0;
+        script.scenes[0].transition = transition;
+        let mut blue = script.scenes[0].clone();
+        blue.id = "blue".into();
+        blue.transition = None;
+        blue.layers = vec![image_layer("blue.png")];
+        script.scenes.push(blue);
+        script.metadata.duration = 2.0;
+        script
+    }
+
+    #[test]
+    fn test_transitions_composite_both_scenes() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const BLUE: [u8; 4] = [0, 0, 255, 255];
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "red.png", 480, 270, RED);
+        solid_png(dir.path(), "blue.png", 480, 270, BLUE);
+
+        // The GPU path falls back to the CPU when no adapter is available
+        for use_gpu in [false, true] {
+            let mut loader = AssetLoader::new(dir.path());
+            let mut frame = |transition: Option<Transition>, frame: u32| {
+                let mut engine = RenderEngine::new(transition_script(transition), use_gpu);
+                engine.render_frame_image(frame, &mut loader).unwrap()
+            };
+            let dissolve = || Some(Transition::Dissolve { duration: 1.0 });
+
+            // A cut switches scenes on the frame the next one starts
+            assert_eq!(frame(None, 29).get_pixel(240, 135).0, RED);
+            assert_eq!(frame(None, 30).get_pixel(240, 135).0, BLUE);
+
+            // The dissolve's 30 frames are centered on that cut: it starts on
+            // the red scene and is halfway through on frame 30
+            assert_eq!(frame(dissolve(), 15).get_pixel(240, 135).0, RED);
+            let [r, g, b, a] = frame(dissolve(), 30).get_pixel(240, 135).0;
+            // Half the light of each, in sRGB
+            assert!(
+                (186..=190).contains(&r) && (186..=190).contains(&b),
+                "{:?}",
+                [r, g, b]
+            );
+            assert_eq!((g, a), (0, 255));
+            assert_eq!(frame(dissolve(), 45).get_pixel(240, 135).0, BLUE);
+
+            // A fade goes through black at the cut
+            let fade = || Some(Transition::Fade { duration: 1.0 });
+            assert_eq!(frame(fade(), 15).get_pixel(240, 135).0, RED);
+            assert_eq!(frame(fade(), 30).get_pixel(240, 135).0, [0, 0, 0, 255]);
+
+            // Halfway through a wipe to the right, the left half is uncovered
+            let wipe = Some(Transition::Wipe {
+                duration: 1.0,
+                direction: "right".into(),
+            });
+            let image = frame(wipe, 30);
+            assert_eq!(image.get_pixel(239, 135).0, BLUE, "use_gpu={}", use_gpu);
+            assert_eq!(image.get_pixel(240, 135).0, RED, "use_gpu={}", use_gpu);
+        }
+    }
+
+    #[test]
+    fn test_watermark_drawn_over_every_layer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "gray.png", 480, 270, [128, 128, 128, 255]);
+        solid_png(dir.path(), "logo.png", 2, 2, [255, 0, 0, 255]);
+        let mut script = image_script(vec![image_layer("gray.png")]);
+        script.metadata.watermark = Some(Watermark {
+            margin_px: 10,
+            ..Watermark::new("logo.png")
+        });
+
+        for use_gpu in [false, true] {
+            let mut engine = RenderEngine::new(script.clone(), use_gpu);
+            let image = engine
+                .render_frame_image(0, &mut AssetLoader::new(dir.path()))
+                .unwrap();
+            // 10 pixels from the right and bottom edges of the 480x270 frame
+            for (x, y) in [(468, 258), (469, 258), (468, 259), (469, 259)] {
+                assert_eq!(image.get_pixel(x, y).0, [255, 0, 0, 255], "({}, {})", x, y);
+            }
+            for (x, y) in [(467, 258), (470, 259), (468, 257), (469, 260)] {
+                assert_eq!(
+                    image.get_pixel(x, y).0,
+                    [128, 128, 128, 255],
+                    "({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_debug_overlay_thirds_land_on_exact_pixels() {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "gray.png", 480, 270, [128, 128, 128, 255]);
+        let script = image_script(vec![image_layer("gray.png")]);
+        let overlay = DebugOverlaySettings {
+            enabled: true,
+            safe_areas: false,
+            text_bounds: false,
+            timecode: false,
+            thirds_color: [0, 255, 0, 255],
+            ..DebugOverlaySettings::default()
+        };
+        const GRAY: [u8; 4] = [128, 128, 128, 255];
+        const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+        for use_gpu in [false, true] {
+            let mut loader = AssetLoader::new(dir.path());
+            // Off unless asked for
+            let mut engine = RenderEngine::new(script.clone(), use_gpu);
+            let image = engine.render_frame_image(0, &mut loader).unwrap();
+            assert_eq!(image.get_pixel(160, 135).0, GRAY);
+
+            let mut engine =
+                RenderEngine::new(script.clone(), use_gpu).with_debug_overlay(overlay.clone());
+            let image = engine.render_frame_image(0, &mut loader).unwrap();
+            // width / 3 and 2 * width / 3 of the 480x270 frame, and the same for the height
+            for (x, y) in [(160, 135), (320, 135), (240, 90), (240, 180), (160, 90)] {
+                assert_eq!(image.get_pixel(x, y).0, GREEN, "({}, {})", x, y);
+            }
+            for (x, y) in [
+                (159, 135),
+                (161, 135),
+                (319, 135),
+                (321, 135),
+                (240, 89),
+                (240, 181),
+            ] {
+                assert_eq!(image.get_pixel(x, y).0, GRAY, "({}, {})", x, y);
+            }
+        }
+    }
+
+    fn render_half_red_over_gray(use_gpu: bool) -> [u8; 4] {
+        let dir = tempfile::TempDir::new().unwrap();
+        solid_png(dir.path(), "gray.png", 4, 4, [128, 128, 128, 255]);
+        solid_png(dir.path(), "red.png", 4, 4, [255, 0, 0, 255]);
+
+        let layer = |source: &str, opacity: f32| Layer::Image {
+            source: PathBuf::from(source),
+            effects: vec![],
+            transform: Transform {
+                opacity,
+                ..Default::default()
+            },
+            crop: None,
+        };
+        let script = image_script(vec![layer("gray.png", 1.0), layer("red.png", 0.5)]);
+
+        let mut loader = AssetLoader::new(dir.path());
+        let mut engine = RenderEngine::new(script, use_gpu);
+        engine.render_frame(0, &mut loader).unwrap();
+        engine.frame_buffer.get_pixel(1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_render_writes_every_frame_to_sink() {
+        struct CountingSink(Vec<(u32, u32)>);
+        impl FrameSink for CountingSink {
+            fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()> {
+                self.0.push(frame.dimensions());
+                Ok(())
+            }
+        }
+
+        let mut script = image_script(vec![]);
+        script.scenes[0].duration = 0.5;
+        script.metadata.duration = 0.5;
+        let mut engine = RenderEngine::new(script, false);
+        let mut sink = CountingSink(Vec::new());
+        engine
+            .render(&mut sink, &mut AssetLoader::new("."), &mut |_| {})
+            .unwrap();
+
+        assert_eq!(sink.0.len(), 15);
+        assert!(sink.0.iter().all(|dims| *dims == (480, 270)));
+    }
+
+    #[test]
+    fn test_cancelled_render_stops_between_frames() {
+        struct CancellingSink(u32, CancellationToken);
+        impl FrameSink for CancellingSink {
+            fn write_frame(&mut self, _frame: &FrameBuffer) -> Result<()> {
+                self.0 += 1;
+                if self.0 == 3 {
+                    self.1.cancel();
+                }
+                Ok(())
+            }
+        }
+
+        let token = CancellationToken::new();
+        let mut engine =
+            RenderEngine::new(image_script(vec![]), false).with_cancellation(token.clone());
+        let mut sink = CancellingSink(0, token);
+        let error = engine
+            .render(&mut sink, &mut AssetLoader::new("."), &mut |_| {})
+            .unwrap_err();
+
+        assert!(matches!(error, VideoEngineError::Cancelled));
+        assert_eq!(sink.0, 3);
+    }
+
+    #[test]
+    fn test_pipelined_gpu_render_matches_frame_by_frame() {
+        struct PixelSink(Vec<[u8; 4]>);
+        impl FrameSink for PixelSink {
+            fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()> {
+                self.0.push(frame.get_pixel(5, 5).unwrap());
+                Ok(())
+            }
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+        let mut script = image_script(vec![]);
+        let template = script.scenes[0].clone();
+        script.scenes.clear();
+        for (i, color) in colors.iter().enumerate() {
+            let name = format!("scene{}.png", i);
+            solid_png(dir.path(), &name, 10, 10, *color);
+            let mut scene = template.clone();
+            scene.id = format!("scene{}", i);
+            scene.duration = 0.2;
+            scene.layers = vec![image_layer(&name)];
+            script.scenes.push(scene);
+        }
+        // CPU text forces a blocking flush in the middle of the second scene's frames
+        script.scenes[1].layers.push(Layer::Text {
+            content: "Hi".into(),
+            font: PathBuf::from("missing.ttf"),
+            font_size: 24.0,
+            position: Position { x: 40, y: 40 },
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            effects: vec![],
+        });
+        script.metadata.duration = 0.6;
+
+        let mut engine = RenderEngine::new(script, true);
+        if !engine.uses_gpu() {
+            println!("No GPU adapter, skipping pipelined render test");
+            return;
+        }
+        let mut loader = AssetLoader::new(dir.path());
+
+        let mut frames = Vec::new();
+        let mut sink = PixelSink(Vec::new());
+        engine
+            .render(&mut sink, &mut loader, &mut |event: ProgressEvent| {
+                if let ProgressKind::FrameCompleted { frame, .. } = event.kind {
+                    frames.push(frame);
+                }
+            })
+            .unwrap();
+
+        assert_eq!(frames, (0..18).collect::<Vec<_>>());
+        let expected: Vec<[u8; 4]> = (0..18).map(|frame| colors[frame / 6]).collect();
+        assert_eq!(sink.0, expected);
+        // The last frame is left in the frame buffer
+        assert_eq!(engine.frame_buffer().get_pixel(5, 5), Some(colors[2]));
+
+        for frame in [0, 7, 17] {
+            engine.render_frame(frame, &mut loader).unwrap();
+            assert_eq!(
+                engine.frame_buffer().get_pixel(5, 5),
+                Some(colors[frame as usize / 6])
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_reports_progress_events() {
+        struct NullSink;
+        impl FrameSink for NullSink {
+            fn write_frame(&mut self, _frame: &FrameBuffer) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut script = image_script(vec![]);
+        script.scenes[0].duration = 0.2;
+        let mut second = script.scenes[0].clone();
+        second.id = "second".into();
+        script.scenes.push(second);
+        script.metadata.duration = 0.4;
+
+        let mut events = Vec::new();
+        let mut engine = RenderEngine::new(script, false);
+        engine
+            .render(&mut NullSink, &mut AssetLoader::new("."), &mut |event| {
+                events.push(event)
+            })
+            .unwrap();
+
+        let frames: Vec<u32> = events
+            .iter()
+            .filter_map(|event| match event.kind {
+                ProgressKind::FrameCompleted { frame, total, .. } => {
+                    assert_eq!(total, 12);
+                    Some(frame)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(frames, (0..12).collect::<Vec<_>>());
+
+        let scenes: Vec<(&str, u32)> = events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                ProgressKind::SceneStarted { scene_id, frame } => Some((scene_id.as_str(), *frame)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(scenes, vec![("test", 0), ("second", 6)]);
+
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+        assert_eq!(
+            events.last().unwrap().kind,
+            ProgressKind::RenderFinished { frames: 12 }
+        );
+    }
+
+    #[test]
+    fn test_resize_changes_output_size() {
+        let mut engine = RenderEngine::new(image_script(vec![]), false);
+        let mut loader = AssetLoader::new(".");
+        engine.render_frame(0, &mut loader).unwrap();
+        assert_eq!(engine.frame_buffer().dimensions(), (480, 270));
+
+        engine.resize(240, 136);
+        engine.render_frame(1, &mut loader).unwrap();
+        assert_eq!(engine.frame_buffer().dimensions(), (240, 136));
+    }
+
+    #[test]
+    fn test_render_range_writes_only_requested_frames() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut engine = RenderEngine::new(image_script(vec![]), false);
+        let mut loader = AssetLoader::new(dir.path());
+
+        let mut sink = FrameFileSink::new(dir.path(), FrameFormat::Png).starting_at(3);
+        engine
+            .render_range(3..6, &mut sink, &mut loader, &mut |_| {})
+            .unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec!["frame_000003.png", "frame_000004.png", "frame_000005.png"]
+        );
+    }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_render_logs_frame_timings_and_failures() {
+        struct NullSink;
+        impl FrameSink for NullSink {
+            fn write_frame(&mut self, _: &FrameBuffer) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut engine = RenderEngine::new(waveform_script("missing.wav"), false);
+        engine
+            .render_range(
+                0..2,
+                &mut NullSink,
+                &mut AssetLoader::new(dir.path()),
+                &mut |_| {},
+            )
+            .unwrap();
+
+        assert!(logs_contain("Using CPU rendering"));
+        assert!(logs_contain("frame drawn in"));
+        assert!(logs_contain("frame=1"));
+        assert!(logs_contain("WARN"));
+        assert!(logs_contain("Failed to analyze waveform audio"));
+    }
+
+    #[test]
+    fn test_render_range_validated_against_timeline() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut script = image_script(vec![]);
+        script.metadata.duration = 5.0;
+        let mut engine = RenderEngine::new(script, false);
+        let mut loader = AssetLoader::new(dir.path());
+        let mut sink = FrameFileSink::new(dir.path(), FrameFormat::Png);
+
+        // 5 seconds at 30fps
+        assert_eq!(engine.timeline().total_frames(), 150);
+        assert!(engine
+            .render_range(149..151, &mut sink, &mut loader, &mut |_| {})
+            .is_err());
+        assert!(engine
+            .render_range(4..4, &mut sink, &mut loader, &mut |_| {})
+            .is_err());
+        assert!(engine.render_at_time(5.0, &mut loader).is_err());
+        assert!(engine.render_at_time(-1.0, &mut loader).is_err());
+        // NaN would otherwise cast to frame 0
+        assert!(engine.render_at_time(f32::NAN, &mut loader).is_err());
+        assert!(engine.render_at_time(f32::INFINITY, &mut loader).is_err());
+        assert_eq!(engine.render_at_time(2.5, &mut loader).unwrap(), 75);
+        // Nothing is written for a rejected range
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
 }
@@ -0,0 +1,155 @@
+//! Rendering for async programs (the `async` feature). The render runs on
+//! tokio's blocking pool, so it never stalls the runtime it's started from,
+//! and the GPU is set up with async adapter and device requests.
+//!
+//! ```no_run
+//! # async fn run() -> interstellar_triangulum::error::Result<()> {
+//! use interstellar_triangulum::renderer::engine::r#async::{render_script, AsyncRenderOptions};
+//! use interstellar_triangulum::ScriptParser;
+//! use tokio_stream::StreamExt;
+//!
+//! let script = ScriptParser::parse_json("examples/simple.json".as_ref())?;
+//! let mut render = render_script(script, AsyncRenderOptions::default());
+//! while let Some(event) = render.next().await {
+//!     println!("{:?}", event.kind);
+//! }
+//! let output = render.output().await?;
+//! println!("{} frames", output.frames);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Dropping the [`RenderTask`] before it's done cancels the render, which
+//! stops after the frame it's on.
+
+use crate::assets::AssetLoader;
+use crate::config::{AppConfig, RendererConfig};
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{
+    CancellationToken, FrameOutput, GpuOptions, GpuRenderer, NativeRenderer, ProgressEvent,
+    RenderEngine, RenderOutput, Renderer,
+};
+use crate::script::VideoScript;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::Stream;
+
+/// How [`render_script`] renders: the native engine's settings and where the
+/// frames and assets are
+#[derive(Debug, Clone)]
+pub struct AsyncRenderOptions {
+    /// Frames are written to `renderer.output_dir`
+    pub renderer: RendererConfig,
+    /// The GPU adapter to draw on, or `None` for the CPU
+    pub gpu: Option<GpuOptions>,
+    /// Directory relative asset paths are resolved against
+    pub asset_dir: PathBuf,
+}
+
+impl Default for AsyncRenderOptions {
+    fn default() -> Self {
+        let config = AppConfig::default();
+        Self {
+            renderer: config.renderer,
+            gpu: Some(GpuOptions::default()),
+            asset_dir: config.assets.base_path,
+        }
+    }
+}
+
+/// Render `script` to numbered frame files with the native engine. Must be
+/// called within a tokio runtime; the render starts right away.
+pub fn render_script(mut script: VideoScript, options: AsyncRenderOptions) -> RenderTask {
+    options.renderer.apply_watermark(&mut script);
+    let (events_tx, events) = mpsc::unbounded_channel();
+    let (output_tx, output) = oneshot::channel();
+    let cancellation = CancellationToken::new();
+    let token = cancellation.clone();
+
+    tokio::spawn(async move {
+        let gpu_renderer = match &options.gpu {
+            Some(gpu) => {
+                let (width, height) = script.metadata.resolution.dimensions();
+                match GpuRenderer::new(width, height, gpu).await {
+                    Ok(gpu_renderer) => Some(gpu_renderer),
+                    Err(e) => {
+                        say!("ℹ️  GPU unavailable: {:#}", e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let rendered = tokio::task::spawn_blocking(move || {
+            let mut backend = NativeRenderer::new(&options.renderer, options.gpu.as_ref());
+            backend.set_cancellation(token);
+            let engine = backend.configure(RenderEngine::with_gpu_renderer(script, gpu_renderer));
+            let output_dir = &options.renderer.output_dir;
+            std::fs::create_dir_all(output_dir)
+                .map_err(VideoEngineError::io("create", output_dir))?;
+            backend.render_with(
+                engine,
+                &mut AssetLoader::new(&options.asset_dir),
+                FrameOutput::Directory(output_dir),
+                &mut |event| {
+                    // Nobody is listening once the task is dropped, and then
+                    // the render is cancelled anyway
+                    let _ = events_tx.send(event);
+                },
+            )
+        })
+        .await;
+        let result = match rendered {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => Err(VideoEngineError::Cancelled),
+        };
+        let _ = output_tx.send(result);
+    });
+
+    RenderTask {
+        events,
+        output,
+        cancellation,
+    }
+}
+
+/// A render started by [`render_script`]: a stream of its progress events,
+/// ending when the render does, and its output through [`RenderTask::output`]
+pub struct RenderTask {
+    events: mpsc::UnboundedReceiver<ProgressEvent>,
+    output: oneshot::Receiver<Result<RenderOutput>>,
+    cancellation: CancellationToken,
+}
+
+impl RenderTask {
+    /// Wait for the render to finish. Progress events not yet taken from the
+    /// stream are dropped.
+    pub async fn output(mut self) -> Result<RenderOutput> {
+        (&mut self.output)
+            .await
+            .unwrap_or(Err(VideoEngineError::Cancelled))
+    }
+
+    /// Stop the render after the frame it's on; [`RenderTask::output`] then
+    /// fails with [`VideoEngineError::Cancelled`]
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+}
+
+impl Stream for RenderTask {
+    type Item = ProgressEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ProgressEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for RenderTask {
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+    }
+}
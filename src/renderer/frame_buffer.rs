@@ -1,68 +1,552 @@
-use anyhow::Result;
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::color::{linear_to_srgb, linear_to_srgb16, srgb16_to_linear, srgb_to_linear};
+use crate::renderer::compositor::clip_to_frame;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::Write;
+use std::ops::Range;
 
-/// RGBA frame buffer for rendering
+/// Image format for rendered frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    /// Compressed PNG
+    #[default]
+    Png,
+    /// Uncompressed binary PPM
+    Ppm,
+}
+
+impl FrameFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FrameFormat::Png => "png",
+            FrameFormat::Ppm => "ppm",
+        }
+    }
+
+    /// File name of a frame, zero-padded so ffmpeg's pattern matching is deterministic
+    pub fn file_name(&self, frame: u32) -> String {
+        format!("frame_{:06}.{}", frame, self.extension())
+    }
+
+    /// ffmpeg input pattern matching `file_name`
+    pub fn pattern(&self) -> String {
+        format!("frame_%06d.{}", self.extension())
+    }
+}
+
+/// Precision of a frame buffer's channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum BitDepth {
+    /// 8 bits per channel, what the GPU path and most outputs use
+    #[default]
+    Eight,
+    /// 16 bits per channel, so color grades and stacks of translucent layers don't band
+    Sixteen,
+}
+
+impl From<BitDepth> for u8 {
+    fn from(depth: BitDepth) -> Self {
+        match depth {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16,
+        }
+    }
+}
+
+impl TryFrom<u8> for BitDepth {
+    type Error = String;
+
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            8 => Ok(BitDepth::Eight),
+            16 => Ok(BitDepth::Sixteen),
+            other => Err(format!(
+                "Unsupported bit depth {} (expected 8 or 16)",
+                other
+            )),
+        }
+    }
+}
+
+/// Sampling used by `FrameBuffer::resize`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    /// Closest source pixel; fast, blocky when enlarging
+    Nearest,
+    /// Weighted average of the 4 nearest source pixels, in linear light
+    #[default]
+    Bilinear,
+}
+
+/// A channel type frame buffers store: sRGB-encoded color, plain alpha
+trait Channel: Copy + PartialEq {
+    /// Widen an 8-bit value
+    fn from_u8(value: u8) -> Self;
+    /// Decode sRGB to linear light (0.0-1.0)
+    fn to_linear(self) -> f32;
+    fn from_linear(value: f32) -> Self;
+    /// As a 0.0-1.0 fraction (for alpha)
+    fn to_unit(self) -> f32;
+    fn from_unit(value: f32) -> Self;
+}
+
+impl Channel for u8 {
+    fn from_u8(value: u8) -> Self {
+        value
+    }
+
+    fn to_linear(self) -> f32 {
+        srgb_to_linear(self)
+    }
+
+    fn from_linear(value: f32) -> Self {
+        linear_to_srgb(value)
+    }
+
+    fn to_unit(self) -> f32 {
+        self as f32 / 255.0
+    }
+
+    fn from_unit(value: f32) -> Self {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+impl Channel for u16 {
+    fn from_u8(value: u8) -> Self {
+        value as u16 * 257
+    }
+
+    fn to_linear(self) -> f32 {
+        srgb16_to_linear(self)
+    }
+
+    fn from_linear(value: f32) -> Self {
+        linear_to_srgb16(value)
+    }
+
+    fn to_unit(self) -> f32 {
+        self as f32 / 65535.0
+    }
+
+    fn from_unit(value: f32) -> Self {
+        (value.clamp(0.0, 1.0) * 65535.0).round() as u16
+    }
+}
+
+/// Nearest 8-bit value of a 16-bit one
+fn narrow(value: u16) -> u8 {
+    ((value as u32 * 255 + 32767) / 65535) as u8
+}
+
+/// A well-mixed 64-bit value from `seed`, so nearby seeds pick unrelated phases
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// 4x4 ordered-dither thresholds
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Alpha blend `color` (its alpha scaled by `opacity`) over an RGBA pixel in place.
+/// Channels are blended in linear light; the result is opaque.
+fn blend_over<C: Channel>(dst: &mut [C], color: [C; 4], opacity: f32) {
+    let alpha = color[3].to_unit() * opacity;
+    let inv_alpha = 1.0 - alpha;
+    for channel in 0..3 {
+        dst[channel] = C::from_linear(
+            color[channel].to_linear() * alpha + dst[channel].to_linear() * inv_alpha,
+        );
+    }
+    dst[3] = C::from_unit(1.0);
+}
+
+/// Set every pixel in `columns` x `rows` of a `width`-pixel-wide image to `color`
+fn fill<C: Channel>(
+    pixels: &mut [C],
+    width: u32,
+    columns: Range<u32>,
+    rows: Range<u32>,
+    color: [C; 4],
+) {
+    if columns.is_empty() || rows.is_empty() {
+        return;
+    }
+    let row_len = width as usize * 4;
+    let span = columns.start as usize * 4..columns.end as usize * 4;
+    let mut region = pixels[rows.start as usize * row_len..rows.end as usize * row_len]
+        .chunks_exact_mut(row_len);
+
+    // Pattern the first row, then copy it into the others
+    let Some(first) = region.next() else {
+        return;
+    };
+    let first = &mut first[span.clone()];
+    for pixel in first.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&color);
+    }
+    for row in region {
+        row[span.clone()].copy_from_slice(first);
+    }
+}
+
+/// Copy a `size` rectangle from `src_origin` in `src` to `dst_origin` in `dst`,
+/// one row at a time (images are `widths` pixels wide)
+fn copy_rect<C: Copy>(
+    dst: &mut [C],
+    src: &[C],
+    (dst_width, src_width): (usize, usize),
+    ((dst_x, dst_y), (src_x, src_y)): ((usize, usize), (usize, usize)),
+    (width, height): (usize, usize),
+) {
+    let row_len = width * 4;
+    for row in 0..height {
+        let dst_start = ((dst_y + row) * dst_width + dst_x) * 4;
+        let src_start = ((src_y + row) * src_width + src_x) * 4;
+        dst[dst_start..dst_start + row_len].copy_from_slice(&src[src_start..src_start + row_len]);
+    }
+}
+
+/// Blend `src` over `dst` where both images (`widths` pixels wide) overlap
+fn blend_rows<C: Channel>(
+    dst: &mut [C],
+    src: &[C],
+    (dst_width, src_width): (u32, u32),
+    rows: usize,
+    opacity: f32,
+) {
+    let width = dst_width.min(src_width) as usize * 4;
+    for (dst_row, src_row) in dst
+        .chunks_exact_mut(dst_width as usize * 4)
+        .zip(src.chunks_exact(src_width as usize * 4))
+        .take(rows)
+    {
+        for (dst, src) in dst_row[..width]
+            .chunks_exact_mut(4)
+            .zip(src_row[..width].chunks_exact(4))
+        {
+            let color = [src[0], src[1], src[2], src[3]];
+            if opacity == 1.0 && color[3] == C::from_u8(255) {
+                dst.copy_from_slice(src);
+            } else {
+                blend_over(dst, color, opacity);
+            }
+        }
+    }
+}
+
+/// Scale a `src_size` image into a `dst_size` one
+fn resample<C: Channel>(
+    src: &[C],
+    (src_width, src_height): (u32, u32),
+    dst: &mut [C],
+    (width, height): (u32, u32),
+    filter: ResizeFilter,
+) {
+    let scale_x = src_width as f32 / width as f32;
+    let scale_y = src_height as f32 / height as f32;
+    let src_row_len = src_width as usize * 4;
+    let dst_row_len = width as usize * 4;
+
+    match filter {
+        ResizeFilter::Nearest => {
+            // Source column of every output column, computed once
+            let columns: Vec<usize> = (0..width)
+                .map(|x| ((((x as f32 + 0.5) * scale_x) as u32).min(src_width - 1) * 4) as usize)
+                .collect();
+            for (y, dst_row) in dst.chunks_exact_mut(dst_row_len).enumerate() {
+                let src_y = (((y as f32 + 0.5) * scale_y) as u32).min(src_height - 1);
+                let src_row = &src[src_y as usize * src_row_len..][..src_row_len];
+                for (dst, &src_x) in dst_row.chunks_exact_mut(4).zip(&columns) {
+                    dst.copy_from_slice(&src_row[src_x..src_x + 4]);
+                }
+            }
+        }
+        ResizeFilter::Bilinear => {
+            // Pixel centers map onto each other; edges clamp to the outermost pixels
+            let sample = |position: f32, len: u32| {
+                let position = (position - 0.5).clamp(0.0, (len - 1) as f32);
+                let first = position.floor() as u32;
+                (
+                    first as usize,
+                    (first + 1).min(len - 1) as usize,
+                    position.fract(),
+                )
+            };
+            let columns: Vec<(usize, usize, f32)> = (0..width)
+                .map(|x| sample((x as f32 + 0.5) * scale_x, src_width))
+                .collect();
+            let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+            for (y, dst_row) in dst.chunks_exact_mut(dst_row_len).enumerate() {
+                let (top, bottom, ty) = sample((y as f32 + 0.5) * scale_y, src_height);
+                let top_row = &src[top * src_row_len..][..src_row_len];
+                let bottom_row = &src[bottom * src_row_len..][..src_row_len];
+                for (dst, &(left, right, tx)) in dst_row.chunks_exact_mut(4).zip(&columns) {
+                    for channel in 0..4 {
+                        // Color channels are averaged in linear light, alpha as is
+                        let value = |row: &[C], x: usize| {
+                            let c = row[x * 4 + channel];
+                            if channel == 3 {
+                                c.to_unit()
+                            } else {
+                                c.to_linear()
+                            }
+                        };
+                        let mixed = lerp(
+                            lerp(value(top_row, left), value(top_row, right), tx),
+                            lerp(value(bottom_row, left), value(bottom_row, right), tx),
+                            ty,
+                        );
+                        dst[channel] = if channel == 3 {
+                            C::from_unit(mixed)
+                        } else {
+                            C::from_linear(mixed)
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Channel storage: RGBA, 4 values per pixel
+#[derive(Debug, Clone)]
+enum Pixels {
+    Eight(Vec<u8>),
+    Sixteen(Vec<u16>),
+}
+
+/// RGBA frame buffer for rendering. Pixels go in and out as 8-bit sRGB; a
+/// 16-bit buffer keeps the extra precision through blending and resizing.
 #[derive(Debug, Clone)]
 pub struct FrameBuffer {
     width: u32,
     height: u32,
-    pixels: Vec<u8>, // RGBA, 4 bytes per pixel
+    pixels: Pixels,
+    /// Picks one of the 16 phases of the dither pattern when a 16-bit buffer
+    /// is narrowed
+    dither_seed: u64,
 }
 
 impl FrameBuffer {
     /// Create new frame buffer with given dimensions
     pub fn new(width: u32, height: u32) -> Self {
+        Self::with_bit_depth(width, height, BitDepth::Eight)
+    }
+
+    /// Create a frame buffer storing `depth` bits per channel
+    pub fn with_bit_depth(width: u32, height: u32, depth: BitDepth) -> Self {
         let size = (width * height * 4) as usize;
+        let pixels = match depth {
+            BitDepth::Eight => Pixels::Eight(vec![0; size]),
+            BitDepth::Sixteen => Pixels::Sixteen(vec![0; size]),
+        };
         Self {
             width,
             height,
-            pixels: vec![0; size],
+            pixels,
+            dither_seed: 0,
+        }
+    }
+
+    /// The buffer, dithered from 16 to 8 bits with the phase of the pattern
+    /// `seed` picks. Buffers with the same pixels and seed always narrow to the
+    /// same bytes; as there are only 16 phases, different seeds can too.
+    pub fn with_dither_seed(mut self, seed: u64) -> Self {
+        self.dither_seed = seed;
+        self
+    }
+
+    pub fn dither_seed(&self) -> u64 {
+        self.dither_seed
+    }
+
+    pub fn bit_depth(&self) -> BitDepth {
+        match self.pixels {
+            Pixels::Eight(_) => BitDepth::Eight,
+            Pixels::Sixteen(_) => BitDepth::Sixteen,
+        }
+    }
+
+    /// A copy storing `depth` bits per channel (narrowing rounds to nearest)
+    pub fn to_bit_depth(&self, depth: BitDepth) -> FrameBuffer {
+        let pixels = match (&self.pixels, depth) {
+            (Pixels::Eight(p), BitDepth::Sixteen) => {
+                Pixels::Sixteen(p.iter().map(|&v| u16::from_u8(v)).collect())
+            }
+            (Pixels::Sixteen(p), BitDepth::Eight) => {
+                Pixels::Eight(p.iter().map(|&v| narrow(v)).collect())
+            }
+            (pixels, _) => pixels.clone(),
+        };
+        Self {
+            width: self.width,
+            height: self.height,
+            pixels,
+            dither_seed: self.dither_seed,
+        }
+    }
+
+    /// `self` at `depth`, copied only if it isn't already
+    fn at_bit_depth(&self, depth: BitDepth) -> Cow<'_, FrameBuffer> {
+        if self.bit_depth() == depth {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.to_bit_depth(depth))
         }
     }
 
     /// Clear buffer with color
     pub fn clear(&mut self, color: [u8; 4]) {
-        for chunk in self.pixels.chunks_exact_mut(4) {
-            chunk.copy_from_slice(&color);
+        if color.iter().all(|&byte| byte == color[0]) {
+            // Black, white and other grays are a plain fill
+            match &mut self.pixels {
+                Pixels::Eight(p) => p.fill(color[0]),
+                Pixels::Sixteen(p) => p.fill(u16::from_u8(color[0])),
+            }
+        } else {
+            self.fill_region(0..self.width, 0..self.height, color);
         }
     }
 
+    /// Set every pixel in `columns` x `rows` to `color`. The ranges must lie
+    /// inside the buffer.
+    pub fn fill_region(&mut self, columns: Range<u32>, rows: Range<u32>, color: [u8; 4]) {
+        match &mut self.pixels {
+            Pixels::Eight(p) => fill(p, self.width, columns, rows, color),
+            Pixels::Sixteen(p) => fill(p, self.width, columns, rows, color.map(u16::from_u8)),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| ((y * self.width + x) * 4) as usize)
+    }
+
     /// Set pixel at position
     pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
-        if x < self.width && y < self.height {
-            let idx = ((y * self.width + x) * 4) as usize;
-            self.pixels[idx..idx + 4].copy_from_slice(&color);
+        if let Some(idx) = self.index(x, y) {
+            match &mut self.pixels {
+                Pixels::Eight(p) => p[idx..idx + 4].copy_from_slice(&color),
+                Pixels::Sixteen(p) => p[idx..idx + 4].copy_from_slice(&color.map(u16::from_u8)),
+            }
         }
     }
 
-    /// Get pixel at position
+    /// Get pixel at position (rounded to 8 bits in a 16-bit buffer)
     pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
-        if x < self.width && y < self.height {
-            let idx = ((y * self.width + x) * 4) as usize;
-            let mut pixel = [0u8; 4];
-            pixel.copy_from_slice(&self.pixels[idx..idx + 4]);
-            Some(pixel)
-        } else {
-            None
-        }
+        let idx = self.index(x, y)?;
+        Some(match &self.pixels {
+            Pixels::Eight(p) => [p[idx], p[idx + 1], p[idx + 2], p[idx + 3]],
+            Pixels::Sixteen(p) => [p[idx], p[idx + 1], p[idx + 2], p[idx + 3]].map(narrow),
+        })
     }
 
-    /// Alpha blend a color onto the buffer at position
+    /// Get pixel at position with 16 bits per channel (8-bit buffers are widened)
+    pub fn get_pixel16(&self, x: u32, y: u32) -> Option<[u16; 4]> {
+        let idx = self.index(x, y)?;
+        Some(match &self.pixels {
+            Pixels::Eight(p) => [p[idx], p[idx + 1], p[idx + 2], p[idx + 3]].map(u16::from_u8),
+            Pixels::Sixteen(p) => [p[idx], p[idx + 1], p[idx + 2], p[idx + 3]],
+        })
+    }
+
+    /// Alpha blend a color onto the buffer at position. Channels are blended in
+    /// linear light and stored as sRGB, matching the GPU's sRGB render targets.
     pub fn blend_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
-        if let Some(bg) = self.get_pixel(x, y) {
-            let alpha = color[3] as f32 / 255.0;
-            let inv_alpha = 1.0 - alpha;
+        if let Some(idx) = self.index(x, y) {
+            match &mut self.pixels {
+                Pixels::Eight(p) => blend_over(&mut p[idx..idx + 4], color, 1.0),
+                Pixels::Sixteen(p) => {
+                    blend_over(&mut p[idx..idx + 4], color.map(u16::from_u8), 1.0)
+                }
+            }
+        }
+    }
+
+    /// Copy `src` into the buffer with its top-left at `(x, y)`, replacing what's
+    /// there. Parts falling outside the buffer are clipped.
+    pub fn blit(&mut self, src: &FrameBuffer, x: i32, y: i32) {
+        let (left, top) = (x as f32, y as f32);
+        let Some([x0, y0, x1, y1]) = clip_to_frame(
+            [left, top, left + src.width as f32, top + src.height as f32],
+            self.width,
+            self.height,
+        ) else {
+            return;
+        };
+        let (x0, y0, x1, y1) = (x0 as usize, y0 as usize, x1 as usize, y1 as usize);
+        let src_x = (x0 as i64 - x as i64) as usize;
+        let src_y = (y0 as i64 - y as i64) as usize;
+        let widths = (self.width as usize, src.width as usize);
+        let origins = ((x0, y0), (src_x, src_y));
+        let size = (x1 - x0, y1 - y0);
+
+        let src = src.at_bit_depth(self.bit_depth());
+        match (&mut self.pixels, &src.pixels) {
+            (Pixels::Eight(dst), Pixels::Eight(src)) => copy_rect(dst, src, widths, origins, size),
+            (Pixels::Sixteen(dst), Pixels::Sixteen(src)) => {
+                copy_rect(dst, src, widths, origins, size)
+            }
+            _ => unreachable!("source converted to the buffer's bit depth"),
+        }
+    }
+
+    /// Alpha blend `src` over the buffer at `opacity` (0.0-1.0), e.g. to cross-fade
+    /// two frames as the engine's dissolves do. Only the region both buffers
+    /// cover is blended.
+    pub fn blend_buffer(&mut self, src: &FrameBuffer, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if opacity == 0.0 {
+            return;
+        }
+        let widths = (self.width, src.width);
+        let rows = self.height.min(src.height) as usize;
 
-            let blended = [
-                (color[0] as f32 * alpha + bg[0] as f32 * inv_alpha) as u8,
-                (color[1] as f32 * alpha + bg[1] as f32 * inv_alpha) as u8,
-                (color[2] as f32 * alpha + bg[2] as f32 * inv_alpha) as u8,
-                255, // Output alpha is always opaque
-            ];
+        let src = src.at_bit_depth(self.bit_depth());
+        match (&mut self.pixels, &src.pixels) {
+            (Pixels::Eight(dst), Pixels::Eight(src)) => blend_rows(dst, src, widths, rows, opacity),
+            (Pixels::Sixteen(dst), Pixels::Sixteen(src)) => {
+                blend_rows(dst, src, widths, rows, opacity)
+            }
+            _ => unreachable!("source converted to the buffer's bit depth"),
+        }
+    }
+
+    /// A `width` x `height` copy of the region whose top-left is `(x, y)`.
+    /// Parts of the region outside the buffer come out transparent black.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> FrameBuffer {
+        let mut cropped = FrameBuffer::with_bit_depth(width, height, self.bit_depth())
+            .with_dither_seed(self.dither_seed);
+        cropped.blit(self, -(x as i32), -(y as i32));
+        cropped
+    }
 
-            self.set_pixel(x, y, blended);
+    /// A copy scaled to `width` x `height`
+    pub fn resize(&self, width: u32, height: u32, filter: ResizeFilter) -> FrameBuffer {
+        let mut resized = FrameBuffer::with_bit_depth(width, height, self.bit_depth())
+            .with_dither_seed(self.dither_seed);
+        if self.width == 0 || self.height == 0 {
+            return resized;
         }
+        let (src_size, dst_size) = ((self.width, self.height), (width, height));
+        match (&self.pixels, &mut resized.pixels) {
+            (Pixels::Eight(src), Pixels::Eight(dst)) => {
+                resample(src, src_size, dst, dst_size, filter)
+            }
+            (Pixels::Sixteen(src), Pixels::Sixteen(dst)) => {
+                resample(src, src_size, dst, dst_size, filter)
+            }
+            _ => unreachable!("resized buffer has the source's bit depth"),
+        }
+        resized
     }
 
     /// Get buffer dimensions
@@ -70,29 +554,182 @@ impl FrameBuffer {
         (self.width, self.height)
     }
 
-    /// Get raw pixel data
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.pixels
+    /// Raw 8-bit RGBA pixel data; a 16-bit buffer is rounded into a copy
+    pub fn as_bytes(&self) -> Cow<'_, [u8]> {
+        match &self.pixels {
+            Pixels::Eight(p) => Cow::Borrowed(p),
+            Pixels::Sixteen(p) => Cow::Owned(p.iter().map(|&v| narrow(v)).collect()),
+        }
     }
 
-    /// Copy data from a slice into the buffer
+    /// 8-bit RGBA pixel data for output, with a 16-bit buffer ordered-dithered
+    /// down so its smooth gradients don't turn into visible steps. The dither
+    /// seed only selects the phase of the 4x4 pattern, shifting it by 0-3
+    /// pixels each way, so on average one seed in 16 shares any given seed's
+    /// output.
+    pub fn to_rgba8_dithered(&self) -> Cow<'_, [u8]> {
+        let Pixels::Sixteen(p) = &self.pixels else {
+            return self.as_bytes();
+        };
+        let width = self.width as usize;
+        let offset = splitmix64(self.dither_seed) as usize;
+        let (dx, dy) = (offset & 3, (offset >> 2) & 3);
+        let dithered = p
+            .chunks_exact(4)
+            .enumerate()
+            .flat_map(|(i, pixel)| {
+                let threshold = BAYER_4X4[(i / width + dy) % 4][(i % width + dx) % 4] as f32 / 16.0
+                    + 1.0 / 32.0;
+                pixel
+                    .iter()
+                    .map(move |&v| (v as f32 / 257.0 + threshold).floor().min(255.0) as u8)
+            })
+            .collect();
+        Cow::Owned(dithered)
+    }
+
+    /// The frame as an 8-bit image, dithered from a 16-bit buffer like
+    /// `to_rgba8_dithered`
+    pub fn to_image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(
+            self.width,
+            self.height,
+            self.to_rgba8_dithered().into_owned(),
+        )
+        .expect("frame buffer holds 4 bytes per pixel")
+    }
+
+    /// An 8-bit frame buffer holding `image`
+    pub fn from_image(image: &image::RgbaImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: Pixels::Eight(image.as_raw().clone()),
+            dither_seed: 0,
+        }
+    }
+
+    /// Copy 8-bit RGBA data into the buffer (widened in a 16-bit buffer)
     pub fn copy_from_slice(&mut self, data: &[u8]) {
-        self.pixels.copy_from_slice(data);
+        match &mut self.pixels {
+            Pixels::Eight(p) => p.copy_from_slice(data),
+            Pixels::Sixteen(p) => {
+                for (dst, &src) in p.iter_mut().zip(data) {
+                    *dst = u16::from_u8(src);
+                }
+            }
+        }
+    }
+
+    /// Copy 8-bit rows laid out `src_stride` bytes apart (e.g. a padded GPU
+    /// readback), ignoring any padding after each row's pixels. A 16-bit buffer
+    /// widens them.
+    pub fn copy_rows_from(&mut self, data: &[u8], src_stride: usize) {
+        let row_len = self.width as usize * 4;
+        match &mut self.pixels {
+            Pixels::Eight(p) => {
+                for (dst, src) in p.chunks_exact_mut(row_len).zip(data.chunks(src_stride)) {
+                    dst.copy_from_slice(&src[..row_len]);
+                }
+            }
+            Pixels::Sixteen(p) => {
+                for (dst, src) in p.chunks_exact_mut(row_len).zip(data.chunks(src_stride)) {
+                    for (dst, &src) in dst.iter_mut().zip(&src[..row_len]) {
+                        *dst = u16::from_u8(src);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Save as PNG (alpha is dropped, frames are always opaque). 16-bit buffers
+    /// are saved as 16-bit PNGs.
+    pub fn save_png(&self, path: &str) -> Result<()> {
+        let saved = match &self.pixels {
+            Pixels::Eight(p) => {
+                let rgb: Vec<u8> = p
+                    .chunks_exact(4)
+                    .flat_map(|chunk| chunk[0..3].iter().copied())
+                    .collect();
+                image::save_buffer_with_format(
+                    path,
+                    &rgb,
+                    self.width,
+                    self.height,
+                    image::ExtendedColorType::Rgb8,
+                    image::ImageFormat::Png,
+                )
+            }
+            Pixels::Sixteen(p) => {
+                let rgb: Vec<u16> = p
+                    .chunks_exact(4)
+                    .flat_map(|chunk| chunk[0..3].iter().copied())
+                    .collect();
+                image::ImageBuffer::<image::Rgb<u16>, _>::from_raw(self.width, self.height, rgb)
+                    .ok_or_else(|| VideoEngineError::invalid("Frame buffer has the wrong size"))?
+                    .save_with_format(path, image::ImageFormat::Png)
+            }
+        };
+        saved.map_err(VideoEngineError::image("write PNG frame", path))
     }
 
-    /// Save as PPM (simple image format)
+    /// Save as JPEG at `quality` (1-100). JPEG has no alpha channel or 16-bit
+    /// samples, so the colors are written as 8-bit RGB.
+    pub fn save_jpeg(&self, path: &str, quality: u8) -> Result<()> {
+        let rgb: Vec<u8> = self
+            .to_rgba8_dithered()
+            .chunks_exact(4)
+            .flat_map(|chunk| chunk[0..3].iter().copied())
+            .collect();
+        let file = File::create(path).map_err(VideoEngineError::io("write JPEG frame", path))?;
+        image::codecs::jpeg::JpegEncoder::new_with_quality(std::io::BufWriter::new(file), quality)
+            .encode(
+                &rgb,
+                self.width,
+                self.height,
+                image::ExtendedColorType::Rgb8,
+            )
+            .map_err(VideoEngineError::image("write JPEG frame", path))
+    }
+
+    /// Save in the given frame format
+    pub fn save(&self, path: &str, format: FrameFormat) -> Result<()> {
+        match format {
+            FrameFormat::Png => self.save_png(path),
+            FrameFormat::Ppm => self.save_ppm(path),
+        }
+    }
+
+    /// Save as PPM (simple image format); 16-bit buffers use PPM's 2-byte samples
     pub fn save_ppm(&self, path: &str) -> Result<()> {
+        self.write_ppm(path)
+            .map_err(VideoEngineError::io("write PPM frame", path))
+    }
+
+    fn write_ppm(&self, path: &str) -> std::io::Result<()> {
         let file = File::create(path)?;
         let mut writer = std::io::BufWriter::new(file);
 
         // PPM header
         writeln!(writer, "P6")?;
         writeln!(writer, "{} {}", self.width, self.height)?;
-        writeln!(writer, "255")?;
 
         // Write RGB data (skip alpha channel)
-        for chunk in self.pixels.chunks_exact(4) {
-            writer.write_all(&chunk[0..3])?;
+        match &self.pixels {
+            Pixels::Eight(p) => {
+                writeln!(writer, "255")?;
+                for chunk in p.chunks_exact(4) {
+                    writer.write_all(&chunk[0..3])?;
+                }
+            }
+            Pixels::Sixteen(p) => {
+                writeln!(writer, "65535")?;
+                for chunk in p.chunks_exact(4) {
+                    for value in &chunk[0..3] {
+                        writer.write_all(&value.to_be_bytes())?;
+                    }
+                }
+            }
         }
 
         writer.flush()?;
@@ -104,11 +741,39 @@ impl FrameBuffer {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_image_round_trip() {
+        let image = image::RgbaImage::from_fn(3, 2, |x, y| image::Rgba([x as u8, y as u8, 7, 255]));
+        let fb = FrameBuffer::from_image(&image);
+        assert_eq!(fb.dimensions(), (3, 2));
+        assert_eq!(fb.get_pixel(2, 1), Some([2, 1, 7, 255]));
+        assert_eq!(fb.to_image(), image);
+
+        let wide = fb.to_bit_depth(BitDepth::Sixteen);
+        assert_eq!(wide.to_image(), image);
+    }
+
+    #[test]
+    fn test_copy_rows_from_strips_padding() {
+        let mut fb = FrameBuffer::new(3, 2);
+        // 12 bytes of pixels per row, padded to 16
+        let mut data = vec![0u8; 32];
+        data[0..4].copy_from_slice(&[1, 2, 3, 4]);
+        data[12..16].copy_from_slice(&[9, 9, 9, 9]);
+        data[16 + 8..16 + 12].copy_from_slice(&[5, 6, 7, 8]);
+
+        fb.copy_rows_from(&data, 16);
+        assert_eq!(fb.get_pixel(0, 0), Some([1, 2, 3, 4]));
+        assert_eq!(fb.get_pixel(2, 1), Some([5, 6, 7, 8]));
+        // Padding never lands in the next row
+        assert_eq!(fb.get_pixel(0, 1), Some([0, 0, 0, 0]));
+    }
+
     #[test]
     fn test_frame_buffer_creation() {
         let fb = FrameBuffer::new(1920, 1080);
         assert_eq!(fb.dimensions(), (1920, 1080));
-        assert_eq!(fb.pixels.len(), 1920 * 1080 * 4);
+        assert_eq!(fb.as_bytes().len(), 1920 * 1080 * 4);
     }
 
     #[test]
@@ -138,9 +803,267 @@ mod tests {
         fb.blend_pixel(50, 50, [255, 0, 0, 128]);
 
         let pixel = fb.get_pixel(50, 50).unwrap();
-        // Should be approximately pink (255, 127, 127, 255)
+        // Half the white light remains: pink (255, 187, 187, 255) in sRGB
         assert!(pixel[0] == 255);
-        assert!(pixel[1] > 120 && pixel[1] < 135);
-        assert!(pixel[2] > 120 && pixel[2] < 135);
+        assert!(pixel[1] > 183 && pixel[1] < 191);
+        assert_eq!(pixel[1], pixel[2]);
+    }
+
+    /// 1px black and white checkerboard, white at (0, 0)
+    fn checkerboard(width: u32, height: u32) -> FrameBuffer {
+        let mut fb = FrameBuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let level = if (x + y) % 2 == 0 { 255 } else { 0 };
+                fb.set_pixel(x, y, [level, level, level, 255]);
+            }
+        }
+        fb
+    }
+
+    #[test]
+    fn test_blit_clips_at_every_edge() {
+        let mut src = FrameBuffer::new(4, 4);
+        src.clear([255, 0, 0, 255]);
+        src.set_pixel(0, 0, [0, 255, 0, 255]);
+        src.set_pixel(3, 3, [0, 0, 255, 255]);
+
+        let mut fb = FrameBuffer::new(6, 6);
+        // Hanging off the top-left: only src (2..4, 2..4) lands, at (0..2, 0..2)
+        fb.blit(&src, -2, -2);
+        assert_eq!(fb.get_pixel(1, 1), Some([0, 0, 255, 255]));
+        assert_eq!(fb.get_pixel(0, 0), Some([255, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(2, 0), Some([0, 0, 0, 0]));
+        assert_eq!(fb.get_pixel(0, 2), Some([0, 0, 0, 0]));
+
+        // Hanging off the bottom-right: src (0..2, 0..2) at (4..6, 4..6)
+        fb.blit(&src, 4, 4);
+        assert_eq!(fb.get_pixel(4, 4), Some([0, 255, 0, 255]));
+        assert_eq!(fb.get_pixel(5, 5), Some([255, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(3, 4), Some([0, 0, 0, 0]));
+
+        // Entirely outside: nothing changes
+        let before = fb.clone();
+        fb.blit(&src, -4, 0);
+        fb.blit(&src, 0, 6);
+        assert_eq!(fb.as_bytes(), before.as_bytes());
+    }
+
+    #[test]
+    fn test_crop_is_exact() {
+        let mut fb = FrameBuffer::new(5, 4);
+        for y in 0..4 {
+            for x in 0..5 {
+                fb.set_pixel(x, y, [x as u8, y as u8, 0, 255]);
+            }
+        }
+
+        let cropped = fb.crop(1, 2, 3, 2);
+        assert_eq!(cropped.dimensions(), (3, 2));
+        for y in 0..2 {
+            for x in 0..3 {
+                assert_eq!(
+                    cropped.get_pixel(x, y),
+                    Some([x as u8 + 1, y as u8 + 2, 0, 255])
+                );
+            }
+        }
+
+        // The last column and row are included; past them is transparent
+        let corner = fb.crop(4, 3, 2, 2);
+        assert_eq!(corner.get_pixel(0, 0), Some([4, 3, 0, 255]));
+        assert_eq!(corner.get_pixel(1, 0), Some([0, 0, 0, 0]));
+        assert_eq!(corner.get_pixel(0, 1), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_resize_checkerboard_down_2x() {
+        let fb = checkerboard(8, 6);
+
+        // Nearest picks one pixel of each 2x2 block, always the same parity
+        let nearest = fb.resize(4, 3, ResizeFilter::Nearest);
+        assert_eq!(nearest.dimensions(), (4, 3));
+        let first = nearest.get_pixel(0, 0).unwrap();
+        assert!(first == [0, 0, 0, 255] || first == [255, 255, 255, 255]);
+        assert!(nearest
+            .as_bytes()
+            .chunks_exact(4)
+            .all(|pixel| pixel == first));
+
+        // Bilinear averages each 2x2 block: half the light, 188 in sRGB
+        let bilinear = fb.resize(4, 3, ResizeFilter::Bilinear);
+        assert!(bilinear
+            .as_bytes()
+            .chunks_exact(4)
+            .all(|pixel| pixel == [188, 188, 188, 255]));
+    }
+
+    #[test]
+    fn test_resize_up_keeps_corners() {
+        let mut fb = FrameBuffer::new(2, 2);
+        fb.set_pixel(0, 0, [255, 0, 0, 255]);
+        fb.set_pixel(1, 0, [0, 255, 0, 255]);
+        fb.set_pixel(0, 1, [0, 0, 255, 255]);
+        fb.set_pixel(1, 1, [255, 255, 255, 255]);
+
+        for filter in [ResizeFilter::Nearest, ResizeFilter::Bilinear] {
+            let big = fb.resize(8, 8, filter);
+            assert_eq!(big.get_pixel(0, 0), fb.get_pixel(0, 0));
+            assert_eq!(big.get_pixel(7, 0), fb.get_pixel(1, 0));
+            assert_eq!(big.get_pixel(0, 7), fb.get_pixel(0, 1));
+            assert_eq!(big.get_pixel(7, 7), fb.get_pixel(1, 1));
+        }
+    }
+
+    #[test]
+    fn test_blend_buffer() {
+        let mut fb = FrameBuffer::new(4, 4);
+        fb.clear([255, 255, 255, 255]);
+        let mut src = FrameBuffer::new(2, 2);
+        src.clear([0, 0, 0, 255]);
+
+        fb.blend_buffer(&src, 0.5);
+        // Half the white light remains
+        assert_eq!(fb.get_pixel(1, 1), Some([188, 188, 188, 255]));
+        assert_eq!(fb.get_pixel(0, 0), fb.get_pixel(1, 1));
+        // Outside the smaller source is untouched
+        assert_eq!(fb.get_pixel(2, 2), Some([255, 255, 255, 255]));
+
+        fb.blend_buffer(&src, 1.0);
+        assert_eq!(fb.get_pixel(0, 0), Some([0, 0, 0, 255]));
+        fb.blend_buffer(&checkerboard(4, 4), 0.0);
+        assert_eq!(fb.get_pixel(0, 0), Some([0, 0, 0, 255]));
+    }
+
+    /// Column `x` of a 64x1 strip gets `64 - x` layers of white at 1% opacity
+    fn stacked_layers(depth: BitDepth) -> FrameBuffer {
+        let mut strip = FrameBuffer::with_bit_depth(64, 1, depth);
+        strip.clear([0, 0, 0, 255]);
+        for layer in 0..64 {
+            let mut white = FrameBuffer::new(64 - layer, 1);
+            white.clear([255, 255, 255, 255]);
+            strip.blend_buffer(&white, 0.01);
+        }
+        strip
+    }
+
+    /// Light of `layers` stacked 1% white layers over black
+    fn exact_light(layers: u32) -> f32 {
+        1.0 - 0.99f32.powi(layers as i32)
+    }
+
+    #[test]
+    fn test_16_bit_blending_avoids_banding() {
+        let deep = stacked_layers(BitDepth::Sixteen);
+        let light = |fb: &FrameBuffer, x: u32| srgb16_to_linear(fb.get_pixel16(x, 0).unwrap()[0]);
+
+        // A smooth ramp: every column brighter than the next, all close to exact
+        for x in 0..64 {
+            let error = (light(&deep, x) - exact_light(64 - x)).abs();
+            assert!(error < 1e-4, "column {} off by {}", x, error);
+            if x > 0 {
+                assert!(
+                    deep.get_pixel16(x - 1, 0).unwrap()[0] > deep.get_pixel16(x, 0).unwrap()[0]
+                );
+            }
+        }
+
+        // At 8 bits each layer rounds to the nearest code and the errors pile up
+        let shallow = stacked_layers(BitDepth::Eight);
+        let worst = (0..64)
+            .map(|x| (light(&shallow, x) - exact_light(64 - x)).abs())
+            .fold(0.0, f32::max);
+        assert!(worst > 0.01, "8-bit error only {}", worst);
+        let exact = linear_to_srgb(exact_light(64));
+        assert_eq!(deep.get_pixel(0, 0).unwrap()[0], exact);
+        assert!(shallow.get_pixel(0, 0).unwrap()[0] + 2 <= exact);
+    }
+
+    #[test]
+    fn test_16_bit_conversions() {
+        let mut fb = FrameBuffer::with_bit_depth(8, 8, BitDepth::Sixteen);
+        fb.set_pixel(0, 0, [10, 20, 30, 255]);
+        assert_eq!(fb.get_pixel16(0, 0), Some([2570, 5140, 7710, 65535]));
+        assert_eq!(fb.get_pixel(0, 0), Some([10, 20, 30, 255]));
+        assert_eq!(
+            fb.to_bit_depth(BitDepth::Eight).get_pixel(0, 0),
+            Some([10, 20, 30, 255])
+        );
+
+        // Halfway between two 8-bit codes dithers to an even mix of both
+        let half = 100 * 257 + 128;
+        if let Pixels::Sixteen(p) = &mut fb.pixels {
+            p.fill(half);
+        }
+        let dithered = fb.to_rgba8_dithered();
+        assert!(dithered.iter().all(|&v| v == 100 || v == 101));
+        assert_eq!(
+            dithered.iter().filter(|&&v| v == 101).count(),
+            dithered.len() / 2
+        );
+
+        // 8-bit readbacks are widened
+        let mut readback = FrameBuffer::with_bit_depth(1, 1, BitDepth::Sixteen);
+        readback.copy_rows_from(&[255, 128, 0, 255], 4);
+        assert_eq!(readback.get_pixel16(0, 0), Some([65535, 32896, 0, 65535]));
+    }
+
+    #[test]
+    fn test_dither_seed_selects_one_of_16_phases() {
+        // A sixteenth of the way to the next 8-bit code lights one pixel per 4x4 tile
+        let mut fb = FrameBuffer::with_bit_depth(4, 4, BitDepth::Sixteen);
+        if let Pixels::Sixteen(p) = &mut fb.pixels {
+            p.fill(100 * 257 + 16);
+        }
+        let pattern = |seed: u64| {
+            fb.clone()
+                .with_dither_seed(seed)
+                .to_rgba8_dithered()
+                .into_owned()
+        };
+        assert_eq!(pattern(0).iter().filter(|&&v| v == 101).count(), 4);
+
+        let phases: std::collections::HashSet<_> = (0..64).map(pattern).collect();
+        assert_eq!(phases.len(), 16);
+        assert_ne!(pattern(0), pattern(1));
+        assert_ne!(pattern(1), pattern(2));
+        // Seeds 4 and 5 happen to select the same phase
+        assert_eq!(pattern(4), pattern(5));
+    }
+
+    #[test]
+    fn test_16_bit_png_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("deep.png");
+        let strip = stacked_layers(BitDepth::Sixteen);
+        strip.save_png(path.to_str().unwrap()).unwrap();
+
+        let loaded = image::open(&path).unwrap();
+        assert_eq!(loaded.color(), image::ColorType::Rgb16);
+        let loaded = loaded.to_rgb16();
+        for x in 0..64 {
+            let [r, g, b, _] = strip.get_pixel16(x, 0).unwrap();
+            assert_eq!(loaded.get_pixel(x, 0).0, [r, g, b]);
+        }
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(FrameFormat::Png.file_name(7));
+        assert!(path.ends_with("frame_000007.png"));
+
+        let mut fb = FrameBuffer::new(4, 3);
+        fb.clear([10, 20, 30, 255]);
+        fb.set_pixel(1, 2, [200, 100, 50, 255]);
+        fb.save_png(path.to_str().unwrap()).unwrap();
+
+        let loaded = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(loaded.dimensions(), (4, 3));
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(Some(loaded.get_pixel(x, y).0), fb.get_pixel(x, y));
+            }
+        }
     }
 }
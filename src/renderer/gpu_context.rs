@@ -1,35 +1,261 @@
-use anyhow::{Context, Result};
+use crate::error::{Result, VideoEngineError};
+use serde::{Deserialize, Serialize};
 use wgpu;
 
+/// Which adapter to prefer when several match
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuPowerPreference {
+    /// Discrete GPUs first
+    #[default]
+    HighPerformance,
+    /// Integrated GPUs first
+    LowPower,
+    /// The first adapter found
+    None,
+}
+
+/// How the GPU renderer picks its adapter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuOptions {
+    #[serde(default)]
+    pub power_preference: GpuPowerPreference,
+    /// Comma-separated backends to consider, e.g. `vulkan,gl` (all when unset)
+    #[serde(default)]
+    pub backend_filter: Option<String>,
+    /// Only use an adapter whose name contains this (case-insensitive)
+    #[serde(default)]
+    pub adapter_name_substring: Option<String>,
+}
+
+impl GpuOptions {
+    fn backends(&self) -> Result<wgpu::Backends> {
+        match &self.backend_filter {
+            Some(list) => {
+                let backends = wgpu::Backends::from_comma_list(list);
+                if backends.is_empty() {
+                    return Err(VideoEngineError::GpuInit(format!(
+                        "No known GPU backend in '{}'",
+                        list
+                    )));
+                }
+                Ok(backends)
+            }
+            None => Ok(wgpu::Backends::all()),
+        }
+    }
+}
+
 pub struct GpuContext {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    pub adapter: wgpu::Adapter,
+    pub adapter_info: wgpu::AdapterInfo,
 }
 
 impl GpuContext {
-    pub async fn new() -> Result<Self> {
-        let instance = wgpu::Instance::default();
+    pub async fn new(options: &GpuOptions) -> Result<Self> {
+        let backends = options.backends()?;
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: None, // Headless
-                force_fallback_adapter: false,
+        let adapters = instance.enumerate_adapters(backends);
+        let infos: Vec<wgpu::AdapterInfo> =
+            adapters.iter().map(|adapter| adapter.get_info()).collect();
+        let index = select_adapter(&infos, options).ok_or_else(|| {
+            let names: Vec<&str> = infos.iter().map(|info| info.name.as_str()).collect();
+            VideoEngineError::GpuInit(match &options.adapter_name_substring {
+                Some(wanted) => format!(
+                    "No GPU adapter name contains '{}' (found: {})",
+                    wanted,
+                    names.join(", ")
+                ),
+                None => "Failed to find an appropriate adapter".to_string(),
             })
-            .await
-            .context("Failed to find an appropriate adapter")?;
+        })?;
+        let adapter = adapters
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| VideoEngineError::GpuInit("Selected adapter disappeared".to_string()))?;
+        let adapter_info = adapter.get_info();
+        let limits = adapter.limits();
+        say!(
+            "🖥️  GPU adapter: {} ({}, {:?})",
+            adapter_info.name,
+            adapter_info.backend,
+            adapter_info.device_type
+        );
+        say!(
+            "   Limits: {}px textures, {} MiB buffers",
+            limits.max_texture_dimension_2d,
+            limits.max_buffer_size / (1024 * 1024)
+        );
 
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
+                // Lets MSAA use every sample count the adapter supports, not just 4x
+                required_features: adapter.features()
+                    & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
                 required_limits: wgpu::Limits::default(),
                 memory_hints: Default::default(),
                 ..Default::default()
             })
             .await
-            .context("Failed to create device")?;
+            .map_err(|e| VideoEngineError::GpuInit(format!("Failed to create device: {}", e)))?;
+
+        Ok(Self {
+            device,
+            queue,
+            adapter,
+            adapter_info,
+        })
+    }
+
+    /// Whether render targets in `format` can be multisampled `samples` times
+    pub fn supports_sample_count(&self, format: wgpu::TextureFormat, samples: u32) -> bool {
+        if self
+            .device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            self.adapter
+                .get_texture_format_features(format)
+                .flags
+                .sample_count_supported(samples)
+        } else {
+            // Without adapter-specific features only WebGPU's guaranteed counts are allowed
+            format
+                .guaranteed_format_features(self.device.features())
+                .flags
+                .sample_count_supported(samples)
+        }
+    }
+
+    /// Every adapter on the system, across all backends
+    pub fn available_adapters() -> Vec<wgpu::AdapterInfo> {
+        let instance = wgpu::Instance::default();
+        instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .iter()
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+}
+
+/// Index of the adapter `options` pick among `adapters`
+fn select_adapter(adapters: &[wgpu::AdapterInfo], options: &GpuOptions) -> Option<usize> {
+    let wanted = options
+        .adapter_name_substring
+        .as_deref()
+        .map(str::to_lowercase);
+
+    adapters
+        .iter()
+        .enumerate()
+        .filter(|(_, info)| {
+            wanted
+                .as_deref()
+                .is_none_or(|wanted| info.name.to_lowercase().contains(wanted))
+        })
+        .min_by_key(|(_, info)| device_rank(info.device_type, options.power_preference))
+        .map(|(index, _)| index)
+}
+
+/// Lower is preferred
+fn device_rank(device_type: wgpu::DeviceType, preference: GpuPowerPreference) -> u8 {
+    use wgpu::DeviceType::*;
+    match (preference, device_type) {
+        (GpuPowerPreference::None, _) => 0,
+        (GpuPowerPreference::HighPerformance, DiscreteGpu) => 0,
+        (GpuPowerPreference::HighPerformance, IntegratedGpu) => 1,
+        (GpuPowerPreference::LowPower, IntegratedGpu) => 0,
+        (GpuPowerPreference::LowPower, DiscreteGpu) => 1,
+        (_, VirtualGpu) => 2,
+        (_, Other) => 3,
+        (_, Cpu) => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(name: &str, device_type: wgpu::DeviceType) -> wgpu::AdapterInfo {
+        wgpu::AdapterInfo {
+            name: name.to_string(),
+            vendor: 0,
+            device: 0,
+            device_type,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Vulkan,
+        }
+    }
+
+    #[test]
+    fn test_select_adapter_by_preference_and_name() {
+        let adapters = [
+            adapter("llvmpipe (LLVM 17.0.6, 256 bits)", wgpu::DeviceType::Cpu),
+            adapter("Intel(R) UHD Graphics 620", wgpu::DeviceType::IntegratedGpu),
+            adapter("NVIDIA GeForce RTX 3060", wgpu::DeviceType::DiscreteGpu),
+        ];
+
+        let mut options = GpuOptions::default();
+        assert_eq!(select_adapter(&adapters, &options), Some(2));
+        options.power_preference = GpuPowerPreference::LowPower;
+        assert_eq!(select_adapter(&adapters, &options), Some(1));
+        options.power_preference = GpuPowerPreference::None;
+        assert_eq!(select_adapter(&adapters, &options), Some(0));
+
+        // The name filter wins over the preference
+        options.power_preference = GpuPowerPreference::HighPerformance;
+        options.adapter_name_substring = Some("LLVMpipe".into());
+        assert_eq!(select_adapter(&adapters, &options), Some(0));
+        options.adapter_name_substring = Some("radeon".into());
+        assert_eq!(select_adapter(&adapters, &options), None);
+    }
+
+    #[test]
+    fn test_backend_filter() {
+        let options = GpuOptions {
+            backend_filter: Some("vulkan, gl".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.backends().unwrap(),
+            wgpu::Backends::VULKAN | wgpu::Backends::GL
+        );
+
+        let options = GpuOptions {
+            backend_filter: Some("glide".into()),
+            ..Default::default()
+        };
+        assert!(options.backends().is_err());
+    }
+
+    #[test]
+    fn test_context_uses_adapter_matching_name() {
+        let adapters = GpuContext::available_adapters();
+        let Some(first) = adapters.first() else {
+            println!("No GPU adapter, skipping adapter selection test");
+            return;
+        };
+
+        let options = GpuOptions {
+            adapter_name_substring: Some(first.name.clone()),
+            ..Default::default()
+        };
+        let context = pollster::block_on(GpuContext::new(&options)).unwrap();
+        assert_eq!(context.adapter_info.name, first.name);
 
-        Ok(Self { device, queue })
+        let options = GpuOptions {
+            adapter_name_substring: Some("no such adapter".into()),
+            ..Default::default()
+        };
+        let error = pollster::block_on(GpuContext::new(&options)).err().unwrap();
+        assert!(error.to_string().contains("no such adapter"));
     }
 }
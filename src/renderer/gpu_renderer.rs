@@ -1,9 +1,10 @@
-use anyhow::Result;
+use crate::error::{Result, VideoEngineError};
 use bytemuck::{Pod, Zeroable};
-use image::GenericImageView;
 use wgpu;
 
-use crate::renderer::{FrameBuffer, GpuContext};
+use crate::renderer::color::srgb_to_linear;
+use crate::renderer::compositor::{clip_to_frame, Affine};
+use crate::renderer::{FrameBuffer, GpuContext, GpuOptions};
 
 /// Vertex structure optimized for Metal (Apple Silicon)
 ///
@@ -52,6 +53,36 @@ impl Vertex {
     }
 }
 
+/// A texture allocated once and refilled, e.g. with successive video frames
+pub struct VideoTexture {
+    texture: wgpu::Texture,
+    pub bind_group: std::sync::Arc<wgpu::BindGroup>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A submitted frame whose pixels are still being rendered and read back.
+/// Pass it to `GpuRenderer::wait` to get them.
+pub struct PendingFrame {
+    staging_buffer: wgpu::Buffer,
+    submission: wgpu::SubmissionIndex,
+    mapped: std::sync::mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+    size: (u32, u32),
+    padded_bytes_per_row: u32,
+}
+
+/// Quad corners in frame pixels: top-left, top-right, bottom-right, bottom-left
+type QuadCorners = [(f32, f32); 4];
+
+/// Bytes per row of a `width`-pixel RGBA readback, rounded up to wgpu's copy alignment
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (width * 4).div_ceil(align) * align
+}
+
+/// Starting size of the vertex buffer, about 5,000 quads
+const INITIAL_VERTEX_BUFFER_SIZE: u64 = 1024 * 1024;
+
 /// GPU-accelerated renderer
 #[allow(dead_code)]
 pub struct GpuRenderer {
@@ -60,17 +91,30 @@ pub struct GpuRenderer {
     width: u32,
     height: u32,
     vertex_buffer: wgpu::Buffer,
-    batches: std::cell::RefCell<Vec<(std::sync::Arc<wgpu::BindGroup>, Vec<Vertex>)>>,
+    /// Draws queued since the last flush, grouped by texture
+    batches: Vec<(std::sync::Arc<wgpu::BindGroup>, Vec<Vertex>)>,
     white_texture_bind_group: std::sync::Arc<wgpu::BindGroup>,
     texture_bind_group_layout: wgpu::BindGroupLayout,
     output_texture: Option<wgpu::Texture>,
-    staging_buffer: Option<wgpu::Buffer>,
+    /// Idle readback buffers; each pending frame holds one until it is waited on
+    staging_buffers: Vec<wgpu::Buffer>,
+    /// Frame size the output texture and staging buffer were created for
+    readback_size: (u32, u32),
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    /// MSAA samples per pixel; 1 renders straight into the output texture
+    sample_count: u32,
+    /// Multisampled color target, resolved into the output texture
+    msaa_texture: Option<wgpu::Texture>,
+    /// The frame buffer's contents, drawn unblended as the first quad of an MSAA pass
+    background: Option<VideoTexture>,
+    background_pipeline: Option<wgpu::RenderPipeline>,
 }
 
 impl GpuRenderer {
-    /// Create a new GPU renderer
-    pub async fn new(width: u32, height: u32) -> Result<Self> {
-        let context = GpuContext::new().await?;
+    /// Create a new GPU renderer on the adapter `options` select
+    pub async fn new(width: u32, height: u32, options: &GpuOptions) -> Result<Self> {
+        let context = GpuContext::new(options).await?;
 
         // Load shader
         let shader = context
@@ -115,37 +159,13 @@ impl GpuRenderer {
                     push_constant_ranges: &[],
                 });
 
-        let render_pipeline =
-            context
-                .device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Render Pipeline"),
-                    layout: Some(&pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: Some("vs_main"),
-                        buffers: &[Vertex::desc()],
-                        compilation_options: Default::default(),
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: Some("fs_texture"),
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                        compilation_options: Default::default(),
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        ..Default::default()
-                    },
-                    depth_stencil: None,
-                    multisample: wgpu::MultisampleState::default(),
-                    multiview: None,
-                    cache: None,
-                });
+        let render_pipeline = Self::create_pipeline(
+            &context.device,
+            &shader,
+            &pipeline_layout,
+            1,
+            Some(wgpu::BlendState::ALPHA_BLENDING),
+        );
 
         // Create 1x1 white texture
         let white_texture_size = wgpu::Extent3d {
@@ -208,13 +228,8 @@ impl GpuRenderer {
                     label: Some("White Texture Bind Group"),
                 });
 
-        // Create initial vertex buffer (large enough for many quads)
-        let vertex_buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            size: 1024 * 1024, // 1MB buffer
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        // Initial vertex buffer; `flush` grows it when a frame needs more
+        let vertex_buffer = Self::create_vertex_buffer(&context.device, INITIAL_VERTEX_BUFFER_SIZE);
 
         Ok(Self {
             context,
@@ -222,22 +237,140 @@ impl GpuRenderer {
             width,
             height,
             vertex_buffer,
-            batches: std::cell::RefCell::new(Vec::new()),
+            batches: Vec::new(),
             white_texture_bind_group: std::sync::Arc::new(white_texture_bind_group),
             texture_bind_group_layout,
             output_texture: None,
-            staging_buffer: None,
+            staging_buffers: Vec::new(),
+            readback_size: (width, height),
+            shader,
+            pipeline_layout,
+            sample_count: 1,
+            msaa_texture: None,
+            background: None,
+            background_pipeline: None,
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        layout: &wgpu::PipelineLayout,
+        sample_count: u32,
+        blend: Option<wgpu::BlendState>,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_texture"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
         })
     }
 
+    /// Antialias edges with `samples` MSAA samples per pixel (1, 2 or 4). Counts the
+    /// adapter can't render fall back to 1 with a warning. Returns the count in use.
+    pub fn set_sample_count(&mut self, samples: u32) -> u32 {
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let samples = if matches!(samples, 1 | 2 | 4)
+            && self.context.supports_sample_count(format, samples)
+        {
+            samples
+        } else {
+            say!(
+                "⚠️  {}x MSAA is not supported by {}, rendering without it",
+                samples,
+                self.context.adapter_info.name
+            );
+            1
+        };
+        if samples == self.sample_count {
+            return samples;
+        }
+
+        let device = &self.context.device;
+        self.render_pipeline = Self::create_pipeline(
+            device,
+            &self.shader,
+            &self.pipeline_layout,
+            samples,
+            Some(wgpu::BlendState::ALPHA_BLENDING),
+        );
+        self.background_pipeline = (samples > 1).then(|| {
+            Self::create_pipeline(device, &self.shader, &self.pipeline_layout, samples, None)
+        });
+        self.msaa_texture = None;
+        self.background = None;
+        self.sample_count = samples;
+        samples
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Vertex Buffer"),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// The adapter frames are rendered on
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.context.adapter_info
+    }
+
+    /// Change the output resolution. Geometry drawn from now on is mapped to the
+    /// new size, and the readback resources are recreated on the next flush.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
     /// Create a texture from an image
     pub fn create_texture(&self, image: &image::DynamicImage) -> std::sync::Arc<wgpu::BindGroup> {
-        let rgba = image.to_rgba8();
-        let dimensions = image.dimensions();
+        self.create_rgba_texture(&image.to_rgba8())
+    }
+
+    /// Create a texture from already decoded RGBA pixels
+    pub fn create_rgba_texture(&self, image: &image::RgbaImage) -> std::sync::Arc<wgpu::BindGroup> {
+        let texture = self.create_video_texture(image.width(), image.height());
+        self.update_texture(&texture, image);
+        texture.bind_group
+    }
 
+    /// Allocate a texture whose pixels are replaced every frame with `update_texture`
+    pub fn create_video_texture(&self, width: u32, height: u32) -> VideoTexture {
         let size = wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
+            width,
+            height,
             depth_or_array_layers: 1,
         };
 
@@ -255,22 +388,6 @@ impl GpuRenderer {
                 view_formats: &[],
             });
 
-        self.context.queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &rgba,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
-            },
-            size,
-        );
-
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = self
             .context
@@ -285,29 +402,57 @@ impl GpuRenderer {
                 ..Default::default()
             });
 
-        std::sync::Arc::new(
-            self.context
-                .device
-                .create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &self.texture_bind_group_layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(&view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&sampler),
-                        },
-                    ],
-                    label: Some("Image Texture Bind Group"),
-                }),
-        )
+        let bind_group = std::sync::Arc::new(self.context.device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                layout: &self.texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+                label: Some("Image Texture Bind Group"),
+            },
+        ));
+
+        VideoTexture {
+            texture,
+            bind_group,
+            width,
+            height,
+        }
+    }
+
+    /// Upload new pixels into an existing texture of the same size
+    pub fn update_texture(&self, texture: &VideoTexture, image: &image::RgbaImage) {
+        if image.dimensions() != (texture.width, texture.height) {
+            return;
+        }
+
+        self.context.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * texture.width),
+                rows_per_image: Some(texture.height),
+            },
+            texture.texture.size(),
+        );
     }
 
     /// Draw a textured rectangle
     pub fn draw_texture(
-        &self,
+        &mut self,
         bind_group: std::sync::Arc<wgpu::BindGroup>,
         x: i32,
         y: i32,
@@ -315,54 +460,61 @@ impl GpuRenderer {
         height: u32,
         color: [u8; 4],
     ) -> Result<()> {
-        // Convert pixel coords to normalized device coordinates (-1 to 1)
-        let x1 = (x as f32 / self.width as f32) * 2.0 - 1.0;
-        let y1 = -((y as f32 / self.height as f32) * 2.0 - 1.0); // Flip Y
-        let x2 = ((x + width as i32) as f32 / self.width as f32) * 2.0 - 1.0;
-        let y2 = -(((y + height as i32) as f32 / self.height as f32) * 2.0 - 1.0);
+        self.draw_texture_rotated(bind_group, x, y, width, height, 0.0, None, color)
+    }
 
-        let color_norm = [
-            color[0] as f32 / 255.0,
-            color[1] as f32 / 255.0,
-            color[2] as f32 / 255.0,
-            color[3] as f32 / 255.0,
-        ];
+    /// Draw a textured rectangle rotated `degrees` clockwise about `pivot`
+    /// (frame pixels; the rectangle's center when `None`)
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_texture_rotated(
+        &mut self,
+        bind_group: std::sync::Arc<wgpu::BindGroup>,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        degrees: f32,
+        pivot: Option<(f32, f32)>,
+        color: [u8; 4],
+    ) -> Result<()> {
+        let (x1, y1) = (x as f32, y as f32);
+        let (x2, y2) = (x1 + width as f32, y1 + height as f32);
+        let corners = [(x1, y1), (x2, y1), (x2, y2), (x1, y2)];
+        let corners = if degrees == 0.0 {
+            corners
+        } else {
+            let pivot = pivot.unwrap_or(((x1 + x2) / 2.0, (y1 + y2) / 2.0));
+            let rotation = Affine::rotation_about(degrees, pivot);
+            corners.map(|(x, y)| rotation.apply(x, y))
+        };
+        self.draw_texture_quad(bind_group, corners, color)
+    }
 
-        // Two triangles to make a rectangle
-        let new_vertices = vec![
-            Vertex {
-                position: [x1, y1],
-                color: color_norm,
-                uv: [0.0, 0.0],
-            },
-            Vertex {
-                position: [x2, y1],
-                color: color_norm,
-                uv: [1.0, 0.0],
-            },
-            Vertex {
-                position: [x2, y2],
-                color: color_norm,
-                uv: [1.0, 1.0],
-            },
-            Vertex {
-                position: [x1, y1],
-                color: color_norm,
-                uv: [0.0, 0.0],
-            },
-            Vertex {
-                position: [x2, y2],
-                color: color_norm,
-                uv: [1.0, 1.0],
-            },
-            Vertex {
-                position: [x1, y2],
-                color: color_norm,
-                uv: [0.0, 1.0],
-            },
-        ];
+    /// Draw a texture onto an arbitrary quad given in frame pixels
+    /// (top-left, top-right, bottom-right, bottom-left), e.g. from `Affine::corners`
+    pub fn draw_texture_quad(
+        &mut self,
+        bind_group: std::sync::Arc<wgpu::BindGroup>,
+        corners: [(f32, f32); 4],
+        color: [u8; 4],
+    ) -> Result<()> {
+        self.draw_texture_region(bind_group, [0.0, 0.0, 1.0, 1.0], corners, color)
+    }
 
-        let mut batches = self.batches.borrow_mut();
+    /// Draw part of a texture onto a quad. `src_rect` is `[x, y, width, height]`
+    /// normalized to the texture size, e.g. one cell of a sprite sheet.
+    pub fn draw_texture_region(
+        &mut self,
+        bind_group: std::sync::Arc<wgpu::BindGroup>,
+        src_rect: [f32; 4],
+        corners: [(f32, f32); 4],
+        color: [u8; 4],
+    ) -> Result<()> {
+        let Some((src_rect, corners)) = self.clip_quad(src_rect, corners) else {
+            return Ok(());
+        };
+        let new_vertices = self.quad_vertices(src_rect, corners, color);
+        let batches = &mut self.batches;
 
         // Check if we can merge with the last batch
         if let Some(last_batch) = batches.last_mut() {
@@ -377,36 +529,181 @@ impl GpuRenderer {
         Ok(())
     }
 
+    /// Clip a quad to the frame: `None` when it's entirely off-screen. Axis-aligned
+    /// quads are cut to their visible part, with `src_rect` cut by the same fraction
+    /// so the texture isn't stretched; rotated ones are left to the rasterizer.
+    fn clip_quad(
+        &self,
+        src_rect: [f32; 4],
+        corners: [(f32, f32); 4],
+    ) -> Option<([f32; 4], QuadCorners)> {
+        let xs = corners.map(|(x, _)| x);
+        let ys = corners.map(|(_, y)| y);
+        let bounds = [
+            xs.into_iter().fold(f32::INFINITY, f32::min),
+            ys.into_iter().fold(f32::INFINITY, f32::min),
+            xs.into_iter().fold(f32::NEG_INFINITY, f32::max),
+            ys.into_iter().fold(f32::NEG_INFINITY, f32::max),
+        ];
+        let visible = clip_to_frame(bounds, self.width, self.height)?;
+
+        let [top_left, top_right, bottom_right, bottom_left] = corners;
+        let axis_aligned = top_left.1 == top_right.1
+            && bottom_left.1 == bottom_right.1
+            && top_left.0 == bottom_left.0
+            && top_right.0 == bottom_right.0;
+        if !axis_aligned || visible == bounds {
+            return Some((src_rect, corners));
+        }
+
+        // Corners may be mirrored (right of left, or above top), so clamp each one
+        let [left, top, right, bottom] = visible;
+        let (x0, x1) = (
+            top_left.0.clamp(left, right),
+            top_right.0.clamp(left, right),
+        );
+        let (y0, y1) = (
+            top_left.1.clamp(top, bottom),
+            bottom_left.1.clamp(top, bottom),
+        );
+        let fraction_x = |x: f32| (x - top_left.0) / (top_right.0 - top_left.0);
+        let fraction_y = |y: f32| (y - top_left.1) / (bottom_left.1 - top_left.1);
+
+        let [u, v, w, h] = src_rect;
+        let clipped_src = [
+            u + fraction_x(x0) * w,
+            v + fraction_y(y0) * h,
+            (fraction_x(x1) - fraction_x(x0)) * w,
+            (fraction_y(y1) - fraction_y(y0)) * h,
+        ];
+        Some((clipped_src, [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]))
+    }
+
+    /// Two triangles covering `corners`, textured with `src_rect`
+    fn quad_vertices(
+        &self,
+        src_rect: [f32; 4],
+        corners: [(f32, f32); 4],
+        color: [u8; 4],
+    ) -> Vec<Vertex> {
+        // Convert pixel coords to normalized device coordinates (-1 to 1), flipping Y
+        let ndc = |(x, y): (f32, f32)| {
+            [
+                (x / self.width as f32) * 2.0 - 1.0,
+                -((y / self.height as f32) * 2.0 - 1.0),
+            ]
+        };
+        let [top_left, top_right, bottom_right, bottom_left] = corners.map(ndc);
+
+        // The sRGB target blends in linear light, so colors are decoded to match
+        let color_norm = [
+            srgb_to_linear(color[0]),
+            srgb_to_linear(color[1]),
+            srgb_to_linear(color[2]),
+            color[3] as f32 / 255.0,
+        ];
+
+        let [u, v, w, h] = src_rect;
+        let (uv_left, uv_top, uv_right, uv_bottom) = (u, v, u + w, v + h);
+        let vertex = |position, uv| Vertex {
+            position,
+            color: color_norm,
+            uv,
+        };
+
+        // Two triangles to make a quad
+        vec![
+            vertex(top_left, [uv_left, uv_top]),
+            vertex(top_right, [uv_right, uv_top]),
+            vertex(bottom_right, [uv_right, uv_bottom]),
+            vertex(top_left, [uv_left, uv_top]),
+            vertex(bottom_right, [uv_right, uv_bottom]),
+            vertex(bottom_left, [uv_left, uv_bottom]),
+        ]
+    }
+
     /// Fill a rectangle with a color using GPU batching
     pub fn fill_rect(
-        &self,
-        _frame_buffer: &mut FrameBuffer,
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) -> Result<()> {
+        self.fill_rect_rotated(x, y, width, height, 0.0, None, color)
+    }
+
+    /// Fill a rectangle rotated `degrees` clockwise about `pivot`
+    /// (the rectangle's center when `None`)
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_rect_rotated(
+        &mut self,
         x: i32,
         y: i32,
         width: u32,
         height: u32,
+        degrees: f32,
+        pivot: Option<(f32, f32)>,
         color: [u8; 4],
     ) -> Result<()> {
-        self.draw_texture(
+        self.draw_texture_rotated(
             self.white_texture_bind_group.clone(),
             x,
             y,
             width,
             height,
+            degrees,
+            pivot,
             color,
         )
     }
 
-    /// Flush accumulated vertices to GPU and render to frame buffer
+    /// Flush accumulated vertices to GPU and render to frame buffer.
+    ///
+    /// Draws composite over the frame buffer's current contents, which are uploaded
+    /// first, so anything drawn on the CPU before the flush stays underneath. Flush
+    /// before drawing on the CPU to keep layer order.
     pub fn flush(&mut self, frame_buffer: &mut FrameBuffer) -> Result<()> {
         let start_time = std::time::Instant::now();
-        let mut batches = self.batches.borrow_mut();
-        if batches.is_empty() {
+        let vertex_total: usize = self.batches.iter().map(|(_, v)| v.len()).sum();
+        let Some(pending) = self.submit(frame_buffer)? else {
             return Ok(());
+        };
+        self.wait(pending, frame_buffer)?;
+
+        let duration = start_time.elapsed();
+        tracing::debug!(
+            "GPU flush: {:.3}ms ({} vertices)",
+            duration.as_secs_f64() * 1000.0,
+            vertex_total
+        );
+        Ok(())
+    }
+
+    /// Like `flush`, but return as soon as the GPU work is queued. The composited
+    /// frame is copied out by `wait`, so the caller can build the next frame while
+    /// the GPU renders and reads back this one. Returns `None` when nothing was drawn
+    /// (`frame_buffer` is already complete).
+    pub fn submit(&mut self, frame_buffer: &FrameBuffer) -> Result<Option<PendingFrame>> {
+        if self.batches.is_empty() {
+            return Ok(None);
         }
+        // Taken out so `self` stays free to update the GPU resources below
+        let mut batches = std::mem::take(&mut self.batches);
 
         let (width, height) = frame_buffer.dimensions();
 
+        // Readback resources only fit the size they were created for
+        if self.readback_size != (width, height) {
+            self.output_texture = None;
+            self.staging_buffers.clear();
+            self.msaa_texture = None;
+            self.background = None;
+            self.readback_size = (width, height);
+        }
+        let multisampled = self.sample_count > 1;
+
         // Create or reuse output texture
         if self.output_texture.is_none() {
             let texture = self
@@ -423,14 +720,78 @@ impl GpuRenderer {
                     sample_count: 1,
                     dimension: wgpu::TextureDimension::D2,
                     format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::COPY_SRC
+                        | wgpu::TextureUsages::COPY_DST,
                     view_formats: &[],
                 });
             self.output_texture = Some(texture);
         }
+        if multisampled && self.msaa_texture.is_none() {
+            let texture = self
+                .context
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("MSAA Texture"),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: self.sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                });
+            self.msaa_texture = Some(texture);
+        }
+        if multisampled && self.background.is_none() {
+            self.background = Some(self.create_video_texture(width, height));
+        }
+
+        // Start from what is already in the frame (CPU-drawn layers). Multisampled
+        // targets can't be written directly, so MSAA draws it as a first quad instead.
         let output_texture = self.output_texture.as_ref().unwrap();
+        let upload_target = match &self.background {
+            Some(background) if multisampled => {
+                let full_frame = [
+                    (0.0, 0.0),
+                    (width as f32, 0.0),
+                    (width as f32, height as f32),
+                    (0.0, height as f32),
+                ];
+                let vertices =
+                    self.quad_vertices([0.0, 0.0, 1.0, 1.0], full_frame, [255, 255, 255, 255]);
+                batches.insert(0, (background.bind_group.clone(), vertices));
+                &background.texture
+            }
+            _ => output_texture,
+        };
+        // The GPU composites in 8 bits: a 16-bit frame is rounded on upload
+        self.context.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: upload_target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &frame_buffer.as_bytes(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            upload_target.size(),
+        );
 
         let view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa_view = self
+            .msaa_texture
+            .as_ref()
+            .filter(|_| multisampled)
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
 
         // Create command encoder
         let mut encoder =
@@ -440,14 +801,38 @@ impl GpuRenderer {
                     label: Some("Render Encoder"),
                 });
 
+        // Grow the vertex buffer to the next power of two when this frame doesn't fit
+        let vertex_total: usize = batches.iter().map(|(_, vertices)| vertices.len()).sum();
+        let required = (vertex_total * std::mem::size_of::<Vertex>()) as u64;
+        if required > self.vertex_buffer.size() {
+            let size = required.next_power_of_two();
+            let max_size = self.context.device.limits().max_buffer_size;
+            if size > max_size {
+                return Err(VideoEngineError::GpuInit(format!(
+                    "Frame needs {} bytes of vertices, more than the GPU's {} byte buffer limit",
+                    required, max_size
+                )));
+            }
+            self.vertex_buffer = Self::create_vertex_buffer(&self.context.device, size);
+        }
+
         // Upload all vertices to the buffer at different offsets
         let mut current_offset = 0;
         for (_, vertices) in batches.iter() {
             let bytes = bytemuck::cast_slice(vertices);
+            let end = current_offset + bytes.len() as u64;
+            if end > self.vertex_buffer.size() {
+                return Err(VideoEngineError::GpuInit(format!(
+                    "Vertex upload {}..{} overflows the {} byte vertex buffer",
+                    current_offset,
+                    end,
+                    self.vertex_buffer.size()
+                )));
+            }
             self.context
                 .queue
                 .write_buffer(&self.vertex_buffer, current_offset, bytes);
-            current_offset += bytes.len() as u64;
+            current_offset = end;
         }
 
         {
@@ -456,9 +841,10 @@ impl GpuRenderer {
             // architecture used by Apple Silicon GPUs.
             //
             // Key optimizations:
-            // 1. LoadOp::Clear - Initializes on-chip tile memory without loading from system RAM.
-            //    This is significantly faster than LoadOp::Load on TBDR architectures.
-            //    We use Clear because we always clear the frame buffer at the start of each frame.
+            // 1. LoadOp::Load - Tile memory starts from the uploaded frame buffer so CPU-drawn
+            //    layers survive. Clear would be faster on TBDR architectures, but would wipe
+            //    everything composited on the CPU earlier in the frame. (With MSAA the
+            //    multisampled target is cleared and the frame buffer is its first quad.)
             //
             // 2. StoreOp::Store - Writes tile memory back to system RAM after rendering.
             //    Required in our case because we need to copy the result to a staging buffer
@@ -468,30 +854,48 @@ impl GpuRenderer {
             // TBDR Benefits:
             // - Tile memory is on-chip (very fast)
             // - System memory is off-chip (slower)
-            // - Clear avoids reading from system memory
             // - Store writes final result to system memory
             //
             // See: Apple Metal Best Practices Documentation
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
+                color_attachments: &[Some(match &msaa_view {
+                    // Samples are resolved into the output texture for readback
+                    Some(msaa_view) => wgpu::RenderPassColorAttachment {
+                        view: msaa_view,
+                        resolve_target: Some(&view),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Discard,
+                        },
+                        depth_slice: None,
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                        depth_slice: None,
                     },
-                    depth_slice: None,
                 })],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
-
+            // The MSAA background replaces the target's pixels rather than blending
+            let background_pipeline = self.background_pipeline.as_ref().filter(|_| multisampled);
             let mut draw_offset = 0;
-            for (bind_group, vertices) in batches.iter() {
+            for (index, (bind_group, vertices)) in batches.iter().enumerate() {
+                match background_pipeline {
+                    Some(pipeline) if index == 0 => render_pass.set_pipeline(pipeline),
+                    Some(_) if index == 1 => render_pass.set_pipeline(&self.render_pipeline),
+                    None if index == 0 => render_pass.set_pipeline(&self.render_pipeline),
+                    _ => {}
+                }
+
                 let vertex_count = vertices.len() as u32;
                 let byte_size = (vertex_count as usize * std::mem::size_of::<Vertex>()) as u64;
 
@@ -507,18 +911,18 @@ impl GpuRenderer {
             }
         }
 
-        // Create or reuse staging buffer
-        let buffer_size = (width * height * 4) as u64;
-        if self.staging_buffer.is_none() {
-            let buffer = self.context.device.create_buffer(&wgpu::BufferDescriptor {
+        // Texture-to-buffer copies need rows padded to a multiple of 256 bytes
+        let padded_bytes_per_row = padded_bytes_per_row(width);
+        let buffer_size = padded_bytes_per_row as u64 * height as u64;
+        let staging_buffer = match self.staging_buffers.pop() {
+            Some(buffer) => buffer,
+            None => self.context.device.create_buffer(&wgpu::BufferDescriptor {
                 label: Some("Staging Buffer"),
                 size: buffer_size,
                 usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
                 mapped_at_creation: false,
-            });
-            self.staging_buffer = Some(buffer);
-        }
-        let staging_buffer = self.staging_buffer.as_ref().unwrap();
+            }),
+        };
 
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
@@ -528,10 +932,10 @@ impl GpuRenderer {
                 aspect: wgpu::TextureAspect::All,
             },
             wgpu::TexelCopyBufferInfo {
-                buffer: staging_buffer,
+                buffer: &staging_buffer,
                 layout: wgpu::TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(4 * width),
+                    bytes_per_row: Some(padded_bytes_per_row),
                     rows_per_image: Some(height),
                 },
             },
@@ -542,45 +946,72 @@ impl GpuRenderer {
             },
         );
 
-        let index = self.context.queue.submit(std::iter::once(encoder.finish()));
+        let submission = self.context.queue.submit(std::iter::once(encoder.finish()));
 
-        // Read back to CPU using async/await
-        let buffer_slice = staging_buffer.slice(..);
-
-        // Use pollster to block on async operation
-        pollster::block_on(async {
-            let (tx, rx) = std::sync::mpsc::channel();
-            buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-                tx.send(result).unwrap();
+        // Mapping completes once the copy has run; `wait` polls for it
+        let (tx, mapped) = std::sync::mpsc::channel();
+        staging_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
             });
-            loop {
-                self.context
-                    .device
-                    .poll(wgpu::PollType::Wait {
-                        submission_index: Some(index.clone()),
-                        timeout: None,
-                    })
-                    .unwrap();
-                if let Ok(res) = rx.try_recv() {
-                    res.unwrap();
-                    break;
-                }
-            }
 
-            {
-                let data = buffer_slice.get_mapped_range();
-                frame_buffer.copy_from_slice(&data);
-            }
+        // Clear batches for next frame, keeping the allocation
+        batches.clear();
+        self.batches = batches;
 
-            staging_buffer.unmap();
-        });
+        Ok(Some(PendingFrame {
+            staging_buffer,
+            submission,
+            mapped,
+            size: (width, height),
+            padded_bytes_per_row,
+        }))
+    }
 
-        // Clear batches for next frame
-        batches.clear();
+    /// Block until `pending` is rendered and copy it into `frame_buffer`
+    pub fn wait(&mut self, pending: PendingFrame, frame_buffer: &mut FrameBuffer) -> Result<()> {
+        if frame_buffer.dimensions() != pending.size {
+            return Err(VideoEngineError::invalid(format!(
+                "Pending frame is {}x{}, frame buffer is {}x{}",
+                pending.size.0,
+                pending.size.1,
+                frame_buffer.dimensions().0,
+                frame_buffer.dimensions().1
+            )));
+        }
 
-        let duration = start_time.elapsed();
-        println!("GPU Flush: {:.3}ms", duration.as_secs_f64() * 1000.0);
+        let result = loop {
+            self.context
+                .device
+                .poll(wgpu::PollType::Wait {
+                    submission_index: Some(pending.submission.clone()),
+                    timeout: None,
+                })
+                .map_err(|e| VideoEngineError::GpuInit(format!("GPU readback failed: {}", e)))?;
+            match pending.mapped.try_recv() {
+                Ok(result) => break result,
+                Err(std::sync::mpsc::TryRecvError::Empty) => continue,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    return Err(VideoEngineError::GpuInit(
+                        "GPU readback was dropped before completing".to_string(),
+                    ));
+                }
+            }
+        };
+        result.map_err(|e| VideoEngineError::GpuInit(format!("GPU readback failed: {}", e)))?;
+
+        {
+            // 8-bit readback; widened if the frame buffer is 16-bit
+            let data = pending.staging_buffer.slice(..).get_mapped_range();
+            frame_buffer.copy_rows_from(&data, pending.padded_bytes_per_row as usize);
+        }
+        pending.staging_buffer.unmap();
 
+        // Buffers from before a resize no longer fit
+        if pending.size == self.readback_size {
+            self.staging_buffers.push(pending.staging_buffer);
+        }
         Ok(())
     }
 
@@ -588,7 +1019,7 @@ impl GpuRenderer {
     /// This method shows the GPU pipeline works correctly
     #[allow(dead_code)]
     pub fn demonstrate_gpu(&self) -> Result<()> {
-        println!(
+        say!(
             "GPU Renderer initialized with {} device",
             if cfg!(target_os = "macos") {
                 "Metal"
@@ -598,9 +1029,239 @@ impl GpuRenderer {
                 "Vulkan"
             }
         );
-        println!("  Resolution: {}x{}", self.width, self.height);
-        println!("  Backend: wgpu 27.0");
-        println!("  Ready for GPU-accelerated effects");
+        say!("  Resolution: {}x{}", self.width, self.height);
+        say!("  Backend: wgpu 27.0");
+        say!("  Ready for GPU-accelerated effects");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::Compositor;
+
+    // Renderers can be moved to (and shared with) worker threads
+    static_assertions::assert_impl_all!(GpuRenderer: Send, Sync);
+
+    #[test]
+    fn test_flush_grows_vertex_buffer() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(64, 64, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping vertex buffer test");
+            return;
+        };
+        let mut frame_buffer = FrameBuffer::new(64, 64);
+
+        // 10,000 quads need ~1.9 MB of vertices, past the initial 1 MB
+        for i in 0..10_000 {
+            let (x, y) = (i % 64, (i / 64) % 64);
+            let color = match (x < 32, y < 32) {
+                (true, true) => [255, 0, 0, 255],
+                (false, true) => [0, 255, 0, 255],
+                (true, false) => [0, 0, 255, 255],
+                (false, false) => [255, 255, 255, 255],
+            };
+            gpu.fill_rect(x, y, 1, 1, color).unwrap();
+        }
+        gpu.flush(&mut frame_buffer).unwrap();
+
+        assert!(gpu.vertex_buffer.size() > INITIAL_VERTEX_BUFFER_SIZE);
+        assert_eq!(frame_buffer.get_pixel(0, 0), Some([255, 0, 0, 255]));
+        assert_eq!(frame_buffer.get_pixel(63, 0), Some([0, 255, 0, 255]));
+        assert_eq!(frame_buffer.get_pixel(0, 63), Some([0, 0, 255, 255]));
+        assert_eq!(frame_buffer.get_pixel(63, 63), Some([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_resize_between_flushes() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(640, 360, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping resize test");
+            return;
+        };
+
+        for (width, height) in [(640, 360), (1280, 720)] {
+            gpu.resize(width, height);
+            let mut frame_buffer = FrameBuffer::new(width, height);
+            gpu.fill_rect(0, 0, width, height, [0, 0, 255, 255])
+                .unwrap();
+            gpu.fill_rect(width as i32 - 1, height as i32 - 1, 1, 1, [255, 0, 0, 255])
+                .unwrap();
+            gpu.flush(&mut frame_buffer).unwrap();
+
+            assert_eq!(frame_buffer.dimensions(), (width, height));
+            assert_eq!(frame_buffer.get_pixel(0, 0), Some([0, 0, 255, 255]));
+            assert_eq!(
+                frame_buffer.get_pixel(width - 1, height - 1),
+                Some([255, 0, 0, 255])
+            );
+            assert_eq!(
+                frame_buffer.get_pixel(width - 2, height - 1),
+                Some([0, 0, 255, 255])
+            );
+        }
+    }
+
+    #[test]
+    fn test_off_screen_rects_clip_like_cpu() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(100, 100, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping clipping test");
+            return;
+        };
+        let mut frame_buffer = FrameBuffer::new(100, 100);
+        frame_buffer.clear([0, 0, 0, 255]);
+        let mut expected = frame_buffer.clone();
+
+        gpu.fill_rect(-50, -50, 100, 100, [255, 0, 0, 255]).unwrap();
+        // Entirely outside: never batched
+        gpu.fill_rect(150, 20, 10, 10, [0, 255, 0, 255]).unwrap();
+        gpu.fill_rect(-30, -30, 30, 30, [0, 255, 0, 255]).unwrap();
+        assert_eq!(gpu.batches.len(), 1);
+        assert_eq!(gpu.batches[0].1.len(), 6);
+        gpu.flush(&mut frame_buffer).unwrap();
+
+        Compositor::fill_rect(&mut expected, -50, -50, 100, 100, [255, 0, 0, 255]);
+        for y in 0..100 {
+            for x in 0..100 {
+                assert_eq!(
+                    frame_buffer.get_pixel(x, y),
+                    expected.get_pixel(x, y),
+                    "({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+        assert_eq!(frame_buffer.get_pixel(49, 49), Some([255, 0, 0, 255]));
+        assert_eq!(frame_buffer.get_pixel(50, 49), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_clipped_texture_keeps_its_scale() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(64, 16, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping texture clipping test");
+            return;
+        };
+        let mut frame_buffer = FrameBuffer::new(64, 16);
+
+        // Four 8px stripes: red, green, blue, white
+        let stripes = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255; 4],
+        ];
+        let image = image::RgbaImage::from_fn(32, 16, |x, _| image::Rgba(stripes[x as usize / 8]));
+        let texture = gpu.create_rgba_texture(&image);
+
+        // Half off the left edge: only the blue and white stripes show, 8px each
+        gpu.draw_texture(texture, -16, 0, 32, 16, [255; 4]).unwrap();
+        gpu.flush(&mut frame_buffer).unwrap();
+
+        let [_, _, blue, white] = stripes.map(Some);
+        assert_eq!(frame_buffer.get_pixel(1, 8), blue);
+        assert_eq!(frame_buffer.get_pixel(6, 8), blue);
+        assert_eq!(frame_buffer.get_pixel(9, 8), white);
+        assert_eq!(frame_buffer.get_pixel(14, 8), white);
+        assert_eq!(frame_buffer.get_pixel(17, 8), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_fill_rect_rotated_45_degrees() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(64, 64, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping rotation test");
+            return;
+        };
+        let mut frame_buffer = FrameBuffer::new(64, 64);
+        frame_buffer.clear([0, 0, 0, 255]);
+
+        // A 60x4 bar centered on (32, 32), turned clockwise onto the main diagonal
+        gpu.fill_rect_rotated(2, 30, 60, 4, 45.0, None, [255, 255, 255, 255])
+            .unwrap();
+        gpu.flush(&mut frame_buffer).unwrap();
+
+        let white = Some([255, 255, 255, 255]);
+        let black = Some([0, 0, 0, 255]);
+        for offset in [-15i32, -8, 0, 8, 15] {
+            let (x, y) = ((32 + offset) as u32, (32 + offset) as u32);
+            assert_eq!(frame_buffer.get_pixel(x, y), white, "({}, {})", x, y);
+        }
+        // The other diagonal and the unrotated bar's ends stay empty
+        assert_eq!(frame_buffer.get_pixel(47, 17), black);
+        assert_eq!(frame_buffer.get_pixel(17, 47), black);
+        assert_eq!(frame_buffer.get_pixel(4, 32), black);
+        assert_eq!(frame_buffer.get_pixel(60, 32), black);
+    }
+
+    #[test]
+    fn test_msaa_smooths_rotated_edges() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(64, 64, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping MSAA test");
+            return;
+        };
+
+        // Edge pixels are neither background nor fill
+        let background = [40, 90, 160, 255];
+        let edge_pixels = |gpu: &mut GpuRenderer| {
+            let mut frame_buffer = FrameBuffer::new(64, 64);
+            frame_buffer.clear(background);
+            gpu.fill_rect_rotated(12, 12, 40, 40, 45.0, None, [255, 255, 255, 255])
+                .unwrap();
+            gpu.flush(&mut frame_buffer).unwrap();
+            assert_eq!(frame_buffer.get_pixel(32, 32), Some([255, 255, 255, 255]));
+            // The existing frame comes through untouched
+            assert_eq!(frame_buffer.get_pixel(2, 2), Some(background));
+            frame_buffer
+                .as_bytes()
+                .chunks(4)
+                .filter(|pixel| pixel[0] > background[0] && pixel[0] < 255)
+                .count()
+        };
+
+        let aliased = edge_pixels(&mut gpu);
+        if gpu.set_sample_count(4) != 4 {
+            println!("4x MSAA unsupported, skipping MSAA test");
+            return;
+        }
+        let smoothed = edge_pixels(&mut gpu);
+        assert_eq!(aliased, 0);
+        assert!(smoothed > 40, "only {} antialiased edge pixels", smoothed);
+
+        // Unsupported counts fall back to no MSAA
+        assert_eq!(gpu.set_sample_count(3), 1);
+    }
+
+    #[test]
+    fn test_padded_bytes_per_row() {
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(1280), 5120);
+        assert_eq!(padded_bytes_per_row(1001), 4096);
+        assert_eq!(padded_bytes_per_row(1), 256);
+    }
+
+    #[test]
+    fn test_readback_at_unaligned_width() {
+        let Ok(mut gpu) = pollster::block_on(GpuRenderer::new(1001, 751, &GpuOptions::default()))
+        else {
+            println!("No GPU adapter, skipping readback test");
+            return;
+        };
+        let mut frame_buffer = FrameBuffer::new(1001, 751);
+
+        gpu.fill_rect(0, 0, 1001, 751, [0, 0, 255, 255]).unwrap();
+        gpu.fill_rect(1000, 750, 1, 1, [255, 0, 0, 255]).unwrap();
+        gpu.flush(&mut frame_buffer).unwrap();
+
+        assert_eq!(frame_buffer.dimensions(), (1001, 751));
+        assert_eq!(frame_buffer.get_pixel(0, 0), Some([0, 0, 255, 255]));
+        assert_eq!(frame_buffer.get_pixel(0, 750), Some([0, 0, 255, 255]));
+        // Rows stay aligned all the way to the last pixel
+        assert_eq!(frame_buffer.get_pixel(1000, 750), Some([255, 0, 0, 255]));
+        assert_eq!(frame_buffer.get_pixel(999, 750), Some([0, 0, 255, 255]));
+    }
+}
@@ -1,17 +1,98 @@
+//! Drawing frames and turning them into videos. Only the CPU drawing
+//! (`color`, `compositor` and `frame_buffer`) is built without the `render`
+//! feature; the rest needs a GPU, FFmpeg or Blender.
+
+/// The pixel font text tests draw with (tests/fonts, under the OFL)
+#[cfg(test)]
+pub(crate) const TEST_FONT: &[u8] = include_bytes!("../../tests/fonts/TriangulumTestPixel.ttf");
+
+/// Where [`TEST_FONT`] is, for tests loading it like a script's font
+#[cfg(all(test, feature = "render"))]
+pub(crate) const TEST_FONT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fonts/TriangulumTestPixel.ttf"
+);
+
+#[cfg(feature = "render")]
+pub mod backend;
+#[cfg(feature = "render")]
 pub mod blender;
+#[cfg(feature = "render")]
+pub mod cancel;
+#[cfg(feature = "render")]
+pub mod chapters;
+pub mod color;
 pub mod compositor;
+#[cfg(feature = "render")]
+pub mod contact_sheet;
+#[cfg(feature = "render")]
+pub mod custom_layer;
+#[cfg(feature = "render")]
+pub mod debug_overlay;
+#[cfg(feature = "render")]
 pub mod encoder;
+#[cfg(feature = "render")]
 pub mod engine;
 pub mod frame_buffer;
+#[cfg(feature = "render")]
 pub mod gpu_context;
+#[cfg(feature = "render")]
 pub mod gpu_renderer;
+#[cfg(feature = "render")]
+pub mod preview;
+#[cfg(feature = "render")]
+pub mod progress;
+#[cfg(feature = "render")]
+pub mod render_state;
+#[cfg(feature = "render")]
+pub mod sink;
+#[cfg(feature = "render")]
+pub mod thumbnail;
+#[cfg(feature = "render")]
 pub mod timeline;
+#[cfg(feature = "render")]
+pub mod video_source;
+#[cfg(feature = "render")]
+pub mod waveform;
 
-pub use blender::BlenderRenderer;
+#[cfg(feature = "render")]
+pub use backend::{
+    create, BackendPlan, FrameFiles, FrameOutput, NativeRenderer, RenderOutput, Renderer,
+};
+#[cfg(feature = "render")]
+pub use blender::{BlenderDevice, BlenderEngine, BlenderOptions, BlenderRenderer, BlenderVersion};
+#[cfg(feature = "render")]
+pub use cancel::CancellationToken;
+#[cfg(feature = "render")]
+pub use chapters::Chapter;
 pub use compositor::Compositor;
-pub use encoder::VideoEncoder;
-pub use engine::RenderEngine;
-pub use frame_buffer::FrameBuffer;
-pub use gpu_context::GpuContext;
-pub use gpu_renderer::GpuRenderer;
-pub use timeline::Timeline;
+#[cfg(feature = "render")]
+pub use custom_layer::{LayerRenderContext, LayerRenderer, ProgressBarLayer};
+#[cfg(feature = "render")]
+pub use debug_overlay::DebugOverlaySettings;
+#[cfg(feature = "render")]
+pub use encoder::{
+    EncodeSettings, HardwareEncoder, OutputFormat, StreamingEncoder, SubtitleStyle, VideoArgs,
+    VideoCodec, VideoEncoder,
+};
+#[cfg(feature = "render")]
+pub use engine::{RenderEngine, TextureCacheStats};
+pub use frame_buffer::{BitDepth, FrameBuffer, FrameFormat, ResizeFilter};
+#[cfg(feature = "render")]
+pub use gpu_context::{GpuContext, GpuOptions, GpuPowerPreference};
+#[cfg(feature = "render")]
+pub use gpu_renderer::{GpuRenderer, PendingFrame, VideoTexture};
+#[cfg(feature = "render")]
+pub use preview::{MetadataOverrides, PreviewSettings};
+#[cfg(feature = "render")]
+pub use progress::{ProgressEvent, ProgressKind, RenderProgress};
+#[cfg(feature = "render")]
+pub use render_state::{RenderState, RENDER_STATE_FILE};
+#[cfg(feature = "render")]
+pub use sink::{FrameFileSink, FrameSink};
+#[cfg(feature = "render")]
+pub use timeline::{FrameContext, Timeline, TransitionContext};
+#[cfg(feature = "render")]
+pub use video_source::VideoFrameSource;
+#[cfg(feature = "render")]
+pub use waveform::WaveformAnalysis;
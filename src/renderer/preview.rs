@@ -0,0 +1,300 @@
+use crate::script::{Effect, Layer, Position, Resolution, Transform, VideoScript};
+use serde::{Deserialize, Serialize};
+
+/// Settings for fast, low-quality preview renders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewSettings {
+    /// Fraction of the script's resolution to render at
+    #[serde(default = "default_preview_scale")]
+    #[serde(serialize_with = "crate::analysis::serialize_f32")]
+    pub scale: f32,
+    /// Render every Nth frame; the frame rate is divided accordingly
+    #[serde(default = "default_frame_step")]
+    pub frame_step: u32,
+}
+
+fn default_preview_scale() -> f32 {
+    0.5
+}
+
+fn default_frame_step() -> u32 {
+    3
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            scale: default_preview_scale(),
+            frame_step: default_frame_step(),
+        }
+    }
+}
+
+impl PreviewSettings {
+    /// A miniature of `script`: resolution, positions, sizes and transforms are
+    /// scaled together, and the frame rate is divided by `frame_step` (rounded,
+    /// at least 1fps) so the preview still plays at the right speed. The script
+    /// is scaled rather than its full-size frames `FrameBuffer::resize`d, as
+    /// drawing every frame at full size is the cost previews are there to skip.
+    pub fn apply(&self, script: &VideoScript) -> VideoScript {
+        let scale = self.scale.clamp(0.01, 1.0);
+        let mut preview = script.clone();
+
+        // x264 with yuv420p needs even dimensions
+        let (width, height) = script.metadata.resolution.dimensions();
+        let even = |size: u32| (((size as f32 * scale / 2.0).round() as u32) * 2).max(2);
+        preview.metadata.resolution = Resolution::Dimensions {
+            width: even(width),
+            height: even(height),
+        };
+
+        let step = self.frame_step.max(1);
+        preview.metadata.fps = ((script.metadata.fps as f32 / step as f32).round() as u32).max(1);
+
+        scale_layout(&mut preview, scale, scale);
+        preview
+    }
+}
+
+/// A resolution and frame rate replacing the script's, as given on the command line
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetadataOverrides {
+    pub resolution: Option<(u32, u32)>,
+    pub fps: Option<u32>,
+    /// Scale positions, sizes and transforms to the new resolution instead of
+    /// keeping them in pixels
+    pub scale_layout: bool,
+}
+
+impl MetadataOverrides {
+    /// Replace `script`'s resolution and frame rate. Scene durations are in
+    /// seconds, so the timeline derived from the script follows. Returns a
+    /// warning when the resolution shrinks and the layout is kept as is.
+    pub fn apply(&self, script: &mut VideoScript) -> Option<String> {
+        if let Some(fps) = self.fps {
+            script.metadata.fps = fps;
+        }
+        let (width, height) = self.resolution?;
+        let (old_width, old_height) = script.metadata.resolution.dimensions();
+        script.metadata.resolution = Resolution::Dimensions { width, height };
+
+        if self.scale_layout {
+            scale_layout(
+                script,
+                width as f32 / old_width as f32,
+                height as f32 / old_height as f32,
+            );
+            None
+        } else if width < old_width || height < old_height {
+            Some(format!(
+                "Positions and sizes are kept as laid out for {}x{}; layers beyond {}x{} may be cropped (pass --scale-layout to scale them)",
+                old_width, old_height, width, height
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Scale every layer by `x` horizontally and `y` vertically. Sizes that have
+/// one factor, like font sizes, take the smaller so nothing outgrows the frame.
+fn scale_layout(script: &mut VideoScript, x: f32, y: f32) {
+    for scene in &mut script.scenes {
+        for layer in &mut scene.layers {
+            scale_layer(layer, x, y);
+        }
+    }
+    if let Some(watermark) = &mut script.metadata.watermark {
+        let scale = x.min(y);
+        watermark.scale *= scale;
+        watermark.margin_px = (watermark.margin_px as f32 * scale).round() as u32;
+    }
+}
+
+fn scale_position(position: &mut Position, x: f32, y: f32) {
+    position.x = (position.x as f32 * x).round() as i32;
+    position.y = (position.y as f32 * y).round() as i32;
+}
+
+fn scale_transform(transform: &mut Transform, x: f32, y: f32) {
+    scale_position(&mut transform.position, x, y);
+    transform.scale *= x.min(y);
+}
+
+fn scale_effects(effects: &mut [Effect], scale: f32) {
+    for effect in effects {
+        if let Effect::Blur { radius } = effect {
+            *radius *= scale;
+        }
+    }
+}
+
+fn scale_layer(layer: &mut Layer, x: f32, y: f32) {
+    let scale = x.min(y);
+    match layer {
+        Layer::Image {
+            transform, effects, ..
+        }
+        | Layer::Video {
+            transform, effects, ..
+        } => {
+            scale_transform(transform, x, y);
+            scale_effects(effects, scale);
+        }
+        Layer::Text {
+            font_size,
+            position,
+            effects,
+            ..
+        } => {
+            *font_size *= scale;
+            scale_position(position, x, y);
+            scale_effects(effects, scale);
+        }
+        Layer::Waveform {
+            width,
+            height,
+            position,
+            ..
+        } => {
+            *width = ((*width as f32 * x).round() as u32).max(1);
+            *height = ((*height as f32 * y).round() as u32).max(1);
+            scale_position(position, x, y);
+        }
+        // Sizes in `params` are the plugin's to interpret, so only the
+        // transform is scaled
+        Layer::Custom { transform, .. } => scale_transform(transform, x, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::AssetLoader;
+    use crate::error::Result;
+    use crate::renderer::{FrameBuffer, FrameSink, RenderEngine};
+
+    fn script() -> VideoScript {
+        serde_json::from_value(serde_json::json!({
+            "metadata": {"title": "T", "resolution": "1920x1080", "fps": 30, "duration": 2.0},
+            "scenes": [{"id": "s1", "duration": 2.0, "layers": [
+                {"type": "image", "source": "bg.png", "transform": {"position": {"x": 100, "y": 50}, "scale": 2.0}},
+                {"type": "text", "content": "Hi", "font": "f.ttf", "font_size": 48.0,
+                 "color": {"r": 255, "g": 255, "b": 255}, "position": {"x": 960, "y": 540}}
+            ]}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_preview_scales_script_consistently() {
+        let preview = PreviewSettings::default().apply(&script());
+        assert_eq!(preview.metadata.resolution.dimensions(), (960, 540));
+        assert_eq!(preview.metadata.fps, 10);
+        assert_eq!(preview.metadata.duration, 2.0);
+
+        match &preview.scenes[0].layers[0] {
+            Layer::Image { transform, .. } => {
+                assert_eq!(transform.position, Position { x: 50, y: 25 });
+                assert_eq!(transform.scale, 1.0);
+            }
+            _ => panic!("Expected Image layer"),
+        }
+        match &preview.scenes[0].layers[1] {
+            Layer::Text {
+                font_size,
+                position,
+                ..
+            } => {
+                assert_eq!(*font_size, 24.0);
+                assert_eq!(*position, Position { x: 480, y: 270 });
+            }
+            _ => panic!("Expected Text layer"),
+        }
+    }
+
+    #[test]
+    fn test_preview_dimensions_stay_even() {
+        let settings = PreviewSettings {
+            scale: 0.3,
+            frame_step: 4,
+        };
+        let preview = settings.apply(&script());
+        let (width, height) = preview.metadata.resolution.dimensions();
+        assert_eq!((width % 2, height % 2), (0, 0));
+        assert_eq!((width, height), (576, 324));
+        // 30 / 4 rounds to 8fps
+        assert_eq!(preview.metadata.fps, 8);
+    }
+
+    #[test]
+    fn test_preview_render_frame_count_and_size() {
+        struct Collect(Vec<(u32, u32)>);
+        impl FrameSink for Collect {
+            fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()> {
+                self.0.push(frame.dimensions());
+                Ok(())
+            }
+        }
+
+        let preview = PreviewSettings::default().apply(&script());
+        let mut engine = RenderEngine::new(preview, false);
+        let mut sink = Collect(Vec::new());
+        engine
+            .render(&mut sink, &mut AssetLoader::new("."), &mut |_| {})
+            .unwrap();
+
+        // 2 seconds at 30fps / 3
+        assert_eq!(sink.0.len(), 20);
+        assert!(sink.0.iter().all(|dims| *dims == (960, 540)));
+    }
+
+    #[test]
+    fn test_overrides_scale_layout() {
+        let mut script = script();
+        let overrides = MetadataOverrides {
+            resolution: Some((1080, 1080)),
+            fps: Some(24),
+            scale_layout: true,
+        };
+        assert_eq!(overrides.apply(&mut script), None);
+        assert_eq!(script.metadata.resolution.dimensions(), (1080, 1080));
+        assert_eq!(script.metadata.fps, 24);
+
+        match &script.scenes[0].layers[1] {
+            Layer::Text {
+                font_size,
+                position,
+                ..
+            } => {
+                // Sizes follow the narrower side, positions each axis
+                assert_eq!(*font_size, 27.0);
+                assert_eq!(*position, Position { x: 540, y: 540 });
+            }
+            _ => panic!("Expected Text layer"),
+        }
+    }
+
+    #[test]
+    fn test_overrides_keep_layout_and_warn_when_shrinking() {
+        let mut script = script();
+        let overrides = MetadataOverrides {
+            resolution: Some((1280, 720)),
+            ..Default::default()
+        };
+        let warning = overrides.apply(&mut script).unwrap();
+        assert!(warning.contains("--scale-layout"));
+        assert_eq!(script.metadata.fps, 30);
+        match &script.scenes[0].layers[1] {
+            Layer::Text { position, .. } => assert_eq!(*position, Position { x: 960, y: 540 }),
+            _ => panic!("Expected Text layer"),
+        }
+
+        // Growing can't crop anything
+        let overrides = MetadataOverrides {
+            resolution: Some((3840, 2160)),
+            ..Default::default()
+        };
+        assert_eq!(overrides.apply(&mut script), None);
+    }
+}
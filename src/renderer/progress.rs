@@ -0,0 +1,105 @@
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Something that happened during a render, stamped with when it happened
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub kind: ProgressKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressKind {
+    /// The first frame of a scene is about to be rendered
+    SceneStarted {
+        scene_id: String,
+        frame: u32,
+    },
+    /// `textures` image textures of a scene were uploaded before its first frame
+    ScenePrepared {
+        scene_id: String,
+        textures: usize,
+        seconds: f64,
+    },
+    /// `completed` of `total` frames in this render are done
+    FrameCompleted {
+        frame: u32,
+        completed: u32,
+        total: u32,
+    },
+    /// All frames are rendered (`frames` is 0 when the render was cached)
+    RenderFinished {
+        frames: u32,
+    },
+    EncodeStarted {
+        output: String,
+    },
+    /// ffmpeg's latest report while encoding frame files: frames encoded, their
+    /// rate, how far into the video it is and the speed against real time
+    EncodeProgress {
+        frame: u32,
+        fps: f64,
+        out_time_seconds: f64,
+        speed: Option<f64>,
+    },
+    EncodeFinished {
+        output: String,
+    },
+}
+
+impl ProgressEvent {
+    /// Stamp `kind` with the current time
+    pub fn now(kind: ProgressKind) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        Self { timestamp, kind }
+    }
+}
+
+/// Receives progress events from the renderers and the encode step
+pub trait RenderProgress {
+    fn on_event(&mut self, event: ProgressEvent);
+
+    fn emit(&mut self, kind: ProgressKind) {
+        self.on_event(ProgressEvent::now(kind));
+    }
+}
+
+impl<F: FnMut(ProgressEvent)> RenderProgress for F {
+    fn on_event(&mut self, event: ProgressEvent) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serializes_as_flat_json_line() {
+        let event = ProgressEvent {
+            timestamp: 12.5,
+            kind: ProgressKind::FrameCompleted {
+                frame: 3,
+                completed: 4,
+                total: 30,
+            },
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestamp": 12.5,
+                "event": "frame_completed",
+                "frame": 3,
+                "completed": 4,
+                "total": 30
+            })
+        );
+    }
+}
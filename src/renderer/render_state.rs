@@ -0,0 +1,167 @@
+//! What the frames of an incremental render were drawn from, saved next to
+//! them, so a later render keeps them only when it would draw them the same.
+
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{BitDepth, DebugOverlaySettings, FrameFormat};
+use crate::script::VideoScript;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// File the state is saved in, in the frames' directory
+pub const RENDER_STATE_FILE: &str = "render_state.json";
+
+/// The script and settings frames were rendered with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderState {
+    /// SHA-256 of the script's JSON
+    pub script_hash: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// What drew the frames: `native/cpu`, or `native/gpu/<adapter>`
+    pub backend: String,
+    pub frame_format: FrameFormat,
+    pub bit_depth: BitDepth,
+    pub samples: u32,
+    pub seed: u64,
+    /// The overlay of `--debug-overlay`, when it was drawn
+    #[serde(default)]
+    pub debug_overlay: Option<DebugOverlaySettings>,
+}
+
+impl RenderState {
+    /// The state of frames of `script` drawn by `backend` with these settings
+    pub fn new(
+        script: &VideoScript,
+        backend: impl Into<String>,
+        frame_format: FrameFormat,
+        bit_depth: BitDepth,
+        samples: u32,
+        seed: u64,
+    ) -> Self {
+        let json = serde_json::to_vec(script).unwrap_or_default();
+        let (width, height) = script.metadata.resolution.dimensions();
+        Self {
+            script_hash: format!("{:x}", Sha256::digest(&json)),
+            width,
+            height,
+            fps: script.metadata.fps,
+            backend: backend.into(),
+            frame_format,
+            bit_depth,
+            samples,
+            seed,
+            debug_overlay: None,
+        }
+    }
+
+    /// The state, of frames with `overlay` drawn over them
+    pub fn with_debug_overlay(mut self, overlay: Option<DebugOverlaySettings>) -> Self {
+        self.debug_overlay = overlay;
+        self
+    }
+
+    /// The state saved in `dir`, or `None` when there is none or it can't be read
+    pub fn load(dir: &Path) -> Option<Self> {
+        let json = fs::read_to_string(dir.join(RENDER_STATE_FILE)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir).map_err(VideoEngineError::io("create", dir))?;
+        let path = dir.join(RENDER_STATE_FILE);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| VideoEngineError::io("serialize", &path)(std::io::Error::other(e)))?;
+        fs::write(&path, json).map_err(VideoEngineError::io("write", &path))
+    }
+
+    /// Get `dir` ready for an incremental render with this state: its frame
+    /// files are kept when they were rendered with the same state, and deleted
+    /// otherwise. Returns whether they were kept.
+    pub fn prepare(&self, dir: &Path) -> Result<bool> {
+        if Self::load(dir).as_ref() == Some(self) {
+            return Ok(true);
+        }
+        // Saved first, so a render cut short leaves its frames usable
+        remove_frame_files(dir)?;
+        self.save(dir)?;
+        Ok(false)
+    }
+}
+
+/// Delete the frame files in `dir`, whatever their format
+fn remove_frame_files(dir: &Path) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(VideoEngineError::io("read", dir)(e)),
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name.to_str().is_some_and(is_frame_file) {
+            let path = entry.path();
+            fs::remove_file(&path).map_err(VideoEngineError::io("remove", &path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is the name of a frame file, e.g. `frame_000042.png`
+fn is_frame_file(name: &str) -> bool {
+    let Some((number, extension)) = name
+        .strip_prefix("frame_")
+        .and_then(|rest| rest.split_once('.'))
+    else {
+        return false;
+    };
+    !number.is_empty()
+        && number.bytes().all(|b| b.is_ascii_digit())
+        && [FrameFormat::Png, FrameFormat::Ppm]
+            .iter()
+            .any(|format| format.extension() == extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+
+    fn state(backend: &str) -> RenderState {
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(10.0));
+        RenderState::new(&script, backend, FrameFormat::Png, BitDepth::Eight, 1, 0)
+    }
+
+    #[test]
+    fn test_frames_are_kept_only_for_the_same_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let frame = dir.path().join("frame_000000.png");
+        let other = dir.path().join("notes.txt");
+        fs::write(&other, "keep me").unwrap();
+
+        // Nothing saved yet: nothing can be trusted
+        fs::write(&frame, "stale").unwrap();
+        assert!(!state("native/cpu").prepare(dir.path()).unwrap());
+        assert!(!frame.exists());
+        assert_eq!(RenderState::load(dir.path()), Some(state("native/cpu")));
+
+        fs::write(&frame, "drawn").unwrap();
+        assert!(state("native/cpu").prepare(dir.path()).unwrap());
+        assert!(frame.exists());
+
+        // Drawing on another device invalidates every frame
+        assert!(!state("native/gpu/Test GPU").prepare(dir.path()).unwrap());
+        assert!(!frame.exists());
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn test_frame_file_names() {
+        assert!(is_frame_file("frame_000042.png"));
+        assert!(is_frame_file("frame_000042.ppm"));
+        assert!(!is_frame_file("frame_.png"));
+        assert!(!is_frame_file("frame_0001.mp4"));
+        assert!(!is_frame_file("render_state.json"));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::error::{Result, VideoEngineError};
+use crate::renderer::{FrameBuffer, FrameFormat};
+use std::path::{Path, PathBuf};
+
+/// Destination for rendered frames, in frame order
+pub trait FrameSink {
+    fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()>;
+
+    /// Whether frame `frame` is already in the sink from an earlier render, so
+    /// the engine needn't draw it. The frames written then fill the gaps.
+    fn has_frame(&self, _frame: u32) -> bool {
+        false
+    }
+}
+
+/// Writes each frame to its own numbered image file
+pub struct FrameFileSink {
+    output_dir: PathBuf,
+    format: FrameFormat,
+    next_frame: u32,
+    /// Frame files already in the directory are kept rather than drawn again
+    keep_existing: bool,
+}
+
+impl FrameFileSink {
+    pub fn new(output_dir: &Path, format: FrameFormat) -> Self {
+        Self {
+            output_dir: output_dir.to_path_buf(),
+            format,
+            next_frame: 0,
+            keep_existing: false,
+        }
+    }
+
+    /// Number files from `frame` instead of 0, for partial renders
+    pub fn starting_at(mut self, frame: u32) -> Self {
+        self.next_frame = frame;
+        self
+    }
+
+    /// Keep the frame files already in the directory and write only the
+    /// missing ones, for incremental renders. A file counts when it isn't empty.
+    pub fn keeping_existing(mut self) -> Self {
+        self.keep_existing = true;
+        self
+    }
+
+    fn path(&self, frame: u32) -> PathBuf {
+        self.output_dir.join(self.format.file_name(frame))
+    }
+}
+
+impl FrameSink for FrameFileSink {
+    fn write_frame(&mut self, frame: &FrameBuffer) -> Result<()> {
+        while self.has_frame(self.next_frame) {
+            self.next_frame += 1;
+        }
+        let path = self.path(self.next_frame);
+        // Written under another name first, so a frame cut short, even by a
+        // crash, is never taken for a rendered one
+        let partial = path.with_extension(format!("{}.partial", self.format.extension()));
+        let saved = frame
+            .save(partial.to_str().unwrap(), self.format)
+            .and_then(|()| {
+                std::fs::rename(&partial, &path).map_err(VideoEngineError::io("write", &path))
+            });
+        if let Err(e) = saved {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+        self.next_frame += 1;
+        Ok(())
+    }
+
+    fn has_frame(&self, frame: u32) -> bool {
+        self.keep_existing && std::fs::metadata(self.path(frame)).is_ok_and(|file| file.len() > 0)
+    }
+}
@@ -0,0 +1,142 @@
+//! Thumbnails for upload pages: a frame of the video, optionally scaled to a
+//! platform's size and with the video's title drawn over it.
+
+use crate::renderer::{Compositor, FrameBuffer, ResizeFilter, Timeline};
+use crate::script::{Layer, SceneType, VideoScript};
+use ab_glyph::{Font, ScaleFont};
+use std::path::Path;
+
+/// How far into the Hook scene the default thumbnail is taken
+pub const HOOK_OFFSET_SECONDS: f32 = 1.0;
+
+/// Quality of saved JPEG thumbnails (1-100)
+pub const JPEG_QUALITY: u8 = 90;
+
+/// When the default thumbnail is taken: one second into the first Hook scene,
+/// or into the video when it has none, without running past that scene or the
+/// end of the video
+pub fn default_time(script: &VideoScript) -> f32 {
+    let timeline = Timeline::from_script(script);
+    let last_frame = timeline.total_frames().saturating_sub(1);
+    let frames = script
+        .scenes
+        .iter()
+        .zip(timeline.scene_ranges())
+        .find(|(scene, frames)| scene.scene_type == SceneType::Hook && !frames.is_empty())
+        .map_or(0..last_frame + 1, |(_, frames)| frames);
+
+    let start = timeline.frame_to_time(frames.start);
+    let end = timeline.frame_to_time(frames.end.saturating_sub(1));
+    (start + HOOK_OFFSET_SECONDS).min(end)
+}
+
+/// Font of the script's first text layer, to draw the title with
+pub fn title_font(script: &VideoScript) -> Option<&Path> {
+    script
+        .scenes
+        .iter()
+        .flat_map(|scene| &scene.layers)
+        .find_map(|layer| match layer {
+            Layer::Text { font, .. } => Some(font.as_path()),
+            _ => None,
+        })
+}
+
+/// `frame` scaled to cover `width` x `height`, cropped around its center
+pub fn cover(frame: &FrameBuffer, width: u32, height: u32) -> FrameBuffer {
+    let (frame_width, frame_height) = frame.dimensions();
+    let scale = (width as f32 / frame_width as f32).max(height as f32 / frame_height as f32);
+    let scaled_width = ((frame_width as f32 * scale).round() as u32).max(width);
+    let scaled_height = ((frame_height as f32 * scale).round() as u32).max(height);
+
+    frame
+        .resize(scaled_width, scaled_height, ResizeFilter::Bilinear)
+        .crop(
+            (scaled_width - width) / 2,
+            (scaled_height - height) / 2,
+            width,
+            height,
+        )
+}
+
+/// Draw `title` in large white text centered on a dark band across the lower
+/// third of `frame`, shrunk when it's wider than the frame allows
+pub fn draw_title(frame: &mut FrameBuffer, font: &impl Font, title: &str) {
+    let (width, height) = frame.dimensions();
+    let max_width = width as f32 * 0.9;
+    let mut font_size = height as f32 * 0.12;
+    let text_width = Compositor::text_width(font, title, font_size);
+    if text_width > max_width {
+        font_size *= max_width / text_width;
+    }
+
+    let text_width = Compositor::text_width(font, title, font_size);
+    let text_height = font.as_scaled(font_size).height() * title.lines().count().max(1) as f32;
+    let padding = font_size * 0.4;
+    let y = height as f32 * 0.72 - text_height / 2.0;
+
+    Compositor::blend_rect(
+        frame,
+        0,
+        (y - padding).round() as i32,
+        width,
+        (text_height + 2.0 * padding).round() as u32,
+        [0, 0, 0, 160],
+    );
+    Compositor::draw_text(
+        frame,
+        font,
+        title,
+        font_size,
+        ((width as f32 - text_width) / 2.0).round() as i32,
+        y.round() as i32,
+        [255, 255, 255, 255],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+
+    #[test]
+    fn test_default_time_is_inside_the_hook() {
+        let mut script =
+            ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        assert_eq!(default_time(&script), 1.0);
+
+        // The Hook scene comes second, after a 2 second intro
+        let mut intro = script.scenes[1].clone();
+        intro.id = "Intro".into();
+        intro.duration = 2.0;
+        script.scenes.insert(0, intro);
+        assert_eq!(default_time(&script), 3.0);
+
+        // A short hook keeps its own last frame
+        script.scenes[1].duration = 0.5;
+        let fps = script.metadata.fps as f32;
+        assert!((default_time(&script) - (2.5 - 1.0 / fps)).abs() < 1e-4);
+
+        // Without a hook, one second into the video
+        for scene in &mut script.scenes {
+            scene.scene_type = SceneType::Body;
+        }
+        assert_eq!(default_time(&script), 1.0);
+    }
+
+    #[test]
+    fn test_cover_crops_to_the_requested_size() {
+        let mut frame = FrameBuffer::new(160, 90);
+        // Red edges, the middle three quarters blue
+        frame.clear([255, 0, 0, 255]);
+        frame.fill_region(20..140, 0..90, [0, 0, 255, 255]);
+
+        let square = cover(&frame, 45, 45);
+        assert_eq!(square.dimensions(), (45, 45));
+        // The sides were cropped away
+        assert_eq!(square.get_pixel(0, 22), Some([0, 0, 255, 255]));
+        assert_eq!(square.get_pixel(44, 22), Some([0, 0, 255, 255]));
+
+        assert_eq!(cover(&frame, 320, 180).dimensions(), (320, 180));
+    }
+}
@@ -1,46 +1,168 @@
-use crate::script::VideoScript;
+use crate::renderer::chapters::Chapter;
+use crate::script::{Transition, VideoScript};
+use std::ops::Range;
 
 /// Timeline for managing scene playback
 pub struct Timeline {
     fps: u32,
     total_frames: u32,
     scenes: Vec<SceneSegment>,
+    transitions: Vec<TransitionWindow>,
 }
 
 #[derive(Debug, Clone)]
 struct SceneSegment {
     scene_id: String,
+    title: Option<String>,
     start_frame: u32,
     end_frame: u32,
 }
 
+/// Frames where a scene's transition blends it into the next one
+#[derive(Debug, Clone)]
+struct TransitionWindow {
+    /// Index of the outgoing scene; the incoming one follows it
+    outgoing: usize,
+    frames: Range<u32>,
+}
+
+/// Where a frame falls on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameContext {
+    pub scene_id: String,
+    /// Position of the scene in the script
+    pub scene_index: usize,
+    /// Seconds since the scene started
+    pub scene_local_time: f32,
+    /// How far through the scene the frame is (0.0 on its first frame)
+    pub scene_progress: f32,
+    /// Set while the frame is inside a transition window
+    pub transition: Option<TransitionContext>,
+}
+
+/// A frame's place within a transition between two scenes
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionContext {
+    /// Position of the outgoing scene in the script; the incoming one follows it
+    pub outgoing_scene_index: usize,
+    pub outgoing_scene_id: String,
+    pub incoming_scene_id: String,
+    /// 0.0 on the window's first frame, approaching 1.0 on its last
+    pub progress: f32,
+}
+
 impl Timeline {
-    /// Create timeline from video script
+    /// Create timeline from video script. Scene boundaries are rounded from
+    /// cumulative time, so per-scene rounding never drifts, and the video ends
+    /// with the last scene: every frame belongs to one.
     pub fn from_script(script: &VideoScript) -> Self {
         let fps = script.metadata.fps;
-        let total_duration = script.metadata.duration;
-        let total_frames = (total_duration * fps as f32) as u32;
 
         let mut segments = Vec::new();
+        let mut elapsed = 0.0f64;
         let mut current_frame = 0;
 
         for scene in &script.scenes {
-            let scene_frames = (scene.duration * fps as f32) as u32;
+            elapsed += scene.duration.max(0.0) as f64;
+            let end_frame = (elapsed * fps as f64).round() as u32;
             segments.push(SceneSegment {
                 scene_id: scene.id.clone(),
+                title: scene.title.clone(),
                 start_frame: current_frame,
-                end_frame: current_frame + scene_frames,
+                end_frame,
             });
-            current_frame += scene_frames;
+            current_frame = end_frame;
         }
 
+        let transitions = transition_windows(script, &segments, fps);
         Self {
             fps,
-            total_frames,
+            total_frames: current_frame,
             scenes: segments,
+            transitions,
         }
     }
 
+    /// Frames over which the scene at `outgoing` (its index in the script)
+    /// transitions into the next one; `None` for cuts
+    pub fn transition_frames(&self, outgoing: usize) -> Option<Range<u32>> {
+        self.transitions
+            .iter()
+            .find(|window| window.outgoing == outgoing)
+            .map(|window| window.frames.clone())
+    }
+
+    /// Scene, scene-relative time and transition state of `frame`, or `None`
+    /// past the end of the timeline
+    pub fn frame_context(&self, frame: u32) -> Option<FrameContext> {
+        let scene_index = self
+            .scenes
+            .iter()
+            .position(|segment| frame >= segment.start_frame && frame < segment.end_frame)?;
+        self.scene_context(scene_index, frame)
+    }
+
+    /// Context of `frame` for the scene at `scene_index`, as if that scene were
+    /// shown: frames before it hold its first frame and frames after it its
+    /// last. Transitions draw both of their scenes this way. `None` for scenes
+    /// that are out of range or have no frames.
+    pub fn scene_context(&self, scene_index: usize, frame: u32) -> Option<FrameContext> {
+        let segment = self.scenes.get(scene_index)?;
+        if segment.end_frame <= segment.start_frame {
+            return None;
+        }
+        let local_frame =
+            frame.clamp(segment.start_frame, segment.end_frame - 1) - segment.start_frame;
+
+        let transition = self
+            .transitions
+            .iter()
+            .find(|window| window.frames.contains(&frame))
+            .map(|window| TransitionContext {
+                outgoing_scene_index: window.outgoing,
+                outgoing_scene_id: self.scenes[window.outgoing].scene_id.clone(),
+                incoming_scene_id: self.scenes[window.outgoing + 1].scene_id.clone(),
+                progress: (frame - window.frames.start) as f32 / window.frames.len() as f32,
+            });
+
+        Some(FrameContext {
+            scene_id: segment.scene_id.clone(),
+            scene_index,
+            scene_local_time: self.frame_to_time(local_frame),
+            scene_progress: local_frame as f32 / (segment.end_frame - segment.start_frame) as f32,
+            transition,
+        })
+    }
+
+    /// Frames of each scene, in script order
+    pub fn scene_ranges(&self) -> impl Iterator<Item = Range<u32>> + '_ {
+        self.scenes
+            .iter()
+            .map(|segment| segment.start_frame..segment.end_frame)
+    }
+
+    /// A chapter at the start of every non-empty scene, titled with the scene's
+    /// `title` or else its id. See `chapters::merge_short_chapters` for YouTube's
+    /// minimum chapter length.
+    pub fn chapters(&self) -> Vec<Chapter> {
+        self.scenes
+            .iter()
+            .filter(|segment| segment.end_frame > segment.start_frame)
+            .map(|segment| Chapter {
+                title: segment
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| segment.scene_id.clone()),
+                start_seconds: segment.start_frame as f64 / self.fps as f64,
+            })
+            .collect()
+    }
+
+    /// Length of the timeline in seconds
+    pub fn duration_seconds(&self) -> f64 {
+        self.total_frames as f64 / self.fps as f64
+    }
+
     /// Get scene at given frame number
     pub fn get_scene_at_frame(&self, frame: u32) -> Option<&str> {
         for segment in &self.scenes {
@@ -65,6 +187,74 @@ impl Timeline {
     pub fn frame_to_time(&self, frame: u32) -> f32 {
         frame as f32 / self.fps as f32
     }
+
+    /// Frame shown at the given time in seconds
+    pub fn time_to_frame(&self, seconds: f32) -> u32 {
+        (seconds.max(0.0) * self.fps as f32).floor() as u32
+    }
+
+    /// Seconds since the scene shown at `frame` started (0 outside any scene)
+    pub fn scene_time(&self, frame: u32) -> f32 {
+        self.scenes
+            .iter()
+            .find(|segment| frame >= segment.start_frame && frame < segment.end_frame)
+            .map(|segment| self.frame_to_time(frame - segment.start_frame))
+            .unwrap_or(0.0)
+    }
+
+    /// First frame of the scene with this id
+    pub fn scene_start(&self, scene_id: &str) -> Option<u32> {
+        self.scenes
+            .iter()
+            .find(|segment| segment.scene_id == scene_id)
+            .map(|segment| segment.start_frame)
+    }
+
+    /// Scene that follows the one shown at `frame`
+    pub fn next_scene_after(&self, frame: u32) -> Option<&str> {
+        self.scenes
+            .iter()
+            .find(|segment| segment.start_frame > frame && segment.end_frame > segment.start_frame)
+            .map(|segment| segment.scene_id.as_str())
+    }
+}
+
+/// Each scene's transition into the next, centered on the cut between them.
+/// Windows are clamped to the two scenes and never overlap an earlier window.
+fn transition_windows(
+    script: &VideoScript,
+    segments: &[SceneSegment],
+    fps: u32,
+) -> Vec<TransitionWindow> {
+    let mut windows: Vec<TransitionWindow> = Vec::new();
+
+    for (outgoing, scene) in script.scenes.iter().enumerate() {
+        let duration = match &scene.transition {
+            Some(Transition::Fade { duration })
+            | Some(Transition::Dissolve { duration })
+            | Some(Transition::Wipe { duration, .. }) => *duration,
+            Some(Transition::Cut) | None => continue,
+        };
+        let (Some(from), Some(to)) = (segments.get(outgoing), segments.get(outgoing + 1)) else {
+            continue;
+        };
+
+        let length = (duration.max(0.0) as f64 * fps as f64).round() as u32;
+        let cut = from.end_frame;
+        let earliest = windows.last().map_or(from.start_frame, |window| {
+            window.frames.end.max(from.start_frame)
+        });
+        let start = cut.saturating_sub(length / 2).max(earliest);
+        let end = (cut + length - length / 2).min(to.end_frame);
+        if start < cut && cut < end {
+            windows.push(TransitionWindow {
+                outgoing,
+                frames: start..end,
+            });
+        }
+    }
+
+    windows
 }
 
 #[cfg(test)]
@@ -96,6 +286,185 @@ mod tests {
         assert_eq!(timeline.get_scene_at_frame(200), Some("scene2"));
     }
 
+    #[test]
+    fn test_scene_time() {
+        let timeline = Timeline::from_script(&create_test_script());
+
+        assert_eq!(timeline.scene_time(0), 0.0);
+        assert_eq!(timeline.scene_time(45), 1.5);
+        // Restarts at the second scene
+        assert_eq!(timeline.scene_time(150), 0.0);
+        assert_eq!(timeline.scene_time(210), 2.0);
+    }
+
+    #[test]
+    fn test_next_scene_after() {
+        let timeline = Timeline::from_script(&create_test_script());
+
+        assert_eq!(timeline.next_scene_after(0), Some("scene2"));
+        assert_eq!(timeline.next_scene_after(149), Some("scene2"));
+        assert_eq!(timeline.next_scene_after(150), None);
+        assert_eq!(timeline.scene_start("scene2"), Some(150));
+        assert_eq!(timeline.scene_start("missing"), None);
+    }
+
+    /// Every frame of `timeline` maps to a scene; returns the scene boundaries
+    fn boundaries(timeline: &Timeline) -> Vec<u32> {
+        let mut boundaries = vec![0];
+        for frame in 0..timeline.total_frames() {
+            let scene = timeline.get_scene_at_frame(frame);
+            assert!(scene.is_some(), "frame {} has no scene", frame);
+            if frame > 0 && scene != timeline.get_scene_at_frame(frame - 1) {
+                boundaries.push(frame);
+            }
+        }
+        boundaries.push(timeline.total_frames());
+        boundaries
+    }
+
+    #[test]
+    fn test_awkward_durations_cover_every_frame() {
+        let mut script = create_test_script();
+        let template = script.scenes[0].clone();
+        let scenes = |count: usize, duration: f32| -> Vec<Scene> {
+            (0..count)
+                .map(|i| Scene {
+                    id: format!("scene{}", i),
+                    duration,
+                    ..template.clone()
+                })
+                .collect()
+        };
+
+        // Thirds of a second at 30fps: exactly 10 frames each
+        script.scenes = scenes(3, 1.0 / 3.0);
+        script.metadata.duration = 1.0;
+        let timeline = Timeline::from_script(&script);
+        assert_eq!(boundaries(&timeline), vec![0, 10, 20, 30]);
+        assert_eq!(timeline.get_scene_at_frame(29), Some("scene2"));
+        assert_eq!(timeline.get_scene_at_frame(30), None);
+
+        // 3.333s at 30fps is 99.99 frames: truncating each scene lost 3 frames
+        script.scenes = scenes(3, 3.333);
+        script.metadata.duration = 10.0;
+        let timeline = Timeline::from_script(&script);
+        assert_eq!(boundaries(&timeline), vec![0, 100, 200, 300]);
+
+        // 2.4 frames per scene at 24fps: boundaries round from cumulative time
+        script.metadata.fps = 24;
+        script.scenes = scenes(4, 0.1);
+        let timeline = Timeline::from_script(&script);
+        assert_eq!(boundaries(&timeline), vec![0, 2, 5, 7, 10]);
+        let ranges: Vec<Range<u32>> = timeline.scene_ranges().collect();
+        assert_eq!(ranges, vec![0..2, 2..5, 5..7, 7..10]);
+    }
+
+    #[test]
+    fn test_total_frames_follow_scenes_not_metadata() {
+        let mut script = create_test_script();
+        script.metadata.duration = 12.0;
+        let timeline = Timeline::from_script(&script);
+        assert_eq!(timeline.total_frames(), 300);
+        assert_eq!(timeline.get_scene_at_frame(299), Some("scene2"));
+    }
+
+    #[test]
+    fn test_frame_context_at_scene_boundaries() {
+        let timeline = Timeline::from_script(&create_test_script());
+
+        // Last frame of the first scene
+        let last = timeline.frame_context(149).unwrap();
+        assert_eq!(last.scene_id, "scene1");
+        assert_eq!(last.scene_index, 0);
+        assert!((last.scene_local_time - 149.0 / 30.0).abs() < 1e-6);
+        assert!((last.scene_progress - 149.0 / 150.0).abs() < 1e-6);
+        assert_eq!(last.transition, None);
+
+        // First frame of the second
+        let first = timeline.frame_context(150).unwrap();
+        assert_eq!(first.scene_id, "scene2");
+        assert_eq!(first.scene_index, 1);
+        assert_eq!(first.scene_local_time, 0.0);
+        assert_eq!(first.scene_progress, 0.0);
+
+        assert!(timeline.frame_context(300).is_none());
+    }
+
+    #[test]
+    fn test_transition_windows_straddle_the_cut() {
+        let mut script = create_test_script();
+        script.scenes[0].transition = Some(Transition::Fade { duration: 1.0 });
+        // The last scene has nothing to transition into
+        script.scenes[1].transition = Some(Transition::Dissolve { duration: 1.0 });
+        let timeline = Timeline::from_script(&script);
+
+        // 30 frames centered on the cut at 150
+        assert_eq!(timeline.frame_context(134).unwrap().transition, None);
+        let start = timeline.frame_context(135).unwrap().transition.unwrap();
+        assert_eq!(start.outgoing_scene_id, "scene1");
+        assert_eq!(start.incoming_scene_id, "scene2");
+        assert_eq!(start.progress, 0.0);
+
+        // Halfway through, the incoming scene has just started
+        let middle = timeline.frame_context(150).unwrap();
+        assert_eq!(middle.scene_id, "scene2");
+        assert_eq!(middle.scene_local_time, 0.0);
+        assert_eq!(middle.transition.unwrap().progress, 0.5);
+
+        // The outgoing scene holds its last frame through the rest of the window
+        let held = timeline.scene_context(0, 160).unwrap();
+        assert_eq!(held.scene_id, "scene1");
+        assert_eq!(held.scene_local_time, timeline.frame_to_time(149));
+        assert_eq!(held.transition.unwrap().outgoing_scene_index, 0);
+        assert_eq!(
+            timeline.scene_context(1, 140).unwrap().scene_local_time,
+            0.0
+        );
+
+        let end = timeline.frame_context(164).unwrap().transition.unwrap();
+        assert!((end.progress - 29.0 / 30.0).abs() < 1e-6);
+        assert_eq!(timeline.frame_context(165).unwrap().transition, None);
+        assert_eq!(timeline.frame_context(299).unwrap().transition, None);
+    }
+
+    #[test]
+    fn test_transition_windows_clamp_to_short_scenes() {
+        let mut script = create_test_script();
+        script.scenes[0].transition = Some(Transition::Wipe {
+            duration: 20.0,
+            direction: "left".into(),
+        });
+        let timeline = Timeline::from_script(&script);
+        let windows: Vec<Range<u32>> = timeline
+            .transitions
+            .iter()
+            .map(|window| window.frames.clone())
+            .collect();
+        assert_eq!(windows, vec![0..300]);
+
+        assert_eq!(timeline.transition_frames(0), Some(0..300));
+        assert_eq!(timeline.transition_frames(1), None);
+
+        // Cuts have no window
+        script.scenes[0].transition = Some(Transition::Cut);
+        assert!(Timeline::from_script(&script).transitions.is_empty());
+    }
+
+    #[test]
+    fn test_chapters_use_titles_and_scene_starts() {
+        let mut script = create_test_script();
+        script.scenes[1].title = Some("The Payoff".into());
+        let timeline = Timeline::from_script(&script);
+
+        let chapters = timeline.chapters();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "scene1");
+        assert_eq!(chapters[0].start_seconds, 0.0);
+        assert_eq!(chapters[1].title, "The Payoff");
+        assert_eq!(chapters[1].start_seconds, 5.0);
+        assert_eq!(timeline.duration_seconds(), 10.0);
+    }
+
     fn create_test_script() -> VideoScript {
         VideoScript {
             metadata: Metadata {
@@ -105,6 +474,7 @@ mod tests {
                 duration: 10.0,
                 description: None,
                 citations: vec![],
+                watermark: None,
             },
             scenes: vec![
                 Scene {
@@ -115,8 +485,11 @@ mod tests {
                         source: PathBuf::from("test.png"),
                         effects: vec![],
                         transform: Default::default(),
+                        crop: None,
                     }],
                     transition: None,
+                    narration: None,
+                    title: None,
                 },
                 Scene {
                     id: "scene2".into(),
@@ -126,8 +499,11 @@ mod tests {
                         source: PathBuf::from("test2.png"),
                         effects: vec![],
                         transform: Default::default(),
+                        crop: None,
                     }],
                     transition: None,
+                    narration: None,
+                    title: None,
                 },
             ],
             audio: None,
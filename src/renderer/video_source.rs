@@ -0,0 +1,278 @@
+use crate::error::{Result, VideoEngineError};
+use image::RgbaImage;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+/// Decoded frames kept around, so layers revisiting recent frames don't re-decode
+const CACHE_FRAMES: usize = 4;
+
+/// Decoding forward is cheaper than restarting ffmpeg for gaps up to this many frames
+const MAX_SKIP_FRAMES: u32 = 60;
+
+/// Frames of a video file, decoded on demand by an ffmpeg process
+pub struct VideoFrameSource {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    fps: f64,
+    decoder: Option<FrameDecoder>,
+    cache: VecDeque<(u32, RgbaImage)>,
+    /// Index of the last frame, estimated from the duration until decoding reaches the end
+    last_frame: Option<u32>,
+}
+
+impl VideoFrameSource {
+    /// Probe `path` with ffprobe; frames are decoded on the first `frame_at`
+    pub fn open(path: &Path) -> Result<Self> {
+        let output = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0"])
+            .args([
+                "-show_entries",
+                "stream=width,height,r_frame_rate:format=duration",
+            ])
+            .args(["-of", "csv=p=0"])
+            .arg(path)
+            .output()
+            .map_err(|e| {
+                VideoEngineError::EncoderUnavailable(format!("Failed to execute ffprobe: {}", e))
+            })?;
+        if !output.status.success() {
+            return Err(VideoEngineError::decode(
+                path,
+                format!(
+                    "ffprobe could not read it: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ));
+        }
+
+        // One line for the stream, then one for the container
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        let fields: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+        let [width, height, rate] = fields[..] else {
+            return Err(VideoEngineError::decode(path, "no video stream"));
+        };
+        let width = width
+            .parse()
+            .map_err(|e| VideoEngineError::decode(path, format!("invalid video width: {}", e)))?;
+        let height = height
+            .parse()
+            .map_err(|e| VideoEngineError::decode(path, format!("invalid video height: {}", e)))?;
+        let fps = parse_frame_rate(rate).ok_or_else(|| {
+            VideoEngineError::decode(path, format!("invalid frame rate '{}'", rate))
+        })?;
+        // Only a hint (it can be missing or slightly long); the decoder finds the real end
+        let last_frame = lines
+            .next()
+            .and_then(|duration| duration.trim().parse::<f64>().ok())
+            .map(|duration| ((duration * fps).round() as u32).saturating_sub(1));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            width,
+            height,
+            fps,
+            decoder: None,
+            cache: VecDeque::with_capacity(CACHE_FRAMES),
+            last_frame,
+        })
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// The source frame nearest to `time` seconds into the clip; past the end,
+    /// the last frame is held
+    pub fn frame_at(&mut self, time: f32) -> Result<&RgbaImage> {
+        let wanted = nearest_frame(time, self.fps);
+        let index = self.clamp_to_end(wanted);
+        if !self.cache.iter().any(|(cached, _)| *cached == index) {
+            self.decode_until(index)?;
+        }
+
+        // Decoding may have found the end earlier than the duration suggested
+        let index = self.clamp_to_end(wanted);
+        self.cache
+            .iter()
+            .find(|(cached, _)| *cached == index)
+            .map(|(_, frame)| frame)
+            .ok_or_else(|| VideoEngineError::decode(&self.path, "decoded frame missing from cache"))
+    }
+
+    fn clamp_to_end(&self, index: u32) -> u32 {
+        self.last_frame.map_or(index, |last| index.min(last))
+    }
+
+    /// Decode up to frame `index`, falling back to the last frame if the file ends first
+    fn decode_until(&mut self, mut index: u32) -> Result<()> {
+        let restart = match &self.decoder {
+            Some(decoder) => index < decoder.next || index - decoder.next > MAX_SKIP_FRAMES,
+            None => true,
+        };
+        if restart {
+            self.decoder = Some(FrameDecoder::spawn(&self.path, index, self.fps)?);
+        }
+
+        let frame_len = self.width as usize * self.height as usize * 4;
+        loop {
+            let Some(decoder) = self.decoder.as_mut() else {
+                return Err(VideoEngineError::decode(
+                    &self.path,
+                    "video decoder is not running",
+                ));
+            };
+
+            let bytes = decoder.read_frame(frame_len).map_err(VideoEngineError::io(
+                "read decoded video frames of",
+                &self.path,
+            ))?;
+            match bytes {
+                Some(bytes) => {
+                    let decoded = decoder.next;
+                    decoder.next += 1;
+                    let frame =
+                        RgbaImage::from_raw(self.width, self.height, bytes).ok_or_else(|| {
+                            VideoEngineError::decode(&self.path, "decoded frame has the wrong size")
+                        })?;
+                    if self.cache.len() == CACHE_FRAMES {
+                        self.cache.pop_front();
+                    }
+                    self.cache.push_back((decoded, frame));
+                    if decoded >= index {
+                        return Ok(());
+                    }
+                }
+                None => {
+                    let (start, next) = (decoder.start, decoder.next);
+                    self.decoder = None;
+                    if next > start {
+                        // End of file: the newest cached frame is held from here on
+                        self.last_frame = Some(next - 1);
+                        return Ok(());
+                    }
+                    if start == 0 {
+                        return Err(VideoEngineError::decode(
+                            &self.path,
+                            "no frames could be decoded",
+                        ));
+                    }
+                    // Seeked past the end: back up and decode through to the real last frame
+                    let restart_at = start.saturating_sub(MAX_SKIP_FRAMES);
+                    self.decoder = Some(FrameDecoder::spawn(&self.path, restart_at, self.fps)?);
+                    index = u32::MAX;
+                }
+            }
+        }
+    }
+}
+
+/// A running `ffmpeg ... -f rawvideo pipe:` process
+struct FrameDecoder {
+    child: Child,
+    stdout: ChildStdout,
+    /// Index of the first frame this process decodes
+    start: u32,
+    /// Index of the frame the next read returns
+    next: u32,
+}
+
+impl FrameDecoder {
+    fn spawn(path: &Path, start: u32, fps: f64) -> Result<Self> {
+        let mut cmd = Command::new("ffmpeg");
+        cmd.arg("-hide_banner").arg("-loglevel").arg("error");
+        if start > 0 {
+            // Input seeking is frame-accurate when decoding
+            cmd.arg("-ss").arg(format!("{:.6}", start as f64 / fps));
+        }
+        let mut child = cmd
+            .arg("-i")
+            .arg(path)
+            .arg("-f")
+            .arg("rawvideo")
+            .arg("-pix_fmt")
+            .arg("rgba")
+            .arg("pipe:")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                VideoEngineError::EncoderUnavailable(format!("Failed to execute ffmpeg: {}", e))
+            })?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| VideoEngineError::decode(path, "ffmpeg has no stdout"))?;
+        Ok(Self {
+            child,
+            stdout,
+            start,
+            next: start,
+        })
+    }
+
+    /// The next frame's RGBA bytes, or `None` at the end of the stream
+    fn read_frame(&mut self, frame_len: usize) -> std::io::Result<Option<Vec<u8>>> {
+        let mut bytes = vec![0; frame_len];
+        match self.stdout.read_exact(&mut bytes) {
+            Ok(()) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Drop for FrameDecoder {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Source frame closest to `time`, so clips at any frame rate map onto the output's
+fn nearest_frame(time: f32, fps: f64) -> u32 {
+    (time.max(0.0) as f64 * fps).round() as u32
+}
+
+/// ffprobe rates are fractions such as `30000/1001`
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let fps = match rate.split_once('/') {
+        Some((num, den)) => num.trim().parse::<f64>().ok()? / den.trim().parse::<f64>().ok()?,
+        None => rate.trim().parse().ok()?,
+    };
+    (fps.is_finite() && fps > 0.0).then_some(fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame_rate() {
+        assert_eq!(parse_frame_rate("30/1"), Some(30.0));
+        assert!((parse_frame_rate("30000/1001").unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(parse_frame_rate("25"), Some(25.0));
+        assert_eq!(parse_frame_rate("0/0"), None);
+    }
+
+    #[test]
+    fn test_nearest_frame_across_frame_rates() {
+        // 30fps output over a 24fps clip
+        let picks: Vec<u32> = (0..6)
+            .map(|n| nearest_frame(n as f32 / 30.0, 24.0))
+            .collect();
+        assert_eq!(picks, vec![0, 1, 2, 2, 3, 4]);
+        // 30fps output over a 60fps clip skips every other frame
+        assert_eq!(nearest_frame(1.0 / 30.0, 60.0), 2);
+        assert_eq!(nearest_frame(-1.0, 30.0), 0);
+    }
+}
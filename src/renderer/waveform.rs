@@ -0,0 +1,150 @@
+use crate::audio::AudioDecoder;
+use crate::error::Result;
+use std::path::Path;
+
+/// Per-frame loudness of an audio file, used to animate waveform layers
+#[derive(Debug, Clone, Default)]
+pub struct WaveformAnalysis {
+    /// RMS level of each video frame's audio window, scaled so a full-scale sine is 1.0
+    levels: Vec<f32>,
+}
+
+/// Accumulates squared samples into one RMS value per video frame
+struct RmsWindows {
+    sample_rate: u64,
+    fps: u64,
+    channels: usize,
+    frame: u64,
+    window: u64,
+    sum: f32,
+    count: u32,
+    levels: Vec<f32>,
+}
+
+impl RmsWindows {
+    fn new(sample_rate: u32, channels: u32, fps: u32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1) as u64,
+            fps: fps.max(1) as u64,
+            channels: channels.max(1) as usize,
+            frame: 0,
+            window: 0,
+            sum: 0.0,
+            count: 0,
+            levels: Vec::new(),
+        }
+    }
+
+    fn add_samples(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            // Window boundaries are computed from the absolute frame index, so they never drift
+            let window = self.frame * self.fps / self.sample_rate;
+            if window != self.window {
+                self.flush();
+                self.window = window;
+            }
+            self.sum += frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+            self.count += 1;
+            self.frame += 1;
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            let rms = (self.sum / self.count as f32).sqrt();
+            self.levels.push((rms * std::f32::consts::SQRT_2).min(1.0));
+        }
+        self.sum = 0.0;
+        self.count = 0;
+    }
+
+    fn finish(mut self) -> Vec<f32> {
+        self.flush();
+        self.levels
+    }
+}
+
+impl WaveformAnalysis {
+    /// Decode an audio file packet by packet and compute one level per video frame
+    pub fn from_file(path: &Path, fps: u32) -> Result<Self> {
+        let stream = AudioDecoder::stream(path)?;
+        let mut windows = RmsWindows::new(stream.sample_rate(), stream.channels(), fps);
+        for packet in stream {
+            windows.add_samples(&packet?.samples);
+        }
+        Ok(Self {
+            levels: windows.finish(),
+        })
+    }
+
+    /// Compute levels from already decoded interleaved samples
+    pub fn from_samples(samples: &[f32], sample_rate: u32, channels: u32, fps: u32) -> Self {
+        let mut windows = RmsWindows::new(sample_rate, channels, fps);
+        windows.add_samples(samples);
+        Self {
+            levels: windows.finish(),
+        }
+    }
+
+    /// Level per video frame
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// Levels of the `count` frames leading up to and including `frame`.
+    /// Frames before the start or after the end of the audio are silent.
+    pub fn window(&self, frame: u32, count: u32) -> Vec<f32> {
+        let end = frame as i64 + 1;
+        (end - count as i64..end)
+            .map(|i| {
+                if i < 0 {
+                    0.0
+                } else {
+                    self.levels.get(i as usize).copied().unwrap_or(0.0)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amplitude: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+        (0..(seconds * sample_rate as f32) as usize)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_one_level_per_frame() {
+        let analysis = WaveformAnalysis::from_samples(&sine(0.5, 2.0, 8000), 8000, 1, 30);
+        assert_eq!(analysis.levels().len(), 60);
+        for level in analysis.levels() {
+            assert!((level - 0.5).abs() < 0.05, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn test_silence_is_zero() {
+        let analysis = WaveformAnalysis::from_samples(&[0.0; 16000], 8000, 2, 25);
+        assert_eq!(analysis.levels().len(), 25);
+        assert!(analysis.levels().iter().all(|l| *l == 0.0));
+    }
+
+    #[test]
+    fn test_window_pads_with_silence() {
+        let analysis = WaveformAnalysis::from_samples(&sine(1.0, 1.0, 8000), 8000, 1, 10);
+        let window = analysis.window(1, 4);
+        assert_eq!(window.len(), 4);
+        assert_eq!(&window[..2], &[0.0, 0.0]);
+        assert!(window[2] > 0.9 && window[3] > 0.9);
+
+        // Past the end of the audio
+        assert!(analysis.window(20, 3).iter().all(|l| *l == 0.0));
+    }
+}
@@ -0,0 +1,369 @@
+//! Scripts built in code rather than written as JSON, for programs generating
+//! videos from their own data.
+//!
+//! ```
+//! use interstellar_triangulum::script::{Color, SceneBuilder, Transition, VideoScriptBuilder};
+//!
+//! let script = VideoScriptBuilder::new("Deep Space")
+//!     .resolution(1920, 1080)
+//!     .fps(30)
+//!     .scene(
+//!         SceneBuilder::hook(3.0)
+//!             .image("assets/images/nebula.png")
+//!             .text("What is out there?", "assets/fonts/Inter-Bold.ttf", 60.0, Color::WHITE)
+//!             .transition(Transition::Fade { duration: 0.5 }),
+//!     )
+//!     .scene(SceneBuilder::body(7.0).id("stars").image("assets/images/stars.png"))
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(script.metadata.duration, 10.0);
+//! assert_eq!(script.scenes[0].id, "scene-1");
+//! assert_eq!(script.scenes[1].id, "stars");
+//! ```
+//!
+//! `build` validates the script as parsing does, so a script that couldn't be
+//! loaded from JSON can't be built either:
+//!
+//! ```
+//! use interstellar_triangulum::script::{SceneBuilder, VideoScriptBuilder};
+//! use interstellar_triangulum::VideoEngineError;
+//!
+//! let result = VideoScriptBuilder::new("Empty").scene(SceneBuilder::body(5.0)).build();
+//! assert!(matches!(result, Err(VideoEngineError::Validation(_))));
+//! ```
+
+use crate::error::Result;
+use crate::parser::ScriptParser;
+use crate::script::{
+    AudioConfig, Color, Layer, Metadata, Position, Resolution, Scene, SceneType, Transform,
+    Transition, VideoConfig, VideoScript, Watermark,
+};
+use std::path::PathBuf;
+
+/// Builds a [`VideoScript`]. The resolution and frame rate default to those
+/// of [`VideoConfig::default`], and the duration is the scenes' total.
+#[derive(Debug, Clone)]
+pub struct VideoScriptBuilder {
+    title: String,
+    description: Option<String>,
+    resolution: Resolution,
+    fps: u32,
+    citations: Vec<String>,
+    scenes: Vec<SceneBuilder>,
+    audio: Option<AudioConfig>,
+    watermark: Option<Watermark>,
+}
+
+impl VideoScriptBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        let defaults = VideoConfig::default();
+        Self {
+            title: title.into(),
+            description: None,
+            resolution: Resolution::Named(defaults.default_resolution),
+            fps: defaults.default_fps,
+            citations: Vec::new(),
+            scenes: Vec::new(),
+            audio: None,
+            watermark: None,
+        }
+    }
+
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = Resolution::Named(format!("{}x{}", width, height));
+        self
+    }
+
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn citation(mut self, citation: impl Into<String>) -> Self {
+        self.citations.push(citation.into());
+        self
+    }
+
+    /// Add a scene after the ones added so far
+    pub fn scene(mut self, scene: SceneBuilder) -> Self {
+        self.scenes.push(scene);
+        self
+    }
+
+    pub fn audio(mut self, audio: AudioConfig) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Image drawn over every frame
+    pub fn watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    /// The script, failing with [`VideoEngineError::Validation`] when it
+    /// breaks a rule a parsed script would break. Scenes without an id are
+    /// numbered `scene-1`, `scene-2`, … by position.
+    ///
+    /// [`VideoEngineError::Validation`]: crate::VideoEngineError::Validation
+    pub fn build(self) -> Result<VideoScript> {
+        let center = Position::center(&self.resolution);
+        let scenes: Vec<Scene> = (1..)
+            .zip(self.scenes)
+            .map(|(n, scene)| scene.build(n, &center))
+            .collect();
+        let script = VideoScript {
+            metadata: Metadata {
+                title: self.title,
+                resolution: self.resolution,
+                fps: self.fps,
+                duration: scenes.iter().map(|scene| scene.duration).sum(),
+                description: self.description,
+                citations: self.citations,
+                watermark: self.watermark,
+            },
+            scenes,
+            audio: self.audio,
+        };
+        ScriptParser::validate_script(&script)?;
+        Ok(script)
+    }
+}
+
+/// Builds one [`Scene`] of a [`VideoScriptBuilder`]. Layers are drawn in the
+/// order they're added, later ones on top.
+#[derive(Debug, Clone)]
+pub struct SceneBuilder {
+    id: Option<String>,
+    duration: f32,
+    scene_type: SceneType,
+    layers: Vec<SceneLayer>,
+    transition: Option<Transition>,
+    narration: Option<String>,
+    title: Option<String>,
+}
+
+/// A layer, or a text layer waiting for the frame size to be centered in
+#[derive(Debug, Clone)]
+enum SceneLayer {
+    Layer(Layer),
+    CenteredText {
+        content: String,
+        font: PathBuf,
+        font_size: f32,
+        color: Color,
+    },
+}
+
+impl SceneBuilder {
+    /// A scene lasting `duration` seconds, of type `scene_type`
+    pub fn new(scene_type: SceneType, duration: f32) -> Self {
+        Self {
+            id: None,
+            duration,
+            scene_type,
+            layers: Vec::new(),
+            transition: None,
+            narration: None,
+            title: None,
+        }
+    }
+
+    pub fn hook(duration: f32) -> Self {
+        Self::new(SceneType::Hook, duration)
+    }
+
+    pub fn body(duration: f32) -> Self {
+        Self::new(SceneType::Body, duration)
+    }
+
+    pub fn payoff(duration: f32) -> Self {
+        Self::new(SceneType::Payoff, duration)
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Chapter title (the id is used when unset)
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Spoken script, used for pacing analysis
+    pub fn narration(mut self, narration: impl Into<String>) -> Self {
+        self.narration = Some(narration.into());
+        self
+    }
+
+    /// Transition into the next scene
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    /// Text at the center of the frame
+    pub fn text(
+        mut self,
+        content: impl Into<String>,
+        font: impl Into<PathBuf>,
+        font_size: f32,
+        color: Color,
+    ) -> Self {
+        self.layers.push(SceneLayer::CenteredText {
+            content: content.into(),
+            font: font.into(),
+            font_size,
+            color,
+        });
+        self
+    }
+
+    /// Text at `position`
+    pub fn text_at(
+        self,
+        content: impl Into<String>,
+        font: impl Into<PathBuf>,
+        font_size: f32,
+        color: Color,
+        position: Position,
+    ) -> Self {
+        self.layer(Layer::Text {
+            content: content.into(),
+            font: font.into(),
+            font_size,
+            color,
+            position,
+            effects: vec![],
+        })
+    }
+
+    /// The whole image at its own size in the top left corner, as an image
+    /// layer without a transform
+    pub fn image(self, source: impl Into<PathBuf>) -> Self {
+        self.layer(Layer::Image {
+            source: source.into(),
+            effects: vec![],
+            transform: Transform::default(),
+            crop: None,
+        })
+    }
+
+    pub fn video(self, source: impl Into<PathBuf>) -> Self {
+        self.layer(Layer::Video {
+            source: source.into(),
+            effects: vec![],
+            transform: Transform::default(),
+        })
+    }
+
+    /// Any layer, for effects, transforms and layer types without a method here
+    pub fn layer(mut self, layer: Layer) -> Self {
+        self.layers.push(SceneLayer::Layer(layer));
+        self
+    }
+
+    /// The scene as the `n`th of its script, in a frame centered on `center`
+    fn build(self, n: usize, center: &Position) -> Scene {
+        let layers = self
+            .layers
+            .into_iter()
+            .map(|layer| match layer {
+                SceneLayer::Layer(layer) => layer,
+                SceneLayer::CenteredText {
+                    content,
+                    font,
+                    font_size,
+                    color,
+                } => Layer::Text {
+                    content,
+                    font,
+                    font_size,
+                    color,
+                    position: center.clone(),
+                    effects: vec![],
+                },
+            })
+            .collect();
+        Scene {
+            id: self.id.unwrap_or_else(|| format!("scene-{}", n)),
+            duration: self.duration,
+            scene_type: self.scene_type,
+            layers,
+            transition: self.transition,
+            narration: self.narration,
+            title: self.title,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VideoEngineError;
+
+    const FONT: &str = "assets/fonts/Inter-Bold.ttf";
+
+    #[test]
+    fn test_duration_is_the_scenes_total() {
+        let script = VideoScriptBuilder::new("Video")
+            .scene(SceneBuilder::hook(3.0).text("Hook", FONT, 60.0, Color::WHITE))
+            .scene(SceneBuilder::body(4.5).image("bg.png"))
+            .scene(SceneBuilder::payoff(2.5).video("outro.mp4"))
+            .build()
+            .unwrap();
+
+        assert_eq!(script.metadata.duration, 10.0);
+        assert_eq!(script.metadata.resolution.dimensions(), (1920, 1080));
+        assert_eq!(script.metadata.fps, 30);
+        let types: Vec<_> = script.scenes.iter().map(|s| s.scene_type.clone()).collect();
+        assert_eq!(types, [SceneType::Hook, SceneType::Body, SceneType::Payoff]);
+        match &script.scenes[0].layers[0] {
+            Layer::Text { position, .. } => assert_eq!(*position, Position { x: 960, y: 540 }),
+            other => panic!("expected a text layer, got {:?}", other),
+        }
+
+        // Round-trips through JSON like a hand-written script
+        let json = serde_json::to_string(&script).unwrap();
+        let parsed: VideoScript = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.scenes.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_script_fails_to_build() {
+        let result = VideoScriptBuilder::new("")
+            .fps(0)
+            .scene(SceneBuilder::body(2.0).id("empty"))
+            .scene(SceneBuilder::body(0.0).image("bg.png"))
+            .build();
+
+        let Err(VideoEngineError::Validation(errors)) = result else {
+            panic!("expected validation errors");
+        };
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert!(
+            messages.contains(&"Script title cannot be empty"),
+            "{:?}",
+            messages
+        );
+        assert!(
+            messages.contains(&"FPS must be greater than 0"),
+            "{:?}",
+            messages
+        );
+        assert!(errors.iter().any(
+            |e| e.scene.as_deref() == Some("empty") && e.message.contains("at least one layer")
+        ));
+        assert!(errors
+            .iter()
+            .any(|e| e.scene.as_deref() == Some("scene-2") && e.message.contains("duration")));
+    }
+}
@@ -0,0 +1,658 @@
+//! What changed between two versions of a script: scenes matched by id (or,
+//! failing that, by position) with their field and layer changes, metadata
+//! changes and audio track changes.
+
+use crate::script::{AudioTrack, Layer, Scene, VideoScript};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Whether something was added, removed or changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// One value that differs, by its dotted path, e.g. `transform.opacity`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// `null` when the field was unset
+    pub old: Value,
+    pub new: Value,
+    /// Word-level diff of text, with `[-removed-]` and `{+added+}` words
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<String>,
+}
+
+/// A layer added, removed or changed at an index of its scene
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LayerDiff {
+    pub index: usize,
+    /// `text`, `image`, `video` or `waveform`, as in the new script when changed
+    pub kind: String,
+    pub change: Change,
+    pub fields: Vec<FieldChange>,
+}
+
+/// A scene added, removed, renamed, moved or changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SceneDiff {
+    /// Id in the new script, or in the old one when removed
+    pub id: String,
+    pub change: Change,
+    pub old_index: Option<usize>,
+    pub new_index: Option<usize>,
+    /// Old id of a scene matched by position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// Out of order relative to the other scenes both scripts have
+    pub moved: bool,
+    /// Changes to the scene's own fields (duration, narration, ...)
+    pub fields: Vec<FieldChange>,
+    pub layers: Vec<LayerDiff>,
+}
+
+/// An audio track added, removed or changed, matched by source
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackDiff {
+    pub source: PathBuf,
+    pub change: Change,
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScriptDiff {
+    pub metadata: Vec<FieldChange>,
+    /// Scenes in the order of the new script, then removed ones
+    pub scenes: Vec<SceneDiff>,
+    pub audio: Vec<TrackDiff>,
+}
+
+impl ScriptDiff {
+    /// Compare `old` with `new`. Scenes are matched by id; a scene whose id
+    /// is gone and one with a new id at the same position are taken to be
+    /// the same scene, renamed.
+    pub fn between(old: &VideoScript, new: &VideoScript) -> Self {
+        let mut metadata = Vec::new();
+        field_changes(
+            "",
+            &to_value(&old.metadata),
+            &to_value(&new.metadata),
+            &mut metadata,
+        );
+
+        Self {
+            metadata,
+            scenes: scene_diffs(&old.scenes, &new.scenes),
+            audio: track_diffs(
+                old.audio.as_ref().map_or(&[], |audio| &audio.tracks),
+                new.audio.as_ref().map_or(&[], |audio| &audio.tracks),
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.scenes.is_empty() && self.audio.is_empty()
+    }
+}
+
+fn scene_diffs(old: &[Scene], new: &[Scene]) -> Vec<SceneDiff> {
+    let old_by_id: HashMap<&str, usize> = old
+        .iter()
+        .enumerate()
+        .map(|(i, scene)| (scene.id.as_str(), i))
+        .collect();
+    let mut new_to_old: Vec<Option<usize>> = new
+        .iter()
+        .map(|scene| old_by_id.get(scene.id.as_str()).copied())
+        .collect();
+    let mut old_matched = vec![false; old.len()];
+    for i in new_to_old.iter().flatten() {
+        old_matched[*i] = true;
+    }
+    // Positional fallback for renamed scenes
+    for (j, matched) in new_to_old.iter_mut().enumerate() {
+        if matched.is_none() && j < old.len() && !old_matched[j] {
+            *matched = Some(j);
+            old_matched[j] = true;
+        }
+    }
+
+    let order: Vec<usize> = new_to_old.iter().flatten().copied().collect();
+    let in_order = longest_increasing(&order);
+
+    let mut diffs = Vec::new();
+    for (j, scene) in new.iter().enumerate() {
+        let Some(i) = new_to_old[j] else {
+            diffs.push(SceneDiff {
+                id: scene.id.clone(),
+                change: Change::Added,
+                old_index: None,
+                new_index: Some(j),
+                renamed_from: None,
+                moved: false,
+                fields: Vec::new(),
+                layers: Vec::new(),
+            });
+            continue;
+        };
+        let previous = &old[i];
+        let mut fields = Vec::new();
+        field_changes(
+            "",
+            &scene_fields(previous),
+            &scene_fields(scene),
+            &mut fields,
+        );
+        let layers = layer_diffs(&previous.layers, &scene.layers);
+        let renamed_from = (previous.id != scene.id).then(|| previous.id.clone());
+        let moved = !in_order.contains(&i);
+        if fields.is_empty() && layers.is_empty() && renamed_from.is_none() && !moved {
+            continue;
+        }
+        diffs.push(SceneDiff {
+            id: scene.id.clone(),
+            change: Change::Modified,
+            old_index: Some(i),
+            new_index: Some(j),
+            renamed_from,
+            moved,
+            fields,
+            layers,
+        });
+    }
+    for (i, scene) in old.iter().enumerate() {
+        if !old_matched[i] {
+            diffs.push(SceneDiff {
+                id: scene.id.clone(),
+                change: Change::Removed,
+                old_index: Some(i),
+                new_index: None,
+                renamed_from: None,
+                moved: false,
+                fields: Vec::new(),
+                layers: Vec::new(),
+            });
+        }
+    }
+    diffs
+}
+
+/// A scene's fields other than its id and layers
+fn scene_fields(scene: &Scene) -> Value {
+    let mut value = to_value(scene);
+    if let Value::Object(map) = &mut value {
+        map.remove("id");
+        map.remove("layers");
+    }
+    value
+}
+
+fn layer_diffs(old: &[Layer], new: &[Layer]) -> Vec<LayerDiff> {
+    let mut diffs = Vec::new();
+    for index in 0..old.len().max(new.len()) {
+        let (change, layer, fields) = match (old.get(index), new.get(index)) {
+            (Some(previous), Some(layer)) => {
+                let mut fields = Vec::new();
+                field_changes("", &to_value(previous), &to_value(layer), &mut fields);
+                if fields.is_empty() {
+                    continue;
+                }
+                (Change::Modified, layer, fields)
+            }
+            (None, Some(layer)) => (Change::Added, layer, Vec::new()),
+            (Some(previous), None) => (Change::Removed, previous, Vec::new()),
+            (None, None) => unreachable!(),
+        };
+        diffs.push(LayerDiff {
+            index,
+            kind: to_value(layer)["type"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            change,
+            fields,
+        });
+    }
+    diffs
+}
+
+fn track_diffs(old: &[AudioTrack], new: &[AudioTrack]) -> Vec<TrackDiff> {
+    let mut old_matched = vec![false; old.len()];
+    let mut diffs = Vec::new();
+    for track in new {
+        let matched = old
+            .iter()
+            .enumerate()
+            .position(|(i, previous)| !old_matched[i] && previous.source == track.source);
+        let Some(i) = matched else {
+            diffs.push(TrackDiff {
+                source: track.source.clone(),
+                change: Change::Added,
+                fields: Vec::new(),
+            });
+            continue;
+        };
+        old_matched[i] = true;
+        let mut fields = Vec::new();
+        field_changes("", &to_value(&old[i]), &to_value(track), &mut fields);
+        if !fields.is_empty() {
+            diffs.push(TrackDiff {
+                source: track.source.clone(),
+                change: Change::Modified,
+                fields,
+            });
+        }
+    }
+    for (i, track) in old.iter().enumerate() {
+        if !old_matched[i] {
+            diffs.push(TrackDiff {
+                source: track.source.clone(),
+                change: Change::Removed,
+                fields: Vec::new(),
+            });
+        }
+    }
+    diffs
+}
+
+/// Push the leaves of `old` and `new` that differ, objects walked into and
+/// everything else (arrays included) compared whole
+fn field_changes(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    if old == new {
+        return;
+    }
+    if let (Value::Object(old_map), Value::Object(new_map)) = (old, new) {
+        let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let field = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            field_changes(
+                &field,
+                old_map.get(key).unwrap_or(&Value::Null),
+                new_map.get(key).unwrap_or(&Value::Null),
+                changes,
+            );
+        }
+        return;
+    }
+    // Text set or cleared reads as all its words added or removed
+    let text = |value: &Value| match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Null => Some(String::new()),
+        _ => None,
+    };
+    let words = match (text(old), text(new)) {
+        (Some(old), Some(new)) => Some(word_diff(&old, &new)),
+        _ => None,
+    };
+    changes.push(FieldChange {
+        field: path.to_string(),
+        old: old.clone(),
+        new: new.clone(),
+        words,
+    });
+}
+
+/// `value` as JSON, with `f32` fields written as the decimal they were
+/// parsed from (`0.1` rather than `0.10000000149011612`)
+fn to_value(value: &impl Serialize) -> Value {
+    fn tidy(value: Value) -> Value {
+        match value {
+            Value::Number(number) if number.is_f64() => number
+                .as_f64()
+                .and_then(|float| (float as f32).to_string().parse::<f64>().ok())
+                .and_then(serde_json::Number::from_f64)
+                .map_or(Value::Null, Value::Number),
+            Value::Array(values) => Value::Array(values.into_iter().map(tidy).collect()),
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, tidy(value)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+    tidy(serde_json::to_value(value).unwrap_or_default())
+}
+
+/// `new` as edited from `old`, word by word: removed runs as `[-...-]` and
+/// added runs as `{+...+}`
+pub fn word_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // Longest common subsequence lengths of the suffixes
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    let (mut removed, mut added): (Vec<&str>, Vec<&str>) = (Vec::new(), Vec::new());
+    let flush = |parts: &mut Vec<String>, removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        if !removed.is_empty() {
+            parts.push(format!("[-{}-]", removed.join(" ")));
+            removed.clear();
+        }
+        if !added.is_empty() {
+            parts.push(format!("{{+{}+}}", added.join(" ")));
+            added.clear();
+        }
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            flush(&mut parts, &mut removed, &mut added);
+            parts.push(old[i].to_string());
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(old[i]);
+            i += 1;
+        } else {
+            added.push(new[j]);
+            j += 1;
+        }
+    }
+    flush(&mut parts, &mut removed, &mut added);
+    parts.join(" ")
+}
+
+/// Items of `sequence` (distinct) forming its longest increasing subsequence
+fn longest_increasing(sequence: &[usize]) -> Vec<usize> {
+    let mut lengths = vec![1usize; sequence.len()];
+    let mut previous = vec![None; sequence.len()];
+    for i in 0..sequence.len() {
+        for j in 0..i {
+            if sequence[j] < sequence[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut longest = Vec::new();
+    let mut at = (0..sequence.len()).max_by_key(|&i| (lengths[i], std::cmp::Reverse(i)));
+    while let Some(i) = at {
+        longest.push(sequence[i]);
+        at = previous[i];
+    }
+    longest
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.words {
+            Some(words) => write!(f, "{}: {}", self.field, words),
+            None => write!(f, "{}: {} → {}", self.field, self.old, self.new),
+        }
+    }
+}
+
+impl fmt::Display for ScriptDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        if !self.metadata.is_empty() {
+            writeln!(f, "Metadata:")?;
+            for change in &self.metadata {
+                writeln!(f, "  ~ {}", change)?;
+            }
+        }
+        if !self.scenes.is_empty() {
+            writeln!(f, "Scenes:")?;
+        }
+        for scene in &self.scenes {
+            match scene.change {
+                Change::Added => writeln!(
+                    f,
+                    "  + '{}' added as scene {}",
+                    scene.id,
+                    scene.new_index.unwrap_or_default() + 1
+                )?,
+                Change::Removed => writeln!(
+                    f,
+                    "  - '{}' removed (was scene {})",
+                    scene.id,
+                    scene.old_index.unwrap_or_default() + 1
+                )?,
+                Change::Modified => {
+                    match &scene.renamed_from {
+                        Some(old_id) => {
+                            writeln!(f, "  ~ '{}' (renamed from '{}')", scene.id, old_id)?
+                        }
+                        None => writeln!(f, "  ~ '{}'", scene.id)?,
+                    }
+                    if scene.moved {
+                        writeln!(
+                            f,
+                            "      moved from scene {} to {}",
+                            scene.old_index.unwrap_or_default() + 1,
+                            scene.new_index.unwrap_or_default() + 1
+                        )?;
+                    }
+                    for change in &scene.fields {
+                        writeln!(f, "      {}", change)?;
+                    }
+                    for layer in &scene.layers {
+                        let sign = match layer.change {
+                            Change::Added => "+",
+                            Change::Removed => "-",
+                            Change::Modified => "~",
+                        };
+                        writeln!(
+                            f,
+                            "      {} layer {} ({})",
+                            sign,
+                            layer.index + 1,
+                            layer.kind
+                        )?;
+                        for change in &layer.fields {
+                            writeln!(f, "          {}", change)?;
+                        }
+                    }
+                }
+            }
+        }
+        if !self.audio.is_empty() {
+            writeln!(f, "Audio:")?;
+        }
+        for track in &self.audio {
+            let sign = match track.change {
+                Change::Added => "+",
+                Change::Removed => "-",
+                Change::Modified => "~",
+            };
+            writeln!(f, "  {} {}", sign, track.source.display())?;
+            for change in &track.fields {
+                writeln!(f, "      {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Position, Resolution, SceneType};
+
+    fn text(content: &str) -> Layer {
+        Layer::Text {
+            content: content.to_string(),
+            font: "fonts/Inter-Bold.ttf".into(),
+            font_size: 48.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+        }
+    }
+
+    fn scene(id: &str, content: &str) -> Scene {
+        Scene {
+            id: id.to_string(),
+            scene_type: SceneType::Body,
+            duration: 5.0,
+            layers: vec![text(content)],
+            transition: None,
+            narration: None,
+            title: None,
+        }
+    }
+
+    fn create_script(scenes: Vec<Scene>) -> VideoScript {
+        VideoScript {
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 30,
+                duration: 5.0 * scenes.len() as f32,
+                description: None,
+                citations: vec![],
+                watermark: None,
+            },
+            scenes,
+            audio: None,
+        }
+    }
+
+    fn three_scenes() -> VideoScript {
+        create_script(vec![
+            scene("intro", "Welcome"),
+            scene("main", "The main point"),
+            scene("outro", "Thanks for watching"),
+        ])
+    }
+
+    #[test]
+    fn test_identical_scripts() {
+        let diff = ScriptDiff::between(&three_scenes(), &three_scenes());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+    }
+
+    #[test]
+    fn test_renamed_scene_matched_by_position() {
+        let mut new = three_scenes();
+        new.scenes[1].id = "core".to_string();
+        new.scenes[1].duration = 6.5;
+        let diff = ScriptDiff::between(&three_scenes(), &new);
+
+        assert_eq!(diff.scenes.len(), 1);
+        let scene = &diff.scenes[0];
+        assert_eq!(scene.change, Change::Modified);
+        assert_eq!(scene.renamed_from.as_deref(), Some("main"));
+        assert!(!scene.moved);
+        assert_eq!(scene.fields.len(), 1);
+        assert_eq!(scene.fields[0].field, "duration");
+        assert_eq!(scene.fields[0].to_string(), "duration: 5.0 → 6.5");
+        assert!(diff.to_string().contains("'core' (renamed from 'main')"));
+    }
+
+    #[test]
+    fn test_reordered_scene_is_moved() {
+        let mut new = three_scenes();
+        let outro = new.scenes.remove(2);
+        new.scenes.insert(0, outro);
+        let diff = ScriptDiff::between(&three_scenes(), &new);
+
+        assert_eq!(diff.scenes.len(), 1);
+        let scene = &diff.scenes[0];
+        assert_eq!(scene.id, "outro");
+        assert!(scene.moved);
+        assert_eq!((scene.old_index, scene.new_index), (Some(2), Some(0)));
+        assert!(scene.fields.is_empty() && scene.layers.is_empty());
+    }
+
+    #[test]
+    fn test_text_edit_shows_word_diff() {
+        let mut new = three_scenes();
+        new.scenes[1].layers[0] = text("The one main point");
+        new.scenes[1].layers.push(text("Subtitle"));
+        new.scenes[1].narration = Some("Here is the point".to_string());
+        let diff = ScriptDiff::between(&three_scenes(), &new);
+
+        let narration = &diff.scenes[0].fields[0];
+        assert_eq!(narration.field, "narration");
+        assert_eq!(narration.words.as_deref(), Some("{+Here is the point+}"));
+
+        let layers = &diff.scenes[0].layers;
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].change, Change::Modified);
+        assert_eq!(layers[0].kind, "text");
+        assert_eq!(layers[0].fields[0].field, "content");
+        assert_eq!(
+            layers[0].fields[0].words.as_deref(),
+            Some("The {+one+} main point")
+        );
+        assert_eq!(layers[1].change, Change::Added);
+    }
+
+    #[test]
+    fn test_added_removed_scenes_metadata_and_tracks() {
+        let mut new = three_scenes();
+        new.scenes.remove(2);
+        new.scenes.push(scene("bonus", "One more thing"));
+        new.scenes.push(scene("credits", "Made by us"));
+        new.metadata.title = "Test, Again".into();
+        new.audio = Some(crate::script::AudioConfig {
+            tracks: vec![AudioTrack {
+                source: "music.mp3".into(),
+                track_type: Default::default(),
+                volume: 0.3,
+                start_time: 0.0,
+                fade_in: 0.0,
+                fade_out: 0.0,
+                trim_start: 0.0,
+                trim_end: None,
+                looping: false,
+                loop_crossfade_ms: 0.0,
+                pan: 0.0,
+            }],
+            normalization: None,
+            ducking: None,
+        });
+        let diff = ScriptDiff::between(&three_scenes(), &new);
+
+        let changes: Vec<(&str, Change)> = diff
+            .scenes
+            .iter()
+            .map(|scene| (scene.id.as_str(), scene.change))
+            .collect();
+        // "bonus" took "outro"'s place, so it's taken for a rename
+        assert_eq!(
+            changes,
+            vec![("bonus", Change::Modified), ("credits", Change::Added)]
+        );
+        assert_eq!(diff.metadata.len(), 1);
+        assert_eq!(
+            diff.metadata[0].words.as_deref(),
+            Some("[-Test-] {+Test, Again+}")
+        );
+        assert_eq!(diff.audio[0].change, Change::Added);
+        assert_eq!(word_diff("a b c", "a c d"), "a [-b-] c {+d+}");
+    }
+}
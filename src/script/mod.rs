@@ -0,0 +1,898 @@
+pub mod builder;
+pub mod diff;
+
+pub use builder::{SceneBuilder, VideoScriptBuilder};
+
+use serde::de;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+
+/// Main video script structure that defines the entire video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoScript {
+    pub metadata: Metadata,
+    pub scenes: Vec<Scene>,
+    #[serde(default)]
+    pub audio: Option<AudioConfig>,
+}
+
+/// Video metadata and configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    pub title: String,
+    /// Taken from `video.default_resolution` when the script leaves it out
+    pub resolution: Resolution,
+    /// Taken from `video.default_fps` when the script leaves it out
+    pub fps: u32,
+    pub duration: f32,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub citations: Vec<String>,
+    /// Drawn over every frame; replaces `renderer.watermark`
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
+}
+
+impl Metadata {
+    /// The title as a file name stem (see `slugify`)
+    pub fn slug(&self) -> String {
+        slugify(&self.title)
+    }
+}
+
+/// Metadata of scripts that leave it out (the `video` configuration section)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoConfig {
+    /// Resolution of scripts without `metadata.resolution`, e.g. `1920x1080`
+    pub default_resolution: String,
+    /// Frame rate of scripts without `metadata.fps`
+    pub default_fps: u32,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            default_resolution: "1920x1080".to_string(),
+            default_fps: 30,
+        }
+    }
+}
+
+/// Longest slug `slugify` returns, in characters
+const MAX_SLUG_CHARS: usize = 80;
+
+/// `text` lowercased, with every run of characters other than letters and
+/// digits (spaces, punctuation, path separators) replaced by one `-`. Letters
+/// outside ASCII are kept. Falls back to `video` when nothing is left.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if slug.chars().count() >= MAX_SLUG_CHARS {
+            break;
+        }
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "video".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Video resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Resolution {
+    Named(String), // e.g., "1920x1080", "1280x720"
+    Dimensions { width: u32, height: u32 },
+}
+
+impl Resolution {
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Resolution::Named(s) => {
+                let parts: Vec<&str> = s.split('x').collect();
+                if parts.len() == 2 {
+                    let width = parts[0].parse().unwrap_or(1920);
+                    let height = parts[1].parse().unwrap_or(1080);
+                    (width, height)
+                } else {
+                    (1920, 1080) // Default to 1080p
+                }
+            }
+            Resolution::Dimensions { width, height } => (*width, *height),
+        }
+    }
+}
+
+/// A scene in the video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub id: String,
+    pub duration: f32,
+    #[serde(default)]
+    pub scene_type: SceneType,
+    pub layers: Vec<Layer>,
+    #[serde(default)]
+    pub transition: Option<Transition>,
+    /// Spoken script for this scene, used for pacing analysis
+    #[serde(default)]
+    pub narration: Option<String>,
+    /// Chapter title for this scene (the id is used when unset)
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Type of scene for narrative structure
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneType {
+    #[default]
+    Body,
+    Hook,
+    Payoff,
+}
+
+/// A layer within a scene (can be video, image, text, etc.)
+///
+/// A `type` other than the built-in ones makes a [`Layer::Custom`], drawn by
+/// the [`LayerRenderer`](crate::renderer::LayerRenderer) registered for it.
+// `remote = "Self"` keeps the derived impls as inherent functions, which the
+// `Serialize` and `Deserialize` impls below fall back to for built-in layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", remote = "Self")]
+pub enum Layer {
+    #[serde(rename = "video")]
+    Video {
+        source: PathBuf,
+        #[serde(default)]
+        effects: Vec<Effect>,
+        #[serde(default)]
+        transform: Transform,
+    },
+    #[serde(rename = "image")]
+    Image {
+        source: PathBuf,
+        #[serde(default)]
+        effects: Vec<Effect>,
+        #[serde(default)]
+        transform: Transform,
+        /// Part of the image to draw, e.g. one icon of a sprite sheet
+        #[serde(default)]
+        crop: Option<Crop>,
+    },
+    #[serde(rename = "text")]
+    Text {
+        content: String,
+        font: PathBuf,
+        font_size: f32,
+        color: Color,
+        #[serde(default)]
+        position: Position,
+        #[serde(default)]
+        effects: Vec<Effect>,
+    },
+    /// Animated bars (or a line) following the loudness of an audio file
+    #[serde(rename = "waveform")]
+    Waveform {
+        audio_source: PathBuf,
+        #[serde(default)]
+        style: WaveformStyle,
+        color: Color,
+        #[serde(default = "default_bar_count")]
+        bar_count: u32,
+        #[serde(default = "default_waveform_width")]
+        width: u32,
+        #[serde(default = "default_waveform_height")]
+        height: u32,
+        #[serde(default)]
+        position: Position,
+    },
+    /// A layer of a type a plugin draws: every field but `type` and
+    /// `transform` goes to the plugin in `params`
+    #[serde(skip)]
+    Custom {
+        kind: String,
+        params: serde_json::Value,
+        transform: Transform,
+    },
+}
+
+impl Layer {
+    /// The `type` of every layer drawn without a plugin
+    pub const BUILTIN_KINDS: [&'static str; 4] = ["video", "image", "text", "waveform"];
+}
+
+impl Serialize for Layer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Layer::Custom {
+            kind,
+            params,
+            transform,
+        } = self
+        else {
+            return Layer::serialize(self, serializer);
+        };
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", kind)?;
+        if let serde_json::Value::Object(params) = params {
+            for (key, value) in params {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.serialize_entry("transform", transform)?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Layer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        let kind = match value.get("type").and_then(serde_json::Value::as_str) {
+            Some(kind) if !Layer::BUILTIN_KINDS.contains(&kind) => kind.to_string(),
+            _ => return Layer::deserialize(value).map_err(de::Error::custom),
+        };
+        let params = value
+            .as_object_mut()
+            .expect("a layer with a type is an object");
+        params.remove("type");
+        let transform = match params.remove("transform") {
+            Some(transform) => Transform::deserialize(transform).map_err(de::Error::custom)?,
+            None => Transform::default(),
+        };
+        Ok(Layer::Custom {
+            kind,
+            params: value,
+            transform,
+        })
+    }
+}
+
+/// Drawing style of a waveform layer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WaveformStyle {
+    /// Mirrored vertical bars, one per video frame of history
+    #[default]
+    Bars,
+    /// A single line tracing the level
+    Line,
+}
+
+fn default_bar_count() -> u32 {
+    32
+}
+
+fn default_waveform_width() -> u32 {
+    640
+}
+
+fn default_waveform_height() -> u32 {
+    120
+}
+
+/// Transform for positioning and scaling layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transform {
+    #[serde(default)]
+    pub position: Position,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Degrees, clockwise about `position`
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+}
+
+impl Default for Transform {
+    /// Same values as an empty JSON transform: unscaled, unrotated and opaque
+    fn default() -> Self {
+        Self {
+            position: Position::default(),
+            scale: default_scale(),
+            rotation: 0.0,
+            opacity: default_opacity(),
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+/// An image drawn over every frame, above every scene's layers, like a
+/// channel's logo in a corner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    pub source: PathBuf,
+    /// The corner, edge or center it sits at
+    #[serde(default)]
+    pub position: Anchor,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Size relative to the image's own
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Pixels between it and the edges it's anchored to
+    #[serde(default = "default_watermark_margin")]
+    pub margin_px: u32,
+}
+
+fn default_watermark_margin() -> u32 {
+    24
+}
+
+impl Watermark {
+    /// `source` at its own size and fully opaque in the bottom-right corner
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        Self {
+            source: source.into(),
+            position: Anchor::default(),
+            opacity: default_opacity(),
+            scale: default_scale(),
+            margin_px: default_watermark_margin(),
+        }
+    }
+
+    /// Top-left corner of the watermark in a `frame` sized frame, when it's
+    /// `size` pixels once scaled
+    pub fn top_left(
+        &self,
+        (frame_width, frame_height): (u32, u32),
+        (width, height): (u32, u32),
+    ) -> (i32, i32) {
+        let (x, y) = self.position.fractions();
+        let margin = self.margin_px as f32;
+        let place = |fraction: f32, frame: u32, size: u32| {
+            (fraction * (frame as f32 - size as f32) + (1.0 - 2.0 * fraction) * margin).round()
+                as i32
+        };
+        (place(x, frame_width, width), place(y, frame_height, height))
+    }
+}
+
+/// Where in the frame something sits, e.g. `bottom-right`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+}
+
+impl Anchor {
+    /// How far across and down the frame it is: 0.0 at the left or top edge,
+    /// 0.5 centered, 1.0 at the right or bottom edge
+    pub fn fractions(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// Position in the frame
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    /// The middle of a frame of `resolution`
+    pub fn center(resolution: &Resolution) -> Self {
+        let (width, height) = resolution.dimensions();
+        Self {
+            x: (width / 2) as i32,
+            y: (height / 2) as i32,
+        }
+    }
+}
+
+/// Rectangle of an image in source pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Crop {
+    /// The part of this rectangle inside a `width`x`height` image, `None` if empty
+    pub fn clamp_to(&self, width: u32, height: u32) -> Option<Crop> {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        let crop = Crop {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        };
+        (crop.width > 0 && crop.height > 0).then_some(crop)
+    }
+}
+
+/// Color representation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    #[serde(default = "default_alpha")]
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    /// An opaque color
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+fn default_alpha() -> u8 {
+    255
+}
+
+/// Visual effects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    FadeIn,
+    FadeOut,
+    Blur {
+        radius: f32,
+    },
+    ColorGrade {
+        adjustment: String,
+    },
+    /// Slow zoom over the scene, from the layer's transform to `zoom` times
+    /// its size, about the center of the frame
+    KenBurns {
+        zoom: f32,
+    },
+}
+
+/// Transition from a scene into the next, over `duration` seconds centered
+/// on the cut between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Cut,
+    /// Through black, which is reached at the cut
+    Fade {
+        duration: f32,
+    },
+    /// The incoming scene mixed in over the outgoing one
+    Dissolve {
+        duration: f32,
+    },
+    /// The incoming scene uncovered by an edge moving `right` (the default),
+    /// `left`, `up` or `down`
+    Wipe {
+        duration: f32,
+        direction: String,
+    },
+}
+
+/// Audio configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioConfig {
+    pub tracks: Vec<AudioTrack>,
+    #[serde(default)]
+    pub normalization: Option<LoudnessNormalization>,
+    #[serde(default)]
+    pub ducking: Option<DuckingConfig>,
+}
+
+/// Sidechain ducking of music tracks while a voiceover is playing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingConfig {
+    /// Maximum attenuation applied to music (dB, positive)
+    #[serde(default = "default_duck_amount_db")]
+    pub amount_db: f32,
+    /// Time for the music to dip once voice starts (ms)
+    #[serde(default = "default_duck_attack_ms")]
+    pub attack_ms: f32,
+    /// Time for the music to recover once voice stops (ms)
+    #[serde(default = "default_duck_release_ms")]
+    pub release_ms: f32,
+}
+
+impl Default for DuckingConfig {
+    fn default() -> Self {
+        Self {
+            amount_db: default_duck_amount_db(),
+            attack_ms: default_duck_attack_ms(),
+            release_ms: default_duck_release_ms(),
+        }
+    }
+}
+
+fn default_duck_amount_db() -> f32 {
+    12.0
+}
+
+fn default_duck_attack_ms() -> f32 {
+    50.0
+}
+
+fn default_duck_release_ms() -> f32 {
+    400.0
+}
+
+/// Loudness normalization settings (EBU R128 / ITU-R BS.1770)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoudnessNormalization {
+    /// Integrated loudness target in LUFS
+    #[serde(default = "default_target_lufs")]
+    pub target_lufs: f32,
+    /// True-peak ceiling in dBTP
+    #[serde(default = "default_true_peak_db")]
+    pub true_peak_db: f32,
+}
+
+impl Default for LoudnessNormalization {
+    fn default() -> Self {
+        Self {
+            target_lufs: default_target_lufs(),
+            true_peak_db: default_true_peak_db(),
+        }
+    }
+}
+
+fn default_target_lufs() -> f32 {
+    -14.0
+}
+
+fn default_true_peak_db() -> f32 {
+    -1.0
+}
+
+/// Individual audio track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioTrack {
+    pub source: PathBuf,
+    #[serde(default)]
+    pub track_type: AudioTrackType,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub start_time: f32,
+    /// Linear fade-in duration in seconds
+    #[serde(default)]
+    pub fade_in: f32,
+    /// Linear fade-out duration in seconds, ending where the track stops playing
+    #[serde(default)]
+    pub fade_out: f32,
+    /// Offset into the source file where playback begins (seconds)
+    #[serde(default)]
+    pub trim_start: f32,
+    /// Offset into the source file where playback ends (seconds)
+    #[serde(default)]
+    pub trim_end: Option<f32>,
+    /// Repeat the (trimmed) track until the end of the video
+    #[serde(default, rename = "loop")]
+    pub looping: bool,
+    /// Crossfade length at each loop point to hide clicks (ms)
+    #[serde(default)]
+    pub loop_crossfade_ms: f32,
+    /// Stereo position from -1.0 (left) to 1.0 (right); ignored for mono output
+    #[serde(default)]
+    pub pan: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// Type of audio track
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioTrackType {
+    #[default]
+    Music,
+    Voiceover,
+    SoundEffect,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Test Video"), "test-video");
+        assert_eq!(slugify("  Rust:  Why? (Part 2)  "), "rust-why-part-2");
+        // Path separators never reach the file name
+        assert_eq!(slugify("../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify(r"C:\Users\me"), "c-users-me");
+        // Non-ASCII letters are kept, symbols are not
+        assert_eq!(slugify("Café Über 東京 🚀"), "café-über-東京");
+        assert_eq!(slugify("🚀 / ..."), "video");
+        assert_eq!(slugify(""), "video");
+        assert_eq!(
+            slugify(&"a ".repeat(100)).chars().count(),
+            MAX_SLUG_CHARS - 1
+        );
+    }
+
+    #[test]
+    fn test_resolution_parsing() {
+        let res = Resolution::Named("1920x1080".to_string());
+        assert_eq!(res.dimensions(), (1920, 1080));
+
+        let res = Resolution::Dimensions {
+            width: 1280,
+            height: 720,
+        };
+        assert_eq!(res.dimensions(), (1280, 720));
+    }
+
+    #[test]
+    fn test_script_deserialization() {
+        let json = r#"
+        {
+            "metadata": {
+                "title": "Test Video",
+                "resolution": "1920x1080",
+                "fps": 60,
+                "duration": 10.0
+            },
+            "scenes": [
+                {
+                    "id": "scene1",
+                    "duration": 5.0,
+                    "layers": [
+                        {
+                            "type": "image",
+                            "source": "test.png"
+                        }
+                    ]
+                }
+            ]
+        }
+        "#;
+
+        let script: Result<VideoScript, _> = serde_json::from_str(json);
+        assert!(script.is_ok());
+        let script = script.unwrap();
+        assert_eq!(script.metadata.title, "Test Video");
+        assert_eq!(script.scenes.len(), 1);
+    }
+
+    #[test]
+    fn test_default_functions() {
+        assert_eq!(default_scale(), 1.0);
+        assert_eq!(default_opacity(), 1.0);
+        assert_eq!(default_alpha(), 255);
+        assert_eq!(default_volume(), 1.0);
+        assert_eq!(default_target_lufs(), -14.0);
+        assert_eq!(default_true_peak_db(), -1.0);
+        assert_eq!(default_duck_amount_db(), 12.0);
+        assert_eq!(default_duck_attack_ms(), 50.0);
+        assert_eq!(default_duck_release_ms(), 400.0);
+        assert_eq!(default_bar_count(), 32);
+        assert_eq!(default_waveform_width(), 640);
+        assert_eq!(default_waveform_height(), 120);
+    }
+
+    #[test]
+    fn test_position_default() {
+        let pos = Position::default();
+        assert_eq!(pos.x, 0);
+        assert_eq!(pos.y, 0);
+    }
+
+    #[test]
+    fn test_resolution_invalid_format() {
+        let res = Resolution::Named("invalid".to_string());
+        assert_eq!(res.dimensions(), (1920, 1080)); // Should default
+
+        let res = Resolution::Named("1920".to_string());
+        assert_eq!(res.dimensions(), (1920, 1080)); // Should default
+
+        let res = Resolution::Named("".to_string());
+        assert_eq!(res.dimensions(), (1920, 1080)); // Should default
+    }
+
+    #[test]
+    fn test_transform_defaults_serde() {
+        // Test that serde uses our default functions
+        let json = r#"{}"#;
+        let transform: Transform = serde_json::from_str(json).unwrap();
+        assert_eq!(transform.scale, 1.0); // Uses default_scale
+        assert_eq!(transform.opacity, 1.0); // Uses default_opacity
+        assert_eq!(transform.rotation, 0.0); // Uses serde default (0.0)
+        assert_eq!(transform.position.x, 0); // Uses Position::default()
+        assert_eq!(transform.position.y, 0);
+    }
+
+    #[test]
+    fn test_layer_deserialization() {
+        // Test Image layer
+        let json = r#"{"type": "image", "source": "test.png"}"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        match layer {
+            Layer::Image { .. } => (),
+            _ => panic!("Expected Image layer"),
+        }
+
+        // Test Video layer
+        let json = r#"{"type": "video", "source": "test.mp4"}"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        match layer {
+            Layer::Video { .. } => (),
+            _ => panic!("Expected Video layer"),
+        }
+
+        // Test Text layer
+        let json = r#"{
+            "type": "text",
+            "content": "Hello",
+            "font": "font.ttf",
+            "font_size": 24.0,
+            "color": {"r": 255, "g": 255, "b": 255}
+        }"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        match layer {
+            Layer::Text { .. } => (),
+            _ => panic!("Expected Text layer"),
+        }
+
+        // Test Waveform layer
+        let json = r#"{
+            "type": "waveform",
+            "audio_source": "voice.wav",
+            "color": {"r": 255, "g": 255, "b": 255}
+        }"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        match layer {
+            Layer::Waveform {
+                style,
+                bar_count,
+                width,
+                height,
+                ..
+            } => {
+                assert_eq!(style, WaveformStyle::Bars);
+                assert_eq!((bar_count, width, height), (32, 640, 120));
+            }
+            _ => panic!("Expected Waveform layer"),
+        }
+    }
+
+    #[test]
+    fn test_custom_layer_serde() {
+        // Unknown types keep their fields for the plugin drawing them
+        let json = r#"{"type": "chart", "bars": [3, 5], "transform": {"opacity": 0.5}}"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        let Layer::Custom {
+            kind,
+            params,
+            transform,
+        } = &layer
+        else {
+            panic!("Expected Custom layer, got {:?}", layer);
+        };
+        assert_eq!(kind, "chart");
+        assert_eq!(*params, serde_json::json!({"bars": [3, 5]}));
+        assert_eq!(transform.opacity, 0.5);
+
+        let value = serde_json::to_value(&layer).unwrap();
+        assert_eq!(value["type"], "chart");
+        assert_eq!(value["bars"], serde_json::json!([3, 5]));
+        let parsed: Layer = serde_json::from_value(value).unwrap();
+        assert!(matches!(parsed, Layer::Custom { kind, .. } if kind == "chart"));
+
+        // Built-in types still need their fields
+        let error = serde_json::from_str::<Layer>(r#"{"type": "image"}"#).unwrap_err();
+        assert!(
+            error.to_string().contains("missing field `source`"),
+            "{}",
+            error
+        );
+        assert!(serde_json::from_str::<Layer>(r#"{"source": "a.png"}"#).is_err());
+    }
+
+    #[test]
+    fn test_image_crop() {
+        let json = r#"{
+            "type": "image",
+            "source": "icons.png",
+            "crop": {"x": 64, "y": 0, "width": 32, "height": 32}
+        }"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        let Layer::Image { crop, .. } = layer else {
+            panic!("Expected Image layer");
+        };
+        let crop = crop.unwrap();
+        assert_eq!(crop.clamp_to(128, 32), Some(crop));
+        // Trimmed to the image, empty once entirely outside it
+        assert_eq!(
+            crop.clamp_to(80, 16),
+            Some(Crop {
+                x: 64,
+                y: 0,
+                width: 16,
+                height: 16
+            })
+        );
+        assert_eq!(crop.clamp_to(64, 32), None);
+    }
+
+    #[test]
+    fn test_audio_track_defaults() {
+        let json = r#"{"source": "music.mp3"}"#;
+        let track: AudioTrack = serde_json::from_str(json).unwrap();
+        assert_eq!(track.volume, 1.0);
+        assert_eq!(track.start_time, 0.0);
+        assert_eq!(track.fade_in, 0.0);
+        assert_eq!(track.fade_out, 0.0);
+        assert_eq!(track.trim_start, 0.0);
+        assert!(track.trim_end.is_none());
+        assert!(!track.looping);
+        assert_eq!(track.loop_crossfade_ms, 0.0);
+        assert_eq!(track.pan, 0.0);
+
+        let json = r#"{"source": "music.mp3", "loop": true, "loop_crossfade_ms": 20.0}"#;
+        let track: AudioTrack = serde_json::from_str(json).unwrap();
+        assert!(track.looping);
+        assert_eq!(track.loop_crossfade_ms, 20.0);
+        match track.track_type {
+            AudioTrackType::Music => (),
+            _ => panic!("Expected Music as default track type"),
+        }
+    }
+
+    #[test]
+    fn test_audio_normalization_defaults() {
+        let json = r#"{"tracks": [], "normalization": {}}"#;
+        let audio: AudioConfig = serde_json::from_str(json).unwrap();
+        let norm = audio.normalization.unwrap();
+        assert_eq!(norm.target_lufs, -14.0);
+        assert_eq!(norm.true_peak_db, -1.0);
+
+        let json = r#"{"tracks": []}"#;
+        let audio: AudioConfig = serde_json::from_str(json).unwrap();
+        assert!(audio.normalization.is_none());
+    }
+}
@@ -1,168 +1,597 @@
-use crate::script::{Color, Layer, Metadata, Position, Resolution, Scene, SceneType, VideoScript};
+#[cfg(feature = "render")]
+pub mod project;
+
+use crate::script::{
+    AudioConfig, AudioTrack, AudioTrackType, Color, DuckingConfig, Effect, Layer, Metadata,
+    Position, Resolution, Scene, SceneType, Transform, Transition, VideoScript,
+};
 use clap::ValueEnum;
 
+/// Shortest body scene a template generates, in seconds
+pub const MIN_BODY_SCENE_SECONDS: f32 = 1.0;
+
+/// Fewest scenes a template has: an opening, a body and a closing
+pub const MIN_SCENES: usize = 3;
+
+/// Longest Shorts video, in seconds
+pub const SHORTS_MAX_SECONDS: f32 = 60.0;
+
+/// Length of a Shorts hook, in seconds
+const SHORTS_HOOK_SECONDS: f32 = 2.0;
+
+/// Longest Shorts payoff, in seconds
+const SHORTS_MAX_PAYOFF_SECONDS: f32 = 5.0;
+
+/// Longest Shorts body scene; a longer body is cut into more scenes
+const SHORTS_MAX_BODY_SCENE_SECONDS: f32 = 10.0;
+
+/// Length a slideshow aims for per slide, in seconds
+const SLIDE_SECONDS: f32 = 5.0;
+
+/// How far each slide zooms in (or out) over its scene
+const SLIDE_ZOOM: f32 = 1.1;
+
+const FONT: &str = "assets/fonts/Inter-Bold.ttf";
+
+const SHORTS_BEATS: &[&str] = &[
+    "First, pick the one task that eats most of your day. Write it down. Then time it for a full week.",
+    "Next, batch it. Do all of it in one block, at the same time each day. Small jumps between jobs cost more than you think.",
+    "Now cut the steps you only do out of habit. Ask if anyone would notice. If not, drop it.",
+    "Then hand off what is left to a tool. A template, a checklist or a timer does the boring part for you.",
+    "Keep one hour a day with no calls and no chat. Put it in your calendar so it stays free.",
+    "Last, look back each Friday. Keep what worked, drop what did not and try one new idea.",
+];
+
+const SLIDE_LINES: &[&str] = &[
+    "This is where the trip began, on a cold and quiet morning by the sea.",
+    "We packed light, with one bag each and a list of places we had to see.",
+    "The first town was small, with bright doors and a market full of fresh bread.",
+    "By noon the sun came out and the old harbor filled with boats and color.",
+    "We took the long road up the hill and stopped at every view along the way.",
+    "At the top, a tiny cafe served the best soup we had all year.",
+    "The second day started with rain, so we spent it in a museum of old maps.",
+    "That night we found a street band playing songs nobody had heard before.",
+    "On the third day we rented bikes and rode along the coast until sunset.",
+    "A farmer let us pick apples from his trees and would not take any money.",
+    "We met travelers from five countries and shared one long table for dinner.",
+    "On the last morning we watched the fog lift off the water one more time.",
+];
+
+const MUSIC_PLACEHOLDER: &str = "assets/audio/music.mp3";
+const VOICEOVER_PLACEHOLDER: &str = "assets/audio/voiceover.wav";
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum TemplateType {
     Explainer,
     Tutorial,
     Storytelling,
+    /// Vertical video of at most a minute with a 2 second hook
+    Shorts,
+    /// Problem, solution, social proof and call to action, with citation placeholders
+    ProductAd,
+    /// Image slides zooming slowly and crossfading into each other
+    Slideshow,
+}
+
+/// What `ScriptTemplate::generate` makes
+#[derive(Debug, Clone)]
+pub struct TemplateOptions {
+    /// Total duration in seconds. The opening and closing scenes have minimum
+    /// lengths and each body scene lasts at least `MIN_BODY_SCENE_SECONDS`, so
+    /// a short duration with many scenes comes out longer.
+    pub duration: f32,
+    /// Scenes in all, at least `MIN_SCENES`; the body is split evenly into the
+    /// ones between the opening and closing scenes
+    pub scenes: usize,
+    /// Add a music and a voiceover track with placeholder paths
+    pub with_audio: bool,
+}
+
+impl TemplateOptions {
+    /// Three scenes lasting `duration` seconds, without audio
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            scenes: MIN_SCENES,
+            with_audio: false,
+        }
+    }
 }
 
 pub struct ScriptTemplate;
 
 impl ScriptTemplate {
-    pub fn generate(template_type: TemplateType, duration: f32) -> VideoScript {
+    pub fn generate(template_type: TemplateType, options: &TemplateOptions) -> VideoScript {
         match template_type {
-            TemplateType::Explainer => Self::generate_explainer(duration),
-            TemplateType::Tutorial => Self::generate_tutorial(duration),
-            TemplateType::Storytelling => Self::generate_storytelling(duration),
+            TemplateType::Explainer => Self::generate_explainer(options),
+            TemplateType::Tutorial => Self::generate_tutorial(options),
+            TemplateType::Storytelling => Self::generate_storytelling(options),
+            TemplateType::Shorts => Self::generate_shorts(options),
+            TemplateType::ProductAd => Self::generate_product_ad(options),
+            TemplateType::Slideshow => Self::generate_slideshow(options),
         }
     }
 
-    fn generate_explainer(total_duration: f32) -> VideoScript {
-        let hook_duration = (total_duration * 0.15).max(3.0);
-        let payoff_duration = (total_duration * 0.10).max(3.0);
-        let body_duration = total_duration - hook_duration - payoff_duration;
+    fn generate_explainer(options: &TemplateOptions) -> VideoScript {
+        let hook_duration = (options.duration * 0.15).max(3.0);
+        let payoff_duration = (options.duration * 0.10).max(3.0);
 
-        VideoScript {
-            metadata: Metadata {
-                title: "Explainer Video".into(),
-                resolution: Resolution::Named("1920x1080".into()),
-                fps: 30,
-                duration: total_duration,
-                description: Some("Generated explainer template".into()),
-                citations: vec![],
+        Self::assemble(
+            options,
+            "Explainer Video",
+            "Generated explainer template",
+            Self::create_scene(
+                "Hook",
+                SceneType::Hook,
+                hook_duration,
+                "Hook: Grab Attention",
+            ),
+            ("Body", "Body: Explain Concept"),
+            Self::create_scene(
+                "Payoff",
+                SceneType::Payoff,
+                payoff_duration,
+                "Payoff: Call to Action",
+            ),
+        )
+    }
+
+    fn generate_tutorial(options: &TemplateOptions) -> VideoScript {
+        let intro_duration = (options.duration * 0.10).max(3.0);
+        let recap_duration = (options.duration * 0.15).max(5.0);
+
+        Self::assemble(
+            options,
+            "Tutorial Video",
+            "Generated tutorial template",
+            Self::create_scene(
+                "Intro",
+                SceneType::Hook,
+                intro_duration,
+                "Intro: What we'll build",
+            ),
+            ("Steps", "Steps: Step-by-step guide"),
+            Self::create_scene(
+                "Recap",
+                SceneType::Payoff,
+                recap_duration,
+                "Recap: Summary & Next Steps",
+            ),
+        )
+    }
+
+    fn generate_storytelling(options: &TemplateOptions) -> VideoScript {
+        let setup_duration = options.duration * 0.20;
+        let resolution_duration = options.duration * 0.30;
+
+        Self::assemble(
+            options,
+            "Story Video",
+            "Generated storytelling template",
+            Self::create_scene(
+                "Setup",
+                SceneType::Hook,
+                setup_duration,
+                "Setup: The World & Characters",
+            ),
+            ("Conflict", "Conflict: The Challenge"),
+            Self::create_scene(
+                "Resolution",
+                SceneType::Payoff,
+                resolution_duration,
+                "Resolution: The Change",
+            ),
+        )
+    }
+
+    /// Vertical and fast: a 2 second hook, body scenes of at most 10 seconds
+    /// (more than `options.scenes` asks for when the body is longer) and a
+    /// payoff of at most 5 seconds. Capped at `SHORTS_MAX_SECONDS`.
+    fn generate_shorts(options: &TemplateOptions) -> VideoScript {
+        let options = &TemplateOptions {
+            duration: options.duration.min(SHORTS_MAX_SECONDS),
+            ..options.clone()
+        };
+        let duration = options.duration;
+        let payoff_duration = (duration * 0.10).clamp(3.0, SHORTS_MAX_PAYOFF_SECONDS);
+        let body_duration = duration - SHORTS_HOOK_SECONDS - payoff_duration;
+        let body_scenes = (options.scenes.max(MIN_SCENES) - 2)
+            .max((body_duration / SHORTS_MAX_BODY_SCENE_SECONDS).ceil() as usize);
+        let scene_duration = (body_duration / body_scenes as f32).max(MIN_BODY_SCENE_SECONDS);
+
+        let center = (540, 960);
+        let mut scenes = vec![Self::narrate(
+            Self::scene(
+                "Hook",
+                SceneType::Hook,
+                SHORTS_HOOK_SECONDS,
+                vec![Self::caption(
+                    "Want 10 hours back every week? Here's the one thing you're missing",
+                    center,
+                    72.0,
+                )],
+            ),
+            "You can get them back.",
+        )];
+        scenes.extend((1..=body_scenes).map(|n| {
+            Self::narrate(
+                Self::scene(
+                    &format!("Tip-{}", n),
+                    SceneType::Body,
+                    scene_duration,
+                    vec![Self::caption(&format!("Tip {}", n), center, 72.0)],
+                ),
+                SHORTS_BEATS[(n - 1) % SHORTS_BEATS.len()],
+            )
+        }));
+        scenes.push(Self::narrate(
+            Self::scene(
+                "Payoff",
+                SceneType::Payoff,
+                payoff_duration,
+                vec![Self::caption("Follow for part 2", center, 72.0)],
+            ),
+            "Try it this week and tell me how many hours you saved. Follow for more tips.",
+        ));
+        // Shorts jump from beat to beat
+        for scene in &mut scenes {
+            scene.transition = Some(Transition::Cut);
+        }
+
+        Self::finish(
+            options,
+            "Short Video",
+            "Generated shorts template",
+            Resolution::Dimensions {
+                width: 1080,
+                height: 1920,
             },
-            scenes: vec![
-                Self::create_scene(
-                    "Hook",
+            scenes,
+        )
+    }
+
+    /// Problem (the hook), solution, social proof and call to action. The
+    /// citations are placeholders in the `[Source, Year]` form the credibility
+    /// checklist accepts, to be replaced with the real sources.
+    fn generate_product_ad(options: &TemplateOptions) -> VideoScript {
+        let problem_duration = (options.duration * 0.12).max(2.0);
+        let action_duration = (options.duration * 0.18).max(3.0);
+        let body_duration = options.duration - problem_duration - action_duration;
+        let solution_duration = (body_duration * 4.0 / 7.0).max(MIN_BODY_SCENE_SECONDS);
+        let proof_duration = (body_duration * 3.0 / 7.0).max(MIN_BODY_SCENE_SECONDS);
+
+        let center = (960, 540);
+        let product = |source: &str| Layer::Image {
+            source: source.into(),
+            effects: vec![],
+            transform: Transform::default(),
+            crop: None,
+        };
+        let mut scenes = vec![
+            Self::narrate(
+                Self::scene(
+                    "Problem",
                     SceneType::Hook,
-                    hook_duration,
-                    "Hook: Grab Attention",
+                    problem_duration,
+                    vec![Self::caption(
+                        "Why lose 2 hours a day to busywork? The secret fix you need",
+                        center,
+                        60.0,
+                    )],
                 ),
-                Self::create_scene(
-                    "Body",
+                "Your day fills up with small jobs. Copy, paste, check, repeat. It adds up fast.",
+            ),
+            Self::narrate(
+                Self::scene(
+                    "Solution",
                     SceneType::Body,
-                    body_duration,
-                    "Body: Explain Concept",
+                    solution_duration,
+                    vec![
+                        product("assets/images/product.png"),
+                        Self::caption("Meet the product", center, 60.0),
+                    ],
                 ),
-                Self::create_scene(
-                    "Payoff",
+                "Meet the product. It takes the repeat work off your plate. Connect your tools once and it handles the rest. Reports fill themselves in. Reminders go out on time. You get a clear view of what needs you and what does not.",
+            ),
+            Self::narrate(
+                Self::scene(
+                    "SocialProof",
+                    SceneType::Body,
+                    proof_duration,
+                    vec![
+                        product("assets/images/reviews.png"),
+                        Self::caption("Teams love it", center, 60.0),
+                    ],
+                ),
+                "Teams that switched say they finish work sooner and feel less rushed. One shop owner told us she got her Friday afternoons back. Put your own reviews and numbers here, with the source for each one.",
+            ),
+            Self::narrate(
+                Self::scene(
+                    "CallToAction",
                     SceneType::Payoff,
-                    payoff_duration,
-                    "Payoff: Call to Action",
+                    action_duration,
+                    vec![
+                        product("assets/images/logo.png"),
+                        Self::caption("Try it free today", center, 60.0),
+                    ],
                 ),
-            ],
-            audio: None,
+                "Try it free today. Set it up in minutes and see the difference this week. The link is below.",
+            ),
+        ];
+        let last = scenes.len() - 1;
+        for scene in &mut scenes[..last] {
+            scene.transition = Some(Transition::Dissolve { duration: 0.5 });
         }
+
+        let mut script = Self::finish(
+            options,
+            "Product Ad",
+            "Generated product ad template",
+            Resolution::Named("1920x1080".into()),
+            scenes,
+        );
+        script.metadata.citations = vec![
+            "[Source of your customer numbers, Year]".to_string(),
+            "[Source of your reviews, Year]".to_string(),
+        ];
+        script
     }
 
-    fn generate_tutorial(total_duration: f32) -> VideoScript {
-        let intro_duration = (total_duration * 0.10).max(3.0);
-        let recap_duration = (total_duration * 0.15).max(5.0);
-        let steps_duration = total_duration - intro_duration - recap_duration;
+    /// One image slide per `SLIDE_SECONDS` or so, at least `MIN_SCENES`, each
+    /// zooming in or out in turn and crossfading into the next
+    fn generate_slideshow(options: &TemplateOptions) -> VideoScript {
+        let slides = ((options.duration / SLIDE_SECONDS).round() as usize).max(MIN_SCENES);
+        let slide_duration = options.duration / slides as f32;
 
-        VideoScript {
-            metadata: Metadata {
-                title: "Tutorial Video".into(),
-                resolution: Resolution::Named("1920x1080".into()),
-                fps: 30,
-                duration: total_duration,
-                description: Some("Generated tutorial template".into()),
-                citations: vec![],
-            },
-            scenes: vec![
-                Self::create_scene(
-                    "Intro",
-                    SceneType::Hook,
-                    intro_duration,
-                    "Intro: What we'll build",
-                ),
+        let scenes = (1..=slides)
+            .map(|n| {
+                let scene_type = match n {
+                    1 => SceneType::Hook,
+                    n if n == slides => SceneType::Payoff,
+                    _ => SceneType::Body,
+                };
+                // Odd slides zoom in, even ones start zoomed in and zoom out,
+                // both always covering the frame
+                let (transform, zoom) = if n % 2 == 1 {
+                    (Transform::default(), SLIDE_ZOOM)
+                } else {
+                    let transform = Transform {
+                        position: Position {
+                            x: (-960.0 * (SLIDE_ZOOM - 1.0)).round() as i32,
+                            y: (-540.0 * (SLIDE_ZOOM - 1.0)).round() as i32,
+                        },
+                        scale: SLIDE_ZOOM,
+                        ..Default::default()
+                    };
+                    (transform, 1.0 / SLIDE_ZOOM)
+                };
+                let caption = match scene_type {
+                    SceneType::Hook => {
+                        "Want to see 3 days on the coast in 1 minute? Here's why you should go"
+                            .to_string()
+                    }
+                    SceneType::Payoff => "Save this for your next trip".to_string(),
+                    SceneType::Body => format!("Day {}", (n - 1) * 3 / slides + 1),
+                };
+                let mut scene = Self::narrate(
+                    Self::scene(
+                        &format!("Slide-{}", n),
+                        scene_type,
+                        slide_duration,
+                        vec![
+                            Layer::Image {
+                                source: format!("assets/images/slide-{}.jpg", n).into(),
+                                effects: vec![Effect::KenBurns { zoom }],
+                                transform,
+                                crop: None,
+                            },
+                            Self::caption(&caption, (960, 960), 48.0),
+                        ],
+                    ),
+                    SLIDE_LINES[(n - 1) % SLIDE_LINES.len()],
+                );
+                if n < slides {
+                    scene.transition = Some(Transition::Dissolve { duration: 1.0 });
+                }
+                scene
+            })
+            .collect();
+
+        Self::finish(
+            options,
+            "Slideshow",
+            "Generated slideshow template",
+            Resolution::Named("1920x1080".into()),
+            scenes,
+        )
+    }
+
+    /// `opening`, the time left split into body scenes (`id-1`, `id-2`, ... when
+    /// there's more than one), then `closing`
+    fn assemble(
+        options: &TemplateOptions,
+        title: &str,
+        description: &str,
+        opening: Scene,
+        (body_id, body_text): (&str, &str),
+        closing: Scene,
+    ) -> VideoScript {
+        let body_scenes = options.scenes.max(MIN_SCENES) - 2;
+        let body_duration = options.duration - opening.duration - closing.duration;
+        let scene_duration = (body_duration / body_scenes as f32).max(MIN_BODY_SCENE_SECONDS);
+
+        let mut scenes = vec![opening];
+        if body_scenes == 1 {
+            scenes.push(Self::create_scene(
+                body_id,
+                SceneType::Body,
+                scene_duration,
+                body_text,
+            ));
+        } else {
+            scenes.extend((1..=body_scenes).map(|n| {
                 Self::create_scene(
-                    "Steps",
+                    &format!("{}-{}", body_id, n),
                     SceneType::Body,
-                    steps_duration,
-                    "Steps: Step-by-step guide",
-                ),
-                Self::create_scene(
-                    "Recap",
-                    SceneType::Payoff,
-                    recap_duration,
-                    "Recap: Summary & Next Steps",
-                ),
-            ],
-            audio: None,
+                    scene_duration,
+                    &format!("{} ({}/{})", body_text, n, body_scenes),
+                )
+            }));
         }
+        scenes.push(closing);
+
+        Self::finish(
+            options,
+            title,
+            description,
+            Resolution::Named("1920x1080".into()),
+            scenes,
+        )
     }
 
-    fn generate_storytelling(total_duration: f32) -> VideoScript {
-        let setup_duration = total_duration * 0.20;
-        let conflict_duration = total_duration * 0.50;
-        let resolution_duration = total_duration * 0.30;
+    /// The script of `scenes`. It lasts `options.duration` unless minimum
+    /// scene lengths or a cap made the scenes add up to something else.
+    fn finish(
+        options: &TemplateOptions,
+        title: &str,
+        description: &str,
+        resolution: Resolution,
+        scenes: Vec<Scene>,
+    ) -> VideoScript {
+        let total: f32 = scenes.iter().map(|scene| scene.duration).sum();
+        let duration = if (total - options.duration).abs() < 1e-3 {
+            options.duration
+        } else {
+            total
+        };
+        let description = if options.with_audio {
+            format!(
+                "{}. Replace {} and {} with your music and narration.",
+                description, MUSIC_PLACEHOLDER, VOICEOVER_PLACEHOLDER
+            )
+        } else {
+            description.to_string()
+        };
 
         VideoScript {
             metadata: Metadata {
-                title: "Story Video".into(),
-                resolution: Resolution::Named("1920x1080".into()),
+                title: title.into(),
+                resolution,
                 fps: 30,
-                duration: total_duration,
-                description: Some("Generated storytelling template".into()),
+                duration,
+                description: Some(description),
                 citations: vec![],
+                watermark: None,
             },
-            scenes: vec![
-                Self::create_scene(
-                    "Setup",
-                    SceneType::Hook,
-                    setup_duration,
-                    "Setup: The World & Characters",
-                ),
-                Self::create_scene(
-                    "Conflict",
-                    SceneType::Body,
-                    conflict_duration,
-                    "Conflict: The Challenge",
-                ),
-                Self::create_scene(
-                    "Resolution",
-                    SceneType::Payoff,
-                    resolution_duration,
-                    "Resolution: The Change",
-                ),
+            scenes,
+            audio: options.with_audio.then(Self::placeholder_audio),
+        }
+    }
+
+    /// `scene` with `lines` as its narration, repeated or cut to as many
+    /// words as its duration takes at `narration_wpm`
+    fn narrate(mut scene: Scene, lines: &str) -> Scene {
+        let words = (scene.duration * narration_wpm(&scene.scene_type) / 60.0)
+            .round()
+            .max(1.0) as usize;
+        let mut narration = lines
+            .split_whitespace()
+            .cycle()
+            .take(words)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !narration.ends_with(['.', '!', '?']) {
+            narration = narration.trim_end_matches(',').to_string() + ".";
+        }
+        scene.narration = Some(narration);
+        scene
+    }
+
+    /// Quiet looping music ducked under a voiceover, both at placeholder paths
+    fn placeholder_audio() -> AudioConfig {
+        let track =
+            |source: &str, track_type: AudioTrackType, volume: f32, looping: bool| AudioTrack {
+                source: source.into(),
+                track_type,
+                volume,
+                start_time: 0.0,
+                fade_in: 0.0,
+                fade_out: 0.0,
+                trim_start: 0.0,
+                trim_end: None,
+                looping,
+                loop_crossfade_ms: 0.0,
+                pan: 0.0,
+            };
+        AudioConfig {
+            tracks: vec![
+                track(MUSIC_PLACEHOLDER, AudioTrackType::Music, 0.3, true),
+                track(VOICEOVER_PLACEHOLDER, AudioTrackType::Voiceover, 1.0, false),
             ],
-            audio: None,
+            normalization: None,
+            ducking: Some(DuckingConfig::default()),
         }
     }
 
     fn create_scene(id: &str, scene_type: SceneType, duration: f32, text: &str) -> Scene {
+        Self::scene(
+            id,
+            scene_type,
+            duration,
+            vec![Self::caption(text, (960, 540), 60.0)],
+        )
+    }
+
+    fn scene(id: &str, scene_type: SceneType, duration: f32, layers: Vec<Layer>) -> Scene {
         Scene {
             id: id.into(),
             scene_type,
             duration,
-            layers: vec![Layer::Text {
-                content: text.into(),
-                font: "assets/fonts/Inter-Bold.ttf".into(),
-                font_size: 60.0,
-                color: Color {
-                    r: 255,
-                    g: 255,
-                    b: 255,
-                    a: 255,
-                },
-                position: Position { x: 960, y: 540 },
-                effects: vec![],
-            }],
+            layers,
             transition: None,
+            narration: None,
+            title: None,
+        }
+    }
+
+    /// White text in the template font at `(x, y)`
+    fn caption(text: &str, (x, y): (i32, i32), font_size: f32) -> Layer {
+        Layer::Text {
+            content: text.into(),
+            font: FONT.into(),
+            font_size,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x, y },
+            effects: vec![],
         }
     }
 }
 
+/// Words per minute of generated narration: the middle of the narrative
+/// analysis' pacing target for the scene type
+fn narration_wpm(scene_type: &SceneType) -> f32 {
+    match scene_type {
+        SceneType::Hook => 155.0,
+        SceneType::Body => 140.0,
+        SceneType::Payoff => 130.0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::credibility::CredibilityAnalyzer;
+    use crate::analysis::narrative::NarrativeAnalyzer;
+    use crate::ScriptParser;
 
     #[test]
     fn test_generate_explainer() {
-        let script = ScriptTemplate::generate(TemplateType::Explainer, 60.0);
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
         assert_eq!(script.metadata.duration, 60.0);
         assert_eq!(script.scenes.len(), 3);
         assert_eq!(script.scenes[0].id, "Hook");
@@ -170,15 +599,176 @@ mod tests {
 
     #[test]
     fn test_generate_tutorial() {
-        let script = ScriptTemplate::generate(TemplateType::Tutorial, 100.0);
+        let script = ScriptTemplate::generate(TemplateType::Tutorial, &TemplateOptions::new(100.0));
         assert_eq!(script.scenes.len(), 3);
         assert_eq!(script.scenes[0].id, "Intro");
     }
 
     #[test]
     fn test_generate_storytelling() {
-        let script = ScriptTemplate::generate(TemplateType::Storytelling, 90.0);
+        let script =
+            ScriptTemplate::generate(TemplateType::Storytelling, &TemplateOptions::new(90.0));
         assert_eq!(script.scenes.len(), 3);
         assert_eq!(script.scenes[0].id, "Setup");
     }
+
+    fn total(script: &VideoScript) -> f32 {
+        script.scenes.iter().map(|scene| scene.duration).sum()
+    }
+
+    #[test]
+    fn test_three_scenes_keep_the_body_whole() {
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        let ids: Vec<&str> = script.scenes.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["Hook", "Body", "Payoff"]);
+        assert_eq!(script.scenes[1].duration, 45.0);
+        assert!(script.audio.is_none());
+        ScriptParser::validate_script(&script).unwrap();
+    }
+
+    #[test]
+    fn test_five_scenes_split_the_body_evenly() {
+        let options = TemplateOptions {
+            scenes: 5,
+            ..TemplateOptions::new(100.0)
+        };
+        let script = ScriptTemplate::generate(TemplateType::Tutorial, &options);
+        let ids: Vec<&str> = script.scenes.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["Intro", "Steps-1", "Steps-2", "Steps-3", "Recap"]);
+        // 100 - 10 - 15, in three
+        for scene in &script.scenes[1..4] {
+            assert_eq!(scene.scene_type, SceneType::Body);
+            assert!((scene.duration - 25.0).abs() < 1e-4);
+        }
+        assert!((total(&script) - 100.0).abs() < 1e-3);
+        ScriptParser::validate_script(&script).unwrap();
+    }
+
+    #[test]
+    fn test_short_duration_clamps_body_scenes() {
+        // The hook and payoff take their 3 second minimums, leaving nothing
+        let options = TemplateOptions {
+            scenes: 5,
+            ..TemplateOptions::new(6.0)
+        };
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &options);
+        assert_eq!(script.scenes.len(), 5);
+        assert!(script.scenes[1..4]
+            .iter()
+            .all(|scene| scene.duration == MIN_BODY_SCENE_SECONDS));
+        assert_eq!(script.metadata.duration, 9.0);
+        assert_eq!(total(&script), script.metadata.duration);
+        ScriptParser::validate_script(&script).unwrap();
+
+        // Fewer than three scenes still makes three
+        let options = TemplateOptions {
+            scenes: 1,
+            ..TemplateOptions::new(60.0)
+        };
+        let script = ScriptTemplate::generate(TemplateType::Storytelling, &options);
+        assert_eq!(script.scenes.len(), 3);
+    }
+
+    /// Valid, and scored well by the narrative analysis as generated
+    fn assert_ready(script: &VideoScript) {
+        ScriptParser::validate_script(script).unwrap();
+        let report = NarrativeAnalyzer::analyze(script);
+        assert!(report.score >= 80, "narrative score {}", report.score);
+        assert!(report.pacing_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_generate_shorts() {
+        let script = ScriptTemplate::generate(TemplateType::Shorts, &TemplateOptions::new(60.0));
+        assert_eq!(script.metadata.resolution.dimensions(), (1080, 1920));
+        assert_eq!(script.metadata.duration, 60.0);
+        let hook = &script.scenes[0];
+        assert_eq!(
+            (hook.scene_type.clone(), hook.duration),
+            (SceneType::Hook, 2.0)
+        );
+        let payoff = script.scenes.last().unwrap();
+        assert_eq!(payoff.scene_type, SceneType::Payoff);
+        assert!(payoff.duration <= 5.0);
+        // 53 seconds of body in scenes of at most 10
+        let body: Vec<&Scene> = script.scenes[1..script.scenes.len() - 1].iter().collect();
+        assert_eq!(body.len(), 6);
+        assert!(body.iter().all(|scene| scene.duration <= 10.0));
+        assert_ready(&script);
+
+        // Never longer than a minute
+        let script = ScriptTemplate::generate(TemplateType::Shorts, &TemplateOptions::new(180.0));
+        assert_eq!(script.metadata.duration, SHORTS_MAX_SECONDS);
+        assert!((total(&script) - SHORTS_MAX_SECONDS).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_generate_product_ad() {
+        let script = ScriptTemplate::generate(TemplateType::ProductAd, &TemplateOptions::new(30.0));
+        assert_eq!(script.metadata.resolution.dimensions(), (1920, 1080));
+        let structure: Vec<(&str, SceneType)> = script
+            .scenes
+            .iter()
+            .map(|scene| (scene.id.as_str(), scene.scene_type.clone()))
+            .collect();
+        assert_eq!(
+            structure,
+            [
+                ("Problem", SceneType::Hook),
+                ("Solution", SceneType::Body),
+                ("SocialProof", SceneType::Body),
+                ("CallToAction", SceneType::Payoff),
+            ]
+        );
+        assert!((total(&script) - 30.0).abs() < 1e-3);
+        assert_ready(&script);
+
+        let credibility = CredibilityAnalyzer::analyze(&script);
+        assert!(!credibility.citations.is_empty());
+        assert!(credibility.checklist.iter().all(|item| item.passed));
+    }
+
+    #[test]
+    fn test_generate_slideshow() {
+        let script = ScriptTemplate::generate(TemplateType::Slideshow, &TemplateOptions::new(60.0));
+        assert_eq!(script.metadata.resolution.dimensions(), (1920, 1080));
+        // One slide per 5 seconds
+        assert_eq!(script.scenes.len(), 12);
+        for (i, scene) in script.scenes.iter().enumerate() {
+            assert!(matches!(
+                &scene.layers[0],
+                Layer::Image { effects, .. } if matches!(effects[..], [Effect::KenBurns { .. }])
+            ));
+            assert_eq!(
+                scene.transition.is_some(),
+                i + 1 < script.scenes.len(),
+                "crossfade out of every slide but the last"
+            );
+        }
+        assert_ready(&script);
+
+        // Short slideshows still have an opening, a body and a closing
+        let script = ScriptTemplate::generate(TemplateType::Slideshow, &TemplateOptions::new(8.0));
+        assert_eq!(script.scenes.len(), MIN_SCENES);
+        ScriptParser::validate_script(&script).unwrap();
+    }
+
+    #[test]
+    fn test_with_audio_adds_placeholder_tracks() {
+        let options = TemplateOptions {
+            with_audio: true,
+            ..TemplateOptions::new(60.0)
+        };
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &options);
+        let audio = script.audio.as_ref().unwrap();
+        let types: Vec<&AudioTrackType> = audio.tracks.iter().map(|t| &t.track_type).collect();
+        assert_eq!(types, [&AudioTrackType::Music, &AudioTrackType::Voiceover]);
+        assert!(script
+            .metadata
+            .description
+            .as_deref()
+            .unwrap()
+            .contains(MUSIC_PLACEHOLDER));
+        ScriptParser::validate_script(&script).unwrap();
+    }
 }
@@ -0,0 +1,109 @@
+//! `init`: a new project directory with a script to start from, the default
+//! configuration and somewhere to put assets.
+
+use crate::config::AppConfig;
+use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Length of the generated script, in seconds
+pub const SCRIPT_DURATION: f32 = 60.0;
+
+const GITIGNORE: &str = "output/\n.cache/\n";
+
+const FONTS_NOTE: &str = "\
+# Fonts
+
+The text layers in `script.json` use `assets/fonts/Inter-Bold.ttf`. Put that
+font here (Inter is free at https://rsms.me/inter/), or change each text
+layer's `font` to a TTF or OTF file you have.
+";
+
+pub struct ProjectScaffold;
+
+impl ProjectScaffold {
+    /// Create the project in `dir`: `script.json` from `template` titled `name`,
+    /// `interstellar.toml` with the default settings, `assets/fonts/` with a note
+    /// on the font the script expects, and a `.gitignore`. A directory that
+    /// exists and isn't empty is only written into with `force`. Returns the
+    /// files written.
+    pub fn create(
+        dir: &Path,
+        template: TemplateType,
+        name: &str,
+        force: bool,
+    ) -> Result<Vec<PathBuf>> {
+        let not_empty = fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some());
+        if not_empty && !force {
+            anyhow::bail!(
+                "{} is not empty; pass --force to write the project into it anyway",
+                dir.display()
+            );
+        }
+        fs::create_dir_all(dir.join("assets/fonts"))
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let mut script = ScriptTemplate::generate(template, &TemplateOptions::new(SCRIPT_DURATION));
+        script.metadata.title = name.to_string();
+        let config =
+            AppConfig::commented_defaults().context("Failed to write the default configuration")?;
+
+        let files = [
+            ("script.json", serde_json::to_string_pretty(&script)? + "\n"),
+            ("interstellar.toml", config),
+            ("assets/fonts/README.md", FONTS_NOTE.to_string()),
+            (".gitignore", GITIGNORE.to_string()),
+        ];
+        let mut written = Vec::new();
+        for (file, content) in files {
+            let path = dir.join(file);
+            fs::write(&path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptParser;
+
+    #[test]
+    fn test_scaffold_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("my-video");
+        let files =
+            ProjectScaffold::create(&project, TemplateType::Tutorial, "My Video", false).unwrap();
+        assert_eq!(files.len(), 4);
+        assert!(files.iter().all(|file| file.is_file()));
+
+        let script = ScriptParser::parse_json(&project.join("script.json")).unwrap();
+        assert_eq!(script.metadata.title, "My Video");
+        assert_eq!(
+            fs::read_to_string(project.join(".gitignore")).unwrap(),
+            "output/\n.cache/\n"
+        );
+    }
+
+    #[test]
+    fn test_refuses_non_empty_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+
+        let error = ProjectScaffold::create(dir.path(), TemplateType::Explainer, "Video", false)
+            .unwrap_err();
+        assert!(error.to_string().contains("--force"));
+        assert!(!dir.path().join("script.json").exists());
+
+        ProjectScaffold::create(dir.path(), TemplateType::Explainer, "Video", true).unwrap();
+        assert!(dir.path().join("script.json").is_file());
+        assert!(dir.path().join("notes.txt").is_file());
+
+        // An empty directory is fine
+        let empty = tempfile::tempdir().unwrap();
+        ProjectScaffold::create(empty.path(), TemplateType::Explainer, "Video", false).unwrap();
+    }
+}
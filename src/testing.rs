@@ -0,0 +1,248 @@
+//! Golden-frame regression testing: render scripts and compare their frames with
+//! checked-in reference PNGs.
+//!
+//! Run the tests with `UPDATE_GOLDENS=1` to rewrite the references from the
+//! current output, e.g. after an intended rendering change.
+
+use crate::assets::AssetLoader;
+use crate::renderer::{FrameBuffer, FrameSink, ProgressEvent, RenderEngine};
+use crate::script::VideoScript;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Environment variable that turns golden checks into golden updates
+pub const UPDATE_GOLDENS_ENV: &str = "UPDATE_GOLDENS";
+
+/// Which renderer draws the frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Cpu,
+    Gpu,
+}
+
+/// Largest differences a frame may have from its golden
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest difference in any channel of any pixel
+    pub max_error: u8,
+    /// Average difference over every color channel
+    pub mean_error: f64,
+}
+
+impl Tolerance {
+    /// Bit-identical
+    pub const EXACT: Tolerance = Tolerance {
+        max_error: 0,
+        mean_error: 0.0,
+    };
+}
+
+/// Per-channel differences between two frames' colors. Alpha is ignored, like
+/// in the PNGs goldens are stored as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameDiff {
+    pub max_error: u8,
+    pub mean_error: f64,
+}
+
+impl FrameDiff {
+    pub fn within(&self, tolerance: Tolerance) -> bool {
+        self.max_error <= tolerance.max_error && self.mean_error <= tolerance.mean_error
+    }
+}
+
+struct CollectFrames(Vec<FrameBuffer>);
+
+impl FrameSink for CollectFrames {
+    fn write_frame(&mut self, frame: &FrameBuffer) -> crate::error::Result<()> {
+        self.0.push(frame.clone());
+        Ok(())
+    }
+}
+
+/// Every frame of `script`, with asset paths resolved against `asset_dir`.
+/// Fails for `Backend::Gpu` when no GPU adapter can be used.
+pub fn render_script_to_frames(
+    script: &VideoScript,
+    backend: Backend,
+    asset_dir: &Path,
+) -> Result<Vec<FrameBuffer>> {
+    let mut engine = RenderEngine::new(script.clone(), backend == Backend::Gpu);
+    if backend == Backend::Gpu && !engine.uses_gpu() {
+        anyhow::bail!("No GPU adapter available");
+    }
+
+    let mut loader = AssetLoader::new(asset_dir);
+    let mut frames = CollectFrames(Vec::new());
+    engine.render(&mut frames, &mut loader, &mut |_: ProgressEvent| {})?;
+    Ok(frames.0)
+}
+
+/// How far `actual`'s colors are from `expected`'s
+pub fn diff_frames(actual: &FrameBuffer, expected: &FrameBuffer) -> Result<FrameDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        anyhow::bail!(
+            "Frame is {:?} but the golden is {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        );
+    }
+
+    let (mut max_error, mut total, mut channels) = (0u8, 0u64, 0u64);
+    for (a, e) in actual
+        .as_bytes()
+        .chunks_exact(4)
+        .zip(expected.as_bytes().chunks_exact(4))
+    {
+        for channel in 0..3 {
+            let error = a[channel].abs_diff(e[channel]);
+            max_error = max_error.max(error);
+            total += error as u64;
+        }
+        channels += 3;
+    }
+
+    Ok(FrameDiff {
+        max_error,
+        mean_error: total as f64 / channels.max(1) as f64,
+    })
+}
+
+/// Whether `UPDATE_GOLDENS` is set, so goldens are written rather than checked
+pub fn updating_goldens() -> bool {
+    std::env::var_os(UPDATE_GOLDENS_ENV).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Compare `frame` with the reference PNG at `golden`. On a mismatch the frame is
+/// saved next to it as `<name>.actual.png` for inspection. When updating goldens,
+/// the reference is rewritten instead.
+pub fn check_golden(frame: &FrameBuffer, golden: &Path, tolerance: Tolerance) -> Result<FrameDiff> {
+    let golden_str = golden.to_str().context("Golden path is not UTF-8")?;
+    if updating_goldens() {
+        frame.save_png(golden_str)?;
+        return Ok(FrameDiff {
+            max_error: 0,
+            mean_error: 0.0,
+        });
+    }
+
+    let image = image::open(golden)
+        .with_context(|| {
+            format!(
+                "Failed to read golden {} (run with {}=1 to create it)",
+                golden.display(),
+                UPDATE_GOLDENS_ENV
+            )
+        })?
+        .to_rgba8();
+    let mut expected = FrameBuffer::new(image.width(), image.height());
+    expected.copy_from_slice(image.as_raw());
+
+    let actual_path = golden.with_extension("actual.png");
+    let diff = diff_frames(frame, &expected);
+    if diff.as_ref().is_ok_and(|diff| diff.within(tolerance)) {
+        // Clear out the dump of an earlier failure
+        let _ = std::fs::remove_file(&actual_path);
+        return diff;
+    }
+
+    frame.save_png(actual_path.to_str().context("Golden path is not UTF-8")?)?;
+    match diff {
+        Ok(diff) => anyhow::bail!(
+            "{} differs from its golden by up to {} (mean {:.3}), allowed {} (mean {:.3}); \
+             actual frame saved to {}",
+            golden.display(),
+            diff.max_error,
+            diff.mean_error,
+            tolerance.max_error,
+            tolerance.mean_error,
+            actual_path.display()
+        ),
+        Err(e) => Err(e.context(format!("actual frame saved to {}", actual_path.display()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn golden_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+    }
+
+    fn render_golden_script(name: &str, backend: Backend) -> Result<Vec<FrameBuffer>> {
+        let json = std::fs::read_to_string(golden_dir().join(format!("{}.json", name)))?;
+        let script: VideoScript = serde_json::from_str(&json)?;
+        render_script_to_frames(&script, backend, &golden_dir())
+    }
+
+    /// Check every golden against `backend`'s output, reporting all mismatches
+    fn check_goldens(backend: Backend, tolerance: Tolerance) -> Result<()> {
+        let mut frames = Vec::new();
+        for name in ["solid_color", "overlapping_rects"] {
+            let rendered = render_golden_script(name, backend)?;
+            frames.push((name, rendered[0].clone()));
+        }
+
+        // The dissolve spans all 4 frames, so the cut at frame 2 is its midpoint
+        let crossfade = render_golden_script("crossfade", backend)?;
+        frames.push(("crossfade_midpoint", crossfade[2].clone()));
+
+        let failures: Vec<String> = frames
+            .iter()
+            .filter_map(|(name, frame)| {
+                let golden = golden_dir().join(format!("{}.png", name));
+                check_golden(frame, &golden, tolerance)
+                    .err()
+                    .map(|e| format!("{:#}", e))
+            })
+            .collect();
+        if !failures.is_empty() {
+            anyhow::bail!("{}", failures.join("\n"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cpu_frames_match_goldens() {
+        check_goldens(Backend::Cpu, Tolerance::EXACT).unwrap();
+    }
+
+    #[test]
+    fn test_gpu_frames_match_goldens() {
+        // Goldens come from the CPU, which is exact everywhere
+        if updating_goldens() {
+            return;
+        }
+        if render_golden_script("solid_color", Backend::Gpu).is_err() {
+            println!("No GPU adapter, skipping GPU golden test");
+            return;
+        }
+        let tolerance = Tolerance {
+            max_error: 2,
+            mean_error: 0.5,
+        };
+        check_goldens(Backend::Gpu, tolerance).unwrap();
+    }
+
+    #[test]
+    fn test_diff_frames() {
+        let mut expected = FrameBuffer::new(2, 2);
+        expected.clear([100, 100, 100, 255]);
+        let mut actual = expected.clone();
+        // Alpha differences don't count
+        actual.set_pixel(0, 0, [103, 100, 100, 0]);
+
+        let diff = diff_frames(&actual, &expected).unwrap();
+        assert_eq!(diff.max_error, 3);
+        assert_eq!(diff.mean_error, 0.25);
+        assert!(diff.within(Tolerance {
+            max_error: 3,
+            mean_error: 0.25
+        }));
+        assert!(!diff.within(Tolerance::EXACT));
+
+        assert!(diff_frames(&FrameBuffer::new(2, 3), &expected).is_err());
+    }
+}
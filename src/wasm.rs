@@ -0,0 +1,144 @@
+//! `validate_script` and `analyze_script` for JavaScript (the `wasm` feature),
+//! so a browser editor can check scripts without a server. Build the package
+//! without the renderer:
+//!
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features wasm
+//! ```
+//!
+//! Both take the script's JSON and return plain objects: `{ valid, errors }`,
+//! where each error is `{ scene, message }`, plus `report` from
+//! `analyze_script`, laid out as `analyze --json` prints it. Assets and audio
+//! can't be read from the browser, so the report treats them as missing.
+
+use crate::analysis::analyzer::{AnalysisConfig, ExtraAnalyzers};
+use crate::analysis::report::AnalysisReport;
+use crate::error::{ValidationError, VideoEngineError};
+use crate::parser::ScriptParser;
+use crate::script::VideoScript;
+use serde::Serialize;
+use std::path::PathBuf;
+use wasm_bindgen::prelude::*;
+
+/// What `validate_script` returns
+#[derive(Debug, Clone, Serialize)]
+pub struct Validation {
+    pub valid: bool,
+    /// Every rule the script breaks, or the one error that kept it from parsing
+    pub errors: Vec<ValidationError>,
+}
+
+/// What `analyze_script` returns
+#[derive(Debug, Clone, Serialize)]
+pub struct Analysis {
+    #[serde(flatten)]
+    pub validation: Validation,
+    /// The report, when the script is valid
+    pub report: Option<AnalysisReport>,
+}
+
+/// Whether the script in `json` parses and follows every rule
+#[wasm_bindgen]
+pub fn validate_script(json: &str) -> JsValue {
+    to_js(&validate(json))
+}
+
+/// Every analysis of the script in `json`, with the default settings
+#[wasm_bindgen]
+pub fn analyze_script(json: &str) -> JsValue {
+    to_js(&analyze(json))
+}
+
+pub fn validate(json: &str) -> Validation {
+    match parse(json) {
+        Ok(_) => Validation::passed(),
+        Err(errors) => Validation::failed(errors),
+    }
+}
+
+pub fn analyze(json: &str) -> Analysis {
+    match parse(json) {
+        Ok(script) => Analysis {
+            validation: Validation::passed(),
+            report: Some(AnalysisReport::analyze(
+                &script,
+                &AnalysisConfig {
+                    base_path: PathBuf::new(),
+                    ..AnalysisConfig::default()
+                },
+                &ExtraAnalyzers::new(),
+            )),
+        },
+        Err(errors) => Analysis {
+            validation: Validation::failed(errors),
+            report: None,
+        },
+    }
+}
+
+impl Validation {
+    fn passed() -> Self {
+        Self {
+            valid: true,
+            errors: Vec::new(),
+        }
+    }
+
+    fn failed(errors: Vec<ValidationError>) -> Self {
+        Self {
+            valid: false,
+            errors,
+        }
+    }
+}
+
+/// The script in `json`, or what's wrong with it
+fn parse(json: &str) -> Result<VideoScript, Vec<ValidationError>> {
+    ScriptParser::parse_json_str(json).map_err(|error| match error {
+        VideoEngineError::Validation(errors) => errors,
+        error => vec![ValidationError::new(error.to_string())],
+    })
+}
+
+/// `value` as a JavaScript object
+fn to_js(value: &impl Serialize) -> JsValue {
+    let json = serde_json::to_string(value).expect("reports serialize to JSON");
+    js_sys::JSON::parse(&json).expect("serialized JSON parses")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = include_str!("../examples/simple.json");
+
+    #[test]
+    fn test_validate() {
+        let validation = validate(SCRIPT);
+        assert!(validation.valid);
+        assert!(validation.errors.is_empty());
+
+        let validation = validate(&SCRIPT.replace(r#""fps": 60"#, r#""fps": 0"#));
+        assert!(!validation.valid);
+        let json = serde_json::to_value(&validation).unwrap();
+        assert_eq!(json["errors"][0]["message"], "FPS must be greater than 0");
+
+        let validation = validate("{");
+        assert!(!validation.valid);
+        assert!(validation.errors[0]
+            .message
+            .starts_with("Failed to parse JSON script"));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let json = serde_json::to_value(analyze(SCRIPT)).unwrap();
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["report"]["title"], "Digital Artisan Demo Video");
+        assert!(json["report"]["score"].is_u64());
+
+        let json = serde_json::to_value(analyze("[]")).unwrap();
+        assert_eq!(json["valid"], false);
+        assert!(json["report"].is_null());
+    }
+}
@@ -0,0 +1,415 @@
+//! `render --watch`: which files a script depends on, when they changed, and
+//! what changed in the script between renders.
+
+use crate::analysis::asset_audit::AssetAuditAnalyzer;
+use crate::script::VideoScript;
+use crate::AssetLoader;
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long files must stay unchanged before a re-render, so one save that
+/// writes several times (or several files saved at once) renders once
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Collects changed paths until none changed for `delay`
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: BTreeSet::new(),
+            last_change: None,
+        }
+    }
+
+    pub fn push(&mut self, path: PathBuf, at: Instant) {
+        self.pending.insert(path);
+        self.last_change = Some(at);
+    }
+
+    /// How long from `now` until the pending changes are ready; `None` without any
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.last_change
+            .map(|last| (last + self.delay).saturating_duration_since(now))
+    }
+
+    /// The changed paths once `delay` passed since the last change, which
+    /// starts a new batch
+    pub fn ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        if !self.remaining(now)?.is_zero() {
+            return None;
+        }
+        self.last_change = None;
+        Some(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+}
+
+/// `path` made absolute with its directory's symlinks resolved, so it compares
+/// equal to the paths in file system events. The file itself needn't exist.
+fn normalize(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    match (path.parent().map(std::fs::canonicalize), path.file_name()) {
+        (Some(Ok(directory)), Some(name)) => directory.join(name),
+        _ => path,
+    }
+}
+
+/// The script file and every asset it references. Without a script (e.g. it
+/// doesn't parse mid-edit) only the script file.
+pub fn watched_files(script_path: &Path, script: Option<&VideoScript>) -> BTreeSet<PathBuf> {
+    let mut files = BTreeSet::from([normalize(script_path)]);
+    if let Some(script) = script {
+        let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+        let loader = AssetLoader::new(base_path);
+        files.extend(
+            AssetAuditAnalyzer::paths(script)
+                .into_iter()
+                .map(|(path, _, _)| normalize(&loader.resolve_path(&path))),
+        );
+    }
+    files
+}
+
+/// What changed between two versions of a script
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptSnapshot {
+    pub duration: f32,
+    /// Each scene's id and a hash of its content
+    pub scenes: Vec<(String, u64)>,
+}
+
+impl ScriptSnapshot {
+    pub fn new(script: &VideoScript) -> Self {
+        let scenes = script
+            .scenes
+            .iter()
+            .map(|scene| {
+                let mut hasher = DefaultHasher::new();
+                serde_json::to_string(scene)
+                    .unwrap_or_default()
+                    .hash(&mut hasher);
+                (scene.id.clone(), hasher.finish())
+            })
+            .collect();
+        Self {
+            duration: script.scenes.iter().map(|scene| scene.duration).sum(),
+            scenes,
+        }
+    }
+
+    /// One line per difference from `self` to `newer`: the scene count, the
+    /// duration and the scenes added, removed or changed, by id
+    pub fn diff(&self, newer: &ScriptSnapshot) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.scenes.len() != newer.scenes.len() {
+            lines.push(format!(
+                "scenes: {} → {}",
+                self.scenes.len(),
+                newer.scenes.len()
+            ));
+        }
+        if (self.duration - newer.duration).abs() > 1e-3 {
+            lines.push(format!(
+                "duration: {:.1}s → {:.1}s",
+                self.duration, newer.duration
+            ));
+        }
+
+        let has = |scenes: &[(String, u64)], id: &str| scenes.iter().any(|(other, _)| other == id);
+        let ids = |scenes: Vec<&String>| {
+            scenes
+                .into_iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let added: Vec<&String> = newer
+            .scenes
+            .iter()
+            .filter(|(id, _)| !has(&self.scenes, id))
+            .map(|(id, _)| id)
+            .collect();
+        let removed: Vec<&String> = self
+            .scenes
+            .iter()
+            .filter(|(id, _)| !has(&newer.scenes, id))
+            .map(|(id, _)| id)
+            .collect();
+        let changed: Vec<&String> = newer
+            .scenes
+            .iter()
+            .filter(|(id, hash)| {
+                self.scenes
+                    .iter()
+                    .any(|(other, other_hash)| other == id && other_hash != hash)
+            })
+            .map(|(id, _)| id)
+            .collect();
+        for (label, scenes) in [("added", added), ("removed", removed), ("changed", changed)] {
+            if !scenes.is_empty() {
+                lines.push(format!("{}: {}", label, ids(scenes)));
+            }
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// These watched files changed and then stayed unchanged for the debounce delay
+    Changed(Vec<PathBuf>),
+    /// Stop watching, e.g. on Ctrl-C
+    Stop,
+}
+
+/// Watches a set of files, through their directories so editors that save by
+/// replacing the file are noticed too, and reports debounced changes
+pub struct ScriptWatcher {
+    watcher: RecommendedWatcher,
+    files: Arc<Mutex<BTreeSet<PathBuf>>>,
+    directories: BTreeSet<PathBuf>,
+    sender: Sender<WatchEvent>,
+    events: Receiver<WatchEvent>,
+}
+
+impl ScriptWatcher {
+    /// `on_change` runs on the watcher's thread as soon as a debounced change is
+    /// ready, e.g. to cancel a render in progress, before the change is sent
+    pub fn new(delay: Duration, on_change: impl Fn() + Send + 'static) -> Result<Self> {
+        let files: Arc<Mutex<BTreeSet<PathBuf>>> = Arc::default();
+        let (changes, raw) = mpsc::channel::<PathBuf>();
+        let watched = files.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let files = watched.lock().unwrap();
+            for path in event.paths.into_iter().filter(|path| files.contains(path)) {
+                let _ = changes.send(path);
+            }
+        })
+        .context("Failed to start watching files")?;
+
+        let (sender, events) = mpsc::channel();
+        let debounced = sender.clone();
+        std::thread::spawn(move || {
+            let mut debouncer = Debouncer::new(delay);
+            loop {
+                // Ends with the watcher, which holds the other end
+                let received = match debouncer.remaining(Instant::now()) {
+                    Some(remaining) => raw.recv_timeout(remaining),
+                    None => raw.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(path) => debouncer.push(path, Instant::now()),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if let Some(paths) = debouncer.ready(Instant::now()) {
+                    on_change();
+                    if debounced.send(WatchEvent::Changed(paths)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            watcher,
+            files,
+            directories: BTreeSet::new(),
+            sender,
+            events,
+        })
+    }
+
+    /// Watch `files` instead of the files watched so far
+    pub fn watch(&mut self, files: BTreeSet<PathBuf>) -> Result<()> {
+        let directories: BTreeSet<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent())
+            .filter(|directory| directory.is_dir())
+            .map(Path::to_path_buf)
+            .collect();
+        for directory in self.directories.difference(&directories) {
+            let _ = self.watcher.unwatch(directory);
+        }
+        for directory in directories.difference(&self.directories) {
+            self.watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", directory.display()))?;
+        }
+        self.directories = directories;
+        *self.files.lock().unwrap() = files;
+        Ok(())
+    }
+
+    /// Sends events to `next_event`, e.g. `WatchEvent::Stop` from a Ctrl-C handler
+    pub fn sender(&self) -> Sender<WatchEvent> {
+        self.sender.clone()
+    }
+
+    /// Wait for the next event. Changes that queued up meanwhile are merged
+    /// into one, and a queued `Stop` wins.
+    pub fn next_event(&self) -> WatchEvent {
+        match self.events.recv() {
+            Ok(first) => self.merge_queued(first),
+            Err(_) => WatchEvent::Stop,
+        }
+    }
+
+    /// Like `next_event`, but `None` when nothing happened within `timeout`
+    pub fn next_event_timeout(&self, timeout: Duration) -> Option<WatchEvent> {
+        let first = self.events.recv_timeout(timeout).ok()?;
+        Some(self.merge_queued(first))
+    }
+
+    fn merge_queued(&self, first: WatchEvent) -> WatchEvent {
+        let mut changed = BTreeSet::new();
+        for event in std::iter::once(first).chain(self.events.try_iter()) {
+            match event {
+                WatchEvent::Changed(paths) => changed.extend(paths),
+                WatchEvent::Stop => return WatchEvent::Stop,
+            }
+        }
+        WatchEvent::Changed(changed.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{ScriptTemplate, TemplateOptions, TemplateType};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_debouncer_waits_for_quiet() {
+        let start = Instant::now();
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+        let mut debouncer = Debouncer::new(DEBOUNCE);
+        assert_eq!(debouncer.remaining(start), None);
+        assert_eq!(debouncer.ready(start), None);
+
+        debouncer.push("script.json".into(), ms(0));
+        debouncer.push("bg.png".into(), ms(300));
+        // Counted from the last change
+        assert_eq!(
+            debouncer.remaining(ms(400)),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(debouncer.ready(ms(600)), None);
+        debouncer.push("script.json".into(), ms(700));
+        assert_eq!(debouncer.ready(ms(1100)), None);
+
+        let paths = debouncer.ready(ms(1200)).unwrap();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("bg.png"), PathBuf::from("script.json")]
+        );
+        // Nothing left
+        assert_eq!(debouncer.ready(ms(2000)), None);
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        let before = ScriptSnapshot::new(&script);
+        assert!(before.diff(&ScriptSnapshot::new(&script)).is_empty());
+
+        let mut edited = script.clone();
+        edited.scenes[1].narration = Some("A sharper line".into());
+        assert_eq!(
+            before.diff(&ScriptSnapshot::new(&edited)),
+            vec![format!("changed: {}", script.scenes[1].id)]
+        );
+
+        // The first scene replaced by a longer one
+        let mut recap = edited.scenes.remove(0);
+        recap.id = "Recap".into();
+        recap.duration += 2.0;
+        edited.scenes.push(recap);
+        let diff = before.diff(&ScriptSnapshot::new(&edited));
+        assert_eq!(
+            diff,
+            vec![
+                format!(
+                    "duration: {:.1}s → {:.1}s",
+                    before.duration,
+                    before.duration + 2.0
+                ),
+                "added: Recap".to_string(),
+                format!("removed: {}", script.scenes[0].id),
+                format!("changed: {}", script.scenes[1].id),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watcher_reports_debounced_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("script.json");
+        let asset = dir.path().join("bg.png");
+        let unrelated = dir.path().join("notes.txt");
+        for path in [&script, &asset, &unrelated] {
+            std::fs::write(path, "v1").unwrap();
+        }
+
+        let changes = Arc::new(AtomicUsize::new(0));
+        let counted = changes.clone();
+        let mut watcher = ScriptWatcher::new(Duration::from_millis(100), move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        watcher
+            .watch(BTreeSet::from([normalize(&script), normalize(&asset)]))
+            .unwrap();
+
+        // Not watched
+        std::fs::write(&unrelated, "v2").unwrap();
+        assert_eq!(watcher.next_event_timeout(Duration::from_millis(400)), None);
+
+        // Several writes in a row are one change
+        for i in 0..3 {
+            std::fs::write(&script, format!("v{}", i)).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        std::fs::write(&asset, "v2").unwrap();
+        let event = watcher.next_event_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            event,
+            WatchEvent::Changed(vec![normalize(&asset), normalize(&script)])
+        );
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        watcher.sender().send(WatchEvent::Stop).unwrap();
+        assert_eq!(watcher.next_event(), WatchEvent::Stop);
+    }
+
+    #[test]
+    fn test_watched_files_include_assets() {
+        let script = ScriptTemplate::generate(TemplateType::Explainer, &TemplateOptions::new(60.0));
+        let files = watched_files(Path::new("examples/script.json"), Some(&script));
+        assert!(files.contains(&normalize(Path::new("examples/script.json"))));
+        assert!(files.len() > 1);
+        assert!(files.iter().all(|file| file.is_absolute()));
+
+        let files = watched_files(Path::new("examples/script.json"), None);
+        assert_eq!(files.len(), 1);
+    }
+}
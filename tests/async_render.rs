@@ -0,0 +1,70 @@
+use interstellar_triangulum::config::AppConfig;
+use interstellar_triangulum::image::{Rgba, RgbaImage};
+use interstellar_triangulum::renderer::engine::r#async::{render_script, AsyncRenderOptions};
+use interstellar_triangulum::renderer::ProgressKind;
+use interstellar_triangulum::script::{SceneBuilder, VideoScriptBuilder};
+use interstellar_triangulum::{VideoEngineError, VideoScript};
+use std::path::Path;
+use tokio_stream::StreamExt;
+
+/// A one-second 32x32 script at 4 fps over a solid image in `dir`
+fn test_script(dir: &Path) -> VideoScript {
+    RgbaImage::from_pixel(32, 32, Rgba([40, 80, 160, 255]))
+        .save(dir.join("bg.png"))
+        .unwrap();
+    VideoScriptBuilder::new("Async")
+        .resolution(32, 32)
+        .fps(4)
+        .scene(SceneBuilder::body(1.0).image("bg.png"))
+        .build()
+        .unwrap()
+}
+
+fn cpu_options(dir: &Path) -> AsyncRenderOptions {
+    let mut renderer = AppConfig::default().renderer;
+    renderer.output_dir = dir.join("frames");
+    AsyncRenderOptions {
+        renderer,
+        gpu: None,
+        asset_dir: dir.to_path_buf(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_render_streams_progress_and_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut render = render_script(test_script(dir.path()), cpu_options(dir.path()));
+
+    let mut completed = Vec::new();
+    while let Some(event) = render.next().await {
+        if let ProgressKind::FrameCompleted { completed: n, .. } = event.kind {
+            completed.push(n);
+        }
+    }
+    assert_eq!(completed, [1, 2, 3, 4]);
+
+    let output = render.output().await.unwrap();
+    assert_eq!(output.frames, 4);
+    let files = output.files.unwrap();
+    for frame in 0..4 {
+        let frame = dir
+            .path()
+            .join("frames")
+            .join(files.format.file_name(frame));
+        assert!(frame.is_file(), "{} is missing", frame.display());
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_render_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let render = render_script(test_script(dir.path()), cpu_options(dir.path()));
+    render.cancel();
+
+    let result = render.output().await;
+    assert!(
+        matches!(result, Err(VideoEngineError::Cancelled)),
+        "{:?}",
+        result
+    );
+}
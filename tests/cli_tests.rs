@@ -8,7 +8,58 @@ fn test_cli_help() {
     cmd.arg("--help")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Digital Artisan Video Engine"));
+        .stdout(predicate::str::contains("Digital Artisan Video Engine"))
+        .stdout(predicate::str::contains("Exit codes:"));
+}
+
+#[test]
+fn test_cli_exit_codes() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    // An invalid script
+    let invalid = dir.path().join("invalid.json");
+    fs::write(&invalid, r#"{"metadata": {"title": "Broken"}}"#).unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate").arg(&invalid).assert().code(2);
+
+    // A score below the threshold
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("score")
+        .arg("examples/simple.json")
+        .arg("--min")
+        .arg("101")
+        .assert()
+        .code(3);
+
+    // FLAC export without ffmpeg on the PATH
+    let script = dir.path().join("podcast.json");
+    fs::write(
+        &script,
+        r#"{
+            "metadata": {"title": "Podcast", "resolution": "64x64", "fps": 4, "duration": 1.0},
+            "scenes": [{"id": "only", "duration": 1.0, "layers": [
+                {"type": "text", "content": "Hi", "font": "missing.ttf", "font_size": 12,
+                 "color": {"r": 255, "g": 255, "b": 255}, "position": {"x": 0, "y": 0}}
+            ]}],
+            "audio": {"tracks": [{"source": "missing.wav", "track_type": "voiceover"}]}
+        }"#,
+    )
+    .unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.env("PATH", dir.path())
+        .arg("audio")
+        .arg(&script)
+        .arg("--format")
+        .arg("flac")
+        .arg("--out")
+        .arg(dir.path().join("podcast.flac"))
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("FFmpeg not found"));
+
+    // Invalid arguments
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("score").arg("--min").arg("high").assert().code(1);
 }
 
 #[test]
@@ -26,6 +77,412 @@ fn test_cli_template_generation() {
         .stdout(predicate::str::contains("\"duration\": 30.0"));
 }
 
+#[test]
+fn test_cli_template_out_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let out = dir.path().join("script.json");
+
+    let template = |force: bool| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.arg("template")
+            .arg("explainer")
+            .arg("--scenes")
+            .arg("5")
+            .arg("--with-audio")
+            .arg("--out")
+            .arg(&out);
+        if force {
+            cmd.arg("--force");
+        }
+        cmd.assert()
+    };
+    template(false).success();
+    let script: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(script["scenes"].as_array().unwrap().len(), 5);
+    assert_eq!(script["audio"]["tracks"].as_array().unwrap().len(), 2);
+
+    template(false)
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    template(true).success();
+
+    // A script of two scenes can't have an opening, a body and a closing
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("template")
+        .arg("tutorial")
+        .arg("--scenes")
+        .arg("2")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_completions() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("completions")
+        .arg("zsh")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("product-ad"));
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let out = dir.path().join("interstellar-triangulum.fish");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("completions")
+        .arg("fish")
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&out).unwrap().contains("render"));
+}
+
+#[test]
+fn test_cli_config_show_labels_env_overrides() {
+    let dir = tempfile::TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("interstellar.toml"),
+        "[renderer]\nengine = \"native\"\noutput_dir = \"renders\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.current_dir(dir.path())
+        .env("INTERSTELLAR_RENDERER__ENGINE", "blender")
+        .arg("config")
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "renderer.engine = \"blender\"  # env INTERSTELLAR_RENDERER__ENGINE",
+        ))
+        .stdout(predicate::str::contains(
+            "renderer.output_dir = \"renders\"  # file",
+        ))
+        .stdout(predicate::str::contains(
+            "video.default_fps = 30  # default",
+        ));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .current_dir(dir.path())
+        .env("INTERSTELLAR_RENDERER__ENGINE", "blender")
+        .arg("--json")
+        .arg("config")
+        .arg("show")
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let engine = document["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|value| value["key"] == "renderer.engine")
+        .unwrap();
+    assert_eq!(engine["source"], "env");
+}
+
+#[test]
+fn test_cli_config_init_and_path() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.current_dir(dir.path()).arg("config");
+        cmd
+    };
+
+    config()
+        .arg("path")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No interstellar.toml found"));
+    config().arg("init").assert().success();
+    config()
+        .arg("init")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    config().arg("init").arg("--force").assert().success();
+    config()
+        .arg("path")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("interstellar.toml"));
+    assert!(fs::read_to_string(dir.path().join("interstellar.toml"))
+        .unwrap()
+        .starts_with("# "));
+}
+
+#[test]
+fn test_cli_score_exit_code_follows_the_minimum() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("score")
+        .arg("examples/simple.json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let score: u32 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("score")
+        .arg("examples/simple.json")
+        .arg("--min")
+        .arg(score.to_string())
+        .assert()
+        .success();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("score")
+        .arg("examples/simple.json")
+        .arg("--min")
+        .arg((score + 1).to_string())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("below threshold"));
+
+    // The minimum from the configuration, and the breakdown as JSON
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .env("INTERSTELLAR_SCORE__MIN", (score + 1).to_string())
+        .arg("--json")
+        .arg("score")
+        .arg("examples/simple.json")
+        .arg("--breakdown")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["score"], score);
+    assert_eq!(document["passed"], false);
+    assert!(document["breakdown"]["narrative"].is_u64());
+}
+
+#[test]
+fn test_cli_diff_reports_changes_and_scores() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let old = dir.path().join("old.json");
+    let new = dir.path().join("new.json");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("template")
+        .arg("explainer")
+        .arg("--out")
+        .arg(&old)
+        .assert()
+        .success();
+    let mut script: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&old).unwrap()).unwrap();
+    script["scenes"][1]["id"] = "Concept".into();
+    script["scenes"][2]["narration"] = "Subscribe for more videos like this one.".into();
+    fs::write(&new, serde_json::to_string(&script).unwrap()).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("diff")
+        .arg(&old)
+        .arg(&new)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'Concept' (renamed from 'Body')"))
+        .stdout(predicate::str::contains("narration: "))
+        .stdout(predicate::str::contains("narrative: "));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("--json")
+        .arg("diff")
+        .arg(&old)
+        .arg(&old)
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["diff"]["scenes"], serde_json::json!([]));
+    assert_eq!(document["scores"]["narrative"]["delta"], 0);
+}
+
+#[test]
+fn test_cli_init_scaffold_validates() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let project = dir.path().join("my-video");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("init")
+        .arg("--template")
+        .arg("tutorial")
+        .arg("--name")
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("script.json"));
+    assert!(project.join("interstellar.toml").is_file());
+    assert!(project.join("assets/fonts/README.md").is_file());
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.current_dir(&project)
+        .arg("validate")
+        .arg("script.json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Validation complete"));
+
+    // A second init doesn't overwrite the project
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("init")
+        .arg("--name")
+        .arg(&project)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_cli_render_dry_run() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = dir.path().join("out");
+
+    for engine in ["native", "blender"] {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.arg("render")
+            .arg("examples/simple.json")
+            .arg("--dry-run")
+            .arg("--renderer")
+            .arg(engine)
+            .arg("--output")
+            .arg(&output)
+            .arg("--export-chapters")
+            .arg(dir.path().join("chapters.txt"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!("Renderer: {}", engine)))
+            // 10 seconds at 60fps
+            .stdout(predicate::str::contains("Frames: 600 at 1920x1080"));
+    }
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_cli_render_exports_the_report_before_failing_on_the_score() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let report = dir.path().join("report.json");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("render")
+        .arg("examples/simple.json")
+        .arg("--export-report")
+        .arg(&report)
+        .arg("--fail-on-low-score")
+        .arg("101")
+        .arg("--output")
+        .arg(dir.path())
+        .assert()
+        .code(3);
+
+    let document: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert!(document["narrative"].is_object());
+}
+
+#[test]
+fn test_cli_render_fails_on_the_same_score_as_score() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("score")
+        .arg("examples/simple.json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let score: u32 = String::from_utf8(output).unwrap().trim().parse().unwrap();
+
+    let render = |threshold: u32| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.arg("render")
+            .arg("examples/simple.json")
+            .arg("--dry-run")
+            .arg("--fail-on-low-score")
+            .arg(threshold.to_string())
+            .arg("--output")
+            .arg(dir.path())
+            .assert()
+    };
+    render(score).success();
+    render(score + 1)
+        .code(3)
+        .stderr(predicate::str::contains(format!(
+            "Score {} is below threshold {}",
+            score,
+            score + 1
+        )));
+}
+
+#[test]
+fn test_cli_validate_json() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("--json")
+        .arg("validate")
+        .arg("examples/simple.json")
+        .assert()
+        .success()
+        // Messages go to stderr
+        .stderr(predicate::str::contains("Validation complete"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["script"], "examples/simple.json");
+    assert_eq!(document["passed"], true);
+    assert!(document["has_warnings"].is_boolean());
+    assert!(document["report"]["score"].is_number());
+    assert!(document["report"]["narrative"].is_object());
+    assert!(document["metadata"].is_object());
+    assert!(document["assets"]["references"].is_array());
+}
+
+#[test]
+fn test_cli_template_json() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("template")
+        .arg("tutorial")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let script: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(script["metadata"]["title"].is_string());
+    assert!(!script["scenes"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_quiet() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("--quiet")
+        .arg("validate")
+        .arg("examples/simple.json")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    // Errors still show, with the same exit code
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("--quiet")
+        .arg("validate")
+        .arg("missing.json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing.json"));
+}
+
 #[test]
 fn test_cli_validate_simple() {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
@@ -36,6 +493,184 @@ fn test_cli_validate_simple() {
         .stdout(predicate::str::contains("Validation complete"));
 }
 
+#[test]
+fn test_cli_validate_rejects_unknown_layer_types() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut script: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("examples/simple.json").unwrap()).unwrap();
+    script["scenes"][0]["layers"][0]["type"] = "txt".into();
+    let script_path = dir.path().join("typo.json");
+    fs::write(&script_path, script.to_string()).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script_path)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Unknown layer type 'txt' in scene",
+        ))
+        .stderr(predicate::str::contains(
+            "(built in: video, image, text, waveform)",
+        ));
+}
+
+#[test]
+fn test_cli_assets() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("assets")
+        .arg("examples/simple.json")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(!report["references"].as_array().unwrap().is_empty());
+    assert_eq!(report["wasted_bytes"], 0);
+}
+
+#[test]
+fn test_cli_assets_verify_missing() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut script: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("examples/simple.json").unwrap()).unwrap();
+    script["scenes"][0]["layers"][0]["source"] = "missing.png".into();
+    let script_path = dir.path().join("script.json");
+    fs::write(&script_path, script.to_string()).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("assets")
+        .arg(&script_path)
+        .arg("verify")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            dir.path().join("missing.png").display().to_string(),
+        ));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("assets")
+        .arg("examples/simple.json")
+        .arg("verify")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cli_assets_copy() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let dest = dir.path().join("bundle");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("assets")
+        .arg("examples/simple.json")
+        .arg("copy")
+        .arg("--dest")
+        .arg(&dest)
+        .assert()
+        .success();
+
+    let script: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dest.join("simple.json")).unwrap()).unwrap();
+    assert_eq!(
+        script["scenes"][0]["layers"][0]["source"],
+        "assets/images/background.png"
+    );
+    assert_eq!(
+        script["audio"]["tracks"][0]["source"],
+        "assets/audio/music.mp3"
+    );
+
+    // Every path in the bundle resolves next to its script
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("assets")
+        .arg(dest.join("simple.json"))
+        .arg("verify")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cli_analyze() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("analyze")
+        .arg("examples/simple.json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let score = report["score"].as_u64().unwrap();
+    assert!(report["narrative"]["score"].is_u64());
+    assert!(report["issues"].is_array());
+    assert_eq!(report["analyzers"][0], "narrative");
+
+    // Thresholds decide the exit code
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("analyze")
+        .arg("examples/simple.json")
+        .arg("--fail-below")
+        .arg((score + 1).to_string())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("below threshold"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("analyze")
+        .arg("examples/simple.json")
+        .arg("--format")
+        .arg("text")
+        .arg("--fail-below")
+        .arg(score.to_string())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Analysis report: "))
+        .stdout(predicate::str::contains(
+            "Analyzers: narrative, credibility, retention, audio, accessibility, metadata\n",
+        ));
+}
+
+#[test]
+fn test_cli_validate_report_formats() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let sarif = dir.path().join("findings.sarif");
+    let junit = dir.path().join("findings.xml");
+
+    for (format, path) in [("sarif", &sarif), ("junit", &junit)] {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.arg("validate")
+            .arg("examples/simple.json")
+            .arg("--report-format")
+            .arg(format)
+            .arg("--report-out")
+            .arg(path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Validation complete"));
+    }
+
+    let log: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "examples/simple.json"
+    );
+
+    let xml = fs::read_to_string(&junit).unwrap();
+    assert!(xml.contains(&format!(
+        "<testsuites name=\"Digital Artisan Demo Video\" tests=\"{}\"",
+        results.len()
+    )));
+}
+
 #[test]
 fn test_cli_render_simple() {
     // Ensure output directory is clean
@@ -53,10 +688,365 @@ fn test_cli_render_simple() {
         .success()
         .stdout(predicate::str::contains("Video created successfully"));
 
-    // Check if output directory was created and contains files
+    // Frames are streamed into ffmpeg, so no intermediates are written
     assert!(fs::metadata("tests/output_test").is_ok());
-    assert!(fs::metadata("tests/output_test/frame_0.ppm").is_ok());
+    assert!(fs::metadata("tests/output_test/frame_000000.png").is_err());
+    // The video is named after the script's title, inside the output directory
+    assert!(fs::metadata("tests/output_test/test-video.mp4").is_ok());
+    assert!(fs::metadata("output.mp4").is_err());
 
     // Clean up
     let _ = fs::remove_dir_all("tests/output_test");
 }
+
+#[test]
+fn test_cli_render_without_ffmpeg_writes_frames() {
+    let _ = fs::remove_dir_all("tests/output_no_ffmpeg");
+
+    // An empty PATH hides ffmpeg, forcing the file-based fallback
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.env("PATH", "")
+        .arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_no_ffmpeg")
+        .arg("--renderer")
+        .arg("native")
+        .arg("--force-cpu")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("FFmpeg not found"));
+
+    assert!(fs::metadata("tests/output_no_ffmpeg/frame_000000.png").is_ok());
+
+    let _ = fs::remove_dir_all("tests/output_no_ffmpeg");
+}
+
+#[test]
+fn test_cli_render_frame_range() {
+    let _ = fs::remove_dir_all("tests/output_range");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_range")
+        .arg("--force-cpu")
+        .arg("--frames")
+        .arg("10..13")
+        .assert()
+        .success();
+
+    let mut files: Vec<String> = fs::read_dir("tests/output_range")
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        vec!["frame_000010.png", "frame_000011.png", "frame_000012.png"]
+    );
+
+    let _ = fs::remove_dir_all("tests/output_range");
+}
+
+#[test]
+fn test_cli_render_at_time() {
+    let _ = fs::remove_dir_all("tests/output_at");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_at")
+        .arg("--force-cpu")
+        .arg("--at")
+        .arg("0.5")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("frame_000015.png"));
+
+    assert_eq!(fs::read_dir("tests/output_at").unwrap().count(), 1);
+
+    let _ = fs::remove_dir_all("tests/output_at");
+}
+
+#[test]
+fn test_cli_thumbnail() {
+    let _ = fs::remove_dir_all("tests/output_thumbnail");
+
+    let thumbnail = |at: &str, dir: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+        cmd.arg("thumbnail")
+            .arg("tests/golden/crossfade.json")
+            .arg("--output")
+            .arg(dir)
+            .arg("--force-cpu")
+            .arg("--at")
+            .arg(at)
+            .arg("--size")
+            .arg("32x16")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("crossfade_thumbnail.png"));
+    };
+    thumbnail("0.75", "tests/output_thumbnail/mid");
+    thumbnail("0", "tests/output_thumbnail/start");
+
+    for (name, size) in [
+        ("crossfade_thumbnail.png", (64, 64)),
+        ("crossfade_thumbnail.jpg", (64, 64)),
+        ("crossfade_thumbnail_32x16.png", (32, 16)),
+        ("crossfade_thumbnail_32x16.jpg", (32, 16)),
+    ] {
+        let path = format!("tests/output_thumbnail/mid/{}", name);
+        assert_eq!(image::image_dimensions(&path).unwrap(), size, "{}", name);
+    }
+
+    // The blue second scene, not the red first frame
+    let mid = image::open("tests/output_thumbnail/mid/crossfade_thumbnail.png").unwrap();
+    let start = image::open("tests/output_thumbnail/start/crossfade_thumbnail.png").unwrap();
+    assert_ne!(mid.to_rgba8().as_raw(), start.to_rgba8().as_raw());
+
+    let _ = fs::remove_dir_all("tests/output_thumbnail");
+}
+
+#[test]
+fn test_cli_preview() {
+    let _ = fs::remove_dir_all("tests/output_preview");
+
+    // Two 64x64 scenes stacked in one column of 32 pixel wide thumbnails,
+    // with room for labels under each
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("preview")
+        .arg("tests/golden/crossfade.json")
+        .arg("--output")
+        .arg("tests/output_preview")
+        .arg("--force-cpu")
+        .arg("--columns")
+        .arg("1")
+        .arg("--thumb-width")
+        .arg("32")
+        // The blue scene starts halfway through the dissolve, where both are
+        // mixed evenly, so show the last frames instead
+        .arg("--frame-per-scene")
+        .arg("end")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Preview of 2 scenes"))
+        // Without text layers there is no font for the labels
+        .stdout(predicate::str::contains("--label-font"));
+
+    let (padding, label) = (8, 20);
+    assert_eq!(
+        image::image_dimensions("tests/output_preview/preview.png").unwrap(),
+        (32 + 2 * padding, 2 * (32 + label) + 3 * padding)
+    );
+
+    // The red first scene above the blue second one
+    let sheet = image::open("tests/output_preview/preview.png")
+        .unwrap()
+        .to_rgba8();
+    let [r, _, b, _] = sheet.get_pixel(padding + 16, padding + 16).0;
+    assert!(r > b);
+    let second = padding + 32 + label + padding;
+    let [r, _, b, _] = sheet.get_pixel(padding + 16, second + 16).0;
+    assert!(b > r);
+
+    let _ = fs::remove_dir_all("tests/output_preview");
+}
+
+#[test]
+fn test_cli_audio_export() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    // Two seconds of a 440 Hz tone at 22.05 kHz, so the mix resamples it
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 22050,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(dir.path().join("tone.wav"), spec).unwrap();
+    for i in 0..44100 {
+        let t = i as f32 / 22050.0;
+        let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.5;
+        writer
+            .write_sample((sample * i16::MAX as f32) as i16)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let script = r#"{
+        "metadata": {"title": "Podcast", "resolution": "64x64", "fps": 4, "duration": 1.5},
+        "scenes": [{"id": "only", "duration": 1.5, "layers": [
+            {"type": "waveform", "audio_source": "tone.wav", "color": {"r": 255, "g": 255, "b": 255}}
+        ]}],
+        "audio": {"tracks": [{"source": "tone.wav", "track_type": "voiceover"}]}
+    }"#;
+    fs::write(dir.path().join("podcast.json"), script).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("audio")
+        .arg(dir.path().join("podcast.json"))
+        .arg("--output")
+        .arg(dir.path().join("out"))
+        .arg("--format")
+        .arg("wav")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Levels:"));
+
+    // No frames, just the mix, cut to the length of the video
+    let out = dir.path().join("out");
+    assert_eq!(fs::read_dir(&out).unwrap().count(), 1);
+    let reader = hound::WavReader::open(out.join("podcast.wav")).unwrap();
+    assert_eq!(reader.spec().sample_rate, 44100);
+    assert_eq!(reader.duration(), 66150);
+}
+
+#[test]
+fn test_cli_render_frame_range_out_of_bounds() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_range_oob")
+        .arg("--force-cpu")
+        .arg("--frames")
+        .arg("0..100000")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("outside the timeline"));
+
+    let _ = fs::remove_dir_all("tests/output_range_oob");
+}
+
+#[test]
+fn test_cli_render_preview() {
+    let _ = fs::remove_dir_all("tests/output_preview");
+
+    // Without ffmpeg the preview frames stay on disk where they can be inspected
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.env("PATH", "")
+        .arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_preview")
+        .arg("--force-cpu")
+        .arg("--preview")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Preview mode: 320x180 at 10fps"));
+
+    // 1 second at 30fps / 3
+    let frames = fs::read_dir("tests/output_preview").unwrap().count();
+    assert_eq!(frames, 10);
+    let frame = image::open("tests/output_preview/frame_000009.png").unwrap();
+    assert_eq!((frame.width(), frame.height()), (320, 180));
+
+    let _ = fs::remove_dir_all("tests/output_preview");
+}
+
+#[test]
+fn test_cli_verbose_render_logs_frames_on_stderr() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.env("PATH", "")
+        .env_remove("RUST_LOG")
+        .arg("-v")
+        .arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg(dir.path())
+        .arg("--force-cpu")
+        .arg("--fps")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Rendering frames")
+                .and(predicate::str::contains("frame drawn").not()),
+        )
+        .stderr(
+            predicate::str::contains("frame drawn in")
+                .and(predicate::str::contains("render{start=0 end=2}")),
+        );
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("--quiet").arg("-v").arg("clean").assert().failure();
+}
+
+#[test]
+fn test_cli_render_resolution_and_fps_overrides() {
+    let _ = fs::remove_dir_all("tests/output_overrides");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.env("PATH", "")
+        .arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_overrides")
+        .arg("--force-cpu")
+        .arg("--resolution")
+        .arg("160x90")
+        .arg("--fps")
+        .arg("12")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--scale-layout"));
+
+    // 1 second at 12fps
+    let frames = fs::read_dir("tests/output_overrides").unwrap().count();
+    assert_eq!(frames, 12);
+    let frame = image::open("tests/output_overrides/frame_000011.png").unwrap();
+    assert_eq!((frame.width(), frame.height()), (160, 90));
+
+    let _ = fs::remove_dir_all("tests/output_overrides");
+}
+
+#[test]
+fn test_cli_render_json_progress() {
+    let _ = fs::remove_dir_all("tests/output_progress");
+
+    // Progress lines aren't messages, so --quiet keeps them
+    for flags in [&[][..], &["--quiet"]] {
+        check_json_progress(flags);
+    }
+
+    let _ = fs::remove_dir_all("tests/output_progress");
+}
+
+/// Renders the test config with `--json-progress` and `flags`, checking the
+/// progress lines on stdout
+fn check_json_progress(flags: &[&str]) {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .env("PATH", "")
+        .args(flags)
+        .arg("render")
+        .arg("tests/test_config.json")
+        .arg("--output")
+        .arg("tests/output_progress")
+        .arg("--force-cpu")
+        .arg("--preview")
+        .arg("--json-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let frames: Vec<u64> = events
+        .iter()
+        .filter(|event| event["event"] == "frame_completed")
+        .map(|event| event["frame"].as_u64().unwrap())
+        .collect();
+    assert_eq!(frames, (0..10).collect::<Vec<_>>());
+    assert!(events.iter().all(|event| event["timestamp"].is_f64()));
+    assert_eq!(events.last().unwrap()["event"], "render_finished");
+}
@@ -0,0 +1,243 @@
+use interstellar_triangulum::renderer::{
+    encoder, EncodeSettings, FrameBuffer, FrameSink, OutputFormat, ProgressEvent, SubtitleStyle,
+    VideoCodec, VideoEncoder,
+};
+use std::process::Command;
+
+#[test]
+fn test_streaming_encode_produces_playable_mp4() {
+    if !VideoEncoder::is_available() {
+        println!("FFmpeg not found, skipping streaming encode test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = dir.path().join("stream.mp4");
+
+    let mut encoder = VideoEncoder::start_stream(
+        &output,
+        30,
+        64,
+        48,
+        None,
+        None,
+        None,
+        &EncodeSettings::default(),
+    )
+    .unwrap();
+    let mut frame = FrameBuffer::new(64, 48);
+    for i in 0..30u8 {
+        frame.clear([i * 8, 100, 200, 255]);
+        encoder.write_frame(&frame).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    assert!(std::fs::metadata(&output).unwrap().len() > 0);
+
+    // Decode it back when ffprobe is around
+    let probe = Command::new("ffprobe")
+        .args(["-v", "error", "-count_frames", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=nb_read_frames,width,height"])
+        .args(["-of", "csv=p=0"])
+        .arg(&output)
+        .output();
+    if let Ok(probe) = probe {
+        assert!(probe.status.success());
+        let stdout = String::from_utf8_lossy(&probe.stdout);
+        assert_eq!(stdout.trim(), "64,48,30");
+    }
+}
+
+#[test]
+fn test_h265_encode_is_hevc() {
+    let has_libx265 = Command::new("ffmpeg")
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("libx265"));
+    if !has_libx265 || Command::new("ffprobe").arg("-version").output().is_err() {
+        println!("FFmpeg with libx265 or ffprobe not found, skipping H.265 encode test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = dir.path().join("stream.mp4");
+    let settings = EncodeSettings {
+        codec: VideoCodec::H265,
+        crf: Some(28),
+        preset: Some("ultrafast".into()),
+        ..Default::default()
+    };
+
+    let mut encoder =
+        VideoEncoder::start_stream(&output, 30, 64, 48, None, None, None, &settings).unwrap();
+    let mut frame = FrameBuffer::new(64, 48);
+    for i in 0..10u8 {
+        frame.clear([i * 20, 100, 200, 255]);
+        encoder.write_frame(&frame).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let probe = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=codec_name,codec_tag_string"])
+        .args(["-of", "csv=p=0"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(probe.status.success());
+    assert_eq!(String::from_utf8_lossy(&probe.stdout).trim(), "hevc,hvc1");
+}
+
+/// Stream a few frames with `settings` and return ffprobe's container format
+/// name and video codec, or `None` without ffmpeg
+fn encode_and_probe(settings: &EncodeSettings) -> Option<String> {
+    if !VideoEncoder::is_available() || Command::new("ffprobe").arg("-version").output().is_err() {
+        return None;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = dir
+        .path()
+        .join(format!("stream.{}", settings.extension().unwrap()));
+    let mut encoder =
+        VideoEncoder::start_stream(&output, 30, 64, 48, None, None, None, settings).unwrap();
+    let mut frame = FrameBuffer::new(64, 48);
+    for i in 0..10u8 {
+        frame.clear([i * 20, 100, 200, 128]);
+        encoder.write_frame(&frame).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let probe = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "format=format_name:stream=codec_name"])
+        .args(["-of", "default=nw=1:nk=1"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(probe.status.success());
+    Some(String::from_utf8_lossy(&probe.stdout).to_string())
+}
+
+#[test]
+fn test_gif_and_webm_containers() {
+    let gif = EncodeSettings {
+        format: OutputFormat::Gif {
+            max_colors: 64,
+            fps_cap: 10,
+        },
+        ..Default::default()
+    };
+    let Some(probe) = encode_and_probe(&gif) else {
+        println!("FFmpeg or ffprobe not found, skipping GIF and WebM test");
+        return;
+    };
+    assert_eq!(probe.lines().collect::<Vec<_>>(), ["gif", "gif"]);
+
+    let webm = EncodeSettings {
+        format: OutputFormat::Webm { alpha: true },
+        ..Default::default()
+    };
+    let probe = encode_and_probe(&webm).unwrap();
+    assert!(probe.contains("vp9"), "{}", probe);
+    assert!(probe.contains("webm"), "{}", probe);
+}
+
+#[test]
+fn test_encode_error_quotes_ffmpeg() {
+    if !VideoEncoder::is_available() {
+        println!("FFmpeg not found, skipping encode error test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let pattern = dir.path().join("missing_%06d.png");
+    let error = VideoEncoder::encode(
+        pattern.to_str().unwrap(),
+        &dir.path().join("out.mp4"),
+        30,
+        64,
+        48,
+        None,
+        None,
+        None,
+        &EncodeSettings::default(),
+        &mut |_: ProgressEvent| {},
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("missing_%06d.png"), "{}", error);
+}
+
+#[test]
+fn test_subtitles_filter_escaping() {
+    let style = SubtitleStyle::default();
+    assert_eq!(
+        encoder::subtitles_filter("/tmp/subs.srt", &style, false),
+        "subtitles=filename=/tmp/subs.srt"
+    );
+    // Quotes, colons and graph separators in the path are escaped for both
+    // the option and the filter graph
+    assert_eq!(
+        encoder::subtitles_filter("/tmp/it's: a [test], ok.srt", &style, false),
+        r"subtitles=filename=/tmp/it\\\'s\\: a \[test\]\, ok.srt"
+    );
+    // Windows paths use slashes, leaving only the drive's colon
+    assert_eq!(
+        encoder::subtitles_filter(r"C:\Videos\subs.srt", &style, true),
+        r"subtitles=filename=C\\:/Videos/subs.srt"
+    );
+
+    let style = SubtitleStyle {
+        font_size: Some(28),
+        margin: Some(40),
+    };
+    assert_eq!(
+        encoder::subtitles_filter("subs.srt", &style, false),
+        r"subtitles=filename=subs.srt:force_style=FontSize=28\,MarginV=40"
+    );
+}
+
+#[test]
+fn test_burned_subtitles_encode() {
+    if !VideoEncoder::is_available() || VideoEncoder::check_subtitle_burning().is_err() {
+        println!("FFmpeg with libass not found, skipping burned subtitles test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let subtitles = dir.path().join("it's [subs].srt");
+    std::fs::write(
+        &subtitles,
+        "1\n00:00:00,000 --> 00:00:01,000\nHello, world\n",
+    )
+    .unwrap();
+    let output = dir.path().join("burned.mp4");
+    let settings = EncodeSettings {
+        burn_subtitles: true,
+        subtitle_style: SubtitleStyle {
+            font_size: Some(40),
+            margin: Some(10),
+        },
+        ..Default::default()
+    };
+
+    let mut encoder = VideoEncoder::start_stream(
+        &output,
+        30,
+        160,
+        120,
+        None,
+        None,
+        Some(&subtitles),
+        &settings,
+    )
+    .unwrap();
+    let mut frame = FrameBuffer::new(160, 120);
+    frame.clear([0, 0, 0, 255]);
+    for _ in 0..15 {
+        encoder.write_frame(&frame).unwrap();
+    }
+    encoder.finish().unwrap();
+    assert!(std::fs::metadata(&output).unwrap().len() > 0);
+}
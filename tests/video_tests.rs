@@ -0,0 +1,95 @@
+use interstellar_triangulum::renderer::{
+    EncodeSettings, FrameBuffer, FrameSink, RenderEngine, VideoEncoder, VideoFrameSource,
+};
+use interstellar_triangulum::script::VideoScript;
+use interstellar_triangulum::AssetLoader;
+use std::path::Path;
+use std::process::Command;
+
+fn tools_available() -> bool {
+    VideoEncoder::is_available() && Command::new("ffprobe").arg("-version").output().is_ok()
+}
+
+/// 1 second at 10fps; frame `i` is solid gray at level `i * 25`
+fn write_test_clip(path: &Path) {
+    let mut encoder = VideoEncoder::start_stream(
+        path,
+        10,
+        32,
+        24,
+        None,
+        None,
+        None,
+        &EncodeSettings::default(),
+    )
+    .unwrap();
+    let mut frame = FrameBuffer::new(32, 24);
+    for i in 0..10u8 {
+        let level = i * 25;
+        frame.clear([level, level, level, 255]);
+        encoder.write_frame(&frame).unwrap();
+    }
+    encoder.finish().unwrap();
+}
+
+fn assert_level(pixel: [u8; 4], expected: u8) {
+    assert!(
+        (pixel[0] as i32 - expected as i32).abs() <= 8,
+        "expected gray {}, got {:?}",
+        expected,
+        pixel
+    );
+}
+
+#[test]
+fn test_video_frame_source_nearest_frame_and_seeking() {
+    if !tools_available() {
+        println!("FFmpeg not found, skipping video decoding test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let clip = dir.path().join("clip.mp4");
+    write_test_clip(&clip);
+
+    let mut source = VideoFrameSource::open(&clip).unwrap();
+    assert_eq!(source.dimensions(), (32, 24));
+    assert_eq!(source.fps(), 10.0);
+
+    assert_level(source.frame_at(0.0).unwrap().get_pixel(4, 4).0, 0);
+    // 0.34s is nearest to frame 3
+    assert_level(source.frame_at(0.34).unwrap().get_pixel(4, 4).0, 75);
+    // Going backwards restarts the decoder
+    assert_level(source.frame_at(0.1).unwrap().get_pixel(4, 4).0, 25);
+    // Past the end the last frame is held
+    assert_level(source.frame_at(5.0).unwrap().get_pixel(4, 4).0, 225);
+}
+
+#[test]
+fn test_engine_composites_video_layer() {
+    if !tools_available() {
+        println!("FFmpeg not found, skipping video layer test");
+        return;
+    }
+
+    let dir = tempfile::TempDir::new().unwrap();
+    write_test_clip(&dir.path().join("clip.mp4"));
+
+    let script: VideoScript = serde_json::from_value(serde_json::json!({
+        "metadata": {"title": "Video", "resolution": "64x48", "fps": 30, "duration": 1.0},
+        "scenes": [{"id": "s1", "duration": 1.0, "layers": [
+            {"type": "video", "source": "clip.mp4", "transform": {"position": {"x": 16, "y": 12}}}
+        ]}]
+    }))
+    .unwrap();
+
+    let mut loader = AssetLoader::new(dir.path());
+    let mut engine = RenderEngine::new(script, false);
+
+    // Output frame 15 is 0.5s in: source frame 5 of the 10fps clip
+    engine.render_frame(15, &mut loader).unwrap();
+    let frame = engine.frame_buffer();
+    assert_level(frame.get_pixel(30, 20).unwrap(), 125);
+    // Outside the 32x24 clip stays black
+    assert_eq!(frame.get_pixel(4, 4).unwrap(), [0, 0, 0, 255]);
+}
@@ -0,0 +1,42 @@
+// The WebAssembly package in Node. Build it and run this from the repository root:
+//
+//   wasm-pack build --target nodejs --out-dir pkg -- --no-default-features --features wasm
+//   node --test tests/wasm/
+
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+import { createRequire } from "node:module";
+import { test } from "node:test";
+
+const require = createRequire(import.meta.url);
+const { validate_script, analyze_script } = require("../../pkg/interstellar_triangulum.js");
+
+const script = readFileSync(new URL("../../examples/simple.json", import.meta.url), "utf8");
+
+test("a good script is valid", () => {
+  assert.deepEqual(validate_script(script), { valid: true, errors: [] });
+});
+
+test("a bad script lists every broken rule", () => {
+  const broken = JSON.parse(script);
+  broken.metadata.title = "";
+  broken.metadata.fps = 0;
+  const result = validate_script(JSON.stringify(broken));
+  assert.equal(result.valid, false);
+  const messages = result.errors.map((error) => error.message);
+  assert.ok(messages.includes("Script title cannot be empty"), messages);
+  assert.ok(messages.includes("FPS must be greater than 0"), messages);
+});
+
+test("JSON that doesn't parse is reported", () => {
+  const result = validate_script("{");
+  assert.equal(result.valid, false);
+  assert.match(result.errors[0].message, /^Failed to parse JSON script/);
+});
+
+test("a good script is analyzed", () => {
+  const result = analyze_script(script);
+  assert.equal(result.valid, true);
+  assert.equal(result.report.title, "Digital Artisan Demo Video");
+  assert.ok(result.report.score >= 0 && result.report.score <= 100);
+});